@@ -0,0 +1,294 @@
+//! `#[derive(ToCadence, FromCadence)]`, see `cadence_rs::json_cadence::convert`
+
+use proc_macro::{
+    Delimiter, Spacing, TokenStream, TokenTree,
+};
+
+/// paths of the generated code
+const CONVERT: &str = "::cadence_rs::json_cadence::convert";
+const JSON_CADENCE: &str = "::cadence_rs::json_cadence::json_cadence";
+const COMPOSITE_KIND: &str = "::cadence_rs::ast::CompositeKind";
+const NUMBER_TYPE: &str = "::cadence_rs::checker::types::NumberType";
+
+/// the types enums can have as raw types
+const RAW_TYPES: &[&str] = &[
+    "Int", "Int8", "Int16", "Int32", "Int64", "Int128", "Int256", "UInt", "UInt8", "UInt16", "UInt32", "UInt64",
+    "UInt128", "UInt256", "Word8", "Word16", "Word32", "Word64", "Word128", "Word256",
+];
+
+#[proc_macro_derive(ToCadence, attributes(cadence))]
+pub fn derive_to_cadence(input: TokenStream) -> TokenStream {
+    expand(input, to_cadence)
+}
+
+#[proc_macro_derive(FromCadence, attributes(cadence))]
+pub fn derive_from_cadence(input: TokenStream) -> TokenStream {
+    expand(input, from_cadence)
+}
+
+/// a struct or enum to derive for, string literals as written
+struct Item {
+    name: String,
+    type_id: String,
+    kind: ItemKind,
+}
+
+enum ItemKind {
+    Struct {
+        /// `Structure`, `Resource` or `Event`
+        kind: &'static str,
+        fields: Vec<Field>,
+    },
+    Enum {
+        raw_type: String,
+        cases: Vec<String>,
+    },
+}
+
+struct Field {
+    name: String,
+    /// a string literal
+    cadence_name: String,
+    ty: String,
+}
+
+fn expand(input: TokenStream, generate: fn(&Item) -> String) -> TokenStream {
+    let code = match parse(input) {
+        Ok(item) => generate(&item),
+        Err(message) => format!("compile_error!({:?});", message),
+    };
+    code.parse().unwrap()
+}
+
+fn to_cadence(item: &Item) -> String {
+    let Item { name, type_id, kind } = item;
+    let (value, ty) = match kind {
+        ItemKind::Struct { kind, fields } => {
+            let values: String = fields.iter()
+                .map(|field| format!("({}, {}::ToCadence::to_cadence(&self.{})),", field.cadence_name, CONVERT, field.name))
+                .collect();
+            let types: String = fields.iter()
+                .map(|field| format!("({}, <{} as {}::ToCadence>::cadence_type()),", field.cadence_name, field.ty, CONVERT))
+                .collect();
+            (
+                format!("{}::composite_value({}::{}, {}, vec![{}])", CONVERT, COMPOSITE_KIND, kind, type_id, values),
+                format!("{}::composite_type({}::{}, {}, vec![{}])", CONVERT, COMPOSITE_KIND, kind, type_id, types),
+            )
+        },
+        ItemKind::Enum { raw_type, cases } => {
+            let cases: String = cases.iter().enumerate().map(|(index, case)| format!("{}::{} => {},", name, case, index)).collect();
+            (
+                format!("{}::enum_value({}, {}::{}, match *self {{ {} }})", CONVERT, type_id, NUMBER_TYPE, raw_type, cases),
+                format!("{}::enum_type({}, {}::{})", CONVERT, type_id, NUMBER_TYPE, raw_type),
+            )
+        },
+    };
+    format!(
+        "impl {convert}::ToCadence for {name} {{
+            fn to_cadence(&self) -> {json_cadence}::Value {{ {value} }}
+            fn cadence_type() -> {json_cadence}::Type {{ {ty} }}
+        }}",
+        convert = CONVERT, json_cadence = JSON_CADENCE,
+    )
+}
+
+fn from_cadence(item: &Item) -> String {
+    let Item { name, type_id, kind } = item;
+    let body = match kind {
+        ItemKind::Struct { kind, fields } => {
+            let fields: String = fields.iter()
+                .map(|field| format!("{}: {}::field(composite, {})?,", field.name, CONVERT, field.cadence_name))
+                .collect();
+            let binding = if fields.is_empty() { "" } else { "let composite = " };
+            format!(
+                "{}{}::composite(value, {}::{}, {})?; Ok({} {{ {} }})",
+                binding, CONVERT, COMPOSITE_KIND, kind, type_id, name, fields,
+            )
+        },
+        ItemKind::Enum { raw_type, cases } => {
+            let arms: String = cases.iter().enumerate().map(|(index, case)| format!("{} => Ok({}::{}),", index, name, case)).collect();
+            format!(
+                "match {}::enum_case(value, {}, {}::{}, {})? {{ {} _ => unreachable!() }}",
+                CONVERT, type_id, NUMBER_TYPE, raw_type, cases.len(), arms,
+            )
+        },
+    };
+    format!(
+        "impl {convert}::FromCadence for {name} {{
+            fn from_cadence(value: &{json_cadence}::Value) -> Result<Self, {json_cadence}::DecodeError> {{ {body} }}
+        }}",
+        convert = CONVERT, json_cadence = JSON_CADENCE,
+    )
+}
+
+fn parse(input: TokenStream) -> Result<Item, String> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut at = 0;
+    let options = attributes(&tokens, &mut at)?;
+    skip_visibility(&tokens, &mut at);
+    let keyword = ident(tokens.get(at)).ok_or("expected a struct or enum")?;
+    let name = ident(tokens.get(at + 1)).ok_or("expected a struct or enum")?;
+    at += 2;
+    if punct(tokens.get(at)) == Some('<') {
+        return Err(format!("`{}` has generic parameters, which deriving Cadence conversions does not support", name))
+    }
+    let type_id = option(&options, "type_id").unwrap_or_else(|| format!("{:?}", name));
+    match keyword.as_str() {
+        "struct" => {
+            check_options(&options, &["type_id", "kind"])?;
+            let kind = match option(&options, "kind").as_deref() {
+                None | Some("\"struct\"") => "Structure",
+                Some("\"resource\"") => "Resource",
+                Some("\"event\"") => "Event",
+                Some(kind) => return Err(format!("unknown kind {}, expected \"struct\", \"resource\" or \"event\"", kind)),
+            };
+            let fields = match tokens.get(at) {
+                Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => fields(group.stream())?,
+                _ if punct(tokens.get(at)) == Some(';') => Vec::new(),
+                _ => return Err(format!("`{}` is a tuple struct, only structs with named fields convert to Cadence", name)),
+            };
+            Ok(Item { name, type_id, kind: ItemKind::Struct { kind, fields } })
+        },
+        "enum" => {
+            check_options(&options, &["type_id", "raw_type"])?;
+            let raw_type = match option(&options, "raw_type") {
+                None => "UInt8".to_string(),
+                Some(raw_type) => {
+                    let raw_type = raw_type.trim_matches('"');
+                    if !RAW_TYPES.contains(&raw_type) {
+                        return Err(format!("`{}` cannot be the raw type of an enum", raw_type))
+                    }
+                    raw_type.to_string()
+                },
+            };
+            let Some(TokenTree::Group(group)) = tokens.get(at) else {
+                return Err("expected the cases of the enum".to_string())
+            };
+            let cases = split(group.stream()).into_iter()
+                .map(|tokens| case(&tokens))
+                .collect::<Result<_, _>>()?;
+            Ok(Item { name, type_id, kind: ItemKind::Enum { raw_type, cases } })
+        },
+        _ => Err("only structs and enums convert to Cadence".to_string()),
+    }
+}
+
+fn ident(token: Option<&TokenTree>) -> Option<String> {
+    match token {
+        Some(TokenTree::Ident(ident)) => Some(ident.to_string()),
+        _ => None,
+    }
+}
+
+fn punct(token: Option<&TokenTree>) -> Option<char> {
+    match token {
+        Some(TokenTree::Punct(punct)) => Some(punct.as_char()),
+        _ => None,
+    }
+}
+
+/// the options of the `#[cadence(...)]` attributes at `at`, skipping the other attributes
+fn attributes(tokens: &[TokenTree], at: &mut usize) -> Result<Vec<(String, String)>, String> {
+    let mut options = Vec::new();
+    while punct(tokens.get(*at)) == Some('#') {
+        let Some(TokenTree::Group(group)) = tokens.get(*at + 1) else {
+            break
+        };
+        *at += 2;
+        let attribute: Vec<TokenTree> = group.stream().into_iter().collect();
+        if ident(attribute.first()).as_deref() != Some("cadence") {
+            continue;
+        }
+        let Some(TokenTree::Group(arguments)) = attribute.get(1) else {
+            return Err("expected `#[cadence(key = \"value\", ...)]`".to_string())
+        };
+        for option in split(arguments.stream()) {
+            match option.as_slice() {
+                [TokenTree::Ident(key), TokenTree::Punct(equals), TokenTree::Literal(value)] if equals.as_char() == '=' && value.to_string().starts_with('"') => {
+                    options.push((key.to_string(), value.to_string()));
+                },
+                _ => return Err("expected `#[cadence(key = \"value\", ...)]`".to_string()),
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn option(options: &[(String, String)], key: &str) -> Option<String> {
+    options.iter().find(|(option, _)| option == key).map(|(_, value)| value.clone())
+}
+
+fn check_options(options: &[(String, String)], keys: &[&str]) -> Result<(), String> {
+    match options.iter().find(|(key, _)| !keys.contains(&key.as_str())) {
+        Some((key, _)) => Err(format!("unknown option `{}`, expected one of `{}`", key, keys.join("`, `"))),
+        None => Ok(()),
+    }
+}
+
+/// `pub`, `pub(crate)`, ...
+fn skip_visibility(tokens: &[TokenTree], at: &mut usize) {
+    if ident(tokens.get(*at)).as_deref() == Some("pub") {
+        *at += 1;
+        if let Some(TokenTree::Group(group)) = tokens.get(*at) {
+            if group.delimiter() == Delimiter::Parenthesis {
+                *at += 1;
+            }
+        }
+    }
+}
+
+/// the tokens between commas, but those inside the `<...>` of types
+fn split(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut parts = vec![Vec::new()];
+    let mut depth = 0usize;
+    let mut arrow = false;
+    for token in stream {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' if !arrow => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    parts.push(Vec::new());
+                    continue;
+                },
+                _ => {},
+            }
+            arrow = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+        } else {
+            arrow = false;
+        }
+        parts.last_mut().unwrap().push(token);
+    }
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+fn fields(stream: TokenStream) -> Result<Vec<Field>, String> {
+    let mut fields = Vec::new();
+    for tokens in split(stream) {
+        let mut at = 0;
+        let options = attributes(&tokens, &mut at)?;
+        check_options(&options, &["rename"])?;
+        skip_visibility(&tokens, &mut at);
+        let name = ident(tokens.get(at)).ok_or("expected a field")?;
+        if punct(tokens.get(at + 1)) != Some(':') {
+            return Err(format!("expected the type of `{}`", name))
+        }
+        let ty: TokenStream = tokens[at + 2..].iter().cloned().collect();
+        let cadence_name = option(&options, "rename").unwrap_or_else(|| format!("{:?}", name.trim_start_matches("r#")));
+        fields.push(Field { name, cadence_name, ty: ty.to_string() });
+    }
+    Ok(fields)
+}
+
+fn case(tokens: &[TokenTree]) -> Result<String, String> {
+    let mut at = 0;
+    let options = attributes(tokens, &mut at)?;
+    check_options(&options, &[])?;
+    let name = ident(tokens.get(at)).ok_or("expected a case")?;
+    match tokens.get(at + 1) {
+        None => Ok(name),
+        Some(TokenTree::Group(_)) => Err(format!("`{}` has fields, only enums without fields convert to Cadence enums", name)),
+        Some(_) => Err(format!("`{}` has a discriminant, the raw values of Cadence enums are the indices of the cases", name)),
+    }
+}
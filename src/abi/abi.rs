@@ -0,0 +1,276 @@
+use crate::ast::{
+    CompositeKind, Declaration, FunctionDeclaration, NominalType, Parameter, Program, Purity, TransactionDeclaration, TypeAnnotation,
+    VariableKind,
+};
+use crate::checker::access::Visibility;
+use crate::checker::checker::TypeCheck;
+use crate::checker::types::Type;
+use crate::json_cadence::json::Json;
+
+/// what code outside of a program can use of it: the public declarations of its contracts,
+/// and the parameters of its transaction or script
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Abi {
+    /// top-level composites and interfaces, in declaration order
+    pub composites: Vec<AbiComposite>,
+    pub entry_point: Option<EntryPoint>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiComposite {
+    /// qualified name, e.g. `Token.Vault`
+    pub name: String,
+    pub kind: CompositeKind,
+    pub is_interface: bool,
+    /// qualified names of the interfaces it conforms to
+    pub conformances: Vec<String>,
+    pub fields: Vec<AbiField>,
+    pub functions: Vec<AbiFunction>,
+    /// the cases of an enum
+    pub cases: Vec<String>,
+    /// the events declared inside it
+    pub events: Vec<AbiEvent>,
+    /// the other public composites and interfaces declared inside it
+    pub nested: Vec<AbiComposite>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiField {
+    pub name: String,
+    pub kind: VariableKind,
+    pub ty: Type,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiFunction {
+    pub name: String,
+    pub is_view: bool,
+    pub parameters: Vec<AbiParameter>,
+    pub return_type: Type,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiParameter {
+    /// the label arguments must have, `None` for `_`
+    pub label: Option<String>,
+    pub name: String,
+    pub ty: Type,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiEvent {
+    /// qualified name, e.g. `Token.Deposited`
+    pub name: String,
+    pub parameters: Vec<AbiParameter>,
+}
+
+/// how a transaction or script is run
+#[derive(Clone, Debug, PartialEq)]
+pub enum EntryPoint {
+    Transaction {
+        parameters: Vec<AbiParameter>,
+        /// the types of the parameters of `prepare`, one account per signer
+        signers: Vec<Type>,
+    },
+    /// the `main` function of a program without contracts or transaction
+    Script {
+        parameters: Vec<AbiParameter>,
+        return_type: Type,
+    },
+}
+
+/// the ABI of a checked program. only members that are public everywhere are included,
+/// events are included whatever their access, as they are observed from outside
+pub fn abi(program: &Program, checked: &TypeCheck) -> Abi {
+    let extractor = Extractor { checked };
+    let mut abi = Abi::default();
+    for declaration in program.declarations.iter() {
+        match declaration {
+            Declaration::Composite(_) | Declaration::Interface(_) => {
+                if let Some(composite) = extractor.composite(declaration, None) {
+                    abi.composites.push(composite);
+                }
+            },
+            Declaration::Transaction(transaction) => abi.entry_point = Some(extractor.transaction(transaction)),
+            _ => (),
+        }
+    }
+    if abi.entry_point.is_none() {
+        let main = program.declarations.iter().find_map(|declaration| match declaration {
+            Declaration::Function(function) if function.identifier.name == "main" => Some(function),
+            _ => None,
+        });
+        if let Some(main) = main {
+            let main = extractor.function(main);
+            abi.entry_point = Some(EntryPoint::Script { parameters: main.parameters, return_type: main.return_type });
+        }
+    }
+    abi
+}
+
+struct Extractor<'a> {
+    checked: &'a TypeCheck,
+}
+
+impl Extractor<'_> {
+    fn ty(&self, annotation: &TypeAnnotation) -> Type {
+        self.checked.annotation_type(annotation).cloned().unwrap_or(Type::Invalid)
+    }
+
+    fn parameters(&self, parameters: &[Parameter]) -> Vec<AbiParameter> {
+        parameters.iter()
+            .map(|parameter| AbiParameter {
+                label: parameter.argument_label().map(str::to_string),
+                name: parameter.identifier.name.clone(),
+                ty: self.ty(&parameter.type_annotation),
+            })
+            .collect()
+    }
+
+    fn function(&self, function: &FunctionDeclaration) -> AbiFunction {
+        AbiFunction {
+            name: function.identifier.name.clone(),
+            is_view: function.purity == Purity::View,
+            parameters: self.parameters(&function.parameters),
+            return_type: function.return_type.as_ref().map_or(Type::Void, |annotation| self.ty(annotation)),
+        }
+    }
+
+    /// the composite or interface, `None` if it is not public, or if it is an event
+    fn composite(&self, declaration: &Declaration, outer: Option<&str>) -> Option<AbiComposite> {
+        let (access, kind, is_interface, identifier, conformances, members) = match declaration {
+            Declaration::Composite(d) => (&d.access, d.kind, false, &d.identifier, &d.conformances, &d.members),
+            Declaration::Interface(d) => (&d.access, d.kind, true, &d.identifier, &d.conformances, &d.members),
+            _ => return None,
+        };
+        // top-level composites of scripts and transactions may omit their access modifier
+        if kind == CompositeKind::Event || (outer.is_some() && Visibility::of(access) != Visibility::Public) {
+            return None
+        }
+        let name = match outer {
+            Some(outer) => format!("{}.{}", outer, identifier.name),
+            None => identifier.name.clone(),
+        };
+        let mut composite = AbiComposite {
+            // as written, the checker does not know imported interfaces
+            conformances: conformances.iter().map(NominalType::name).collect(),
+            name,
+            kind,
+            is_interface,
+            fields: Vec::new(),
+            functions: Vec::new(),
+            cases: Vec::new(),
+            events: Vec::new(),
+            nested: Vec::new(),
+        };
+        for member in members.iter() {
+            match member {
+                Declaration::Field(field) if Visibility::of(&field.access) == Visibility::Public => {
+                    composite.fields.push(AbiField {
+                        name: field.identifier.name.clone(),
+                        kind: field.variable_kind,
+                        ty: self.ty(&field.type_annotation),
+                    });
+                },
+                Declaration::Function(function) if Visibility::of(&function.access) == Visibility::Public => {
+                    composite.functions.push(self.function(function));
+                },
+                Declaration::EnumCase(case) => composite.cases.push(case.identifier.name.clone()),
+                Declaration::Composite(event) if event.kind == CompositeKind::Event => {
+                    composite.events.push(AbiEvent {
+                        name: format!("{}.{}", composite.name, event.identifier.name),
+                        parameters: self.parameters(&event.parameters),
+                    });
+                },
+                Declaration::Composite(_) | Declaration::Interface(_) => {
+                    if let Some(nested) = self.composite(member, Some(&composite.name)) {
+                        composite.nested.push(nested);
+                    }
+                },
+                _ => (),
+            }
+        }
+        Some(composite)
+    }
+
+    fn transaction(&self, transaction: &TransactionDeclaration) -> EntryPoint {
+        let signers = transaction.prepare.iter()
+            .flat_map(|prepare| prepare.function.parameters.iter())
+            .map(|parameter| self.ty(&parameter.type_annotation))
+            .collect();
+        EntryPoint::Transaction { parameters: self.parameters(&transaction.parameters), signers }
+    }
+}
+
+impl Abi {
+    pub fn to_json(&self) -> Json {
+        let entry_point = match &self.entry_point {
+            None => Json::Null,
+            Some(EntryPoint::Transaction { parameters, signers }) => Json::object(vec![
+                ("kind", Json::string("transaction")),
+                ("parameters", parameters_json(parameters)),
+                ("signers", Json::Array(signers.iter().map(type_json).collect())),
+            ]),
+            Some(EntryPoint::Script { parameters, return_type }) => Json::object(vec![
+                ("kind", Json::string("script")),
+                ("parameters", parameters_json(parameters)),
+                ("returnType", type_json(return_type)),
+            ]),
+        };
+        Json::object(vec![
+            ("composites", Json::Array(self.composites.iter().map(AbiComposite::to_json).collect())),
+            ("entryPoint", entry_point),
+        ])
+    }
+}
+
+impl AbiComposite {
+    pub fn to_json(&self) -> Json {
+        let fields = self.fields.iter()
+            .map(|field| {
+                let kind = match field.kind {
+                    VariableKind::Constant => "let",
+                    VariableKind::Variable => "var",
+                };
+                Json::object(vec![("name", Json::string(&field.name)), ("kind", Json::string(kind)), ("type", type_json(&field.ty))])
+            })
+            .collect();
+        let functions = self.functions.iter()
+            .map(|function| Json::object(vec![
+                ("name", Json::string(&function.name)),
+                ("view", Json::Bool(function.is_view)),
+                ("parameters", parameters_json(&function.parameters)),
+                ("returnType", type_json(&function.return_type)),
+            ]))
+            .collect();
+        let events = self.events.iter()
+            .map(|event| Json::object(vec![("name", Json::string(&event.name)), ("parameters", parameters_json(&event.parameters))]))
+            .collect();
+        Json::object(vec![
+            ("name", Json::string(&self.name)),
+            ("kind", Json::string(self.kind.keyword())),
+            ("interface", Json::Bool(self.is_interface)),
+            ("conformances", Json::Array(self.conformances.iter().map(|name| Json::string(name)).collect())),
+            ("fields", Json::Array(fields)),
+            ("functions", Json::Array(functions)),
+            ("cases", Json::Array(self.cases.iter().map(|case| Json::string(case)).collect())),
+            ("events", Json::Array(events)),
+            ("nested", Json::Array(self.nested.iter().map(AbiComposite::to_json).collect())),
+        ])
+    }
+}
+
+fn parameters_json(parameters: &[AbiParameter]) -> Json {
+    Json::Array(parameters.iter()
+        .map(|parameter| Json::object(vec![
+            ("label", parameter.label.as_deref().map_or(Json::Null, Json::string)),
+            ("name", Json::string(&parameter.name)),
+            ("type", type_json(&parameter.ty)),
+        ]))
+        .collect())
+}
+
+/// types as Cadence writes them
+fn type_json(ty: &Type) -> Json {
+    Json::string(&ty.to_string())
+}
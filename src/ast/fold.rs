@@ -0,0 +1,556 @@
+use super::{
+    Access, Argument, Authorization, Block, CompositeDeclaration, Condition, Declaration, ElseBranch, EntitlementDeclaration,
+    EntitlementMappingDeclaration, EntitlementRelation, EnumCaseDeclaration, Expression,
+    FieldDeclaration, FunctionDeclaration, Identifier, IfStatement, IfTest, ImportDeclaration, ImportLocation,
+    InterfaceDeclaration, NominalType, Parameter, Program, SpecialFunctionDeclaration, Span, Statement, SwitchCase,
+    TransactionDeclaration, Type, TypeAnnotation, VariableDeclaration,
+};
+
+/// owning transformation of the AST, each node is rebuilt from its folded children.
+/// every method defaults to folding the node's children, override it to replace a node
+/// and call the matching `walk_` function to keep descending
+pub trait Fold {
+    fn fold_program(&mut self, program: Program) -> Program {
+        walk_program(self, program)
+    }
+
+    fn fold_declaration(&mut self, declaration: Declaration) -> Declaration {
+        walk_declaration(self, declaration)
+    }
+
+    fn fold_import_declaration(&mut self, declaration: ImportDeclaration) -> ImportDeclaration {
+        walk_import_declaration(self, declaration)
+    }
+
+    fn fold_composite_declaration(&mut self, declaration: CompositeDeclaration) -> CompositeDeclaration {
+        walk_composite_declaration(self, declaration)
+    }
+
+    fn fold_interface_declaration(&mut self, declaration: InterfaceDeclaration) -> InterfaceDeclaration {
+        walk_interface_declaration(self, declaration)
+    }
+
+    fn fold_field_declaration(&mut self, declaration: FieldDeclaration) -> FieldDeclaration {
+        walk_field_declaration(self, declaration)
+    }
+
+    fn fold_function_declaration(&mut self, declaration: FunctionDeclaration) -> FunctionDeclaration {
+        walk_function_declaration(self, declaration)
+    }
+
+    fn fold_special_function_declaration(&mut self, declaration: SpecialFunctionDeclaration) -> SpecialFunctionDeclaration {
+        walk_special_function_declaration(self, declaration)
+    }
+
+    fn fold_enum_case_declaration(&mut self, declaration: EnumCaseDeclaration) -> EnumCaseDeclaration {
+        walk_enum_case_declaration(self, declaration)
+    }
+
+    fn fold_variable_declaration(&mut self, declaration: VariableDeclaration) -> VariableDeclaration {
+        walk_variable_declaration(self, declaration)
+    }
+
+    fn fold_transaction_declaration(&mut self, declaration: TransactionDeclaration) -> TransactionDeclaration {
+        walk_transaction_declaration(self, declaration)
+    }
+
+    fn fold_entitlement_declaration(&mut self, declaration: EntitlementDeclaration) -> EntitlementDeclaration {
+        walk_entitlement_declaration(self, declaration)
+    }
+
+    fn fold_entitlement_mapping_declaration(&mut self, declaration: EntitlementMappingDeclaration) -> EntitlementMappingDeclaration {
+        walk_entitlement_mapping_declaration(self, declaration)
+    }
+
+    fn fold_access(&mut self, access: Access) -> Access {
+        walk_access(self, access)
+    }
+
+    fn fold_authorization(&mut self, authorization: Authorization) -> Authorization {
+        walk_authorization(self, authorization)
+    }
+
+    fn fold_parameter(&mut self, parameter: Parameter) -> Parameter {
+        walk_parameter(self, parameter)
+    }
+
+    fn fold_condition(&mut self, condition: Condition) -> Condition {
+        walk_condition(self, condition)
+    }
+
+    fn fold_type_annotation(&mut self, type_annotation: TypeAnnotation) -> TypeAnnotation {
+        walk_type_annotation(self, type_annotation)
+    }
+
+    fn fold_type(&mut self, ty: Type) -> Type {
+        walk_type(self, ty)
+    }
+
+    fn fold_nominal_type(&mut self, nominal: NominalType) -> NominalType {
+        walk_nominal_type(self, nominal)
+    }
+
+    fn fold_block(&mut self, block: Block) -> Block {
+        walk_block(self, block)
+    }
+
+    fn fold_statement(&mut self, statement: Statement) -> Statement {
+        walk_statement(self, statement)
+    }
+
+    fn fold_if_statement(&mut self, statement: IfStatement) -> IfStatement {
+        walk_if_statement(self, statement)
+    }
+
+    fn fold_switch_case(&mut self, case: SwitchCase) -> SwitchCase {
+        walk_switch_case(self, case)
+    }
+
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        walk_expression(self, expression)
+    }
+
+    fn fold_argument(&mut self, argument: Argument) -> Argument {
+        walk_argument(self, argument)
+    }
+
+    fn fold_identifier(&mut self, identifier: Identifier) -> Identifier {
+        walk_identifier(self, identifier)
+    }
+
+    /// every span of the tree passes through here, e.g. to shift or erase positions
+    fn fold_span(&mut self, span: Span) -> Span {
+        span
+    }
+}
+
+fn fold_all<T>(items: Vec<T>, fold: impl FnMut(T) -> T) -> Vec<T> {
+    items.into_iter().map(fold).collect()
+}
+
+/// folds a boxed node, reusing its allocation
+fn fold_box<T>(mut item: Box<T>, fold: impl FnOnce(T) -> T) -> Box<T> {
+    *item = fold(*item);
+    item
+}
+
+pub fn walk_program<F: Fold + ?Sized>(folder: &mut F, program: Program) -> Program {
+    Program {
+        declarations: fold_all(program.declarations, |d| folder.fold_declaration(d)),
+        span: folder.fold_span(program.span),
+    }
+}
+
+pub fn walk_declaration<F: Fold + ?Sized>(folder: &mut F, declaration: Declaration) -> Declaration {
+    match declaration {
+        Declaration::Import(d) => Declaration::Import(folder.fold_import_declaration(d)),
+        Declaration::Composite(d) => Declaration::Composite(folder.fold_composite_declaration(d)),
+        Declaration::Interface(d) => Declaration::Interface(folder.fold_interface_declaration(d)),
+        Declaration::Field(d) => Declaration::Field(folder.fold_field_declaration(d)),
+        Declaration::Function(d) => Declaration::Function(folder.fold_function_declaration(d)),
+        Declaration::SpecialFunction(d) => Declaration::SpecialFunction(folder.fold_special_function_declaration(d)),
+        Declaration::EnumCase(d) => Declaration::EnumCase(folder.fold_enum_case_declaration(d)),
+        Declaration::Variable(d) => Declaration::Variable(folder.fold_variable_declaration(d)),
+        Declaration::Transaction(d) => Declaration::Transaction(folder.fold_transaction_declaration(d)),
+        Declaration::Entitlement(d) => Declaration::Entitlement(folder.fold_entitlement_declaration(d)),
+        Declaration::EntitlementMapping(d) => Declaration::EntitlementMapping(folder.fold_entitlement_mapping_declaration(d)),
+        Declaration::Error(span) => Declaration::Error(folder.fold_span(span)),
+    }
+}
+
+pub fn walk_import_declaration<F: Fold + ?Sized>(folder: &mut F, declaration: ImportDeclaration) -> ImportDeclaration {
+    ImportDeclaration {
+        identifiers: fold_all(declaration.identifiers, |i| folder.fold_identifier(i)),
+        location: match declaration.location {
+            ImportLocation::Identifier(identifier) => ImportLocation::Identifier(folder.fold_identifier(identifier)),
+            location => location,
+        },
+        span: folder.fold_span(declaration.span),
+    }
+}
+
+pub fn walk_composite_declaration<F: Fold + ?Sized>(folder: &mut F, declaration: CompositeDeclaration) -> CompositeDeclaration {
+    CompositeDeclaration {
+        access: folder.fold_access(declaration.access),
+        kind: declaration.kind,
+        identifier: folder.fold_identifier(declaration.identifier),
+        conformances: fold_all(declaration.conformances, |c| folder.fold_nominal_type(c)),
+        parameters: fold_all(declaration.parameters, |p| folder.fold_parameter(p)),
+        members: fold_all(declaration.members, |m| folder.fold_declaration(m)),
+        span: folder.fold_span(declaration.span),
+    }
+}
+
+pub fn walk_interface_declaration<F: Fold + ?Sized>(folder: &mut F, declaration: InterfaceDeclaration) -> InterfaceDeclaration {
+    InterfaceDeclaration {
+        access: folder.fold_access(declaration.access),
+        kind: declaration.kind,
+        identifier: folder.fold_identifier(declaration.identifier),
+        conformances: fold_all(declaration.conformances, |c| folder.fold_nominal_type(c)),
+        members: fold_all(declaration.members, |m| folder.fold_declaration(m)),
+        span: folder.fold_span(declaration.span),
+    }
+}
+
+pub fn walk_field_declaration<F: Fold + ?Sized>(folder: &mut F, declaration: FieldDeclaration) -> FieldDeclaration {
+    FieldDeclaration {
+        access: folder.fold_access(declaration.access),
+        variable_kind: declaration.variable_kind,
+        identifier: folder.fold_identifier(declaration.identifier),
+        type_annotation: folder.fold_type_annotation(declaration.type_annotation),
+        span: folder.fold_span(declaration.span),
+    }
+}
+
+pub fn walk_function_declaration<F: Fold + ?Sized>(folder: &mut F, declaration: FunctionDeclaration) -> FunctionDeclaration {
+    FunctionDeclaration {
+        access: folder.fold_access(declaration.access),
+        purity: declaration.purity,
+        identifier: folder.fold_identifier(declaration.identifier),
+        parameters: fold_all(declaration.parameters, |p| folder.fold_parameter(p)),
+        return_type: declaration.return_type.map(|t| folder.fold_type_annotation(t)),
+        pre_conditions: fold_all(declaration.pre_conditions, |c| folder.fold_condition(c)),
+        post_conditions: fold_all(declaration.post_conditions, |c| folder.fold_condition(c)),
+        body: declaration.body.map(|b| folder.fold_block(b)),
+        span: folder.fold_span(declaration.span),
+    }
+}
+
+pub fn walk_special_function_declaration<F: Fold + ?Sized>(
+    folder: &mut F,
+    declaration: SpecialFunctionDeclaration,
+) -> SpecialFunctionDeclaration {
+    SpecialFunctionDeclaration {
+        kind: declaration.kind,
+        function: folder.fold_function_declaration(declaration.function),
+    }
+}
+
+pub fn walk_enum_case_declaration<F: Fold + ?Sized>(folder: &mut F, declaration: EnumCaseDeclaration) -> EnumCaseDeclaration {
+    EnumCaseDeclaration {
+        access: folder.fold_access(declaration.access),
+        identifier: folder.fold_identifier(declaration.identifier),
+        span: folder.fold_span(declaration.span),
+    }
+}
+
+pub fn walk_variable_declaration<F: Fold + ?Sized>(folder: &mut F, declaration: VariableDeclaration) -> VariableDeclaration {
+    VariableDeclaration {
+        access: folder.fold_access(declaration.access),
+        kind: declaration.kind,
+        identifier: folder.fold_identifier(declaration.identifier),
+        type_annotation: declaration.type_annotation.map(|t| folder.fold_type_annotation(t)),
+        transfer: declaration.transfer,
+        value: folder.fold_expression(declaration.value),
+        second: declaration.second.map(|(transfer, value)| (transfer, Box::new(folder.fold_expression(*value)))),
+        span: folder.fold_span(declaration.span),
+    }
+}
+
+pub fn walk_transaction_declaration<F: Fold + ?Sized>(folder: &mut F, declaration: TransactionDeclaration) -> TransactionDeclaration {
+    TransactionDeclaration {
+        parameters: fold_all(declaration.parameters, |p| folder.fold_parameter(p)),
+        fields: fold_all(declaration.fields, |f| folder.fold_field_declaration(f)),
+        prepare: declaration.prepare.map(|p| folder.fold_special_function_declaration(p)),
+        pre_conditions: fold_all(declaration.pre_conditions, |c| folder.fold_condition(c)),
+        execute: declaration.execute.map(|b| folder.fold_block(b)),
+        post_conditions: fold_all(declaration.post_conditions, |c| folder.fold_condition(c)),
+        span: folder.fold_span(declaration.span),
+    }
+}
+
+pub fn walk_entitlement_declaration<F: Fold + ?Sized>(folder: &mut F, declaration: EntitlementDeclaration) -> EntitlementDeclaration {
+    EntitlementDeclaration {
+        access: folder.fold_access(declaration.access),
+        identifier: folder.fold_identifier(declaration.identifier),
+        span: folder.fold_span(declaration.span),
+    }
+}
+
+pub fn walk_entitlement_mapping_declaration<F: Fold + ?Sized>(
+    folder: &mut F,
+    declaration: EntitlementMappingDeclaration,
+) -> EntitlementMappingDeclaration {
+    EntitlementMappingDeclaration {
+        access: folder.fold_access(declaration.access),
+        identifier: folder.fold_identifier(declaration.identifier),
+        relations: fold_all(declaration.relations, |relation| EntitlementRelation {
+            input: folder.fold_nominal_type(relation.input),
+            output: folder.fold_nominal_type(relation.output),
+            span: folder.fold_span(relation.span),
+        }),
+        includes: fold_all(declaration.includes, |i| folder.fold_nominal_type(i)),
+        span: folder.fold_span(declaration.span),
+    }
+}
+
+pub fn walk_access<F: Fold + ?Sized>(folder: &mut F, access: Access) -> Access {
+    match access {
+        Access::Entitlements(authorization) => Access::Entitlements(folder.fold_authorization(authorization)),
+        access => access,
+    }
+}
+
+pub fn walk_authorization<F: Fold + ?Sized>(folder: &mut F, authorization: Authorization) -> Authorization {
+    match authorization {
+        Authorization::Conjunction(entitlements) => {
+            Authorization::Conjunction(fold_all(entitlements, |e| folder.fold_nominal_type(e)))
+        },
+        Authorization::Disjunction(entitlements) => {
+            Authorization::Disjunction(fold_all(entitlements, |e| folder.fold_nominal_type(e)))
+        },
+        Authorization::Mapping(mapping) => Authorization::Mapping(folder.fold_nominal_type(mapping)),
+    }
+}
+
+pub fn walk_parameter<F: Fold + ?Sized>(folder: &mut F, parameter: Parameter) -> Parameter {
+    Parameter {
+        label: parameter.label.map(|l| folder.fold_identifier(l)),
+        identifier: folder.fold_identifier(parameter.identifier),
+        type_annotation: folder.fold_type_annotation(parameter.type_annotation),
+        default: parameter.default.map(|d| folder.fold_expression(d)),
+        span: folder.fold_span(parameter.span),
+    }
+}
+
+pub fn walk_condition<F: Fold + ?Sized>(folder: &mut F, condition: Condition) -> Condition {
+    Condition {
+        test: folder.fold_expression(condition.test),
+        message: condition.message.map(|m| folder.fold_expression(m)),
+        span: folder.fold_span(condition.span),
+    }
+}
+
+pub fn walk_type_annotation<F: Fold + ?Sized>(folder: &mut F, type_annotation: TypeAnnotation) -> TypeAnnotation {
+    TypeAnnotation {
+        is_resource: type_annotation.is_resource,
+        ty: folder.fold_type(type_annotation.ty),
+        span: folder.fold_span(type_annotation.span),
+    }
+}
+
+pub fn walk_type<F: Fold + ?Sized>(folder: &mut F, ty: Type) -> Type {
+    match ty {
+        Type::Nominal(nominal) => Type::Nominal(folder.fold_nominal_type(nominal)),
+        Type::Optional { ty, span } => Type::Optional {
+            ty: fold_box(ty, |t| folder.fold_type(t)),
+            span: folder.fold_span(span),
+        },
+        Type::VariableSized { ty, span } => Type::VariableSized {
+            ty: fold_box(ty, |t| folder.fold_type(t)),
+            span: folder.fold_span(span),
+        },
+        Type::ConstantSized { ty, size, span } => Type::ConstantSized {
+            ty: fold_box(ty, |t| folder.fold_type(t)),
+            size,
+            span: folder.fold_span(span),
+        },
+        Type::Dictionary { key, value, span } => Type::Dictionary {
+            key: fold_box(key, |t| folder.fold_type(t)),
+            value: fold_box(value, |t| folder.fold_type(t)),
+            span: folder.fold_span(span),
+        },
+        Type::Function { parameters, return_type, span } => Type::Function {
+            parameters: fold_all(parameters, |p| folder.fold_type_annotation(p)),
+            return_type: fold_box(return_type, |t| folder.fold_type_annotation(t)),
+            span: folder.fold_span(span),
+        },
+        Type::Reference { authorized, entitlements, ty, span } => Type::Reference {
+            authorized,
+            entitlements: entitlements.map(|e| folder.fold_authorization(e)),
+            ty: fold_box(ty, |t| folder.fold_type(t)),
+            span: folder.fold_span(span),
+        },
+        Type::Restricted { ty, restrictions, span } => Type::Restricted {
+            ty: ty.map(|ty| fold_box(ty, |t| folder.fold_type(t))),
+            restrictions: fold_all(restrictions, |r| folder.fold_nominal_type(r)),
+            span: folder.fold_span(span),
+        },
+        Type::Instantiation { ty, type_arguments, span } => Type::Instantiation {
+            ty: fold_box(ty, |t| folder.fold_type(t)),
+            type_arguments: fold_all(type_arguments, |a| folder.fold_type_annotation(a)),
+            span: folder.fold_span(span),
+        },
+    }
+}
+
+pub fn walk_nominal_type<F: Fold + ?Sized>(folder: &mut F, nominal: NominalType) -> NominalType {
+    NominalType {
+        identifier: folder.fold_identifier(nominal.identifier),
+        nested_identifiers: fold_all(nominal.nested_identifiers, |i| folder.fold_identifier(i)),
+    }
+}
+
+pub fn walk_block<F: Fold + ?Sized>(folder: &mut F, block: Block) -> Block {
+    Block {
+        statements: fold_all(block.statements, |s| folder.fold_statement(s)),
+        span: folder.fold_span(block.span),
+    }
+}
+
+pub fn walk_statement<F: Fold + ?Sized>(folder: &mut F, statement: Statement) -> Statement {
+    match statement {
+        Statement::Expression(expression) => Statement::Expression(folder.fold_expression(expression)),
+        Statement::Return { expression, span } => Statement::Return {
+            expression: expression.map(|e| folder.fold_expression(e)),
+            span: folder.fold_span(span),
+        },
+        Statement::Break { span } => Statement::Break { span: folder.fold_span(span) },
+        Statement::Continue { span } => Statement::Continue { span: folder.fold_span(span) },
+        Statement::Variable(declaration) => Statement::Variable(folder.fold_variable_declaration(declaration)),
+        Statement::Assignment { target, transfer, value, span } => Statement::Assignment {
+            target: folder.fold_expression(target),
+            transfer,
+            value: folder.fold_expression(value),
+            span: folder.fold_span(span),
+        },
+        Statement::Swap { left, right, span } => Statement::Swap {
+            left: folder.fold_expression(left),
+            right: folder.fold_expression(right),
+            span: folder.fold_span(span),
+        },
+        Statement::If(statement) => Statement::If(folder.fold_if_statement(statement)),
+        Statement::While { test, block, span } => Statement::While {
+            test: folder.fold_expression(test),
+            block: folder.fold_block(block),
+            span: folder.fold_span(span),
+        },
+        Statement::For { index, identifier, value, block, span } => Statement::For {
+            index: index.map(|i| folder.fold_identifier(i)),
+            identifier: folder.fold_identifier(identifier),
+            value: folder.fold_expression(value),
+            block: folder.fold_block(block),
+            span: folder.fold_span(span),
+        },
+        Statement::Switch { expression, cases, span } => Statement::Switch {
+            expression: folder.fold_expression(expression),
+            cases: fold_all(cases, |c| folder.fold_switch_case(c)),
+            span: folder.fold_span(span),
+        },
+        Statement::Emit { invocation, span } => Statement::Emit {
+            invocation: folder.fold_expression(invocation),
+            span: folder.fold_span(span),
+        },
+        Statement::Destroy { expression, span } => Statement::Destroy {
+            expression: folder.fold_expression(expression),
+            span: folder.fold_span(span),
+        },
+        Statement::Error(span) => Statement::Error(folder.fold_span(span)),
+    }
+}
+
+pub fn walk_if_statement<F: Fold + ?Sized>(folder: &mut F, statement: IfStatement) -> IfStatement {
+    IfStatement {
+        test: match statement.test {
+            IfTest::Expression(test) => IfTest::Expression(folder.fold_expression(test)),
+            IfTest::Binding(binding) => IfTest::Binding(fold_box(binding, |b| folder.fold_variable_declaration(b))),
+        },
+        then: folder.fold_block(statement.then),
+        else_: statement.else_.map(|else_| match else_ {
+            ElseBranch::Block(block) => ElseBranch::Block(folder.fold_block(block)),
+            ElseBranch::If(else_if) => ElseBranch::If(fold_box(else_if, |s| folder.fold_if_statement(s))),
+        }),
+        span: folder.fold_span(statement.span),
+    }
+}
+
+pub fn walk_switch_case<F: Fold + ?Sized>(folder: &mut F, case: SwitchCase) -> SwitchCase {
+    SwitchCase {
+        expressions: fold_all(case.expressions, |e| folder.fold_expression(e)),
+        statements: fold_all(case.statements, |s| folder.fold_statement(s)),
+        span: folder.fold_span(case.span),
+    }
+}
+
+pub fn walk_expression<F: Fold + ?Sized>(folder: &mut F, expression: Expression) -> Expression {
+    match expression {
+        Expression::Nil { span } => Expression::Nil { span: folder.fold_span(span) },
+        Expression::Bool { value, span } => Expression::Bool { value, span: folder.fold_span(span) },
+        Expression::Integer { literal, span } => Expression::Integer { literal, span: folder.fold_span(span) },
+        Expression::FixedPoint { literal, span } => Expression::FixedPoint { literal, span: folder.fold_span(span) },
+        Expression::String { value, span } => Expression::String { value, span: folder.fold_span(span) },
+        Expression::Array { values, span } => Expression::Array {
+            values: fold_all(values, |v| folder.fold_expression(v)),
+            span: folder.fold_span(span),
+        },
+        Expression::Dictionary { entries, span } => Expression::Dictionary {
+            entries: entries.into_iter()
+                .map(|(key, value)| (folder.fold_expression(key), folder.fold_expression(value)))
+                .collect(),
+            span: folder.fold_span(span),
+        },
+        Expression::Identifier(identifier) => Expression::Identifier(folder.fold_identifier(identifier)),
+        Expression::Path { domain, identifier, span } => Expression::Path {
+            domain: folder.fold_identifier(domain),
+            identifier: folder.fold_identifier(identifier),
+            span: folder.fold_span(span),
+        },
+        Expression::Member { expression, optional, identifier, span } => Expression::Member {
+            expression: fold_box(expression, |e| folder.fold_expression(e)),
+            optional,
+            identifier: folder.fold_identifier(identifier),
+            span: folder.fold_span(span),
+        },
+        Expression::Index { expression, index, span } => Expression::Index {
+            expression: fold_box(expression, |e| folder.fold_expression(e)),
+            index: fold_box(index, |e| folder.fold_expression(e)),
+            span: folder.fold_span(span),
+        },
+        Expression::Invocation { invoked, type_arguments, arguments, span } => Expression::Invocation {
+            invoked: fold_box(invoked, |e| folder.fold_expression(e)),
+            type_arguments: fold_all(type_arguments, |t| folder.fold_type_annotation(t)),
+            arguments: fold_all(arguments, |a| folder.fold_argument(a)),
+            span: folder.fold_span(span),
+        },
+        Expression::Unary { operation, expression, span } => Expression::Unary {
+            operation,
+            expression: fold_box(expression, |e| folder.fold_expression(e)),
+            span: folder.fold_span(span),
+        },
+        Expression::Binary { operation, left, right, span } => Expression::Binary {
+            operation,
+            left: fold_box(left, |e| folder.fold_expression(e)),
+            right: fold_box(right, |e| folder.fold_expression(e)),
+            span: folder.fold_span(span),
+        },
+        Expression::Conditional { test, then, else_, span } => Expression::Conditional {
+            test: fold_box(test, |e| folder.fold_expression(e)),
+            then: fold_box(then, |e| folder.fold_expression(e)),
+            else_: fold_box(else_, |e| folder.fold_expression(e)),
+            span: folder.fold_span(span),
+        },
+        Expression::Casting { expression, operation, type_annotation, span } => Expression::Casting {
+            expression: fold_box(expression, |e| folder.fold_expression(e)),
+            operation,
+            type_annotation: fold_box(type_annotation, |t| folder.fold_type_annotation(t)),
+            span: folder.fold_span(span),
+        },
+        Expression::Create { invocation, span } => Expression::Create {
+            invocation: fold_box(invocation, |e| folder.fold_expression(e)),
+            span: folder.fold_span(span),
+        },
+        Expression::Reference { expression, ty, span } => Expression::Reference {
+            expression: fold_box(expression, |e| folder.fold_expression(e)),
+            ty: ty.map(|ty| fold_box(ty, |t| folder.fold_type(t))),
+            span: folder.fold_span(span),
+        },
+        Expression::Force { expression, span } => Expression::Force {
+            expression: fold_box(expression, |e| folder.fold_expression(e)),
+            span: folder.fold_span(span),
+        },
+    }
+}
+
+pub fn walk_argument<F: Fold + ?Sized>(folder: &mut F, argument: Argument) -> Argument {
+    Argument {
+        label: argument.label.map(|l| folder.fold_identifier(l)),
+        expression: folder.fold_expression(argument.expression),
+    }
+}
+
+pub fn walk_identifier<F: Fold + ?Sized>(folder: &mut F, identifier: Identifier) -> Identifier {
+    Identifier {
+        name: identifier.name,
+        span: folder.fold_span(identifier.span),
+    }
+}
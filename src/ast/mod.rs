@@ -0,0 +1,866 @@
+pub mod fold;
+pub mod visit;
+pub mod visit_mut;
+
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub at: usize,
+    pub size: usize,
+}
+
+impl Span {
+    pub fn new(at: usize, size: usize) -> Self {
+        Span { at, size }
+    }
+
+    /// span covering `from` up to the end of `to`
+    pub fn between(from: Span, to: Span) -> Self {
+        let end = to.end().max(from.end());
+        Span { at: from.at, size: end - from.at }
+    }
+
+    pub fn end(&self) -> usize {
+        self.at + self.size
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Identifier {
+    pub name: String,
+    pub span: Span,
+}
+
+impl Identifier {
+    pub fn new(name: &str, span: Span) -> Self {
+        Identifier { name: name.to_string(), span }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Program {
+    pub declarations: Vec<Declaration>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Declaration {
+    Import(ImportDeclaration),
+    Composite(CompositeDeclaration),
+    Interface(InterfaceDeclaration),
+    Field(FieldDeclaration),
+    Function(FunctionDeclaration),
+    SpecialFunction(SpecialFunctionDeclaration),
+    EnumCase(EnumCaseDeclaration),
+    Variable(VariableDeclaration),
+    Transaction(TransactionDeclaration),
+    Entitlement(EntitlementDeclaration),
+    EntitlementMapping(EntitlementMappingDeclaration),
+    /// placeholder for a declaration that failed to parse
+    Error(Span),
+}
+
+impl Declaration {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Import(d) => d.span,
+            Self::Composite(d) => d.span,
+            Self::Interface(d) => d.span,
+            Self::Field(d) => d.span,
+            Self::Function(d) => d.span,
+            Self::SpecialFunction(d) => d.function.span,
+            Self::EnumCase(d) => d.span,
+            Self::Variable(d) => d.span,
+            Self::Transaction(d) => d.span,
+            Self::Entitlement(d) => d.span,
+            Self::EntitlementMapping(d) => d.span,
+            Self::Error(span) => *span,
+        }
+    }
+
+    /// name of the declared entity, if it has one
+    pub fn identifier(&self) -> Option<&Identifier> {
+        match self {
+            Self::Composite(d) => Some(&d.identifier),
+            Self::Interface(d) => Some(&d.identifier),
+            Self::Field(d) => Some(&d.identifier),
+            Self::Function(d) => Some(&d.identifier),
+            Self::SpecialFunction(d) => Some(&d.function.identifier),
+            Self::EnumCase(d) => Some(&d.identifier),
+            Self::Variable(d) => Some(&d.identifier),
+            Self::Entitlement(d) => Some(&d.identifier),
+            Self::EntitlementMapping(d) => Some(&d.identifier),
+            Self::Import(_) | Self::Transaction(_) | Self::Error(_) => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Access {
+    NotSpecified,
+    Pub,            // pub
+    PubSet,         // pub(set)
+    Priv,           // priv
+    All,            // access(all)
+    Self_,          // access(self)
+    Contract,       // access(contract)
+    Account,        // access(account)
+    /// `access(E)`, `access(E, F)`, `access(E | F)` or `access(mapping M)`
+    Entitlements(Authorization),
+}
+
+impl fmt::Display for Access {
+    /// the modifier as written, empty if not specified
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotSpecified => Ok(()),
+            Self::Pub => write!(f, "pub"),
+            Self::PubSet => write!(f, "pub(set)"),
+            Self::Priv => write!(f, "priv"),
+            Self::All => write!(f, "access(all)"),
+            Self::Self_ => write!(f, "access(self)"),
+            Self::Contract => write!(f, "access(contract)"),
+            Self::Account => write!(f, "access(account)"),
+            Self::Entitlements(authorization) => write!(f, "access({})", authorization),
+        }
+    }
+}
+
+/// the entitlements `access(...)` requires, or `auth(...)` grants
+#[derive(Clone, Debug, PartialEq)]
+pub enum Authorization {
+    /// `E, F`: all of them, also a single entitlement
+    Conjunction(Vec<NominalType>),
+    /// `E | F`: one of them
+    Disjunction(Vec<NominalType>),
+    /// `mapping M`: the entitlements mapped from those of the accessing reference
+    Mapping(NominalType),
+}
+
+impl Authorization {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Conjunction(entitlements) | Self::Disjunction(entitlements) => {
+                Span::between(entitlements[0].span(), entitlements[entitlements.len() - 1].span())
+            },
+            Self::Mapping(mapping) => mapping.span(),
+        }
+    }
+}
+
+impl fmt::Display for Authorization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (entitlements, separator) = match self {
+            Self::Conjunction(entitlements) => (entitlements, ", "),
+            Self::Disjunction(entitlements) => (entitlements, " | "),
+            Self::Mapping(mapping) => return write!(f, "mapping {}", mapping.name()),
+        };
+        for (index, entitlement) in entitlements.iter().enumerate() {
+            if index > 0 {
+                write!(f, "{}", separator)?;
+            }
+            write!(f, "{}", entitlement.name())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VariableKind {
+    Constant, // let
+    Variable, // var
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompositeKind {
+    Structure,
+    Resource,
+    Contract,
+    Event,
+    Enum,
+}
+
+impl CompositeKind {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::Structure => "struct",
+            Self::Resource => "resource",
+            Self::Contract => "contract",
+            Self::Event => "event",
+            Self::Enum => "enum",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportDeclaration {
+    pub identifiers: Vec<Identifier>,
+    pub location: ImportLocation,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImportLocation {
+    Address(String),        // import A from 0x01
+    String(String),         // import "A"
+    Identifier(Identifier), // import A from B
+}
+
+/// `struct`, `resource`, `contract`, `event` and `enum` declarations
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompositeDeclaration {
+    pub access: Access,
+    pub kind: CompositeKind,
+    pub identifier: Identifier,
+    /// for enums, the raw type
+    pub conformances: Vec<NominalType>,
+    /// for events, the event parameters
+    pub parameters: Vec<Parameter>,
+    pub members: Vec<Declaration>,
+    pub span: Span,
+}
+
+impl CompositeDeclaration {
+    pub fn raw_type(&self) -> Option<&NominalType> {
+        if self.kind == CompositeKind::Enum {
+            self.conformances.first()
+        } else {
+            None
+        }
+    }
+}
+
+/// `struct interface`, `resource interface` and `contract interface` declarations
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterfaceDeclaration {
+    pub access: Access,
+    pub kind: CompositeKind,
+    pub identifier: Identifier,
+    pub conformances: Vec<NominalType>,
+    pub members: Vec<Declaration>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldDeclaration {
+    pub access: Access,
+    pub variable_kind: VariableKind,
+    pub identifier: Identifier,
+    pub type_annotation: TypeAnnotation,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnumCaseDeclaration {
+    pub access: Access,
+    pub identifier: Identifier,
+    pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Purity {
+    Impure,
+    View, // view fun
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionDeclaration {
+    pub access: Access,
+    pub purity: Purity,
+    pub identifier: Identifier,
+    pub parameters: Vec<Parameter>,
+    pub return_type: Option<TypeAnnotation>,
+    pub pre_conditions: Vec<Condition>,
+    pub post_conditions: Vec<Condition>,
+    /// interface functions may omit the body
+    pub body: Option<Block>,
+    pub span: Span,
+}
+
+/// `test: "message"` in a `pre` or `post` block
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    pub test: Expression,
+    pub message: Option<Expression>,
+    pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecialFunctionKind {
+    Initializer, // init
+    Destructor,  // destroy
+    Prepare,     // prepare
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpecialFunctionDeclaration {
+    pub kind: SpecialFunctionKind,
+    pub function: FunctionDeclaration,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    /// argument label, `_` when arguments are passed without label
+    pub label: Option<Identifier>,
+    pub identifier: Identifier,
+    pub type_annotation: TypeAnnotation,
+    /// `= value`, only for parameters of `ResourceDestroyed` events
+    pub default: Option<Expression>,
+    pub span: Span,
+}
+
+impl Parameter {
+    /// label callers must use, `None` for `_`
+    pub fn argument_label(&self) -> Option<&str> {
+        match &self.label {
+            Some(label) if label.name == "_" => None,
+            Some(label) => Some(&label.name),
+            None => Some(&self.identifier.name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransactionDeclaration {
+    pub parameters: Vec<Parameter>,
+    pub fields: Vec<FieldDeclaration>,
+    pub prepare: Option<SpecialFunctionDeclaration>,
+    pub pre_conditions: Vec<Condition>,
+    pub execute: Option<Block>,
+    pub post_conditions: Vec<Condition>,
+    pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transfer {
+    Copy,      // =
+    Move,      // <-
+    MoveForce, // <-!
+}
+
+impl Transfer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Copy => "=",
+            Self::Move => "<-",
+            Self::MoveForce => "<-!",
+        }
+    }
+}
+
+/// `entitlement E`
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntitlementDeclaration {
+    pub access: Access,
+    pub identifier: Identifier,
+    pub span: Span,
+}
+
+/// `entitlement mapping M { E -> F include N }`
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntitlementMappingDeclaration {
+    pub access: Access,
+    pub identifier: Identifier,
+    pub relations: Vec<EntitlementRelation>,
+    /// mappings whose relations are included
+    pub includes: Vec<NominalType>,
+    pub span: Span,
+}
+
+/// `E -> F`
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntitlementRelation {
+    pub input: NominalType,
+    pub output: NominalType,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariableDeclaration {
+    pub access: Access,
+    pub kind: VariableKind,
+    pub identifier: Identifier,
+    pub type_annotation: Option<TypeAnnotation>,
+    pub transfer: Transfer,
+    pub value: Expression,
+    /// `let old <- self.vaults[id] <- vault`: the value moved into `value` after it is moved out
+    pub second: Option<(Transfer, Box<Expression>)>,
+    pub span: Span,
+}
+
+/// a type, optionally prefixed with `@` for resources
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeAnnotation {
+    pub is_resource: bool,
+    pub ty: Type,
+    pub span: Span,
+}
+
+/// `A` or `A.B.C`
+#[derive(Clone, Debug, PartialEq)]
+pub struct NominalType {
+    pub identifier: Identifier,
+    pub nested_identifiers: Vec<Identifier>,
+}
+
+impl NominalType {
+    pub fn span(&self) -> Span {
+        match self.nested_identifiers.last() {
+            Some(last) => Span::between(self.identifier.span, last.span),
+            None => self.identifier.span,
+        }
+    }
+
+    /// dotted name, e.g. `FungibleToken.Vault`
+    pub fn name(&self) -> String {
+        let mut name = self.identifier.name.clone();
+        for nested in self.nested_identifiers.iter() {
+            name.push('.');
+            name.push_str(&nested.name);
+        }
+        name
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Nominal(NominalType),
+    Optional {
+        ty: Box<Type>,
+        span: Span,
+    },
+    /// `[T]`
+    VariableSized {
+        ty: Box<Type>,
+        span: Span,
+    },
+    /// `[T; N]`
+    ConstantSized {
+        ty: Box<Type>,
+        size: u64,
+        span: Span,
+    },
+    /// `{K: V}`
+    Dictionary {
+        key: Box<Type>,
+        value: Box<Type>,
+        span: Span,
+    },
+    /// `((P1, P2): R)`
+    Function {
+        parameters: Vec<TypeAnnotation>,
+        return_type: Box<TypeAnnotation>,
+        span: Span,
+    },
+    /// `&T`, `auth &T` or `auth(E) &T`
+    Reference {
+        authorized: bool,
+        /// the entitlements of `auth(...)`
+        entitlements: Option<Authorization>,
+        ty: Box<Type>,
+        span: Span,
+    },
+    /// `T{I1, I2}` or `{I1, I2}`
+    Restricted {
+        ty: Option<Box<Type>>,
+        restrictions: Vec<NominalType>,
+        span: Span,
+    },
+    /// `T<A, B>`
+    Instantiation {
+        ty: Box<Type>,
+        type_arguments: Vec<TypeAnnotation>,
+        span: Span,
+    },
+}
+
+impl Type {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Nominal(nominal) => nominal.span(),
+            Self::Optional { span, .. } |
+            Self::VariableSized { span, .. } |
+            Self::ConstantSized { span, .. } |
+            Self::Dictionary { span, .. } |
+            Self::Function { span, .. } |
+            Self::Reference { span, .. } |
+            Self::Restricted { span, .. } |
+            Self::Instantiation { span, .. } => *span,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Statement>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Statement {
+    Expression(Expression),
+    Return {
+        expression: Option<Expression>,
+        span: Span,
+    },
+    Break {
+        span: Span,
+    },
+    Continue {
+        span: Span,
+    },
+    Variable(VariableDeclaration),
+    /// `a = b`, `a <- b` or `a <-! b`
+    Assignment {
+        target: Expression,
+        transfer: Transfer,
+        value: Expression,
+        span: Span,
+    },
+    /// `a <-> b`
+    Swap {
+        left: Expression,
+        right: Expression,
+        span: Span,
+    },
+    If(IfStatement),
+    While {
+        test: Expression,
+        block: Block,
+        span: Span,
+    },
+    /// `for x in values {}` or `for i, x in values {}`
+    For {
+        index: Option<Identifier>,
+        identifier: Identifier,
+        value: Expression,
+        block: Block,
+        span: Span,
+    },
+    Switch {
+        expression: Expression,
+        cases: Vec<SwitchCase>,
+        span: Span,
+    },
+    /// `emit E()`
+    Emit {
+        invocation: Expression,
+        span: Span,
+    },
+    /// `destroy r`
+    Destroy {
+        expression: Expression,
+        span: Span,
+    },
+    /// placeholder for a statement that failed to parse
+    Error(Span),
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Expression(e) => e.span(),
+            Self::Variable(d) => d.span,
+            Self::If(s) => s.span,
+            Self::Error(span) => *span,
+            Self::Return { span, .. } |
+            Self::Break { span } |
+            Self::Continue { span } |
+            Self::Assignment { span, .. } |
+            Self::Swap { span, .. } |
+            Self::While { span, .. } |
+            Self::For { span, .. } |
+            Self::Switch { span, .. } |
+            Self::Emit { span, .. } |
+            Self::Destroy { span, .. } => *span,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IfStatement {
+    pub test: IfTest,
+    pub then: Block,
+    pub else_: Option<ElseBranch>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum IfTest {
+    Expression(Expression),
+    /// optional binding, `if let x = optional {}`
+    Binding(Box<VariableDeclaration>),
+}
+
+/// `case a, b: statements`, or `default: statements` when there are no expressions
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwitchCase {
+    pub expressions: Vec<Expression>,
+    pub statements: Vec<Statement>,
+    pub span: Span,
+}
+
+impl SwitchCase {
+    pub fn is_default(&self) -> bool {
+        self.expressions.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ElseBranch {
+    Block(Block),
+    If(Box<IfStatement>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOperation {
+    Minus, // -
+    Not,   // !
+    Move,  // <-
+}
+
+impl UnaryOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Minus => "-",
+            Self::Not => "!",
+            Self::Move => "<-",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOperation {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    NilCoalescing,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseAnd,
+    BitwiseShiftLeft,
+    BitwiseShiftRight,
+    Plus,
+    Minus,
+    Mul,
+    Div,
+    Mod,
+}
+
+impl BinaryOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Or => "||",
+            Self::And => "&&",
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::Less => "<",
+            Self::LessEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterEqual => ">=",
+            Self::NilCoalescing => "??",
+            Self::BitwiseOr => "|",
+            Self::BitwiseXor => "^",
+            Self::BitwiseAnd => "&",
+            Self::BitwiseShiftLeft => "<<",
+            Self::BitwiseShiftRight => ">>",
+            Self::Plus => "+",
+            Self::Minus => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Mod => "%",
+        }
+    }
+
+    /// binding power, higher binds tighter
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Or => PRECEDENCE_LOGICAL_OR,
+            Self::And => PRECEDENCE_LOGICAL_AND,
+            Self::Equal | Self::NotEqual |
+            Self::Less | Self::LessEqual |
+            Self::Greater | Self::GreaterEqual => PRECEDENCE_COMPARISON,
+            Self::NilCoalescing => PRECEDENCE_NIL_COALESCING,
+            Self::BitwiseOr => PRECEDENCE_BITWISE_OR,
+            Self::BitwiseXor => PRECEDENCE_BITWISE_XOR,
+            Self::BitwiseAnd => PRECEDENCE_BITWISE_AND,
+            Self::BitwiseShiftLeft | Self::BitwiseShiftRight => PRECEDENCE_BITWISE_SHIFT,
+            Self::Plus | Self::Minus => PRECEDENCE_ADDITION,
+            Self::Mul | Self::Div | Self::Mod => PRECEDENCE_MULTIPLICATION,
+        }
+    }
+
+    /// `??` is the only right associative binary operation
+    pub fn is_right_associative(&self) -> bool {
+        *self == Self::NilCoalescing
+    }
+}
+
+pub const PRECEDENCE_TERNARY: u8 = 10;
+pub const PRECEDENCE_LOGICAL_OR: u8 = 20;
+pub const PRECEDENCE_LOGICAL_AND: u8 = 30;
+pub const PRECEDENCE_COMPARISON: u8 = 40;
+pub const PRECEDENCE_NIL_COALESCING: u8 = 50;
+pub const PRECEDENCE_BITWISE_OR: u8 = 60;
+pub const PRECEDENCE_BITWISE_XOR: u8 = 70;
+pub const PRECEDENCE_BITWISE_AND: u8 = 80;
+pub const PRECEDENCE_BITWISE_SHIFT: u8 = 90;
+pub const PRECEDENCE_ADDITION: u8 = 100;
+pub const PRECEDENCE_MULTIPLICATION: u8 = 110;
+pub const PRECEDENCE_CASTING: u8 = 120;
+pub const PRECEDENCE_UNARY_PREFIX: u8 = 130;
+pub const PRECEDENCE_UNARY_POSTFIX: u8 = 140;
+pub const PRECEDENCE_ACCESS: u8 = 150;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CastingOperation {
+    Static,    // as
+    Failable,  // as?
+    Force,     // as!
+}
+
+impl CastingOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Static => "as",
+            Self::Failable => "as?",
+            Self::Force => "as!",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Argument {
+    pub label: Option<Identifier>,
+    pub expression: Expression,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    Nil {
+        span: Span,
+    },
+    Bool {
+        value: bool,
+        span: Span,
+    },
+    /// decimal, `0x` or `0b` integer literal as written
+    Integer {
+        literal: String,
+        span: Span,
+    },
+    /// `1.5`, as written
+    FixedPoint {
+        literal: String,
+        span: Span,
+    },
+    String {
+        value: String,
+        span: Span,
+    },
+    Array {
+        values: Vec<Expression>,
+        span: Span,
+    },
+    Dictionary {
+        entries: Vec<(Expression, Expression)>,
+        span: Span,
+    },
+    Identifier(Identifier),
+    /// `/storage/vault`
+    Path {
+        domain: Identifier,
+        identifier: Identifier,
+        span: Span,
+    },
+    /// `a.b` or `a?.b`
+    Member {
+        expression: Box<Expression>,
+        optional: bool,
+        identifier: Identifier,
+        span: Span,
+    },
+    Index {
+        expression: Box<Expression>,
+        index: Box<Expression>,
+        span: Span,
+    },
+    Invocation {
+        invoked: Box<Expression>,
+        type_arguments: Vec<TypeAnnotation>,
+        arguments: Vec<Argument>,
+        span: Span,
+    },
+    Unary {
+        operation: UnaryOperation,
+        expression: Box<Expression>,
+        span: Span,
+    },
+    Binary {
+        operation: BinaryOperation,
+        left: Box<Expression>,
+        right: Box<Expression>,
+        span: Span,
+    },
+    /// `test ? then : else_`
+    Conditional {
+        test: Box<Expression>,
+        then: Box<Expression>,
+        else_: Box<Expression>,
+        span: Span,
+    },
+    Casting {
+        expression: Box<Expression>,
+        operation: CastingOperation,
+        type_annotation: Box<TypeAnnotation>,
+        span: Span,
+    },
+    /// `create R()`
+    Create {
+        invocation: Box<Expression>,
+        span: Span,
+    },
+    /// `&x as &T`
+    Reference {
+        expression: Box<Expression>,
+        ty: Option<Box<Type>>,
+        span: Span,
+    },
+    /// `x!`
+    Force {
+        expression: Box<Expression>,
+        span: Span,
+    },
+}
+
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Identifier(identifier) => identifier.span,
+            Self::Nil { span } |
+            Self::Bool { span, .. } |
+            Self::Integer { span, .. } |
+            Self::FixedPoint { span, .. } |
+            Self::String { span, .. } |
+            Self::Array { span, .. } |
+            Self::Dictionary { span, .. } |
+            Self::Path { span, .. } |
+            Self::Member { span, .. } |
+            Self::Index { span, .. } |
+            Self::Invocation { span, .. } |
+            Self::Unary { span, .. } |
+            Self::Binary { span, .. } |
+            Self::Conditional { span, .. } |
+            Self::Casting { span, .. } |
+            Self::Create { span, .. } |
+            Self::Reference { span, .. } |
+            Self::Force { span, .. } => *span,
+        }
+    }
+}
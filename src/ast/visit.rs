@@ -0,0 +1,487 @@
+use super::{
+    Access, Argument, Authorization, Block, CompositeDeclaration, Condition, Declaration, ElseBranch, EntitlementDeclaration,
+    EntitlementMappingDeclaration, EnumCaseDeclaration, Expression,
+    FieldDeclaration, FunctionDeclaration, Identifier, IfStatement, IfTest, ImportDeclaration, ImportLocation,
+    InterfaceDeclaration, NominalType, Parameter, Program, SpecialFunctionDeclaration, Statement, SwitchCase,
+    TransactionDeclaration, Type, TypeAnnotation, VariableDeclaration,
+};
+
+/// read-only traversal of the AST.
+/// every method defaults to walking the node's children, override it to act on a node
+/// and call the matching `walk_` function to keep descending
+pub trait Visitor {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program)
+    }
+
+    fn visit_declaration(&mut self, declaration: &Declaration) {
+        walk_declaration(self, declaration)
+    }
+
+    fn visit_import_declaration(&mut self, declaration: &ImportDeclaration) {
+        walk_import_declaration(self, declaration)
+    }
+
+    fn visit_composite_declaration(&mut self, declaration: &CompositeDeclaration) {
+        walk_composite_declaration(self, declaration)
+    }
+
+    fn visit_interface_declaration(&mut self, declaration: &InterfaceDeclaration) {
+        walk_interface_declaration(self, declaration)
+    }
+
+    fn visit_field_declaration(&mut self, declaration: &FieldDeclaration) {
+        walk_field_declaration(self, declaration)
+    }
+
+    fn visit_function_declaration(&mut self, declaration: &FunctionDeclaration) {
+        walk_function_declaration(self, declaration)
+    }
+
+    fn visit_special_function_declaration(&mut self, declaration: &SpecialFunctionDeclaration) {
+        walk_special_function_declaration(self, declaration)
+    }
+
+    fn visit_enum_case_declaration(&mut self, declaration: &EnumCaseDeclaration) {
+        walk_enum_case_declaration(self, declaration)
+    }
+
+    fn visit_variable_declaration(&mut self, declaration: &VariableDeclaration) {
+        walk_variable_declaration(self, declaration)
+    }
+
+    fn visit_transaction_declaration(&mut self, declaration: &TransactionDeclaration) {
+        walk_transaction_declaration(self, declaration)
+    }
+
+    fn visit_entitlement_declaration(&mut self, declaration: &EntitlementDeclaration) {
+        walk_entitlement_declaration(self, declaration)
+    }
+
+    fn visit_entitlement_mapping_declaration(&mut self, declaration: &EntitlementMappingDeclaration) {
+        walk_entitlement_mapping_declaration(self, declaration)
+    }
+
+    fn visit_access(&mut self, access: &Access) {
+        walk_access(self, access)
+    }
+
+    fn visit_authorization(&mut self, authorization: &Authorization) {
+        walk_authorization(self, authorization)
+    }
+
+    fn visit_parameter(&mut self, parameter: &Parameter) {
+        walk_parameter(self, parameter)
+    }
+
+    fn visit_condition(&mut self, condition: &Condition) {
+        walk_condition(self, condition)
+    }
+
+    fn visit_type_annotation(&mut self, type_annotation: &TypeAnnotation) {
+        walk_type_annotation(self, type_annotation)
+    }
+
+    fn visit_type(&mut self, ty: &Type) {
+        walk_type(self, ty)
+    }
+
+    fn visit_nominal_type(&mut self, nominal: &NominalType) {
+        walk_nominal_type(self, nominal)
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_if_statement(&mut self, statement: &IfStatement) {
+        walk_if_statement(self, statement)
+    }
+
+    fn visit_switch_case(&mut self, case: &SwitchCase) {
+        walk_switch_case(self, case)
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_argument(&mut self, argument: &Argument) {
+        walk_argument(self, argument)
+    }
+
+    fn visit_identifier(&mut self, _identifier: &Identifier) {}
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    for declaration in program.declarations.iter() {
+        visitor.visit_declaration(declaration);
+    }
+}
+
+pub fn walk_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &Declaration) {
+    match declaration {
+        Declaration::Import(d) => visitor.visit_import_declaration(d),
+        Declaration::Composite(d) => visitor.visit_composite_declaration(d),
+        Declaration::Interface(d) => visitor.visit_interface_declaration(d),
+        Declaration::Field(d) => visitor.visit_field_declaration(d),
+        Declaration::Function(d) => visitor.visit_function_declaration(d),
+        Declaration::SpecialFunction(d) => visitor.visit_special_function_declaration(d),
+        Declaration::EnumCase(d) => visitor.visit_enum_case_declaration(d),
+        Declaration::Variable(d) => visitor.visit_variable_declaration(d),
+        Declaration::Transaction(d) => visitor.visit_transaction_declaration(d),
+        Declaration::Entitlement(d) => visitor.visit_entitlement_declaration(d),
+        Declaration::EntitlementMapping(d) => visitor.visit_entitlement_mapping_declaration(d),
+        Declaration::Error(_) => (),
+    }
+}
+
+pub fn walk_import_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &ImportDeclaration) {
+    for identifier in declaration.identifiers.iter() {
+        visitor.visit_identifier(identifier);
+    }
+    if let ImportLocation::Identifier(identifier) = &declaration.location {
+        visitor.visit_identifier(identifier);
+    }
+}
+
+pub fn walk_composite_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &CompositeDeclaration) {
+    visitor.visit_access(&declaration.access);
+    visitor.visit_identifier(&declaration.identifier);
+    for conformance in declaration.conformances.iter() {
+        visitor.visit_nominal_type(conformance);
+    }
+    for parameter in declaration.parameters.iter() {
+        visitor.visit_parameter(parameter);
+    }
+    for member in declaration.members.iter() {
+        visitor.visit_declaration(member);
+    }
+}
+
+pub fn walk_interface_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &InterfaceDeclaration) {
+    visitor.visit_access(&declaration.access);
+    visitor.visit_identifier(&declaration.identifier);
+    for conformance in declaration.conformances.iter() {
+        visitor.visit_nominal_type(conformance);
+    }
+    for member in declaration.members.iter() {
+        visitor.visit_declaration(member);
+    }
+}
+
+pub fn walk_field_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &FieldDeclaration) {
+    visitor.visit_access(&declaration.access);
+    visitor.visit_identifier(&declaration.identifier);
+    visitor.visit_type_annotation(&declaration.type_annotation);
+}
+
+pub fn walk_function_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &FunctionDeclaration) {
+    visitor.visit_access(&declaration.access);
+    visitor.visit_identifier(&declaration.identifier);
+    for parameter in declaration.parameters.iter() {
+        visitor.visit_parameter(parameter);
+    }
+    if let Some(return_type) = &declaration.return_type {
+        visitor.visit_type_annotation(return_type);
+    }
+    for condition in declaration.pre_conditions.iter() {
+        visitor.visit_condition(condition);
+    }
+    for condition in declaration.post_conditions.iter() {
+        visitor.visit_condition(condition);
+    }
+    if let Some(body) = &declaration.body {
+        visitor.visit_block(body);
+    }
+}
+
+pub fn walk_special_function_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &SpecialFunctionDeclaration) {
+    visitor.visit_function_declaration(&declaration.function);
+}
+
+pub fn walk_enum_case_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &EnumCaseDeclaration) {
+    visitor.visit_access(&declaration.access);
+    visitor.visit_identifier(&declaration.identifier);
+}
+
+pub fn walk_variable_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &VariableDeclaration) {
+    visitor.visit_access(&declaration.access);
+    visitor.visit_identifier(&declaration.identifier);
+    if let Some(type_annotation) = &declaration.type_annotation {
+        visitor.visit_type_annotation(type_annotation);
+    }
+    visitor.visit_expression(&declaration.value);
+    if let Some((_, value)) = &declaration.second {
+        visitor.visit_expression(value);
+    }
+}
+
+pub fn walk_transaction_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &TransactionDeclaration) {
+    for parameter in declaration.parameters.iter() {
+        visitor.visit_parameter(parameter);
+    }
+    for field in declaration.fields.iter() {
+        visitor.visit_field_declaration(field);
+    }
+    if let Some(prepare) = &declaration.prepare {
+        visitor.visit_special_function_declaration(prepare);
+    }
+    for condition in declaration.pre_conditions.iter() {
+        visitor.visit_condition(condition);
+    }
+    if let Some(execute) = &declaration.execute {
+        visitor.visit_block(execute);
+    }
+    for condition in declaration.post_conditions.iter() {
+        visitor.visit_condition(condition);
+    }
+}
+
+pub fn walk_entitlement_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &EntitlementDeclaration) {
+    visitor.visit_access(&declaration.access);
+    visitor.visit_identifier(&declaration.identifier);
+}
+
+pub fn walk_entitlement_mapping_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &EntitlementMappingDeclaration) {
+    visitor.visit_access(&declaration.access);
+    visitor.visit_identifier(&declaration.identifier);
+    for relation in declaration.relations.iter() {
+        visitor.visit_nominal_type(&relation.input);
+        visitor.visit_nominal_type(&relation.output);
+    }
+    for include in declaration.includes.iter() {
+        visitor.visit_nominal_type(include);
+    }
+}
+
+pub fn walk_access<V: Visitor + ?Sized>(visitor: &mut V, access: &Access) {
+    if let Access::Entitlements(authorization) = access {
+        visitor.visit_authorization(authorization);
+    }
+}
+
+pub fn walk_authorization<V: Visitor + ?Sized>(visitor: &mut V, authorization: &Authorization) {
+    match authorization {
+        Authorization::Conjunction(entitlements) | Authorization::Disjunction(entitlements) => {
+            for entitlement in entitlements.iter() {
+                visitor.visit_nominal_type(entitlement);
+            }
+        },
+        Authorization::Mapping(mapping) => visitor.visit_nominal_type(mapping),
+    }
+}
+
+pub fn walk_parameter<V: Visitor + ?Sized>(visitor: &mut V, parameter: &Parameter) {
+    if let Some(label) = &parameter.label {
+        visitor.visit_identifier(label);
+    }
+    visitor.visit_identifier(&parameter.identifier);
+    visitor.visit_type_annotation(&parameter.type_annotation);
+    if let Some(default) = &parameter.default {
+        visitor.visit_expression(default);
+    }
+}
+
+pub fn walk_condition<V: Visitor + ?Sized>(visitor: &mut V, condition: &Condition) {
+    visitor.visit_expression(&condition.test);
+    if let Some(message) = &condition.message {
+        visitor.visit_expression(message);
+    }
+}
+
+pub fn walk_type_annotation<V: Visitor + ?Sized>(visitor: &mut V, type_annotation: &TypeAnnotation) {
+    visitor.visit_type(&type_annotation.ty);
+}
+
+pub fn walk_type<V: Visitor + ?Sized>(visitor: &mut V, ty: &Type) {
+    match ty {
+        Type::Nominal(nominal) => visitor.visit_nominal_type(nominal),
+        Type::Optional { ty, .. } |
+        Type::VariableSized { ty, .. } |
+        Type::ConstantSized { ty, .. } => visitor.visit_type(ty),
+        Type::Reference { entitlements, ty, .. } => {
+            if let Some(entitlements) = entitlements {
+                visitor.visit_authorization(entitlements);
+            }
+            visitor.visit_type(ty);
+        },
+        Type::Dictionary { key, value, .. } => {
+            visitor.visit_type(key);
+            visitor.visit_type(value);
+        },
+        Type::Function { parameters, return_type, .. } => {
+            for parameter in parameters.iter() {
+                visitor.visit_type_annotation(parameter);
+            }
+            visitor.visit_type_annotation(return_type);
+        },
+        Type::Restricted { ty, restrictions, .. } => {
+            if let Some(ty) = ty {
+                visitor.visit_type(ty);
+            }
+            for restriction in restrictions.iter() {
+                visitor.visit_nominal_type(restriction);
+            }
+        },
+        Type::Instantiation { ty, type_arguments, .. } => {
+            visitor.visit_type(ty);
+            for argument in type_arguments.iter() {
+                visitor.visit_type_annotation(argument);
+            }
+        },
+    }
+}
+
+pub fn walk_nominal_type<V: Visitor + ?Sized>(visitor: &mut V, nominal: &NominalType) {
+    visitor.visit_identifier(&nominal.identifier);
+    for nested in nominal.nested_identifiers.iter() {
+        visitor.visit_identifier(nested);
+    }
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, block: &Block) {
+    for statement in block.statements.iter() {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Expression(expression) => visitor.visit_expression(expression),
+        Statement::Return { expression, .. } => {
+            if let Some(expression) = expression {
+                visitor.visit_expression(expression);
+            }
+        },
+        Statement::Break { .. } | Statement::Continue { .. } | Statement::Error(_) => (),
+        Statement::Variable(declaration) => visitor.visit_variable_declaration(declaration),
+        Statement::Assignment { target, value, .. } => {
+            visitor.visit_expression(target);
+            visitor.visit_expression(value);
+        },
+        Statement::Swap { left, right, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        },
+        Statement::If(statement) => visitor.visit_if_statement(statement),
+        Statement::While { test, block, .. } => {
+            visitor.visit_expression(test);
+            visitor.visit_block(block);
+        },
+        Statement::For { index, identifier, value, block, .. } => {
+            if let Some(index) = index {
+                visitor.visit_identifier(index);
+            }
+            visitor.visit_identifier(identifier);
+            visitor.visit_expression(value);
+            visitor.visit_block(block);
+        },
+        Statement::Switch { expression, cases, .. } => {
+            visitor.visit_expression(expression);
+            for case in cases.iter() {
+                visitor.visit_switch_case(case);
+            }
+        },
+        Statement::Emit { invocation, .. } => visitor.visit_expression(invocation),
+        Statement::Destroy { expression, .. } => visitor.visit_expression(expression),
+    }
+}
+
+pub fn walk_if_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &IfStatement) {
+    match &statement.test {
+        IfTest::Expression(test) => visitor.visit_expression(test),
+        IfTest::Binding(binding) => visitor.visit_variable_declaration(binding),
+    }
+    visitor.visit_block(&statement.then);
+    match &statement.else_ {
+        Some(ElseBranch::Block(block)) => visitor.visit_block(block),
+        Some(ElseBranch::If(else_if)) => visitor.visit_if_statement(else_if),
+        None => (),
+    }
+}
+
+pub fn walk_switch_case<V: Visitor + ?Sized>(visitor: &mut V, case: &SwitchCase) {
+    for expression in case.expressions.iter() {
+        visitor.visit_expression(expression);
+    }
+    for statement in case.statements.iter() {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &Expression) {
+    match expression {
+        Expression::Nil { .. } |
+        Expression::Bool { .. } |
+        Expression::Integer { .. } |
+        Expression::FixedPoint { .. } |
+        Expression::String { .. } => (),
+        Expression::Array { values, .. } => {
+            for value in values.iter() {
+                visitor.visit_expression(value);
+            }
+        },
+        Expression::Dictionary { entries, .. } => {
+            for (key, value) in entries.iter() {
+                visitor.visit_expression(key);
+                visitor.visit_expression(value);
+            }
+        },
+        Expression::Identifier(identifier) => visitor.visit_identifier(identifier),
+        Expression::Path { domain, identifier, .. } => {
+            visitor.visit_identifier(domain);
+            visitor.visit_identifier(identifier);
+        },
+        Expression::Member { expression, identifier, .. } => {
+            visitor.visit_expression(expression);
+            visitor.visit_identifier(identifier);
+        },
+        Expression::Index { expression, index, .. } => {
+            visitor.visit_expression(expression);
+            visitor.visit_expression(index);
+        },
+        Expression::Invocation { invoked, type_arguments, arguments, .. } => {
+            visitor.visit_expression(invoked);
+            for argument in type_arguments.iter() {
+                visitor.visit_type_annotation(argument);
+            }
+            for argument in arguments.iter() {
+                visitor.visit_argument(argument);
+            }
+        },
+        Expression::Unary { expression, .. } |
+        Expression::Force { expression, .. } => visitor.visit_expression(expression),
+        Expression::Create { invocation, .. } => visitor.visit_expression(invocation),
+        Expression::Binary { left, right, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        },
+        Expression::Conditional { test, then, else_, .. } => {
+            visitor.visit_expression(test);
+            visitor.visit_expression(then);
+            visitor.visit_expression(else_);
+        },
+        Expression::Casting { expression, type_annotation, .. } => {
+            visitor.visit_expression(expression);
+            visitor.visit_type_annotation(type_annotation);
+        },
+        Expression::Reference { expression, ty, .. } => {
+            visitor.visit_expression(expression);
+            if let Some(ty) = ty {
+                visitor.visit_type(ty);
+            }
+        },
+    }
+}
+
+pub fn walk_argument<V: Visitor + ?Sized>(visitor: &mut V, argument: &Argument) {
+    if let Some(label) = &argument.label {
+        visitor.visit_identifier(label);
+    }
+    visitor.visit_expression(&argument.expression);
+}
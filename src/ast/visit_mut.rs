@@ -0,0 +1,487 @@
+use super::{
+    Access, Argument, Authorization, Block, CompositeDeclaration, Condition, Declaration, ElseBranch, EntitlementDeclaration,
+    EntitlementMappingDeclaration, EnumCaseDeclaration, Expression,
+    FieldDeclaration, FunctionDeclaration, Identifier, IfStatement, IfTest, ImportDeclaration, ImportLocation,
+    InterfaceDeclaration, NominalType, Parameter, Program, SpecialFunctionDeclaration, Statement, SwitchCase,
+    TransactionDeclaration, Type, TypeAnnotation, VariableDeclaration,
+};
+
+/// in-place traversal of the AST, for transformations that keep the tree's shape.
+/// every method defaults to walking the node's children, override it to act on a node
+/// and call the matching `walk_` function to keep descending
+pub trait VisitorMut {
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        walk_program(self, program)
+    }
+
+    fn visit_declaration_mut(&mut self, declaration: &mut Declaration) {
+        walk_declaration(self, declaration)
+    }
+
+    fn visit_import_declaration_mut(&mut self, declaration: &mut ImportDeclaration) {
+        walk_import_declaration(self, declaration)
+    }
+
+    fn visit_composite_declaration_mut(&mut self, declaration: &mut CompositeDeclaration) {
+        walk_composite_declaration(self, declaration)
+    }
+
+    fn visit_interface_declaration_mut(&mut self, declaration: &mut InterfaceDeclaration) {
+        walk_interface_declaration(self, declaration)
+    }
+
+    fn visit_field_declaration_mut(&mut self, declaration: &mut FieldDeclaration) {
+        walk_field_declaration(self, declaration)
+    }
+
+    fn visit_function_declaration_mut(&mut self, declaration: &mut FunctionDeclaration) {
+        walk_function_declaration(self, declaration)
+    }
+
+    fn visit_special_function_declaration_mut(&mut self, declaration: &mut SpecialFunctionDeclaration) {
+        walk_special_function_declaration(self, declaration)
+    }
+
+    fn visit_enum_case_declaration_mut(&mut self, declaration: &mut EnumCaseDeclaration) {
+        walk_enum_case_declaration(self, declaration)
+    }
+
+    fn visit_variable_declaration_mut(&mut self, declaration: &mut VariableDeclaration) {
+        walk_variable_declaration(self, declaration)
+    }
+
+    fn visit_transaction_declaration_mut(&mut self, declaration: &mut TransactionDeclaration) {
+        walk_transaction_declaration(self, declaration)
+    }
+
+    fn visit_entitlement_declaration_mut(&mut self, declaration: &mut EntitlementDeclaration) {
+        walk_entitlement_declaration(self, declaration)
+    }
+
+    fn visit_entitlement_mapping_declaration_mut(&mut self, declaration: &mut EntitlementMappingDeclaration) {
+        walk_entitlement_mapping_declaration(self, declaration)
+    }
+
+    fn visit_access_mut(&mut self, access: &mut Access) {
+        walk_access(self, access)
+    }
+
+    fn visit_authorization_mut(&mut self, authorization: &mut Authorization) {
+        walk_authorization(self, authorization)
+    }
+
+    fn visit_parameter_mut(&mut self, parameter: &mut Parameter) {
+        walk_parameter(self, parameter)
+    }
+
+    fn visit_condition_mut(&mut self, condition: &mut Condition) {
+        walk_condition(self, condition)
+    }
+
+    fn visit_type_annotation_mut(&mut self, type_annotation: &mut TypeAnnotation) {
+        walk_type_annotation(self, type_annotation)
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        walk_type(self, ty)
+    }
+
+    fn visit_nominal_type_mut(&mut self, nominal: &mut NominalType) {
+        walk_nominal_type(self, nominal)
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_statement_mut(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_if_statement_mut(&mut self, statement: &mut IfStatement) {
+        walk_if_statement(self, statement)
+    }
+
+    fn visit_switch_case_mut(&mut self, case: &mut SwitchCase) {
+        walk_switch_case(self, case)
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_argument_mut(&mut self, argument: &mut Argument) {
+        walk_argument(self, argument)
+    }
+
+    fn visit_identifier_mut(&mut self, _identifier: &mut Identifier) {}
+}
+
+pub fn walk_program<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    for declaration in program.declarations.iter_mut() {
+        visitor.visit_declaration_mut(declaration);
+    }
+}
+
+pub fn walk_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut Declaration) {
+    match declaration {
+        Declaration::Import(d) => visitor.visit_import_declaration_mut(d),
+        Declaration::Composite(d) => visitor.visit_composite_declaration_mut(d),
+        Declaration::Interface(d) => visitor.visit_interface_declaration_mut(d),
+        Declaration::Field(d) => visitor.visit_field_declaration_mut(d),
+        Declaration::Function(d) => visitor.visit_function_declaration_mut(d),
+        Declaration::SpecialFunction(d) => visitor.visit_special_function_declaration_mut(d),
+        Declaration::EnumCase(d) => visitor.visit_enum_case_declaration_mut(d),
+        Declaration::Variable(d) => visitor.visit_variable_declaration_mut(d),
+        Declaration::Transaction(d) => visitor.visit_transaction_declaration_mut(d),
+        Declaration::Entitlement(d) => visitor.visit_entitlement_declaration_mut(d),
+        Declaration::EntitlementMapping(d) => visitor.visit_entitlement_mapping_declaration_mut(d),
+        Declaration::Error(_) => (),
+    }
+}
+
+pub fn walk_import_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut ImportDeclaration) {
+    for identifier in declaration.identifiers.iter_mut() {
+        visitor.visit_identifier_mut(identifier);
+    }
+    if let ImportLocation::Identifier(identifier) = &mut declaration.location {
+        visitor.visit_identifier_mut(identifier);
+    }
+}
+
+pub fn walk_composite_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut CompositeDeclaration) {
+    visitor.visit_access_mut(&mut declaration.access);
+    visitor.visit_identifier_mut(&mut declaration.identifier);
+    for conformance in declaration.conformances.iter_mut() {
+        visitor.visit_nominal_type_mut(conformance);
+    }
+    for parameter in declaration.parameters.iter_mut() {
+        visitor.visit_parameter_mut(parameter);
+    }
+    for member in declaration.members.iter_mut() {
+        visitor.visit_declaration_mut(member);
+    }
+}
+
+pub fn walk_interface_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut InterfaceDeclaration) {
+    visitor.visit_access_mut(&mut declaration.access);
+    visitor.visit_identifier_mut(&mut declaration.identifier);
+    for conformance in declaration.conformances.iter_mut() {
+        visitor.visit_nominal_type_mut(conformance);
+    }
+    for member in declaration.members.iter_mut() {
+        visitor.visit_declaration_mut(member);
+    }
+}
+
+pub fn walk_field_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut FieldDeclaration) {
+    visitor.visit_access_mut(&mut declaration.access);
+    visitor.visit_identifier_mut(&mut declaration.identifier);
+    visitor.visit_type_annotation_mut(&mut declaration.type_annotation);
+}
+
+pub fn walk_function_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut FunctionDeclaration) {
+    visitor.visit_access_mut(&mut declaration.access);
+    visitor.visit_identifier_mut(&mut declaration.identifier);
+    for parameter in declaration.parameters.iter_mut() {
+        visitor.visit_parameter_mut(parameter);
+    }
+    if let Some(return_type) = &mut declaration.return_type {
+        visitor.visit_type_annotation_mut(return_type);
+    }
+    for condition in declaration.pre_conditions.iter_mut() {
+        visitor.visit_condition_mut(condition);
+    }
+    for condition in declaration.post_conditions.iter_mut() {
+        visitor.visit_condition_mut(condition);
+    }
+    if let Some(body) = &mut declaration.body {
+        visitor.visit_block_mut(body);
+    }
+}
+
+pub fn walk_special_function_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut SpecialFunctionDeclaration) {
+    visitor.visit_function_declaration_mut(&mut declaration.function);
+}
+
+pub fn walk_enum_case_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut EnumCaseDeclaration) {
+    visitor.visit_access_mut(&mut declaration.access);
+    visitor.visit_identifier_mut(&mut declaration.identifier);
+}
+
+pub fn walk_variable_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut VariableDeclaration) {
+    visitor.visit_access_mut(&mut declaration.access);
+    visitor.visit_identifier_mut(&mut declaration.identifier);
+    if let Some(type_annotation) = &mut declaration.type_annotation {
+        visitor.visit_type_annotation_mut(type_annotation);
+    }
+    visitor.visit_expression_mut(&mut declaration.value);
+    if let Some((_, value)) = &mut declaration.second {
+        visitor.visit_expression_mut(value);
+    }
+}
+
+pub fn walk_transaction_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut TransactionDeclaration) {
+    for parameter in declaration.parameters.iter_mut() {
+        visitor.visit_parameter_mut(parameter);
+    }
+    for field in declaration.fields.iter_mut() {
+        visitor.visit_field_declaration_mut(field);
+    }
+    if let Some(prepare) = &mut declaration.prepare {
+        visitor.visit_special_function_declaration_mut(prepare);
+    }
+    for condition in declaration.pre_conditions.iter_mut() {
+        visitor.visit_condition_mut(condition);
+    }
+    if let Some(execute) = &mut declaration.execute {
+        visitor.visit_block_mut(execute);
+    }
+    for condition in declaration.post_conditions.iter_mut() {
+        visitor.visit_condition_mut(condition);
+    }
+}
+
+pub fn walk_entitlement_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut EntitlementDeclaration) {
+    visitor.visit_access_mut(&mut declaration.access);
+    visitor.visit_identifier_mut(&mut declaration.identifier);
+}
+
+pub fn walk_entitlement_mapping_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut EntitlementMappingDeclaration) {
+    visitor.visit_access_mut(&mut declaration.access);
+    visitor.visit_identifier_mut(&mut declaration.identifier);
+    for relation in declaration.relations.iter_mut() {
+        visitor.visit_nominal_type_mut(&mut relation.input);
+        visitor.visit_nominal_type_mut(&mut relation.output);
+    }
+    for include in declaration.includes.iter_mut() {
+        visitor.visit_nominal_type_mut(include);
+    }
+}
+
+pub fn walk_access<V: VisitorMut + ?Sized>(visitor: &mut V, access: &mut Access) {
+    if let Access::Entitlements(authorization) = access {
+        visitor.visit_authorization_mut(authorization);
+    }
+}
+
+pub fn walk_authorization<V: VisitorMut + ?Sized>(visitor: &mut V, authorization: &mut Authorization) {
+    match authorization {
+        Authorization::Conjunction(entitlements) | Authorization::Disjunction(entitlements) => {
+            for entitlement in entitlements.iter_mut() {
+                visitor.visit_nominal_type_mut(entitlement);
+            }
+        },
+        Authorization::Mapping(mapping) => visitor.visit_nominal_type_mut(mapping),
+    }
+}
+
+pub fn walk_parameter<V: VisitorMut + ?Sized>(visitor: &mut V, parameter: &mut Parameter) {
+    if let Some(label) = &mut parameter.label {
+        visitor.visit_identifier_mut(label);
+    }
+    visitor.visit_identifier_mut(&mut parameter.identifier);
+    visitor.visit_type_annotation_mut(&mut parameter.type_annotation);
+    if let Some(default) = &mut parameter.default {
+        visitor.visit_expression_mut(default);
+    }
+}
+
+pub fn walk_condition<V: VisitorMut + ?Sized>(visitor: &mut V, condition: &mut Condition) {
+    visitor.visit_expression_mut(&mut condition.test);
+    if let Some(message) = &mut condition.message {
+        visitor.visit_expression_mut(message);
+    }
+}
+
+pub fn walk_type_annotation<V: VisitorMut + ?Sized>(visitor: &mut V, type_annotation: &mut TypeAnnotation) {
+    visitor.visit_type_mut(&mut type_annotation.ty);
+}
+
+pub fn walk_type<V: VisitorMut + ?Sized>(visitor: &mut V, ty: &mut Type) {
+    match ty {
+        Type::Nominal(nominal) => visitor.visit_nominal_type_mut(nominal),
+        Type::Optional { ty, .. } |
+        Type::VariableSized { ty, .. } |
+        Type::ConstantSized { ty, .. } => visitor.visit_type_mut(ty),
+        Type::Reference { entitlements, ty, .. } => {
+            if let Some(entitlements) = entitlements {
+                visitor.visit_authorization_mut(entitlements);
+            }
+            visitor.visit_type_mut(ty);
+        },
+        Type::Dictionary { key, value, .. } => {
+            visitor.visit_type_mut(key);
+            visitor.visit_type_mut(value);
+        },
+        Type::Function { parameters, return_type, .. } => {
+            for parameter in parameters.iter_mut() {
+                visitor.visit_type_annotation_mut(parameter);
+            }
+            visitor.visit_type_annotation_mut(return_type);
+        },
+        Type::Restricted { ty, restrictions, .. } => {
+            if let Some(ty) = ty {
+                visitor.visit_type_mut(ty);
+            }
+            for restriction in restrictions.iter_mut() {
+                visitor.visit_nominal_type_mut(restriction);
+            }
+        },
+        Type::Instantiation { ty, type_arguments, .. } => {
+            visitor.visit_type_mut(ty);
+            for argument in type_arguments.iter_mut() {
+                visitor.visit_type_annotation_mut(argument);
+            }
+        },
+    }
+}
+
+pub fn walk_nominal_type<V: VisitorMut + ?Sized>(visitor: &mut V, nominal: &mut NominalType) {
+    visitor.visit_identifier_mut(&mut nominal.identifier);
+    for nested in nominal.nested_identifiers.iter_mut() {
+        visitor.visit_identifier_mut(nested);
+    }
+}
+
+pub fn walk_block<V: VisitorMut + ?Sized>(visitor: &mut V, block: &mut Block) {
+    for statement in block.statements.iter_mut() {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_statement<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::Expression(expression) => visitor.visit_expression_mut(expression),
+        Statement::Return { expression, .. } => {
+            if let Some(expression) = expression {
+                visitor.visit_expression_mut(expression);
+            }
+        },
+        Statement::Break { .. } | Statement::Continue { .. } | Statement::Error(_) => (),
+        Statement::Variable(declaration) => visitor.visit_variable_declaration_mut(declaration),
+        Statement::Assignment { target, value, .. } => {
+            visitor.visit_expression_mut(target);
+            visitor.visit_expression_mut(value);
+        },
+        Statement::Swap { left, right, .. } => {
+            visitor.visit_expression_mut(left);
+            visitor.visit_expression_mut(right);
+        },
+        Statement::If(statement) => visitor.visit_if_statement_mut(statement),
+        Statement::While { test, block, .. } => {
+            visitor.visit_expression_mut(test);
+            visitor.visit_block_mut(block);
+        },
+        Statement::For { index, identifier, value, block, .. } => {
+            if let Some(index) = index {
+                visitor.visit_identifier_mut(index);
+            }
+            visitor.visit_identifier_mut(identifier);
+            visitor.visit_expression_mut(value);
+            visitor.visit_block_mut(block);
+        },
+        Statement::Switch { expression, cases, .. } => {
+            visitor.visit_expression_mut(expression);
+            for case in cases.iter_mut() {
+                visitor.visit_switch_case_mut(case);
+            }
+        },
+        Statement::Emit { invocation, .. } => visitor.visit_expression_mut(invocation),
+        Statement::Destroy { expression, .. } => visitor.visit_expression_mut(expression),
+    }
+}
+
+pub fn walk_if_statement<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut IfStatement) {
+    match &mut statement.test {
+        IfTest::Expression(test) => visitor.visit_expression_mut(test),
+        IfTest::Binding(binding) => visitor.visit_variable_declaration_mut(binding),
+    }
+    visitor.visit_block_mut(&mut statement.then);
+    match &mut statement.else_ {
+        Some(ElseBranch::Block(block)) => visitor.visit_block_mut(block),
+        Some(ElseBranch::If(else_if)) => visitor.visit_if_statement_mut(else_if),
+        None => (),
+    }
+}
+
+pub fn walk_switch_case<V: VisitorMut + ?Sized>(visitor: &mut V, case: &mut SwitchCase) {
+    for expression in case.expressions.iter_mut() {
+        visitor.visit_expression_mut(expression);
+    }
+    for statement in case.statements.iter_mut() {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_expression<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut Expression) {
+    match expression {
+        Expression::Nil { .. } |
+        Expression::Bool { .. } |
+        Expression::Integer { .. } |
+        Expression::FixedPoint { .. } |
+        Expression::String { .. } => (),
+        Expression::Array { values, .. } => {
+            for value in values.iter_mut() {
+                visitor.visit_expression_mut(value);
+            }
+        },
+        Expression::Dictionary { entries, .. } => {
+            for (key, value) in entries.iter_mut() {
+                visitor.visit_expression_mut(key);
+                visitor.visit_expression_mut(value);
+            }
+        },
+        Expression::Identifier(identifier) => visitor.visit_identifier_mut(identifier),
+        Expression::Path { domain, identifier, .. } => {
+            visitor.visit_identifier_mut(domain);
+            visitor.visit_identifier_mut(identifier);
+        },
+        Expression::Member { expression, identifier, .. } => {
+            visitor.visit_expression_mut(expression);
+            visitor.visit_identifier_mut(identifier);
+        },
+        Expression::Index { expression, index, .. } => {
+            visitor.visit_expression_mut(expression);
+            visitor.visit_expression_mut(index);
+        },
+        Expression::Invocation { invoked, type_arguments, arguments, .. } => {
+            visitor.visit_expression_mut(invoked);
+            for argument in type_arguments.iter_mut() {
+                visitor.visit_type_annotation_mut(argument);
+            }
+            for argument in arguments.iter_mut() {
+                visitor.visit_argument_mut(argument);
+            }
+        },
+        Expression::Unary { expression, .. } |
+        Expression::Force { expression, .. } => visitor.visit_expression_mut(expression),
+        Expression::Create { invocation, .. } => visitor.visit_expression_mut(invocation),
+        Expression::Binary { left, right, .. } => {
+            visitor.visit_expression_mut(left);
+            visitor.visit_expression_mut(right);
+        },
+        Expression::Conditional { test, then, else_, .. } => {
+            visitor.visit_expression_mut(test);
+            visitor.visit_expression_mut(then);
+            visitor.visit_expression_mut(else_);
+        },
+        Expression::Casting { expression, type_annotation, .. } => {
+            visitor.visit_expression_mut(expression);
+            visitor.visit_type_annotation_mut(type_annotation);
+        },
+        Expression::Reference { expression, ty, .. } => {
+            visitor.visit_expression_mut(expression);
+            if let Some(ty) = ty {
+                visitor.visit_type_mut(ty);
+            }
+        },
+    }
+}
+
+pub fn walk_argument<V: VisitorMut + ?Sized>(visitor: &mut V, argument: &mut Argument) {
+    if let Some(label) = &mut argument.label {
+        visitor.visit_identifier_mut(label);
+    }
+    visitor.visit_expression_mut(&mut argument.expression);
+}
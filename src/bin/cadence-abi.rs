@@ -0,0 +1,81 @@
+//! `cadence-abi [<path>]`
+//!
+//! prints the ABI of a `.cdc` file as JSON: the public fields and functions, events and nested types of its
+//! contracts and interfaces, and the parameters of its transaction or script. without path stdin is read.
+//! the exit code is 1 if the file has errors, which are reported to stderr, 2 if it cannot be read or parsed
+
+use std::env;
+use std::fs;
+use std::io::{
+    self, Read,
+};
+use std::process::ExitCode;
+
+use cadence_rs::abi::abi::abi;
+use cadence_rs::ast::Span;
+use cadence_rs::checker::checker::check;
+use cadence_rs::parser::parser::parse_with_errors;
+
+const USAGE: &str = "usage: cadence-abi [<path>]";
+
+fn main() -> ExitCode {
+    let mut path = None;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS
+            },
+            _ if arg.starts_with('-') => {
+                eprintln!("error: unknown option `{}`\n{}", arg, USAGE);
+                return ExitCode::from(2)
+            },
+            _ if path.is_some() => {
+                eprintln!("error: more than one path\n{}", USAGE);
+                return ExitCode::from(2)
+            },
+            _ => path = Some(arg),
+        }
+    }
+
+    let (name, source) = match &path {
+        Some(path) => (path.clone(), fs::read_to_string(path)),
+        None => {
+            let mut source = String::new();
+            ("<stdin>".to_string(), io::stdin().read_to_string(&mut source).map(|_| source))
+        },
+    };
+    let source = match source {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("error: cannot read {}: {}", name, error);
+            return ExitCode::from(2)
+        },
+    };
+
+    let (program, errors) = parse_with_errors(&source);
+    if !errors.is_empty() {
+        for error in errors.iter() {
+            report(&name, &source, error.span, &error.message);
+        }
+        return ExitCode::from(2)
+    }
+    let checked = check(&program);
+    let mut failed = false;
+    for error in checked.errors() {
+        report(&name, &source, error.span, &error.message);
+        failed = true;
+    }
+    if failed {
+        return ExitCode::from(1)
+    }
+    println!("{}", abi(&program, &checked).to_json().pretty());
+    ExitCode::SUCCESS
+}
+
+fn report(name: &str, source: &str, span: Span, message: &str) {
+    let before = &source[..span.at.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |at| at + 1) + 1;
+    eprintln!("{}:{}:{}: error: {}", name, line, column, message);
+}
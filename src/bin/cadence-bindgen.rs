@@ -0,0 +1,64 @@
+//! `cadence-bindgen [-o <file>] <path>...`
+//!
+//! generates Rust bindings for the transactions and scripts among the `.cdc` files, directories are searched
+//! recursively: a module for each, named after its file, with functions that encode the arguments as JSON-Cadence
+//! and, for scripts, decode the result. the bindings are printed, or written to the file given with `-o`.
+//! from a build script, `cadence_rs::bindgen::bindgen::build` generates them whenever the files change.
+//! the exit code is 2 if a file cannot be read, parsed or checked
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use cadence_rs::bindgen::bindgen::generate_files;
+
+const USAGE: &str = "usage: cadence-bindgen [-o <file>] <path>...";
+
+fn main() -> ExitCode {
+    let mut paths = Vec::new();
+    let mut out = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" => match args.next() {
+                Some(file) => out = Some(PathBuf::from(file)),
+                None => {
+                    eprintln!("error: `-o` needs a file\n{}", USAGE);
+                    return ExitCode::from(2)
+                },
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS
+            },
+            _ if arg.starts_with('-') => {
+                eprintln!("error: unknown option `{}`\n{}", arg, USAGE);
+                return ExitCode::from(2)
+            },
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("error: no paths\n{}", USAGE);
+        return ExitCode::from(2)
+    }
+
+    let code = match generate_files(&paths) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("{}: error: {}", error.path, error.message);
+            return ExitCode::from(2)
+        },
+    };
+    match out {
+        Some(out) => {
+            if let Err(error) = fs::write(&out, code) {
+                eprintln!("error: cannot write {}: {}", out.display(), error);
+                return ExitCode::from(2)
+            }
+        },
+        None => print!("{}", code),
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,131 @@
+//! `cadence-check [--surface] [<path>...]`
+//!
+//! type checks `.cdc` files and tracks their resources, directories are searched recursively.
+//! without paths stdin is checked.
+//! `--surface` prints the externally accessible members of all contracts as JSON, diagnostics then go to stderr.
+//! the exit code is 1 if any file has errors, 2 if a file cannot be read or parsed
+
+use std::env;
+use std::fs;
+use std::io::{
+    self, Read,
+};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use cadence_rs::ast::Span;
+use cadence_rs::checker::access::{
+    Surface, surface,
+};
+use cadence_rs::checker::checker::check;
+use cadence_rs::checker::diagnostic::Severity;
+use cadence_rs::files::files::collect_files;
+use cadence_rs::parser::parser::parse_with_errors;
+
+const USAGE: &str = "usage: cadence-check [--surface] [<path>...]";
+
+/// what checking all inputs found
+#[derive(Default)]
+struct Outcome {
+    errors: bool,
+    failed: bool,
+    /// the contracts of all inputs, when `--surface` is given
+    surface: Option<Surface>,
+}
+
+fn main() -> ExitCode {
+    let mut paths = Vec::new();
+    let mut outcome = Outcome::default();
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--surface" => outcome.surface = Some(Surface::default()),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS
+            },
+            _ if arg.starts_with('-') => {
+                eprintln!("error: unknown option `{}`\n{}", arg, USAGE);
+                return ExitCode::from(2)
+            },
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if paths.is_empty() {
+        let mut source = String::new();
+        match io::stdin().read_to_string(&mut source) {
+            Ok(_) => check_source("<stdin>", &source, &mut outcome),
+            Err(error) => {
+                eprintln!("error: cannot read stdin: {}", error);
+                outcome.failed = true;
+            },
+        }
+    } else {
+        let mut files = Vec::new();
+        for path in paths.iter() {
+            if let Err(error) = collect_files(path, &mut files) {
+                eprintln!("error: cannot read {}: {}", path.display(), error);
+                outcome.failed = true;
+            }
+        }
+        for file in files.iter() {
+            match fs::read_to_string(file) {
+                Ok(source) => check_source(&file.display().to_string(), &source, &mut outcome),
+                Err(error) => {
+                    eprintln!("error: cannot read {}: {}", file.display(), error);
+                    outcome.failed = true;
+                },
+            }
+        }
+    }
+
+    if let Some(surface) = &outcome.surface {
+        println!("{}", surface.to_json());
+    }
+    if outcome.failed {
+        ExitCode::from(2)
+    } else if outcome.errors {
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn check_source(name: &str, source: &str, outcome: &mut Outcome) {
+    let to_stderr = outcome.surface.is_some();
+    let (program, errors) = parse_with_errors(source);
+    if !errors.is_empty() {
+        for error in errors.iter() {
+            report(name, source, error.span, "error", &error.message, to_stderr);
+        }
+        outcome.failed = true;
+        return
+    }
+    let checked = check(&program);
+    for diagnostic in checked.diagnostics.iter() {
+        let severity = match diagnostic.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        report(name, source, diagnostic.span, severity, &diagnostic.message, to_stderr);
+        for note in diagnostic.notes.iter() {
+            report(name, source, note.span, "note", &note.message, to_stderr);
+        }
+    }
+    outcome.errors |= checked.errors().next().is_some();
+    if let Some(all) = &mut outcome.surface {
+        all.contracts.extend(surface(&checked).contracts);
+    }
+}
+
+fn report(name: &str, source: &str, span: Span, severity: &str, message: &str, to_stderr: bool) {
+    let before = &source[..span.at.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |at| at + 1) + 1;
+    let text = format!("{}:{}:{}: {}: {}", name, line, column, severity, message);
+    if to_stderr {
+        eprintln!("{}", text);
+    } else {
+        println!("{}", text);
+    }
+}
@@ -0,0 +1,175 @@
+//! `cadence-fmt [--check] [--config <file>] [<path>...]`
+//!
+//! formats `.cdc` files in place, directories are searched recursively.
+//! without paths stdin is formatted to stdout.
+//! with `--check` nothing is written, and the exit code is 1 if any file would change.
+//! settings come from `--config`, or the `cadencefmt.toml` closest above each file
+
+use std::env;
+use std::fs;
+use std::io::{
+    self, Read,
+};
+use std::path::{
+    Path, PathBuf,
+};
+use std::process::ExitCode;
+
+use cadence_rs::files::files::collect_files;
+use cadence_rs::format::config::{
+    Config, ConfigError,
+};
+use cadence_rs::format::format::format_source;
+use cadence_rs::parser::parser::ParseError;
+
+const USAGE: &str = "usage: cadence-fmt [--check] [--config <file>] [<path>...]";
+
+struct Options {
+    check: bool,
+    config: Option<PathBuf>,
+    paths: Vec<PathBuf>,
+}
+
+/// what formatting all inputs found
+#[derive(Default)]
+struct Outcome {
+    unformatted: bool,
+    failed: bool,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {}\n{}", message, USAGE);
+            return ExitCode::from(2)
+        },
+    };
+    let outcome = if options.paths.is_empty() {
+        format_stdin(&options)
+    } else {
+        format_paths(&options)
+    };
+    if outcome.failed {
+        ExitCode::from(2)
+    } else if outcome.unformatted {
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options { check: false, config: None, paths: Vec::new() };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => options.check = true,
+            "--config" => match args.next() {
+                Some(path) => options.config = Some(PathBuf::from(path)),
+                None => return Err("`--config` needs a file".to_string()),
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            },
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ => options.paths.push(PathBuf::from(arg)),
+        }
+    }
+    Ok(options)
+}
+
+/// the config given on the command line, otherwise the one found from `dir`
+fn config(options: &Options, dir: &Path) -> Result<Config, ConfigError> {
+    match options.config.clone().or_else(|| Config::find(dir)) {
+        Some(path) => Config::load(&path).map_err(|mut error| {
+            if error.line > 0 {
+                error.message = format!("{}:{}: {}", path.display(), error.line, error.message);
+            }
+            error
+        }),
+        None => Ok(Config::default()),
+    }
+}
+
+fn format_stdin(options: &Options) -> Outcome {
+    let mut outcome = Outcome::default();
+    let mut source = String::new();
+    if let Err(error) = io::stdin().read_to_string(&mut source) {
+        eprintln!("error: cannot read stdin: {}", error);
+        outcome.failed = true;
+        return outcome
+    }
+    let dir = env::current_dir().unwrap_or_default();
+    match format(options, &dir, "<stdin>", &source) {
+        Some(formatted) if options.check => outcome.unformatted = formatted != source,
+        Some(formatted) => print!("{}", formatted),
+        None => outcome.failed = true,
+    }
+    outcome
+}
+
+fn format_paths(options: &Options) -> Outcome {
+    let mut outcome = Outcome::default();
+    let mut files = Vec::new();
+    for path in options.paths.iter() {
+        if let Err(error) = collect_files(path, &mut files) {
+            eprintln!("error: cannot read {}: {}", path.display(), error);
+            outcome.failed = true;
+        }
+    }
+
+    for file in files.iter() {
+        let source = match fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("error: cannot read {}: {}", file.display(), error);
+                outcome.failed = true;
+                continue;
+            },
+        };
+        let dir = file.parent().unwrap_or(Path::new("."));
+        let Some(formatted) = format(options, dir, &file.display().to_string(), &source) else {
+            outcome.failed = true;
+            continue;
+        };
+        if formatted == source {
+            continue;
+        }
+        if options.check {
+            println!("{}", file.display());
+            outcome.unformatted = true;
+        } else if let Err(error) = fs::write(file, formatted) {
+            eprintln!("error: cannot write {}: {}", file.display(), error);
+            outcome.failed = true;
+        }
+    }
+    outcome
+}
+
+/// the formatted source, or `None` after reporting why it cannot be formatted
+fn format(options: &Options, dir: &Path, name: &str, source: &str) -> Option<String> {
+    let config = match config(options, dir) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("error: {}", error.message);
+            return None
+        },
+    };
+    match format_source(source, &config) {
+        Ok(formatted) => Some(formatted),
+        Err(errors) => {
+            for error in errors.iter() {
+                report(name, source, error);
+            }
+            None
+        },
+    }
+}
+
+fn report(name: &str, source: &str, error: &ParseError) {
+    let before = &source[..error.span.at.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |at| at + 1) + 1;
+    eprintln!("{}:{}:{}: {}", name, line, column, error.message);
+}
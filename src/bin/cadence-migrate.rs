@@ -0,0 +1,157 @@
+//! `cadence-migrate [--check] [<path>...]`
+//!
+//! rewrites pre-1.0 `.cdc` files to Cadence 1.0 in place, directories are searched recursively.
+//! without paths stdin is migrated to stdout.
+//! what is left to do by hand is reported as `<file>:<line>:<column>: follow-up: ...`, on stderr for stdin.
+//! with `--check` nothing is written, and the exit code is 1 if any file would change.
+//! the output is formatted with the `cadencefmt.toml` closest above each file
+
+use std::env;
+use std::fs;
+use std::io::{
+    self, Read,
+};
+use std::path::{
+    Path, PathBuf,
+};
+use std::process::ExitCode;
+
+use cadence_rs::ast::Span;
+use cadence_rs::files::files::collect_files;
+use cadence_rs::format::config::Config;
+use cadence_rs::migrate::migrate::migrate_source;
+
+const USAGE: &str = "usage: cadence-migrate [--check] [<path>...]";
+
+/// what migrating all inputs found
+#[derive(Default)]
+struct Outcome {
+    changed: bool,
+    failed: bool,
+}
+
+fn main() -> ExitCode {
+    let mut check = false;
+    let mut paths = Vec::new();
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--check" => check = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS
+            },
+            _ if arg.starts_with('-') => {
+                eprintln!("error: unknown option `{}`\n{}", arg, USAGE);
+                return ExitCode::from(2)
+            },
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let outcome = if paths.is_empty() {
+        migrate_stdin(check)
+    } else {
+        migrate_paths(&paths, check)
+    };
+    if outcome.failed {
+        ExitCode::from(2)
+    } else if check && outcome.changed {
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn migrate_stdin(check: bool) -> Outcome {
+    let mut outcome = Outcome::default();
+    let mut source = String::new();
+    if let Err(error) = io::stdin().read_to_string(&mut source) {
+        eprintln!("error: cannot read stdin: {}", error);
+        outcome.failed = true;
+        return outcome
+    }
+    let dir = env::current_dir().unwrap_or_default();
+    match migrate(&dir, "<stdin>", &source, true) {
+        Some(migrated) if check => outcome.changed = migrated != source,
+        Some(migrated) => print!("{}", migrated),
+        None => outcome.failed = true,
+    }
+    outcome
+}
+
+fn migrate_paths(paths: &[PathBuf], check: bool) -> Outcome {
+    let mut outcome = Outcome::default();
+    let mut files = Vec::new();
+    for path in paths.iter() {
+        if let Err(error) = collect_files(path, &mut files) {
+            eprintln!("error: cannot read {}: {}", path.display(), error);
+            outcome.failed = true;
+        }
+    }
+
+    for file in files.iter() {
+        let source = match fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("error: cannot read {}: {}", file.display(), error);
+                outcome.failed = true;
+                continue;
+            },
+        };
+        let dir = file.parent().unwrap_or(Path::new("."));
+        let Some(migrated) = migrate(dir, &file.display().to_string(), &source, false) else {
+            outcome.failed = true;
+            continue;
+        };
+        if migrated == source {
+            continue;
+        }
+        outcome.changed = true;
+        if check {
+            println!("{}", file.display());
+        } else if let Err(error) = fs::write(file, migrated) {
+            eprintln!("error: cannot write {}: {}", file.display(), error);
+            outcome.failed = true;
+        }
+    }
+    outcome
+}
+
+/// the migrated source after reporting its follow-ups, or `None` after reporting why it cannot be migrated
+fn migrate(dir: &Path, name: &str, source: &str, to_stderr: bool) -> Option<String> {
+    let config = match Config::find(dir).map(|path| Config::load(&path)) {
+        Some(Ok(config)) => config,
+        Some(Err(error)) => {
+            eprintln!("error: {}", error.message);
+            return None
+        },
+        None => Config::default(),
+    };
+    match migrate_source(source, &config) {
+        Ok(migration) => {
+            // follow-ups point into the source before migration
+            for follow_up in migration.follow_ups.iter() {
+                report(name, source, follow_up.span, "follow-up", &follow_up.message, to_stderr);
+            }
+            Some(migration.source)
+        },
+        Err(errors) => {
+            for error in errors.iter() {
+                report(name, source, error.span, "error", &error.message, true);
+            }
+            None
+        },
+    }
+}
+
+fn report(name: &str, source: &str, span: Span, severity: &str, message: &str, to_stderr: bool) {
+    let before = &source[..span.at.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |at| at + 1) + 1;
+    let text = format!("{}:{}:{}: {}: {}", name, line, column, severity, message);
+    if to_stderr {
+        eprintln!("{}", text);
+    } else {
+        println!("{}", text);
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{
+    Path, PathBuf,
+};
+
+use crate::abi::abi::{
+    AbiParameter, EntryPoint, abi,
+};
+use crate::ast::{
+    Declaration, Program, Span,
+};
+use crate::checker::checker::check;
+use crate::checker::types::{
+    NumberType, Type,
+};
+use crate::files::files::collect_files;
+use crate::parser::parser::parse_with_errors;
+
+/// paths of the generated code
+const CONVERT: &str = "::cadence_rs::json_cadence::convert";
+const JSON_CADENCE: &str = "::cadence_rs::json_cadence::json_cadence";
+
+/// keywords of Rust, which identifiers are raw for
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn", "else", "enum",
+    "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro", "match", "mod", "move",
+    "mut", "override", "priv", "pub", "ref", "return", "static", "struct", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// keywords that cannot be raw identifiers
+const RESERVED: &[&str] = &["crate", "self", "super", "Self", "_"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BindgenError {
+    pub message: String,
+    /// the file, with the line and column for errors in its code
+    pub path: String,
+}
+
+impl fmt::Display for BindgenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// for a build script: generates the bindings of the `.cdc` files below `dir` into `$OUT_DIR/<out>`,
+/// and has Cargo run the script again when they change.
+/// the bindings are then included with `include!(concat!(env!("OUT_DIR"), "/<out>"))`
+pub fn build(dir: impl AsRef<Path>, out: &str) -> Result<(), BindgenError> {
+    let dir = dir.as_ref();
+    // for a directory, Cargo looks at every file below it, so that new files are picked up too
+    println!("cargo:rerun-if-changed={}", dir.display());
+    let code = generate_files(&[dir.to_path_buf()])?;
+    let out_dir = env::var("OUT_DIR")
+        .map_err(|_| BindgenError { message: "OUT_DIR is not set, bindings are built from build scripts".to_string(), path: out.to_string() })?;
+    let out = Path::new(&out_dir).join(out);
+    fs::write(&out, code).map_err(|error| BindgenError { message: format!("cannot write: {}", error), path: out.display().to_string() })
+}
+
+/// the bindings of the transactions and scripts among the files, and the `.cdc` files below the directories.
+/// each gets a module named after its file, files without transaction or script are skipped
+pub fn generate_files(paths: &[PathBuf]) -> Result<String, BindgenError> {
+    let mut files = Vec::new();
+    for path in paths.iter() {
+        collect_files(path, &mut files)
+            .map_err(|error| BindgenError { message: format!("cannot read: {}", error), path: path.display().to_string() })?;
+    }
+    let mut code = String::from("// generated by cadence-bindgen, do not edit\n");
+    let mut modules = HashSet::new();
+    for file in files.iter() {
+        let name = file.display().to_string();
+        let source = fs::read_to_string(file).map_err(|error| BindgenError { message: format!("cannot read: {}", error), path: name.clone() })?;
+        let stem = file.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().to_string());
+        let module = identifier(&stem);
+        if let Some(bindings) = generate(&name, &module, &source)? {
+            if !modules.insert(module.clone()) {
+                return Err(BindgenError { message: format!("another file is bound to the module `{}` too", module), path: name })
+            }
+            code.push('\n');
+            code.push_str(&bindings);
+        }
+    }
+    Ok(code)
+}
+
+/// the module of bindings for a transaction or script, `None` for other programs.
+/// the module has the code as `CODE` and `arguments`, which takes the parameters of the transaction or script,
+/// and for transactions the addresses of the accounts signing it. for scripts, `result` decodes what they return
+pub fn generate(name: &str, module: &str, source: &str) -> Result<Option<String>, BindgenError> {
+    let error = |span: Span, message: &str| {
+        let before = &source[..span.at.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |at| at + 1) + 1;
+        BindgenError { message: message.to_string(), path: format!("{}:{}:{}", name, line, column) }
+    };
+    let (program, errors) = parse_with_errors(source);
+    if let Some(first) = errors.first() {
+        return Err(error(first.span, &first.message))
+    }
+    let checked = check(&program);
+    if let Some(first) = checked.errors().next() {
+        return Err(error(first.span, &first.message))
+    }
+
+    let mut code = String::new();
+    let mut names = HashSet::new();
+    match abi(&program, &checked).entry_point {
+        None => return Ok(None),
+        Some(EntryPoint::Transaction { parameters, signers }) => {
+            code.push_str(&format!("/// `{}`, a transaction\npub mod {} {{\n", name, module));
+            code.push_str(&format!("    pub const CODE: &str = {:?};\n\n", source));
+            let (arguments, values) = arguments(&parameters, &mut names);
+            let signers: Vec<(String, &Type)> = program_signers(&program).into_iter().zip(signers.iter())
+                .map(|(signer, ty)| (unique(&identifier(&signer), &mut names), ty))
+                .collect();
+            code.push_str("    /// the arguments of the transaction, and the accounts signing it for the parameters of `prepare`");
+            for (signer, ty) in signers.iter() {
+                code.push_str(&format!("\n    /// - `{}`: `{}`", signer.trim_start_matches("r#"), ty));
+            }
+            let parameters: Vec<String> = arguments.into_iter()
+                .chain(signers.iter().map(|(signer, _)| format!("{}: {}::Address", signer, CONVERT)))
+                .collect();
+            let authorizers: Vec<&str> = signers.iter().map(|(signer, _)| signer.as_str()).collect();
+            code.push_str(&format!(
+                "\n    #[allow(clippy::too_many_arguments)]\n    pub fn arguments({}) -> {}::TransactionPayload {{\n",
+                parameters.join(", "), CONVERT,
+            ));
+            code.push_str(&format!(
+                "        {}::TransactionPayload {{\n            arguments: vec![{}],\n            authorizers: vec![{}],\n        }}\n    }}\n}}\n",
+                CONVERT, values.join(", "), authorizers.join(", "),
+            ));
+        },
+        Some(EntryPoint::Script { parameters, return_type }) => {
+            code.push_str(&format!("/// `{}`, a script\npub mod {} {{\n", name, module));
+            code.push_str(&format!("    pub const CODE: &str = {:?};\n\n", source));
+            let (arguments, values) = arguments(&parameters, &mut names);
+            code.push_str("    /// the arguments of the script\n    #[allow(clippy::too_many_arguments)]\n");
+            code.push_str(&format!(
+                "    pub fn arguments({}) -> Vec<String> {{\n        vec![{}]\n    }}\n",
+                arguments.join(", "), values.join(", "),
+            ));
+            if return_type != Type::Void {
+                code.push_str(&format!(
+                    "\n    /// what the script returns, `{}`, from JSON-Cadence\n    pub fn result(json: &str) -> Result<{}, {}::DecodeError> {{\n        {}::from_json_cadence(json)\n    }}\n",
+                    return_type, rust_type(&return_type), JSON_CADENCE, CONVERT,
+                ));
+            }
+            code.push_str("}\n");
+        },
+    }
+    Ok(Some(code))
+}
+
+/// the names of the parameters of `prepare`
+fn program_signers(program: &Program) -> Vec<String> {
+    program.declarations.iter()
+        .find_map(|declaration| match declaration {
+            Declaration::Transaction(transaction) => transaction.prepare.as_ref(),
+            _ => None,
+        })
+        .map(|prepare| prepare.function.parameters.iter().map(|parameter| parameter.identifier.name.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// the Rust parameters, and their values as JSON-Cadence
+fn arguments(parameters: &[AbiParameter], names: &mut HashSet<String>) -> (Vec<String>, Vec<String>) {
+    parameters.iter()
+        .map(|parameter| {
+            let name = unique(&identifier(&parameter.name), names);
+            let value = format!("{}::to_json_cadence(&{})", CONVERT, name);
+            (format!("{}: {}", name, rust_type(&parameter.ty)), value)
+        })
+        .unzip()
+}
+
+/// the Rust type of values of a Cadence type. numbers that no Rust integer holds, e.g. `UFix64` or `Int`, are the
+/// checked numbers of `convert`. other types without Rust counterpart, e.g. composites, are JSON-Cadence values,
+/// which derived `ToCadence` and `FromCadence` convert to and from Rust types
+fn rust_type(ty: &Type) -> String {
+    let value = format!("{}::Value", JSON_CADENCE);
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::String => "String".to_string(),
+        Type::Address => format!("{}::Address", CONVERT),
+        Type::Number(number) => match number {
+            NumberType::UInt8 => "u8",
+            NumberType::UInt16 => "u16",
+            NumberType::UInt32 => "u32",
+            NumberType::UInt64 => "u64",
+            NumberType::UInt128 => "u128",
+            NumberType::Int8 => "i8",
+            NumberType::Int16 => "i16",
+            NumberType::Int32 => "i32",
+            NumberType::Int64 => "i64",
+            NumberType::Int128 => "i128",
+            NumberType::Int | NumberType::Int256 | NumberType::UInt | NumberType::UInt256 | NumberType::Word8 |
+            NumberType::Word16 | NumberType::Word32 | NumberType::Word64 | NumberType::Word128 | NumberType::Word256 |
+            NumberType::Fix64 | NumberType::UFix64 => return format!("{}::{}", CONVERT, number.name()),
+            // values of abstract number types may be of any of their subtypes
+            _ => &value,
+        }.to_string(),
+        Type::Optional(ty) => format!("Option<{}>", rust_type(ty)),
+        Type::VariableSized(ty) | Type::ConstantSized(ty, _) => format!("Vec<{}>", rust_type(ty)),
+        // values are not hashable
+        Type::Dictionary(key, ty) if rust_type(key) != value => {
+            format!("::std::collections::HashMap<{}, {}>", rust_type(key), rust_type(ty))
+        },
+        _ => value,
+    }
+}
+
+/// a Cadence name as snake case Rust identifier, e.g. `getNFTIds` as `get_nft_ids`
+fn identifier(name: &str) -> String {
+    let chars: Vec<char> = name.chars().map(|c| if c.is_alphanumeric() { c } else { '_' }).collect();
+    let mut snake = String::new();
+    for (index, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            let previous = chars[index - 1];
+            let next_lower = chars.get(index + 1).is_some_and(|next| next.is_lowercase());
+            if previous.is_lowercase() || previous.is_ascii_digit() || (previous.is_uppercase() && next_lower) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    if snake.is_empty() || snake.starts_with(|c: char| c.is_ascii_digit()) {
+        snake.insert(0, '_');
+    }
+    if RESERVED.contains(&snake.as_str()) {
+        snake.push('_');
+    } else if KEYWORDS.contains(&snake.as_str()) {
+        snake.insert_str(0, "r#");
+    }
+    snake
+}
+
+/// `name`, or with `_` appended until no other parameter has it
+fn unique(name: &str, names: &mut HashSet<String>) -> String {
+    let mut name = name.to_string();
+    while !names.insert(name.trim_start_matches("r#").to_string()) {
+        name = format!("{}_", name.trim_start_matches("r#"));
+    }
+    name
+}
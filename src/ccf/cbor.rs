@@ -0,0 +1,227 @@
+use std::fmt;
+
+/// arrays and tags nested deeper than this do not parse, instead of overflowing the stack
+const MAX_DEPTH: usize = 256;
+
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const TAG: u8 = 6;
+const SIMPLE: u8 = 7;
+
+/// a data item of the subset of CBOR that CCF uses: no maps, floats or indefinite lengths
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Cbor {
+    Unsigned(u64),
+    /// `-1 - n`
+    Negative(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Tag(u64, Box<Cbor>),
+    Bool(bool),
+    Null,
+}
+
+impl Cbor {
+    pub fn tag(tag: u64, content: Cbor) -> Self {
+        Cbor::Tag(tag, Box::new(content))
+    }
+
+    pub fn text(value: &str) -> Self {
+        Cbor::Text(value.to_string())
+    }
+
+    pub fn int(value: i128) -> Self {
+        if value < 0 {
+            Cbor::Negative((-1 - value) as u64)
+        } else {
+            Cbor::Unsigned(value as u64)
+        }
+    }
+
+    /// the encoding, with heads as short as possible
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes);
+        bytes
+    }
+
+    fn write(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Cbor::Unsigned(value) => head(bytes, UNSIGNED, *value),
+            Cbor::Negative(value) => head(bytes, NEGATIVE, *value),
+            Cbor::Bytes(value) => {
+                head(bytes, BYTES, value.len() as u64);
+                bytes.extend_from_slice(value);
+            },
+            Cbor::Text(value) => {
+                head(bytes, TEXT, value.len() as u64);
+                bytes.extend_from_slice(value.as_bytes());
+            },
+            Cbor::Array(items) => {
+                head(bytes, ARRAY, items.len() as u64);
+                for item in items.iter() {
+                    item.write(bytes);
+                }
+            },
+            Cbor::Tag(tag, content) => {
+                head(bytes, TAG, *tag);
+                content.write(bytes);
+            },
+            Cbor::Bool(value) => head(bytes, SIMPLE, if *value { 21 } else { 20 }),
+            Cbor::Null => head(bytes, SIMPLE, 22),
+        }
+    }
+
+    /// what kind of item it is, for messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Cbor::Unsigned(_) => "an unsigned integer",
+            Cbor::Negative(_) => "a negative integer",
+            Cbor::Bytes(_) => "a byte string",
+            Cbor::Text(_) => "a text string",
+            Cbor::Array(_) => "an array",
+            Cbor::Tag(..) => "a tag",
+            Cbor::Bool(_) => "a boolean",
+            Cbor::Null => "null",
+        }
+    }
+}
+
+fn head(bytes: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+    match argument {
+        0..=23 => bytes.push(major | argument as u8),
+        24..=0xff => bytes.extend_from_slice(&[major | 24, argument as u8]),
+        0x100..=0xffff => {
+            bytes.push(major | 25);
+            bytes.extend_from_slice(&(argument as u16).to_be_bytes());
+        },
+        0x1_0000..=0xffff_ffff => {
+            bytes.push(major | 26);
+            bytes.extend_from_slice(&(argument as u32).to_be_bytes());
+        },
+        _ => {
+            bytes.push(major | 27);
+            bytes.extend_from_slice(&argument.to_be_bytes());
+        },
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CborError {
+    pub message: String,
+    /// offset of the item in the bytes
+    pub at: usize,
+}
+
+impl CborError {
+    pub fn new(message: &str, at: usize) -> Self {
+        CborError { message: message.to_string(), at }
+    }
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.at)
+    }
+}
+
+/// parse one data item that spans all of `bytes`. heads must be as short as possible, as CCF requires
+pub fn parse_cbor(bytes: &[u8]) -> Result<Cbor, CborError> {
+    let mut reader = Reader { bytes, at: 0, depth: 0 };
+    let item = reader.item()?;
+    if reader.at < bytes.len() {
+        return Err(CborError::new("trailing bytes after the data item", reader.at))
+    }
+    Ok(item)
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+    at: usize,
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, size: usize) -> Result<&[u8], CborError> {
+        let end = self.at.checked_add(size).filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| CborError::new("unexpected end of the data", self.at))?;
+        let taken = &self.bytes[self.at..end];
+        self.at = end;
+        Ok(taken)
+    }
+
+    /// the major type and argument of the head of the next item
+    fn head(&mut self) -> Result<(u8, u64), CborError> {
+        let at = self.at;
+        let initial = self.take(1)?[0];
+        let (major, additional) = (initial >> 5, initial & 0x1f);
+        let argument = match additional {
+            0..=23 => return Ok((major, u64::from(additional))),
+            24 => u64::from(self.take(1)?[0]),
+            25 => self.take(2)?.iter().fold(0, |value, byte| (value << 8) | u64::from(*byte)),
+            26 => self.take(4)?.iter().fold(0, |value, byte| (value << 8) | u64::from(*byte)),
+            27 => self.take(8)?.iter().fold(0, |value, byte| (value << 8) | u64::from(*byte)),
+            31 => return Err(CborError::new("indefinite lengths are not allowed", at)),
+            _ => return Err(CborError::new("reserved additional information", at)),
+        };
+        let shortest = match additional {
+            24 => argument >= 24,
+            25 => argument > 0xff,
+            26 => argument > 0xffff,
+            _ => argument > 0xffff_ffff,
+        };
+        if !shortest || major == SIMPLE {
+            return Err(CborError::new("head is not as short as possible", at))
+        }
+        Ok((major, argument))
+    }
+
+    fn item(&mut self) -> Result<Cbor, CborError> {
+        let at = self.at;
+        let (major, argument) = self.head()?;
+        let size = |argument: u64| usize::try_from(argument).map_err(|_| CborError::new("length too large", at));
+        Ok(match major {
+            UNSIGNED => Cbor::Unsigned(argument),
+            NEGATIVE => Cbor::Negative(argument),
+            BYTES => Cbor::Bytes(self.take(size(argument)?)?.to_vec()),
+            TEXT => {
+                let text = self.take(size(argument)?)?;
+                let text = std::str::from_utf8(text).map_err(|_| CborError::new("text string is not UTF-8", at))?;
+                Cbor::Text(text.to_string())
+            },
+            ARRAY | TAG => {
+                if self.depth == MAX_DEPTH {
+                    return Err(CborError::new("too deeply nested", at))
+                }
+                self.depth += 1;
+                let item = if major == TAG {
+                    Cbor::tag(argument, self.item()?)
+                } else {
+                    // every item takes at least a byte
+                    if size(argument)? > self.bytes.len() - self.at {
+                        return Err(CborError::new("unexpected end of the data", self.at))
+                    }
+                    let mut items = Vec::new();
+                    for _ in 0..argument {
+                        items.push(self.item()?);
+                    }
+                    Cbor::Array(items)
+                };
+                self.depth -= 1;
+                item
+            },
+            SIMPLE => match argument {
+                20 => Cbor::Bool(false),
+                21 => Cbor::Bool(true),
+                22 => Cbor::Null,
+                _ => return Err(CborError::new("floats and simple values other than booleans and null are not allowed", at)),
+            },
+            _ => return Err(CborError::new("maps are not allowed", at)),
+        })
+    }
+}
@@ -0,0 +1,1052 @@
+use std::fmt;
+
+use crate::ast::CompositeKind;
+use crate::checker::types::NumberType;
+use crate::interpreter::bigint::BigInt;
+use crate::interpreter::number::Number;
+use crate::json_cadence::json_cadence::{
+    Authorization, Composite, CompositeType, FunctionType, Parameter, Type, Value, is_identifier,
+};
+
+use super::cbor::{
+    Cbor, parse_cbor,
+};
+
+// tags of messages
+const TYPE_DEF_AND_VALUE: u64 = 129;
+const TYPE_AND_VALUE: u64 = 130;
+
+// tags of inline types
+const TYPE_REF: u64 = 136;
+const SIMPLE_TYPE: u64 = 137;
+const OPTIONAL_TYPE: u64 = 138;
+const VARSIZED_ARRAY_TYPE: u64 = 139;
+const CONSTSIZED_ARRAY_TYPE: u64 = 140;
+const DICT_TYPE: u64 = 141;
+const REFERENCE_TYPE: u64 = 142;
+const INTERSECTION_TYPE: u64 = 143;
+const CAPABILITY_TYPE: u64 = 144;
+/// unauthorized, entitlement sets and entitlement maps
+const AUTHORIZATION_TYPES: [u64; 3] = [145, 146, 147];
+
+/// the first of the type definitions of `COMPOSITE_KINDS`
+const COMPOSITE_TYPE: u64 = 160;
+/// the first of the type definitions of `INTERFACE_KINDS`
+const INTERFACE_TYPE: u64 = 176;
+
+// tags of type values
+const TYPE_VALUE_REF: u64 = 184;
+const SIMPLE_TYPE_VALUE: u64 = 185;
+const OPTIONAL_TYPE_VALUE: u64 = 186;
+const VARSIZED_ARRAY_TYPE_VALUE: u64 = 187;
+const CONSTSIZED_ARRAY_TYPE_VALUE: u64 = 188;
+const DICT_TYPE_VALUE: u64 = 189;
+const REFERENCE_TYPE_VALUE: u64 = 190;
+const INTERSECTION_TYPE_VALUE: u64 = 191;
+const CAPABILITY_TYPE_VALUE: u64 = 192;
+const FUNCTION_TYPE_VALUE: u64 = 193;
+const AUTHORIZATION_TYPE_VALUES: [u64; 3] = [194, 195, 196];
+const COMPOSITE_TYPE_VALUE: u64 = 208;
+const INTERFACE_TYPE_VALUE: u64 = 224;
+
+// tags of CBOR for integers that do not fit 64 bits
+const BIGNUM: u64 = 2;
+const NEGATIVE_BIGNUM: u64 = 3;
+
+/// in the order of their tags
+const COMPOSITE_KINDS: [CompositeKind; 5] = [
+    CompositeKind::Structure, CompositeKind::Resource, CompositeKind::Event, CompositeKind::Contract, CompositeKind::Enum,
+];
+const INTERFACE_KINDS: [CompositeKind; 3] = [CompositeKind::Structure, CompositeKind::Resource, CompositeKind::Contract];
+
+/// the ids of the types without type arguments
+const SIMPLE_TYPE_IDS: &[(&str, u64)] = &[
+    ("Bool", 0), ("String", 1), ("Character", 2), ("Address", 3), ("Int", 4), ("Int8", 5), ("Int16", 6), ("Int32", 7),
+    ("Int64", 8), ("Int128", 9), ("Int256", 10), ("UInt", 11), ("UInt8", 12), ("UInt16", 13), ("UInt32", 14),
+    ("UInt64", 15), ("UInt128", 16), ("UInt256", 17), ("Word8", 18), ("Word16", 19), ("Word32", 20), ("Word64", 21),
+    ("Fix64", 22), ("UFix64", 23), ("Path", 24), ("CapabilityPath", 25), ("StoragePath", 26), ("PublicPath", 27),
+    ("PrivatePath", 28), ("AuthAccount", 29), ("PublicAccount", 30), ("AuthAccount.Keys", 31),
+    ("PublicAccount.Keys", 32), ("AuthAccount.Contracts", 33), ("PublicAccount.Contracts", 34),
+    ("DeployedContract", 35), ("AccountKey", 36), ("Block", 37), ("Any", 38), ("AnyStruct", 39), ("AnyResource", 40),
+    ("Type", 41), ("Never", 42), ("Number", 43), ("SignedNumber", 44), ("Integer", 45), ("SignedInteger", 46),
+    ("FixedPoint", 47), ("SignedFixedPoint", 48), ("Bytes", 49), ("Void", 50), ("Function", 51), ("Word128", 52),
+    ("Word256", 53), ("AnyStructAttachment", 54), ("AnyResourceAttachment", 55), ("StorageCapabilityController", 56),
+    ("AccountCapabilityController", 57), ("Account", 58), ("Account.Contracts", 59), ("Account.Keys", 60),
+    ("Account.Inbox", 61), ("Account.StorageCapabilities", 62), ("Account.AccountCapabilities", 63),
+    ("Account.Capabilities", 64), ("Account.Storage", 65), ("HashableStruct", 66),
+];
+
+/// static types that do not tell how a value is encoded, values of them are encoded together with their own type
+const ABSTRACT_TYPES: &[&str] = &[
+    "Any", "AnyStruct", "AnyResource", "AnyStructAttachment", "AnyResourceAttachment", "HashableStruct", "Path",
+    "CapabilityPath", "Number", "SignedNumber", "Integer", "SignedInteger", "FixedPoint", "SignedFixedPoint",
+];
+
+/// domains of paths with their ids and the types of their paths
+const PATH_DOMAINS: &[(&str, u64, &str)] = &[
+    ("storage", 1, "StoragePath"), ("private", 2, "PrivatePath"), ("public", 3, "PublicPath"),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CcfError {
+    pub message: String,
+    /// where in the message, e.g. `value.balance`, empty for the whole message
+    pub path: String,
+}
+
+impl CcfError {
+    pub fn new(message: &str, path: &str) -> Self {
+        CcfError { message: message.to_string(), path: path.to_string() }
+    }
+}
+
+impl fmt::Display for CcfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// a value as a CCF message, encoded by its static type `ty`. deterministic messages sort type definitions by the
+/// encoding of their type ids, and fields, dictionary entries, intersections and entitlements by their encoding
+pub fn encode(value: &Value, ty: &Type, deterministic: bool) -> Result<Vec<u8>, CcfError> {
+    let mut encoder = Encoder { deterministic, typedefs: Vec::new() };
+    // the first pass finds the composite types to define, the second encodes them by their final ids
+    encoder.message(value, ty)?;
+    if deterministic {
+        encoder.typedefs.sort_by_cached_key(|composite| Cbor::text(&composite.type_id).to_bytes());
+    }
+    Ok(encoder.message(value, ty)?.to_bytes())
+}
+
+/// a value from a CCF message, which must be valid as a whole: defined types, values of their types, numbers in range
+pub fn decode(bytes: &[u8]) -> Result<Value, CcfError> {
+    decode_typed(bytes).map(|(_, value)| value)
+}
+
+/// a value from a CCF message, with the static type it is encoded by
+pub fn decode_typed(bytes: &[u8]) -> Result<(Type, Value), CcfError> {
+    let cbor = parse_cbor(bytes)
+        .map_err(|error| CcfError::new(&format!("invalid CBOR at byte {}: {}", error.at, error.message), ""))?;
+    let mut decoder = Decoder { typedefs: Vec::new() };
+    let type_and_value = match &cbor {
+        Cbor::Tag(TYPE_DEF_AND_VALUE, message) => {
+            let [typedefs, type_and_value] = tuple(message, "")?;
+            decoder.define(typedefs, "typedefs")?;
+            type_and_value
+        },
+        Cbor::Tag(TYPE_AND_VALUE, type_and_value) => type_and_value,
+        cbor => {
+            let message = format!("expected a message tagged {} or {}, got {}", TYPE_DEF_AND_VALUE, TYPE_AND_VALUE, cbor.kind());
+            return Err(CcfError::new(&message, ""))
+        },
+    };
+    let [ty, value] = tuple(type_and_value, "")?;
+    let ty = decoder.inline_type(ty, "type", &mut Vec::new())?;
+    let value = decoder.value(value, &ty, "value")?;
+    Ok((ty, value))
+}
+
+/// the type of a value as it is, which encodes it with as few types inside as possible.
+/// arrays and dictionaries of values of different types are of `AnyStruct` or `AnyResource`
+pub fn infer_type(value: &Value) -> Type {
+    match value {
+        Value::Void => Type::simple("Void"),
+        Value::Optional(None) => Type::Optional(Box::new(Type::simple("Never"))),
+        Value::Optional(Some(value)) => Type::Optional(Box::new(infer_type(value))),
+        Value::Bool(_) => Type::simple("Bool"),
+        Value::String(_) => Type::simple("String"),
+        Value::Character(_) => Type::simple("Character"),
+        Value::Address(_) => Type::simple("Address"),
+        Value::Number(number) => Type::simple(number.ty().name()),
+        Value::Array(values) => Type::VariableSizedArray(Box::new(common_type(values.iter()))),
+        Value::Dictionary(entries) => Type::Dictionary(
+            Box::new(common_type(entries.iter().map(|(key, _)| key))),
+            Box::new(common_type(entries.iter().map(|(_, value)| value))),
+        ),
+        Value::Struct(_) | Value::Resource(_) | Value::Event(_) | Value::Contract(_) | Value::Enum(_) => {
+            let (kind, composite) = composite(value).unwrap();
+            let fields: Vec<(String, Type)> = composite.fields.iter().map(|(name, value)| (name.clone(), infer_type(value))).collect();
+            let raw_type = match kind {
+                CompositeKind::Enum => fields.iter().find(|(name, _)| name == "rawValue").map(|(_, ty)| Box::new(ty.clone())),
+                _ => None,
+            };
+            Type::Composite(CompositeType {
+                kind,
+                is_interface: false,
+                type_id: composite.type_id.clone(),
+                fields,
+                initializers: Vec::new(),
+                raw_type,
+            })
+        },
+        Value::Path { domain, .. } => {
+            Type::simple(PATH_DOMAINS.iter().find(|(name, _, _)| name == domain).map_or("Path", |(_, _, ty)| ty))
+        },
+        Value::Type(_) => Type::simple("Type"),
+        Value::Capability { borrow_type, .. } => Type::Capability(Some(Box::new(borrow_type.clone()))),
+        Value::Function(function) => Type::Function(function.clone()),
+    }
+}
+
+/// the type of all of the values if they have the same
+fn common_type<'v>(values: impl Iterator<Item = &'v Value>) -> Type {
+    let values: Vec<&Value> = values.collect();
+    let types: Vec<Type> = values.iter().map(|value| infer_type(value)).collect();
+    match types.first() {
+        Some(first) if types.iter().all(|ty| ty == first) => first.clone(),
+        _ if values.iter().any(|value| matches!(value, Value::Resource(_))) => Type::simple("AnyResource"),
+        _ => Type::simple("AnyStruct"),
+    }
+}
+
+fn composite(value: &Value) -> Option<(CompositeKind, &Composite)> {
+    match value {
+        Value::Struct(composite) => Some((CompositeKind::Structure, composite)),
+        Value::Resource(composite) => Some((CompositeKind::Resource, composite)),
+        Value::Event(composite) => Some((CompositeKind::Event, composite)),
+        Value::Contract(composite) => Some((CompositeKind::Contract, composite)),
+        Value::Enum(composite) => Some((CompositeKind::Enum, composite)),
+        _ => None,
+    }
+}
+
+fn is_abstract(ty: &Type) -> bool {
+    match ty {
+        Type::Simple(name) => ABSTRACT_TYPES.contains(&name.as_str()),
+        Type::Composite(composite) => composite.is_interface,
+        Type::Intersection { .. } | Type::Capability(None) => true,
+        _ => false,
+    }
+}
+
+/// the numbers that are CBOR integers, others are bignums
+fn is_fixed_size(ty: NumberType) -> bool {
+    matches!(ty,
+        NumberType::Int8 | NumberType::Int16 | NumberType::Int32 | NumberType::Int64 | NumberType::UInt8 |
+        NumberType::UInt16 | NumberType::UInt32 | NumberType::UInt64 | NumberType::Word8 | NumberType::Word16 |
+        NumberType::Word32 | NumberType::Word64 | NumberType::Fix64 | NumberType::UFix64
+    )
+}
+
+/// a type as Cadence writes it, for messages
+fn describe(ty: &Type) -> String {
+    match ty {
+        Type::Simple(name) => name.clone(),
+        Type::Optional(ty) => format!("{}?", describe(ty)),
+        Type::VariableSizedArray(ty) => format!("[{}]", describe(ty)),
+        Type::ConstantSizedArray(ty, size) => format!("[{}; {}]", describe(ty), size),
+        Type::Dictionary(key, value) => format!("{{{}: {}}}", describe(key), describe(value)),
+        Type::Composite(composite) => composite.type_id.clone(),
+        Type::Reference { ty, .. } => format!("&{}", describe(ty)),
+        Type::Intersection { type_id, .. } => type_id.clone(),
+        Type::Capability(None) => "Capability".to_string(),
+        Type::Capability(Some(ty)) => format!("Capability<{}>", describe(ty)),
+        Type::Function(function) => {
+            let parameters: Vec<String> = function.parameters.iter().map(|parameter| describe(&parameter.ty)).collect();
+            format!("fun({}): {}", parameters.join(", "), describe(&function.return_type))
+        },
+    }
+}
+
+/// what kind of value it is, for messages
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Void => "Void",
+        Value::Optional(_) => "an optional",
+        Value::Bool(_) => "a boolean",
+        Value::String(_) => "a string",
+        Value::Character(_) => "a character",
+        Value::Address(_) => "an address",
+        Value::Number(_) => "a number",
+        Value::Array(_) => "an array",
+        Value::Dictionary(_) => "a dictionary",
+        Value::Struct(_) => "a struct",
+        Value::Resource(_) => "a resource",
+        Value::Event(_) => "an event",
+        Value::Contract(_) => "a contract",
+        Value::Enum(_) => "an enum",
+        Value::Path { .. } => "a path",
+        Value::Type(_) => "a type",
+        Value::Capability { .. } => "a capability",
+        Value::Function(_) => "a function",
+    }
+}
+
+fn child(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// a type definition id, as few big-endian bytes as hold it
+fn id_bytes(id: usize) -> Cbor {
+    Cbor::Bytes((id as u64).to_be_bytes().into_iter().skip_while(|byte| *byte == 0).collect())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// the tag of a composite type definition or type value, from the tags of the first composite and interface
+fn composite_tag(composite: &CompositeType, tags: (u64, u64), path: &str) -> Result<u64, CcfError> {
+    let (kinds, tag): (&[CompositeKind], u64) = if composite.is_interface { (&INTERFACE_KINDS, tags.1) } else { (&COMPOSITE_KINDS, tags.0) };
+    kinds.iter().position(|kind| *kind == composite.kind)
+        .map(|offset| tag + offset as u64)
+        .ok_or_else(|| CcfError::new(&format!("{} interfaces cannot be encoded", composite.kind.keyword()), path))
+}
+
+/// the kind of a composite type definition or type value by its tag, and whether it is an interface
+fn composite_kind(tag: u64, tags: (u64, u64)) -> Option<(CompositeKind, bool)> {
+    let kind = |kinds: &[CompositeKind], first: u64| tag.checked_sub(first).and_then(|offset| kinds.get(offset as usize)).copied();
+    kind(&COMPOSITE_KINDS, tags.0).map(|kind| (kind, false))
+        .or_else(|| kind(&INTERFACE_KINDS, tags.1).map(|kind| (kind, true)))
+}
+
+fn simple_type_id(name: &str, path: &str) -> Result<u64, CcfError> {
+    SIMPLE_TYPE_IDS.iter().find(|(simple, _)| *simple == name).map(|(_, id)| *id)
+        .ok_or_else(|| CcfError::new(&format!("`{}` has no CCF type id", name), path))
+}
+
+fn simple_type(id: u64, path: &str) -> Result<Type, CcfError> {
+    SIMPLE_TYPE_IDS.iter().find(|(_, simple)| *simple == id).map(|(name, _)| Type::simple(name))
+        .ok_or_else(|| CcfError::new(&format!("unknown simple type id {}", id), path))
+}
+
+fn encode_number(number: &Number) -> Cbor {
+    let value = number.value();
+    if is_fixed_size(number.ty()) {
+        // the raw values of these fit 64 bits
+        return Cbor::int(value.to_i128().unwrap_or_default())
+    }
+    if value.is_negative() {
+        Cbor::tag(NEGATIVE_BIGNUM, Cbor::Bytes((value.abs() - BigInt::from(1i128)).to_bytes(0)))
+    } else {
+        Cbor::tag(BIGNUM, Cbor::Bytes(value.to_bytes(0)))
+    }
+}
+
+struct Encoder {
+    deterministic: bool,
+    /// the composite types the message defines, by id
+    typedefs: Vec<CompositeType>,
+}
+
+impl Encoder {
+    fn message(&mut self, value: &Value, ty: &Type) -> Result<Cbor, CcfError> {
+        let type_and_value = Cbor::Array(vec![self.inline_type(ty, "type")?, self.value(value, ty, "value")?]);
+        // defining a type can refer to more
+        let mut typedefs = Vec::new();
+        while typedefs.len() < self.typedefs.len() {
+            typedefs.push(self.typedef(typedefs.len())?);
+        }
+        if typedefs.is_empty() {
+            return Ok(Cbor::tag(TYPE_AND_VALUE, type_and_value))
+        }
+        Ok(Cbor::tag(TYPE_DEF_AND_VALUE, Cbor::Array(vec![Cbor::Array(typedefs), type_and_value])))
+    }
+
+    fn sort(&self, items: &mut [Cbor]) {
+        if self.deterministic {
+            items.sort_by_cached_key(Cbor::to_bytes);
+        }
+    }
+
+    /// the id of a composite type, which the first of its type id with fields defines
+    fn define(&mut self, composite: &CompositeType) -> usize {
+        let mut composite = composite.clone();
+        if self.deterministic {
+            composite.fields.sort_by_cached_key(|(name, _)| Cbor::text(name).to_bytes());
+        }
+        match self.typedefs.iter().position(|defined| defined.type_id == composite.type_id) {
+            Some(id) => {
+                // recursive types are exported without fields inside of themselves
+                if self.typedefs[id].fields.is_empty() {
+                    self.typedefs[id] = composite;
+                }
+                id
+            },
+            None => {
+                self.typedefs.push(composite);
+                self.typedefs.len() - 1
+            },
+        }
+    }
+
+    fn typedef(&mut self, id: usize) -> Result<Cbor, CcfError> {
+        let composite = self.typedefs[id].clone();
+        let path = format!("typedefs[{}]", id);
+        let tag = composite_tag(&composite, (COMPOSITE_TYPE, INTERFACE_TYPE), &path)?;
+        if composite.is_interface {
+            return Ok(Cbor::tag(tag, Cbor::Array(vec![id_bytes(id), Cbor::text(&composite.type_id)])))
+        }
+        let mut fields = Vec::new();
+        for (name, ty) in composite.fields.iter() {
+            fields.push(Cbor::Array(vec![Cbor::text(name), self.inline_type(ty, &child(&path, name))?]));
+        }
+        Ok(Cbor::tag(tag, Cbor::Array(vec![id_bytes(id), Cbor::text(&composite.type_id), Cbor::Array(fields)])))
+    }
+
+    fn inline_type(&mut self, ty: &Type, path: &str) -> Result<Cbor, CcfError> {
+        Ok(match ty {
+            Type::Simple(name) => Cbor::tag(SIMPLE_TYPE, Cbor::Unsigned(simple_type_id(name, path)?)),
+            Type::Optional(ty) => Cbor::tag(OPTIONAL_TYPE, self.inline_type(ty, path)?),
+            Type::VariableSizedArray(ty) => Cbor::tag(VARSIZED_ARRAY_TYPE, self.inline_type(ty, path)?),
+            Type::ConstantSizedArray(ty, size) => {
+                Cbor::tag(CONSTSIZED_ARRAY_TYPE, Cbor::Array(vec![Cbor::Unsigned(*size), self.inline_type(ty, path)?]))
+            },
+            Type::Dictionary(key, value) => {
+                Cbor::tag(DICT_TYPE, Cbor::Array(vec![self.inline_type(key, path)?, self.inline_type(value, path)?]))
+            },
+            Type::Composite(composite) => Cbor::tag(TYPE_REF, id_bytes(self.define(composite))),
+            Type::Reference { authorization, ty } => {
+                let authorization = self.authorization(authorization, AUTHORIZATION_TYPES);
+                Cbor::tag(REFERENCE_TYPE, Cbor::Array(vec![authorization, self.inline_type(ty, path)?]))
+            },
+            Type::Intersection { types, .. } => {
+                let mut encoded = Vec::new();
+                for ty in types.iter() {
+                    encoded.push(self.inline_type(ty, path)?);
+                }
+                self.sort(&mut encoded);
+                // the type intersections restricted before Cadence 1.0, which is never encoded
+                Cbor::tag(INTERSECTION_TYPE, Cbor::Array(vec![Cbor::Null, Cbor::Array(encoded)]))
+            },
+            Type::Capability(None) => Cbor::tag(CAPABILITY_TYPE, Cbor::Null),
+            Type::Capability(Some(ty)) => Cbor::tag(CAPABILITY_TYPE, self.inline_type(ty, path)?),
+            // functions are values of one simple type, their value holds the type of the function
+            Type::Function(_) => Cbor::tag(SIMPLE_TYPE, Cbor::Unsigned(simple_type_id("Function", path)?)),
+        })
+    }
+
+    /// with the tags of unauthorized, entitlement sets and entitlement maps, inline types and type values differ
+    fn authorization(&self, authorization: &Authorization, tags: [u64; 3]) -> Cbor {
+        let set = |kind: u64, entitlements: &[String]| {
+            let mut entitlements: Vec<Cbor> = entitlements.iter().map(|entitlement| Cbor::text(entitlement)).collect();
+            self.sort(&mut entitlements);
+            Cbor::tag(tags[1], Cbor::Array(vec![Cbor::Unsigned(kind), Cbor::Array(entitlements)]))
+        };
+        match authorization {
+            Authorization::Unauthorized => Cbor::tag(tags[0], Cbor::Null),
+            Authorization::Conjunction(entitlements) => set(0, entitlements),
+            Authorization::Disjunction(entitlements) => set(1, entitlements),
+            Authorization::Mapping(mapping) => Cbor::tag(tags[2], Cbor::text(mapping)),
+        }
+    }
+
+    fn value(&mut self, value: &Value, ty: &Type, path: &str) -> Result<Cbor, CcfError> {
+        if is_abstract(ty) {
+            let runtime = infer_type(value);
+            let type_and_value = vec![self.inline_type(&runtime, path)?, self.value(value, &runtime, path)?];
+            return Ok(Cbor::tag(TYPE_AND_VALUE, Cbor::Array(type_and_value)))
+        }
+        let mismatch = || CcfError::new(&format!("expected a value of type `{}`, got {}", describe(ty), kind(value)), path);
+        Ok(match (ty, value) {
+            (Type::Simple(name), Value::Void) if name == "Void" => Cbor::Null,
+            (Type::Simple(name), Value::Bool(value)) if name == "Bool" => Cbor::Bool(*value),
+            (Type::Simple(name), Value::String(value)) if name == "String" => Cbor::text(value),
+            (Type::Simple(name), Value::Character(value)) if name == "Character" => Cbor::text(value),
+            (Type::Simple(name), Value::Address(address)) if name == "Address" => Cbor::Bytes(address.to_be_bytes().to_vec()),
+            (Type::Simple(name), Value::Number(number)) if name == number.ty().name() => encode_number(number),
+            (Type::Simple(name), Value::Path { domain, identifier }) => {
+                let Some((_, id, _)) = PATH_DOMAINS.iter().find(|(known, _, path_type)| known == domain && path_type == name) else {
+                    return Err(mismatch())
+                };
+                Cbor::Array(vec![Cbor::Unsigned(*id), Cbor::text(identifier)])
+            },
+            (Type::Simple(name), Value::Type(ty)) if name == "Type" => self.type_value(ty, &mut Vec::new(), path)?,
+            (Type::Simple(name), Value::Function(function)) if name == "Function" => {
+                self.function_type(function, &mut Vec::new(), path)?
+            },
+            (Type::Function(_), Value::Function(function)) => self.function_type(function, &mut Vec::new(), path)?,
+            (Type::Optional(_), Value::Optional(None)) => Cbor::Null,
+            (Type::Optional(ty), Value::Optional(Some(value))) => self.value(value, ty, path)?,
+            (Type::VariableSizedArray(ty), Value::Array(values)) => self.array(values, ty, path)?,
+            (Type::ConstantSizedArray(ty, size), Value::Array(values)) if values.len() as u64 == *size => {
+                self.array(values, ty, path)?
+            },
+            (Type::Dictionary(key_type, value_type), Value::Dictionary(entries)) => {
+                let mut encoded = Vec::new();
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    let path = format!("{}[{}]", path, index);
+                    encoded.push((self.value(key, key_type, &child(&path, "key"))?, self.value(value, value_type, &child(&path, "value"))?));
+                }
+                if self.deterministic {
+                    encoded.sort_by_cached_key(|(key, _)| key.to_bytes());
+                }
+                Cbor::Array(encoded.into_iter().flat_map(|(key, value)| [key, value]).collect())
+            },
+            (Type::Composite(ty), value) => {
+                let Some((kind, composite)) = composite(value).filter(|(kind, composite)| *kind == ty.kind && composite.type_id == ty.type_id) else {
+                    return Err(mismatch())
+                };
+                let id = self.define(ty);
+                let fields = self.typedefs[id].fields.clone();
+                if let Some((name, _)) = composite.fields.iter().find(|(name, _)| !fields.iter().any(|(field, _)| field == name)) {
+                    let message = format!("`{}` has no field `{}`", composite.type_id, name);
+                    return Err(CcfError::new(&message, path))
+                }
+                let mut encoded = Vec::new();
+                for (name, ty) in fields.iter() {
+                    let Some(value) = composite.field(name) else {
+                        let message = format!("the {} is missing the field `{}`", kind.keyword(), name);
+                        return Err(CcfError::new(&message, path))
+                    };
+                    encoded.push(self.value(value, ty, &child(path, name))?);
+                }
+                Cbor::Array(encoded)
+            },
+            (Type::Reference { ty, .. }, value) => self.value(value, ty, path)?,
+            (Type::Capability(Some(_)), Value::Capability { id, address, .. }) => {
+                Cbor::Array(vec![Cbor::Bytes(address.to_be_bytes().to_vec()), Cbor::Unsigned(*id)])
+            },
+            _ => return Err(mismatch()),
+        })
+    }
+
+    fn array(&mut self, values: &[Value], ty: &Type, path: &str) -> Result<Cbor, CcfError> {
+        let mut encoded = Vec::new();
+        for (index, value) in values.iter().enumerate() {
+            encoded.push(self.value(value, ty, &format!("{}[{}]", path, index))?);
+        }
+        Ok(Cbor::Array(encoded))
+    }
+
+    /// `composites` are the type ids of the composite types encoded so far, encoded again by their index
+    fn type_value(&mut self, ty: &Type, composites: &mut Vec<String>, path: &str) -> Result<Cbor, CcfError> {
+        Ok(match ty {
+            Type::Simple(name) => Cbor::tag(SIMPLE_TYPE_VALUE, Cbor::Unsigned(simple_type_id(name, path)?)),
+            Type::Optional(ty) => Cbor::tag(OPTIONAL_TYPE_VALUE, self.type_value(ty, composites, path)?),
+            Type::VariableSizedArray(ty) => Cbor::tag(VARSIZED_ARRAY_TYPE_VALUE, self.type_value(ty, composites, path)?),
+            Type::ConstantSizedArray(ty, size) => {
+                let ty = self.type_value(ty, composites, path)?;
+                Cbor::tag(CONSTSIZED_ARRAY_TYPE_VALUE, Cbor::Array(vec![Cbor::Unsigned(*size), ty]))
+            },
+            Type::Dictionary(key, value) => {
+                let key = self.type_value(key, composites, path)?;
+                Cbor::tag(DICT_TYPE_VALUE, Cbor::Array(vec![key, self.type_value(value, composites, path)?]))
+            },
+            Type::Composite(composite) => {
+                if let Some(id) = composites.iter().position(|type_id| *type_id == composite.type_id) {
+                    return Ok(Cbor::tag(TYPE_VALUE_REF, id_bytes(id)))
+                }
+                let id = composites.len();
+                composites.push(composite.type_id.clone());
+                let tag = composite_tag(composite, (COMPOSITE_TYPE_VALUE, INTERFACE_TYPE_VALUE), path)?;
+                let raw_type = match &composite.raw_type {
+                    Some(ty) => self.type_value(ty, composites, path)?,
+                    None => Cbor::Null,
+                };
+                let mut fields = Vec::new();
+                for (name, ty) in composite.fields.iter() {
+                    fields.push(Cbor::Array(vec![Cbor::text(name), self.type_value(ty, composites, path)?]));
+                }
+                self.sort(&mut fields);
+                let mut initializers = Vec::new();
+                for parameters in composite.initializers.iter() {
+                    initializers.push(self.parameters(parameters, composites, path)?);
+                }
+                let composite = vec![id_bytes(id), Cbor::text(&composite.type_id), raw_type, Cbor::Array(fields), Cbor::Array(initializers)];
+                Cbor::tag(tag, Cbor::Array(composite))
+            },
+            Type::Reference { authorization, ty } => {
+                let authorization = self.authorization(authorization, AUTHORIZATION_TYPE_VALUES);
+                Cbor::tag(REFERENCE_TYPE_VALUE, Cbor::Array(vec![authorization, self.type_value(ty, composites, path)?]))
+            },
+            Type::Intersection { types, .. } => {
+                let mut encoded = Vec::new();
+                for ty in types.iter() {
+                    encoded.push(self.type_value(ty, composites, path)?);
+                }
+                self.sort(&mut encoded);
+                Cbor::tag(INTERSECTION_TYPE_VALUE, Cbor::Array(vec![Cbor::Null, Cbor::Array(encoded)]))
+            },
+            Type::Capability(None) => Cbor::tag(CAPABILITY_TYPE_VALUE, Cbor::Null),
+            Type::Capability(Some(ty)) => Cbor::tag(CAPABILITY_TYPE_VALUE, self.type_value(ty, composites, path)?),
+            Type::Function(function) => Cbor::tag(FUNCTION_TYPE_VALUE, self.function_type(function, composites, path)?),
+        })
+    }
+
+    /// type parameters, parameters and the return type. whether the function is `view` is not encoded
+    fn function_type(&mut self, function: &FunctionType, composites: &mut Vec<String>, path: &str) -> Result<Cbor, CcfError> {
+        let mut type_parameters = Vec::new();
+        for (name, bound) in function.type_parameters.iter() {
+            let bound = match bound {
+                Some(bound) => self.type_value(bound, composites, path)?,
+                None => Cbor::Null,
+            };
+            type_parameters.push(Cbor::Array(vec![Cbor::text(name), bound]));
+        }
+        let parameters = self.parameters(&function.parameters, composites, path)?;
+        let return_type = self.type_value(&function.return_type, composites, path)?;
+        Ok(Cbor::Array(vec![Cbor::Array(type_parameters), parameters, return_type]))
+    }
+
+    fn parameters(&mut self, parameters: &[Parameter], composites: &mut Vec<String>, path: &str) -> Result<Cbor, CcfError> {
+        let mut encoded = Vec::new();
+        for parameter in parameters.iter() {
+            let ty = self.type_value(&parameter.ty, composites, path)?;
+            encoded.push(Cbor::Array(vec![Cbor::text(&parameter.label), Cbor::text(&parameter.identifier), ty]));
+        }
+        Ok(Cbor::Array(encoded))
+    }
+}
+
+// decoding
+
+fn array<'c>(cbor: &'c Cbor, path: &str) -> Result<&'c [Cbor], CcfError> {
+    match cbor {
+        Cbor::Array(items) => Ok(items),
+        cbor => Err(CcfError::new(&format!("expected an array, got {}", cbor.kind()), path)),
+    }
+}
+
+/// the elements of an array of exactly `N`
+fn tuple<'c, const N: usize>(cbor: &'c Cbor, path: &str) -> Result<[&'c Cbor; N], CcfError> {
+    let items = array(cbor, path)?;
+    if items.len() != N {
+        return Err(CcfError::new(&format!("expected an array of {} elements, got {}", N, items.len()), path))
+    }
+    Ok(std::array::from_fn(|index| &items[index]))
+}
+
+fn text<'c>(cbor: &'c Cbor, path: &str) -> Result<&'c str, CcfError> {
+    match cbor {
+        Cbor::Text(value) => Ok(value),
+        cbor => Err(CcfError::new(&format!("expected a text string, got {}", cbor.kind()), path)),
+    }
+}
+
+fn bytes<'c>(cbor: &'c Cbor, path: &str) -> Result<&'c [u8], CcfError> {
+    match cbor {
+        Cbor::Bytes(value) => Ok(value),
+        cbor => Err(CcfError::new(&format!("expected a byte string, got {}", cbor.kind()), path)),
+    }
+}
+
+fn unsigned(cbor: &Cbor, path: &str) -> Result<u64, CcfError> {
+    match cbor {
+        Cbor::Unsigned(value) => Ok(*value),
+        cbor => Err(CcfError::new(&format!("expected an unsigned integer, got {}", cbor.kind()), path)),
+    }
+}
+
+fn address(cbor: &Cbor, path: &str) -> Result<u64, CcfError> {
+    let address = bytes(cbor, path)?;
+    let address: [u8; 8] = address.try_into()
+        .map_err(|_| CcfError::new(&format!("expected an address of 8 bytes, got {}", address.len()), path))?;
+    Ok(u64::from_be_bytes(address))
+}
+
+fn decode_number(cbor: &Cbor, ty: NumberType, path: &str) -> Result<Number, CcfError> {
+    let value = match cbor {
+        Cbor::Unsigned(value) if is_fixed_size(ty) => BigInt::from(i128::from(*value)),
+        Cbor::Negative(value) if is_fixed_size(ty) => BigInt::from(-1 - i128::from(*value)),
+        Cbor::Tag(tag @ (BIGNUM | NEGATIVE_BIGNUM), magnitude) if !is_fixed_size(ty) => {
+            let magnitude = bytes(magnitude, path)?;
+            if magnitude.first() == Some(&0) {
+                return Err(CcfError::new("bignum with leading zero bytes", path))
+            }
+            let magnitude = BigInt::from_bytes(magnitude);
+            if *tag == BIGNUM { magnitude } else { -magnitude - BigInt::from(1i128) }
+        },
+        cbor => {
+            let expected = if is_fixed_size(ty) { "an integer" } else { "a bignum" };
+            return Err(CcfError::new(&format!("expected {} for `{}`, got {}", expected, ty.name(), cbor.kind()), path))
+        },
+    };
+    Number::from_raw(ty, value).map_err(|_| CcfError::new(&format!("out of range for `{}`", ty.name()), path))
+}
+
+fn decode_authorization(cbor: &Cbor, tags: [u64; 3], path: &str) -> Result<Authorization, CcfError> {
+    match cbor {
+        Cbor::Tag(tag, content) if *tag == tags[0] && **content == Cbor::Null => Ok(Authorization::Unauthorized),
+        Cbor::Tag(tag, content) if *tag == tags[1] => {
+            let [kind, entitlements] = tuple(content, path)?;
+            let entitlements = array(entitlements, path)?.iter()
+                .map(|entitlement| text(entitlement, path).map(str::to_string))
+                .collect::<Result<Vec<_>, _>>()?;
+            if entitlements.is_empty() {
+                return Err(CcfError::new("expected at least one entitlement", path))
+            }
+            match unsigned(kind, path)? {
+                0 => Ok(Authorization::Conjunction(entitlements)),
+                1 => Ok(Authorization::Disjunction(entitlements)),
+                kind => Err(CcfError::new(&format!("unknown entitlement set kind {}", kind), path)),
+            }
+        },
+        Cbor::Tag(tag, mapping) if *tag == tags[2] => Ok(Authorization::Mapping(text(mapping, path)?.to_string())),
+        cbor => Err(CcfError::new(&format!("expected an authorization, got {}", cbor.kind()), path)),
+    }
+}
+
+/// `{A.0000000000000001.Token.Receiver,...}`
+fn intersection(types: Vec<Type>) -> Type {
+    let type_ids: Vec<String> = types.iter().map(describe).collect();
+    Type::Intersection { type_id: format!("{{{}}}", type_ids.join(",")), types }
+}
+
+fn composite_value(kind: CompositeKind, composite: Composite) -> Value {
+    match kind {
+        CompositeKind::Structure => Value::Struct(composite),
+        CompositeKind::Resource => Value::Resource(composite),
+        CompositeKind::Event => Value::Event(composite),
+        CompositeKind::Contract => Value::Contract(composite),
+        CompositeKind::Enum => Value::Enum(composite),
+    }
+}
+
+/// a composite type as it is defined, the types of its fields not decoded yet
+struct Typedef {
+    id: Vec<u8>,
+    kind: CompositeKind,
+    is_interface: bool,
+    type_id: String,
+    fields: Vec<(String, Cbor)>,
+}
+
+struct Decoder {
+    typedefs: Vec<Typedef>,
+}
+
+impl Decoder {
+    fn define(&mut self, typedefs: &Cbor, path: &str) -> Result<(), CcfError> {
+        for (index, typedef) in array(typedefs, path)?.iter().enumerate() {
+            let path = format!("{}[{}]", path, index);
+            let tags = (COMPOSITE_TYPE, INTERFACE_TYPE);
+            let (tag, content) = match typedef {
+                Cbor::Tag(tag, content) if composite_kind(*tag, tags).is_some() => (*tag, content),
+                typedef => return Err(CcfError::new(&format!("expected a composite type definition, got {}", typedef.kind()), &path)),
+            };
+            let (kind, is_interface) = composite_kind(tag, tags).unwrap();
+            let (id, type_id, fields) = if is_interface {
+                let [id, type_id] = tuple(content, &path)?;
+                (id, type_id, Vec::new())
+            } else {
+                let [id, type_id, fields] = tuple(content, &path)?;
+                let mut decoded: Vec<(String, Cbor)> = Vec::new();
+                for (index, field) in array(fields, &child(&path, "fields"))?.iter().enumerate() {
+                    let path = format!("{}.fields[{}]", path, index);
+                    let [name, ty] = tuple(field, &path)?;
+                    let name = text(name, &path)?;
+                    if decoded.iter().any(|(field, _)| field == name) {
+                        return Err(CcfError::new(&format!("duplicate field `{}`", name), &path))
+                    }
+                    decoded.push((name.to_string(), ty.clone()));
+                }
+                (id, type_id, decoded)
+            };
+            let id = bytes(id, &path)?.to_vec();
+            let type_id = text(type_id, &path)?.to_string();
+            if type_id.is_empty() {
+                return Err(CcfError::new("empty type id", &path))
+            }
+            if self.typedefs.iter().any(|typedef| typedef.id == id) {
+                return Err(CcfError::new(&format!("type id 0x{} is defined twice", hex(&id)), &path))
+            }
+            if self.typedefs.iter().any(|typedef| typedef.type_id == type_id) {
+                return Err(CcfError::new(&format!("`{}` is defined twice", type_id), &path))
+            }
+            self.typedefs.push(Typedef { id, kind, is_interface, type_id, fields });
+        }
+        Ok(())
+    }
+
+    /// the composite type of a definition, without fields inside of itself. `resolving` are the definitions it is inside of
+    fn composite(&self, index: usize, resolving: &mut Vec<usize>) -> Result<CompositeType, CcfError> {
+        let typedef = &self.typedefs[index];
+        let mut composite = CompositeType {
+            kind: typedef.kind,
+            is_interface: typedef.is_interface,
+            type_id: typedef.type_id.clone(),
+            fields: Vec::new(),
+            initializers: Vec::new(),
+            raw_type: None,
+        };
+        if resolving.contains(&index) {
+            return Ok(composite)
+        }
+        resolving.push(index);
+        for (name, ty) in typedef.fields.iter() {
+            let ty = self.inline_type(ty, &format!("typedefs[{}].{}", index, name), resolving)?;
+            composite.fields.push((name.clone(), ty));
+        }
+        resolving.pop();
+        if composite.kind == CompositeKind::Enum {
+            composite.raw_type = composite.fields.iter().find(|(name, _)| name == "rawValue").map(|(_, ty)| Box::new(ty.clone()));
+        }
+        Ok(composite)
+    }
+
+    fn inline_type(&self, cbor: &Cbor, path: &str, resolving: &mut Vec<usize>) -> Result<Type, CcfError> {
+        let Cbor::Tag(tag, content) = cbor else {
+            return Err(CcfError::new(&format!("expected a type, got {}", cbor.kind()), path))
+        };
+        Ok(match *tag {
+            TYPE_REF => {
+                let id = bytes(content, path)?;
+                let Some(index) = self.typedefs.iter().position(|typedef| typedef.id == id) else {
+                    return Err(CcfError::new(&format!("no type is defined with id 0x{}", hex(id)), path))
+                };
+                Type::Composite(self.composite(index, resolving)?)
+            },
+            SIMPLE_TYPE => simple_type(unsigned(content, path)?, path)?,
+            OPTIONAL_TYPE => Type::Optional(Box::new(self.inline_type(content, path, resolving)?)),
+            VARSIZED_ARRAY_TYPE => Type::VariableSizedArray(Box::new(self.inline_type(content, path, resolving)?)),
+            CONSTSIZED_ARRAY_TYPE => {
+                let [size, ty] = tuple(content, path)?;
+                Type::ConstantSizedArray(Box::new(self.inline_type(ty, path, resolving)?), unsigned(size, path)?)
+            },
+            DICT_TYPE => {
+                let [key, value] = tuple(content, path)?;
+                Type::Dictionary(Box::new(self.inline_type(key, path, resolving)?), Box::new(self.inline_type(value, path, resolving)?))
+            },
+            REFERENCE_TYPE => {
+                let [authorization, ty] = tuple(content, path)?;
+                let authorization = decode_authorization(authorization, AUTHORIZATION_TYPES, path)?;
+                Type::Reference { authorization, ty: Box::new(self.inline_type(ty, path, resolving)?) }
+            },
+            INTERSECTION_TYPE => {
+                let [legacy, types] = tuple(content, path)?;
+                if *legacy != Cbor::Null {
+                    return Err(CcfError::new("restricted types from before Cadence 1.0 are not supported", path))
+                }
+                let types = array(types, path)?.iter()
+                    .map(|ty| self.inline_type(ty, path, resolving))
+                    .collect::<Result<_, _>>()?;
+                intersection(types)
+            },
+            CAPABILITY_TYPE => match &**content {
+                Cbor::Null => Type::Capability(None),
+                ty => Type::Capability(Some(Box::new(self.inline_type(ty, path, resolving)?))),
+            },
+            tag => return Err(CcfError::new(&format!("unknown type tag {}", tag), path)),
+        })
+    }
+
+    /// `composites` are the composite types decoded so far by their ids, which later ones refer to
+    fn type_value(&self, cbor: &Cbor, composites: &mut Vec<(Vec<u8>, CompositeType)>, path: &str) -> Result<Type, CcfError> {
+        let Cbor::Tag(tag, content) = cbor else {
+            return Err(CcfError::new(&format!("expected a type value, got {}", cbor.kind()), path))
+        };
+        if let Some((kind, is_interface)) = composite_kind(*tag, (COMPOSITE_TYPE_VALUE, INTERFACE_TYPE_VALUE)) {
+            let [id, type_id, raw_type, fields, initializers] = tuple(content, path)?;
+            let id = bytes(id, path)?.to_vec();
+            if composites.iter().any(|(defined, _)| *defined == id) {
+                return Err(CcfError::new(&format!("type id 0x{} is defined twice", hex(&id)), path))
+            }
+            let type_id = text(type_id, path)?.to_string();
+            if type_id.is_empty() {
+                return Err(CcfError::new("empty type id", path))
+            }
+            let mut composite = CompositeType { kind, is_interface, type_id, fields: Vec::new(), initializers: Vec::new(), raw_type: None };
+            composites.push((id, composite.clone()));
+            if *raw_type != Cbor::Null {
+                composite.raw_type = Some(Box::new(self.type_value(raw_type, composites, path)?));
+            }
+            for field in array(fields, path)?.iter() {
+                let [name, ty] = tuple(field, path)?;
+                let name = text(name, path)?.to_string();
+                if composite.fields.iter().any(|(field, _)| *field == name) {
+                    return Err(CcfError::new(&format!("duplicate field `{}`", name), path))
+                }
+                composite.fields.push((name, self.type_value(ty, composites, path)?));
+            }
+            for parameters in array(initializers, path)?.iter() {
+                composite.initializers.push(self.parameters(parameters, composites, path)?);
+            }
+            return Ok(Type::Composite(composite))
+        }
+        Ok(match *tag {
+            TYPE_VALUE_REF => {
+                let id = bytes(content, path)?;
+                let Some((_, composite)) = composites.iter().find(|(defined, _)| defined == id) else {
+                    return Err(CcfError::new(&format!("no type is defined with id 0x{}", hex(id)), path))
+                };
+                Type::Composite(composite.clone())
+            },
+            SIMPLE_TYPE_VALUE => simple_type(unsigned(content, path)?, path)?,
+            OPTIONAL_TYPE_VALUE => Type::Optional(Box::new(self.type_value(content, composites, path)?)),
+            VARSIZED_ARRAY_TYPE_VALUE => Type::VariableSizedArray(Box::new(self.type_value(content, composites, path)?)),
+            CONSTSIZED_ARRAY_TYPE_VALUE => {
+                let [size, ty] = tuple(content, path)?;
+                Type::ConstantSizedArray(Box::new(self.type_value(ty, composites, path)?), unsigned(size, path)?)
+            },
+            DICT_TYPE_VALUE => {
+                let [key, value] = tuple(content, path)?;
+                let key = self.type_value(key, composites, path)?;
+                Type::Dictionary(Box::new(key), Box::new(self.type_value(value, composites, path)?))
+            },
+            REFERENCE_TYPE_VALUE => {
+                let [authorization, ty] = tuple(content, path)?;
+                let authorization = decode_authorization(authorization, AUTHORIZATION_TYPE_VALUES, path)?;
+                Type::Reference { authorization, ty: Box::new(self.type_value(ty, composites, path)?) }
+            },
+            INTERSECTION_TYPE_VALUE => {
+                let [legacy, types] = tuple(content, path)?;
+                if *legacy != Cbor::Null {
+                    return Err(CcfError::new("restricted types from before Cadence 1.0 are not supported", path))
+                }
+                let types = array(types, path)?.iter()
+                    .map(|ty| self.type_value(ty, composites, path))
+                    .collect::<Result<_, _>>()?;
+                intersection(types)
+            },
+            CAPABILITY_TYPE_VALUE => match &**content {
+                Cbor::Null => Type::Capability(None),
+                ty => Type::Capability(Some(Box::new(self.type_value(ty, composites, path)?))),
+            },
+            FUNCTION_TYPE_VALUE => Type::Function(self.function_type(content, composites, path)?),
+            tag => return Err(CcfError::new(&format!("unknown type value tag {}", tag), path)),
+        })
+    }
+
+    fn function_type(&self, cbor: &Cbor, composites: &mut Vec<(Vec<u8>, CompositeType)>, path: &str) -> Result<FunctionType, CcfError> {
+        let [type_parameters, parameters, return_type] = tuple(cbor, path)?;
+        let mut function = FunctionType {
+            is_view: false,
+            type_parameters: Vec::new(),
+            parameters: Vec::new(),
+            return_type: Box::new(Type::simple("Void")),
+        };
+        for type_parameter in array(type_parameters, path)?.iter() {
+            let [name, bound] = tuple(type_parameter, path)?;
+            let bound = match bound {
+                Cbor::Null => None,
+                bound => Some(self.type_value(bound, composites, path)?),
+            };
+            function.type_parameters.push((text(name, path)?.to_string(), bound));
+        }
+        function.parameters = self.parameters(parameters, composites, path)?;
+        function.return_type = Box::new(self.type_value(return_type, composites, path)?);
+        Ok(function)
+    }
+
+    fn parameters(&self, cbor: &Cbor, composites: &mut Vec<(Vec<u8>, CompositeType)>, path: &str) -> Result<Vec<Parameter>, CcfError> {
+        let mut parameters = Vec::new();
+        for parameter in array(cbor, path)?.iter() {
+            let [label, identifier, ty] = tuple(parameter, path)?;
+            parameters.push(Parameter {
+                label: text(label, path)?.to_string(),
+                identifier: text(identifier, path)?.to_string(),
+                ty: self.type_value(ty, composites, path)?,
+            });
+        }
+        Ok(parameters)
+    }
+
+    fn value(&self, cbor: &Cbor, ty: &Type, path: &str) -> Result<Value, CcfError> {
+        if is_abstract(ty) {
+            let Cbor::Tag(TYPE_AND_VALUE, type_and_value) = cbor else {
+                let message = format!("expected a value of `{}` tagged with its type, got {}", describe(ty), cbor.kind());
+                return Err(CcfError::new(&message, path))
+            };
+            let [runtime, value] = tuple(type_and_value, path)?;
+            let runtime = self.inline_type(runtime, path, &mut Vec::new())?;
+            if is_abstract(&runtime) {
+                return Err(CcfError::new(&format!("the type of a value cannot be `{}`", describe(&runtime)), path))
+            }
+            return self.value(value, &runtime, path)
+        }
+        let expected = |what: &str| CcfError::new(&format!("expected {} for `{}`, got {}", what, describe(ty), cbor.kind()), path);
+        Ok(match ty {
+            Type::Simple(name) => match (name.as_str(), cbor) {
+                ("Void", Cbor::Null) => Value::Void,
+                ("Bool", Cbor::Bool(value)) => Value::Bool(*value),
+                ("String", Cbor::Text(value)) => Value::String(value.clone()),
+                ("Character", Cbor::Text(value)) => {
+                    if value.chars().count() != 1 {
+                        return Err(CcfError::new(&format!("expected a single character, got `{}`", value), path))
+                    }
+                    Value::Character(value.clone())
+                },
+                ("Address", cbor) => Value::Address(address(cbor, path)?),
+                ("Type", cbor) => Value::Type(self.type_value(cbor, &mut Vec::new(), path)?),
+                ("Function", cbor) => Value::Function(self.function_type(cbor, &mut Vec::new(), path)?),
+                (name, cbor) => {
+                    if let Some(number) = NumberType::from_name(name) {
+                        return Ok(Value::Number(decode_number(cbor, number, path)?))
+                    }
+                    let Some((domain, id, _)) = PATH_DOMAINS.iter().find(|(_, _, path_type)| *path_type == name) else {
+                        return Err(match name {
+                            "Void" | "Bool" | "String" | "Character" => expected(&format!("a `{}`", name)),
+                            name => CcfError::new(&format!("values of `{}` cannot be decoded", name), path),
+                        })
+                    };
+                    let [encoded, identifier] = tuple(cbor, path)?;
+                    if unsigned(encoded, path)? != *id {
+                        return Err(CcfError::new(&format!("expected the domain {} of `{}`", id, name), path))
+                    }
+                    let identifier = text(identifier, path)?;
+                    if !is_identifier(identifier) {
+                        return Err(CcfError::new(&format!("invalid path identifier `{}`", identifier), path))
+                    }
+                    Value::Path { domain: domain.to_string(), identifier: identifier.to_string() }
+                },
+            },
+            Type::Optional(ty) => match cbor {
+                Cbor::Null => Value::Optional(None),
+                cbor => Value::Optional(Some(Box::new(self.value(cbor, ty, path)?))),
+            },
+            Type::VariableSizedArray(ty) => Value::Array(self.array(cbor, ty, path)?),
+            Type::ConstantSizedArray(ty, size) => {
+                let values = self.array(cbor, ty, path)?;
+                if values.len() as u64 != *size {
+                    return Err(CcfError::new(&format!("expected {} elements, got {}", size, values.len()), path))
+                }
+                Value::Array(values)
+            },
+            Type::Dictionary(key_type, value_type) => {
+                let items = array(cbor, path)?;
+                if items.len() % 2 != 0 {
+                    return Err(expected("keys and values"))
+                }
+                let mut entries = Vec::new();
+                for (index, entry) in items.chunks(2).enumerate() {
+                    let path = format!("{}[{}]", path, index);
+                    let key = self.value(&entry[0], key_type, &child(&path, "key"))?;
+                    if !key.is_hashable() {
+                        return Err(CcfError::new(&format!("{} cannot be a key", kind(&key)), &child(&path, "key")))
+                    }
+                    entries.push((key, self.value(&entry[1], value_type, &child(&path, "value"))?));
+                }
+                Value::Dictionary(entries)
+            },
+            Type::Composite(composite) => {
+                let Some(index) = self.typedefs.iter().position(|typedef| typedef.type_id == composite.type_id) else {
+                    return Err(CcfError::new(&format!("`{}` is not defined", composite.type_id), path))
+                };
+                let types = self.composite(index, &mut Vec::new())?.fields;
+                let values = array(cbor, path)?;
+                if values.len() != types.len() {
+                    let message = format!("expected {} fields for `{}`, got {}", types.len(), composite.type_id, values.len());
+                    return Err(CcfError::new(&message, path))
+                }
+                let mut fields = Vec::new();
+                for ((name, ty), value) in types.iter().zip(values.iter()) {
+                    fields.push((name.clone(), self.value(value, ty, &child(path, name))?));
+                }
+                composite_value(composite.kind, Composite { type_id: composite.type_id.clone(), fields })
+            },
+            Type::Reference { ty, .. } => self.value(cbor, ty, path)?,
+            Type::Capability(Some(borrow_type)) => {
+                let [address_value, id] = tuple(cbor, path)?;
+                Value::Capability { id: unsigned(id, path)?, address: address(address_value, path)?, borrow_type: (**borrow_type).clone() }
+            },
+            Type::Function(_) => Value::Function(self.function_type(cbor, &mut Vec::new(), path)?),
+            // abstract
+            Type::Intersection { .. } | Type::Capability(None) => unreachable!(),
+        })
+    }
+
+    fn array(&self, cbor: &Cbor, ty: &Type, path: &str) -> Result<Vec<Value>, CcfError> {
+        array(cbor, path)?.iter().enumerate()
+            .map(|(index, value)| self.value(value, ty, &format!("{}[{}]", path, index)))
+            .collect()
+    }
+}
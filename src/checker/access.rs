@@ -0,0 +1,344 @@
+use std::collections::{
+    HashMap, HashSet,
+};
+
+use crate::ast::visit::{
+    self, Visitor,
+};
+use crate::ast::{
+    Access, CompositeDeclaration, CompositeKind, Expression, Identifier, InterfaceDeclaration, Program, Span,
+    Statement, TransactionDeclaration, VariableKind,
+};
+use crate::json_cadence::json::quote;
+
+use super::checker::{
+    TRANSACTION_TYPE, TypeCheck,
+};
+use super::diagnostic::Diagnostic;
+use super::types::{
+    Authorization, CompositeType, Member, MemberKind, Type, Types,
+};
+
+/// from where a declaration can be accessed, narrowest first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Visibility {
+    /// `access(self)` or `priv`: inside the declaring composite
+    Private,
+    /// `access(contract)`: inside the enclosing contract
+    Contract,
+    /// `access(account)`: inside the contracts of the same account
+    Account,
+    /// `pub` or `access(all)`: everywhere
+    Public,
+}
+
+impl Visibility {
+    /// a declaration without access modifier is private
+    pub fn of(access: &Access) -> Self {
+        match access {
+            Access::NotSpecified | Access::Priv | Access::Self_ => Self::Private,
+            Access::Contract => Self::Contract,
+            Access::Account => Self::Account,
+            // entitled members can be accessed through authorized references from anywhere
+            Access::Pub | Access::PubSet | Access::All | Access::Entitlements(_) => Self::Public,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Private => "private",
+            Self::Contract => "contract",
+            Self::Account => "account",
+            Self::Public => "public",
+        }
+    }
+}
+
+/// the access modifier as written, `access(self)` if there is none
+pub fn keyword(access: &Access) -> String {
+    match access {
+        Access::NotSpecified => Access::Self_.to_string(),
+        access => access.to_string(),
+    }
+}
+
+/// the entitlements of an `access(...)` modifier, e.g. `Withdraw | Deposit`
+fn entitlements(authorization: &Authorization) -> String {
+    match authorization {
+        Authorization::Conjunction(entitlements) => entitlements.join(", "),
+        Authorization::Disjunction(entitlements) => entitlements.join(" | "),
+        Authorization::Mapping(mapping) => format!("mapping {}", mapping),
+        Authorization::Unauthorized | Authorization::Auth => String::new(),
+    }
+}
+
+/// report reads, calls and writes of members from where their access modifiers do not allow it
+pub fn check(program: &Program, checked: &TypeCheck) -> Vec<Diagnostic> {
+    let names = checked.types.composites.values().map(|composite| (composite.span, composite.name.clone())).collect();
+    let mut checker = AccessChecker {
+        checked,
+        names,
+        current: Vec::new(),
+        writes: HashSet::new(),
+        diagnostics: Vec::new(),
+    };
+    checker.visit_program(program);
+    checker.diagnostics
+}
+
+struct AccessChecker<'a> {
+    checked: &'a TypeCheck,
+    /// qualified names of composites and interfaces, by the span of their name
+    names: HashMap<Span, String>,
+    /// qualified names of the composites around the current code, innermost last
+    current: Vec<String>,
+    /// spans of member expressions that are assigned or swapped
+    writes: HashSet<Span>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl AccessChecker<'_> {
+    fn types(&self) -> &Types {
+        &self.checked.types
+    }
+
+    fn nested(&mut self, name: Span, walk: impl FnOnce(&mut Self)) {
+        let name = self.names.get(&name).cloned();
+        let pushed = name.is_some();
+        if let Some(name) = name {
+            self.current.push(name);
+        }
+        walk(self);
+        if pushed {
+            self.current.pop();
+        }
+    }
+
+    /// whether the current code is inside `owner`, a composite nested in it, or a composite conforming to it
+    fn is_inside(&self, owner: &str) -> bool {
+        let Some(current) = self.current.last() else {
+            return false
+        };
+        current == owner ||
+            current.strip_prefix(owner).is_some_and(|rest| rest.starts_with('.')) ||
+            self.types().conforms(current, owner)
+    }
+
+    fn is_accessible(&self, owner: &str, access: &Access) -> bool {
+        match Visibility::of(access) {
+            Visibility::Public => true,
+            Visibility::Account => self.current.last().is_some_and(|current| current != TRANSACTION_TYPE) || self.is_inside(owner),
+            Visibility::Contract => {
+                let current = self.current.last().and_then(|current| contract_of(self.types(), current));
+                (current.is_some() && current == contract_of(self.types(), owner)) || self.is_inside(owner)
+            },
+            Visibility::Private => self.is_inside(owner),
+        }
+    }
+
+    fn member(&mut self, base: &Expression, identifier: &Identifier, span: Span) {
+        let Some(ty) = self.checked.expression_type(base) else {
+            return
+        };
+        let Some((owner, member)) = declared_member(self.types(), ty.unwrap_optional(), &identifier.name) else {
+            return
+        };
+        let owner_name = owner.name.clone();
+        if !self.is_accessible(&owner_name, &member.access) {
+            let message = format!("cannot access `{}`: it is `{}` in `{}`", member.name, keyword(&member.access), owner_name);
+            let diagnostic = Diagnostic::error(&message, identifier.span).with_note(&format!("`{}` declared here", member.name), member.span);
+            self.diagnostics.push(diagnostic);
+            return
+        }
+        // owned values hold every entitlement, references only those they are authorized for
+        if let Type::Reference { authorization, .. } = ty.unwrap_optional() {
+            let required = &member.entitlements;
+            if matches!(required, Authorization::Conjunction(_) | Authorization::Disjunction(_)) && !authorization.grants(required) {
+                let message = format!(
+                    "cannot access `{}` through `{}`: it requires a reference authorized for `{}`",
+                    member.name, ty.unwrap_optional(), entitlements(required),
+                );
+                let diagnostic = Diagnostic::error(&message, identifier.span).with_note(&format!("`{}` declared here", member.name), member.span);
+                self.diagnostics.push(diagnostic);
+                return
+            }
+        }
+        // writes of constant fields are reported by the type checker
+        let is_variable = member.kind == MemberKind::Field(VariableKind::Variable);
+        if self.writes.contains(&span) && is_variable && member.access != Access::PubSet && !self.is_inside(&owner_name) {
+            let message = format!(
+                "cannot write to field `{}` of `{}` from outside, it is `{}` and not `pub(set)`",
+                member.name, owner_name, keyword(&member.access),
+            );
+            let diagnostic = Diagnostic::error(&message, identifier.span).with_note(&format!("`{}` declared here", member.name), member.span);
+            self.diagnostics.push(diagnostic);
+        }
+    }
+}
+
+impl Visitor for AccessChecker<'_> {
+    fn visit_composite_declaration(&mut self, declaration: &CompositeDeclaration) {
+        self.nested(declaration.identifier.span, |checker| visit::walk_composite_declaration(checker, declaration));
+    }
+
+    fn visit_interface_declaration(&mut self, declaration: &InterfaceDeclaration) {
+        self.nested(declaration.identifier.span, |checker| visit::walk_interface_declaration(checker, declaration));
+    }
+
+    fn visit_transaction_declaration(&mut self, declaration: &TransactionDeclaration) {
+        self.current.push(TRANSACTION_TYPE.to_string());
+        visit::walk_transaction_declaration(self, declaration);
+        self.current.pop();
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Assignment { target, .. } => {
+                self.writes.insert(target.span());
+            },
+            Statement::Swap { left, right, .. } => {
+                self.writes.insert(left.span());
+                self.writes.insert(right.span());
+            },
+            _ => (),
+        }
+        visit::walk_statement(self, statement)
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let Expression::Member { expression: base, identifier, span, .. } = expression {
+            self.member(base, identifier, *span);
+        }
+        visit::walk_expression(self, expression)
+    }
+}
+
+/// the declared member of a composite, interface or reference to one, with the composite or interface declaring it.
+/// members of built-in types are public
+fn declared_member<'t>(types: &'t Types, ty: &Type, name: &str) -> Option<(&'t CompositeType, &'t Member)> {
+    match ty {
+        Type::Composite(composite) | Type::Interface(composite) => {
+            let composite = types.composite(composite)?;
+            if let Some(member) = composite.member(name) {
+                return Some((composite, member))
+            }
+            composite.conformances.iter().find_map(|interface| declared_member(types, &Type::Interface(interface.clone()), name))
+        },
+        Type::Restricted { ty, restrictions } => {
+            restrictions.iter()
+                .find_map(|interface| declared_member(types, &Type::Interface(interface.clone()), name))
+                .or_else(|| declared_member(types, ty, name))
+        },
+        Type::Reference { ty, .. } => declared_member(types, ty, name),
+        _ => None,
+    }
+}
+
+/// the qualified name of the contract a composite is declared in, or is
+fn contract_of(types: &Types, name: &str) -> Option<String> {
+    let outermost = name.split('.').next()?;
+    types.composite(outermost).filter(|composite| composite.kind == CompositeKind::Contract).map(|composite| composite.name.clone())
+}
+
+/// the externally accessible members of each contract of a program
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Surface {
+    pub contracts: Vec<ContractSurface>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractSurface {
+    pub name: String,
+    /// members of the contract and of the composites declared in it, in declaration order
+    pub members: Vec<SurfaceMember>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SurfaceMember {
+    /// qualified name, e.g. `Token.Vault.withdraw`
+    pub name: String,
+    pub kind: MemberKind,
+    pub access: Access,
+    /// the narrowest visibility of the member and the composites around it
+    pub visibility: Visibility,
+    pub ty: Type,
+}
+
+impl SurfaceMember {
+    /// whether the field can be written from outside
+    pub fn is_settable(&self) -> bool {
+        self.access == Access::PubSet && self.visibility == Visibility::Public
+    }
+}
+
+/// the fields and functions of the contracts of a checked program that are `access(account)` or wider,
+/// for reviewing what other code can call
+pub fn surface(checked: &TypeCheck) -> Surface {
+    let types = &checked.types;
+    let mut contracts: Vec<&CompositeType> = types.composites.values()
+        .filter(|composite| composite.kind == CompositeKind::Contract && !composite.is_interface && !composite.name.contains('.'))
+        .collect();
+    contracts.sort_by_key(|contract| contract.span.at);
+
+    let mut surface = Surface::default();
+    for contract in contracts {
+        let mut members = Vec::new();
+        surface_members(types, contract, Visibility::of(&contract.access), &mut members);
+        members.sort_by_key(|(at, _)| *at);
+        surface.contracts.push(ContractSurface {
+            name: contract.name.clone(),
+            members: members.into_iter().map(|(_, member)| member).collect(),
+        });
+    }
+    surface
+}
+
+/// the externally accessible members of the composite and the composites nested in it, by position
+fn surface_members(types: &Types, composite: &CompositeType, visibility: Visibility, members: &mut Vec<(usize, SurfaceMember)>) {
+    if composite.is_interface || composite.kind == CompositeKind::Event {
+        return
+    }
+    for member in composite.members.iter() {
+        let visibility = visibility.min(Visibility::of(&member.access));
+        if member.kind == MemberKind::EnumCase || visibility < Visibility::Account {
+            continue;
+        }
+        members.push((member.span.at, SurfaceMember {
+            name: format!("{}.{}", composite.name, member.name),
+            kind: member.kind,
+            access: member.access.clone(),
+            visibility,
+            ty: member.ty.clone(),
+        }));
+    }
+    for nested in composite.nested.iter().filter_map(|nested| types.composite(nested)) {
+        surface_members(types, nested, visibility.min(Visibility::of(&nested.access)), members);
+    }
+}
+
+impl Surface {
+    /// the report as JSON, one contract member per line
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n  \"contracts\": [");
+        for (index, contract) in self.contracts.iter().enumerate() {
+            json.push_str(if index > 0 { ",\n" } else { "\n" });
+            json.push_str(&format!("    {{\n      \"name\": {},\n      \"members\": [", quote(&contract.name)));
+            for (index, member) in contract.members.iter().enumerate() {
+                json.push_str(if index > 0 { ",\n" } else { "\n" });
+                let kind = match member.kind {
+                    MemberKind::Field(VariableKind::Constant) => "let",
+                    MemberKind::Field(VariableKind::Variable) => "var",
+                    MemberKind::Function { .. } | MemberKind::EnumCase => "fun",
+                };
+                json.push_str(&format!(
+                    "        {{\"name\": {}, \"kind\": {}, \"access\": {}, \"visibility\": {}, \"type\": {}, \"settable\": {}}}",
+                    quote(&member.name), quote(kind), quote(&keyword(&member.access)), quote(member.visibility.as_str()),
+                    quote(&member.ty.to_string()), member.is_settable(),
+                ));
+            }
+            json.push_str(if contract.members.is_empty() { "]\n    }" } else { "\n      ]\n    }" });
+        }
+        json.push_str(if self.contracts.is_empty() { "]\n}" } else { "\n  ]\n}" });
+        json
+    }
+}
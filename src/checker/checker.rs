@@ -0,0 +1,1778 @@
+use std::collections::HashMap;
+
+use crate::ast::{
+    self, Argument, BinaryOperation, Block, CastingOperation, CompositeDeclaration, CompositeKind, Condition,
+    Declaration, ElseBranch, EntitlementMappingDeclaration, Expression, FieldDeclaration, FunctionDeclaration,
+    Identifier, IfStatement, IfTest, InterfaceDeclaration, NominalType, Program, SpecialFunctionKind, Span, Statement,
+    TransactionDeclaration, TypeAnnotation, UnaryOperation, VariableDeclaration, VariableKind,
+};
+
+use super::access;
+use super::conformance::{
+    self, InheritedConditions,
+};
+use super::diagnostic::Diagnostic;
+use super::linearity;
+use super::resolver::{
+    Resolution, resolve,
+};
+use super::scope::{
+    SymbolId, SymbolKind,
+};
+use super::types::{
+    Authorization, CompositeType, EntitlementMapping, FunctionParameter, FunctionType, Member, MemberKind, NumberType,
+    PathType, Type, Types,
+};
+
+/// the name of the composite standing for a transaction, the type of `self` in it
+pub const TRANSACTION_TYPE: &str = "transaction";
+
+const DEFAULT_ARGUMENTS: &str = "only parameters of `ResourceDestroyed` events can have default arguments";
+
+/// types of a checked program
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeCheck {
+    pub resolution: Resolution,
+    pub types: Types,
+    /// types of variables, constants, parameters and functions
+    pub symbol_types: HashMap<SymbolId, Type>,
+    /// type of every checked expression, by span
+    pub expression_types: HashMap<Span, Type>,
+    /// type of every converted type annotation, by span
+    pub annotation_types: HashMap<Span, Type>,
+    /// interface conditions that also apply to the implementations of conforming composites
+    pub inherited_conditions: Vec<InheritedConditions>,
+    /// diagnostics of name resolution, type checking, interface conformance, access control, then resource tracking
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl TypeCheck {
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|diagnostic| diagnostic.is_error())
+    }
+
+    pub fn expression_type(&self, expression: &Expression) -> Option<&Type> {
+        self.expression_types.get(&expression.span())
+    }
+
+    pub fn annotation_type(&self, annotation: &TypeAnnotation) -> Option<&Type> {
+        self.annotation_types.get(&annotation.span)
+    }
+
+    pub fn symbol_type(&self, symbol: SymbolId) -> Option<&Type> {
+        self.symbol_types.get(&symbol)
+    }
+
+    /// the symbol the identifier at `span` refers to or declares
+    pub fn symbol_of(&self, span: Span) -> Option<SymbolId> {
+        self.resolution.references.iter().find(|reference| reference.span == span).map(|reference| reference.symbol)
+            .or_else(|| self.resolution.symbols.iter().position(|symbol| symbol.span == Some(span)))
+    }
+}
+
+/// resolve names, check the types of every declaration, statement and expression of `program`, the conformances
+/// of its composites and the access to their members, then track its resources
+pub fn check(program: &Program) -> TypeCheck {
+    let resolution = resolve(program);
+    let mut checker = Checker::new(&resolution);
+    checker.declare_composites(&program.declarations, None);
+    checker.define(&program.declarations);
+    for declaration in program.declarations.iter() {
+        checker.declaration(declaration);
+    }
+
+    let mut diagnostics = resolution.diagnostics.clone();
+    diagnostics.append(&mut checker.diagnostics);
+    let mut checked = TypeCheck {
+        types: checker.types,
+        symbol_types: checker.symbol_types,
+        expression_types: checker.expression_types,
+        annotation_types: checker.annotations,
+        resolution,
+        inherited_conditions: Vec::new(),
+        diagnostics,
+    };
+    // resources are only tracked through well-typed programs
+    let is_well_typed = checked.errors().next().is_none();
+    let (mut conformance, inherited_conditions) = conformance::check(program, &checked.types);
+    checked.diagnostics.append(&mut conformance);
+    checked.inherited_conditions = inherited_conditions;
+    let mut access = access::check(program, &checked);
+    checked.diagnostics.append(&mut access);
+    if is_well_typed {
+        let mut resources = linearity::check(program, &checked);
+        checked.diagnostics.append(&mut resources);
+    }
+    checked
+}
+
+/// what `return` and assignments to constant fields depend on
+#[derive(Clone, Debug)]
+struct FunctionContext {
+    return_type: Type,
+    /// inside `init` or `prepare`, where constant fields of `self` are assigned
+    is_initializer: bool,
+}
+
+struct Checker<'a> {
+    resolution: &'a Resolution,
+    /// symbols of used identifiers, by offset
+    references: HashMap<usize, SymbolId>,
+    /// symbols of declaring identifiers, by offset
+    declarations: HashMap<usize, SymbolId>,
+    types: Types,
+    /// qualified names of declared composites and interfaces
+    composite_names: HashMap<SymbolId, String>,
+    /// qualified names of declared entitlements and entitlement mappings
+    entitlement_names: HashMap<SymbolId, String>,
+    /// converted type annotations, each is converted and reported once
+    annotations: HashMap<Span, Type>,
+    symbol_types: HashMap<SymbolId, Type>,
+    expression_types: HashMap<Span, Type>,
+    diagnostics: Vec<Diagnostic>,
+    self_type: Option<Type>,
+    function: Option<FunctionContext>,
+    /// loops and switches around the current statement, `true` for loops
+    breakable: Vec<bool>,
+    /// whether the declarations are nested in a contract interface, whose composites are type requirements
+    in_contract_interface: bool,
+}
+
+impl<'a> Checker<'a> {
+    fn new(resolution: &'a Resolution) -> Self {
+        let references = resolution.references.iter().map(|reference| (reference.span.at, reference.symbol)).collect();
+        let declarations = resolution.symbols.iter().enumerate()
+            .filter_map(|(id, symbol)| symbol.span.map(|span| (span.at, id)))
+            .collect();
+        let mut types = Types::default();
+        let mut entitlement_names = HashMap::new();
+        // built-in entitlements are the only ones declared without a declaration
+        for (id, symbol) in resolution.symbols.iter().enumerate() {
+            if symbol.kind == SymbolKind::Entitlement && symbol.span.is_none() {
+                types.entitlements.insert(symbol.name.clone(), Span::default());
+                entitlement_names.insert(id, symbol.name.clone());
+            }
+        }
+        Checker {
+            resolution,
+            references,
+            declarations,
+            types,
+            composite_names: HashMap::new(),
+            entitlement_names,
+            annotations: HashMap::new(),
+            symbol_types: HashMap::new(),
+            expression_types: HashMap::new(),
+            diagnostics: Vec::new(),
+            self_type: None,
+            function: None,
+            breakable: Vec::new(),
+            in_contract_interface: false,
+        }
+    }
+
+    fn error(&mut self, message: &str, span: Span) {
+        self.diagnostics.push(Diagnostic::error(message, span));
+    }
+
+    fn warning(&mut self, message: &str, span: Span) {
+        self.diagnostics.push(Diagnostic::warning(message, span));
+    }
+
+    fn mismatch(&mut self, expected: &Type, actual: &Type, span: Span) {
+        self.error(&format!("mismatched types: expected `{}`, got `{}`", expected, actual), span);
+    }
+
+    fn is_subtype(&self, sub: &Type, sup: &Type) -> bool {
+        self.types.is_subtype(sub, sup)
+    }
+
+    fn declare_type(&mut self, identifier: &Identifier, ty: Type) {
+        if let Some(symbol) = self.declarations.get(&identifier.span.at) {
+            self.symbol_types.insert(*symbol, ty);
+        }
+    }
+
+    fn composite_type(&self, name: &str) -> Type {
+        match self.types.composite(name) {
+            Some(composite) if composite.is_interface => Type::Interface(name.to_string()),
+            _ => Type::Composite(name.to_string()),
+        }
+    }
+
+    fn composite_mut(&mut self, name: &str) -> &mut CompositeType {
+        self.types.composites.get_mut(name).expect("composite declared before it is defined")
+    }
+
+    fn qualified_name(&self, identifier: &Identifier) -> String {
+        self.declarations.get(&identifier.span.at)
+            .and_then(|symbol| self.composite_names.get(symbol))
+            .cloned()
+            .unwrap_or_else(|| identifier.name.clone())
+    }
+
+    // declaration of types
+
+    /// register composites, interfaces and entitlements by qualified name, returning the names of the composites
+    /// and interfaces
+    fn declare_composites(&mut self, declarations: &[Declaration], outer: Option<&str>) -> Vec<String> {
+        let mut names = Vec::new();
+        for declaration in declarations.iter() {
+            let (identifier, kind, is_interface, access, members) = match declaration {
+                Declaration::Entitlement(d) => {
+                    let name = self.declare_entitlement(&d.identifier, outer);
+                    self.types.entitlements.insert(name, d.identifier.span);
+                    continue;
+                },
+                Declaration::EntitlementMapping(d) => {
+                    let name = self.declare_entitlement(&d.identifier, outer);
+                    let mapping = EntitlementMapping { relations: Vec::new(), includes: Vec::new() };
+                    self.types.entitlement_mappings.insert(name, mapping);
+                    continue;
+                },
+                Declaration::Composite(d) => (&d.identifier, d.kind, false, &d.access, &d.members),
+                Declaration::Interface(d) => (&d.identifier, d.kind, true, &d.access, &d.members),
+                Declaration::Transaction(d) => {
+                    let composite = CompositeType::new(TRANSACTION_TYPE, CompositeKind::Structure, false, ast::Access::NotSpecified, d.span);
+                    self.types.composites.entry(TRANSACTION_TYPE.to_string()).or_insert(composite);
+                    continue;
+                },
+                _ => continue,
+            };
+            let name = match outer {
+                Some(outer) => format!("{}.{}", outer, identifier.name),
+                None => identifier.name.clone(),
+            };
+            if let Some(symbol) = self.declarations.get(&identifier.span.at) {
+                self.composite_names.insert(*symbol, name.clone());
+            }
+            let mut composite = CompositeType::new(&name, kind, is_interface, access.clone(), identifier.span);
+            composite.nested = self.declare_composites(members, Some(&name));
+            self.types.composites.insert(name.clone(), composite);
+            names.push(name);
+        }
+        names
+    }
+
+    fn declare_entitlement(&mut self, identifier: &Identifier, outer: Option<&str>) -> String {
+        let name = match outer {
+            Some(outer) => format!("{}.{}", outer, identifier.name),
+            None => identifier.name.clone(),
+        };
+        if let Some(symbol) = self.declarations.get(&identifier.span.at) {
+            self.entitlement_names.insert(*symbol, name.clone());
+        }
+        name
+    }
+
+    /// fill in members and conformances, and the types of functions and variables declared at the top level
+    fn define(&mut self, declarations: &[Declaration]) {
+        for declaration in declarations.iter() {
+            match declaration {
+                Declaration::Composite(d) => self.define_composite(d),
+                Declaration::Interface(d) => self.define_interface(d),
+                Declaration::Function(d) => {
+                    let ty = Type::Function(self.function_type(d));
+                    self.declare_type(&d.identifier, ty);
+                },
+                Declaration::EntitlementMapping(d) => self.define_entitlement_mapping(d),
+                Declaration::Transaction(d) => {
+                    for parameter in d.parameters.iter() {
+                        let ty = self.annotation(&parameter.type_annotation);
+                        self.declare_type(&parameter.identifier, ty);
+                    }
+                    let members = d.fields.iter().map(|field| self.field(field)).collect();
+                    self.composite_mut(TRANSACTION_TYPE).members = members;
+                },
+                _ => (),
+            }
+        }
+    }
+
+    fn define_composite(&mut self, declaration: &CompositeDeclaration) {
+        let name = self.qualified_name(&declaration.identifier);
+        let keyword = declaration.kind.keyword();
+
+        let mut conformances = Vec::new();
+        let mut raw_type = None;
+        if declaration.kind == CompositeKind::Enum {
+            if let Some(raw) = declaration.raw_type() {
+                let ty = self.nominal(raw);
+                if !ty.is_unknown() && !ty.as_number().is_some_and(|number| number.is_integer() && !number.is_abstract()) {
+                    self.error(&format!("enum raw type must be an integer type, got `{}`", ty), raw.span());
+                }
+                raw_type = Some(ty);
+            }
+        } else {
+            conformances = self.conformances(&declaration.conformances, declaration.kind, keyword, &declaration.identifier);
+        }
+
+        let mut members = self.members(&declaration.members, &name);
+        let mut initializer = self.initializer(&declaration.members);
+        if declaration.kind == CompositeKind::Event {
+            initializer = Vec::new();
+            for parameter in declaration.parameters.iter() {
+                if let Some(default) = parameter.default.as_ref().filter(|_| declaration.identifier.name != "ResourceDestroyed") {
+                    self.error(DEFAULT_ARGUMENTS, default.span());
+                }
+                let ty = self.annotation(&parameter.type_annotation);
+                self.declare_type(&parameter.identifier, ty.clone());
+                initializer.push(FunctionParameter { label: parameter.argument_label().map(str::to_string), ty: ty.clone() });
+                members.push(Member {
+                    name: parameter.identifier.name.clone(),
+                    kind: MemberKind::Field(VariableKind::Constant),
+                    ty,
+                    access: ast::Access::Pub,
+                    entitlements: Authorization::Unauthorized,
+                    span: parameter.identifier.span,
+                });
+            }
+        }
+
+        let composite = self.composite_mut(&name);
+        composite.conformances = conformances;
+        composite.raw_type = raw_type;
+        composite.members = members;
+        composite.initializer = initializer;
+        self.define(&declaration.members);
+    }
+
+    fn define_interface(&mut self, declaration: &InterfaceDeclaration) {
+        let name = self.qualified_name(&declaration.identifier);
+        let conformances = self.conformances(&declaration.conformances, declaration.kind, "interface", &declaration.identifier);
+        let members = self.members(&declaration.members, &name);
+        let initializer = self.initializer(&declaration.members);
+
+        let composite = self.composite_mut(&name);
+        composite.conformances = conformances;
+        composite.members = members;
+        composite.initializer = initializer;
+        self.define(&declaration.members);
+    }
+
+    fn define_entitlement_mapping(&mut self, declaration: &EntitlementMappingDeclaration) {
+        let mut relations = Vec::new();
+        for relation in declaration.relations.iter() {
+            let input = self.entitlement(&relation.input, false);
+            let output = self.entitlement(&relation.output, false);
+            if let (Some(input), Some(output)) = (input, output) {
+                relations.push((input, output));
+            }
+        }
+        let includes = declaration.includes.iter().filter_map(|include| self.entitlement(include, true)).collect();
+        let Some(name) = self.declarations.get(&declaration.identifier.span.at).and_then(|symbol| self.entitlement_names.get(symbol)) else {
+            return
+        };
+        let mapping = EntitlementMapping { relations, includes };
+        self.types.entitlement_mappings.insert(name.clone(), mapping);
+    }
+
+    /// qualified names of the declared interfaces conformed to, imported ones are not checked
+    fn conformances(&mut self, conformances: &[NominalType], kind: CompositeKind, what: &str, identifier: &Identifier) -> Vec<String> {
+        let mut names = Vec::new();
+        for conformance in conformances.iter() {
+            match self.nominal(conformance) {
+                Type::Interface(interface) => {
+                    let interface_kind = self.types.composite(&interface).map(|interface| interface.kind);
+                    if interface_kind.is_some_and(|interface_kind| interface_kind != kind) {
+                        let message = format!(
+                            "{} `{}` cannot conform to {} interface `{}`",
+                            what, identifier.name, interface_kind.unwrap().keyword(), interface,
+                        );
+                        self.error(&message, conformance.span());
+                    }
+                    names.push(interface);
+                },
+                ty if ty.is_unknown() => (),
+                ty => self.error(&format!("`{}` is not an interface", ty), conformance.span()),
+            }
+        }
+        names
+    }
+
+    fn members(&mut self, declarations: &[Declaration], composite: &str) -> Vec<Member> {
+        // only references to structs and resources are authorized
+        let entitled = self.types.composite(composite)
+            .is_some_and(|composite| matches!(composite.kind, CompositeKind::Structure | CompositeKind::Resource));
+        let mut members = Vec::new();
+        for declaration in declarations.iter() {
+            let mut member = match declaration {
+                Declaration::Field(d) => self.field(d),
+                Declaration::Function(d) => Member {
+                    name: d.identifier.name.clone(),
+                    kind: MemberKind::Function { has_body: has_implementation(d) },
+                    ty: Type::Function(self.function_type(d)),
+                    access: d.access.clone(),
+                    entitlements: Authorization::Unauthorized,
+                    span: d.identifier.span,
+                },
+                Declaration::EnumCase(d) => Member {
+                    name: d.identifier.name.clone(),
+                    kind: MemberKind::EnumCase,
+                    ty: Type::Composite(composite.to_string()),
+                    access: d.access.clone(),
+                    entitlements: Authorization::Unauthorized,
+                    span: d.identifier.span,
+                },
+                _ => continue,
+            };
+            if member.kind != MemberKind::EnumCase && entitled {
+                member.entitlements = self.required_entitlements(&member.access);
+            } else {
+                self.unentitled_access(&member.access);
+            }
+            members.push(member);
+        }
+        members
+    }
+
+    /// transaction fields cannot require entitlements, members of composites get theirs in `members`
+    fn field(&mut self, declaration: &FieldDeclaration) -> Member {
+        Member {
+            name: declaration.identifier.name.clone(),
+            kind: MemberKind::Field(declaration.variable_kind),
+            ty: self.annotation(&declaration.type_annotation),
+            access: declaration.access.clone(),
+            entitlements: Authorization::Unauthorized,
+            span: declaration.identifier.span,
+        }
+    }
+
+    /// what a reference must be authorized for to access a member declared with `access`
+    fn required_entitlements(&mut self, access: &ast::Access) -> Authorization {
+        match access {
+            ast::Access::Entitlements(authorization) => self.authorization(authorization),
+            _ => Authorization::Unauthorized,
+        }
+    }
+
+    /// report entitlement access where no reference could be authorized for it
+    fn unentitled_access(&mut self, access: &ast::Access) {
+        if let ast::Access::Entitlements(authorization) = access {
+            let message = "entitlement access is only allowed on fields and functions of structs, resources and their interfaces";
+            self.error(message, authorization.span());
+        }
+    }
+
+    /// the qualified names of the entitlements, which must be declared
+    fn authorization(&mut self, authorization: &ast::Authorization) -> Authorization {
+        match authorization {
+            ast::Authorization::Conjunction(entitlements) => {
+                Authorization::Conjunction(entitlements.iter().filter_map(|entitlement| self.entitlement(entitlement, false)).collect())
+            },
+            ast::Authorization::Disjunction(entitlements) => {
+                Authorization::Disjunction(entitlements.iter().filter_map(|entitlement| self.entitlement(entitlement, false)).collect())
+            },
+            ast::Authorization::Mapping(mapping) => match self.entitlement(mapping, true) {
+                Some(mapping) => Authorization::Mapping(mapping),
+                None => Authorization::Auth,
+            },
+        }
+    }
+
+    /// the qualified name of an entitlement, or an entitlement mapping if `is_mapping`.
+    /// imported ones are not checked
+    fn entitlement(&mut self, nominal: &NominalType, is_mapping: bool) -> Option<String> {
+        let &symbol = self.references.get(&nominal.identifier.span.at)?;
+        let (expected, what) = if is_mapping {
+            (SymbolKind::EntitlementMapping, "entitlement mapping")
+        } else {
+            (SymbolKind::Entitlement, "entitlement")
+        };
+        let name = match self.resolution.symbol(symbol).kind {
+            SymbolKind::Import => return Some(nominal.name()),
+            kind if kind == expected && nominal.nested_identifiers.is_empty() => self.entitlement_names.get(&symbol).cloned(),
+            // declared in a contract, e.g. `Token.Withdraw`
+            SymbolKind::Composite(_) => {
+                let outer = self.composite_names.get(&symbol).cloned().unwrap_or_else(|| nominal.identifier.name.clone());
+                let nested: Vec<&str> = nominal.nested_identifiers.iter().map(|nested| nested.name.as_str()).collect();
+                Some(format!("{}.{}", outer, nested.join(".")))
+            },
+            _ => None,
+        };
+        let declared = name.filter(|name| if is_mapping {
+            self.types.entitlement_mappings.contains_key(name)
+        } else {
+            self.types.entitlements.contains_key(name)
+        });
+        if declared.is_none() {
+            self.error(&format!("`{}` is not an {}", nominal.name(), what), nominal.span());
+        }
+        declared
+    }
+
+    fn initializer(&mut self, declarations: &[Declaration]) -> Vec<FunctionParameter> {
+        let init = declarations.iter().find_map(|declaration| match declaration {
+            Declaration::SpecialFunction(d) if d.kind == SpecialFunctionKind::Initializer => Some(&d.function),
+            _ => None,
+        });
+        match init {
+            Some(init) => self.function_type(init).parameters,
+            None => Vec::new(),
+        }
+    }
+
+    fn function_type(&mut self, declaration: &FunctionDeclaration) -> FunctionType {
+        let parameters = declaration.parameters.iter()
+            .map(|parameter| FunctionParameter {
+                label: parameter.argument_label().map(str::to_string),
+                ty: self.annotation(&parameter.type_annotation),
+            })
+            .collect();
+        let return_type = match &declaration.return_type {
+            Some(return_type) => self.annotation(return_type),
+            None => Type::Void,
+        };
+        FunctionType::new(parameters, return_type)
+    }
+
+    // conversion of type annotations
+
+    /// the type of an annotation, which must have `@` exactly for resource types
+    fn annotation(&mut self, annotation: &TypeAnnotation) -> Type {
+        if let Some(ty) = self.annotations.get(&annotation.span) {
+            return ty.clone()
+        }
+        let mut ty = self.convert(&annotation.ty);
+        if annotation.is_resource {
+            mark_resource(&mut ty);
+        }
+        if !contains_invalid(&ty) {
+            let is_resource = self.types.is_resource(&ty);
+            if annotation.is_resource && !is_resource {
+                self.error(&format!("invalid resource annotation `@` on non-resource type `{}`", ty), annotation.span);
+            } else if !annotation.is_resource && is_resource {
+                self.error(&format!("missing resource annotation `@` on resource type `{}`", ty), annotation.span);
+            }
+        }
+        self.annotations.insert(annotation.span, ty.clone());
+        ty
+    }
+
+    fn convert(&mut self, ty: &ast::Type) -> Type {
+        match ty {
+            ast::Type::Nominal(nominal) => self.nominal(nominal),
+            ast::Type::Optional { ty, .. } => Type::optional(self.convert(ty)),
+            ast::Type::VariableSized { ty, .. } => Type::VariableSized(Box::new(self.convert(ty))),
+            ast::Type::ConstantSized { ty, size, .. } => Type::ConstantSized(Box::new(self.convert(ty)), *size),
+            ast::Type::Dictionary { key, value, .. } => {
+                Type::Dictionary(Box::new(self.convert(key)), Box::new(self.convert(value)))
+            },
+            ast::Type::Function { parameters, return_type, .. } => {
+                let parameters = parameters.iter()
+                    .map(|parameter| FunctionParameter { label: None, ty: self.annotation(parameter) })
+                    .collect();
+                Type::Function(FunctionType::new(parameters, self.annotation(return_type)))
+            },
+            ast::Type::Reference { authorized, entitlements, ty, .. } => {
+                let authorization = match entitlements {
+                    Some(entitlements) => self.authorization(entitlements),
+                    None if *authorized => Authorization::Auth,
+                    None => Authorization::Unauthorized,
+                };
+                Type::Reference { authorization, ty: Box::new(self.convert(ty)) }
+            },
+            ast::Type::Restricted { ty, restrictions, .. } => {
+                let mut names = Vec::new();
+                let mut is_resource = false;
+                for restriction in restrictions.iter() {
+                    match self.nominal(restriction) {
+                        Type::Interface(interface) => {
+                            is_resource |= self.types.composite(&interface).is_some_and(|c| c.is_resource());
+                            names.push(interface);
+                        },
+                        ty if ty.is_unknown() => names.push(restriction.name()),
+                        ty => self.error(&format!("restricted types can only be restricted by interfaces, got `{}`", ty), restriction.span()),
+                    }
+                }
+                let ty = match ty {
+                    Some(ty) => self.convert(ty),
+                    None if is_resource => Type::AnyResource,
+                    None => Type::AnyStruct,
+                };
+                Type::Restricted { ty: Box::new(ty), restrictions: names }
+            },
+            ast::Type::Instantiation { ty, type_arguments, span } => {
+                let base = self.convert(ty);
+                let arguments: Vec<Type> = type_arguments.iter().map(|argument| self.annotation(argument)).collect();
+                match (base, arguments.as_slice()) {
+                    (Type::Capability(None), [argument]) => Type::Capability(Some(Box::new(argument.clone()))),
+                    (base, _) if base.is_unknown() => base,
+                    (base, _) => {
+                        self.error(&format!("type `{}` has no type parameters for the {} type arguments", base, arguments.len()), *span);
+                        base
+                    },
+                }
+            },
+        }
+    }
+
+    fn nominal(&mut self, nominal: &NominalType) -> Type {
+        let Some(&symbol) = self.references.get(&nominal.identifier.span.at) else {
+            // reported by the resolver
+            return Type::Invalid
+        };
+        match self.resolution.symbol(symbol).kind {
+            SymbolKind::BuiltInType if nominal.nested_identifiers.is_empty() => built_in_type(&nominal.identifier.name),
+            SymbolKind::BuiltInType => Type::Other(nominal.name()),
+            SymbolKind::Import => Type::Imported { name: nominal.name(), is_resource: false },
+            SymbolKind::Composite(_) | SymbolKind::Interface(_) => {
+                let mut name = self.composite_names.get(&symbol).cloned().unwrap_or_else(|| nominal.identifier.name.clone());
+                for nested in nominal.nested_identifiers.iter() {
+                    let qualified = format!("{}.{}", name, nested.name);
+                    if !self.types.composite(&name).is_some_and(|composite| composite.nested.contains(&qualified)) {
+                        self.error(&format!("type `{}` has no nested type `{}`", name, nested.name), nested.span);
+                        return Type::Invalid
+                    }
+                    name = qualified;
+                }
+                self.composite_type(&name)
+            },
+            _ => Type::Invalid,
+        }
+    }
+
+    // declarations
+
+    fn declaration(&mut self, declaration: &Declaration) {
+        match declaration {
+            Declaration::Composite(d) => {
+                self.unentitled_access(&d.access);
+                let name = self.qualified_name(&d.identifier);
+                self.composite(&name, d.kind, &d.members);
+                if d.kind != CompositeKind::Event && d.kind != CompositeKind::Enum {
+                    self.field_initialization(&name, &d.members, &d.members);
+                }
+            },
+            Declaration::Interface(d) => {
+                self.unentitled_access(&d.access);
+                let name = self.qualified_name(&d.identifier);
+                let outer = self.in_contract_interface;
+                self.in_contract_interface |= d.kind == CompositeKind::Contract;
+                self.composite(&name, d.kind, &d.members);
+                self.in_contract_interface = outer;
+            },
+            Declaration::Function(d) => {
+                // the access of members is checked with the other members
+                if self.self_type.is_none() {
+                    self.unentitled_access(&d.access);
+                }
+                self.function(d, false)
+            },
+            Declaration::SpecialFunction(d) => self.function(&d.function, d.kind != SpecialFunctionKind::Destructor),
+            Declaration::Variable(d) => {
+                self.unentitled_access(&d.access);
+                self.variable(d)
+            },
+            Declaration::Transaction(d) => self.transaction(d),
+            Declaration::Entitlement(ast::EntitlementDeclaration { access, .. }) |
+            Declaration::EntitlementMapping(EntitlementMappingDeclaration { access, .. }) => self.unentitled_access(access),
+            Declaration::Import(_) | Declaration::Field(_) | Declaration::EnumCase(_) | Declaration::Error(_) => (),
+        }
+    }
+
+    fn composite(&mut self, name: &str, kind: CompositeKind, members: &[Declaration]) {
+        if matches!(kind, CompositeKind::Structure | CompositeKind::Event) {
+            let fields: Vec<Member> = self.types.composite(name).map(|c| c.members.clone()).unwrap_or_default();
+            for field in fields.iter().filter(|member| matches!(member.kind, MemberKind::Field(_))) {
+                if self.types.is_resource(&field.ty) {
+                    let message = format!("{} `{}` cannot have resource field `{}`", kind.keyword(), name, field.name);
+                    self.error(&message, field.span);
+                }
+            }
+        }
+        let outer = self.self_type.replace(self.composite_type(name));
+        for member in members.iter() {
+            self.declaration(member);
+        }
+        self.self_type = outer;
+    }
+
+    /// every field must be assigned in `init`, or `prepare` for transactions
+    fn field_initialization(&mut self, name: &str, members: &[Declaration], functions: &[Declaration]) {
+        let fields: Vec<&Identifier> = members.iter()
+            .filter_map(|member| match member {
+                Declaration::Field(d) => Some(&d.identifier),
+                _ => None,
+            })
+            .collect();
+        let initializer = functions.iter().find_map(|declaration| match declaration {
+            Declaration::SpecialFunction(d) if d.kind != SpecialFunctionKind::Destructor => Some(&d.function),
+            _ => None,
+        });
+        // type requirements leave initialization to the conforming contracts' composites
+        if self.in_contract_interface && !initializer.is_some_and(has_implementation) {
+            return
+        }
+        let initializer = initializer.and_then(|function| function.body.as_ref());
+        let mut assigned = Vec::new();
+        if let Some(body) = initializer {
+            assigned_fields(&body.statements, &mut assigned);
+        }
+        for field in fields {
+            if !assigned.contains(&field.name.as_str()) {
+                self.error(&format!("missing initialization of field `{}` in type `{}`", field.name, name), field.span);
+            }
+        }
+    }
+
+    fn transaction(&mut self, declaration: &TransactionDeclaration) {
+        let outer = self.self_type.replace(Type::Composite(TRANSACTION_TYPE.to_string()));
+        if let Some(prepare) = &declaration.prepare {
+            self.function(&prepare.function, true);
+        }
+        let fields: Vec<Declaration> = declaration.fields.iter().cloned().map(Declaration::Field).collect();
+        let prepare: Vec<Declaration> = declaration.prepare.iter().cloned().map(Declaration::SpecialFunction).collect();
+        self.field_initialization(TRANSACTION_TYPE, &fields, &prepare);
+
+        let context = FunctionContext { return_type: Type::Void, is_initializer: false };
+        let outer_function = self.function.replace(context);
+        self.conditions(&declaration.pre_conditions);
+        if let Some(execute) = &declaration.execute {
+            self.block(execute);
+        }
+        self.conditions(&declaration.post_conditions);
+        self.function = outer_function;
+        self.self_type = outer;
+    }
+
+    fn function(&mut self, declaration: &FunctionDeclaration, is_initializer: bool) {
+        let function = self.function_type(declaration);
+        for (parameter, ty) in declaration.parameters.iter().zip(function.parameters.iter()) {
+            self.declare_type(&parameter.identifier, ty.ty.clone());
+            if let Some(default) = &parameter.default {
+                self.error(DEFAULT_ARGUMENTS, default.span());
+            }
+        }
+        let return_type = (*function.return_type).clone();
+        let context = FunctionContext { return_type: return_type.clone(), is_initializer };
+        let outer = self.function.replace(context);
+        let outer_breakable = std::mem::take(&mut self.breakable);
+
+        self.conditions(&declaration.pre_conditions);
+        self.conditions(&declaration.post_conditions);
+        if let Some(body) = &declaration.body {
+            self.statements(&body.statements);
+            let returns_value = !matches!(return_type, Type::Void | Type::Never | Type::Invalid);
+            // interface functions and type requirements with only conditions have no body to return from
+            let in_interface = matches!(self.self_type, Some(Type::Interface(_))) || self.in_contract_interface;
+            let is_requirement = in_interface && !has_implementation(declaration);
+            if returns_value && !is_requirement && !self.returns(&body.statements) {
+                self.error("missing return statement", declaration.identifier.span);
+            }
+        }
+
+        self.breakable = outer_breakable;
+        self.function = outer;
+    }
+
+    fn conditions(&mut self, conditions: &[Condition]) {
+        for condition in conditions.iter() {
+            self.expect(&condition.test, &Type::Bool);
+            if let Some(message) = &condition.message {
+                self.expect(message, &Type::String);
+            }
+        }
+    }
+
+    /// check the value, declare the variable with the annotated or inferred type
+    fn variable(&mut self, declaration: &VariableDeclaration) {
+        let ty = match &declaration.type_annotation {
+            Some(annotation) => {
+                let ty = self.annotation(annotation);
+                self.expect(&declaration.value, &ty);
+                ty
+            },
+            None => {
+                let ty = self.expression(&declaration.value, None);
+                if ty == Type::nil() {
+                    self.error("cannot infer the type of `nil`, add a type annotation", declaration.value.span());
+                }
+                ty
+            },
+        };
+        // the second value replaces the first, which is moved into the variable
+        if let Some((_, value)) = &declaration.second {
+            let target = self.expression_types.get(&declaration.value.span()).cloned().unwrap_or(Type::Invalid);
+            self.expect(value, &target);
+        }
+        self.declare_type(&declaration.identifier, ty);
+    }
+
+    // statements
+
+    fn block(&mut self, block: &Block) {
+        self.statements(&block.statements);
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements.iter() {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Expression(expression) => {
+                self.expression(expression, None);
+            },
+            Statement::Return { expression, span } => {
+                let return_type = self.function.as_ref().map_or(Type::Void, |function| function.return_type.clone());
+                match expression {
+                    Some(expression) if return_type == Type::Void => {
+                        self.expression(expression, None);
+                        self.error("unexpected return value in function returning `Void`", expression.span());
+                    },
+                    Some(expression) => {
+                        self.expect(expression, &return_type);
+                    },
+                    None if !matches!(return_type, Type::Void | Type::Invalid) => {
+                        self.error(&format!("missing return value of type `{}`", return_type), *span);
+                    },
+                    None => (),
+                }
+            },
+            Statement::Break { span } => {
+                if self.breakable.is_empty() {
+                    self.error("`break` outside of a loop or switch", *span);
+                }
+            },
+            Statement::Continue { span } => {
+                if !self.breakable.contains(&true) {
+                    self.error("`continue` outside of a loop", *span);
+                }
+            },
+            Statement::Variable(declaration) => self.variable(declaration),
+            Statement::Assignment { target, value, .. } => {
+                let ty = self.assignment_target(target);
+                self.expect(value, &ty);
+            },
+            Statement::Swap { left, right, span } => {
+                let left = self.assignment_target(left);
+                let right = self.assignment_target(right);
+                if !self.is_subtype(&left, &right) || !self.is_subtype(&right, &left) {
+                    self.error(&format!("cannot swap `{}` and `{}`", left, right), *span);
+                }
+            },
+            Statement::If(statement) => self.if_statement(statement),
+            Statement::While { test, block, .. } => {
+                self.expect(test, &Type::Bool);
+                self.breakable.push(true);
+                self.block(block);
+                self.breakable.pop();
+            },
+            Statement::For { index, identifier, value, block, .. } => {
+                let ty = self.expression(value, None);
+                let element = match ty.dereference() {
+                    Type::VariableSized(element) | Type::ConstantSized(element, _) => (**element).clone(),
+                    ty if ty.is_unknown() => Type::Invalid,
+                    ty => {
+                        self.error(&format!("cannot iterate over value of type `{}`", ty), value.span());
+                        Type::Invalid
+                    },
+                };
+                if let Some(index) = index {
+                    self.declare_type(index, Type::Number(NumberType::Int));
+                }
+                self.declare_type(identifier, element);
+                self.breakable.push(true);
+                self.block(block);
+                self.breakable.pop();
+            },
+            Statement::Switch { expression, cases, .. } => {
+                let ty = self.expression(expression, None);
+                self.breakable.push(false);
+                for case in cases.iter() {
+                    for value in case.expressions.iter() {
+                        let value_ty = self.expression(value, Some(&ty));
+                        if !self.is_subtype(&value_ty, &ty) && !self.is_subtype(&ty, &value_ty) {
+                            self.error(&format!("cannot match `{}` against case of type `{}`", ty, value_ty), value.span());
+                        }
+                    }
+                    self.statements(&case.statements);
+                }
+                self.breakable.pop();
+            },
+            Statement::Emit { invocation, .. } => {
+                let ty = self.expression(invocation, None);
+                let is_event = match &ty {
+                    Type::Composite(name) => self.types.composite(name).is_some_and(|c| c.kind == CompositeKind::Event),
+                    ty => ty.is_unknown(),
+                };
+                if !is_event {
+                    self.error(&format!("cannot emit non-event type `{}`", ty), invocation.span());
+                }
+            },
+            Statement::Destroy { expression, .. } => {
+                let ty = self.expression(expression, None);
+                if !ty.is_unknown() && !self.types.is_resource(&ty) {
+                    self.error(&format!("cannot destroy non-resource type `{}`", ty), expression.span());
+                }
+            },
+            Statement::Error(_) => (),
+        }
+    }
+
+    fn if_statement(&mut self, statement: &IfStatement) {
+        match &statement.test {
+            IfTest::Expression(test) => {
+                self.expect(test, &Type::Bool);
+            },
+            IfTest::Binding(binding) => {
+                let ty = match &binding.type_annotation {
+                    Some(annotation) => {
+                        let ty = self.annotation(annotation);
+                        self.expect(&binding.value, &Type::optional(ty.clone()));
+                        ty
+                    },
+                    None => match self.expression(&binding.value, None) {
+                        Type::Optional(ty) => *ty,
+                        ty if ty.is_unknown() => ty,
+                        ty => {
+                            self.error(&format!("optional binding requires an optional value, got `{}`", ty), binding.value.span());
+                            ty
+                        },
+                    },
+                };
+                self.declare_type(&binding.identifier, ty);
+            },
+        }
+        self.block(&statement.then);
+        match &statement.else_ {
+            Some(ElseBranch::Block(block)) => self.block(block),
+            Some(ElseBranch::If(else_if)) => self.if_statement(else_if),
+            None => (),
+        }
+    }
+
+    /// whether every path through the statements returns or aborts
+    fn returns(&self, statements: &[Statement]) -> bool {
+        statements.iter().any(|statement| match statement {
+            Statement::Return { .. } => true,
+            Statement::Expression(expression) => self.expression_types.get(&expression.span()) == Some(&Type::Never),
+            Statement::If(statement) => self.if_returns(statement),
+            Statement::Switch { cases, .. } => {
+                cases.iter().any(|case| case.is_default()) && cases.iter().all(|case| self.returns(&case.statements))
+            },
+            _ => false,
+        })
+    }
+
+    fn if_returns(&self, statement: &IfStatement) -> bool {
+        self.returns(&statement.then.statements) && match &statement.else_ {
+            Some(ElseBranch::Block(block)) => self.returns(&block.statements),
+            Some(ElseBranch::If(else_if)) => self.if_returns(else_if),
+            None => false,
+        }
+    }
+
+    /// the type a value assigned to `target` must have
+    fn assignment_target(&mut self, target: &Expression) -> Type {
+        match target {
+            Expression::Identifier(identifier) => {
+                let ty = self.expression(target, None);
+                if let Some(&symbol) = self.references.get(&identifier.span.at) {
+                    let kind = self.resolution.symbol(symbol).kind;
+                    if kind != SymbolKind::Variable {
+                        self.error(&format!("cannot assign to {} `{}`", kind.describe(), identifier.name), identifier.span);
+                    }
+                }
+                ty
+            },
+            Expression::Member { expression, identifier, .. } => {
+                let ty = self.expression(target, None);
+                let base = self.expression_types.get(&expression.span()).cloned().unwrap_or(Type::Invalid);
+                let member = self.types.member(base.unwrap_optional(), &identifier.name);
+                let in_initializer = self.function.as_ref().is_some_and(|function| function.is_initializer);
+                let of_self = matches!(&**expression, Expression::Identifier(base) if base.name == "self");
+                match member.map(|member| member.kind) {
+                    Some(MemberKind::Field(VariableKind::Constant)) if !(in_initializer && of_self) => {
+                        self.error(&format!("cannot assign to constant field `{}`", identifier.name), identifier.span);
+                    },
+                    Some(MemberKind::Function { .. } | MemberKind::EnumCase) => {
+                        self.error(&format!("cannot assign to `{}`", identifier.name), identifier.span);
+                    },
+                    _ => (),
+                }
+                ty
+            },
+            Expression::Index { .. } => self.expression(target, None),
+            _ => {
+                self.expression(target, None);
+                self.error("invalid assignment target", target.span());
+                Type::Invalid
+            },
+        }
+    }
+
+    // expressions
+
+    /// check `expression` and report if its type is not a subtype of `expected`
+    fn expect(&mut self, expression: &Expression, expected: &Type) -> Type {
+        let ty = self.expression(expression, Some(expected));
+        if !self.is_subtype(&ty, expected) {
+            self.mismatch(expected, &ty, expression.span());
+        }
+        ty
+    }
+
+    /// the type of `expression`, `expected` guides literals
+    fn expression(&mut self, expression: &Expression, expected: Option<&Type>) -> Type {
+        let ty = self.expression_type(expression, expected);
+        self.expression_types.insert(expression.span(), ty.clone());
+        ty
+    }
+
+    fn expression_type(&mut self, expression: &Expression, expected: Option<&Type>) -> Type {
+        match expression {
+            Expression::Nil { .. } => Type::nil(),
+            Expression::Bool { .. } => Type::Bool,
+            Expression::Integer { literal, span } => self.integer(literal, *span, expected, false),
+            Expression::FixedPoint { literal, span } => self.fixed_point(literal, *span, expected, false),
+            Expression::String { value, .. } => {
+                let is_character = value.chars().count() == 1;
+                match expected.map(Type::unwrap_optional) {
+                    Some(Type::Character) if is_character => Type::Character,
+                    _ => Type::String,
+                }
+            },
+            Expression::Array { values, span } => self.array(values, *span, expected),
+            Expression::Dictionary { entries, span } => self.dictionary(entries, *span, expected),
+            Expression::Identifier(identifier) => match self.references.get(&identifier.span.at) {
+                Some(&symbol) => self.value(symbol),
+                None => Type::Invalid,
+            },
+            Expression::Path { domain, .. } => match domain.name.as_str() {
+                "storage" => Type::Path(PathType::StoragePath),
+                "public" => Type::Path(PathType::PublicPath),
+                "private" => Type::Path(PathType::PrivatePath),
+                _ => {
+                    self.error(&format!("invalid path domain `{}`, expected `storage`, `public` or `private`", domain.name), domain.span);
+                    Type::Invalid
+                },
+            },
+            Expression::Member { expression, optional, identifier, .. } => self.member(expression, *optional, identifier),
+            Expression::Index { expression, index, .. } => {
+                let ty = self.expression(expression, None);
+                match ty.dereference() {
+                    Type::VariableSized(element) | Type::ConstantSized(element, _) => {
+                        let index_ty = self.expression(index, Some(&Type::Number(NumberType::Int)));
+                        if !index_ty.is_unknown() && !index_ty.as_number().is_some_and(|number| number.is_integer()) {
+                            self.error(&format!("array index must be an integer, got `{}`", index_ty), index.span());
+                        }
+                        (**element).clone()
+                    },
+                    Type::Dictionary(key, value) => {
+                        let value = Type::optional((**value).clone());
+                        self.expect(index, key);
+                        value
+                    },
+                    ty if ty.is_unknown() => {
+                        self.expression(index, None);
+                        Type::Invalid
+                    },
+                    ty => {
+                        self.error(&format!("cannot index into value of type `{}`", ty), expression.span());
+                        self.expression(index, None);
+                        Type::Invalid
+                    },
+                }
+            },
+            Expression::Invocation { .. } => self.invocation(expression, expected, false),
+            Expression::Unary { operation, expression, span } => self.unary(*operation, expression, *span, expected),
+            Expression::Binary { operation, left, right, span } => self.binary(*operation, left, right, *span, expected),
+            Expression::Conditional { test, then, else_, .. } => {
+                self.expect(test, &Type::Bool);
+                let then = self.expression(then, expected);
+                let else_ = self.expression(else_, expected);
+                self.types.common_supertype(&then, &else_)
+            },
+            Expression::Casting { expression, operation, type_annotation, span } => {
+                let target = self.annotation(type_annotation);
+                match operation {
+                    CastingOperation::Static => {
+                        self.expect(expression, &target);
+                        target
+                    },
+                    CastingOperation::Failable | CastingOperation::Force => {
+                        let ty = self.expression(expression, None);
+                        if !ty.is_unknown() && !contains_invalid(&target) {
+                            if self.types.is_resource(&ty) != self.types.is_resource(&target) {
+                                self.error(&format!("cannot cast between resource and non-resource types: `{}` and `{}`", ty, target), *span);
+                            } else if self.is_subtype(&ty, &target) {
+                                let message = format!("cast of `{}` to `{}` always succeeds, use `as`", ty, target);
+                                self.warning(&message, *span);
+                            }
+                        }
+                        match operation {
+                            CastingOperation::Failable => Type::optional(target),
+                            _ => target,
+                        }
+                    },
+                }
+            },
+            Expression::Create { invocation, .. } => {
+                let ty = self.invocation(invocation, expected, true);
+                self.expression_types.insert(invocation.span(), ty.clone());
+                ty
+            },
+            Expression::Reference { expression, ty, span } => {
+                let target = ty.as_ref().map(|ty| self.convert(ty));
+                let referenced = match &target {
+                    Some(Type::Reference { ty, .. }) => Some((**ty).clone()),
+                    Some(target) if target.is_unknown() => None,
+                    Some(target) => {
+                        self.error(&format!("expected a reference type, got `{}`", target), *span);
+                        None
+                    },
+                    None => None,
+                };
+                let ty = self.expression(expression, referenced.as_ref());
+                // references to optionals are optional references
+                let (ty, is_optional) = match ty {
+                    Type::Optional(inner) if !referenced.as_ref().is_some_and(Type::is_optional) => (*inner, true),
+                    ty => (ty, false),
+                };
+                let reference = match (target, referenced) {
+                    (Some(target), Some(referenced)) => {
+                        if !self.is_subtype(&ty, &referenced) {
+                            self.mismatch(&referenced, &ty, expression.span());
+                        }
+                        target
+                    },
+                    (Some(target), None) if target.is_unknown() => target,
+                    _ => Type::reference(ty),
+                };
+                if is_optional {
+                    Type::optional(reference)
+                } else {
+                    reference
+                }
+            },
+            Expression::Force { expression, .. } => {
+                let expected = expected.map(|expected| Type::optional(expected.clone()));
+                match self.expression(expression, expected.as_ref()) {
+                    Type::Optional(ty) => *ty,
+                    ty if ty.is_unknown() => ty,
+                    ty => {
+                        self.error(&format!("cannot force-unwrap non-optional type `{}`", ty), expression.span());
+                        ty
+                    },
+                }
+            },
+        }
+    }
+
+    /// the type of a name used as a value
+    fn value(&self, symbol: SymbolId) -> Type {
+        let symbol_data = self.resolution.symbol(symbol);
+        match symbol_data.kind {
+            SymbolKind::BuiltInType => built_in_type_value(&symbol_data.name),
+            SymbolKind::BuiltInFunction => built_in_function(&symbol_data.name),
+            SymbolKind::Composite(kind) => {
+                let name = self.composite_names.get(&symbol).cloned().unwrap_or_else(|| symbol_data.name.clone());
+                self.composite_value(&name, kind)
+            },
+            SymbolKind::SelfValue => self.self_type.clone().unwrap_or(Type::Invalid),
+            SymbolKind::PostCondition if symbol_data.name == "result" => {
+                self.function.as_ref().map_or(Type::Invalid, |function| function.return_type.clone())
+            },
+            // `before(x)` has the type of `x`
+            SymbolKind::PostCondition => Type::Function(FunctionType {
+                type_parameters: vec![Type::Invalid],
+                parameters: vec![FunctionParameter { label: None, ty: Type::TypeParameter(0) }],
+                return_type: Box::new(Type::TypeParameter(0)),
+            }),
+            _ => self.symbol_types.get(&symbol).cloned().unwrap_or(Type::Invalid),
+        }
+    }
+
+    /// the value a composite's name stands for: its constructor, or the contract or enum itself
+    fn composite_value(&self, name: &str, kind: CompositeKind) -> Type {
+        match kind {
+            CompositeKind::Contract | CompositeKind::Enum => Type::Composite(name.to_string()),
+            CompositeKind::Structure | CompositeKind::Resource | CompositeKind::Event => {
+                let parameters = self.types.composite(name).map(|c| c.initializer.clone()).unwrap_or_default();
+                Type::Function(FunctionType::new(parameters, Type::Composite(name.to_string())))
+            },
+        }
+    }
+
+    fn member(&mut self, expression: &Expression, optional: bool, identifier: &Identifier) -> Type {
+        let base = self.expression(expression, None);
+        if base.is_unknown() {
+            return Type::Invalid
+        }
+        let ty = match (&base, optional) {
+            (Type::Optional(ty), true) => (**ty).clone(),
+            (ty, true) => {
+                self.error(&format!("optional chaining requires an optional type, got `{}`", ty), expression.span());
+                ty.clone()
+            },
+            (Type::Optional(_), false) => {
+                let message = format!("cannot access member `{}` of optional type `{}`, use `?.`", identifier.name, base);
+                self.error(&message, identifier.span);
+                return Type::Invalid
+            },
+            (ty, false) => ty.clone(),
+        };
+
+        let member_ty = match self.types.member(&ty, &identifier.name) {
+            Some(Member { entitlements: Authorization::Mapping(mapping), ty: member_ty, .. }) => {
+                // an owned value is fully entitled
+                let granted = match &ty {
+                    Type::Reference { authorization, .. } => Some(authorization),
+                    _ => None,
+                };
+                let image = self.types.map_entitlements(&mapping, granted);
+                map_reference(member_ty, &mapping, &image)
+            },
+            Some(member) => member.ty,
+            None => match self.nested_value(&ty, &identifier.name) {
+                Some(nested) => nested,
+                None if self.types.has_known_members(&ty) => {
+                    self.error(&format!("value of type `{}` has no member `{}`", ty, identifier.name), identifier.span);
+                    return Type::Invalid
+                },
+                None => return Type::Invalid,
+            },
+        };
+        match (optional, member_ty) {
+            (true, Type::Optional(member_ty)) => Type::Optional(member_ty),
+            (true, member_ty) => Type::optional(member_ty),
+            (false, member_ty) => member_ty,
+        }
+    }
+
+    /// a composite declared inside a contract, used through the contract
+    fn nested_value(&self, ty: &Type, name: &str) -> Option<Type> {
+        let Type::Composite(outer) = ty.dereference() else {
+            return None
+        };
+        let qualified = format!("{}.{}", outer, name);
+        let outer = self.types.composite(outer)?;
+        if !outer.nested.contains(&qualified) {
+            return None
+        }
+        let nested = self.types.composite(&qualified)?;
+        if nested.is_interface {
+            return None
+        }
+        Some(self.composite_value(&qualified, nested.kind))
+    }
+
+    /// the composite a constructor invocation creates, if `invoked` names a composite
+    fn constructed(&self, invoked: &Expression) -> Option<String> {
+        match invoked {
+            Expression::Identifier(identifier) => {
+                let symbol = *self.references.get(&identifier.span.at)?;
+                match self.resolution.symbol(symbol).kind {
+                    SymbolKind::Composite(_) => self.composite_names.get(&symbol).cloned(),
+                    _ => None,
+                }
+            },
+            Expression::Member { expression, identifier, optional: false, .. } => {
+                let Type::Composite(outer) = self.expression_types.get(&expression.span())? else {
+                    return None
+                };
+                let qualified = format!("{}.{}", outer, identifier.name);
+                self.types.composite(outer)?.nested.contains(&qualified).then_some(qualified)
+            },
+            _ => None,
+        }
+    }
+
+    fn invocation(&mut self, expression: &Expression, expected: Option<&Type>, is_create: bool) -> Type {
+        let Expression::Invocation { invoked, type_arguments, arguments, span } = expression else {
+            return self.expression(expression, expected)
+        };
+        let ty = self.expression(invoked, None);
+
+        let constructed = self.constructed(invoked).and_then(|name| self.types.composite(&name).cloned());
+        match &constructed {
+            Some(composite) if composite.is_resource() && !is_create => {
+                self.error(&format!("cannot create resource `{}` without `create`", composite.name), *span);
+            },
+            Some(composite) if !composite.is_resource() && is_create => {
+                self.error(&format!("cannot `create` non-resource type `{}`", composite.name), *span);
+            },
+            None if is_create && !ty.is_unknown() => {
+                self.error("`create` requires the invocation of a resource constructor", *span);
+            },
+            _ => (),
+        }
+
+        let type_arguments: Vec<Type> = type_arguments.iter().map(|argument| self.annotation(argument)).collect();
+        let function = match ty {
+            Type::Function(function) => function,
+            Type::Composite(name) if self.types.composite(&name).is_some_and(|c| c.kind == CompositeKind::Enum) => {
+                let raw_type = self.types.composite(&name).and_then(|c| c.raw_type.clone()).unwrap_or(Type::Invalid);
+                let parameters = vec![FunctionParameter { label: Some("rawValue".to_string()), ty: raw_type }];
+                FunctionType::new(parameters, Type::optional(Type::Composite(name)))
+            },
+            ty => {
+                if !ty.is_unknown() {
+                    self.error(&format!("cannot call value of type `{}`", ty), invoked.span());
+                }
+                for argument in arguments.iter() {
+                    self.expression(&argument.expression, None);
+                }
+                return Type::Invalid
+            },
+        };
+        // `assert`'s message is optional
+        let function = match &**invoked {
+            Expression::Identifier(identifier) if identifier.name == "assert" && arguments.len() == 1 &&
+                self.references.get(&identifier.span.at).is_some_and(|&symbol| {
+                    self.resolution.symbol(symbol).kind == SymbolKind::BuiltInFunction
+                }) => {
+                FunctionType::new(function.parameters[..1].to_vec(), Type::Void)
+            },
+            _ => function,
+        };
+        self.call(&function, type_arguments, arguments, *span)
+    }
+
+    /// check arguments against the parameters, inferring missing type arguments from them
+    fn call(&mut self, function: &FunctionType, type_arguments: Vec<Type>, arguments: &[Argument], span: Span) -> Type {
+        if type_arguments.len() > function.type_parameters.len() {
+            let message = format!(
+                "incorrect number of type arguments: expected at most {}, got {}",
+                function.type_parameters.len(), type_arguments.len(),
+            );
+            self.error(&message, span);
+        }
+        let mut bound: Vec<Option<Type>> = (0..function.type_parameters.len())
+            .map(|index| type_arguments.get(index).cloned())
+            .collect();
+
+        if arguments.len() != function.parameters.len() {
+            let message = format!(
+                "incorrect number of arguments: expected {}, got {}",
+                function.parameters.len(), arguments.len(),
+            );
+            self.error(&message, span);
+        }
+
+        for (index, argument) in arguments.iter().enumerate() {
+            let Some(parameter) = function.parameters.get(index) else {
+                self.expression(&argument.expression, None);
+                continue;
+            };
+            match (&parameter.label, &argument.label) {
+                (Some(expected), None) => {
+                    self.error(&format!("missing argument label `{}`", expected), argument.expression.span());
+                },
+                (Some(expected), Some(label)) if *expected != label.name => {
+                    self.error(&format!("incorrect argument label: expected `{}`, got `{}`", expected, label.name), label.span);
+                },
+                (None, Some(label)) => {
+                    self.error(&format!("unexpected argument label `{}`", label.name), label.span);
+                },
+                _ => (),
+            }
+            match &parameter.ty {
+                Type::TypeParameter(index) if bound.get(*index).is_some_and(Option::is_none) => {
+                    let ty = self.expression(&argument.expression, None);
+                    bound[*index] = Some(ty);
+                },
+                ty => {
+                    let ty = ty.substitute(&self.type_arguments(function, &bound));
+                    self.expect(&argument.expression, &ty);
+                },
+            }
+        }
+        function.return_type.substitute(&self.type_arguments(function, &bound))
+    }
+
+    fn type_arguments(&self, function: &FunctionType, bound: &[Option<Type>]) -> Vec<Type> {
+        bound.iter().zip(function.type_parameters.iter())
+            .map(|(bound, default)| bound.clone().unwrap_or_else(|| default.clone()))
+            .collect()
+    }
+
+    fn unary(&mut self, operation: UnaryOperation, expression: &Expression, span: Span, expected: Option<&Type>) -> Type {
+        match operation {
+            UnaryOperation::Minus => {
+                let ty = match expression {
+                    Expression::Integer { literal, span } => self.integer(literal, *span, expected, true),
+                    Expression::FixedPoint { literal, span } => self.fixed_point(literal, *span, expected, true),
+                    expression => self.expression(expression, expected),
+                };
+                self.expression_types.insert(expression.span(), ty.clone());
+                match ty.as_number() {
+                    Some(number) if !number.is_signed() => {
+                        self.error(&format!("cannot negate unsigned type `{}`", ty), span);
+                    },
+                    None if !ty.is_unknown() => self.error(&format!("cannot apply `-` to `{}`", ty), span),
+                    _ => (),
+                }
+                ty
+            },
+            UnaryOperation::Not => {
+                self.expect(expression, &Type::Bool);
+                Type::Bool
+            },
+            UnaryOperation::Move => self.expression(expression, expected),
+        }
+    }
+
+    /// check both operands, the literal after the other so it takes the other's type
+    fn operands(&mut self, left: &Expression, right: &Expression, expected: Option<&Type>) -> (Type, Type) {
+        if is_number_literal(left) && !is_number_literal(right) {
+            let right = self.expression(right, expected);
+            let left = self.expression(left, Some(&right));
+            (left, right)
+        } else {
+            let left = self.expression(left, expected);
+            let right = self.expression(right, Some(&left));
+            (left, right)
+        }
+    }
+
+    fn binary(&mut self, operation: BinaryOperation, left: &Expression, right: &Expression, span: Span, expected: Option<&Type>) -> Type {
+        let symbol = operation.as_str();
+        match operation {
+            BinaryOperation::And | BinaryOperation::Or => {
+                self.expect(left, &Type::Bool);
+                self.expect(right, &Type::Bool);
+                Type::Bool
+            },
+            BinaryOperation::Equal | BinaryOperation::NotEqual => {
+                let (left, right) = self.operands(left, right, None);
+                if self.types.is_resource(&left) || self.types.is_resource(&right) {
+                    self.error(&format!("cannot compare resources with `{}`", symbol), span);
+                } else if !self.is_subtype(&left, &right) && !self.is_subtype(&right, &left) {
+                    self.error(&format!("cannot compare `{}` and `{}`", left, right), span);
+                }
+                Type::Bool
+            },
+            BinaryOperation::Less | BinaryOperation::LessEqual | BinaryOperation::Greater | BinaryOperation::GreaterEqual => {
+                let (left, right) = self.operands(left, right, None);
+                self.arithmetic(symbol, &left, &right, span, false);
+                Type::Bool
+            },
+            BinaryOperation::NilCoalescing => {
+                let optional = expected.map(|expected| Type::optional(expected.clone()));
+                let left_ty = self.expression(left, optional.as_ref());
+                let inner = match &left_ty {
+                    Type::Optional(inner) => (**inner).clone(),
+                    ty if ty.is_unknown() => Type::Invalid,
+                    ty => {
+                        self.error(&format!("left side of `??` must be optional, got `{}`", ty), left.span());
+                        ty.clone()
+                    },
+                };
+                let right_expected = match &inner {
+                    Type::Never => expected.cloned(),
+                    inner => Some(inner.clone()),
+                };
+                let right_ty = self.expression(right, right_expected.as_ref());
+                if inner == Type::Never {
+                    return right_ty
+                }
+                if right_ty.is_optional() {
+                    if !self.is_subtype(&right_ty, &Type::optional(inner.clone())) {
+                        self.mismatch(&Type::optional(inner.clone()), &right_ty, right.span());
+                    }
+                    Type::optional(inner)
+                } else {
+                    if !self.is_subtype(&right_ty, &inner) {
+                        self.mismatch(&inner, &right_ty, right.span());
+                    }
+                    inner
+                }
+            },
+            BinaryOperation::BitwiseOr | BinaryOperation::BitwiseXor | BinaryOperation::BitwiseAnd |
+            BinaryOperation::BitwiseShiftLeft | BinaryOperation::BitwiseShiftRight |
+            BinaryOperation::Plus | BinaryOperation::Minus | BinaryOperation::Mul | BinaryOperation::Div | BinaryOperation::Mod => {
+                let expected = expected.filter(|expected| expected.unwrap_optional().as_number().is_some());
+                let (left, right) = self.operands(left, right, expected);
+                let is_bitwise = operation.precedence() < ast::PRECEDENCE_ADDITION;
+                self.arithmetic(symbol, &left, &right, span, is_bitwise)
+            },
+        }
+    }
+
+    /// operands must have the same number type, integers for bitwise operations
+    fn arithmetic(&mut self, symbol: &str, left: &Type, right: &Type, span: Span, is_bitwise: bool) -> Type {
+        if left.is_unknown() || right.is_unknown() {
+            return if left.is_unknown() { right.clone() } else { left.clone() }
+        }
+        let valid = |ty: &Type| ty.as_number().is_some_and(|number| !is_bitwise || number.is_integer());
+        if left == right && valid(left) {
+            return left.clone()
+        }
+        if left == right {
+            self.error(&format!("cannot apply `{}` to `{}`", symbol, left), span);
+        } else {
+            self.error(&format!("cannot apply `{}` to `{}` and `{}`", symbol, left, right), span);
+        }
+        Type::Invalid
+    }
+
+    /// the number type of an integer literal, checked against the range of the type
+    fn integer(&mut self, literal: &str, span: Span, expected: Option<&Type>, negative: bool) -> Type {
+        let expected = expected.map(Type::unwrap_optional);
+        let digits: String = literal.chars().filter(|c| *c != '_').collect();
+        let (radix, digits) = match digits.get(..2) {
+            Some("0x") => (16, &digits[2..]),
+            Some("0b") => (2, &digits[2..]),
+            Some("0o") => (8, &digits[2..]),
+            _ => (10, digits.as_str()),
+        };
+
+        if expected == Some(&Type::Address) && radix == 16 {
+            if digits.len() > 16 {
+                self.error(&format!("address literal `{}` is too large", literal), span);
+            }
+            return Type::Address
+        }
+
+        let ty = match expected.and_then(Type::as_number) {
+            Some(number) if number.is_integer() && !number.is_abstract() => number,
+            _ => NumberType::Int,
+        };
+        // negating unsigned types is reported instead
+        let range = ty.integer_range().filter(|_| !negative || ty.is_signed());
+        if let Some((min, max)) = range {
+            let limit = if negative { min.unsigned_abs() } else { max };
+            let in_range = u128::from_str_radix(digits, radix).is_ok_and(|value| value <= limit);
+            if !in_range {
+                let sign = if negative { "-" } else { "" };
+                self.error(&format!("integer literal `{}{}` is out of range for `{}`", sign, literal, ty.name()), span);
+            }
+        }
+        Type::Number(ty)
+    }
+
+    /// the number type of a fixed-point literal, `UFix64` unless negative or expected otherwise
+    fn fixed_point(&mut self, literal: &str, span: Span, expected: Option<&Type>, negative: bool) -> Type {
+        let ty = match expected.map(Type::unwrap_optional).and_then(Type::as_number) {
+            Some(NumberType::Fix64) => NumberType::Fix64,
+            Some(NumberType::UFix64) => NumberType::UFix64,
+            _ if negative => NumberType::Fix64,
+            _ => NumberType::UFix64,
+        };
+        let digits: String = literal.chars().filter(|c| *c != '_').collect();
+        let (integer, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+        if fraction.len() > 8 {
+            self.error(&format!("fixed-point literal `{}` has more than 8 fractional digits", literal), span);
+        }
+        let max: u128 = if ty == NumberType::Fix64 { 92_233_720_368 } else { 184_467_440_737 };
+        if !integer.parse::<u128>().is_ok_and(|value| value <= max) {
+            let sign = if negative { "-" } else { "" };
+            self.error(&format!("fixed-point literal `{}{}` is out of range for `{}`", sign, literal, ty.name()), span);
+        }
+        Type::Number(ty)
+    }
+
+    fn array(&mut self, values: &[Expression], span: Span, expected: Option<&Type>) -> Type {
+        let expected = expected.map(Type::unwrap_optional);
+        let element = match expected {
+            Some(Type::VariableSized(element) | Type::ConstantSized(element, _)) => Some((**element).clone()),
+            _ => None,
+        };
+        if let Some(element) = element {
+            for value in values.iter() {
+                self.expect(value, &element);
+            }
+            return match expected {
+                Some(Type::ConstantSized(_, size)) if *size == values.len() as u64 => {
+                    Type::ConstantSized(Box::new(element), *size)
+                },
+                _ => Type::VariableSized(Box::new(element)),
+            }
+        }
+        let types: Vec<Type> = values.iter().map(|value| self.expression(value, None)).collect();
+        match types.into_iter().reduce(|a, b| self.types.common_supertype(&a, &b)) {
+            Some(element) => Type::VariableSized(Box::new(element)),
+            None if expected.is_some_and(|expected| !expected.is_unknown()) => Type::VariableSized(Box::new(Type::Never)),
+            None => {
+                if expected.is_none() {
+                    self.error("cannot infer the type of an empty array literal, add a type annotation", span);
+                }
+                Type::VariableSized(Box::new(Type::Invalid))
+            },
+        }
+    }
+
+    fn dictionary(&mut self, entries: &[(Expression, Expression)], span: Span, expected: Option<&Type>) -> Type {
+        if let Some(Type::Dictionary(key, value)) = expected.map(Type::unwrap_optional) {
+            let (key, value) = ((**key).clone(), (**value).clone());
+            for (entry_key, entry_value) in entries.iter() {
+                self.expect(entry_key, &key);
+                self.expect(entry_value, &value);
+            }
+            return Type::Dictionary(Box::new(key), Box::new(value))
+        }
+        let mut key = None;
+        let mut value = None;
+        for (entry_key, entry_value) in entries.iter() {
+            let key_ty = self.expression(entry_key, None);
+            let value_ty = self.expression(entry_value, None);
+            key = Some(match key {
+                Some(key) => self.types.common_supertype(&key, &key_ty),
+                None => key_ty,
+            });
+            value = Some(match value {
+                Some(value) => self.types.common_supertype(&value, &value_ty),
+                None => value_ty,
+            });
+        }
+        match (key, value) {
+            (Some(key), Some(value)) => Type::Dictionary(Box::new(key), Box::new(value)),
+            _ => {
+                if expected.is_none() {
+                    self.error("cannot infer the type of an empty dictionary literal, add a type annotation", span);
+                }
+                let never = || Box::new(if expected.is_some() { Type::Never } else { Type::Invalid });
+                Type::Dictionary(never(), never())
+            },
+        }
+    }
+}
+
+fn built_in_type(name: &str) -> Type {
+    if let Some(number) = NumberType::from_name(name) {
+        return Type::Number(number)
+    }
+    match name {
+        "Never" => Type::Never,
+        "Void" => Type::Void,
+        "AnyStruct" => Type::AnyStruct,
+        "AnyResource" => Type::AnyResource,
+        "Bool" => Type::Bool,
+        "Character" => Type::Character,
+        "String" => Type::String,
+        "Address" => Type::Address,
+        "Type" => Type::MetaType,
+        "Block" => Type::Block,
+        "Path" => Type::Path(PathType::Path),
+        "StoragePath" => Type::Path(PathType::StoragePath),
+        "PublicPath" => Type::Path(PathType::PublicPath),
+        "PrivatePath" => Type::Path(PathType::PrivatePath),
+        "CapabilityPath" => Type::Path(PathType::CapabilityPath),
+        "Capability" => Type::Capability(None),
+        "AuthAccount" => Type::AuthAccount,
+        "PublicAccount" => Type::PublicAccount,
+        name => Type::Other(name.to_string()),
+    }
+}
+
+/// built-in types used as values: number conversions, `Address(...)` and `Type<T>()`
+fn built_in_type_value(name: &str) -> Type {
+    let conversion = |parameter: Type, result: Type| {
+        Type::Function(FunctionType::new(vec![FunctionParameter { label: None, ty: parameter }], result))
+    };
+    match built_in_type(name) {
+        Type::Number(number) if !number.is_abstract() => conversion(Type::Number(NumberType::Number), Type::Number(number)),
+        Type::Address => conversion(Type::Number(NumberType::Integer), Type::Address),
+        Type::MetaType => Type::Function(FunctionType {
+            type_parameters: vec![Type::Invalid],
+            parameters: Vec::new(),
+            return_type: Box::new(Type::MetaType),
+        }),
+        _ => Type::Other(name.to_string()),
+    }
+}
+
+fn built_in_function(name: &str) -> Type {
+    let function = |parameters: Vec<(Option<&str>, Type)>, return_type: Type| {
+        let parameters = parameters.into_iter()
+            .map(|(label, ty)| FunctionParameter { label: label.map(str::to_string), ty })
+            .collect();
+        Type::Function(FunctionType::new(parameters, return_type))
+    };
+    match name {
+        "log" => function(vec![(None, Type::AnyStruct)], Type::Void),
+        "panic" => function(vec![(None, Type::String)], Type::Never),
+        "assert" => function(vec![(None, Type::Bool), (Some("message"), Type::String)], Type::Void),
+        "getAccount" => function(vec![(None, Type::Address)], Type::PublicAccount),
+        "getAuthAccount" => function(vec![(None, Type::Address)], Type::AuthAccount),
+        "getCurrentBlock" => function(vec![], Type::Block),
+        "getBlock" => function(vec![(Some("at"), Type::Number(NumberType::UInt64))], Type::optional(Type::Block)),
+        "unsafeRandom" | "revertibleRandom" => function(vec![], Type::Number(NumberType::UInt64)),
+        _ => Type::Invalid,
+    }
+}
+
+/// interface functions with only conditions leave the implementation to the conforming composites
+fn has_implementation(declaration: &FunctionDeclaration) -> bool {
+    declaration.body.as_ref().is_some_and(|body| !body.statements.is_empty())
+}
+
+/// an `@` annotation makes imported types resources
+fn mark_resource(ty: &mut Type) {
+    match ty {
+        Type::Imported { is_resource, .. } => *is_resource = true,
+        Type::Optional(ty) | Type::VariableSized(ty) | Type::ConstantSized(ty, _) | Type::Restricted { ty, .. } => {
+            mark_resource(ty)
+        },
+        Type::Dictionary(_, value) => mark_resource(value),
+        _ => (),
+    }
+}
+
+/// replace the mapped authorization of references in a member's type with the entitlements it maps to
+fn map_reference(ty: Type, mapping: &str, image: &Authorization) -> Type {
+    match ty {
+        Type::Reference { authorization: Authorization::Mapping(mapped), ty } if mapped == mapping => {
+            Type::Reference { authorization: image.clone(), ty }
+        },
+        Type::Optional(ty) => Type::optional(map_reference(*ty, mapping, image)),
+        Type::Function(mut function) => {
+            function.return_type = Box::new(map_reference(*function.return_type, mapping, image));
+            Type::Function(function)
+        },
+        ty => ty,
+    }
+}
+
+fn contains_invalid(ty: &Type) -> bool {
+    match ty {
+        Type::Invalid => true,
+        Type::Optional(ty) | Type::VariableSized(ty) | Type::ConstantSized(ty, _) | Type::Reference { ty, .. } |
+        Type::Restricted { ty, .. } => contains_invalid(ty),
+        Type::Dictionary(key, value) => contains_invalid(key) || contains_invalid(value),
+        _ => false,
+    }
+}
+
+fn is_number_literal(expression: &Expression) -> bool {
+    match expression {
+        Expression::Integer { .. } | Expression::FixedPoint { .. } => true,
+        Expression::Unary { operation: UnaryOperation::Minus, expression, .. } => is_number_literal(expression),
+        _ => false,
+    }
+}
+
+/// names of the fields assigned through `self` in the statements, nested blocks included
+fn assigned_fields<'s>(statements: &'s [Statement], assigned: &mut Vec<&'s str>) {
+    for statement in statements.iter() {
+        match statement {
+            Statement::Assignment { target: Expression::Member { expression, identifier, .. }, .. } => {
+                if matches!(&**expression, Expression::Identifier(base) if base.name == "self") {
+                    assigned.push(&identifier.name);
+                }
+            },
+            Statement::If(statement) => assigned_fields_if(statement, assigned),
+            Statement::While { block, .. } | Statement::For { block, .. } => assigned_fields(&block.statements, assigned),
+            Statement::Switch { cases, .. } => {
+                for case in cases.iter() {
+                    assigned_fields(&case.statements, assigned);
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+fn assigned_fields_if<'s>(statement: &'s IfStatement, assigned: &mut Vec<&'s str>) {
+    assigned_fields(&statement.then.statements, assigned);
+    match &statement.else_ {
+        Some(ElseBranch::Block(block)) => assigned_fields(&block.statements, assigned),
+        Some(ElseBranch::If(else_if)) => assigned_fields_if(else_if, assigned),
+        None => (),
+    }
+}
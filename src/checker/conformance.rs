@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use crate::ast::{
+    Access, Condition, Declaration, FunctionDeclaration, InterfaceDeclaration, Program, Span, SpecialFunctionKind,
+    VariableKind,
+};
+
+use super::access::{
+    self, Visibility,
+};
+use super::diagnostic::Diagnostic;
+use super::types::{
+    CompositeType, FunctionParameter, FunctionType, Member, MemberKind, Type, Types,
+};
+
+/// conditions an interface declares for a function, which also apply to a conforming composite's implementation
+#[derive(Clone, Debug, PartialEq)]
+pub struct InheritedConditions {
+    /// qualified name of the conforming composite
+    pub composite: String,
+    pub function: String,
+    /// qualified name of the interface declaring the conditions
+    pub interface: String,
+    pub pre_conditions: Vec<Condition>,
+    pub post_conditions: Vec<Condition>,
+}
+
+/// check that composites implement the interfaces they conform to, and collect the inherited conditions
+pub fn check(program: &Program, types: &Types) -> (Vec<Diagnostic>, Vec<InheritedConditions>) {
+    let mut interfaces = HashMap::new();
+    collect_interfaces(&program.declarations, types, &mut interfaces);
+    let mut checker = ConformanceChecker { types, interfaces, diagnostics: Vec::new(), inherited: Vec::new() };
+
+    let mut composites: Vec<&CompositeType> = types.composites.values().filter(|c| !c.is_interface).collect();
+    composites.sort_by_key(|composite| composite.span.at);
+    for composite in composites {
+        checker.composite(composite);
+    }
+    (checker.diagnostics, checker.inherited)
+}
+
+/// interface declarations by qualified name
+fn collect_interfaces<'p>(declarations: &'p [Declaration], types: &Types, interfaces: &mut HashMap<String, &'p InterfaceDeclaration>) {
+    for declaration in declarations.iter() {
+        match declaration {
+            Declaration::Composite(d) => collect_interfaces(&d.members, types, interfaces),
+            Declaration::Interface(d) => {
+                let name = types.composites.values()
+                    .find(|composite| composite.is_interface && composite.span == d.identifier.span)
+                    .map(|composite| composite.name.clone());
+                if let Some(name) = name {
+                    interfaces.insert(name, d);
+                }
+                collect_interfaces(&d.members, types, interfaces);
+            },
+            _ => (),
+        }
+    }
+}
+
+struct ConformanceChecker<'a> {
+    types: &'a Types,
+    interfaces: HashMap<String, &'a InterfaceDeclaration>,
+    diagnostics: Vec<Diagnostic>,
+    inherited: Vec<InheritedConditions>,
+}
+
+impl ConformanceChecker<'_> {
+    /// report at the implementation, with a note at the requirement
+    fn error(&mut self, message: &str, span: Span, interface: &str, requirement: Span) {
+        let note = format!("requirement of `{}` declared here", interface);
+        self.diagnostics.push(Diagnostic::error(message, span).with_note(&note, requirement));
+    }
+
+    /// every interface the composite conforms to, directly or through other interfaces, in declaration order
+    fn conformances(&self, composite: &CompositeType) -> Vec<String> {
+        let mut all: Vec<String> = Vec::new();
+        let mut pending = composite.conformances.clone();
+        while !pending.is_empty() {
+            let interface = pending.remove(0);
+            if all.contains(&interface) {
+                continue;
+            }
+            if let Some(declaration) = self.types.composite(&interface) {
+                pending.extend(declaration.conformances.iter().cloned());
+            }
+            all.push(interface);
+        }
+        all
+    }
+
+    fn composite(&mut self, composite: &CompositeType) {
+        let interfaces = self.conformances(composite);
+        for interface in interfaces.iter() {
+            let Some(interface) = self.types.composite(interface) else {
+                continue
+            };
+            for requirement in interface.members.iter() {
+                match requirement.kind {
+                    MemberKind::Field(_) => self.field(composite, interface, requirement),
+                    MemberKind::Function { .. } => self.function(composite, interface, requirement, &interfaces),
+                    MemberKind::EnumCase => (),
+                }
+            }
+            self.initializer(composite, interface);
+        }
+    }
+
+    fn field(&mut self, composite: &CompositeType, interface: &CompositeType, requirement: &Member) {
+        let name = &requirement.name;
+        let Some(implementation) = composite.member(name) else {
+            let message = format!("`{}` does not conform to `{}`: missing field `{}`", composite.simple_name(), interface.name, name);
+            self.error(&message, composite.span, &interface.name, requirement.span);
+            return
+        };
+        let MemberKind::Field(kind) = implementation.kind else {
+            let message = format!("`{}` must be a field, as required by `{}`", name, interface.name);
+            self.error(&message, implementation.span, &interface.name, requirement.span);
+            return
+        };
+        if MemberKind::Field(kind) != requirement.kind {
+            let keyword = |kind: MemberKind| if kind == MemberKind::Field(VariableKind::Constant) { "let" } else { "var" };
+            let message = format!(
+                "field `{}` is declared with `{}`, but `{}` requires `{}`",
+                name, keyword(implementation.kind), interface.name, keyword(requirement.kind),
+            );
+            self.error(&message, implementation.span, &interface.name, requirement.span);
+        }
+        if !self.same_type(&implementation.ty, &requirement.ty) {
+            let message = format!(
+                "field `{}` has type `{}`, but `{}` requires `{}`",
+                name, implementation.ty, interface.name, requirement.ty,
+            );
+            self.error(&message, implementation.span, &interface.name, requirement.span);
+        }
+        self.access(implementation, interface, requirement);
+    }
+
+    fn function(&mut self, composite: &CompositeType, interface: &CompositeType, requirement: &Member, interfaces: &[String]) {
+        let name = &requirement.name;
+        let implementation = composite.member(name);
+        match implementation {
+            Some(implementation) if matches!(implementation.kind, MemberKind::Function { .. }) => {
+                if let (Type::Function(implemented), Type::Function(required)) = (&implementation.ty, &requirement.ty) {
+                    self.signature(name, implementation.span, implemented, &interface.name, requirement.span, required);
+                }
+                self.access(implementation, interface, requirement);
+            },
+            Some(implementation) => {
+                let message = format!("`{}` must be a function, as required by `{}`", name, interface.name);
+                self.error(&message, implementation.span, &interface.name, requirement.span);
+                return
+            },
+            None => {
+                // interfaces may provide a default implementation, but only one of them
+                let defaults: Vec<&CompositeType> = interfaces.iter()
+                    .filter_map(|interface| self.types.composite(interface))
+                    .filter(|interface| {
+                        interface.member(name).is_some_and(|member| member.kind == MemberKind::Function { has_body: true })
+                    })
+                    .collect();
+                match defaults.as_slice() {
+                    [] => {
+                        let message = format!(
+                            "`{}` does not conform to `{}`: missing function `{}`",
+                            composite.simple_name(), interface.name, name,
+                        );
+                        self.error(&message, composite.span, &interface.name, requirement.span);
+                        return
+                    },
+                    [_] => (),
+                    [first, second, ..] if first.name == interface.name => {
+                        let message = format!(
+                            "`{}` inherits conflicting default implementations of `{}` from `{}` and `{}`",
+                            composite.simple_name(), name, first.name, second.name,
+                        );
+                        let mut diagnostic = Diagnostic::error(&message, composite.span);
+                        for default in [first, second] {
+                            let span = default.member(name).map_or(default.span, |member| member.span);
+                            diagnostic = diagnostic.with_note(&format!("default implementation of `{}`", default.name), span);
+                        }
+                        self.diagnostics.push(diagnostic);
+                    },
+                    _ => (),
+                }
+            },
+        }
+        self.conditions(composite, interface, name);
+    }
+
+    /// an interface's `init` fixes the parameters of the conforming composite's `init`
+    fn initializer(&mut self, composite: &CompositeType, interface: &CompositeType) {
+        let Some(declaration) = self.interfaces.get(&interface.name) else {
+            return
+        };
+        let Some(init) = declaration.members.iter().find_map(|member| match member {
+            Declaration::SpecialFunction(d) if d.kind == SpecialFunctionKind::Initializer => Some(&d.function),
+            _ => None,
+        }) else {
+            return
+        };
+        let required = FunctionType::new(interface.initializer.clone(), Type::Void);
+        let implemented = FunctionType::new(composite.initializer.clone(), Type::Void);
+        self.signature("init", composite.span, &implemented, &interface.name, init.identifier.span, &required);
+    }
+
+    /// parameter count, argument labels, parameter types and return type must match exactly
+    fn signature(&mut self, name: &str, span: Span, implemented: &FunctionType, interface: &str, requirement: Span, required: &FunctionType) {
+        if implemented.parameters.len() != required.parameters.len() {
+            let message = format!(
+                "`{}` has {} parameters, but `{}` requires {}",
+                name, implemented.parameters.len(), interface, required.parameters.len(),
+            );
+            self.error(&message, span, interface, requirement);
+            return
+        }
+        let label = |parameter: &FunctionParameter| parameter.label.clone().unwrap_or_else(|| "_".to_string());
+        for (index, (implemented, required)) in implemented.parameters.iter().zip(required.parameters.iter()).enumerate() {
+            if implemented.label != required.label {
+                let message = format!(
+                    "parameter {} of `{}` has argument label `{}`, but `{}` requires `{}`",
+                    index + 1, name, label(implemented), interface, label(required),
+                );
+                self.error(&message, span, interface, requirement);
+            }
+            if !self.same_type(&implemented.ty, &required.ty) {
+                let message = format!(
+                    "parameter {} of `{}` has type `{}`, but `{}` requires `{}`",
+                    index + 1, name, implemented.ty, interface, required.ty,
+                );
+                self.error(&message, span, interface, requirement);
+            }
+        }
+        if !self.same_type(&implemented.return_type, &required.return_type) {
+            let message = format!(
+                "`{}` returns `{}`, but `{}` requires `{}`",
+                name, implemented.return_type, interface, required.return_type,
+            );
+            self.error(&message, span, interface, requirement);
+        }
+    }
+
+    /// the implementation must be at least as accessible as the requirement, and entitled the same
+    fn access(&mut self, implementation: &Member, interface: &CompositeType, requirement: &Member) {
+        let settable = |access: &Access| *access == Access::PubSet;
+        if Visibility::of(&implementation.access) < Visibility::of(&requirement.access) ||
+            (settable(&requirement.access) && !settable(&implementation.access)) {
+            let message = format!(
+                "`{}` is less accessible than required by `{}`: `{}`",
+                implementation.name, interface.name, access::keyword(&requirement.access),
+            );
+            self.error(&message, implementation.span, &interface.name, requirement.span);
+        } else if implementation.entitlements != requirement.entitlements {
+            let message = format!(
+                "`{}` is `{}`, but `{}` requires `{}`",
+                implementation.name, access::keyword(&implementation.access), interface.name, access::keyword(&requirement.access),
+            );
+            self.error(&message, implementation.span, &interface.name, requirement.span);
+        }
+    }
+
+    fn same_type(&self, a: &Type, b: &Type) -> bool {
+        self.types.is_subtype(a, b) && self.types.is_subtype(b, a)
+    }
+
+    /// record the interface's conditions of the function for the composite
+    fn conditions(&mut self, composite: &CompositeType, interface: &CompositeType, name: &str) {
+        let Some(declaration) = self.interfaces.get(&interface.name) else {
+            return
+        };
+        let function = declaration.members.iter().find_map(|member| match member {
+            Declaration::Function(d) if d.identifier.name == name => Some(d),
+            _ => None,
+        });
+        let Some(FunctionDeclaration { pre_conditions, post_conditions, .. }) = function else {
+            return
+        };
+        if pre_conditions.is_empty() && post_conditions.is_empty() {
+            return
+        }
+        self.inherited.push(InheritedConditions {
+            composite: composite.name.clone(),
+            function: name.to_string(),
+            interface: interface.name.clone(),
+            pre_conditions: pre_conditions.clone(),
+            post_conditions: post_conditions.clone(),
+        });
+    }
+}
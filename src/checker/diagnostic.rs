@@ -0,0 +1,42 @@
+use crate::ast::Span;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// another place a diagnostic relates to, e.g. the declaration it conflicts with
+#[derive(Clone, Debug, PartialEq)]
+pub struct Note {
+    pub message: String,
+    pub span: Span,
+}
+
+/// a problem found by one of the checking passes
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    pub notes: Vec<Note>,
+}
+
+impl Diagnostic {
+    pub fn error(message: &str, span: Span) -> Self {
+        Diagnostic { severity: Severity::Error, message: message.to_string(), span, notes: Vec::new() }
+    }
+
+    pub fn warning(message: &str, span: Span) -> Self {
+        Diagnostic { severity: Severity::Warning, message: message.to_string(), span, notes: Vec::new() }
+    }
+
+    pub fn with_note(mut self, message: &str, span: Span) -> Self {
+        self.notes.push(Note { message: message.to_string(), span });
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
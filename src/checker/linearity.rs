@@ -0,0 +1,571 @@
+use std::collections::HashMap;
+
+use crate::ast::{
+    BinaryOperation, Block, Condition, Declaration, ElseBranch, Expression, FunctionDeclaration, Identifier,
+    IfStatement, IfTest, Program, Span, Statement, Transfer, UnaryOperation,
+};
+
+use super::checker::TypeCheck;
+use super::diagnostic::Diagnostic;
+use super::scope::SymbolId;
+use super::types::Type;
+
+/// how far a resource variable has been moved
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Resource {
+    Available,
+    /// moved on every path, at the span
+    Moved(Span),
+    /// moved on some paths only
+    MaybeMoved(Span),
+}
+
+/// resource variables at a point of a function
+#[derive(Clone, Debug, Default)]
+struct State {
+    resources: HashMap<SymbolId, Resource>,
+    /// after `return`, `break`, `continue` or an expression of type `Never`
+    unreachable: bool,
+}
+
+impl State {
+    /// the state after either of two paths
+    fn merge(self, other: State) -> State {
+        if self.unreachable {
+            return other
+        }
+        if other.unreachable {
+            return self
+        }
+        let mut resources = self.resources;
+        for (symbol, b) in other.resources {
+            let merged = match (resources.get(&symbol).copied(), b) {
+                (Some(Resource::Available), Resource::Available) => Resource::Available,
+                (Some(Resource::Moved(span)), Resource::Moved(_)) => Resource::Moved(span),
+                (Some(Resource::Moved(span) | Resource::MaybeMoved(span)), _) |
+                (_, Resource::Moved(span) | Resource::MaybeMoved(span)) => Resource::MaybeMoved(span),
+                (_, Resource::Available) => Resource::Available,
+            };
+            resources.insert(symbol, merged);
+        }
+        State { resources, unreachable: false }
+    }
+}
+
+/// states leaving a loop early
+#[derive(Default)]
+struct Loop {
+    /// number of scopes outside the loop
+    scopes: usize,
+    breaks: Vec<State>,
+    continues: Vec<State>,
+}
+
+/// report resources that are lost, used after being moved, moved twice, or transferred without `<-`
+pub fn check(program: &Program, checked: &TypeCheck) -> Vec<Diagnostic> {
+    let mut tracker = Tracker {
+        checked,
+        references: checked.resolution.references.iter().map(|reference| (reference.span.at, reference.symbol)).collect(),
+        declarations: checked.resolution.symbols.iter().enumerate()
+            .filter_map(|(id, symbol)| symbol.span.map(|span| (span.at, id)))
+            .collect(),
+        state: State::default(),
+        scopes: Vec::new(),
+        loops: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    tracker.declarations(&program.declarations);
+    tracker.diagnostics
+}
+
+struct Tracker<'a> {
+    checked: &'a TypeCheck,
+    /// symbols of used identifiers, by offset
+    references: HashMap<usize, SymbolId>,
+    /// symbols of declaring identifiers, by offset
+    declarations: HashMap<usize, SymbolId>,
+    state: State,
+    /// resource variables declared in each enclosing block, with their declaring identifiers
+    scopes: Vec<Vec<(SymbolId, Identifier)>>,
+    loops: Vec<Loop>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Tracker<'_> {
+    fn error(&mut self, message: &str, span: Span) {
+        self.diagnostics.push(Diagnostic::error(message, span));
+    }
+
+    fn is_resource(&self, ty: &Type) -> bool {
+        self.checked.types.is_resource(ty)
+    }
+
+    /// whether the expression's type is a resource, `None` if its type is not fully known
+    fn resource_expression(&self, expression: &Expression) -> Option<bool> {
+        let ty = self.checked.expression_type(expression)?;
+        is_known(ty).then(|| self.is_resource(ty))
+    }
+
+    /// the tracked resource variable an identifier refers to
+    fn variable(&self, identifier: &Identifier) -> Option<SymbolId> {
+        let symbol = self.references.get(&identifier.span.at)?;
+        self.state.resources.contains_key(symbol).then_some(*symbol)
+    }
+
+    // declarations
+
+    fn declarations(&mut self, declarations: &[Declaration]) {
+        for declaration in declarations.iter() {
+            match declaration {
+                Declaration::Composite(d) => self.declarations(&d.members),
+                Declaration::Interface(d) => {
+                    for member in d.members.iter() {
+                        match member {
+                            // functions with only conditions leave moving their resources to the implementations
+                            Declaration::Function(f) if f.body.as_ref().is_none_or(|body| body.statements.is_empty()) => (),
+                            member => self.declarations(std::slice::from_ref(member)),
+                        }
+                    }
+                },
+                Declaration::Function(d) => self.function(d),
+                Declaration::SpecialFunction(d) => self.function(&d.function),
+                Declaration::Transaction(d) => {
+                    if let Some(prepare) = &d.prepare {
+                        self.function(&prepare.function);
+                    }
+                    self.state = State::default();
+                    self.enter();
+                    self.conditions(&d.pre_conditions);
+                    if let Some(execute) = &d.execute {
+                        self.block(execute);
+                    }
+                    self.conditions(&d.post_conditions);
+                    self.exit();
+                },
+                _ => (),
+            }
+        }
+    }
+
+    fn function(&mut self, declaration: &FunctionDeclaration) {
+        let Some(body) = &declaration.body else {
+            return
+        };
+        self.state = State::default();
+        self.enter();
+        for parameter in declaration.parameters.iter() {
+            self.declare(&parameter.identifier);
+        }
+        self.conditions(&declaration.pre_conditions);
+        self.conditions(&declaration.post_conditions);
+        self.statements(&body.statements);
+        self.exit();
+    }
+
+    fn conditions(&mut self, conditions: &[Condition]) {
+        for condition in conditions.iter() {
+            self.expression(&condition.test);
+            if let Some(message) = &condition.message {
+                self.expression(message);
+            }
+        }
+    }
+
+    // scopes
+
+    fn enter(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    /// resources of the innermost scope must have been moved at its end
+    fn exit(&mut self) {
+        let scope = self.scopes.pop().unwrap_or_default();
+        if !self.state.unreachable {
+            for (_, identifier) in scope.iter() {
+                self.check_loss(identifier);
+            }
+        }
+        for (symbol, _) in scope.iter() {
+            self.state.resources.remove(symbol);
+        }
+    }
+
+    /// whether the declared variable has a resource type
+    fn declares_resource(&self, identifier: &Identifier) -> bool {
+        self.declarations.get(&identifier.span.at)
+            .and_then(|&symbol| self.checked.symbol_type(symbol))
+            .is_some_and(|ty| is_known(ty) && self.is_resource(ty))
+    }
+
+    /// track the variable declared by `identifier` if it holds a resource
+    fn declare(&mut self, identifier: &Identifier) {
+        let Some(&symbol) = self.declarations.get(&identifier.span.at) else {
+            return
+        };
+        if self.declares_resource(identifier) {
+            self.state.resources.insert(symbol, Resource::Available);
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.push((symbol, identifier.clone()));
+            }
+        }
+    }
+
+    fn check_loss(&mut self, identifier: &Identifier) {
+        let Some(&symbol) = self.declarations.get(&identifier.span.at) else {
+            return
+        };
+        match self.state.resources.get(&symbol) {
+            Some(Resource::Available) => {
+                let message = format!("loss of resource `{}`: it is neither moved nor destroyed", identifier.name);
+                self.error(&message, identifier.span);
+            },
+            Some(Resource::MaybeMoved(_)) => {
+                let message = format!("potential loss of resource `{}`: it is only moved on some paths", identifier.name);
+                self.error(&message, identifier.span);
+            },
+            _ => (),
+        }
+    }
+
+    /// leaving the scopes above `depth` early, by `return`, `break` or `continue`
+    fn leave(&mut self, depth: usize) {
+        if self.state.unreachable {
+            return
+        }
+        let identifiers: Vec<Identifier> = self.scopes[depth..].iter().flatten().map(|(_, identifier)| identifier.clone()).collect();
+        for identifier in identifiers.iter() {
+            self.check_loss(identifier);
+        }
+    }
+
+    // statements
+
+    fn block(&mut self, block: &Block) {
+        self.enter();
+        self.statements(&block.statements);
+        self.exit();
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements.iter() {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Expression(expression) => {
+                self.expression(expression);
+                if self.resource_expression(expression) == Some(true) {
+                    self.error("loss of resource: the value of this expression is neither moved nor destroyed", expression.span());
+                }
+                if self.checked.expression_type(expression) == Some(&Type::Never) {
+                    self.state.unreachable = true;
+                }
+            },
+            Statement::Return { expression, .. } => {
+                if let Some(expression) = expression {
+                    self.argument(expression);
+                }
+                self.leave(0);
+                self.state.unreachable = true;
+            },
+            Statement::Break { .. } | Statement::Continue { .. } => {
+                if let Some(depth) = self.loops.last().map(|loop_| loop_.scopes) {
+                    self.leave(depth);
+                    let state = self.state.clone();
+                    let loop_ = self.loops.last_mut().unwrap();
+                    match statement {
+                        Statement::Break { .. } => loop_.breaks.push(state),
+                        _ => loop_.continues.push(state),
+                    }
+                }
+                self.state.unreachable = true;
+            },
+            Statement::Variable(declaration) => {
+                self.transfer(declaration.transfer, &declaration.value, self.declares_resource(&declaration.identifier));
+                if let Some((transfer, value)) = &declaration.second {
+                    self.transfer(*transfer, value, self.resource_expression(&declaration.value) == Some(true));
+                }
+                self.declare(&declaration.identifier);
+            },
+            Statement::Assignment { target, transfer, value, .. } => {
+                self.transfer(*transfer, value, self.resource_expression(target) == Some(true));
+                match target {
+                    Expression::Identifier(identifier) => {
+                        if let Some(symbol) = self.variable(identifier) {
+                            let overwritten = self.state.resources.get(&symbol) == Some(&Resource::Available);
+                            if overwritten && *transfer != Transfer::MoveForce {
+                                let message = format!("loss of resource `{}`: it is overwritten by this assignment", identifier.name);
+                                self.error(&message, identifier.span);
+                            }
+                            self.state.resources.insert(symbol, Resource::Available);
+                        }
+                    },
+                    target => self.assignment_target(target),
+                }
+            },
+            Statement::Swap { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            },
+            Statement::If(statement) => self.if_statement(statement),
+            Statement::While { test, block, .. } => {
+                self.expression(test);
+                self.looped(block, None);
+            },
+            Statement::For { value, identifier, block, .. } => {
+                self.expression(value);
+                self.looped(block, Some(identifier));
+            },
+            Statement::Switch { expression, cases, .. } => {
+                self.expression(expression);
+                let before = self.state.clone();
+                let mut after = if cases.iter().any(|case| case.is_default()) {
+                    State { unreachable: true, ..State::default() }
+                } else {
+                    before.clone()
+                };
+                self.loops.push(Loop { scopes: self.scopes.len(), ..Loop::default() });
+                for case in cases.iter() {
+                    self.state = before.clone();
+                    for value in case.expressions.iter() {
+                        self.expression(value);
+                    }
+                    self.enter();
+                    self.statements(&case.statements);
+                    self.exit();
+                    after = after.merge(self.state.clone());
+                }
+                // `break` leaves the switch
+                let switch = self.loops.pop().unwrap();
+                self.state = switch.breaks.into_iter().chain(switch.continues).fold(after, State::merge);
+            },
+            Statement::Emit { invocation, .. } => self.expression(invocation),
+            Statement::Destroy { expression, .. } => self.moved(expression),
+            Statement::Error(_) => (),
+        }
+    }
+
+    fn if_statement(&mut self, statement: &IfStatement) {
+        match &statement.test {
+            IfTest::Expression(test) => {
+                self.expression(test);
+                let before = self.state.clone();
+                self.block(&statement.then);
+                let then = std::mem::replace(&mut self.state, before);
+                self.else_branch(statement);
+                self.state = then.merge(self.state.clone());
+            },
+            IfTest::Binding(binding) => {
+                // the optional is moved into the binding, which exists in the `then` block only
+                self.transfer(binding.transfer, &binding.value, self.declares_resource(&binding.identifier));
+                let before = self.state.clone();
+                self.enter();
+                self.declare(&binding.identifier);
+                self.statements(&statement.then.statements);
+                self.exit();
+                let then = std::mem::replace(&mut self.state, before);
+                self.else_branch(statement);
+                self.state = then.merge(self.state.clone());
+            },
+        }
+    }
+
+    fn else_branch(&mut self, statement: &IfStatement) {
+        match &statement.else_ {
+            Some(ElseBranch::Block(block)) => self.block(block),
+            Some(ElseBranch::If(else_if)) => self.if_statement(else_if),
+            None => (),
+        }
+    }
+
+    /// the body runs any number of times, so it must not move resources declared outside of it
+    fn looped(&mut self, block: &Block, element: Option<&Identifier>) {
+        let before = self.state.clone();
+        self.loops.push(Loop { scopes: self.scopes.len(), ..Loop::default() });
+        self.enter();
+        if let Some(element) = element {
+            self.declare(element);
+        }
+        self.statements(&block.statements);
+        self.exit();
+        let loop_ = self.loops.pop().unwrap();
+
+        let mut ends = loop_.continues;
+        ends.push(self.state.clone());
+        let mut reported = Vec::new();
+        for end in ends.iter().filter(|end| !end.unreachable) {
+            for (symbol, resource) in end.resources.iter() {
+                let moved = match resource {
+                    Resource::Moved(span) | Resource::MaybeMoved(span) => *span,
+                    Resource::Available => continue,
+                };
+                if before.resources.get(symbol) == Some(&Resource::Available) && !reported.contains(symbol) {
+                    reported.push(*symbol);
+                    let name = &self.checked.resolution.symbol(*symbol).name;
+                    let message = format!("resource `{}` is moved in a loop, it would be moved again in the next iteration", name);
+                    self.error(&message, moved);
+                }
+            }
+        }
+        self.state = ends.into_iter().chain(loop_.breaks).fold(before, State::merge);
+    }
+
+    // transfers
+
+    /// the value of a declaration or assignment, transferred with `=`, `<-` or `<-!`,
+    /// `into_resource` if the declared or assigned variable has a resource type
+    fn transfer(&mut self, transfer: Transfer, value: &Expression, into_resource: bool) {
+        // `nil` is moved into an optional resource
+        match (self.resource_expression(value).map(|resource| resource || into_resource), transfer) {
+            (Some(true), Transfer::Copy) => {
+                self.error("missing move operator: resources are transferred with `<-`, not `=`", value.span());
+            },
+            (Some(false), Transfer::Move | Transfer::MoveForce) => {
+                let message = format!("invalid move operator `{}` for non-resource value, use `=`", transfer.as_str());
+                self.error(&message, value.span());
+            },
+            _ => (),
+        }
+        self.moved(value);
+    }
+
+    /// a resource passed to a function, returned, or put into an array or dictionary, which requires `<-`
+    fn argument(&mut self, expression: &Expression) {
+        let is_move = matches!(expression, Expression::Unary { operation: UnaryOperation::Move, .. });
+        if self.resource_expression(expression) == Some(true) && !is_move {
+            self.error("missing move operator `<-` for resource value", expression.span());
+        }
+        self.moved(expression);
+    }
+
+    /// an expression whose value is moved
+    fn moved(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Unary { operation: UnaryOperation::Move, expression, .. } |
+            Expression::Casting { expression, .. } |
+            Expression::Force { expression, .. } => self.moved(expression),
+            Expression::Identifier(identifier) => match self.variable(identifier) {
+                Some(symbol) => self.move_variable(symbol, identifier),
+                None => self.expression(expression),
+            },
+            Expression::Conditional { test, then, else_, .. } => {
+                self.expression(test);
+                self.branches(|tracker| tracker.moved(then), |tracker| tracker.moved(else_));
+            },
+            Expression::Binary { operation: BinaryOperation::NilCoalescing, left, right, .. } => {
+                self.moved(left);
+                self.branches(|_| (), |tracker| tracker.moved(right));
+            },
+            expression => self.expression(expression),
+        }
+    }
+
+    fn move_variable(&mut self, symbol: SymbolId, identifier: &Identifier) {
+        match self.state.resources.get(&symbol) {
+            Some(Resource::Moved(_)) => {
+                self.error(&format!("resource `{}` has already been moved", identifier.name), identifier.span);
+            },
+            Some(Resource::MaybeMoved(_)) => {
+                self.error(&format!("resource `{}` may have already been moved", identifier.name), identifier.span);
+            },
+            _ => (),
+        }
+        self.state.resources.insert(symbol, Resource::Moved(identifier.span));
+    }
+
+    /// two alternative paths from the current state
+    fn branches(&mut self, first: impl FnOnce(&mut Self), second: impl FnOnce(&mut Self)) {
+        let before = self.state.clone();
+        first(self);
+        let first_state = std::mem::replace(&mut self.state, before);
+        second(self);
+        self.state = first_state.merge(self.state.clone());
+    }
+
+    // uses
+
+    /// the sub-expressions of an assignment target are used, not moved
+    fn assignment_target(&mut self, target: &Expression) {
+        match target {
+            Expression::Member { expression, .. } => self.expression(expression),
+            Expression::Index { expression, index, .. } => {
+                self.expression(expression);
+                self.expression(index);
+            },
+            target => self.expression(target),
+        }
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Nil { .. } | Expression::Bool { .. } | Expression::Integer { .. } |
+            Expression::FixedPoint { .. } | Expression::String { .. } | Expression::Path { .. } => (),
+            Expression::Identifier(identifier) => {
+                if let Some(symbol) = self.variable(identifier) {
+                    match self.state.resources.get(&symbol) {
+                        Some(Resource::Moved(_)) => {
+                            self.error(&format!("use of moved resource `{}`", identifier.name), identifier.span);
+                        },
+                        Some(Resource::MaybeMoved(_)) => {
+                            self.error(&format!("use of potentially moved resource `{}`", identifier.name), identifier.span);
+                        },
+                        _ => (),
+                    }
+                }
+            },
+            Expression::Array { values, .. } => {
+                for value in values.iter() {
+                    self.argument(value);
+                }
+            },
+            Expression::Dictionary { entries, .. } => {
+                for (key, value) in entries.iter() {
+                    self.expression(key);
+                    self.argument(value);
+                }
+            },
+            Expression::Member { expression, .. } => self.expression(expression),
+            Expression::Index { expression, index, .. } => {
+                self.expression(expression);
+                self.expression(index);
+            },
+            Expression::Invocation { invoked, arguments, .. } => {
+                self.expression(invoked);
+                for argument in arguments.iter() {
+                    self.argument(&argument.expression);
+                }
+            },
+            Expression::Unary { operation: UnaryOperation::Move, .. } => self.moved(expression),
+            Expression::Unary { expression, .. } => self.expression(expression),
+            Expression::Binary { operation: BinaryOperation::NilCoalescing | BinaryOperation::And | BinaryOperation::Or, left, right, .. } => {
+                self.expression(left);
+                self.branches(|_| (), |tracker| tracker.expression(right));
+            },
+            Expression::Binary { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            },
+            Expression::Conditional { test, then, else_, .. } => {
+                self.expression(test);
+                self.branches(|tracker| tracker.expression(then), |tracker| tracker.expression(else_));
+            },
+            Expression::Casting { expression, .. } |
+            Expression::Create { invocation: expression, .. } |
+            Expression::Reference { expression, .. } |
+            Expression::Force { expression, .. } => self.expression(expression),
+        }
+    }
+}
+
+/// whether the type has no parts the type checker could not determine
+fn is_known(ty: &Type) -> bool {
+    match ty {
+        Type::Invalid | Type::TypeParameter(_) => false,
+        Type::Optional(ty) | Type::VariableSized(ty) | Type::ConstantSized(ty, _) | Type::Reference { ty, .. } |
+        Type::Restricted { ty, .. } => is_known(ty),
+        Type::Dictionary(key, value) => is_known(key) && is_known(value),
+        _ => true,
+    }
+}
@@ -0,0 +1,9 @@
+pub mod access;
+#[allow(clippy::module_inception)]
+pub mod checker;
+pub mod conformance;
+pub mod diagnostic;
+pub mod linearity;
+pub mod resolver;
+pub mod scope;
+pub mod types;
@@ -0,0 +1,442 @@
+use crate::ast::{
+    Block, CompositeDeclaration, Declaration, ElseBranch, Expression, FieldDeclaration, FunctionDeclaration,
+    Identifier, IfStatement, IfTest, ImportLocation, InterfaceDeclaration, NominalType, Program,
+    SpecialFunctionDeclaration, SpecialFunctionKind, Span, Statement, SwitchCase, TransactionDeclaration,
+    VariableDeclaration, VariableKind,
+};
+use crate::ast::visit::{
+    Visitor, walk_expression, walk_statement,
+};
+
+use super::diagnostic::Diagnostic;
+use super::scope::{
+    BUILT_IN_ENTITLEMENTS, BUILT_IN_FUNCTIONS, BUILT_IN_TYPES, Namespace, Scope, ScopeId, ScopeKind, Symbol, SymbolId, SymbolKind,
+};
+
+/// an identifier bound to the declaration it refers to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reference {
+    pub span: Span,
+    pub symbol: SymbolId,
+}
+
+/// scopes of a program and what every name in it refers to
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resolution {
+    /// the built-in scope first, then the program scope, inner scopes after the scopes containing them
+    pub scopes: Vec<Scope>,
+    pub symbols: Vec<Symbol>,
+    /// in source order
+    pub references: Vec<Reference>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Resolution {
+    pub fn symbol(&self, id: SymbolId) -> &Symbol {
+        &self.symbols[id]
+    }
+
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id]
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|diagnostic| diagnostic.is_error())
+    }
+
+    /// the symbol used or declared by the identifier at `offset`, for go-to-definition
+    pub fn symbol_at(&self, offset: usize) -> Option<SymbolId> {
+        let contains = |span: Span| span.at <= offset && offset < span.end();
+        self.references.iter().find(|reference| contains(reference.span)).map(|reference| reference.symbol)
+            .or_else(|| self.symbols.iter().position(|symbol| symbol.span.is_some_and(contains)))
+    }
+
+    /// all uses of `symbol`, for renaming together with its declaration
+    pub fn references_to(&self, symbol: SymbolId) -> Vec<Span> {
+        self.references.iter().filter(|reference| reference.symbol == symbol).map(|reference| reference.span).collect()
+    }
+
+    /// the innermost scope containing `offset`
+    pub fn scope_at(&self, offset: usize) -> ScopeId {
+        // scopes are created after the scopes containing them
+        self.scopes.iter().rposition(|scope| {
+            scope.kind == ScopeKind::Program || (scope.span.at <= offset && offset < scope.span.end())
+        }).unwrap_or(0)
+    }
+
+    /// what `name` means in `scope`
+    pub fn lookup(&self, scope: ScopeId, name: &str, namespace: Namespace) -> Option<SymbolId> {
+        let mut current = Some(scope);
+        while let Some(id) = current {
+            let scope = &self.scopes[id];
+            if let Some(symbol) = scope.get(name) {
+                let kind = self.symbols[symbol].kind;
+                // members are reached through `self`, transaction parameters are not members
+                let visible = !scope.kind.has_members() || kind.is_in(Namespace::Type) || kind == SymbolKind::Parameter;
+                if visible && kind.is_in(namespace) {
+                    return Some(symbol)
+                }
+            }
+            current = scope.parent;
+        }
+        None
+    }
+}
+
+/// build the scopes of `program` and bind every identifier use to its declaration
+pub fn resolve(program: &Program) -> Resolution {
+    let mut resolver = Resolver {
+        resolution: Resolution { scopes: Vec::new(), symbols: Vec::new(), references: Vec::new(), diagnostics: Vec::new() },
+        current: 0,
+    };
+    resolver.resolution.scopes.push(Scope::new(ScopeKind::BuiltIn, None, Span::default()));
+    for name in BUILT_IN_TYPES.iter() {
+        resolver.declare_implicit(name, SymbolKind::BuiltInType);
+    }
+    for name in BUILT_IN_FUNCTIONS.iter() {
+        resolver.declare_implicit(name, SymbolKind::BuiltInFunction);
+    }
+    for name in BUILT_IN_ENTITLEMENTS.iter() {
+        resolver.declare_implicit(name, SymbolKind::Entitlement);
+    }
+    resolver.visit_program(program);
+    resolver.resolution.references.sort_by_key(|reference| reference.span.at);
+    resolver.resolution
+}
+
+struct Resolver {
+    resolution: Resolution,
+    current: ScopeId,
+}
+
+impl Resolver {
+    fn enter(&mut self, kind: ScopeKind, span: Span) {
+        self.resolution.scopes.push(Scope::new(kind, Some(self.current), span));
+        self.current = self.resolution.scopes.len() - 1;
+    }
+
+    fn exit(&mut self) {
+        self.current = self.resolution.scopes[self.current].parent.expect("exit from the built-in scope");
+    }
+
+    fn scoped(&mut self, kind: ScopeKind, span: Span, body: impl FnOnce(&mut Self)) {
+        self.enter(kind, span);
+        body(self);
+        self.exit();
+    }
+
+    fn add_symbol(&mut self, name: &str, kind: SymbolKind, span: Option<Span>) -> SymbolId {
+        let id = self.resolution.symbols.len();
+        self.resolution.symbols.push(Symbol { name: name.to_string(), kind, span, scope: self.current });
+        self.resolution.scopes[self.current].insert(name, id);
+        id
+    }
+
+    fn declare_implicit(&mut self, name: &str, kind: SymbolKind) {
+        self.add_symbol(name, kind, None);
+    }
+
+    fn declare(&mut self, identifier: &Identifier, kind: SymbolKind) {
+        let name = &identifier.name;
+        let scope = &self.resolution.scopes[self.current];
+        if let Some(previous) = scope.get(name) {
+            let previous = &self.resolution.symbols[previous];
+            let message = format!("cannot redeclare `{}`: it is already declared as {} in this scope", name, previous.kind.describe());
+            self.resolution.diagnostics.push(Diagnostic::error(&message, identifier.span));
+        } else if let Some(parent) = scope.parent.filter(|_| kind.is_local()) {
+            if let Some(shadowed) = self.resolution.lookup(parent, name, Namespace::Value) {
+                let shadowed = &self.resolution.symbols[shadowed];
+                let message = format!("`{}` shadows the {} of the same name", name, shadowed.kind.describe());
+                self.resolution.diagnostics.push(Diagnostic::warning(&message, identifier.span));
+            }
+        }
+        self.add_symbol(name, kind, Some(identifier.span));
+    }
+
+    /// declare named declarations ahead of their scope's bodies, which may use them before their position
+    fn declare_all(&mut self, declarations: &[Declaration]) {
+        for declaration in declarations.iter() {
+            let kind = match declaration {
+                Declaration::Import(import) => {
+                    for identifier in import.identifiers.iter() {
+                        self.declare(identifier, SymbolKind::Import);
+                    }
+                    if import.identifiers.is_empty() {
+                        match &import.location {
+                            ImportLocation::Identifier(identifier) => self.declare(identifier, SymbolKind::Import),
+                            ImportLocation::String(name) => {
+                                let identifier = Identifier::new(name, import.span);
+                                self.declare(&identifier, SymbolKind::Import);
+                            },
+                            ImportLocation::Address(_) => (),
+                        }
+                    }
+                    continue;
+                },
+                Declaration::Composite(d) => SymbolKind::Composite(d.kind),
+                Declaration::Interface(d) => SymbolKind::Interface(d.kind),
+                Declaration::Field(_) => SymbolKind::Field,
+                Declaration::Function(_) => SymbolKind::Function,
+                Declaration::EnumCase(_) => SymbolKind::EnumCase,
+                Declaration::Variable(d) => variable_kind(d.kind),
+                Declaration::Entitlement(_) => SymbolKind::Entitlement,
+                Declaration::EntitlementMapping(_) => SymbolKind::EntitlementMapping,
+                Declaration::SpecialFunction(_) | Declaration::Transaction(_) | Declaration::Error(_) => continue,
+            };
+            if let Some(identifier) = declaration.identifier() {
+                self.declare(identifier, kind);
+            }
+        }
+    }
+
+    fn resolve(&mut self, identifier: &Identifier, namespace: Namespace) {
+        match self.resolution.lookup(self.current, &identifier.name, namespace) {
+            Some(symbol) => self.resolution.references.push(Reference { span: identifier.span, symbol }),
+            None => {
+                let what = match namespace {
+                    Namespace::Type => "type",
+                    Namespace::Value => "variable",
+                };
+                let message = format!("cannot find {} in this scope: `{}`", what, identifier.name);
+                self.resolution.diagnostics.push(Diagnostic::error(&message, identifier.span));
+            },
+        }
+    }
+
+    /// a function's parameters, conditions and body, functions of composites and transactions have `self`
+    fn function(&mut self, declaration: &FunctionDeclaration, parameter_kind: SymbolKind) {
+        for parameter in declaration.parameters.iter() {
+            self.visit_type_annotation(&parameter.type_annotation);
+        }
+        if let Some(return_type) = &declaration.return_type {
+            self.visit_type_annotation(return_type);
+        }
+        let is_member = self.resolution.scopes[self.current].kind.has_members();
+        self.scoped(ScopeKind::Function, declaration.span, |r| {
+            if is_member {
+                r.declare_implicit("self", SymbolKind::SelfValue);
+            }
+            for parameter in declaration.parameters.iter() {
+                r.declare(&parameter.identifier, parameter_kind);
+            }
+            for condition in declaration.pre_conditions.iter() {
+                r.visit_condition(condition);
+            }
+            if !declaration.post_conditions.is_empty() {
+                r.post_conditions(declaration, |r| {
+                    for condition in declaration.post_conditions.iter() {
+                        r.visit_condition(condition);
+                    }
+                });
+            }
+            // the body shares the scope of the parameters
+            if let Some(body) = &declaration.body {
+                r.statements(&body.statements);
+            }
+        });
+    }
+
+    /// `before`, and `result` if the function returns a value
+    fn post_conditions(&mut self, declaration: &FunctionDeclaration, body: impl FnOnce(&mut Self)) {
+        let span = Span::between(declaration.post_conditions[0].span, declaration.post_conditions.last().unwrap().span);
+        self.scoped(ScopeKind::Block, span, |r| {
+            r.declare_implicit("before", SymbolKind::PostCondition);
+            if declaration.return_type.is_some() {
+                r.declare_implicit("result", SymbolKind::PostCondition);
+            }
+            body(r);
+        });
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements.iter() {
+            self.visit_statement(statement);
+        }
+    }
+
+    /// resolve the value and type of a local variable, then declare it
+    fn local_variable(&mut self, declaration: &VariableDeclaration) {
+        self.visit_variable_declaration(declaration);
+        self.declare(&declaration.identifier, variable_kind(declaration.kind));
+    }
+}
+
+fn variable_kind(kind: VariableKind) -> SymbolKind {
+    match kind {
+        VariableKind::Constant => SymbolKind::Constant,
+        VariableKind::Variable => SymbolKind::Variable,
+    }
+}
+
+impl Visitor for Resolver {
+    fn visit_program(&mut self, program: &Program) {
+        self.enter(ScopeKind::Program, program.span);
+        self.declare_all(&program.declarations);
+        for declaration in program.declarations.iter() {
+            self.visit_declaration(declaration);
+        }
+        self.exit();
+    }
+
+    fn visit_composite_declaration(&mut self, declaration: &CompositeDeclaration) {
+        self.visit_access(&declaration.access);
+        for conformance in declaration.conformances.iter() {
+            self.visit_nominal_type(conformance);
+        }
+        self.scoped(ScopeKind::Composite, declaration.span, |r| {
+            // event parameters become the event's fields
+            for parameter in declaration.parameters.iter() {
+                r.visit_type_annotation(&parameter.type_annotation);
+                r.declare(&parameter.identifier, SymbolKind::Field);
+            }
+            r.declare_all(&declaration.members);
+            for member in declaration.members.iter() {
+                r.visit_declaration(member);
+            }
+        });
+    }
+
+    fn visit_interface_declaration(&mut self, declaration: &InterfaceDeclaration) {
+        self.visit_access(&declaration.access);
+        for conformance in declaration.conformances.iter() {
+            self.visit_nominal_type(conformance);
+        }
+        self.scoped(ScopeKind::Interface, declaration.span, |r| {
+            r.declare_all(&declaration.members);
+            for member in declaration.members.iter() {
+                r.visit_declaration(member);
+            }
+        });
+    }
+
+    fn visit_field_declaration(&mut self, declaration: &FieldDeclaration) {
+        self.visit_access(&declaration.access);
+        self.visit_type_annotation(&declaration.type_annotation);
+    }
+
+    fn visit_function_declaration(&mut self, declaration: &FunctionDeclaration) {
+        self.visit_access(&declaration.access);
+        self.function(declaration, SymbolKind::Parameter);
+    }
+
+    fn visit_special_function_declaration(&mut self, declaration: &SpecialFunctionDeclaration) {
+        let parameter_kind = match declaration.kind {
+            SpecialFunctionKind::Prepare => SymbolKind::Signer,
+            SpecialFunctionKind::Initializer | SpecialFunctionKind::Destructor => SymbolKind::Parameter,
+        };
+        self.function(&declaration.function, parameter_kind);
+    }
+
+    /// declared by the enclosing scope or statement, only the type and value are resolved here
+    fn visit_variable_declaration(&mut self, declaration: &VariableDeclaration) {
+        if let Some(type_annotation) = &declaration.type_annotation {
+            self.visit_type_annotation(type_annotation);
+        }
+        self.visit_expression(&declaration.value);
+        if let Some((_, value)) = &declaration.second {
+            self.visit_expression(value);
+        }
+    }
+
+    fn visit_transaction_declaration(&mut self, declaration: &TransactionDeclaration) {
+        self.scoped(ScopeKind::Transaction, declaration.span, |r| {
+            for parameter in declaration.parameters.iter() {
+                r.visit_type_annotation(&parameter.type_annotation);
+                r.declare(&parameter.identifier, SymbolKind::Parameter);
+            }
+            for field in declaration.fields.iter() {
+                r.visit_field_declaration(field);
+                r.declare(&field.identifier, SymbolKind::Field);
+            }
+            if let Some(prepare) = &declaration.prepare {
+                r.visit_special_function_declaration(prepare);
+            }
+            let phases = declaration.pre_conditions.iter().map(|condition| condition.span)
+                .chain(declaration.execute.iter().map(|execute| execute.span))
+                .chain(declaration.post_conditions.iter().map(|condition| condition.span))
+                .reduce(Span::between);
+            if let Some(span) = phases {
+                // `pre`, `execute` and `post` see `self` like the functions of the transaction
+                r.scoped(ScopeKind::Function, span, |r| {
+                    r.declare_implicit("self", SymbolKind::SelfValue);
+                    for condition in declaration.pre_conditions.iter() {
+                        r.visit_condition(condition);
+                    }
+                    if let Some(execute) = &declaration.execute {
+                        r.visit_block(execute);
+                    }
+                    if let (Some(first), Some(last)) = (declaration.post_conditions.first(), declaration.post_conditions.last()) {
+                        r.scoped(ScopeKind::Block, Span::between(first.span, last.span), |r| {
+                            r.declare_implicit("before", SymbolKind::PostCondition);
+                            for condition in declaration.post_conditions.iter() {
+                                r.visit_condition(condition);
+                            }
+                        });
+                    }
+                });
+            }
+        });
+    }
+
+    fn visit_nominal_type(&mut self, nominal: &NominalType) {
+        // nested identifiers are members of the first one
+        self.resolve(&nominal.identifier, Namespace::Type);
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.scoped(ScopeKind::Block, block.span, |r| r.statements(&block.statements));
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Variable(declaration) => self.local_variable(declaration),
+            Statement::For { index, identifier, value, block, .. } => {
+                self.visit_expression(value);
+                self.scoped(ScopeKind::Block, block.span, |r| {
+                    if let Some(index) = index {
+                        r.declare(index, SymbolKind::Constant);
+                    }
+                    r.declare(identifier, SymbolKind::Constant);
+                    r.statements(&block.statements);
+                });
+            },
+            _ => walk_statement(self, statement),
+        }
+    }
+
+    fn visit_if_statement(&mut self, statement: &IfStatement) {
+        match &statement.test {
+            IfTest::Expression(test) => {
+                self.visit_expression(test);
+                self.visit_block(&statement.then);
+            },
+            // the binding is only declared in the `then` block
+            IfTest::Binding(binding) => {
+                self.visit_variable_declaration(binding);
+                self.scoped(ScopeKind::Block, statement.then.span, |r| {
+                    r.declare(&binding.identifier, variable_kind(binding.kind));
+                    r.statements(&statement.then.statements);
+                });
+            },
+        }
+        match &statement.else_ {
+            Some(ElseBranch::Block(block)) => self.visit_block(block),
+            Some(ElseBranch::If(else_if)) => self.visit_if_statement(else_if),
+            None => (),
+        }
+    }
+
+    fn visit_switch_case(&mut self, case: &SwitchCase) {
+        for expression in case.expressions.iter() {
+            self.visit_expression(expression);
+        }
+        self.scoped(ScopeKind::Block, case.span, |r| r.statements(&case.statements));
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Identifier(identifier) => self.resolve(identifier, Namespace::Value),
+            _ => walk_expression(self, expression),
+        }
+    }
+}
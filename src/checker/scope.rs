@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use crate::ast::{
+    CompositeKind, Span,
+};
+
+pub type ScopeId = usize;
+pub type SymbolId = usize;
+
+/// types and values are looked up separately, composites are both
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Namespace {
+    Type,
+    Value,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// predeclared types and functions, parent of the program scope
+    BuiltIn,
+    Program,
+    Composite,
+    Interface,
+    Transaction,
+    Function,
+    Block,
+}
+
+impl ScopeKind {
+    /// members of composites, interfaces and transactions are reached through `self`, not by name
+    pub fn has_members(&self) -> bool {
+        matches!(self, Self::Composite | Self::Interface | Self::Transaction)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// `Int`, `String`, ..., also callable as conversion functions
+    BuiltInType,
+    /// `log`, `panic`, ...
+    BuiltInFunction,
+    Import,
+    Composite(CompositeKind),
+    Interface(CompositeKind),
+    /// `entitlement E`
+    Entitlement,
+    /// `entitlement mapping M`
+    EntitlementMapping,
+    Field,
+    Function,
+    EnumCase,
+    Constant,
+    Variable,
+    Parameter,
+    /// parameter of a transaction's `prepare`
+    Signer,
+    /// `self` in functions of composites, interfaces and transactions
+    SelfValue,
+    /// `result` and `before` in post conditions
+    PostCondition,
+}
+
+impl SymbolKind {
+    pub fn is_in(&self, namespace: Namespace) -> bool {
+        match namespace {
+            Namespace::Type => matches!(self,
+                Self::BuiltInType | Self::Import | Self::Composite(_) | Self::Interface(_) | Self::Entitlement | Self::EntitlementMapping
+            ),
+            Namespace::Value => !matches!(self, Self::Interface(_) | Self::Entitlement | Self::EntitlementMapping),
+        }
+    }
+
+    /// declared inside a function, where hiding an outer declaration is reported
+    pub fn is_local(&self) -> bool {
+        matches!(self, Self::Constant | Self::Variable | Self::Parameter | Self::Signer)
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::BuiltInType => "built-in type",
+            Self::BuiltInFunction => "built-in function",
+            Self::Import => "import",
+            Self::Composite(kind) => kind.keyword(),
+            Self::Interface(_) => "interface",
+            Self::Entitlement => "entitlement",
+            Self::EntitlementMapping => "entitlement mapping",
+            Self::Field => "field",
+            Self::Function => "function",
+            Self::EnumCase => "enum case",
+            Self::Constant => "constant",
+            Self::Variable => "variable",
+            Self::Parameter => "parameter",
+            Self::Signer => "signer",
+            Self::SelfValue => "self",
+            Self::PostCondition => "post-condition value",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// the declaring identifier, `None` for built-ins, `self`, `result` and `before`
+    pub span: Option<Span>,
+    pub scope: ScopeId,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<ScopeId>,
+    pub span: Span,
+    /// declarations in order
+    pub symbols: Vec<SymbolId>,
+    names: HashMap<String, SymbolId>,
+}
+
+impl Scope {
+    pub fn new(kind: ScopeKind, parent: Option<ScopeId>, span: Span) -> Self {
+        Scope { kind, parent, span, symbols: Vec::new(), names: HashMap::new() }
+    }
+
+    /// the first declaration of `name` in this scope
+    pub fn get(&self, name: &str) -> Option<SymbolId> {
+        self.names.get(name).copied()
+    }
+
+    /// add a declaration, the name keeps referring to an earlier one
+    pub fn insert(&mut self, name: &str, symbol: SymbolId) {
+        self.symbols.push(symbol);
+        self.names.entry(name.to_string()).or_insert(symbol);
+    }
+}
+
+pub const BUILT_IN_TYPES: &[&str] = &[
+    "Never", "Void", "AnyStruct", "AnyResource", "Bool", "Character", "String", "Address", "Type", "Block",
+    "Path", "StoragePath", "PublicPath", "PrivatePath", "CapabilityPath", "Capability",
+    "Number", "SignedNumber", "Integer", "SignedInteger", "FixedPoint", "SignedFixedPoint",
+    "Int", "Int8", "Int16", "Int32", "Int64", "Int128", "Int256",
+    "UInt", "UInt8", "UInt16", "UInt32", "UInt64", "UInt128", "UInt256",
+    "Word8", "Word16", "Word32", "Word64", "Word128", "Word256", "Fix64", "UFix64",
+    "AuthAccount", "PublicAccount", "Account", "DeployedContract", "PublicKey", "HashAlgorithm", "SignatureAlgorithm",
+    "RLP", "BLS",
+];
+
+/// the entitlements of `Account` and its members
+pub const BUILT_IN_ENTITLEMENTS: &[&str] = &[
+    "Storage", "SaveValue", "LoadValue", "CopyValue", "BorrowValue", "Contracts", "AddContract", "UpdateContract",
+    "RemoveContract", "Keys", "AddKey", "RevokeKey", "Inbox", "PublishInboxCapability", "UnpublishInboxCapability",
+    "ClaimInboxCapability", "Capabilities", "StorageCapabilities", "AccountCapabilities", "PublishCapability",
+    "UnpublishCapability", "GetStorageCapabilityController", "IssueStorageCapabilityController",
+    "GetAccountCapabilityController", "IssueAccountCapabilityController", "Mutate", "Insert", "Remove",
+];
+
+pub const BUILT_IN_FUNCTIONS: &[&str] = &[
+    "log", "panic", "assert", "getAccount", "getAuthAccount", "getCurrentBlock", "getBlock", "unsafeRandom",
+    "revertibleRandom",
+];
@@ -0,0 +1,851 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::ast::{
+    Access, CompositeKind, Span, VariableKind,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NumberType {
+    Number,
+    SignedNumber,
+    Integer,
+    SignedInteger,
+    FixedPoint,
+    SignedFixedPoint,
+    Int,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Int128,
+    Int256,
+    UInt,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    UInt128,
+    UInt256,
+    Word8,
+    Word16,
+    Word32,
+    Word64,
+    Word128,
+    Word256,
+    Fix64,
+    UFix64,
+}
+
+const NUMBER_TYPES: &[NumberType] = &[
+    NumberType::Number, NumberType::SignedNumber, NumberType::Integer, NumberType::SignedInteger,
+    NumberType::FixedPoint, NumberType::SignedFixedPoint,
+    NumberType::Int, NumberType::Int8, NumberType::Int16, NumberType::Int32, NumberType::Int64, NumberType::Int128,
+    NumberType::Int256, NumberType::UInt, NumberType::UInt8, NumberType::UInt16, NumberType::UInt32, NumberType::UInt64,
+    NumberType::UInt128, NumberType::UInt256, NumberType::Word8, NumberType::Word16, NumberType::Word32,
+    NumberType::Word64, NumberType::Word128, NumberType::Word256, NumberType::Fix64, NumberType::UFix64,
+];
+
+impl NumberType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        NUMBER_TYPES.iter().find(|number| number.name() == name).copied()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Number => "Number",
+            Self::SignedNumber => "SignedNumber",
+            Self::Integer => "Integer",
+            Self::SignedInteger => "SignedInteger",
+            Self::FixedPoint => "FixedPoint",
+            Self::SignedFixedPoint => "SignedFixedPoint",
+            Self::Int => "Int",
+            Self::Int8 => "Int8",
+            Self::Int16 => "Int16",
+            Self::Int32 => "Int32",
+            Self::Int64 => "Int64",
+            Self::Int128 => "Int128",
+            Self::Int256 => "Int256",
+            Self::UInt => "UInt",
+            Self::UInt8 => "UInt8",
+            Self::UInt16 => "UInt16",
+            Self::UInt32 => "UInt32",
+            Self::UInt64 => "UInt64",
+            Self::UInt128 => "UInt128",
+            Self::UInt256 => "UInt256",
+            Self::Word8 => "Word8",
+            Self::Word16 => "Word16",
+            Self::Word32 => "Word32",
+            Self::Word64 => "Word64",
+            Self::Word128 => "Word128",
+            Self::Word256 => "Word256",
+            Self::Fix64 => "Fix64",
+            Self::UFix64 => "UFix64",
+        }
+    }
+
+    /// `Number`, `Integer`, ..., which have no values of their own
+    pub fn is_abstract(&self) -> bool {
+        matches!(self,
+            Self::Number | Self::SignedNumber | Self::Integer | Self::SignedInteger | Self::FixedPoint | Self::SignedFixedPoint
+        )
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.is_subtype(Self::Integer)
+    }
+
+    pub fn is_fixed_point(&self) -> bool {
+        self.is_subtype(Self::FixedPoint)
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.is_subtype(Self::SignedNumber)
+    }
+
+    /// the abstract type directly above this one
+    fn parent(&self) -> Option<Self> {
+        match self {
+            Self::Number => None,
+            Self::SignedNumber | Self::Integer | Self::FixedPoint => Some(Self::Number),
+            Self::SignedInteger | Self::UInt | Self::UInt8 | Self::UInt16 | Self::UInt32 | Self::UInt64 | Self::UInt128 |
+            Self::UInt256 | Self::Word8 | Self::Word16 | Self::Word32 | Self::Word64 | Self::Word128 | Self::Word256 => {
+                Some(Self::Integer)
+            },
+            Self::SignedFixedPoint | Self::UFix64 => Some(Self::FixedPoint),
+            Self::Int | Self::Int8 | Self::Int16 | Self::Int32 | Self::Int64 | Self::Int128 | Self::Int256 => {
+                Some(Self::SignedInteger)
+            },
+            Self::Fix64 => Some(Self::SignedFixedPoint),
+        }
+    }
+
+    pub fn is_subtype(&self, other: Self) -> bool {
+        if *self == other {
+            return true
+        }
+        // signed integers and fixed points are also signed numbers
+        if other == Self::SignedNumber && matches!(self, Self::SignedInteger | Self::SignedFixedPoint) {
+            return true
+        }
+        self.parent().is_some_and(|parent| parent.is_subtype(other))
+    }
+
+    /// smallest and largest value of a fixed size integer type, `None` if unbounded or wider than 128 bits
+    pub fn integer_range(&self) -> Option<(i128, u128)> {
+        let bits = match self {
+            Self::Int8 | Self::UInt8 | Self::Word8 => 8,
+            Self::Int16 | Self::UInt16 | Self::Word16 => 16,
+            Self::Int32 | Self::UInt32 | Self::Word32 => 32,
+            Self::Int64 | Self::UInt64 | Self::Word64 => 64,
+            Self::Int128 | Self::UInt128 | Self::Word128 => 128,
+            _ => return None,
+        };
+        if self.is_signed() {
+            Some((i128::MIN >> (128 - bits), (i128::MAX >> (128 - bits)) as u128))
+        } else {
+            Some((0, u128::MAX >> (128 - bits)))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PathType {
+    Path,
+    StoragePath,
+    CapabilityPath,
+    PublicPath,
+    PrivatePath,
+}
+
+impl PathType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Path => "Path",
+            Self::StoragePath => "StoragePath",
+            Self::CapabilityPath => "CapabilityPath",
+            Self::PublicPath => "PublicPath",
+            Self::PrivatePath => "PrivatePath",
+        }
+    }
+
+    pub fn is_subtype(&self, other: Self) -> bool {
+        match (self, other) {
+            (a, b) if *a == b => true,
+            (_, Self::Path) => true,
+            (Self::PublicPath | Self::PrivatePath, Self::CapabilityPath) => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionParameter {
+    /// the label arguments must have, `None` for none
+    pub label: Option<String>,
+    pub ty: Type,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionType {
+    /// types used for missing type arguments that cannot be inferred
+    pub type_parameters: Vec<Type>,
+    pub parameters: Vec<FunctionParameter>,
+    pub return_type: Box<Type>,
+}
+
+impl FunctionType {
+    pub fn new(parameters: Vec<FunctionParameter>, return_type: Type) -> Self {
+        FunctionType { type_parameters: Vec::new(), parameters, return_type: Box::new(return_type) }
+    }
+}
+
+/// the entitlements a reference is authorized for, or a member requires
+#[derive(Clone, Debug, PartialEq)]
+pub enum Authorization {
+    /// `&T`, or a member any reference can access
+    Unauthorized,
+    /// `auth &T` from before entitlements, which grants everything
+    Auth,
+    /// all of the qualified entitlements
+    Conjunction(Vec<String>),
+    /// one of the qualified entitlements
+    Disjunction(Vec<String>),
+    /// the entitlements a qualified entitlement mapping maps those of the accessing reference to
+    Mapping(String),
+}
+
+impl Authorization {
+    pub fn is_authorized(&self) -> bool {
+        *self != Authorization::Unauthorized
+    }
+
+    /// whether a reference authorized for these entitlements may stand for one authorized for `required`
+    pub fn grants(&self, required: &Authorization) -> bool {
+        match (self, required) {
+            (_, Authorization::Unauthorized) => true,
+            (Authorization::Unauthorized, _) => false,
+            (Authorization::Auth, _) | (_, Authorization::Auth) => true,
+            (Authorization::Mapping(granted), Authorization::Mapping(required)) => granted == required,
+            (Authorization::Mapping(_), _) | (_, Authorization::Mapping(_)) => false,
+            (Authorization::Conjunction(granted), Authorization::Conjunction(required)) => {
+                required.iter().all(|entitlement| granted.contains(entitlement))
+            },
+            (Authorization::Conjunction(granted), Authorization::Disjunction(required)) => {
+                required.iter().any(|entitlement| granted.contains(entitlement))
+            },
+            // only one of the granted entitlements is known to be held
+            (Authorization::Disjunction(granted), Authorization::Conjunction(required)) => {
+                granted.iter().all(|entitlement| required.iter().all(|required| required == entitlement))
+            },
+            (Authorization::Disjunction(granted), Authorization::Disjunction(required)) => {
+                granted.iter().all(|entitlement| required.contains(entitlement))
+            },
+        }
+    }
+}
+
+impl fmt::Display for Authorization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Authorization::Unauthorized => Ok(()),
+            Authorization::Auth => write!(f, "auth "),
+            Authorization::Conjunction(entitlements) => write!(f, "auth({}) ", entitlements.join(", ")),
+            Authorization::Disjunction(entitlements) => write!(f, "auth({}) ", entitlements.join(" | ")),
+            Authorization::Mapping(mapping) => write!(f, "auth(mapping {}) ", mapping),
+        }
+    }
+}
+
+/// `entitlement mapping M { E -> F include N }`, by qualified names
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntitlementMapping {
+    pub relations: Vec<(String, String)>,
+    pub includes: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    /// the type of what failed to check, accepted everywhere to avoid follow-up errors
+    Invalid,
+    Never,
+    Void,
+    AnyStruct,
+    AnyResource,
+    Bool,
+    Character,
+    String,
+    Address,
+    /// `Type`, the type of run-time types
+    MetaType,
+    Block,
+    Number(NumberType),
+    Path(PathType),
+    Optional(Box<Type>),
+    VariableSized(Box<Type>),
+    ConstantSized(Box<Type>, u64),
+    Dictionary(Box<Type>, Box<Type>),
+    Function(FunctionType),
+    Reference {
+        authorization: Authorization,
+        ty: Box<Type>,
+    },
+    /// `T{I1, I2}`, restrictions are interface names
+    Restricted {
+        ty: Box<Type>,
+        restrictions: Vec<String>,
+    },
+    /// a composite declared in the program, by qualified name, e.g. `Token.Vault`
+    Composite(String),
+    Interface(String),
+    /// `Capability` or `Capability<T>`
+    Capability(Option<Box<Type>>),
+    AuthAccount,
+    PublicAccount,
+    /// a built-in type whose members are not checked
+    Other(String),
+    /// a type declared by an imported contract, only known to be a resource from its annotation
+    Imported {
+        name: String,
+        is_resource: bool,
+    },
+    /// a type argument of a generic function, replaced when it is invoked
+    TypeParameter(usize),
+}
+
+impl Type {
+    pub fn optional(ty: Type) -> Self {
+        Type::Optional(Box::new(ty))
+    }
+
+    pub fn reference(ty: Type) -> Self {
+        Type::Reference { authorization: Authorization::Unauthorized, ty: Box::new(ty) }
+    }
+
+    /// the type of `nil`
+    pub fn nil() -> Self {
+        Type::optional(Type::Never)
+    }
+
+    /// whether checking of expressions of this type is skipped
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Type::Invalid | Type::Imported { .. })
+    }
+
+    pub fn is_optional(&self) -> bool {
+        matches!(self, Type::Optional(_))
+    }
+
+    /// `T` for `T?`, otherwise the type itself
+    pub fn unwrap_optional(&self) -> &Type {
+        match self {
+            Type::Optional(ty) => ty,
+            ty => ty,
+        }
+    }
+
+    /// the referenced type for references, otherwise the type itself
+    pub fn dereference(&self) -> &Type {
+        match self {
+            Type::Reference { ty, .. } => ty,
+            ty => ty,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<NumberType> {
+        match self {
+            Type::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    /// replace type parameters by the given arguments
+    pub fn substitute(&self, arguments: &[Type]) -> Type {
+        let map = |ty: &Type| Box::new(ty.substitute(arguments));
+        match self {
+            Type::TypeParameter(index) => arguments.get(*index).cloned().unwrap_or(Type::Invalid),
+            Type::Optional(ty) => Type::Optional(map(ty)),
+            Type::VariableSized(ty) => Type::VariableSized(map(ty)),
+            Type::ConstantSized(ty, size) => Type::ConstantSized(map(ty), *size),
+            Type::Dictionary(key, value) => Type::Dictionary(map(key), map(value)),
+            Type::Reference { authorization, ty } => Type::Reference { authorization: authorization.clone(), ty: map(ty) },
+            Type::Restricted { ty, restrictions } => Type::Restricted { ty: map(ty), restrictions: restrictions.clone() },
+            Type::Capability(Some(ty)) => Type::Capability(Some(map(ty))),
+            Type::Function(function) => Type::Function(FunctionType {
+                type_parameters: function.type_parameters.clone(),
+                parameters: function.parameters.iter()
+                    .map(|parameter| FunctionParameter { label: parameter.label.clone(), ty: parameter.ty.substitute(arguments) })
+                    .collect(),
+                return_type: map(&function.return_type),
+            }),
+            ty => ty.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Invalid => write!(f, "<<invalid>>"),
+            Type::Never => write!(f, "Never"),
+            Type::Void => write!(f, "Void"),
+            Type::AnyStruct => write!(f, "AnyStruct"),
+            Type::AnyResource => write!(f, "AnyResource"),
+            Type::Bool => write!(f, "Bool"),
+            Type::Character => write!(f, "Character"),
+            Type::String => write!(f, "String"),
+            Type::Address => write!(f, "Address"),
+            Type::MetaType => write!(f, "Type"),
+            Type::Block => write!(f, "Block"),
+            Type::Number(number) => write!(f, "{}", number.name()),
+            Type::Path(path) => write!(f, "{}", path.name()),
+            Type::Optional(ty) => write!(f, "{}?", ty),
+            Type::VariableSized(ty) => write!(f, "[{}]", ty),
+            Type::ConstantSized(ty, size) => write!(f, "[{}; {}]", ty, size),
+            Type::Dictionary(key, value) => write!(f, "{{{}: {}}}", key, value),
+            Type::Function(function) => {
+                write!(f, "((")?;
+                for (index, parameter) in function.parameters.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", parameter.ty)?;
+                }
+                write!(f, "): {})", function.return_type)
+            },
+            Type::Reference { authorization, ty } => write!(f, "{}&{}", authorization, ty),
+            Type::Restricted { ty, restrictions } => {
+                if !matches!(**ty, Type::AnyStruct | Type::AnyResource) {
+                    write!(f, "{}", ty)?;
+                }
+                write!(f, "{{{}}}", restrictions.join(", "))
+            },
+            Type::Composite(name) | Type::Interface(name) | Type::Other(name) | Type::Imported { name, .. } => {
+                write!(f, "{}", name)
+            },
+            Type::Capability(None) => write!(f, "Capability"),
+            Type::Capability(Some(ty)) => write!(f, "Capability<{}>", ty),
+            Type::AuthAccount => write!(f, "AuthAccount"),
+            Type::PublicAccount => write!(f, "PublicAccount"),
+            Type::TypeParameter(index) => write!(f, "T{}", index),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemberKind {
+    Field(VariableKind),
+    Function {
+        /// interface functions may leave the implementation to the conforming composites
+        has_body: bool,
+    },
+    EnumCase,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Member {
+    pub name: String,
+    pub kind: MemberKind,
+    pub ty: Type,
+    pub access: Access,
+    /// what a reference must be authorized for to access the member, from `access(E)`
+    pub entitlements: Authorization,
+    /// the member's name in its declaration
+    pub span: Span,
+}
+
+/// the members and conformances of a declared composite or interface
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompositeType {
+    /// qualified name, e.g. `Token.Vault`
+    pub name: String,
+    pub kind: CompositeKind,
+    pub is_interface: bool,
+    pub access: Access,
+    /// qualified names of the interfaces it conforms to
+    pub conformances: Vec<String>,
+    pub members: Vec<Member>,
+    /// parameters of `init`, or of an event
+    pub initializer: Vec<FunctionParameter>,
+    /// qualified names of the composites and interfaces declared inside it
+    pub nested: Vec<String>,
+    /// for enums, the raw value type
+    pub raw_type: Option<Type>,
+    /// the name in the declaration
+    pub span: Span,
+}
+
+impl CompositeType {
+    pub fn new(name: &str, kind: CompositeKind, is_interface: bool, access: Access, span: Span) -> Self {
+        CompositeType {
+            name: name.to_string(),
+            kind,
+            is_interface,
+            access,
+            conformances: Vec::new(),
+            members: Vec::new(),
+            initializer: Vec::new(),
+            nested: Vec::new(),
+            raw_type: None,
+            span,
+        }
+    }
+
+    pub fn member(&self, name: &str) -> Option<&Member> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    pub fn is_resource(&self) -> bool {
+        self.kind == CompositeKind::Resource
+    }
+
+    /// the last part of the qualified name
+    pub fn simple_name(&self) -> &str {
+        self.name.rsplit('.').next().unwrap_or(&self.name)
+    }
+}
+
+/// the composites and interfaces of a program, which subtyping and member lookup depend on
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Types {
+    pub composites: HashMap<String, CompositeType>,
+    /// declared entitlements by qualified name, with the span of their name
+    pub entitlements: HashMap<String, Span>,
+    pub entitlement_mappings: HashMap<String, EntitlementMapping>,
+}
+
+impl Types {
+    pub fn composite(&self, name: &str) -> Option<&CompositeType> {
+        self.composites.get(name)
+    }
+
+    /// the entitlements `mapping` maps the granted ones to, a value that is not a reference grants every entitlement
+    pub fn map_entitlements(&self, mapping: &str, granted: Option<&Authorization>) -> Authorization {
+        let mut relations = Vec::new();
+        self.mapping_relations(mapping, &mut Vec::new(), &mut relations);
+        let image = |inputs: Option<&[String]>| {
+            let mut outputs: Vec<String> = Vec::new();
+            for (input, output) in relations.iter() {
+                if inputs.is_none_or(|inputs| inputs.contains(input)) && !outputs.contains(output) {
+                    outputs.push(output.clone());
+                }
+            }
+            outputs
+        };
+        let (outputs, is_disjunction) = match granted {
+            None | Some(Authorization::Auth) => (image(None), false),
+            Some(Authorization::Conjunction(entitlements)) => (image(Some(entitlements)), false),
+            Some(Authorization::Disjunction(entitlements)) => (image(Some(entitlements)), true),
+            Some(Authorization::Unauthorized | Authorization::Mapping(_)) => (Vec::new(), false),
+        };
+        match outputs {
+            outputs if outputs.is_empty() => Authorization::Unauthorized,
+            outputs if is_disjunction => Authorization::Disjunction(outputs),
+            outputs => Authorization::Conjunction(outputs),
+        }
+    }
+
+    /// relations of the mapping and of the mappings it includes, each mapping once
+    fn mapping_relations(&self, mapping: &str, visited: &mut Vec<String>, relations: &mut Vec<(String, String)>) {
+        if visited.iter().any(|visited| visited == mapping) {
+            return
+        }
+        visited.push(mapping.to_string());
+        let Some(declaration) = self.entitlement_mappings.get(mapping) else {
+            return
+        };
+        relations.extend(declaration.relations.iter().cloned());
+        for include in declaration.includes.iter() {
+            self.mapping_relations(include, visited, relations);
+        }
+    }
+
+    /// whether values of the type must be moved and destroyed explicitly
+    pub fn is_resource(&self, ty: &Type) -> bool {
+        match ty {
+            Type::AnyResource => true,
+            Type::Composite(name) | Type::Interface(name) => self.composite(name).is_some_and(|c| c.is_resource()),
+            Type::Imported { is_resource, .. } => *is_resource,
+            Type::Optional(ty) | Type::VariableSized(ty) | Type::ConstantSized(ty, _) | Type::Restricted { ty, .. } => {
+                self.is_resource(ty)
+            },
+            Type::Dictionary(_, value) => self.is_resource(value),
+            _ => false,
+        }
+    }
+
+    /// whether `composite` declares conformance to `interface`, directly or through other interfaces
+    pub fn conforms(&self, composite: &str, interface: &str) -> bool {
+        let Some(declaration) = self.composite(composite) else {
+            return false
+        };
+        declaration.conformances.iter().any(|conformance| conformance == interface || self.conforms(conformance, interface))
+    }
+
+    pub fn is_subtype(&self, sub: &Type, sup: &Type) -> bool {
+        if sub == sup {
+            return true
+        }
+        match (sub, sup) {
+            (sub, sup) if sub.is_unknown() || sup.is_unknown() => true,
+            (Type::Never, _) => true,
+            (_, Type::AnyStruct) => !self.is_resource(sub) && *sub != Type::Void,
+            (_, Type::AnyResource) => self.is_resource(sub),
+            (Type::Number(sub), Type::Number(sup)) => sub.is_subtype(*sup),
+            (Type::Path(sub), Type::Path(sup)) => sub.is_subtype(*sup),
+            (Type::Optional(sub), Type::Optional(sup)) => self.is_subtype(sub, sup),
+            (sub, Type::Optional(sup)) => self.is_subtype(sub, sup),
+            (Type::VariableSized(sub), Type::VariableSized(sup)) => self.is_subtype(sub, sup),
+            (Type::ConstantSized(sub, n), Type::ConstantSized(sup, m)) => n == m && self.is_subtype(sub, sup),
+            (Type::Dictionary(sub_key, sub_value), Type::Dictionary(sup_key, sup_value)) => {
+                self.is_subtype(sub_key, sup_key) && self.is_subtype(sub_value, sup_value)
+            },
+            (Type::Function(sub), Type::Function(sup)) => {
+                sub.parameters.len() == sup.parameters.len() &&
+                    sub.parameters.iter().zip(sup.parameters.iter()).all(|(a, b)| self.is_subtype(&b.ty, &a.ty)) &&
+                    self.is_subtype(&sub.return_type, &sup.return_type)
+            },
+            // a reference cannot stand for one authorized for more entitlements
+            (Type::Reference { authorization: sub_auth, ty: sub }, Type::Reference { authorization: sup_auth, ty: sup }) => {
+                sub_auth.grants(sup_auth) && self.is_subtype(sub, sup)
+            },
+            (Type::Composite(composite), Type::Interface(interface)) |
+            (Type::Interface(composite), Type::Interface(interface)) => self.conforms(composite, interface),
+            (Type::Restricted { ty, restrictions }, Type::Interface(interface)) => {
+                restrictions.iter().any(|restriction| restriction == interface || self.conforms(restriction, interface)) ||
+                    self.is_subtype(ty, sup)
+            },
+            // `R{I}` is an `R`, and a `{I}` with fewer restrictions
+            (Type::Restricted { ty, restrictions }, Type::Restricted { ty: sup_ty, restrictions: sup_restrictions }) => {
+                self.is_subtype(ty, sup_ty) && sup_restrictions.iter().all(|restriction| {
+                    restrictions.contains(restriction) || self.is_subtype(ty, &Type::Interface(restriction.clone()))
+                })
+            },
+            (Type::Restricted { ty, .. }, sup) => self.is_subtype(ty, sup),
+            (sub, Type::Restricted { ty, restrictions }) => {
+                self.is_subtype(sub, ty) &&
+                    restrictions.iter().all(|restriction| self.is_subtype(sub, &Type::Interface(restriction.clone())))
+            },
+            (Type::Capability(Some(sub)), Type::Capability(Some(sup))) => self.is_subtype(sub, sup),
+            (Type::Capability(_), Type::Capability(None)) => true,
+            _ => false,
+        }
+    }
+
+    /// the most specific type both types are subtypes of
+    pub fn common_supertype(&self, a: &Type, b: &Type) -> Type {
+        if self.is_subtype(a, b) {
+            return b.clone()
+        }
+        if self.is_subtype(b, a) {
+            return a.clone()
+        }
+        match (a, b) {
+            (Type::Optional(a), b) | (b, Type::Optional(a)) => Type::optional(self.common_supertype(a, b.unwrap_optional())),
+            (Type::Number(a), Type::Number(b)) => {
+                let mut common = *a;
+                while !b.is_subtype(common) {
+                    common = common.parent().unwrap_or(NumberType::Number);
+                }
+                Type::Number(common)
+            },
+            _ if self.is_resource(a) && self.is_resource(b) => Type::AnyResource,
+            _ => Type::AnyStruct,
+        }
+    }
+
+    /// a field or function of the type, built-in types included
+    pub fn member(&self, ty: &Type, name: &str) -> Option<Member> {
+        let field = |ty: Type| Some(Member::built_in(name, MemberKind::Field(VariableKind::Constant), ty));
+        let function = |parameters: Vec<(Option<&str>, Type)>, return_type: Type| {
+            let parameters = parameters.into_iter()
+                .map(|(label, ty)| FunctionParameter { label: label.map(str::to_string), ty })
+                .collect();
+            Some(Member::built_in(name, MemberKind::Function { has_body: true }, Type::Function(FunctionType::new(parameters, return_type))))
+        };
+        let generic = |type_parameters: Vec<Type>, parameters: Vec<(Option<&str>, Type)>, return_type: Type| {
+            let mut member = function(parameters, return_type)?;
+            if let Type::Function(function) = &mut member.ty {
+                function.type_parameters = type_parameters;
+            }
+            Some(member)
+        };
+        let any_reference = Type::reference(Type::AnyStruct);
+        let bytes = Type::VariableSized(Box::new(Type::Number(NumberType::UInt8)));
+
+        // members every type has
+        match name {
+            "getType" => return function(vec![], Type::MetaType),
+            "isInstance" => return function(vec![(None, Type::MetaType)], Type::Bool),
+            _ => (),
+        }
+
+        match ty {
+            Type::Composite(composite) | Type::Interface(composite) => {
+                let composite = self.composite(composite)?;
+                if let Some(member) = composite.member(name) {
+                    return Some(member.clone())
+                }
+                if name == "rawValue" {
+                    return field(composite.raw_type.clone()?)
+                }
+                // members of conformances have default implementations
+                let inherited = composite.conformances.iter().find_map(|interface| self.member(&Type::Interface(interface.clone()), name));
+                match (composite.kind, name) {
+                    (_, _) if inherited.is_some() => inherited,
+                    (CompositeKind::Resource, "uuid") => field(Type::Number(NumberType::UInt64)),
+                    (CompositeKind::Resource, "owner") => field(Type::optional(Type::PublicAccount)),
+                    (CompositeKind::Contract, "account") => field(Type::AuthAccount),
+                    _ => None,
+                }
+            },
+            Type::Restricted { ty, restrictions } => {
+                let from_restrictions = restrictions.iter().find_map(|interface| self.member(&Type::Interface(interface.clone()), name));
+                match **ty {
+                    Type::AnyStruct | Type::AnyResource => from_restrictions,
+                    _ => from_restrictions.or_else(|| self.member(ty, name)),
+                }
+            },
+            Type::Reference { ty, .. } => self.member(ty, name),
+            Type::String => match name {
+                "length" => field(Type::Number(NumberType::Int)),
+                "utf8" => field(bytes),
+                "concat" => function(vec![(None, Type::String)], Type::String),
+                "slice" => function(vec![(Some("from"), Type::Number(NumberType::Int)), (Some("upTo"), Type::Number(NumberType::Int))], Type::String),
+                "toLower" | "toUpper" => function(vec![], Type::String),
+                "contains" => function(vec![(None, Type::String)], Type::Bool),
+                "split" => function(vec![(Some("separator"), Type::String)], Type::VariableSized(Box::new(Type::String))),
+                "replaceAll" => function(vec![(Some("of"), Type::String), (Some("with"), Type::String)], Type::String),
+                "decodeHex" => function(vec![], bytes),
+                _ => None,
+            },
+            Type::VariableSized(element) | Type::ConstantSized(element, _) => {
+                let element = (**element).clone();
+                let array = Type::VariableSized(Box::new(element.clone()));
+                let fixed = matches!(ty, Type::ConstantSized(..));
+                match name {
+                    "length" => field(Type::Number(NumberType::Int)),
+                    "contains" => function(vec![(None, element)], Type::Bool),
+                    "firstIndex" => function(vec![(Some("of"), element)], Type::optional(Type::Number(NumberType::Int))),
+                    "reverse" => function(vec![], ty.clone()),
+                    _ if fixed => None,
+                    "append" => function(vec![(None, element)], Type::Void),
+                    "appendAll" => function(vec![(None, array)], Type::Void),
+                    "insert" => function(vec![(Some("at"), Type::Number(NumberType::Int)), (None, element)], Type::Void),
+                    "remove" => function(vec![(Some("at"), Type::Number(NumberType::Int))], element),
+                    "removeFirst" | "removeLast" => function(vec![], element),
+                    "concat" => function(vec![(None, array.clone())], array),
+                    "slice" => function(vec![(Some("from"), Type::Number(NumberType::Int)), (Some("upTo"), Type::Number(NumberType::Int))], array),
+                    _ => None,
+                }
+            },
+            Type::Dictionary(key, value) => {
+                let (key, value) = ((**key).clone(), (**value).clone());
+                match name {
+                    "length" => field(Type::Number(NumberType::Int)),
+                    "keys" => field(Type::VariableSized(Box::new(key))),
+                    "values" => field(Type::VariableSized(Box::new(value))),
+                    "insert" => function(vec![(Some("key"), key), (None, value.clone())], Type::optional(value)),
+                    "remove" => function(vec![(Some("key"), key)], Type::optional(value)),
+                    "containsKey" => function(vec![(None, key)], Type::Bool),
+                    _ => None,
+                }
+            },
+            Type::Number(number) => match name {
+                "toString" => function(vec![], Type::String),
+                "toBigEndianBytes" => function(vec![], bytes),
+                "saturatingAdd" | "saturatingSubtract" | "saturatingMultiply" | "saturatingDivide" if !number.is_abstract() => {
+                    function(vec![(None, ty.clone())], ty.clone())
+                },
+                _ => None,
+            },
+            Type::Address => match name {
+                "toString" => function(vec![], Type::String),
+                "toBytes" => function(vec![], bytes),
+                _ => None,
+            },
+            Type::Path(_) | Type::Character => match name {
+                "toString" => function(vec![], Type::String),
+                _ => None,
+            },
+            Type::MetaType => match name {
+                "identifier" => field(Type::String),
+                "isSubtype" => function(vec![(Some("of"), Type::MetaType)], Type::Bool),
+                _ => None,
+            },
+            Type::Block => match name {
+                "height" | "view" => field(Type::Number(NumberType::UInt64)),
+                "timestamp" => field(Type::Number(NumberType::UFix64)),
+                "id" => field(Type::ConstantSized(Box::new(Type::Number(NumberType::UInt8)), 32)),
+                _ => None,
+            },
+            Type::AuthAccount | Type::PublicAccount => {
+                let is_auth = *ty == Type::AuthAccount;
+                let storage = Type::Path(PathType::StoragePath);
+                match name {
+                    "address" => field(Type::Address),
+                    "balance" | "availableBalance" => field(Type::Number(NumberType::UFix64)),
+                    "storageUsed" | "storageCapacity" => field(Type::Number(NumberType::UInt64)),
+                    // the members of 1.0 accounts are not checked
+                    "contracts" | "keys" | "storage" | "capabilities" | "inbox" => field(Type::Other(format!("{}.{}", ty, name))),
+                    "getCapability" => generic(
+                        vec![Type::Invalid],
+                        vec![(None, Type::Path(if is_auth { PathType::CapabilityPath } else { PathType::PublicPath }))],
+                        Type::Capability(Some(Box::new(Type::TypeParameter(0)))),
+                    ),
+                    _ if !is_auth => None,
+                    "save" => generic(vec![Type::Invalid], vec![(None, Type::TypeParameter(0)), (Some("to"), storage)], Type::Void),
+                    "load" | "copy" => generic(vec![Type::Invalid], vec![(Some("from"), storage)], Type::optional(Type::TypeParameter(0))),
+                    "borrow" => generic(vec![any_reference], vec![(Some("from"), storage)], Type::optional(Type::TypeParameter(0))),
+                    "type" => function(vec![(Some("at"), storage)], Type::optional(Type::MetaType)),
+                    "link" => generic(
+                        vec![any_reference],
+                        vec![(None, Type::Path(PathType::CapabilityPath)), (Some("target"), Type::Path(PathType::Path))],
+                        Type::optional(Type::Capability(Some(Box::new(Type::TypeParameter(0))))),
+                    ),
+                    "unlink" => function(vec![(None, Type::Path(PathType::CapabilityPath))], Type::Void),
+                    _ => None,
+                }
+            },
+            Type::Capability(borrow_type) => {
+                let borrow_type = borrow_type.as_deref().cloned().unwrap_or(any_reference);
+                match name {
+                    "borrow" => generic(vec![borrow_type], vec![], Type::optional(Type::TypeParameter(0))),
+                    "check" => generic(vec![borrow_type], vec![], Type::Bool),
+                    "address" => field(Type::Address),
+                    "id" => field(Type::Number(NumberType::UInt64)),
+                    _ => None,
+                }
+            },
+            _ => None,
+        }
+    }
+
+    /// whether `member` of the type is looked up in `self.member` rather than not modelled at all
+    pub fn has_known_members(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Invalid | Type::Imported { .. } | Type::Other(_) | Type::TypeParameter(_) => false,
+            Type::Reference { ty, .. } => self.has_known_members(ty),
+            // restrictions by imported interfaces are kept by name only
+            Type::Interface(name) => self.composite(name).is_some(),
+            Type::Restricted { ty, restrictions } => {
+                self.has_known_members(ty) &&
+                    restrictions.iter().all(|restriction| self.has_known_members(&Type::Interface(restriction.clone())))
+            },
+            _ => true,
+        }
+    }
+}
+
+impl Member {
+    pub fn built_in(name: &str, kind: MemberKind, ty: Type) -> Self {
+        Member {
+            name: name.to_string(),
+            kind,
+            ty,
+            access: Access::Pub,
+            entitlements: Authorization::Unauthorized,
+            span: Span::default(),
+        }
+    }
+}
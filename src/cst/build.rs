@@ -0,0 +1,266 @@
+use crate::ast::{
+    Argument, Block, CompositeDeclaration, Condition, Declaration, Expression, FieldDeclaration,
+    FunctionDeclaration, IfStatement, NominalType, Parameter, Program, SpecialFunctionDeclaration, Span,
+    Statement, SwitchCase, Type, TypeAnnotation, VariableDeclaration,
+};
+use crate::ast::visit::{
+    Visitor, walk_argument, walk_block, walk_composite_declaration, walk_condition, walk_declaration,
+    walk_expression, walk_field_declaration, walk_function_declaration, walk_if_statement, walk_parameter,
+    walk_statement, walk_switch_case, walk_type, walk_type_annotation, walk_variable_declaration,
+};
+use crate::lexer::lexer::Lexer;
+use crate::lexer::token::TokenKind;
+use crate::parser::parser::{
+    ParseError, parse_with_errors,
+};
+
+use super::green::{
+    GreenNode, GreenNodeBuilder,
+};
+use super::kind::SyntaxKind;
+use super::syntax::SyntaxNode;
+
+/// parse `source` into a lossless syntax tree, whose text is exactly `source`
+pub fn parse_cst(source: &str) -> (SyntaxNode, Vec<ParseError>) {
+    let (program, errors) = parse_with_errors(source);
+    (SyntaxNode::new_root(build_green(source, &program)), errors)
+}
+
+/// green tree of `source` with nodes at the spans of the AST nodes of `program`
+pub fn build_green(source: &str, program: &Program) -> GreenNode {
+    let mut collector = NodeCollector { nodes: Vec::new() };
+    collector.visit_program(program);
+    let mut nodes = collector.nodes;
+    nodes.retain(|(span, _)| span.size > 0);
+    // parents before children, spans sharing a start ordered by decreasing size
+    nodes.sort_by(|(a, _), (b, _)| a.at.cmp(&b.at).then(b.end().cmp(&a.end())));
+
+    let mut builder = GreenNodeBuilder::new();
+    builder.start_node(SyntaxKind::Program);
+    let mut open: Vec<usize> = vec![source.len()];
+    let mut next = 0;
+    let mut cursor = 0;
+
+    for (kind, at, size) in tokens(source, &nodes) {
+        while open.len() > 1 && *open.last().unwrap() <= at {
+            builder.finish_node();
+            open.pop();
+        }
+        trivia(&mut builder, &source[cursor..at]);
+        while next < nodes.len() && nodes[next].0.at <= at {
+            let (span, kind) = nodes[next];
+            builder.start_node(kind);
+            open.push(span.end().min(*open.last().unwrap()));
+            next += 1;
+        }
+        builder.token(kind, &source[at..at + size]);
+        cursor = at + size;
+    }
+    while open.len() > 1 {
+        builder.finish_node();
+        open.pop();
+    }
+    trivia(&mut builder, &source[cursor..]);
+    builder.finish_node();
+    builder.finish()
+}
+
+/// lexer tokens without `EOF`, `>>` split where a node ends between both `>`
+fn tokens(source: &str, nodes: &[(Span, SyntaxKind)]) -> Vec<(SyntaxKind, usize, usize)> {
+    let mut tokens = Vec::new();
+    for token in Lexer::new(source).tokenize_all() {
+        match token.kind {
+            TokenKind::EOF => break,
+            TokenKind::BitwiseShiftRight if nodes.iter().any(|(span, _)| span.end() == token.at + 1) => {
+                tokens.push((SyntaxKind::AngleClose, token.at, 1));
+                tokens.push((SyntaxKind::AngleClose, token.at + 1, 1));
+            },
+            kind => tokens.push((SyntaxKind::from(&kind), token.at, token.size)),
+        }
+    }
+    tokens
+}
+
+/// split the text between two tokens into whitespace and comments
+fn trivia(builder: &mut GreenNodeBuilder, text: &str) {
+    let bytes = text.as_bytes();
+    let mut at = 0;
+    while at < bytes.len() {
+        let start = at;
+        let kind = if bytes[at].is_ascii_whitespace() {
+            while at < bytes.len() && bytes[at].is_ascii_whitespace() {
+                at += 1;
+            }
+            SyntaxKind::Whitespace
+        } else if text[at..].starts_with("//") {
+            while at < bytes.len() && bytes[at] != b'\n' {
+                at += 1;
+            }
+            SyntaxKind::Comment
+        } else if text[at..].starts_with("/*") {
+            at = match text[at + 2..].find("*/") {
+                Some(end) => at + 2 + end + 2,
+                None => bytes.len(),
+            };
+            SyntaxKind::Comment
+        } else {
+            at += 1;
+            SyntaxKind::ErrorToken
+        };
+        builder.token(kind, &text[start..at]);
+    }
+}
+
+/// spans and kinds of all AST nodes that become CST nodes
+struct NodeCollector {
+    nodes: Vec<(Span, SyntaxKind)>,
+}
+
+impl Visitor for NodeCollector {
+    fn visit_declaration(&mut self, declaration: &Declaration) {
+        let kind = match declaration {
+            Declaration::Import(_) => SyntaxKind::ImportDeclaration,
+            Declaration::Interface(_) => SyntaxKind::InterfaceDeclaration,
+            Declaration::EnumCase(_) => SyntaxKind::EnumCaseDeclaration,
+            Declaration::Transaction(_) => SyntaxKind::TransactionDeclaration,
+            Declaration::Entitlement(_) => SyntaxKind::EntitlementDeclaration,
+            Declaration::EntitlementMapping(_) => SyntaxKind::EntitlementMappingDeclaration,
+            Declaration::Error(_) => SyntaxKind::ErrorDeclaration,
+            // pushed by their own visit methods, also reached from transactions
+            Declaration::Composite(_) | Declaration::Field(_) | Declaration::Function(_) |
+            Declaration::SpecialFunction(_) | Declaration::Variable(_) => return walk_declaration(self, declaration),
+        };
+        self.nodes.push((declaration.span(), kind));
+        walk_declaration(self, declaration);
+    }
+
+    fn visit_composite_declaration(&mut self, declaration: &CompositeDeclaration) {
+        self.nodes.push((declaration.span, SyntaxKind::CompositeDeclaration));
+        walk_composite_declaration(self, declaration);
+    }
+
+    fn visit_field_declaration(&mut self, declaration: &FieldDeclaration) {
+        self.nodes.push((declaration.span, SyntaxKind::FieldDeclaration));
+        walk_field_declaration(self, declaration);
+    }
+
+    fn visit_function_declaration(&mut self, declaration: &FunctionDeclaration) {
+        self.nodes.push((declaration.span, SyntaxKind::FunctionDeclaration));
+        walk_function_declaration(self, declaration);
+    }
+
+    fn visit_special_function_declaration(&mut self, declaration: &SpecialFunctionDeclaration) {
+        self.nodes.push((declaration.function.span, SyntaxKind::SpecialFunctionDeclaration));
+        walk_function_declaration(self, &declaration.function);
+    }
+
+    fn visit_variable_declaration(&mut self, declaration: &VariableDeclaration) {
+        self.nodes.push((declaration.span, SyntaxKind::VariableDeclaration));
+        walk_variable_declaration(self, declaration);
+    }
+
+    fn visit_parameter(&mut self, parameter: &Parameter) {
+        self.nodes.push((parameter.span, SyntaxKind::Parameter));
+        walk_parameter(self, parameter);
+    }
+
+    fn visit_condition(&mut self, condition: &Condition) {
+        self.nodes.push((condition.span, SyntaxKind::Condition));
+        walk_condition(self, condition);
+    }
+
+    fn visit_type_annotation(&mut self, type_annotation: &TypeAnnotation) {
+        self.nodes.push((type_annotation.span, SyntaxKind::TypeAnnotation));
+        walk_type_annotation(self, type_annotation);
+    }
+
+    fn visit_type(&mut self, ty: &Type) {
+        let kind = match ty {
+            Type::Nominal(_) => return walk_type(self, ty),
+            Type::Optional { .. } => SyntaxKind::OptionalType,
+            Type::VariableSized { .. } => SyntaxKind::VariableSizedType,
+            Type::ConstantSized { .. } => SyntaxKind::ConstantSizedType,
+            Type::Dictionary { .. } => SyntaxKind::DictionaryType,
+            Type::Function { .. } => SyntaxKind::FunctionType,
+            Type::Reference { .. } => SyntaxKind::ReferenceType,
+            Type::Restricted { .. } => SyntaxKind::RestrictedType,
+            Type::Instantiation { .. } => SyntaxKind::InstantiationType,
+        };
+        self.nodes.push((ty.span(), kind));
+        walk_type(self, ty);
+    }
+
+    fn visit_nominal_type(&mut self, nominal: &NominalType) {
+        self.nodes.push((nominal.span(), SyntaxKind::NominalType));
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.nodes.push((block.span, SyntaxKind::Block));
+        walk_block(self, block);
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        let kind = match statement {
+            Statement::Variable(_) | Statement::If(_) => return walk_statement(self, statement),
+            Statement::Expression(_) => SyntaxKind::ExpressionStatement,
+            Statement::Return { .. } => SyntaxKind::ReturnStatement,
+            Statement::Break { .. } => SyntaxKind::BreakStatement,
+            Statement::Continue { .. } => SyntaxKind::ContinueStatement,
+            Statement::Assignment { .. } => SyntaxKind::AssignmentStatement,
+            Statement::Swap { .. } => SyntaxKind::SwapStatement,
+            Statement::While { .. } => SyntaxKind::WhileStatement,
+            Statement::For { .. } => SyntaxKind::ForStatement,
+            Statement::Switch { .. } => SyntaxKind::SwitchStatement,
+            Statement::Emit { .. } => SyntaxKind::EmitStatement,
+            Statement::Destroy { .. } => SyntaxKind::DestroyStatement,
+            Statement::Error(_) => SyntaxKind::ErrorStatement,
+        };
+        self.nodes.push((statement.span(), kind));
+        walk_statement(self, statement);
+    }
+
+    fn visit_if_statement(&mut self, statement: &IfStatement) {
+        self.nodes.push((statement.span, SyntaxKind::IfStatement));
+        walk_if_statement(self, statement);
+    }
+
+    fn visit_switch_case(&mut self, case: &SwitchCase) {
+        self.nodes.push((case.span, SyntaxKind::SwitchCase));
+        walk_switch_case(self, case);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        let kind = match expression {
+            Expression::Nil { .. } => SyntaxKind::NilExpression,
+            Expression::Bool { .. } => SyntaxKind::BoolExpression,
+            Expression::Integer { .. } => SyntaxKind::IntegerExpression,
+            Expression::FixedPoint { .. } => SyntaxKind::FixedPointExpression,
+            Expression::String { .. } => SyntaxKind::StringExpression,
+            Expression::Array { .. } => SyntaxKind::ArrayExpression,
+            Expression::Dictionary { .. } => SyntaxKind::DictionaryExpression,
+            Expression::Identifier(_) => SyntaxKind::IdentifierExpression,
+            Expression::Path { .. } => SyntaxKind::PathExpression,
+            Expression::Member { .. } => SyntaxKind::MemberExpression,
+            Expression::Index { .. } => SyntaxKind::IndexExpression,
+            Expression::Invocation { .. } => SyntaxKind::InvocationExpression,
+            Expression::Unary { .. } => SyntaxKind::UnaryExpression,
+            Expression::Binary { .. } => SyntaxKind::BinaryExpression,
+            Expression::Conditional { .. } => SyntaxKind::ConditionalExpression,
+            Expression::Casting { .. } => SyntaxKind::CastingExpression,
+            Expression::Create { .. } => SyntaxKind::CreateExpression,
+            Expression::Reference { .. } => SyntaxKind::ReferenceExpression,
+            Expression::Force { .. } => SyntaxKind::ForceExpression,
+        };
+        self.nodes.push((expression.span(), kind));
+        walk_expression(self, expression);
+    }
+
+    fn visit_argument(&mut self, argument: &Argument) {
+        let span = match &argument.label {
+            Some(label) => Span::between(label.span, argument.expression.span()),
+            None => argument.expression.span(),
+        };
+        self.nodes.push((span, SyntaxKind::Argument));
+        walk_argument(self, argument);
+    }
+}
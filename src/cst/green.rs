@@ -0,0 +1,150 @@
+use std::rc::Rc;
+
+use super::kind::SyntaxKind;
+
+/// immutable token, knows its text but not its position
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GreenToken {
+    pub kind: SyntaxKind,
+    pub text: String,
+}
+
+impl GreenToken {
+    pub fn new(kind: SyntaxKind, text: &str) -> Self {
+        GreenToken { kind, text: text.to_string() }
+    }
+
+    pub fn width(&self) -> usize {
+        self.text.len()
+    }
+}
+
+/// immutable node, shared between trees before and after an edit
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GreenNode {
+    pub kind: SyntaxKind,
+    width: usize,
+    children: Vec<GreenElement>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            Self::Node(node) => node.kind,
+            Self::Token(token) => token.kind,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        match self {
+            Self::Node(node) => node.width(),
+            Self::Token(token) => token.width(),
+        }
+    }
+}
+
+impl From<GreenNode> for GreenElement {
+    fn from(node: GreenNode) -> Self {
+        GreenElement::Node(Rc::new(node))
+    }
+}
+
+impl From<GreenToken> for GreenElement {
+    fn from(token: GreenToken) -> Self {
+        GreenElement::Token(Rc::new(token))
+    }
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let width = children.iter().map(|child| child.width()).sum();
+        GreenNode { kind, width, children }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+
+    /// copy of this node with the child at `index` replaced
+    pub fn replace_child(&self, index: usize, replacement: GreenElement) -> GreenNode {
+        let mut children = self.children.clone();
+        children[index] = replacement;
+        GreenNode::new(self.kind, children)
+    }
+
+    /// copy of this node with `replacement` inserted before the child at `index`
+    pub fn insert_child(&self, index: usize, replacement: GreenElement) -> GreenNode {
+        let mut children = self.children.clone();
+        children.insert(index, replacement);
+        GreenNode::new(self.kind, children)
+    }
+
+    /// copy of this node without the child at `index`
+    pub fn remove_child(&self, index: usize) -> GreenNode {
+        let mut children = self.children.clone();
+        children.remove(index);
+        GreenNode::new(self.kind, children)
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = String::with_capacity(self.width);
+        self.write_text(&mut text);
+        text
+    }
+
+    fn write_text(&self, text: &mut String) {
+        for child in self.children.iter() {
+            match child {
+                GreenElement::Node(node) => node.write_text(text),
+                GreenElement::Token(token) => text.push_str(&token.text),
+            }
+        }
+    }
+}
+
+/// builds a green tree from a flat stream of tokens and node boundaries
+#[derive(Default)]
+pub struct GreenNodeBuilder {
+    parents: Vec<(SyntaxKind, usize)>,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNodeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start_node(&mut self, kind: SyntaxKind) {
+        self.parents.push((kind, self.children.len()));
+    }
+
+    pub fn token(&mut self, kind: SyntaxKind, text: &str) {
+        self.children.push(GreenToken::new(kind, text).into());
+    }
+
+    pub fn finish_node(&mut self) {
+        let (kind, first_child) = self.parents.pop().expect("no node to finish");
+        let children = self.children.split_off(first_child);
+        self.children.push(GreenNode::new(kind, children).into());
+    }
+
+    /// the root node, all started nodes must be finished
+    pub fn finish(mut self) -> GreenNode {
+        assert!(self.parents.is_empty(), "unfinished nodes");
+        assert_eq!(self.children.len(), 1, "expected a single root node");
+        match self.children.pop() {
+            Some(GreenElement::Node(node)) => Rc::try_unwrap(node).unwrap_or_else(|node| (*node).clone()),
+            _ => panic!("root must be a node"),
+        }
+    }
+}
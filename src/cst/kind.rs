@@ -0,0 +1,222 @@
+use crate::lexer::token::{
+    TokenKind, Keyword,
+};
+
+/// kinds of tokens, trivia and nodes in the concrete syntax tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    // trivia
+    Whitespace,
+    Comment,
+
+    // tokens, one per `TokenKind`
+    ParenOpen,
+    ParenClose,
+    BracketOpen,
+    BracketClose,
+    BraceOpen,
+    BraceClose,
+    AngleOpen,
+    AngleClose,
+    Comma,
+    Dot,
+    Plus,
+    Minus,
+    Asterisk,
+    Slash,
+    Backslash,
+    Percent,
+    Question,
+    QuestionDot,
+    QuestionDouble,
+    Exclamation,
+    At,
+    LogicalConjunction,
+    LogicalDisjunction,
+    Xor,
+    DoubleQuote,
+    SingleQuote,
+    SemiColon,
+    Colon,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    Assign,
+    EqualTo,
+    Swap,
+    Move,
+    MoveForce,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseShiftRight,
+    BitwiseShiftLeft,
+    String,
+    Identifier,
+    Keyword(Keyword),
+    ErrorToken, // TokenKind::None
+
+    // nodes
+    Program,
+    ImportDeclaration,
+    CompositeDeclaration,
+    InterfaceDeclaration,
+    FieldDeclaration,
+    FunctionDeclaration,
+    SpecialFunctionDeclaration,
+    EnumCaseDeclaration,
+    VariableDeclaration,
+    TransactionDeclaration,
+    EntitlementDeclaration,
+    EntitlementMappingDeclaration,
+    ErrorDeclaration,
+    Parameter,
+    Condition,
+
+    TypeAnnotation,
+    NominalType,
+    OptionalType,
+    VariableSizedType,
+    ConstantSizedType,
+    DictionaryType,
+    FunctionType,
+    ReferenceType,
+    RestrictedType,
+    InstantiationType,
+
+    Block,
+    ExpressionStatement,
+    ReturnStatement,
+    BreakStatement,
+    ContinueStatement,
+    AssignmentStatement,
+    SwapStatement,
+    IfStatement,
+    WhileStatement,
+    ForStatement,
+    SwitchStatement,
+    SwitchCase,
+    EmitStatement,
+    DestroyStatement,
+    ErrorStatement,
+
+    NilExpression,
+    BoolExpression,
+    IntegerExpression,
+    FixedPointExpression,
+    StringExpression,
+    ArrayExpression,
+    DictionaryExpression,
+    IdentifierExpression,
+    PathExpression,
+    MemberExpression,
+    IndexExpression,
+    InvocationExpression,
+    Argument,
+    UnaryExpression,
+    BinaryExpression,
+    ConditionalExpression,
+    CastingExpression,
+    CreateExpression,
+    ReferenceExpression,
+    ForceExpression,
+}
+
+impl SyntaxKind {
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, Self::Whitespace | Self::Comment)
+    }
+
+    pub fn is_declaration(&self) -> bool {
+        matches!(self,
+            Self::ImportDeclaration | Self::CompositeDeclaration | Self::InterfaceDeclaration |
+            Self::FieldDeclaration | Self::FunctionDeclaration | Self::SpecialFunctionDeclaration |
+            Self::EnumCaseDeclaration | Self::VariableDeclaration | Self::TransactionDeclaration |
+            Self::EntitlementDeclaration | Self::EntitlementMappingDeclaration | Self::ErrorDeclaration
+        )
+    }
+
+    pub fn is_statement(&self) -> bool {
+        matches!(self,
+            Self::ExpressionStatement | Self::ReturnStatement | Self::BreakStatement |
+            Self::ContinueStatement | Self::AssignmentStatement | Self::SwapStatement |
+            Self::IfStatement | Self::WhileStatement | Self::ForStatement | Self::SwitchStatement |
+            Self::EmitStatement | Self::DestroyStatement | Self::ErrorStatement
+        )
+    }
+
+    pub fn is_type(&self) -> bool {
+        matches!(self,
+            Self::NominalType | Self::OptionalType | Self::VariableSizedType | Self::ConstantSizedType |
+            Self::DictionaryType | Self::FunctionType | Self::ReferenceType | Self::RestrictedType |
+            Self::InstantiationType
+        )
+    }
+
+    pub fn is_expression(&self) -> bool {
+        matches!(self,
+            Self::NilExpression | Self::BoolExpression | Self::IntegerExpression |
+            Self::FixedPointExpression | Self::StringExpression | Self::ArrayExpression |
+            Self::DictionaryExpression | Self::IdentifierExpression | Self::PathExpression |
+            Self::MemberExpression | Self::IndexExpression | Self::InvocationExpression |
+            Self::UnaryExpression | Self::BinaryExpression | Self::ConditionalExpression |
+            Self::CastingExpression | Self::CreateExpression | Self::ReferenceExpression |
+            Self::ForceExpression
+        )
+    }
+}
+
+impl From<&TokenKind> for SyntaxKind {
+    fn from(kind: &TokenKind) -> Self {
+        match kind {
+            TokenKind::ParenOpen => Self::ParenOpen,
+            TokenKind::ParenClose => Self::ParenClose,
+            TokenKind::BracketOpen => Self::BracketOpen,
+            TokenKind::BracketClose => Self::BracketClose,
+            TokenKind::BraceOpen => Self::BraceOpen,
+            TokenKind::BraceClose => Self::BraceClose,
+            TokenKind::AngleOpen => Self::AngleOpen,
+            TokenKind::AngleClose => Self::AngleClose,
+            TokenKind::Comma => Self::Comma,
+            TokenKind::Dot => Self::Dot,
+            TokenKind::Plus => Self::Plus,
+            TokenKind::Minus => Self::Minus,
+            TokenKind::Asterisk => Self::Asterisk,
+            TokenKind::Slash => Self::Slash,
+            TokenKind::Backslash => Self::Backslash,
+            TokenKind::Percent => Self::Percent,
+            TokenKind::Question => Self::Question,
+            TokenKind::QuestionDot => Self::QuestionDot,
+            TokenKind::QuestionDouble => Self::QuestionDouble,
+            TokenKind::Exclamation => Self::Exclamation,
+            TokenKind::At => Self::At,
+            TokenKind::LogicalConjunction => Self::LogicalConjunction,
+            TokenKind::LogicalDisjunction => Self::LogicalDisjunction,
+            TokenKind::Xor => Self::Xor,
+            TokenKind::DoubleQuote => Self::DoubleQuote,
+            TokenKind::SingleQuote => Self::SingleQuote,
+            TokenKind::SemiColon => Self::SemiColon,
+            TokenKind::Colon => Self::Colon,
+            TokenKind::NotEqual => Self::NotEqual,
+            TokenKind::LessThan => Self::LessThan,
+            TokenKind::LessThanOrEqual => Self::LessThanOrEqual,
+            TokenKind::GreaterThan => Self::GreaterThan,
+            TokenKind::GreaterThanOrEqual => Self::GreaterThanOrEqual,
+            TokenKind::Assign => Self::Assign,
+            TokenKind::EqualTo => Self::EqualTo,
+            TokenKind::Swap => Self::Swap,
+            TokenKind::Move => Self::Move,
+            TokenKind::MoveForce => Self::MoveForce,
+            TokenKind::BitwiseAnd => Self::BitwiseAnd,
+            TokenKind::BitwiseOr => Self::BitwiseOr,
+            TokenKind::BitwiseShiftRight => Self::BitwiseShiftRight,
+            TokenKind::BitwiseShiftLeft => Self::BitwiseShiftLeft,
+            TokenKind::String(_) | TokenKind::UnterminatedString(_) => Self::String,
+            TokenKind::UnterminatedComment => Self::Comment,
+            TokenKind::Identifier(_) => Self::Identifier,
+            TokenKind::Keyword(keyword) => Self::Keyword(*keyword),
+            TokenKind::None | TokenKind::EOF => Self::ErrorToken,
+        }
+    }
+}
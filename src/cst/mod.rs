@@ -0,0 +1,5 @@
+pub mod build;
+pub mod green;
+pub mod kind;
+pub mod nodes;
+pub mod syntax;
@@ -0,0 +1,235 @@
+use crate::ast::{
+    self, Span,
+};
+use crate::ast::fold::Fold;
+use crate::lexer::token::Keyword;
+use crate::parser::parser::{
+    ParseError, ParseResult, Parser, parse,
+};
+
+use super::kind::SyntaxKind;
+use super::syntax::{
+    SyntaxNode, SyntaxToken,
+};
+
+/// typed view of a syntax node
+pub trait CstNode: Sized {
+    fn can_cast(kind: SyntaxKind) -> bool;
+    fn cast(node: SyntaxNode) -> Option<Self>;
+    fn syntax(&self) -> &SyntaxNode;
+
+    fn text(&self) -> String {
+        self.syntax().text()
+    }
+}
+
+macro_rules! cst_node {
+    ($name:ident, $can_cast:expr) => {
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct $name(SyntaxNode);
+
+        impl CstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                $can_cast(kind)
+            }
+
+            fn cast(node: SyntaxNode) -> Option<Self> {
+                if Self::can_cast(node.kind()) {
+                    Some($name(node))
+                } else {
+                    None
+                }
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                &self.0
+            }
+        }
+    };
+}
+
+cst_node!(ProgramNode, |kind| kind == SyntaxKind::Program);
+cst_node!(DeclarationNode, |kind: SyntaxKind| kind.is_declaration());
+cst_node!(ParameterNode, |kind| kind == SyntaxKind::Parameter);
+cst_node!(BlockNode, |kind| kind == SyntaxKind::Block);
+cst_node!(StatementNode, |kind: SyntaxKind| kind.is_statement() || kind == SyntaxKind::VariableDeclaration);
+cst_node!(ExpressionNode, |kind: SyntaxKind| kind.is_expression());
+cst_node!(TypeAnnotationNode, |kind| kind == SyntaxKind::TypeAnnotation);
+cst_node!(TypeNode, |kind: SyntaxKind| kind.is_type());
+
+fn children<N: CstNode>(node: &SyntaxNode) -> Vec<N> {
+    node.children().into_iter().filter_map(N::cast).collect()
+}
+
+fn child<N: CstNode>(node: &SyntaxNode) -> Option<N> {
+    node.children().into_iter().find_map(N::cast)
+}
+
+/// parse the text of `node` alone, with spans relative to the whole tree
+fn reparse<T>(
+    node: &SyntaxNode,
+    parse: impl FnOnce(&mut Parser) -> ParseResult<T>,
+    shift: impl FnOnce(&mut Shift, T) -> T,
+) -> ParseResult<T> {
+    let offset = node.span().at;
+    let text = node.text();
+    let mut parser = Parser::new(&text);
+    match parse(&mut parser) {
+        Ok(result) => Ok(shift(&mut Shift(offset), result)),
+        Err(error) => Err(ParseError::new(&error.message, Span::new(error.span.at + offset, error.span.size))),
+    }
+}
+
+/// moves spans of a node parsed on its own to where the node is in the tree
+struct Shift(usize);
+
+impl Fold for Shift {
+    fn fold_span(&mut self, span: Span) -> Span {
+        Span::new(span.at + self.0, span.size)
+    }
+}
+
+impl ProgramNode {
+    pub fn declarations(&self) -> Vec<DeclarationNode> {
+        children(&self.0)
+    }
+
+    pub fn to_ast(&self) -> ParseResult<ast::Program> {
+        parse(&self.0.text())
+    }
+}
+
+impl DeclarationNode {
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind()
+    }
+
+    /// the token naming this declaration, if it has a name: the last one before the parameters, type,
+    /// conformances, value or members, e.g. `R` in `access(all) resource R: I {}`
+    pub fn identifier(&self) -> Option<SyntaxToken> {
+        if matches!(self.kind(), SyntaxKind::ImportDeclaration | SyntaxKind::TransactionDeclaration | SyntaxKind::ErrorDeclaration) {
+            return None
+        }
+        let tokens = self.0.tokens();
+        let mut rest = tokens.as_slice();
+        // `access(all)` or `pub(set)`, whose `(` does not start parameters
+        let is_access = rest.first().is_some_and(|token| matches!(token.kind(), SyntaxKind::Keyword(Keyword::Access | Keyword::Pub)));
+        if is_access && rest.get(1).is_some_and(|token| token.kind() == SyntaxKind::ParenOpen) {
+            let close = rest.iter().position(|token| token.kind() == SyntaxKind::ParenClose)?;
+            rest = &rest[close + 1..];
+        }
+        rest.iter()
+            .take_while(|token| !matches!(token.kind(),
+                SyntaxKind::Colon | SyntaxKind::ParenOpen | SyntaxKind::BraceOpen | SyntaxKind::AngleOpen |
+                SyntaxKind::Assign | SyntaxKind::Move | SyntaxKind::MoveForce
+            ))
+            .last()
+            .filter(|token| matches!(token.kind(), SyntaxKind::Identifier | SyntaxKind::Keyword(_)))
+            .cloned()
+    }
+
+    /// members of a composite or interface
+    pub fn members(&self) -> Vec<DeclarationNode> {
+        children(&self.0)
+    }
+
+    pub fn parameters(&self) -> Vec<ParameterNode> {
+        children(&self.0)
+    }
+
+    /// type of a field, or return type of a function
+    pub fn type_annotation(&self) -> Option<TypeAnnotationNode> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<BlockNode> {
+        child(&self.0)
+    }
+
+    /// members are declarations nested in a composite, interface or transaction
+    pub fn is_member(&self) -> bool {
+        self.0.parent().is_some_and(|parent| matches!(parent.kind(),
+            SyntaxKind::CompositeDeclaration | SyntaxKind::InterfaceDeclaration | SyntaxKind::TransactionDeclaration
+        ))
+    }
+
+    pub fn to_ast(&self) -> ParseResult<ast::Declaration> {
+        let is_member = self.is_member();
+        reparse(&self.0, |parser| parser.parse_declaration(is_member), Shift::fold_declaration)
+    }
+}
+
+impl ParameterNode {
+    /// `label` in `label name: T`
+    pub fn label(&self) -> Option<SyntaxToken> {
+        let names = self.names();
+        if names.len() == 2 {
+            names.first().cloned()
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.names().pop()
+    }
+
+    pub fn type_annotation(&self) -> Option<TypeAnnotationNode> {
+        child(&self.0)
+    }
+
+    /// tokens before the `:`
+    fn names(&self) -> Vec<SyntaxToken> {
+        self.0.tokens().into_iter().take_while(|token| token.kind() != SyntaxKind::Colon).collect()
+    }
+}
+
+impl BlockNode {
+    pub fn statements(&self) -> Vec<StatementNode> {
+        children(&self.0)
+    }
+
+    pub fn to_ast(&self) -> ParseResult<ast::Block> {
+        reparse(&self.0, |parser| parser.parse_block(), Shift::fold_block)
+    }
+}
+
+impl StatementNode {
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind()
+    }
+
+    pub fn to_ast(&self) -> ParseResult<ast::Statement> {
+        reparse(&self.0, |parser| parser.parse_statement(), Shift::fold_statement)
+    }
+}
+
+impl ExpressionNode {
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind()
+    }
+
+    pub fn to_ast(&self) -> ParseResult<ast::Expression> {
+        reparse(&self.0, |parser| parser.parse_expression(), Shift::fold_expression)
+    }
+}
+
+impl TypeAnnotationNode {
+    pub fn ty(&self) -> Option<TypeNode> {
+        child(&self.0)
+    }
+
+    pub fn to_ast(&self) -> ParseResult<ast::TypeAnnotation> {
+        reparse(&self.0, |parser| parser.parse_type_annotation(), Shift::fold_type_annotation)
+    }
+}
+
+impl TypeNode {
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind()
+    }
+
+    pub fn to_ast(&self) -> ParseResult<ast::Type> {
+        reparse(&self.0, |parser| parser.parse_type(), Shift::fold_type)
+    }
+}
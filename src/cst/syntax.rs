@@ -0,0 +1,255 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::Span;
+
+use super::green::{
+    GreenElement, GreenNode, GreenToken,
+};
+use super::kind::SyntaxKind;
+
+/// node of the red tree: a green node with its position and parent
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    index: usize,
+    offset: usize,
+}
+
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    index: usize,
+    offset: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            Self::Node(node) => node.kind(),
+            Self::Token(token) => token.kind(),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Node(node) => node.span(),
+            Self::Token(token) => token.span(),
+        }
+    }
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: GreenNode) -> Self {
+        SyntaxNode(Rc::new(NodeData {
+            green: Rc::new(green),
+            parent: None,
+            index: 0,
+            offset: 0,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &GreenNode {
+        &self.0.green
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.0.offset, self.0.green.width())
+    }
+
+    pub fn text(&self) -> String {
+        self.0.green.text()
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    /// position among the parent's children, tokens included
+    pub fn index(&self) -> usize {
+        self.0.index
+    }
+
+    pub fn root(&self) -> SyntaxNode {
+        self.ancestors().pop().unwrap_or_else(|| self.clone())
+    }
+
+    pub fn ancestors(&self) -> Vec<SyntaxNode> {
+        let mut ancestors = Vec::new();
+        let mut current = self.parent();
+        while let Some(node) = current {
+            current = node.parent();
+            ancestors.push(node);
+        }
+        ancestors
+    }
+
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        let mut elements = Vec::new();
+        for (index, child) in self.0.green.children().iter().enumerate() {
+            let element = match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                    green: green.clone(),
+                    parent: Some(self.clone()),
+                    index,
+                    offset,
+                }))),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green: green.clone(),
+                    parent: self.clone(),
+                    index,
+                    offset,
+                }),
+            };
+            offset += child.width();
+            elements.push(element);
+        }
+        elements
+    }
+
+    pub fn children(&self) -> Vec<SyntaxNode> {
+        self.children_with_tokens().into_iter().filter_map(|element| match element {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        }).collect()
+    }
+
+    /// direct child tokens, trivia excluded
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        self.children_with_tokens().into_iter().filter_map(|element| match element {
+            SyntaxElement::Token(token) if !token.kind().is_trivia() => Some(token),
+            _ => None,
+        }).collect()
+    }
+
+    /// this node and all nodes below it, in source order
+    pub fn descendants(&self) -> Vec<SyntaxNode> {
+        let mut nodes = vec![self.clone()];
+        for child in self.children() {
+            nodes.extend(child.descendants());
+        }
+        nodes
+    }
+
+    /// all tokens below this node, trivia included, in source order
+    pub fn descendant_tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for element in self.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(node) => tokens.extend(node.descendant_tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    pub fn first_token(&self) -> Option<SyntaxToken> {
+        self.descendant_tokens().into_iter().find(|token| !token.kind().is_trivia())
+    }
+
+    /// innermost token covering `offset`
+    pub fn token_at_offset(&self, offset: usize) -> Option<SyntaxToken> {
+        for element in self.children_with_tokens() {
+            let span = element.span();
+            if offset < span.at || offset >= span.end() {
+                continue;
+            }
+            return match element {
+                SyntaxElement::Node(node) => node.token_at_offset(offset),
+                SyntaxElement::Token(token) => Some(token),
+            }
+        }
+        None
+    }
+
+    /// innermost node covering the whole `span`
+    pub fn covering_node(&self, span: Span) -> SyntaxNode {
+        for child in self.children() {
+            let child_span = child.span();
+            if child_span.at <= span.at && span.end() <= child_span.end() && child_span.size > 0 {
+                return child.covering_node(span)
+            }
+        }
+        self.clone()
+    }
+
+    /// root of a new tree where this node is replaced with `replacement`
+    pub fn replace_with(&self, replacement: GreenNode) -> GreenNode {
+        match self.parent() {
+            None => replacement,
+            Some(parent) => {
+                let green = parent.green().replace_child(self.index(), replacement.into());
+                parent.replace_with(green)
+            },
+        }
+    }
+}
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0.green, &other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(f, "{:?}@{}..{}", self.kind(), span.at, span.end())
+    }
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.offset, self.green.width())
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// root of a new tree where this token's text is replaced
+    pub fn replace_with(&self, replacement: GreenToken) -> GreenNode {
+        let green = self.parent.green().replace_child(self.index, replacement.into());
+        self.parent.replace_with(green)
+    }
+}
+
+impl PartialEq for SyntaxToken {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.green, &other.green) && self.offset == other.offset
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(f, "{:?}@{}..{} {:?}", self.kind(), span.at, span.end(), self.text())
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+
+use crate::ast::{
+    CompositeKind, Declaration, ImportLocation, Span,
+};
+use crate::interpreter::interpreter::{
+    ErrorKind, Interpreter, RuntimeError, checked_program,
+};
+use crate::interpreter::meter::{
+    CostTable, FLOW_COMPUTATION_LIMIT, FunctionUsage, Meter,
+};
+use crate::interpreter::storage::{
+    Account, Contract, Ledger,
+};
+use crate::interpreter::value::Value;
+use crate::parser::parser::parse;
+
+use super::events::{
+    Event, events_json,
+};
+
+/// what a transaction emitted and logged, and the computation it used
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TransactionResult {
+    pub events: Vec<Event>,
+    pub logs: Vec<String>,
+    pub computation: u64,
+    /// by qualified name of the function
+    pub usage: BTreeMap<String, FunctionUsage>,
+}
+
+/// accounts kept in memory, which contracts are deployed to and transactions and scripts run against.
+/// a transaction that fails changes nothing
+#[derive(Debug)]
+pub struct Emulator {
+    ledger: Ledger,
+    /// the events of all transactions, in the order they were emitted
+    events: Vec<Event>,
+    /// the number of transactions run, deployments and failed ones included
+    transactions: usize,
+    costs: CostTable,
+    /// the computation a transaction or script can use
+    limit: u64,
+}
+
+impl Default for Emulator {
+    fn default() -> Self {
+        Emulator {
+            ledger: Ledger::default(),
+            events: Vec::new(),
+            transactions: 0,
+            costs: CostTable::default(),
+            limit: FLOW_COMPUTATION_LIMIT,
+        }
+    }
+}
+
+impl Emulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// run what follows with other costs and computation limit
+    pub fn set_computation(&mut self, costs: CostTable, limit: u64) {
+        self.costs = costs;
+        self.limit = limit;
+    }
+
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// the events with a type id, e.g. `A.0000000000000001.Token.Deposited`
+    pub fn events_of_type<'e>(&'e self, type_id: &'e str) -> impl Iterator<Item = &'e Event> {
+        self.events.iter().filter(move |event| event.type_id == type_id)
+    }
+
+    /// the event log as JSON, with the values of the events as JSON-Cadence
+    pub fn events_json(&self) -> String {
+        events_json(&self.events)
+    }
+
+    /// a new account at the address after the last one, starting at `0x01`
+    pub fn create_account(&mut self) -> u64 {
+        let address = self.ledger.accounts.keys().next_back().map_or(1, |last| last + 1);
+        self.ledger.accounts.insert(address, Account::default());
+        address
+    }
+
+    /// deploy the contract or contract interface `source` declares to an account, and initialize it
+    pub fn deploy_contract(&mut self, address: u64, source: &str) -> Result<TransactionResult, RuntimeError> {
+        let program = parse(source).map_err(|error| RuntimeError::new(ErrorKind::InvalidProgram, &error.message, error.span))?;
+        let contracts: Vec<&str> = program.declarations.iter()
+            .filter_map(|declaration| match declaration {
+                Declaration::Composite(d) if d.kind == CompositeKind::Contract => Some(d.identifier.name.as_str()),
+                Declaration::Interface(d) if d.kind == CompositeKind::Contract => Some(d.identifier.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        let &[name] = contracts.as_slice() else {
+            let message = "a deployed program declares exactly one contract or contract interface";
+            return Err(RuntimeError::new(ErrorKind::InvalidProgram, message, program.span))
+        };
+        if let Some(deployed) = self.ledger.contract_address(name) {
+            let message = format!("contract `{}` is already deployed to 0x{:016x}", name, deployed);
+            return Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, program.span))
+        }
+        let Some(account) = self.ledger.accounts.get_mut(&address) else {
+            return Err(account_error(address))
+        };
+        // the contract knows its account while it is initialized
+        account.contracts.insert(name.to_string(), Contract { source: source.to_string(), value: None });
+        let result = self.run(source, |_| Ok(()));
+        if result.is_err() {
+            if let Some(account) = self.ledger.accounts.get_mut(&address) {
+                account.contracts.remove(name);
+            }
+        }
+        result.map(|(_, result)| result)
+    }
+
+    /// run a transaction signed by the accounts at `signers`, in the order of the parameters of `prepare`
+    pub fn execute_transaction(&mut self, source: &str, arguments: Vec<Value>, signers: &[u64]) -> Result<TransactionResult, RuntimeError> {
+        if let Some(&missing) = signers.iter().find(|signer| !self.ledger.accounts.contains_key(signer)) {
+            return Err(account_error(missing))
+        }
+        self.run(source, |interpreter| interpreter.execute(arguments, signers)).map(|(_, result)| result)
+    }
+
+    /// run the `main` function of a script, which changes nothing
+    pub fn execute_script(&mut self, source: &str, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+        let snapshot = self.ledger.snapshot();
+        let (transactions, events) = (self.transactions, self.events.len());
+        let result = self.run(source, |interpreter| interpreter.invoke("main", arguments));
+        self.ledger = snapshot;
+        self.transactions = transactions;
+        self.events.truncate(events);
+        result.map(|(value, _)| value)
+    }
+
+    /// run `f` on the source linked with the contracts it imports, and roll back the ledger if it fails
+    fn run<T>(&mut self, source: &str, f: impl FnOnce(&mut Interpreter) -> Result<T, RuntimeError>) -> Result<(T, TransactionResult), RuntimeError> {
+        let transaction = self.transactions;
+        self.transactions += 1;
+        let linked = self.link(source)?;
+        let (program, checked) = checked_program(&linked)?;
+        let snapshot = self.ledger.snapshot();
+        let ledger = std::mem::take(&mut self.ledger);
+        let result = Interpreter::with_ledger(&program, &checked, ledger, Meter::new(self.costs, self.limit)).and_then(|mut interpreter| {
+            let value = f(&mut interpreter)?;
+            Ok((value, interpreter))
+        });
+        match result {
+            Ok((value, interpreter)) => {
+                self.ledger = interpreter.ledger;
+                let mut events: Vec<Event> = Vec::new();
+                for event in interpreter.events.iter() {
+                    events.extend(Event::new(event, &checked.types, &self.ledger, transaction, events.len()));
+                }
+                self.events.extend(events.iter().cloned());
+                let result = TransactionResult {
+                    events,
+                    logs: interpreter.logs,
+                    computation: interpreter.meter.used(),
+                    usage: interpreter.meter.usage().clone(),
+                };
+                Ok((value, result))
+            },
+            Err(error) => {
+                self.ledger = snapshot;
+                Err(error)
+            },
+        }
+    }
+
+    /// the source followed by the sources of the contracts it imports, directly or through other contracts.
+    /// imports are blanked out, so spans in the source stay the same
+    fn link(&self, source: &str) -> Result<String, RuntimeError> {
+        let mut linked = String::new();
+        let mut included: Vec<String> = Vec::new();
+        let mut pending = vec![source.to_string()];
+        while let Some(source) = pending.pop() {
+            let program = parse(&source).map_err(|error| RuntimeError::new(ErrorKind::InvalidProgram, &error.message, error.span))?;
+            let mut bytes = source.into_bytes();
+            for declaration in program.declarations.iter() {
+                let Declaration::Import(import) = declaration else {
+                    continue
+                };
+                for byte in bytes[import.span.at..import.span.end()].iter_mut().filter(|byte| **byte != b'\n') {
+                    *byte = b' ';
+                }
+                let address = match &import.location {
+                    ImportLocation::Address(address) => u64::from_str_radix(address.trim_start_matches("0x"), 16).ok(),
+                    _ => None,
+                };
+                let names = match &import.location {
+                    ImportLocation::String(name) => vec![name.clone()],
+                    _ => import.identifiers.iter().map(|identifier| identifier.name.clone()).collect(),
+                };
+                for name in names {
+                    if included.contains(&name) {
+                        continue;
+                    }
+                    let contract = match address {
+                        Some(address) => self.ledger.accounts.get(&address).and_then(|account| account.contracts.get(&name)),
+                        None => self.ledger.accounts.values().find_map(|account| account.contracts.get(&name)),
+                    };
+                    let Some(contract) = contract else {
+                        let at = address.map(|address| format!(" at 0x{:016x}", address)).unwrap_or_default();
+                        return Err(RuntimeError::new(ErrorKind::InvalidProgram, &format!("cannot find contract `{}`{}", name, at), import.span))
+                    };
+                    pending.push(contract.source.clone());
+                    included.push(name);
+                }
+            }
+            linked.push_str(&String::from_utf8_lossy(&bytes));
+            linked.push('\n');
+        }
+        Ok(linked)
+    }
+}
+
+fn account_error(address: u64) -> RuntimeError {
+    RuntimeError::new(ErrorKind::Storage, &format!("account 0x{:016x} does not exist", address), Span::default())
+}
@@ -0,0 +1,70 @@
+use crate::checker::types::Types;
+use crate::interpreter::storage::Ledger;
+use crate::interpreter::value::Value;
+use crate::json_cadence::export::export;
+use crate::json_cadence::json::Json;
+use crate::json_cadence::json_cadence::{
+    self, encode,
+};
+
+/// an event a transaction emitted
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    /// `A.<address>.<Contract>.<Event>`
+    pub type_id: String,
+    /// in the order of the parameters of the event
+    pub fields: Vec<(String, Value)>,
+    /// index of the transaction among the ones the emulator executed, deployments included
+    pub transaction: usize,
+    /// index among the events of the transaction
+    pub index: usize,
+    /// exported when it was emitted, when the contracts of its types were known
+    pub value: json_cadence::Value,
+}
+
+impl Event {
+    /// the event an `emit` emitted, `None` for values that are no events
+    pub fn new(value: &Value, types: &Types, ledger: &Ledger, transaction: usize, index: usize) -> Option<Event> {
+        let Value::Composite(composite) = value else {
+            return None
+        };
+        let exported = export(value, types, ledger)?;
+        if !matches!(exported, json_cadence::Value::Event(_)) {
+            return None
+        }
+        let composite = composite.borrow();
+        Some(Event {
+            type_id: ledger.type_id(&composite.name),
+            fields: composite.fields.clone(),
+            transaction,
+            index,
+            value: exported,
+        })
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        self.fields.iter().find(|(field, _)| field == name).map(|(_, value)| value)
+    }
+
+    /// the value of the event in the JSON-Cadence data interchange format
+    pub fn to_json_cadence(&self) -> String {
+        encode(&self.value)
+    }
+}
+
+/// the events as JSON, one per line, with their type id, position and value as JSON-Cadence
+pub fn events_json(events: &[Event]) -> String {
+    let mut json = String::from("[");
+    for (index, event) in events.iter().enumerate() {
+        let object = Json::object(vec![
+            ("type", Json::string(&event.type_id)),
+            ("transactionIndex", Json::Number(event.transaction.to_string())),
+            ("eventIndex", Json::Number(event.index.to_string())),
+            ("values", event.value.to_json()),
+        ]);
+        json.push_str(if index > 0 { ",\n" } else { "\n" });
+        json.push_str(&format!("  {}", object));
+    }
+    json.push_str(if events.is_empty() { "]" } else { "\n]" });
+    json
+}
@@ -0,0 +1,25 @@
+use std::fs;
+use std::io;
+use std::path::{
+    Path, PathBuf,
+};
+
+/// `path` itself if it is a file, otherwise the `.cdc` files below it in name order
+pub fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(())
+    }
+    let mut entries = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            collect_files(&entry, files)?;
+        } else if entry.extension().is_some_and(|extension| extension == "cdc") {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
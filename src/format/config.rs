@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::{
+    Path, PathBuf,
+};
+
+use crate::printer::printer::PrintOptions;
+
+pub const CONFIG_FILE: &str = "cadencefmt.toml";
+
+/// formatter settings, read from `cadencefmt.toml`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// lines longer than this get their parameter lists, argument lists and conditions wrapped
+    pub max_width: usize,
+    /// spaces per indentation level
+    pub tab_spaces: usize,
+    pub hard_tabs: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { max_width: 100, tab_spaces: 4, hard_tabs: false }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigError {
+    pub message: String,
+    /// 1-based, 0 if the error is not on a line
+    pub line: usize,
+}
+
+impl ConfigError {
+    pub fn new(message: &str, line: usize) -> Self {
+        ConfigError { message: message.to_string(), line }
+    }
+}
+
+impl Config {
+    /// parse the `key = value` lines of a config file, unset keys keep their default
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = match line.find('#') {
+                Some(at) => &line[..at],
+                None => line,
+            }.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(ConfigError::new(&format!("expected `key = value`, got `{}`", line), line_number))
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "max_width" => config.max_width = parse_positive(key, value, line_number)?,
+                "tab_spaces" => config.tab_spaces = parse_positive(key, value, line_number)?,
+                "hard_tabs" => config.hard_tabs = match value {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(ConfigError::new(&format!("expected `true` or `false` for `{}`", key), line_number)),
+                },
+                _ => return Err(ConfigError::new(&format!("unknown option `{}`", key), line_number)),
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| ConfigError::new(&format!("cannot read {}: {}", path.display(), error), 0))?;
+        Config::parse(&text)
+    }
+
+    /// the config file in `dir` or the closest directory above it
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors().map(|dir| dir.join(CONFIG_FILE)).find(|path| path.is_file())
+    }
+
+    pub fn print_options(&self) -> PrintOptions {
+        PrintOptions { max_width: self.max_width, indent_width: self.tab_spaces, hard_tabs: self.hard_tabs }
+    }
+}
+
+fn parse_positive(key: &str, value: &str, line: usize) -> Result<usize, ConfigError> {
+    match value.parse() {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(ConfigError::new(&format!("expected a positive integer for `{}`, got `{}`", key, value), line)),
+    }
+}
@@ -0,0 +1,53 @@
+use crate::cst::build::build_green;
+use crate::cst::kind::SyntaxKind;
+use crate::cst::syntax::SyntaxNode;
+use crate::parser::parser::{
+    ParseError, parse_with_errors,
+};
+use crate::printer::printer::{
+    Comment, Printer, Trivia,
+};
+
+use super::config::Config;
+
+/// format a program, sources with parse errors are left alone
+pub fn format_source(source: &str, config: &Config) -> Result<String, Vec<ParseError>> {
+    let (program, errors) = parse_with_errors(source);
+    if !errors.is_empty() {
+        return Err(errors)
+    }
+    let tree = SyntaxNode::new_root(build_green(source, &program));
+    let mut printer = Printer::with_options(config.print_options()).with_trivia(trivia(&tree));
+    printer.program(&program);
+    Ok(printer.finish())
+}
+
+/// comments of the tree, and where it has empty lines
+pub fn trivia(tree: &SyntaxNode) -> Trivia {
+    let mut trivia = Trivia::default();
+    // whether only whitespace came since the last line break, and whether an empty line did
+    let mut line_start = true;
+    let mut blank_line = false;
+    for token in tree.descendant_tokens() {
+        match token.kind() {
+            SyntaxKind::Whitespace => {
+                let line_breaks = token.text().matches('\n').count();
+                line_start |= line_breaks > 0;
+                blank_line |= line_breaks > 1;
+                continue;
+            },
+            SyntaxKind::Comment => trivia.comments.push(Comment {
+                text: token.text().trim_end().to_string(),
+                span: token.span(),
+                own_line: line_start,
+            }),
+            _ => (),
+        }
+        if blank_line {
+            trivia.blank_lines.push(token.span().at);
+        }
+        line_start = false;
+        blank_line = false;
+    }
+    trivia
+}
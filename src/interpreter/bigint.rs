@@ -0,0 +1,373 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{
+    Add, Mul, Neg, Sub,
+};
+
+/// an arbitrary precision integer, the value of every number type
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    /// never set for zero
+    negative: bool,
+    /// base 2^32 digits, least significant first, without leading zeros
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        BigInt::default()
+    }
+
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        BigInt { negative: negative && !magnitude.is_empty(), magnitude }
+    }
+
+    /// 2^bits
+    pub fn power_of_two(bits: usize) -> Self {
+        let mut magnitude = vec![0; bits / 32 + 1];
+        magnitude[bits / 32] = 1 << (bits % 32);
+        BigInt::new(false, magnitude)
+    }
+
+    /// parse digits without sign or prefix in the given radix
+    pub fn parse(digits: &str, radix: u32) -> Option<Self> {
+        if digits.is_empty() {
+            return None
+        }
+        let mut magnitude = Vec::new();
+        for digit in digits.chars() {
+            let digit = digit.to_digit(radix)?;
+            magnitude = add_small(&mul_small(&magnitude, radix), digit);
+        }
+        Some(BigInt::new(false, magnitude))
+    }
+
+    /// the non-negative integer of big-endian bytes
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let magnitude = bytes.rchunks(4)
+            .map(|chunk| chunk.iter().fold(0, |digit, byte| (digit << 8) | u32::from(*byte)))
+            .collect();
+        BigInt::new(false, magnitude)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::new(false, self.magnitude.clone())
+    }
+
+    /// the number of bits of the magnitude
+    pub fn bit_len(&self) -> usize {
+        match self.magnitude.last() {
+            Some(last) => self.magnitude.len() * 32 - last.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.bit_len() > 127 {
+            // only the smallest `i128` needs all 128 bits
+            return (*self == BigInt::from(i128::MIN)).then_some(i128::MIN)
+        }
+        let magnitude = self.magnitude.iter().rev().fold(0i128, |value, digit| (value << 32) | *digit as i128);
+        Some(if self.negative { -magnitude } else { magnitude })
+    }
+
+    /// the truncated quotient and the remainder, which has the sign of `self`, `None` for division by zero
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &other.magnitude);
+        Some((BigInt::new(self.negative != other.negative, quotient), BigInt::new(self.negative, remainder)))
+    }
+
+    pub fn shift_left(&self, bits: usize) -> BigInt {
+        BigInt::new(self.negative, shift_left_magnitude(&self.magnitude, bits))
+    }
+
+    /// arithmetic shift, rounding towards negative infinity
+    pub fn shift_right(&self, bits: usize) -> BigInt {
+        let magnitude = shift_right_magnitude(&self.magnitude, bits);
+        let is_exact = shift_left_magnitude(&magnitude, bits) == self.magnitude;
+        let shifted = BigInt::new(self.negative, magnitude);
+        if self.negative && !is_exact {
+            shifted - BigInt::from(1i128)
+        } else {
+            shifted
+        }
+    }
+
+    /// the value modulo 2^bits, as two's complement of that width when `signed`
+    pub fn wrap(&self, bits: usize, signed: bool) -> BigInt {
+        let modulus = BigInt::power_of_two(bits);
+        let mut value = self.div_rem(&modulus).map_or(BigInt::zero(), |(_, remainder)| remainder);
+        if value.negative {
+            value = value + modulus.clone();
+        }
+        if signed && value.bit_len() == bits {
+            value = value - modulus;
+        }
+        value
+    }
+
+    /// `&`, `|` or `^` of the two's complements of both values, digit by digit
+    pub fn bitwise(&self, other: &BigInt, operation: impl Fn(u32, u32) -> u32) -> BigInt {
+        // wide enough for the sign bit of both
+        let bits = (self.bit_len().max(other.bit_len()) + 1).div_ceil(32) * 32;
+        let a = self.wrap(bits, false);
+        let b = other.wrap(bits, false);
+        let magnitude = (0..bits / 32)
+            .map(|index| operation(*a.magnitude.get(index).unwrap_or(&0), *b.magnitude.get(index).unwrap_or(&0)))
+            .collect();
+        BigInt::new(false, magnitude).wrap(bits, true)
+    }
+
+    /// big-endian bytes of the magnitude, at least `size` of them
+    pub fn to_bytes(&self, size: usize) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self.magnitude.iter().rev().flat_map(|digit| digit.to_be_bytes()).skip_while(|byte| *byte == 0).collect();
+        while bytes.len() < size {
+            bytes.insert(0, 0);
+        }
+        bytes
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(value: i128) -> Self {
+        BigInt::new(value < 0, digits(value.unsigned_abs()))
+    }
+}
+
+impl From<u128> for BigInt {
+    fn from(value: u128) -> Self {
+        BigInt::new(false, digits(value))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitude(&self.magnitude, &other.magnitude))
+        }
+        match compare_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::new(other.negative, sub_magnitude(&other.magnitude, &self.magnitude)),
+            _ => BigInt::new(self.negative, sub_magnitude(&self.magnitude, &other.magnitude)),
+        }
+    }
+}
+
+impl Sub for BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: BigInt) -> BigInt {
+        self + -other
+    }
+}
+
+impl Mul for BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: BigInt) -> BigInt {
+        let mut product = vec![0u32; self.magnitude.len() + other.magnitude.len()];
+        for (i, a) in self.magnitude.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in other.magnitude.iter().enumerate() {
+                let sum = product[i + j] as u64 + *a as u64 * *b as u64 + carry;
+                product[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            product[i + other.magnitude.len()] = carry as u32;
+        }
+        BigInt::new(self.negative != other.negative, product)
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0")
+        }
+        // nine decimal digits at a time
+        let mut chunks = Vec::new();
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = div_rem_small(&magnitude, 1_000_000_000);
+            chunks.push(remainder);
+            magnitude = quotient;
+        }
+        write!(f, "{}", if self.negative { "-" } else { "" })?;
+        for (index, chunk) in chunks.iter().rev().enumerate() {
+            if index == 0 {
+                write!(f, "{}", chunk)?;
+            } else {
+                write!(f, "{:09}", chunk)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn digits(mut value: u128) -> Vec<u32> {
+    let mut digits = Vec::new();
+    while value > 0 {
+        digits.push(value as u32);
+        value >>= 32;
+    }
+    digits
+}
+
+fn trim(mut magnitude: Vec<u32>) -> Vec<u32> {
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+    magnitude
+}
+
+fn compare_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for index in 0..a.len().max(b.len()) {
+        let digit = *a.get(index).unwrap_or(&0) as u64 + *b.get(index).unwrap_or(&0) as u64 + carry;
+        sum.push(digit as u32);
+        carry = digit >> 32;
+    }
+    sum.push(carry as u32);
+    trim(sum)
+}
+
+/// `a - b` for `a >= b`
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (index, digit) in a.iter().enumerate() {
+        let mut value = *digit as i64 - *b.get(index).unwrap_or(&0) as i64 - borrow;
+        borrow = (value < 0) as i64;
+        if value < 0 {
+            value += 1 << 32;
+        }
+        difference.push(value as u32);
+    }
+    trim(difference)
+}
+
+fn mul_small(a: &[u32], factor: u32) -> Vec<u32> {
+    let mut product = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0u64;
+    for digit in a.iter() {
+        let value = *digit as u64 * factor as u64 + carry;
+        product.push(value as u32);
+        carry = value >> 32;
+    }
+    product.push(carry as u32);
+    trim(product)
+}
+
+fn add_small(a: &[u32], addend: u32) -> Vec<u32> {
+    add_magnitude(a, &[addend])
+}
+
+fn div_rem_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0; a.len()];
+    let mut remainder = 0u64;
+    for (index, digit) in a.iter().enumerate().rev() {
+        let value = (remainder << 32) | *digit as u64;
+        quotient[index] = (value / divisor as u64) as u32;
+        remainder = value % divisor as u64;
+    }
+    (trim(quotient), remainder as u32)
+}
+
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = b {
+        let (quotient, remainder) = div_rem_small(a, *divisor);
+        return (quotient, trim(vec![remainder]))
+    }
+    if compare_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec())
+    }
+    // one bit at a time, from the most significant
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for bit in (0..a.len() * 32).rev() {
+        remainder = shift_left_magnitude(&remainder, 1);
+        if a[bit / 32] >> (bit % 32) & 1 == 1 {
+            remainder = add_small(&remainder, 1);
+        }
+        if compare_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (trim(quotient), remainder)
+}
+
+fn shift_left_magnitude(a: &[u32], bits: usize) -> Vec<u32> {
+    if a.is_empty() {
+        return Vec::new()
+    }
+    let (digits, bits) = (bits / 32, bits % 32);
+    let mut shifted = vec![0u32; digits];
+    let mut carry = 0u32;
+    for digit in a.iter() {
+        shifted.push(if bits == 0 { *digit } else { (digit << bits) | carry });
+        carry = if bits == 0 { 0 } else { digit >> (32 - bits) };
+    }
+    shifted.push(carry);
+    trim(shifted)
+}
+
+fn shift_right_magnitude(a: &[u32], bits: usize) -> Vec<u32> {
+    let (digits, bits) = (bits / 32, bits % 32);
+    if digits >= a.len() {
+        return Vec::new()
+    }
+    let a = &a[digits..];
+    let shifted = (0..a.len())
+        .map(|index| {
+            let high = if bits == 0 { 0 } else { a.get(index + 1).map_or(0, |next| next << (32 - bits)) };
+            (a[index] >> bits) | high
+        })
+        .collect();
+    trim(shifted)
+}
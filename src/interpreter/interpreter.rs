@@ -0,0 +1,1552 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::ast::visit::{
+    self, Visitor,
+};
+use crate::ast::{
+    Argument, BinaryOperation, Block, CastingOperation, CompositeDeclaration, CompositeKind, Condition, Declaration, ElseBranch,
+    Expression, FunctionDeclaration, Identifier, IfStatement, IfTest, Program, SpecialFunctionKind, Span, Statement,
+    SwitchCase, TransactionDeclaration, Transfer, TypeAnnotation, UnaryOperation, VariableDeclaration,
+};
+use crate::checker::checker::{
+    TRANSACTION_TYPE, TypeCheck, check,
+};
+use crate::checker::types::{
+    NumberType, Type,
+};
+use crate::parser::parser::parse;
+
+use super::meter::Meter;
+use super::number::{
+    Number, NumberError,
+};
+use super::storage::{
+    Controller, Ledger, Link,
+};
+use super::value::{
+    AccountPart, CapabilityTarget, CompositeValue, DictionaryValue, Function, Value,
+};
+
+/// how deep declared functions can call each other, like the call stack limit of the reference runtime
+pub const CALL_DEPTH_LIMIT: usize = 2000;
+
+/// bytes of the native stack the calls of a program can use, less than the 2 MiB of a spawned thread
+/// so that deep recursion stops with an error instead of overflowing the stack of a test, which is
+/// reached before `CALL_DEPTH_LIMIT` unless the calls are shallow
+pub const CALL_STACK_SIZE: usize = 3 * 512 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// the program does not parse or check, or uses what it cannot
+    InvalidProgram,
+    Panic,
+    Arithmetic(NumberError),
+    PreCondition,
+    PostCondition,
+    Assertion,
+    /// `x!` of `nil`
+    NilUnwrap,
+    IndexOutOfBounds,
+    /// `as!` of a value that is not of the type
+    FailedCast,
+    /// a built-in function or member the interpreter does not implement
+    Unsupported,
+    /// saving over a stored value, publishing over a capability, or using an account that does not exist
+    Storage,
+    /// the program used more computation than its limit
+    ComputationLimit,
+    /// declared functions called each other deeper than `CALL_DEPTH_LIMIT` or `CALL_STACK_SIZE` allows
+    CallDepthLimit,
+}
+
+impl ErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidProgram => "invalid program",
+            Self::Panic => "panic",
+            Self::Arithmetic(error) => error.as_str(),
+            Self::PreCondition => "pre-condition failed",
+            Self::PostCondition => "post-condition failed",
+            Self::Assertion => "assertion failed",
+            Self::NilUnwrap => "unexpectedly found nil while forcing an optional",
+            Self::IndexOutOfBounds => "index out of bounds",
+            Self::FailedCast => "failed to force-cast value",
+            Self::Unsupported => "unsupported",
+            Self::Storage => "storage error",
+            Self::ComputationLimit => "computation limit exceeded",
+            Self::CallDepthLimit => "call stack depth limit exceeded",
+        }
+    }
+}
+
+/// why a program stopped
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    /// the message of `panic`, of the failed condition or `assert`, otherwise what went wrong
+    pub message: String,
+    pub span: Span,
+}
+
+impl RuntimeError {
+    pub fn new(kind: ErrorKind, message: &str, span: Span) -> Self {
+        RuntimeError { kind, message: message.to_string(), span }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.message.is_empty() {
+            write!(f, "{}", self.kind.as_str())
+        } else if let ErrorKind::Arithmetic(_) = self.kind {
+            // the message of an arithmetic error starts with its kind
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.kind.as_str(), self.message)
+        }
+    }
+}
+
+/// parse and check a program, which runs only without errors
+pub fn checked_program(source: &str) -> Result<(Program, TypeCheck), RuntimeError> {
+    let program = parse(source).map_err(|error| RuntimeError::new(ErrorKind::InvalidProgram, &error.message, error.span))?;
+    let checked = check(&program);
+    if let Some(error) = checked.errors().next() {
+        return Err(RuntimeError::new(ErrorKind::InvalidProgram, &error.message, error.span))
+    }
+    Ok((program, checked))
+}
+
+/// parse, check, then run the `main` function of a script
+pub fn run_script(source: &str, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (program, checked) = checked_program(source)?;
+    let mut interpreter = Interpreter::new(&program, &checked)?;
+    interpreter.invoke("main", arguments)
+}
+
+/// how a statement completed
+#[derive(Clone, Debug, PartialEq)]
+enum Flow {
+    Normal,
+    Return(Value),
+    Break,
+    Continue,
+}
+
+/// the variables of a running function
+#[derive(Clone, Debug, Default)]
+struct Frame {
+    /// innermost last
+    scopes: Vec<HashMap<String, Value>>,
+    self_value: Option<Value>,
+    /// qualified name of the composite whose function runs, nested types are found through it
+    composite: Option<String>,
+    /// values of the arguments of `before(...)` in post conditions, by span of the argument
+    before: HashMap<Span, Value>,
+}
+
+/// runs a checked program, the checker's types tell literals and casts apart
+pub struct Interpreter<'p> {
+    checked: &'p TypeCheck,
+    /// by qualified name, `Token.Vault.deposit`, initializers as `Token.Vault.init`, destructors as `Token.Vault.destroy`
+    functions: HashMap<String, &'p FunctionDeclaration>,
+    /// by qualified name
+    composites: HashMap<String, &'p CompositeDeclaration>,
+    /// contracts and top-level constants and variables
+    globals: HashMap<String, Value>,
+    transaction: Option<&'p TransactionDeclaration>,
+    frame: Frame,
+    /// frames of the functions that are running
+    depth: usize,
+    /// address of the native stack where the outermost function was called
+    stack_start: usize,
+    /// the accounts the program runs with
+    pub ledger: Ledger,
+    /// the computation the program used
+    pub meter: Meter,
+    /// what `log` printed
+    pub logs: Vec<String>,
+    /// what `emit` emitted
+    pub events: Vec<Value>,
+}
+
+impl<'p> Interpreter<'p> {
+    /// initialize the contracts and top-level variables of the program
+    pub fn new(program: &'p Program, checked: &'p TypeCheck) -> Result<Self, RuntimeError> {
+        Self::with_ledger(program, checked, Ledger::default(), Meter::default())
+    }
+
+    /// like `new`, with the accounts of the ledger, contracts deployed to them are not initialized again,
+    /// and metered from the start
+    pub fn with_ledger(program: &'p Program, checked: &'p TypeCheck, ledger: Ledger, meter: Meter) -> Result<Self, RuntimeError> {
+        let mut interpreter = Interpreter {
+            checked,
+            functions: HashMap::new(),
+            composites: HashMap::new(),
+            globals: HashMap::new(),
+            transaction: None,
+            frame: Frame { scopes: vec![HashMap::new()], ..Frame::default() },
+            depth: 0,
+            stack_start: 0,
+            ledger,
+            meter,
+            logs: Vec::new(),
+            events: Vec::new(),
+        };
+        interpreter.collect(&program.declarations, None);
+        for declaration in program.declarations.iter() {
+            match declaration {
+                Declaration::Variable(variable) => {
+                    let value = interpreter.transfer(&variable.value, variable.transfer)?;
+                    interpreter.globals.insert(variable.identifier.name.clone(), value);
+                },
+                Declaration::Composite(composite) if composite.kind == CompositeKind::Contract => {
+                    let name = &composite.identifier.name;
+                    if let Some(value) = interpreter.ledger.contract(name) {
+                        interpreter.globals.insert(name.clone(), value.clone());
+                        continue;
+                    }
+                    let value = interpreter.construct(name, Vec::new(), composite.span)?;
+                    if let Some(contract) = interpreter.ledger.contract_mut(name) {
+                        contract.value = Some(value);
+                    }
+                },
+                _ => (),
+            }
+        }
+        Ok(interpreter)
+    }
+
+    fn collect(&mut self, declarations: &'p [Declaration], outer: Option<&str>) {
+        let qualify = |name: &str| outer.map_or(name.to_string(), |outer| format!("{}.{}", outer, name));
+        for declaration in declarations.iter() {
+            match declaration {
+                Declaration::Composite(composite) => {
+                    let name = qualify(&composite.identifier.name);
+                    self.composites.insert(name.clone(), composite);
+                    self.collect(&composite.members, Some(&name));
+                },
+                // for default implementations and inherited conditions
+                Declaration::Interface(interface) => self.collect(&interface.members, Some(&qualify(&interface.identifier.name))),
+                Declaration::Function(function) => {
+                    self.functions.insert(qualify(&function.identifier.name), function);
+                },
+                Declaration::Transaction(transaction) => self.transaction = Some(transaction),
+                Declaration::SpecialFunction(special) if outer.is_some() => {
+                    let name = match special.kind {
+                        SpecialFunctionKind::Initializer => "init",
+                        SpecialFunctionKind::Destructor => "destroy",
+                        SpecialFunctionKind::Prepare => continue,
+                    };
+                    self.functions.insert(qualify(name), &special.function);
+                },
+                _ => (),
+            }
+        }
+    }
+
+    /// call a top-level function
+    pub fn invoke(&mut self, name: &str, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+        let Some(&function) = self.functions.get(name) else {
+            return Err(RuntimeError::new(ErrorKind::InvalidProgram, &format!("cannot find function `{}`", name), Span::default()))
+        };
+        self.call_function(function, name, None, arguments)
+    }
+
+    /// run the transaction of the program, signed by the accounts at `signers`
+    pub fn execute(&mut self, arguments: Vec<Value>, signers: &[u64]) -> Result<(), RuntimeError> {
+        let Some(transaction) = self.transaction else {
+            return Err(RuntimeError::new(ErrorKind::InvalidProgram, "the program has no transaction", Span::default()))
+        };
+        if arguments.len() != transaction.parameters.len() {
+            let message = format!("the transaction takes {} arguments, got {}", transaction.parameters.len(), arguments.len());
+            return Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, transaction.span))
+        }
+        let signer_parameters = transaction.prepare.as_ref().map_or(0, |prepare| prepare.function.parameters.len());
+        if signers.len() != signer_parameters {
+            let message = format!("`prepare` takes {} signers, got {}", signer_parameters, signers.len());
+            return Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, transaction.span))
+        }
+
+        let scope = transaction.parameters.iter().map(|parameter| parameter.identifier.name.clone()).zip(arguments).collect();
+        let composite = CompositeValue { name: TRANSACTION_TYPE.to_string(), kind: CompositeKind::Structure, fields: Vec::new(), uuid: None };
+        let frame = Frame { scopes: vec![scope], self_value: Some(Value::Composite(Value::shared(composite))), ..Frame::default() };
+        self.in_frame(frame, |this| {
+            if let Some(prepare) = &transaction.prepare {
+                let accounts = signers.iter().map(|&address| Value::Account { address, part: AccountPart::Account });
+                let scope = prepare.function.parameters.iter().map(|parameter| parameter.identifier.name.clone()).zip(accounts).collect();
+                if let Some(body) = &prepare.function.body {
+                    this.in_scope(scope, |this| this.statements(&body.statements))?;
+                }
+            }
+            this.conditions(&transaction.pre_conditions, ErrorKind::PreCondition)?;
+            this.before(&transaction.post_conditions)?;
+            if let Some(execute) = &transaction.execute {
+                this.block(execute)?;
+            }
+            this.conditions(&transaction.post_conditions, ErrorKind::PostCondition)
+        })
+    }
+
+    /// run `f` in `frame`, then return to the current one
+    fn in_frame<T>(&mut self, frame: Frame, f: impl FnOnce(&mut Self) -> Result<T, RuntimeError>) -> Result<T, RuntimeError> {
+        let caller = std::mem::replace(&mut self.frame, frame);
+        let result = f(self);
+        self.frame = caller;
+        result
+    }
+
+    /// run `f` with another innermost scope
+    fn in_scope<T>(&mut self, scope: HashMap<String, Value>, f: impl FnOnce(&mut Self) -> Result<T, RuntimeError>) -> Result<T, RuntimeError> {
+        self.frame.scopes.push(scope);
+        let result = f(self);
+        self.frame.scopes.pop();
+        result
+    }
+
+    // functions
+
+    /// call a declared function by qualified name, on `receiver` for functions of composites
+    fn call_function(&mut self, function: &'p FunctionDeclaration, name: &str, receiver: Option<Value>, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+        let composite = name.rsplit_once('.').map(|(composite, _)| composite.to_string());
+        let frame = Frame { scopes: vec![parameters(function, &arguments)], self_value: receiver, composite, before: HashMap::new() };
+        // the native stack would overflow long before the computation limit is reached
+        let stack = 0u8;
+        let stack = std::ptr::addr_of!(stack) as usize;
+        if self.depth == 0 {
+            self.stack_start = stack;
+        }
+        if self.depth >= CALL_DEPTH_LIMIT || self.stack_start.abs_diff(stack) > CALL_STACK_SIZE {
+            let message = format!("`{}` is called {} deep", name, self.depth);
+            return Err(RuntimeError::new(ErrorKind::CallDepthLimit, &message, function.span))
+        }
+        self.depth += 1;
+        self.meter.enter(name);
+        let result = self.meter.charge(self.meter.costs.function_invocation, function.span)
+            .and_then(|_| self.in_frame(frame, |this| this.function_body(function, name, &arguments)));
+        self.meter.exit();
+        self.depth -= 1;
+        result
+    }
+
+    fn function_body(&mut self, function: &'p FunctionDeclaration, name: &str, arguments: &[Value]) -> Result<Value, RuntimeError> {
+        // conditions of the interfaces the composite conforms to, with the interfaces' parameter names
+        let inherited: Vec<(&'p [Condition], &'p [Condition], &'p FunctionDeclaration)> = match name.rsplit_once('.') {
+            Some((composite, function_name)) => self.checked.inherited_conditions.iter()
+                .filter(|inherited| inherited.composite == composite && inherited.function == function_name)
+                .filter_map(|inherited| {
+                    let requirement = self.functions.get(&format!("{}.{}", inherited.interface, function_name))?;
+                    Some((inherited.pre_conditions.as_slice(), inherited.post_conditions.as_slice(), *requirement))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        for (pre_conditions, _, requirement) in inherited.iter() {
+            self.in_scope(parameters(requirement, arguments), |this| this.conditions(pre_conditions, ErrorKind::PreCondition))?;
+        }
+        self.conditions(&function.pre_conditions, ErrorKind::PreCondition)?;
+        self.before(&function.post_conditions)?;
+        for (_, post_conditions, requirement) in inherited.iter() {
+            self.in_scope(parameters(requirement, arguments), |this| this.before(post_conditions))?;
+        }
+
+        let result = match &function.body {
+            Some(body) => match self.block(body)? {
+                Flow::Return(value) => value,
+                _ => Value::Void,
+            },
+            None => Value::Void,
+        };
+
+        let mut scope = HashMap::new();
+        scope.insert("result".to_string(), result.clone());
+        self.in_scope(scope, |this| this.conditions(&function.post_conditions, ErrorKind::PostCondition))?;
+        for (_, post_conditions, requirement) in inherited.iter() {
+            let mut scope = parameters(requirement, arguments);
+            scope.insert("result".to_string(), result.clone());
+            self.in_scope(scope, |this| this.conditions(post_conditions, ErrorKind::PostCondition))?;
+        }
+        Ok(result)
+    }
+
+    fn conditions(&mut self, conditions: &[Condition], kind: ErrorKind) -> Result<(), RuntimeError> {
+        for condition in conditions.iter() {
+            if self.boolean(&condition.test)? {
+                continue;
+            }
+            let message = match &condition.message {
+                Some(message) => text(&self.expression(message)?),
+                None => String::new(),
+            };
+            return Err(RuntimeError::new(kind, &message, condition.span))
+        }
+        Ok(())
+    }
+
+    /// evaluate the arguments of `before(...)` in the post conditions when the function is entered
+    fn before(&mut self, post_conditions: &[Condition]) -> Result<(), RuntimeError> {
+        let mut befores = Befores::default();
+        for condition in post_conditions.iter() {
+            befores.visit_condition(condition);
+        }
+        for argument in befores.arguments.iter() {
+            let value = self.expression(argument)?.copy();
+            self.frame.before.insert(argument.span(), value);
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, function: Function, type_arguments: &[Type], arguments: Vec<Value>, span: Span) -> Result<Value, RuntimeError> {
+        match function {
+            Function::Declared { name, receiver } => {
+                let Some(&declaration) = self.functions.get(&name) else {
+                    return Err(RuntimeError::new(ErrorKind::InvalidProgram, &format!("cannot find function `{}`", name), span))
+                };
+                self.call_function(declaration, &name, receiver.map(|receiver| *receiver), arguments)
+            },
+            Function::Member { name, receiver } => {
+                self.meter.charge(self.meter.costs.function_invocation, span)?;
+                self.member_function(&name, *receiver, type_arguments, arguments, span)
+            },
+            Function::BuiltIn(name) => {
+                self.meter.charge(self.meter.costs.function_invocation, span)?;
+                self.built_in(&name, type_arguments, arguments, span)
+            },
+            Function::Constructor(name) => self.construct(&name, arguments, span),
+        }
+    }
+
+    /// create a composite, run its initializer, and for contracts make it global first
+    fn construct(&mut self, name: &str, arguments: Vec<Value>, span: Span) -> Result<Value, RuntimeError> {
+        let Some(&declaration) = self.composites.get(name) else {
+            return Err(RuntimeError::new(ErrorKind::InvalidProgram, &format!("cannot find composite `{}`", name), span))
+        };
+        match declaration.kind {
+            CompositeKind::Enum => {
+                let raw_value = arguments.into_iter().next().unwrap_or(Value::Void);
+                let case = self.enum_cases(name).into_iter().find(|case| match case {
+                    Value::Composite(composite) => composite.borrow().field("rawValue").is_some_and(|raw| raw.equals(&raw_value)),
+                    _ => false,
+                });
+                Ok(case.map_or(Value::Nil, Value::some))
+            },
+            CompositeKind::Event => {
+                let fields = declaration.parameters.iter().map(|parameter| parameter.identifier.name.clone()).zip(arguments).collect();
+                Ok(Value::Composite(Value::shared(CompositeValue { name: name.to_string(), kind: CompositeKind::Event, fields, uuid: None })))
+            },
+            kind => {
+                let uuid = (kind == CompositeKind::Resource).then(|| {
+                    self.ledger.uuid += 1;
+                    self.ledger.uuid
+                });
+                let value = Value::Composite(Value::shared(CompositeValue { name: name.to_string(), kind, fields: Vec::new(), uuid }));
+                if kind == CompositeKind::Contract {
+                    self.globals.insert(name.to_string(), value.clone());
+                }
+                let initializer = format!("{}.init", name);
+                if let Some(&function) = self.functions.get(&initializer) {
+                    self.call_function(function, &initializer, Some(value.clone()), arguments)?;
+                }
+                Ok(value)
+            },
+        }
+    }
+
+    /// the cases of an enum, with raw values in declaration order
+    fn enum_cases(&self, name: &str) -> Vec<Value> {
+        let Some(declaration) = self.composites.get(name) else {
+            return Vec::new()
+        };
+        let raw_type = self.checked.types.composite(name)
+            .and_then(|composite| composite.raw_type.as_ref())
+            .and_then(Type::as_number)
+            .unwrap_or(NumberType::UInt8);
+        declaration.members.iter()
+            .filter(|member| matches!(member, Declaration::EnumCase(_)))
+            .enumerate()
+            .filter_map(|(index, _)| {
+                let raw_value = Number::integer(raw_type, index as i128).ok()?;
+                let fields = vec![("rawValue".to_string(), Value::Number(raw_value))];
+                let composite = CompositeValue { name: name.to_string(), kind: CompositeKind::Enum, fields, uuid: None };
+                Some(Value::Composite(Value::shared(composite)))
+            })
+            .collect()
+    }
+
+    fn enum_case(&self, name: &str, case: &str) -> Option<Value> {
+        let declaration = self.composites.get(name)?;
+        let index = declaration.members.iter()
+            .filter_map(|member| match member {
+                Declaration::EnumCase(case) => Some(&case.identifier.name),
+                _ => None,
+            })
+            .position(|name| name == case)?;
+        self.enum_cases(name).into_iter().nth(index)
+    }
+
+    /// the function a composite declares or inherits from the default implementation of an interface
+    fn find_function(&self, composite: &str, name: &str) -> Option<String> {
+        let qualified = format!("{}.{}", composite, name);
+        if let Some(function) = self.functions.get(&qualified) {
+            let is_interface = self.checked.types.composite(composite).is_some_and(|composite| composite.is_interface);
+            if !is_interface || function.body.as_ref().is_some_and(|body| !body.statements.is_empty()) {
+                return Some(qualified)
+            }
+        }
+        let conformances = self.checked.types.composite(composite).map(|composite| composite.conformances.clone()).unwrap_or_default();
+        conformances.iter().find_map(|interface| self.find_function(interface, name))
+    }
+
+    fn built_in(&mut self, name: &str, type_arguments: &[Type], arguments: Vec<Value>, span: Span) -> Result<Value, RuntimeError> {
+        let mut arguments = arguments.into_iter();
+        let mut argument = || arguments.next().unwrap_or(Value::Void);
+        if let Some(ty) = NumberType::from_name(name) {
+            return match argument() {
+                Value::Number(number) => number.convert(ty).map(Value::Number).map_err(|error| arithmetic(error, &format!("conversion to {}", name), span)),
+                _ => Err(RuntimeError::new(ErrorKind::InvalidProgram, &format!("`{}` converts numbers", name), span)),
+            }
+        }
+        match name {
+            "log" => {
+                let value = argument();
+                self.logs.push(value.to_string());
+                Ok(Value::Void)
+            },
+            "panic" => Err(RuntimeError::new(ErrorKind::Panic, &text(&argument()), span)),
+            "assert" => match argument() {
+                Value::Bool(true) => Ok(Value::Void),
+                _ => Err(RuntimeError::new(ErrorKind::Assertion, &text(&argument().unwrap_optional().unwrap_or(Value::Void)), span)),
+            },
+            "Address" => match argument() {
+                Value::Number(number) => number.to_i128()
+                    .and_then(|number| u64::try_from(number).ok())
+                    .map(Value::Address)
+                    .ok_or_else(|| arithmetic(NumberError::Overflow, "conversion to Address", span)),
+                _ => Err(RuntimeError::new(ErrorKind::InvalidProgram, "`Address` converts integers", span)),
+            },
+            "Type" => Ok(Value::Type(type_arguments.first().cloned().unwrap_or(Type::Invalid))),
+            "getAccount" | "getAuthAccount" => match argument() {
+                Value::Address(address) => Ok(Value::Account { address, part: AccountPart::Account }),
+                _ => Err(RuntimeError::new(ErrorKind::InvalidProgram, &format!("`{}` takes an address", name), span)),
+            },
+            name => Err(RuntimeError::new(ErrorKind::Unsupported, &format!("`{}` is not supported by the interpreter", name), span)),
+        }
+    }
+
+    /// functions of built-in types, and `getType` and `isInstance` of all values
+    fn member_function(&mut self, name: &str, receiver: Value, type_arguments: &[Type], arguments: Vec<Value>, span: Span) -> Result<Value, RuntimeError> {
+        let argument = |index: usize| arguments.get(index).cloned().unwrap_or(Value::Void);
+        match name {
+            "getType" => return Ok(Value::Type(receiver.dynamic_type())),
+            "isInstance" => {
+                let Value::Type(ty) = argument(0) else {
+                    return Err(RuntimeError::new(ErrorKind::InvalidProgram, "`isInstance` takes a type", span))
+                };
+                return Ok(Value::Bool(self.checked.types.is_subtype(&receiver.dynamic_type(), &ty)))
+            },
+            _ => (),
+        }
+        if let Value::Account { .. } | Value::Capability { .. } = receiver.dereference() {
+            return self.account_function(name, receiver.dereference(), type_arguments, arguments, span)
+        }
+        let unsupported = || {
+            let message = format!("`{}` of `{}` is not supported by the interpreter", name, receiver.dynamic_type());
+            Err(RuntimeError::new(ErrorKind::Unsupported, &message, span))
+        };
+        match receiver.dereference() {
+            Value::Array(array) => {
+                let length = array.borrow().values.len();
+                match name {
+                    "append" => array.borrow_mut().values.push(argument(0)),
+                    "appendAll" => {
+                        if let Value::Array(other) = argument(0).dereference() {
+                            let values: Vec<Value> = other.borrow().values.iter().map(Value::copy).collect();
+                            array.borrow_mut().values.extend(values);
+                        }
+                    },
+                    "insert" => {
+                        let index = position(&argument(0), length + 1, length, span)?;
+                        array.borrow_mut().values.insert(index, argument(1));
+                    },
+                    "remove" => {
+                        let index = position(&argument(0), length, length, span)?;
+                        return Ok(array.borrow_mut().values.remove(index))
+                    },
+                    "removeFirst" | "removeLast" => {
+                        let index = if name == "removeFirst" { 0 } else { length.saturating_sub(1) };
+                        let index = position(&Value::int(index as i128), length, length, span)?;
+                        return Ok(array.borrow_mut().values.remove(index))
+                    },
+                    "contains" => return Ok(Value::Bool(array.borrow().values.iter().any(|value| value.equals(&argument(0))))),
+                    "firstIndex" => {
+                        let index = array.borrow().values.iter().position(|value| value.equals(&argument(0)));
+                        return Ok(index.map_or(Value::Nil, |index| Value::some(Value::int(index as i128))))
+                    },
+                    "concat" | "slice" | "reverse" => {
+                        let array = array.borrow();
+                        let values: Vec<Value> = match name {
+                            "concat" => match argument(0).dereference() {
+                                Value::Array(other) => array.values.iter().chain(other.borrow().values.iter()).map(Value::copy).collect(),
+                                _ => return unsupported(),
+                            },
+                            "slice" => {
+                                let from = position(&argument(0), length + 1, length, span)?;
+                                let up_to = position(&argument(1), length + 1, length, span)?;
+                                if from > up_to {
+                                    return Err(RuntimeError::new(ErrorKind::IndexOutOfBounds, &format!("slice from {} up to {}", from, up_to), span))
+                                }
+                                array.values[from..up_to].iter().map(Value::copy).collect()
+                            },
+                            _ => array.values.iter().rev().map(Value::copy).collect(),
+                        };
+                        return Ok(Value::array(array.element.clone(), values))
+                    },
+                    _ => return unsupported(),
+                }
+                Ok(Value::Void)
+            },
+            Value::Dictionary(dictionary) => match name {
+                "insert" => {
+                    let old = dictionary.borrow_mut().insert(argument(0), argument(1));
+                    Ok(old.map_or(Value::Nil, Value::some))
+                },
+                "remove" => {
+                    let old = dictionary.borrow_mut().remove(&argument(0));
+                    Ok(old.map_or(Value::Nil, Value::some))
+                },
+                "containsKey" => Ok(Value::Bool(dictionary.borrow().get(&argument(0)).is_some())),
+                _ => unsupported(),
+            },
+            Value::String(string) => {
+                let other = text(&argument(0));
+                match name {
+                    "concat" => Ok(Value::String(format!("{}{}", string, other))),
+                    "slice" => {
+                        let characters: Vec<char> = string.chars().collect();
+                        let length = characters.len();
+                        let from = position(&argument(0), length + 1, length, span)?;
+                        let up_to = position(&argument(1), length + 1, length, span)?;
+                        if from > up_to {
+                            return Err(RuntimeError::new(ErrorKind::IndexOutOfBounds, &format!("slice from {} up to {}", from, up_to), span))
+                        }
+                        Ok(Value::String(characters[from..up_to].iter().collect()))
+                    },
+                    "toLower" => Ok(Value::String(string.to_lowercase())),
+                    "toUpper" => Ok(Value::String(string.to_uppercase())),
+                    "contains" => Ok(Value::Bool(string.contains(other.as_str()))),
+                    "split" => {
+                        let parts = string.split(other.as_str()).map(Value::string).collect();
+                        Ok(Value::array(Type::String, parts))
+                    },
+                    "replaceAll" => Ok(Value::String(string.replace(other.as_str(), &text(&argument(1))))),
+                    _ => unsupported(),
+                }
+            },
+            Value::Number(_) | Value::Address(_) | Value::Path { .. } | Value::Character(_) if name == "toString" => {
+                Ok(Value::String(text(receiver.dereference())))
+            },
+            Value::Number(number) => match (name, argument(0)) {
+                ("toBigEndianBytes", _) => {
+                    let bytes = number.to_big_endian_bytes().into_iter()
+                        .filter_map(|byte| Number::integer(NumberType::UInt8, byte as i128).ok())
+                        .map(Value::Number)
+                        .collect();
+                    Ok(Value::array(Type::Number(NumberType::UInt8), bytes))
+                },
+                ("saturatingAdd", Value::Number(other)) => Ok(Value::Number(number.saturating_add(&other))),
+                ("saturatingSubtract", Value::Number(other)) => Ok(Value::Number(number.saturating_sub(&other))),
+                ("saturatingMultiply", Value::Number(other)) => Ok(Value::Number(number.saturating_mul(&other))),
+                ("saturatingDivide", Value::Number(other)) => {
+                    let operation = format!("{} saturating division", number.ty().name());
+                    number.saturating_div(&other).map(Value::Number).map_err(|error| arithmetic(error, &operation, span))
+                },
+                _ => unsupported(),
+            },
+            Value::Type(ty) if name == "isSubtype" => match argument(0) {
+                Value::Type(other) => Ok(Value::Bool(self.checked.types.is_subtype(ty, &other))),
+                _ => unsupported(),
+            },
+            _ => unsupported(),
+        }
+    }
+
+    /// functions of accounts, of the objects grouping them, and of capabilities.
+    /// `save`, `load`, `copy`, `borrow` and `type` are functions of accounts before Cadence 1.0, and of `account.storage` after
+    fn account_function(&mut self, name: &str, receiver: &Value, type_arguments: &[Type], arguments: Vec<Value>, span: Span) -> Result<Value, RuntimeError> {
+        let types = &self.checked.types;
+        let argument = |index: usize| arguments.get(index).cloned().unwrap_or(Value::Void);
+        let type_argument = type_arguments.first().cloned();
+        // a reference type, or the type of the referenced value
+        let fits = |value: &Value, ty: &Type| types.is_subtype(&value.dynamic_type(), ty.dereference());
+        let path = |index: usize| match argument(index) {
+            Value::Path { domain, identifier } => Ok((domain, identifier)),
+            _ => Err(RuntimeError::new(ErrorKind::InvalidProgram, &format!("`{}` takes a path", name), span)),
+        };
+        let writes = matches!(name, "save" | "load" | "link" | "unlink" | "publish" | "unpublish" | "issue" | "retarget" | "delete" | "setTag");
+        self.meter.charge(if writes { self.meter.costs.storage_write } else { self.meter.costs.storage_read }, span)?;
+        let unsupported = || {
+            let message = format!("`{}` of `{}` is not supported by the interpreter", name, receiver.dynamic_type());
+            Err(RuntimeError::new(ErrorKind::Unsupported, &message, span))
+        };
+
+        if let Value::Capability { address, target, borrow_type } = receiver {
+            let borrow_type = type_argument.unwrap_or_else(|| borrow_type.clone());
+            let borrowed = self.ledger.capability_target(*address, target)
+                .filter(|(value, controller_type)| fits(value, &borrow_type) && controller_type.is_none_or(|ty| fits(value, ty)))
+                .map(|(value, _)| value.clone());
+            return match name {
+                "borrow" => Ok(borrowed.map_or(Value::Nil, |value| Value::some(Value::Reference(Box::new(value))))),
+                "check" => Ok(Value::Bool(borrowed.is_some())),
+                _ => unsupported(),
+            }
+        }
+        let Value::Account { address, part } = *receiver else {
+            return unsupported()
+        };
+        let Some(account) = self.ledger.accounts.get_mut(&address) else {
+            return Err(RuntimeError::new(ErrorKind::Storage, &format!("account 0x{:016x} does not exist", address), span))
+        };
+        let any_reference = || Type::reference(Type::AnyStruct);
+        let capability = |target: CapabilityTarget, borrow_type: Type| Value::Capability { address, target, borrow_type };
+        let optional = |value: Option<Value>| value.map_or(Value::Nil, Value::some);
+
+        match (part, name) {
+            (AccountPart::Account | AccountPart::Storage, "save" | "load" | "copy" | "borrow" | "type" | "check") => {
+                let (domain, identifier) = path(if name == "save" { 1 } else { 0 })?;
+                if domain != "storage" {
+                    return Err(RuntimeError::new(ErrorKind::InvalidProgram, &format!("`{}` takes a storage path", name), span))
+                }
+                let ty = type_argument.unwrap_or(Type::AnyStruct);
+                match name {
+                    "save" if account.storage.contains_key(&identifier) => {
+                        let message = format!("cannot save to /storage/{} of account 0x{:016x}, it already stores a value", identifier, address);
+                        Err(RuntimeError::new(ErrorKind::Storage, &message, span))
+                    },
+                    "save" => {
+                        account.storage.insert(identifier, argument(0));
+                        Ok(Value::Void)
+                    },
+                    "type" => Ok(optional(account.storage.get(&identifier).map(|value| Value::Type(value.dynamic_type())))),
+                    "check" => Ok(Value::Bool(account.storage.get(&identifier).is_some_and(|value| fits(value, &ty)))),
+                    "borrow" => Ok(optional(account.storage.get(&identifier)
+                        .filter(|value| fits(value, &ty))
+                        .map(|value| Value::Reference(Box::new(value.clone()))))),
+                    _ => match account.storage.get(&identifier) {
+                        None => Ok(Value::Nil),
+                        Some(value) if !fits(value, &ty) => {
+                            let message = format!("expected `{}`, got `{}`", ty, value.dynamic_type());
+                            Err(RuntimeError::new(ErrorKind::FailedCast, &message, span))
+                        },
+                        Some(value) if name == "copy" => Ok(Value::some(value.copy())),
+                        Some(_) => Ok(optional(account.storage.remove(&identifier))),
+                    },
+                }
+            },
+            (AccountPart::Account, "link") => {
+                let (domain, identifier) = path(0)?;
+                let (target_domain, target_identifier) = path(1)?;
+                let key = (domain.clone(), identifier.clone());
+                if account.links.contains_key(&key) {
+                    return Ok(Value::Nil)
+                }
+                let borrow_type = type_argument.unwrap_or_else(any_reference);
+                let link = Link { domain: target_domain, identifier: target_identifier, borrow_type: borrow_type.clone() };
+                account.links.insert(key, link);
+                Ok(Value::some(capability(CapabilityTarget::Link { domain, identifier }, borrow_type)))
+            },
+            (AccountPart::Account, "unlink") => {
+                account.links.remove(&path(0)?);
+                Ok(Value::Void)
+            },
+            (AccountPart::Account, "getCapability") => {
+                let (domain, identifier) = path(0)?;
+                Ok(capability(CapabilityTarget::Link { domain, identifier }, type_argument.unwrap_or_else(any_reference)))
+            },
+            (AccountPart::Capabilities, "get" | "borrow") => {
+                let (_, identifier) = path(0)?;
+                let borrow_type = type_argument.unwrap_or_else(any_reference);
+                // capabilities published with another type borrow nothing
+                let target = match account.published.get(&identifier) {
+                    Some(Value::Capability { target, borrow_type: published, .. }) if types.is_subtype(published, &borrow_type) => target.clone(),
+                    _ => CapabilityTarget::Controller(0),
+                };
+                let capability = capability(target, borrow_type);
+                match name {
+                    "get" => Ok(capability),
+                    _ => self.account_function("borrow", &capability, &[], Vec::new(), span),
+                }
+            },
+            (AccountPart::Capabilities, "exists") => Ok(Value::Bool(account.published.contains_key(&path(0)?.1))),
+            (AccountPart::Capabilities, "publish") => {
+                let (_, identifier) = path(1)?;
+                if account.published.contains_key(&identifier) {
+                    let message = format!("cannot publish at /public/{} of account 0x{:016x}, a capability is already published there", identifier, address);
+                    return Err(RuntimeError::new(ErrorKind::Storage, &message, span))
+                }
+                account.published.insert(identifier, argument(0));
+                Ok(Value::Void)
+            },
+            (AccountPart::Capabilities, "unpublish") => Ok(optional(account.published.remove(&path(0)?.1))),
+            (AccountPart::StorageCapabilities, "issue") => {
+                let (_, target) = path(0)?;
+                let borrow_type = type_argument.unwrap_or_else(any_reference);
+                account.capability_id += 1;
+                let controller = Controller { target, borrow_type: borrow_type.clone(), tag: String::new() };
+                account.controllers.insert(account.capability_id, controller);
+                Ok(capability(CapabilityTarget::Controller(account.capability_id), borrow_type))
+            },
+            (AccountPart::StorageCapabilities, "getController") => {
+                let id = match argument(0) {
+                    Value::Number(number) => number.to_i128().and_then(|id| u64::try_from(id).ok()),
+                    _ => None,
+                };
+                let controller = id.filter(|id| account.controllers.contains_key(id));
+                Ok(optional(controller.map(|id| Value::Account { address, part: AccountPart::Controller(id) })))
+            },
+            (AccountPart::StorageCapabilities, "getControllers") => {
+                let (_, identifier) = path(0)?;
+                let controllers = account.controllers.iter()
+                    .filter(|(_, controller)| controller.target == identifier)
+                    .map(|(&id, _)| Value::Account { address, part: AccountPart::Controller(id) })
+                    .collect();
+                Ok(Value::array(Type::reference(Type::Other(AccountPart::Controller(0).as_str().to_string())), controllers))
+            },
+            (AccountPart::Controller(id), "target" | "retarget" | "delete" | "setTag") => {
+                let Some(controller) = account.controllers.get_mut(&id) else {
+                    return Err(RuntimeError::new(ErrorKind::Storage, &format!("capability {} was deleted", id), span))
+                };
+                match name {
+                    "target" => return Ok(Value::Path { domain: "storage".to_string(), identifier: controller.target.clone() }),
+                    "retarget" => controller.target = path(0)?.1,
+                    "setTag" => controller.tag = text(&argument(0)),
+                    _ => {
+                        account.controllers.remove(&id);
+                    },
+                }
+                Ok(Value::Void)
+            },
+            _ => unsupported(),
+        }
+    }
+
+    /// destroy a resource and the resources it owns, running custom destructors and emitting `ResourceDestroyed`
+    fn destroy(&mut self, value: Value) -> Result<(), RuntimeError> {
+        match value {
+            Value::Some(value) => self.destroy(*value),
+            Value::Array(array) => {
+                let values = std::mem::take(&mut array.borrow_mut().values);
+                values.into_iter().try_for_each(|value| self.destroy(value))
+            },
+            Value::Dictionary(dictionary) => {
+                let entries = std::mem::take(&mut dictionary.borrow_mut().entries);
+                entries.into_iter().try_for_each(|(_, value)| self.destroy(value))
+            },
+            Value::Composite(composite) if composite.borrow().kind == CompositeKind::Resource => {
+                let name = composite.borrow().name.clone();
+                let destructor = format!("{}.destroy", name);
+                if let Some(&function) = self.functions.get(&destructor) {
+                    self.call_function(function, &destructor, Some(Value::Composite(composite.clone())), Vec::new())?;
+                }
+
+                // default arguments are evaluated on the destroyed resource
+                let event = format!("{}.ResourceDestroyed", name);
+                if let Some(&declaration) = self.composites.get(&event) {
+                    let frame = Frame {
+                        scopes: vec![HashMap::new()],
+                        self_value: Some(Value::Composite(composite.clone())),
+                        composite: Some(name.clone()),
+                        before: HashMap::new(),
+                    };
+                    let fields = self.in_frame(frame, |this| {
+                        let mut fields = Vec::new();
+                        for parameter in declaration.parameters.iter() {
+                            let value = match &parameter.default {
+                                Some(default) => this.expression(default)?.copy(),
+                                None => Value::Nil,
+                            };
+                            fields.push((parameter.identifier.name.clone(), value));
+                        }
+                        Ok(fields)
+                    })?;
+                    let event = CompositeValue { name: event, kind: CompositeKind::Event, fields, uuid: None };
+                    self.events.push(Value::Composite(Value::shared(event)));
+                }
+
+                let fields = std::mem::take(&mut composite.borrow_mut().fields);
+                fields.into_iter().try_for_each(|(_, value)| self.destroy(value))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    // statements
+
+    fn block(&mut self, block: &Block) -> Result<Flow, RuntimeError> {
+        self.in_scope(HashMap::new(), |this| this.statements(&block.statements))
+    }
+
+    fn statements(&mut self, statements: &[Statement]) -> Result<Flow, RuntimeError> {
+        for statement in statements.iter() {
+            match self.statement(statement)? {
+                Flow::Normal => (),
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<Flow, RuntimeError> {
+        self.meter.charge(self.meter.costs.statement, statement.span())?;
+        match statement {
+            Statement::Expression(expression) => {
+                self.expression(expression)?;
+            },
+            Statement::Return { expression, .. } => {
+                let value = match expression {
+                    Some(expression) => self.transfer(expression, Transfer::Copy)?,
+                    None => Value::Void,
+                };
+                return Ok(Flow::Return(value))
+            },
+            Statement::Break { .. } => return Ok(Flow::Break),
+            Statement::Continue { .. } => return Ok(Flow::Continue),
+            Statement::Variable(declaration) => self.variable_declaration(declaration)?,
+            Statement::Assignment { target, transfer, value, .. } => {
+                let value = self.transfer(value, *transfer)?;
+                self.assign(target, value)?;
+            },
+            Statement::Swap { left, right, .. } => self.swap(left, right)?,
+            Statement::If(statement) => return self.if_statement(statement),
+            Statement::While { test, block, .. } => return self.while_statement(test, block, statement.span()),
+            Statement::For { index, identifier, value, block, .. } => {
+                return self.for_statement(index.as_ref(), identifier, value, block, statement.span())
+            },
+            Statement::Switch { expression, cases, .. } => return self.switch(expression, cases),
+            Statement::Emit { invocation, .. } => {
+                let event = self.expression(invocation)?;
+                self.events.push(event);
+            },
+            Statement::Destroy { expression, .. } => {
+                let value = self.expression(expression)?;
+                self.destroy(value)?;
+            },
+            Statement::Error(span) => {
+                return Err(RuntimeError::new(ErrorKind::InvalidProgram, "statement failed to parse", *span))
+            },
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn variable_declaration(&mut self, declaration: &VariableDeclaration) -> Result<(), RuntimeError> {
+        let value = self.transfer(&declaration.value, declaration.transfer)?;
+        if let Some((transfer, second)) = &declaration.second {
+            let second = self.transfer(second, *transfer)?;
+            self.assign(&declaration.value, second)?;
+        }
+        self.declare(&declaration.identifier.name, value);
+        Ok(())
+    }
+
+    fn swap(&mut self, left: &Expression, right: &Expression) -> Result<(), RuntimeError> {
+        let left_value = self.expression(left)?;
+        let right_value = self.expression(right)?;
+        self.assign(left, right_value)?;
+        self.assign(right, left_value)
+    }
+
+    fn while_statement(&mut self, test: &Expression, block: &Block, span: Span) -> Result<Flow, RuntimeError> {
+        while self.boolean(test)? {
+            self.meter.charge(self.meter.costs.loop_iteration, span)?;
+            match self.block(block)? {
+                Flow::Break => break,
+                Flow::Return(value) => return Ok(Flow::Return(value)),
+                Flow::Normal | Flow::Continue => (),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn for_statement(&mut self, index: Option<&Identifier>, identifier: &Identifier, value: &Expression, block: &Block, span: Span) -> Result<Flow, RuntimeError> {
+        let values = match self.expression(value)?.dereference() {
+            Value::Array(array) => array.borrow().values.clone(),
+            Value::String(string) => string.chars().map(|character| Value::Character(character.to_string())).collect(),
+            value => {
+                let message = format!("cannot iterate over `{}`", value.dynamic_type());
+                return Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, span))
+            },
+        };
+        for (position, element) in values.into_iter().enumerate() {
+            self.meter.charge(self.meter.costs.loop_iteration, span)?;
+            let mut scope = HashMap::new();
+            scope.insert(identifier.name.clone(), element.copy());
+            if let Some(index) = index {
+                scope.insert(index.name.clone(), Value::int(position as i128));
+            }
+            match self.in_scope(scope, |this| this.statements(&block.statements))? {
+                Flow::Break => break,
+                Flow::Return(value) => return Ok(Flow::Return(value)),
+                Flow::Normal | Flow::Continue => (),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn switch(&mut self, expression: &Expression, cases: &[SwitchCase]) -> Result<Flow, RuntimeError> {
+        let value = self.expression(expression)?;
+        for case in cases.iter() {
+            let mut matches = case.expressions.is_empty();
+            for expression in case.expressions.iter() {
+                if !matches && self.expression(expression)?.equals(&value) {
+                    matches = true;
+                }
+            }
+            if matches {
+                // `break` leaves the switch
+                return match self.in_scope(HashMap::new(), |this| this.statements(&case.statements))? {
+                    Flow::Break => Ok(Flow::Normal),
+                    flow => Ok(flow),
+                }
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn if_statement(&mut self, statement: &IfStatement) -> Result<Flow, RuntimeError> {
+        let scope = match &statement.test {
+            IfTest::Expression(test) => self.boolean(test)?.then(HashMap::new),
+            IfTest::Binding(declaration) => self.transfer(&declaration.value, declaration.transfer)?.unwrap_optional().map(|value| {
+                let mut scope = HashMap::new();
+                scope.insert(declaration.identifier.name.clone(), value);
+                scope
+            }),
+        };
+        match (scope, &statement.else_) {
+            (Some(scope), _) => self.in_scope(scope, |this| this.statements(&statement.then.statements)),
+            (None, Some(ElseBranch::Block(block))) => self.block(block),
+            (None, Some(ElseBranch::If(statement))) => self.if_statement(statement),
+            (None, None) => Ok(Flow::Normal),
+        }
+    }
+
+    fn declare(&mut self, name: &str, value: Value) {
+        if let Some(scope) = self.frame.scopes.last_mut() {
+            scope.insert(name.to_string(), value);
+        }
+    }
+
+    fn assign(&mut self, target: &Expression, value: Value) -> Result<(), RuntimeError> {
+        match target {
+            Expression::Identifier(identifier) => {
+                if let Some(variable) = self.frame.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(&identifier.name)) {
+                    *variable = value;
+                } else if let Some(variable) = self.globals.get_mut(&identifier.name) {
+                    *variable = value;
+                } else {
+                    let message = format!("cannot find variable `{}`", identifier.name);
+                    return Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, identifier.span))
+                }
+            },
+            Expression::Member { expression, identifier, .. } => match self.expression(expression)?.dereference() {
+                Value::Composite(composite) => composite.borrow_mut().set_field(&identifier.name, value),
+                base => {
+                    let message = format!("cannot assign to `{}` of `{}`", identifier.name, base.dynamic_type());
+                    return Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, identifier.span))
+                },
+            },
+            Expression::Index { expression, index, span } => {
+                let container = self.expression(expression)?;
+                let key = self.expression(index)?;
+                match container.dereference() {
+                    Value::Array(array) => {
+                        let length = array.borrow().values.len();
+                        let position = position(&key, length, length, *span)?;
+                        array.borrow_mut().values[position] = value;
+                    },
+                    // `nil` removes the key
+                    Value::Dictionary(dictionary) => match value.unwrap_optional() {
+                        Some(value) => {
+                            dictionary.borrow_mut().insert(key, value);
+                        },
+                        None => {
+                            dictionary.borrow_mut().remove(&key);
+                        },
+                    },
+                    container => {
+                        let message = format!("cannot index into `{}`", container.dynamic_type());
+                        return Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, *span))
+                    },
+                }
+            },
+            target => return Err(RuntimeError::new(ErrorKind::InvalidProgram, "invalid assignment target", target.span())),
+        }
+        Ok(())
+    }
+
+    // expressions
+
+    /// the value to store or pass: copied with `=`, moved with `<-`
+    fn transfer(&mut self, expression: &Expression, transfer: Transfer) -> Result<Value, RuntimeError> {
+        let value = self.expression(expression)?;
+        Ok(match transfer {
+            Transfer::Copy => value.copy(),
+            Transfer::Move | Transfer::MoveForce => value,
+        })
+    }
+
+    fn boolean(&mut self, expression: &Expression) -> Result<bool, RuntimeError> {
+        match self.expression(expression)? {
+            Value::Bool(value) => Ok(value),
+            value => {
+                let message = format!("expected `Bool`, got `{}`", value.dynamic_type());
+                Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, expression.span()))
+            },
+        }
+    }
+
+    /// the type the checker gave an expression, concrete types for literals of abstract number types
+    fn literal_type(&self, expression: &Expression) -> Type {
+        match self.checked.expression_type(expression).map(Type::unwrap_optional) {
+            Some(Type::Number(number)) if number.is_abstract() && matches!(expression, Expression::FixedPoint { .. }) => {
+                Type::Number(NumberType::UFix64)
+            },
+            Some(Type::Number(number)) if number.is_abstract() => Type::Number(NumberType::Int),
+            Some(ty) => ty.clone(),
+            None => Type::Invalid,
+        }
+    }
+
+    fn number(&self, expression: &Expression, literal: &str, negative: bool, span: Span) -> Result<Value, RuntimeError> {
+        match self.literal_type(expression) {
+            Type::Address => {
+                let digits = literal.trim_start_matches("0x").replace('_', "");
+                u64::from_str_radix(&digits, 16).map(Value::Address).map_err(|_| arithmetic(NumberError::Overflow, "Address literal", span))
+            },
+            ty => {
+                let number = ty.as_number().unwrap_or(match expression {
+                    Expression::FixedPoint { .. } => NumberType::UFix64,
+                    _ => NumberType::Int,
+                });
+                Number::parse(number, literal, negative)
+                    .map(Value::Number)
+                    .map_err(|error| arithmetic(error, &format!("{} literal", number.name()), span))
+            },
+        }
+    }
+
+    pub fn expression(&mut self, expression: &Expression) -> Result<Value, RuntimeError> {
+        match expression {
+            Expression::Nil { .. } => Ok(Value::Nil),
+            Expression::Bool { value, .. } => Ok(Value::Bool(*value)),
+            Expression::Integer { literal, span } | Expression::FixedPoint { literal, span } => self.number(expression, literal, false, *span),
+            Expression::String { value, .. } => Ok(match self.literal_type(expression) {
+                Type::Character => Value::Character(value.clone()),
+                _ => Value::String(value.clone()),
+            }),
+            Expression::Array { values, .. } => self.array(expression, values),
+            Expression::Dictionary { entries, .. } => self.dictionary(expression, entries),
+            Expression::Identifier(identifier) => self.variable(&identifier.name, identifier.span),
+            Expression::Path { domain, identifier, .. } => {
+                Ok(Value::Path { domain: domain.name.clone(), identifier: identifier.name.clone() })
+            },
+            Expression::Member { expression, optional, identifier, .. } => {
+                let base = self.expression(expression)?;
+                if !*optional {
+                    return self.member(base, &identifier.name, identifier.span)
+                }
+                match base.unwrap_optional() {
+                    Some(base) => Ok(match self.member(base, &identifier.name, identifier.span)? {
+                        value @ (Value::Some(_) | Value::Nil) => value,
+                        value => Value::some(value),
+                    }),
+                    None => Ok(Value::Nil),
+                }
+            },
+            Expression::Index { expression, index, span } => self.index(expression, index, *span),
+            Expression::Invocation { invoked, type_arguments, arguments, span } => self.invocation(invoked, type_arguments, arguments, *span),
+            Expression::Unary { operation, expression: operand, span } => self.unary(*operation, operand, *span),
+            Expression::Binary { operation, left, right, span } => self.binary(*operation, left, right, *span),
+            Expression::Conditional { test, then, else_, .. } => {
+                if self.boolean(test)? {
+                    self.expression(then)
+                } else {
+                    self.expression(else_)
+                }
+            },
+            Expression::Casting { expression, operation, type_annotation, span } => self.cast(expression, *operation, type_annotation, *span),
+            Expression::Create { invocation, .. } => self.expression(invocation),
+            Expression::Reference { expression, .. } => {
+                let value = self.expression(expression)?;
+                // references to optionals are optional references
+                Ok(match value {
+                    Value::Nil => Value::Nil,
+                    Value::Some(value) => Value::some(Value::Reference(value)),
+                    value => Value::Reference(Box::new(value)),
+                })
+            },
+            Expression::Force { expression, span } => match self.expression(expression)?.unwrap_optional() {
+                Some(value) => Ok(value),
+                None => Err(RuntimeError::new(ErrorKind::NilUnwrap, "", *span)),
+            },
+        }
+    }
+
+    // the arms of `expression` that hold values are functions of their own,
+    // so that the frames of recursive calls stay small
+
+    fn array(&mut self, expression: &Expression, values: &[Expression]) -> Result<Value, RuntimeError> {
+        let element = match self.literal_type(expression) {
+            Type::VariableSized(element) | Type::ConstantSized(element, _) => *element,
+            _ => Type::AnyStruct,
+        };
+        let mut elements = Vec::new();
+        for value in values.iter() {
+            elements.push(self.expression(value)?.copy());
+        }
+        Ok(Value::array(element, elements))
+    }
+
+    fn dictionary(&mut self, expression: &Expression, entries: &[(Expression, Expression)]) -> Result<Value, RuntimeError> {
+        let (key, value) = match self.literal_type(expression) {
+            Type::Dictionary(key, value) => (*key, *value),
+            _ => (Type::AnyStruct, Type::AnyStruct),
+        };
+        let mut dictionary = DictionaryValue { key, value, entries: Vec::new() };
+        for (key, value) in entries.iter() {
+            let key = self.expression(key)?.copy();
+            let value = self.expression(value)?.copy();
+            dictionary.insert(key, value);
+        }
+        Ok(Value::Dictionary(Value::shared(dictionary)))
+    }
+
+    fn index(&mut self, expression: &Expression, index: &Expression, span: Span) -> Result<Value, RuntimeError> {
+        let container = self.expression(expression)?;
+        let key = self.expression(index)?;
+        match container.dereference() {
+            Value::Array(array) => {
+                let array = array.borrow();
+                let position = position(&key, array.values.len(), array.values.len(), span)?;
+                Ok(array.values[position].clone())
+            },
+            Value::Dictionary(dictionary) => Ok(dictionary.borrow().get(&key).cloned().map_or(Value::Nil, Value::some)),
+            container => {
+                let message = format!("cannot index into `{}`", container.dynamic_type());
+                Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, span))
+            },
+        }
+    }
+
+    fn invocation(&mut self, invoked: &Expression, type_arguments: &[TypeAnnotation], arguments: &[Argument], span: Span) -> Result<Value, RuntimeError> {
+        // `before(x)` was evaluated when the function was entered
+        if let (Expression::Identifier(identifier), [argument]) = (invoked, arguments) {
+            if identifier.name == "before" {
+                if let Some(value) = self.frame.before.get(&argument.expression.span()) {
+                    return Ok(value.clone())
+                }
+            }
+        }
+        let function = self.expression(invoked)?;
+        let type_arguments: Vec<Type> = type_arguments.iter()
+            .map(|argument| self.checked.annotation_type(argument).cloned().unwrap_or(Type::Invalid))
+            .collect();
+        let mut values = Vec::new();
+        for argument in arguments.iter() {
+            values.push(self.expression(&argument.expression)?.copy());
+        }
+        // `a?.f()` calls `f` if `a` is not `nil`
+        let is_chained = matches!(invoked, Expression::Member { optional: true, .. });
+        match function {
+            Value::Function(function) => self.call(function, &type_arguments, values, span),
+            Value::Nil if is_chained => Ok(Value::Nil),
+            Value::Some(function) if is_chained => match *function {
+                Value::Function(function) => self.call(function, &type_arguments, values, span).map(Value::some),
+                _ => Err(RuntimeError::new(ErrorKind::InvalidProgram, "cannot call value", invoked.span())),
+            },
+            _ => Err(RuntimeError::new(ErrorKind::InvalidProgram, "cannot call value", invoked.span())),
+        }
+    }
+
+    fn unary(&mut self, operation: UnaryOperation, operand: &Expression, span: Span) -> Result<Value, RuntimeError> {
+        match operation {
+            UnaryOperation::Minus => match operand {
+                Expression::Integer { literal, .. } | Expression::FixedPoint { literal, .. } => {
+                    self.number(operand, literal, true, span)
+                },
+                operand => match self.expression(operand)? {
+                    Value::Number(number) => {
+                        let operation = format!("{} negation", number.ty().name());
+                        number.checked_neg().map(Value::Number).map_err(|error| arithmetic(error, &operation, span))
+                    },
+                    value => {
+                        let message = format!("cannot negate `{}`", value.dynamic_type());
+                        Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, span))
+                    },
+                },
+            },
+            UnaryOperation::Not => Ok(Value::Bool(!self.boolean(operand)?)),
+            UnaryOperation::Move => self.expression(operand),
+        }
+    }
+
+    fn cast(&mut self, expression: &Expression, operation: CastingOperation, type_annotation: &TypeAnnotation, span: Span) -> Result<Value, RuntimeError> {
+        let value = self.expression(expression)?;
+        if operation == CastingOperation::Static {
+            return Ok(value)
+        }
+        let target = self.checked.annotation_type(type_annotation).cloned().unwrap_or(Type::Invalid);
+        let fits = self.checked.types.is_subtype(&value.dynamic_type(), &target);
+        match (operation, fits) {
+            (CastingOperation::Failable, true) => Ok(Value::some(value)),
+            (CastingOperation::Failable, false) => Ok(Value::Nil),
+            (_, true) => Ok(value),
+            (_, false) => {
+                let message = format!("expected `{}`, got `{}`", target, value.dynamic_type());
+                Err(RuntimeError::new(ErrorKind::FailedCast, &message, span))
+            },
+        }
+    }
+
+    /// a name used as value: local variables, `self`, globals, then declared and built-in functions and types
+    fn variable(&self, name: &str, span: Span) -> Result<Value, RuntimeError> {
+        if let Some(value) = self.frame.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+            return Ok(value.clone())
+        }
+        if name == "self" {
+            if let Some(value) = &self.frame.self_value {
+                return Ok(value.clone())
+            }
+        }
+        if let Some(value) = self.globals.get(name) {
+            return Ok(value.clone())
+        }
+        if self.functions.contains_key(name) {
+            return Ok(Value::Function(Function::Declared { name: name.to_string(), receiver: None }))
+        }
+        // nested types are used by their name inside the composite declaring them
+        let mut outer = self.frame.composite.clone();
+        while let Some(composite) = outer {
+            let qualified = format!("{}.{}", composite, name);
+            if self.composites.contains_key(&qualified) {
+                return Ok(Value::Function(Function::Constructor(qualified)))
+            }
+            outer = composite.rsplit_once('.').map(|(outer, _)| outer.to_string());
+        }
+        if self.composites.contains_key(name) {
+            return Ok(Value::Function(Function::Constructor(name.to_string())))
+        }
+        match name {
+            "log" | "panic" | "assert" | "getAccount" | "getAuthAccount" | "getCurrentBlock" | "getBlock" | "unsafeRandom" |
+            "revertibleRandom" | "Address" | "Type" => Ok(Value::Function(Function::BuiltIn(name.to_string()))),
+            _ if NumberType::from_name(name).is_some() => Ok(Value::Function(Function::BuiltIn(name.to_string()))),
+            _ => Err(RuntimeError::new(ErrorKind::InvalidProgram, &format!("cannot find `{}`", name), span)),
+        }
+    }
+
+    fn member(&mut self, base: Value, name: &str, span: Span) -> Result<Value, RuntimeError> {
+        let member_function = |base: Value| Ok(Value::Function(Function::Member { name: name.to_string(), receiver: Box::new(base) }));
+        match base.dereference() {
+            Value::Composite(composite) => {
+                let (composite_name, uuid) = {
+                    let composite = composite.borrow();
+                    if let Some(field) = composite.field(name) {
+                        return Ok(field.clone())
+                    }
+                    (composite.name.clone(), composite.uuid)
+                };
+                if let (Some(uuid), "uuid") = (uuid, name) {
+                    return Ok(Value::uint64(uuid))
+                }
+                if name == "account" {
+                    if let Some(address) = self.ledger.contract_address(&composite_name) {
+                        return Ok(Value::Account { address, part: AccountPart::Account })
+                    }
+                }
+                if let Some(function) = self.find_function(&composite_name, name) {
+                    return Ok(Value::Function(Function::Declared { name: function, receiver: Some(Box::new(base)) }))
+                }
+                let nested = format!("{}.{}", composite_name, name);
+                if self.composites.contains_key(&nested) {
+                    return Ok(Value::Function(Function::Constructor(nested)))
+                }
+                match name {
+                    "getType" | "isInstance" => member_function(base),
+                    _ => {
+                        let message = format!("`{}` of `{}` is not supported by the interpreter", name, composite_name);
+                        Err(RuntimeError::new(ErrorKind::Unsupported, &message, span))
+                    },
+                }
+            },
+            Value::Function(Function::Constructor(composite)) => match self.enum_case(composite, name) {
+                Some(case) => Ok(case),
+                None => {
+                    let message = format!("`{}` of `{}` is not supported by the interpreter", name, composite);
+                    Err(RuntimeError::new(ErrorKind::Unsupported, &message, span))
+                },
+            },
+            Value::Array(array) if name == "length" => Ok(Value::int(array.borrow().values.len() as i128)),
+            Value::Dictionary(dictionary) => {
+                let dictionary = dictionary.borrow();
+                match name {
+                    "length" => Ok(Value::int(dictionary.entries.len() as i128)),
+                    "keys" => Ok(Value::array(dictionary.key.clone(), dictionary.entries.iter().map(|(key, _)| key.copy()).collect())),
+                    "values" => Ok(Value::array(dictionary.value.clone(), dictionary.entries.iter().map(|(_, value)| value.copy()).collect())),
+                    _ => member_function(base.clone()),
+                }
+            },
+            Value::String(string) => match name {
+                "length" => Ok(Value::int(string.chars().count() as i128)),
+                "utf8" => {
+                    let bytes = string.bytes()
+                        .filter_map(|byte| Number::integer(NumberType::UInt8, byte as i128).ok())
+                        .map(Value::Number)
+                        .collect();
+                    Ok(Value::array(Type::Number(NumberType::UInt8), bytes))
+                },
+                _ => member_function(base.clone()),
+            },
+            Value::Type(ty) if name == "identifier" => Ok(Value::String(ty.to_string())),
+            &Value::Account { address, part } => {
+                let account = |part: AccountPart| Ok(Value::Account { address, part });
+                let controller = |id: &u64| self.ledger.accounts.get(&address).and_then(|account| account.controllers.get(id));
+                match (part, name) {
+                    (AccountPart::Account, "address") => Ok(Value::Address(address)),
+                    (AccountPart::Account, "storage") => account(AccountPart::Storage),
+                    (AccountPart::Account, "capabilities") => account(AccountPart::Capabilities),
+                    (AccountPart::Account, "contracts") => account(AccountPart::Contracts),
+                    (AccountPart::Capabilities, "storage") => account(AccountPart::StorageCapabilities),
+                    (AccountPart::Contracts, "names") => {
+                        let names = self.ledger.accounts.get(&address)
+                            .map(|account| account.contracts.keys().map(|name| Value::string(name)).collect())
+                            .unwrap_or_default();
+                        Ok(Value::array(Type::String, names))
+                    },
+                    (AccountPart::Controller(id), "capabilityID") => Ok(Value::uint64(id)),
+                    (AccountPart::Controller(id), "borrowType") => {
+                        Ok(Value::Type(controller(&id).map_or(Type::Invalid, |controller| controller.borrow_type.clone())))
+                    },
+                    (AccountPart::Controller(id), "tag") => Ok(Value::String(controller(&id).map(|controller| controller.tag.clone()).unwrap_or_default())),
+                    _ => member_function(base.clone()),
+                }
+            },
+            Value::Capability { address, target, .. } => match name {
+                "address" => Ok(Value::Address(*address)),
+                "id" => Ok(Value::uint64(match target {
+                    CapabilityTarget::Controller(id) => *id,
+                    CapabilityTarget::Link { .. } => 0,
+                })),
+                _ => member_function(base.clone()),
+            },
+            _ => member_function(base.clone()),
+        }
+    }
+
+    fn binary(&mut self, operation: BinaryOperation, left: &Expression, right: &Expression, span: Span) -> Result<Value, RuntimeError> {
+        match operation {
+            BinaryOperation::Or => return Ok(Value::Bool(self.boolean(left)? || self.boolean(right)?)),
+            BinaryOperation::And => return Ok(Value::Bool(self.boolean(left)? && self.boolean(right)?)),
+            BinaryOperation::NilCoalescing => {
+                return match self.expression(left)?.unwrap_optional() {
+                    Some(value) => Ok(value),
+                    None => self.expression(right),
+                }
+            },
+            _ => (),
+        }
+        let left = self.expression(left)?;
+        let right = self.expression(right)?;
+        match operation {
+            BinaryOperation::Equal => return Ok(Value::Bool(left.equals(&right))),
+            BinaryOperation::NotEqual => return Ok(Value::Bool(!left.equals(&right))),
+            BinaryOperation::Less | BinaryOperation::LessEqual | BinaryOperation::Greater | BinaryOperation::GreaterEqual => {
+                let ordering = match (left.dereference(), right.dereference()) {
+                    (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+                    (Value::String(a), Value::String(b)) | (Value::Character(a), Value::Character(b)) => Some(a.cmp(b)),
+                    _ => None,
+                };
+                let Some(ordering) = ordering else {
+                    let message = format!("cannot compare `{}` and `{}`", left.dynamic_type(), right.dynamic_type());
+                    return Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, span))
+                };
+                return Ok(Value::Bool(match operation {
+                    BinaryOperation::Less => ordering == Ordering::Less,
+                    BinaryOperation::LessEqual => ordering != Ordering::Greater,
+                    BinaryOperation::Greater => ordering == Ordering::Greater,
+                    _ => ordering != Ordering::Less,
+                }))
+            },
+            _ => (),
+        }
+        let (Value::Number(a), Value::Number(b)) = (left.dereference(), right.dereference()) else {
+            let message = format!("cannot apply `{}` to `{}` and `{}`", operation.as_str(), left.dynamic_type(), right.dynamic_type());
+            return Err(RuntimeError::new(ErrorKind::InvalidProgram, &message, span))
+        };
+        let (result, name) = match operation {
+            BinaryOperation::Plus => (a.checked_add(b), "addition"),
+            BinaryOperation::Minus => (a.checked_sub(b), "subtraction"),
+            BinaryOperation::Mul => (a.checked_mul(b), "multiplication"),
+            BinaryOperation::Div => (a.checked_div(b), "division"),
+            BinaryOperation::Mod => (a.checked_rem(b), "remainder"),
+            BinaryOperation::BitwiseOr => (a.bitwise_or(b), "bitwise or"),
+            BinaryOperation::BitwiseXor => (a.bitwise_xor(b), "bitwise xor"),
+            BinaryOperation::BitwiseAnd => (a.bitwise_and(b), "bitwise and"),
+            BinaryOperation::BitwiseShiftLeft => (a.shift_left(b), "left shift"),
+            _ => (a.shift_right(b), "right shift"),
+        };
+        result.map(Value::Number).map_err(|error| arithmetic(error, &format!("{} {}", a.ty().name(), name), span))
+    }
+}
+
+/// the parameters of `function` bound to the arguments
+fn parameters(function: &FunctionDeclaration, arguments: &[Value]) -> HashMap<String, Value> {
+    function.parameters.iter().map(|parameter| parameter.identifier.name.clone()).zip(arguments.iter().cloned()).collect()
+}
+
+/// `error` of `operation`, e.g. `overflow in UInt8 addition`
+fn arithmetic(error: NumberError, operation: &str, span: Span) -> RuntimeError {
+    RuntimeError::new(ErrorKind::Arithmetic(error), &format!("{} in {}", error.as_str(), operation), span)
+}
+
+/// strings without quotes, other values as `log` prints them
+fn text(value: &Value) -> String {
+    match value.dereference() {
+        Value::String(string) | Value::Character(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// the index an integer value stands for, if it is below `bound`
+fn position(index: &Value, bound: usize, length: usize, span: Span) -> Result<usize, RuntimeError> {
+    let position = match index.dereference() {
+        Value::Number(number) => number.to_i128().and_then(|index| usize::try_from(index).ok()),
+        _ => None,
+    };
+    match position {
+        Some(position) if position < bound => Ok(position),
+        _ => {
+            let message = format!("index {} is out of bounds for length {}", text(index), length);
+            Err(RuntimeError::new(ErrorKind::IndexOutOfBounds, &message, span))
+        },
+    }
+}
+
+/// the arguments of `before(...)`
+#[derive(Default)]
+struct Befores {
+    arguments: Vec<Expression>,
+}
+
+impl Visitor for Befores {
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let Expression::Invocation { invoked, arguments, .. } = expression {
+            if let (Expression::Identifier(identifier), [argument]) = (&**invoked, arguments.as_slice()) {
+                if identifier.name == "before" {
+                    self.arguments.push(argument.expression.clone());
+                }
+            }
+        }
+        visit::walk_expression(self, expression)
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use crate::ast::Span;
+
+use super::interpreter::{
+    ErrorKind, RuntimeError,
+};
+
+/// the highest computation limit of a transaction on Flow
+pub const FLOW_COMPUTATION_LIMIT: u64 = 9999;
+
+/// what an operation costs, in the computation units of Flow
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CostTable {
+    pub statement: u64,
+    /// each iteration of a `while` or `for` loop, besides the statements of the body
+    pub loop_iteration: u64,
+    /// declared, built-in and member functions, and initializers
+    pub function_invocation: u64,
+    /// `copy`, `borrow`, `type`, `check`, getting capabilities and controllers
+    pub storage_read: u64,
+    /// `save`, `load`, linking, issuing and publishing capabilities, changing controllers
+    pub storage_write: u64,
+}
+
+impl Default for CostTable {
+    /// close to the weights of Flow: statements, loop iterations and invocations cost a unit,
+    /// storage operations more for the registers they read and write
+    fn default() -> Self {
+        CostTable {
+            statement: 1,
+            loop_iteration: 1,
+            function_invocation: 1,
+            storage_read: 10,
+            storage_write: 10,
+        }
+    }
+}
+
+/// the computation of the invocations of one function
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FunctionUsage {
+    pub invocations: u64,
+    /// including the functions it calls, recursive calls count for each invocation they are part of
+    pub computation: u64,
+    /// excluding the functions it calls
+    pub own: u64,
+}
+
+/// a function that is running
+#[derive(Clone, Debug)]
+struct Call {
+    name: String,
+    /// computation used when it was invoked
+    start: u64,
+    /// computation of the functions it called
+    callees: u64,
+}
+
+/// counts the computation a program uses, and stops it at the limit
+#[derive(Clone, Debug)]
+pub struct Meter {
+    pub costs: CostTable,
+    pub limit: u64,
+    used: u64,
+    /// by qualified name, `Token.Vault.deposit`
+    functions: BTreeMap<String, FunctionUsage>,
+    /// innermost last
+    calls: Vec<Call>,
+}
+
+impl Default for Meter {
+    /// with the costs of Flow, without a limit
+    fn default() -> Self {
+        Meter::new(CostTable::default(), u64::MAX)
+    }
+}
+
+impl Meter {
+    pub fn new(costs: CostTable, limit: u64) -> Self {
+        Meter { costs, limit, used: 0, functions: BTreeMap::new(), calls: Vec::new() }
+    }
+
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    pub fn usage(&self) -> &BTreeMap<String, FunctionUsage> {
+        &self.functions
+    }
+
+    /// use `cost` more, the operation at `span` fails if it exceeds the limit
+    pub fn charge(&mut self, cost: u64, span: Span) -> Result<(), RuntimeError> {
+        self.used = self.used.saturating_add(cost);
+        if self.used > self.limit {
+            let message = format!("used more than the limit of {}", self.limit);
+            return Err(RuntimeError::new(ErrorKind::ComputationLimit, &message, span))
+        }
+        Ok(())
+    }
+
+    /// start counting for an invocation of a function
+    pub fn enter(&mut self, name: &str) {
+        self.calls.push(Call { name: name.to_string(), start: self.used, callees: 0 });
+    }
+
+    /// stop counting for the innermost invocation
+    pub fn exit(&mut self) {
+        let Some(call) = self.calls.pop() else {
+            return
+        };
+        let computation = self.used - call.start;
+        if let Some(caller) = self.calls.last_mut() {
+            caller.callees += computation;
+        }
+        let usage = self.functions.entry(call.name).or_default();
+        usage.invocations += 1;
+        usage.computation += computation;
+        usage.own += computation - call.callees;
+    }
+
+    /// the usage of the functions, most computation first, one per line
+    pub fn report(&self) -> String {
+        let mut functions: Vec<(&String, &FunctionUsage)> = self.functions.iter().collect();
+        functions.sort_by(|(a_name, a), (b_name, b)| b.computation.cmp(&a.computation).then(a_name.cmp(b_name)));
+        let width = functions.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max("function".len());
+        let mut report = format!("{:width$}  {:>11}  {:>11}  {:>11}\n", "function", "invocations", "computation", "own");
+        for (name, usage) in functions {
+            report.push_str(&format!("{:width$}  {:>11}  {:>11}  {:>11}\n", name, usage.invocations, usage.computation, usage.own));
+        }
+        report.push_str(&format!("total computation: {}\n", self.used));
+        report
+    }
+}
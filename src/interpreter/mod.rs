@@ -0,0 +1,7 @@
+#[allow(clippy::module_inception)]
+pub mod interpreter;
+pub mod bigint;
+pub mod meter;
+pub mod number;
+pub mod storage;
+pub mod value;
@@ -0,0 +1,332 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::checker::types::NumberType;
+
+use super::bigint::BigInt;
+
+/// fixed point values are integers scaled by 10^8
+pub const FIXED_POINT_SCALE: i128 = 100_000_000;
+
+const FIXED_POINT_DECIMALS: usize = 8;
+
+/// what the reference runtime reports arithmetic with as errors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumberError {
+    Overflow,
+    Underflow,
+    DivisionByZero,
+    /// a literal that is not a number of the type, e.g. too many decimals, or a negative shift
+    Invalid,
+}
+
+impl NumberError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Overflow => "overflow",
+            Self::Underflow => "underflow",
+            Self::DivisionByZero => "division by zero",
+            Self::Invalid => "invalid number",
+        }
+    }
+}
+
+/// a value of a concrete number type.
+/// `IntN` and `UIntN` are checked, `WordN` wrap, `Int` and `UInt` are unbounded,
+/// `Fix64` and `UFix64` have 8 decimals and round towards zero
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Number {
+    ty: NumberType,
+    /// fixed points scaled by `FIXED_POINT_SCALE`
+    value: BigInt,
+}
+
+impl Number {
+    /// the integer `value` of type `ty`, if it is in range
+    pub fn integer(ty: NumberType, value: i128) -> Result<Self, NumberError> {
+        Self::new(ty, BigInt::from(value))
+    }
+
+    /// the integer `value` of type `ty`, if it is in range
+    pub fn new(ty: NumberType, value: BigInt) -> Result<Self, NumberError> {
+        let value = if ty.is_fixed_point() { value * scale() } else { value };
+        Self::checked(ty, value)
+    }
+
+    /// a number of `ty` from its raw value as `value` returns it, if it is in range
+    pub fn from_raw(ty: NumberType, value: BigInt) -> Result<Self, NumberError> {
+        Self::checked(ty, value)
+    }
+
+    /// a number of `ty` from its raw value, fixed points scaled
+    fn checked(ty: NumberType, value: BigInt) -> Result<Self, NumberError> {
+        let (min, max) = range(ty);
+        if min.is_some_and(|min| value < min) {
+            return Err(NumberError::Underflow)
+        }
+        if max.is_some_and(|max| value > max) {
+            return Err(NumberError::Overflow)
+        }
+        Ok(Number { ty, value })
+    }
+
+    /// a number of `ty` from a raw result, wrapped for `WordN` types
+    fn result(ty: NumberType, value: BigInt) -> Result<Self, NumberError> {
+        match bits(ty) {
+            Some(bits) if is_word(ty) => Ok(Number { ty, value: value.wrap(bits, false) }),
+            _ => Self::checked(ty, value),
+        }
+    }
+
+    /// a number of `ty` from a raw result, clamped to the range of the type
+    fn saturated(ty: NumberType, value: BigInt) -> Number {
+        let (min, max) = range(ty);
+        let value = match (min, max) {
+            (Some(min), _) if value < min => min,
+            (_, Some(max)) if value > max => max,
+            _ => value,
+        };
+        Number { ty, value }
+    }
+
+    /// the smallest value of a bounded type
+    pub fn min(ty: NumberType) -> Option<Number> {
+        range(ty).0.map(|value| Number { ty, value })
+    }
+
+    /// the largest value of a bounded type
+    pub fn max(ty: NumberType) -> Option<Number> {
+        range(ty).1.map(|value| Number { ty, value })
+    }
+
+    /// parse a literal as written in source, `1_000`, `0xff`, `0b101`, `0o17` or `1.5`
+    pub fn parse(ty: NumberType, literal: &str, negative: bool) -> Result<Self, NumberError> {
+        let literal = literal.replace('_', "");
+        let magnitude = if ty.is_fixed_point() {
+            let (integer, fraction) = literal.split_once('.').unwrap_or((&literal, ""));
+            if fraction.len() > FIXED_POINT_DECIMALS {
+                return Err(NumberError::Invalid)
+            }
+            let integer = BigInt::parse(integer, 10).ok_or(NumberError::Invalid)?;
+            let fraction = BigInt::parse(&format!("{:0<8}", fraction), 10).ok_or(NumberError::Invalid)?;
+            integer * scale() + fraction
+        } else {
+            let (digits, radix) = match literal.get(..2) {
+                Some("0x") => (&literal[2..], 16),
+                Some("0b") => (&literal[2..], 2),
+                Some("0o") => (&literal[2..], 8),
+                _ => (literal.as_str(), 10),
+            };
+            BigInt::parse(digits, radix).ok_or(NumberError::Invalid)?
+        };
+        Self::checked(ty, if negative { -magnitude } else { magnitude })
+    }
+
+    pub fn ty(&self) -> NumberType {
+        self.ty
+    }
+
+    /// the raw value, fixed points scaled by `FIXED_POINT_SCALE`
+    pub fn value(&self) -> &BigInt {
+        &self.value
+    }
+
+    /// the integer value, fixed points truncated, if it fits
+    pub fn to_i128(&self) -> Option<i128> {
+        self.integer_part().to_i128()
+    }
+
+    fn integer_part(&self) -> BigInt {
+        if self.ty.is_fixed_point() {
+            self.value.div_rem(&scale()).map_or(BigInt::zero(), |(quotient, _)| quotient)
+        } else {
+            self.value.clone()
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    fn product(&self, other: &Number) -> BigInt {
+        let product = self.value.clone() * other.value.clone();
+        if self.ty.is_fixed_point() { truncate(&product, &scale()) } else { product }
+    }
+
+    fn quotient(&self, other: &Number) -> Result<BigInt, NumberError> {
+        if other.is_zero() {
+            return Err(NumberError::DivisionByZero)
+        }
+        let dividend = if self.ty.is_fixed_point() { self.value.clone() * scale() } else { self.value.clone() };
+        Ok(truncate(&dividend, &other.value))
+    }
+
+    pub fn checked_add(&self, other: &Number) -> Result<Number, NumberError> {
+        Self::result(self.ty, self.value.clone() + other.value.clone())
+    }
+
+    pub fn checked_sub(&self, other: &Number) -> Result<Number, NumberError> {
+        Self::result(self.ty, self.value.clone() - other.value.clone())
+    }
+
+    pub fn checked_mul(&self, other: &Number) -> Result<Number, NumberError> {
+        Self::result(self.ty, self.product(other))
+    }
+
+    /// the quotient rounded towards zero
+    pub fn checked_div(&self, other: &Number) -> Result<Number, NumberError> {
+        Self::result(self.ty, self.quotient(other)?)
+    }
+
+    /// the remainder of the quotient rounded towards zero, with the sign of `self`
+    pub fn checked_rem(&self, other: &Number) -> Result<Number, NumberError> {
+        let (_, remainder) = self.value.div_rem(&other.value).ok_or(NumberError::DivisionByZero)?;
+        Self::result(self.ty, remainder)
+    }
+
+    pub fn checked_neg(&self) -> Result<Number, NumberError> {
+        Self::result(self.ty, -self.value.clone())
+    }
+
+    pub fn saturating_add(&self, other: &Number) -> Number {
+        Self::saturated(self.ty, self.value.clone() + other.value.clone())
+    }
+
+    pub fn saturating_sub(&self, other: &Number) -> Number {
+        Self::saturated(self.ty, self.value.clone() - other.value.clone())
+    }
+
+    pub fn saturating_mul(&self, other: &Number) -> Number {
+        Self::saturated(self.ty, self.product(other))
+    }
+
+    /// only the smallest value divided by -1 saturates, division by zero still fails
+    pub fn saturating_div(&self, other: &Number) -> Result<Number, NumberError> {
+        Ok(Self::saturated(self.ty, self.quotient(other)?))
+    }
+
+    pub fn bitwise_or(&self, other: &Number) -> Result<Number, NumberError> {
+        Self::result(self.ty, self.value.bitwise(&other.value, |a, b| a | b))
+    }
+
+    pub fn bitwise_xor(&self, other: &Number) -> Result<Number, NumberError> {
+        Self::result(self.ty, self.value.bitwise(&other.value, |a, b| a ^ b))
+    }
+
+    pub fn bitwise_and(&self, other: &Number) -> Result<Number, NumberError> {
+        Self::result(self.ty, self.value.bitwise(&other.value, |a, b| a & b))
+    }
+
+    /// bits shifted out of fixed size types are lost, also for signed ones
+    pub fn shift_left(&self, other: &Number) -> Result<Number, NumberError> {
+        let amount = shift_amount(other)?;
+        match bits(self.ty) {
+            Some(bits) => {
+                let shifted = self.value.shift_left(amount.min(bits + 1));
+                Ok(Number { ty: self.ty, value: shifted.wrap(bits, self.ty.is_signed()) })
+            },
+            // unbounded values would take all memory
+            None if amount > u16::MAX as usize => Err(NumberError::Overflow),
+            None => Self::result(self.ty, self.value.shift_left(amount)),
+        }
+    }
+
+    /// arithmetic shift, negative values round towards negative infinity
+    pub fn shift_right(&self, other: &Number) -> Result<Number, NumberError> {
+        let amount = shift_amount(other)?.min(self.value.bit_len() + 1);
+        Self::result(self.ty, self.value.shift_right(amount))
+    }
+
+    /// the value as another number type: fixed points are truncated towards zero when converted to integers,
+    /// `WordN` types wrap, the others must be in range
+    pub fn convert(&self, ty: NumberType) -> Result<Number, NumberError> {
+        let value = match (self.ty.is_fixed_point(), ty.is_fixed_point()) {
+            (true, false) => self.integer_part(),
+            (false, true) => self.value.clone() * scale(),
+            _ => self.value.clone(),
+        };
+        Self::result(ty, value)
+    }
+
+    /// the two's complement in the size of the type, for `Int` and `UInt` in as few bytes as hold the value
+    pub fn to_big_endian_bytes(&self) -> Vec<u8> {
+        match bits(self.ty) {
+            Some(bits) => self.value.wrap(bits, false).to_bytes(bits / 8),
+            None if self.value.is_negative() => {
+                let mut size = 1;
+                while self.value < -BigInt::power_of_two(size * 8 - 1) {
+                    size += 1;
+                }
+                self.value.wrap(size * 8, false).to_bytes(size)
+            },
+            // positive signed values keep a zero sign bit
+            None => {
+                let sign = usize::from(self.ty.is_signed());
+                self.value.to_bytes((self.value.bit_len() + sign).div_ceil(8).max(1))
+            },
+        }
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self.ty == other.ty).then(|| self.value.cmp(&other.value))
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.ty.is_fixed_point() {
+            return write!(f, "{}", self.value)
+        }
+        let sign = if self.value.is_negative() { "-" } else { "" };
+        let (integer, fraction) = self.value.abs().div_rem(&scale()).unwrap_or_default();
+        write!(f, "{}{}.{:08}", sign, integer, fraction.to_i128().unwrap_or(0))
+    }
+}
+
+fn scale() -> BigInt {
+    BigInt::from(FIXED_POINT_SCALE)
+}
+
+/// the quotient rounded towards zero, for a divisor that is not zero
+fn truncate(dividend: &BigInt, divisor: &BigInt) -> BigInt {
+    dividend.div_rem(divisor).map_or(BigInt::zero(), |(quotient, _)| quotient)
+}
+
+fn shift_amount(amount: &Number) -> Result<usize, NumberError> {
+    if amount.value.is_negative() {
+        return Err(NumberError::Invalid)
+    }
+    Ok(amount.value.to_i128().and_then(|amount| usize::try_from(amount).ok()).unwrap_or(usize::MAX))
+}
+
+fn is_word(ty: NumberType) -> bool {
+    matches!(ty, NumberType::Word8 | NumberType::Word16 | NumberType::Word32 | NumberType::Word64 | NumberType::Word128 | NumberType::Word256)
+}
+
+/// the size of fixed size types
+fn bits(ty: NumberType) -> Option<usize> {
+    match ty {
+        NumberType::Int8 | NumberType::UInt8 | NumberType::Word8 => Some(8),
+        NumberType::Int16 | NumberType::UInt16 | NumberType::Word16 => Some(16),
+        NumberType::Int32 | NumberType::UInt32 | NumberType::Word32 => Some(32),
+        NumberType::Int64 | NumberType::UInt64 | NumberType::Word64 | NumberType::Fix64 | NumberType::UFix64 => Some(64),
+        NumberType::Int128 | NumberType::UInt128 | NumberType::Word128 => Some(128),
+        NumberType::Int256 | NumberType::UInt256 | NumberType::Word256 => Some(256),
+        _ => None,
+    }
+}
+
+/// smallest and largest raw value of a type, `None` where unbounded
+fn range(ty: NumberType) -> (Option<BigInt>, Option<BigInt>) {
+    match bits(ty) {
+        Some(bits) if ty.is_signed() => {
+            let limit = BigInt::power_of_two(bits - 1);
+            (Some(-limit.clone()), Some(limit - BigInt::from(1i128)))
+        },
+        Some(bits) => (Some(BigInt::zero()), Some(BigInt::power_of_two(bits) - BigInt::from(1i128))),
+        None if ty == NumberType::UInt => (Some(BigInt::zero()), None),
+        None => (None, None),
+    }
+}
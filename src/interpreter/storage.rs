@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+
+use crate::checker::types::Type;
+
+use super::value::{
+    CapabilityTarget, Value,
+};
+
+/// the state kept between transactions: accounts with what they store, and the uuids given out
+#[derive(Debug, Default)]
+pub struct Ledger {
+    pub accounts: BTreeMap<u64, Account>,
+    /// the uuid of the last resource created
+    pub uuid: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct Account {
+    /// values by identifier of their `/storage` path
+    pub storage: BTreeMap<String, Value>,
+    /// links of public and private paths, by domain and identifier, before Cadence 1.0
+    pub links: BTreeMap<(String, String), Link>,
+    /// capabilities published by identifier of their `/public` path
+    pub published: BTreeMap<String, Value>,
+    /// by capability id
+    pub controllers: BTreeMap<u64, Controller>,
+    /// the id of the last capability issued
+    pub capability_id: u64,
+    /// by name
+    pub contracts: BTreeMap<String, Contract>,
+}
+
+/// `link<T>(path, target: target)`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Link {
+    pub domain: String,
+    pub identifier: String,
+    pub borrow_type: Type,
+}
+
+/// what a storage capability issued with `issue<T>(path)` borrows
+#[derive(Clone, Debug, PartialEq)]
+pub struct Controller {
+    /// identifier of the `/storage` path
+    pub target: String,
+    pub borrow_type: Type,
+    pub tag: String,
+}
+
+#[derive(Debug)]
+pub struct Contract {
+    pub source: String,
+    /// `None` while it is initialized, and for contract interfaces
+    pub value: Option<Value>,
+}
+
+impl Ledger {
+    /// the address of the account a contract is deployed to
+    pub fn contract_address(&self, name: &str) -> Option<u64> {
+        self.accounts.iter().find(|(_, account)| account.contracts.contains_key(name)).map(|(address, _)| *address)
+    }
+
+    /// the type id of a composite by qualified name, `A.<address>.<Contract>.<Name>` for the types of deployed contracts
+    pub fn type_id(&self, name: &str) -> String {
+        let contract = name.split('.').next().unwrap_or(name);
+        match self.contract_address(contract) {
+            Some(address) => format!("A.{:016x}.{}", address, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// the value of an initialized contract
+    pub fn contract(&self, name: &str) -> Option<&Value> {
+        self.accounts.values().find_map(|account| account.contracts.get(name)?.value.as_ref())
+    }
+
+    pub fn contract_mut(&mut self, name: &str) -> Option<&mut Contract> {
+        self.accounts.values_mut().find_map(|account| account.contracts.get_mut(name))
+    }
+
+    /// the stored value a capability borrows, following links, and the type it is borrowed as
+    pub fn capability_target(&self, address: u64, target: &CapabilityTarget) -> Option<(&Value, Option<&Type>)> {
+        let account = self.accounts.get(&address)?;
+        match target {
+            CapabilityTarget::Controller(id) => {
+                let controller = account.controllers.get(id)?;
+                Some((account.storage.get(&controller.target)?, Some(&controller.borrow_type)))
+            },
+            CapabilityTarget::Link { domain, identifier } => {
+                let mut path = (domain.clone(), identifier.clone());
+                // links can form cycles
+                for _ in 0..account.links.len() + 1 {
+                    if path.0 == "storage" {
+                        return Some((account.storage.get(&path.1)?, None))
+                    }
+                    let link = account.links.get(&path)?;
+                    path = (link.domain.clone(), link.identifier.clone());
+                }
+                None
+            },
+        }
+    }
+
+    /// a copy sharing no values with the ledger, to roll back to
+    pub fn snapshot(&self) -> Ledger {
+        let accounts = self.accounts.iter()
+            .map(|(address, account)| {
+                let duplicate = |values: &BTreeMap<String, Value>| {
+                    values.iter().map(|(key, value)| (key.clone(), value.duplicate())).collect()
+                };
+                let contracts = account.contracts.iter()
+                    .map(|(name, contract)| {
+                        let value = contract.value.as_ref().map(Value::duplicate);
+                        (name.clone(), Contract { source: contract.source.clone(), value })
+                    })
+                    .collect();
+                let account = Account {
+                    storage: duplicate(&account.storage),
+                    links: account.links.clone(),
+                    published: duplicate(&account.published),
+                    controllers: account.controllers.clone(),
+                    capability_id: account.capability_id,
+                    contracts,
+                };
+                (*address, account)
+            })
+            .collect();
+        Ledger { accounts, uuid: self.uuid }
+    }
+}
@@ -0,0 +1,381 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::CompositeKind;
+use crate::checker::types::{
+    NumberType, PathType, Type,
+};
+
+use super::number::Number;
+
+/// arrays, dictionaries and composites are shared by references to them, and copied when transferred with `=`
+pub type Shared<T> = Rc<RefCell<T>>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Void,
+    Nil,
+    Some(Box<Value>),
+    Bool(bool),
+    Character(String),
+    String(String),
+    Address(u64),
+    Number(Number),
+    /// `/storage/vault`
+    Path {
+        domain: String,
+        identifier: String,
+    },
+    Array(Shared<ArrayValue>),
+    Dictionary(Shared<DictionaryValue>),
+    Composite(Shared<CompositeValue>),
+    /// `&value`, the value is a shared one
+    Reference(Box<Value>),
+    Function(Function),
+    /// a run-time type, from `Type<T>()` or `getType()`, or a type used as value, e.g. `UInt8` in `UInt8(1)`
+    Type(Type),
+    /// an account, or one of the objects its functions are grouped in, e.g. `account.storage`
+    Account {
+        address: u64,
+        part: AccountPart,
+    },
+    /// a capability to borrow a value stored in an account
+    Capability {
+        address: u64,
+        target: CapabilityTarget,
+        /// the reference type it borrows as
+        borrow_type: Type,
+    },
+}
+
+/// what of an account a value stands for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountPart {
+    Account,
+    /// `account.storage`
+    Storage,
+    /// `account.capabilities`
+    Capabilities,
+    /// `account.capabilities.storage`
+    StorageCapabilities,
+    /// `account.contracts`
+    Contracts,
+    /// the controller of a storage capability, by capability id
+    Controller(u64),
+}
+
+impl AccountPart {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Account => "Account",
+            Self::Storage => "Account.Storage",
+            Self::Capabilities => "Account.Capabilities",
+            Self::StorageCapabilities => "Account.StorageCapabilities",
+            Self::Contracts => "Account.Contracts",
+            Self::Controller(_) => "StorageCapabilityController",
+        }
+    }
+}
+
+/// what a capability borrows
+#[derive(Clone, Debug, PartialEq)]
+pub enum CapabilityTarget {
+    /// a public or private path, which is linked to a storage path, before Cadence 1.0
+    Link {
+        domain: String,
+        identifier: String,
+    },
+    /// a storage capability controller by capability id, 0 for capabilities that borrow nothing
+    Controller(u64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArrayValue {
+    /// the static type of the elements
+    pub element: Type,
+    pub values: Vec<Value>,
+}
+
+/// entries in insertion order
+#[derive(Clone, Debug, PartialEq)]
+pub struct DictionaryValue {
+    pub key: Type,
+    pub value: Type,
+    pub entries: Vec<(Value, Value)>,
+}
+
+impl DictionaryValue {
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        self.entries.iter().find(|(k, _)| k.equals(key)).map(|(_, value)| value)
+    }
+
+    /// the previous value of the key
+    pub fn insert(&mut self, key: Value, value: Value) -> Option<Value> {
+        match self.entries.iter_mut().find(|(k, _)| k.equals(&key)) {
+            Some((_, old)) => Some(std::mem::replace(old, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            },
+        }
+    }
+
+    pub fn remove(&mut self, key: &Value) -> Option<Value> {
+        let index = self.entries.iter().position(|(k, _)| k.equals(key))?;
+        Some(self.entries.remove(index).1)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompositeValue {
+    /// qualified name, e.g. `Token.Vault`
+    pub name: String,
+    pub kind: CompositeKind,
+    /// in the order they were initialized
+    pub fields: Vec<(String, Value)>,
+    /// unique id of resources
+    pub uuid: Option<u64>,
+}
+
+impl CompositeValue {
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        self.fields.iter().find(|(field, _)| field == name).map(|(_, value)| value)
+    }
+
+    pub fn set_field(&mut self, name: &str, value: Value) {
+        match self.fields.iter_mut().find(|(field, _)| field == name) {
+            Some((_, old)) => *old = value,
+            None => self.fields.push((name.to_string(), value)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Function {
+    /// a declared function by qualified name, e.g. `Token.Vault.deposit`, and the composite it is called on
+    Declared {
+        name: String,
+        receiver: Option<Box<Value>>,
+    },
+    /// a function of a built-in type, e.g. `append` of an array
+    Member {
+        name: String,
+        receiver: Box<Value>,
+    },
+    /// a global built-in function, e.g. `panic`, or a built-in type used as conversion, e.g. `UInt8`
+    BuiltIn(String),
+    /// a declared composite by qualified name, which constructs it, or for enums, has the cases as members
+    Constructor(String),
+}
+
+impl Value {
+    pub fn shared<T>(value: T) -> Shared<T> {
+        Rc::new(RefCell::new(value))
+    }
+
+    pub fn some(value: Value) -> Value {
+        Value::Some(Box::new(value))
+    }
+
+    pub fn string(value: &str) -> Value {
+        Value::String(value.to_string())
+    }
+
+    pub fn int(value: i128) -> Value {
+        Value::Number(Number::integer(NumberType::Int, value).unwrap_or_else(|_| unreachable!()))
+    }
+
+    pub fn uint64(value: u64) -> Value {
+        Value::Number(Number::integer(NumberType::UInt64, value as i128).unwrap_or_else(|_| unreachable!()))
+    }
+
+    pub fn array(element: Type, values: Vec<Value>) -> Value {
+        Value::Array(Value::shared(ArrayValue { element, values }))
+    }
+
+    /// the value of a present optional, `None` for `nil`.
+    /// values are not wrapped where they are implicitly converted to optionals, so those are present too
+    pub fn unwrap_optional(self) -> Option<Value> {
+        match self {
+            Value::Nil => None,
+            Value::Some(value) => Some(*value),
+            value => Some(value),
+        }
+    }
+
+    /// the value the reference refers to, or the value itself
+    pub fn dereference(&self) -> &Value {
+        match self {
+            Value::Reference(value) => value.dereference(),
+            value => value,
+        }
+    }
+
+    /// the value to store when transferred with `=`: structures and containers are copied,
+    /// resources are moved, so they stay shared
+    pub fn copy(&self) -> Value {
+        match self {
+            Value::Some(value) => Value::some(value.copy()),
+            Value::Array(array) => {
+                let array = array.borrow();
+                Value::array(array.element.clone(), array.values.iter().map(Value::copy).collect())
+            },
+            Value::Dictionary(dictionary) => {
+                let dictionary = dictionary.borrow();
+                Value::Dictionary(Value::shared(DictionaryValue {
+                    key: dictionary.key.clone(),
+                    value: dictionary.value.clone(),
+                    entries: dictionary.entries.iter().map(|(key, value)| (key.copy(), value.copy())).collect(),
+                }))
+            },
+            Value::Composite(composite) if composite.borrow().kind == CompositeKind::Structure => {
+                let composite = composite.borrow();
+                Value::Composite(Value::shared(CompositeValue {
+                    name: composite.name.clone(),
+                    kind: composite.kind,
+                    fields: composite.fields.iter().map(|(name, value)| (name.clone(), value.copy())).collect(),
+                    uuid: composite.uuid,
+                }))
+            },
+            value => value.clone(),
+        }
+    }
+
+    /// a copy that shares nothing with the value, resources included, to restore state from
+    pub fn duplicate(&self) -> Value {
+        match self {
+            Value::Some(value) => Value::some(value.duplicate()),
+            Value::Reference(value) => Value::Reference(Box::new(value.duplicate())),
+            Value::Array(array) => {
+                let array = array.borrow();
+                Value::array(array.element.clone(), array.values.iter().map(Value::duplicate).collect())
+            },
+            Value::Dictionary(dictionary) => {
+                let dictionary = dictionary.borrow();
+                Value::Dictionary(Value::shared(DictionaryValue {
+                    key: dictionary.key.clone(),
+                    value: dictionary.value.clone(),
+                    entries: dictionary.entries.iter().map(|(key, value)| (key.duplicate(), value.duplicate())).collect(),
+                }))
+            },
+            Value::Composite(composite) => {
+                let composite = composite.borrow();
+                Value::Composite(Value::shared(CompositeValue {
+                    name: composite.name.clone(),
+                    kind: composite.kind,
+                    fields: composite.fields.iter().map(|(name, value)| (name.clone(), value.duplicate())).collect(),
+                    uuid: composite.uuid,
+                }))
+            },
+            value => value.clone(),
+        }
+    }
+
+    /// `==` of equatable values, enum cases compare by raw value
+    pub fn equals(&self, other: &Value) -> bool {
+        match (self.dereference(), other.dereference()) {
+            (Value::Some(a), Value::Some(b)) => a.equals(b),
+            // values of optional type are not always wrapped
+            (Value::Some(a), b) | (b, Value::Some(a)) if *b != Value::Nil => a.equals(b),
+            (Value::Array(a), Value::Array(b)) => {
+                let (a, b) = (a.borrow(), b.borrow());
+                a.values.len() == b.values.len() && a.values.iter().zip(b.values.iter()).all(|(a, b)| a.equals(b))
+            },
+            (Value::Dictionary(a), Value::Dictionary(b)) => {
+                let (a, b) = (a.borrow(), b.borrow());
+                a.entries.len() == b.entries.len() &&
+                    a.entries.iter().all(|(key, value)| b.get(key).is_some_and(|other| value.equals(other)))
+            },
+            (Value::Composite(a), Value::Composite(b)) => {
+                let (a, b) = (a.borrow(), b.borrow());
+                a.kind == CompositeKind::Enum && a.name == b.name && a.field("rawValue") == b.field("rawValue")
+            },
+            (a, b) => a == b,
+        }
+    }
+
+    /// the run-time type, containers have the static type of their elements
+    pub fn dynamic_type(&self) -> Type {
+        match self {
+            Value::Void => Type::Void,
+            Value::Nil => Type::nil(),
+            Value::Some(value) => Type::optional(value.dynamic_type()),
+            Value::Bool(_) => Type::Bool,
+            Value::Character(_) => Type::Character,
+            Value::String(_) => Type::String,
+            Value::Address(_) => Type::Address,
+            Value::Number(number) => Type::Number(number.ty()),
+            Value::Path { domain, .. } => Type::Path(match domain.as_str() {
+                "storage" => PathType::StoragePath,
+                "public" => PathType::PublicPath,
+                _ => PathType::PrivatePath,
+            }),
+            Value::Array(array) => Type::VariableSized(Box::new(array.borrow().element.clone())),
+            Value::Dictionary(dictionary) => {
+                let dictionary = dictionary.borrow();
+                Type::Dictionary(Box::new(dictionary.key.clone()), Box::new(dictionary.value.clone()))
+            },
+            Value::Composite(composite) => Type::Composite(composite.borrow().name.clone()),
+            Value::Reference(value) => Type::reference(value.dynamic_type()),
+            Value::Function(_) => Type::Other("Function".to_string()),
+            Value::Type(_) => Type::MetaType,
+            Value::Account { part, .. } => Type::Other(part.as_str().to_string()),
+            Value::Capability { borrow_type, .. } => Type::Capability(Some(Box::new(borrow_type.clone()))),
+        }
+    }
+}
+
+/// the value as `log` prints it
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Void => write!(f, "()"),
+            Value::Nil => write!(f, "nil"),
+            Value::Some(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Character(value) | Value::String(value) => write!(f, "{:?}", value),
+            Value::Address(address) => write!(f, "0x{:016x}", address),
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Path { domain, identifier } => write!(f, "/{}/{}", domain, identifier),
+            Value::Array(array) => {
+                write!(f, "[")?;
+                for (index, value) in array.borrow().values.iter().enumerate() {
+                    write!(f, "{}{}", if index > 0 { ", " } else { "" }, value)?;
+                }
+                write!(f, "]")
+            },
+            Value::Dictionary(dictionary) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in dictionary.borrow().entries.iter().enumerate() {
+                    write!(f, "{}{}: {}", if index > 0 { ", " } else { "" }, key, value)?;
+                }
+                write!(f, "}}")
+            },
+            Value::Composite(composite) => {
+                let composite = composite.borrow();
+                write!(f, "{}(", composite.name)?;
+                let uuid = composite.uuid.map(|uuid| ("uuid".to_string(), Value::uint64(uuid)));
+                for (index, (name, value)) in uuid.iter().chain(composite.fields.iter()).enumerate() {
+                    write!(f, "{}{}: {}", if index > 0 { ", " } else { "" }, name, value)?;
+                }
+                write!(f, ")")
+            },
+            Value::Reference(value) => write!(f, "{}", value),
+            Value::Function(
+                Function::Declared { name, .. } | Function::Member { name, .. } | Function::BuiltIn(name) | Function::Constructor(name),
+            ) => write!(f, "fun {}", name),
+            Value::Type(ty) => write!(f, "Type<{}>()", ty),
+            Value::Account { address, part: AccountPart::Controller(id) } => {
+                write!(f, "StorageCapabilityController(address: 0x{:016x}, capabilityID: {})", address, id)
+            },
+            Value::Account { address, part } => write!(f, "{}(address: 0x{:016x})", part.as_str(), address),
+            Value::Capability { address, target: CapabilityTarget::Link { domain, identifier }, borrow_type } => {
+                write!(f, "Capability<{}>(address: 0x{:016x}, path: /{}/{})", borrow_type, address, domain, identifier)
+            },
+            Value::Capability { address, target: CapabilityTarget::Controller(id), borrow_type } => {
+                write!(f, "Capability<{}>(address: 0x{:016x}, id: {})", borrow_type, address, id)
+            },
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+
+use crate::ast::CompositeKind;
+use crate::checker::types::NumberType;
+use crate::interpreter::bigint::BigInt;
+use crate::interpreter::number::{
+    Number, NumberError,
+};
+
+use super::json::Json;
+use super::json_cadence::{
+    Composite, CompositeType, DecodeError, Parameter, Type, Value, decode, encode,
+};
+
+/// `#[derive(ToCadence, FromCadence)]` for structs with named fields and enums without fields.
+/// `#[cadence(type_id = "A.0000000000000001.Shapes.Shape")]` sets the type id, the name of the Rust type by default,
+/// `#[cadence(kind = "resource")]` or `"event"` the kind of structs, `#[cadence(raw_type = "UInt64")]` the raw type
+/// of enums, `UInt8` by default, and `#[cadence(rename = "sideCount")]` the name of a field
+pub use cadence_derive::{
+    FromCadence, ToCadence,
+};
+
+/// a Rust value that is a Cadence value, which arguments of transactions and scripts are built from
+pub trait ToCadence {
+    fn to_cadence(&self) -> Value;
+
+    /// the static type of the values, which CCF encodes them by
+    fn cadence_type() -> Type where Self: Sized;
+}
+
+/// a Rust value read from a Cadence value of its type, results of scripts and fields of events
+pub trait FromCadence: Sized {
+    fn from_cadence(value: &Value) -> Result<Self, DecodeError>;
+}
+
+/// a value as compact JSON-Cadence
+pub fn to_json_cadence<T: ToCadence>(value: &T) -> String {
+    encode(&value.to_cadence())
+}
+
+pub fn from_json_cadence<T: FromCadence>(text: &str) -> Result<T, DecodeError> {
+    T::from_cadence(&decode(text)?)
+}
+
+/// what sending a transaction takes besides its code: each argument as JSON-Cadence,
+/// and the accounts that sign it, one for each parameter of `prepare`
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransactionPayload {
+    pub arguments: Vec<String>,
+    pub authorizers: Vec<Address>,
+}
+
+/// `error` of the element or field at `path`
+fn inside(mut error: DecodeError, path: &str) -> DecodeError {
+    error.path = match error.path.chars().next() {
+        None => path.to_string(),
+        Some('[') => format!("{}{}", path, error.path),
+        Some(_) => format!("{}.{}", path, error.path),
+    };
+    error
+}
+
+/// the type of a value as JSON-Cadence names it, for messages
+fn type_name(value: &Value) -> String {
+    match value.to_json().get("type") {
+        Some(Json::String(ty)) => ty.clone(),
+        _ => "?".to_string(),
+    }
+}
+
+fn mismatch(expected: &str, value: &Value) -> DecodeError {
+    DecodeError::new(&format!("expected `{}`, got `{}`", expected, type_name(value)), "")
+}
+
+/// the number of a value of type `ty`
+fn number(value: &Value, ty: NumberType) -> Result<&Number, DecodeError> {
+    match value {
+        Value::Number(number) if number.ty() == ty => Ok(number),
+        value => Err(mismatch(ty.name(), value)),
+    }
+}
+
+/// the value of a struct, resource or event
+pub fn composite_value(kind: CompositeKind, type_id: &str, fields: Vec<(&str, Value)>) -> Value {
+    let composite = Composite::new(type_id, fields);
+    match kind {
+        CompositeKind::Resource => Value::Resource(composite),
+        CompositeKind::Event => Value::Event(composite),
+        CompositeKind::Contract => Value::Contract(composite),
+        CompositeKind::Enum => Value::Enum(composite),
+        CompositeKind::Structure => Value::Struct(composite),
+    }
+}
+
+/// the type of a struct, resource or event, initialized with its fields as the arguments of the same labels
+pub fn composite_type(kind: CompositeKind, type_id: &str, fields: Vec<(&str, Type)>) -> Type {
+    let initializer = fields.iter()
+        .map(|(name, ty)| Parameter { label: name.to_string(), identifier: name.to_string(), ty: ty.clone() })
+        .collect();
+    Type::Composite(CompositeType {
+        kind,
+        is_interface: false,
+        type_id: type_id.to_string(),
+        fields: fields.into_iter().map(|(name, ty)| (name.to_string(), ty)).collect(),
+        initializers: if kind == CompositeKind::Event { Vec::new() } else { vec![initializer] },
+        raw_type: None,
+    })
+}
+
+/// the composite of a value of `kind` with the type id, whose fields derived `FromCadence` reads
+pub fn composite<'v>(value: &'v Value, kind: CompositeKind, type_id: &str) -> Result<&'v Composite, DecodeError> {
+    let composite = match (kind, value) {
+        (CompositeKind::Structure, Value::Struct(composite)) |
+        (CompositeKind::Resource, Value::Resource(composite)) |
+        (CompositeKind::Event, Value::Event(composite)) |
+        (CompositeKind::Contract, Value::Contract(composite)) |
+        (CompositeKind::Enum, Value::Enum(composite)) => composite,
+        (kind, value) => {
+            let expected = match kind {
+                CompositeKind::Structure => "Struct",
+                CompositeKind::Resource => "Resource",
+                CompositeKind::Event => "Event",
+                CompositeKind::Contract => "Contract",
+                CompositeKind::Enum => "Enum",
+            };
+            return Err(mismatch(expected, value))
+        },
+    };
+    if composite.type_id != type_id {
+        return Err(DecodeError::new(&format!("expected `{}`, got `{}`", type_id, composite.type_id), ""))
+    }
+    Ok(composite)
+}
+
+/// a field of a composite
+pub fn field<T: FromCadence>(composite: &Composite, name: &str) -> Result<T, DecodeError> {
+    let value = composite.field(name)
+        .ok_or_else(|| DecodeError::new(&format!("`{}` has no field `{}`", composite.type_id, name), ""))?;
+    T::from_cadence(value).map_err(|error| inside(error, name))
+}
+
+/// the case of an enum, which is its raw value
+pub fn enum_value(type_id: &str, raw_type: NumberType, case: usize) -> Value {
+    let raw_value = Number::integer(raw_type, case as i128).expect("an enum has more cases than its raw type holds");
+    Value::Enum(Composite::new(type_id, vec![("rawValue", Value::Number(raw_value))]))
+}
+
+pub fn enum_type(type_id: &str, raw_type: NumberType) -> Type {
+    let raw_type = Type::simple(raw_type.name());
+    Type::Composite(CompositeType {
+        kind: CompositeKind::Enum,
+        is_interface: false,
+        type_id: type_id.to_string(),
+        fields: vec![("rawValue".to_string(), raw_type.clone())],
+        initializers: Vec::new(),
+        raw_type: Some(Box::new(raw_type)),
+    })
+}
+
+/// the case of an enum value with one of `cases`
+pub fn enum_case(value: &Value, type_id: &str, raw_type: NumberType, cases: usize) -> Result<usize, DecodeError> {
+    let composite = composite(value, CompositeKind::Enum, type_id)?;
+    let raw_value = field::<Value>(composite, "rawValue")?;
+    let raw_value = number(&raw_value, raw_type).map_err(|error| inside(error, "rawValue"))?;
+    raw_value.to_i128().and_then(|case| usize::try_from(case).ok()).filter(|case| *case < cases)
+        .ok_or_else(|| DecodeError::new(&format!("`{}` has no case with the raw value {}", type_id, raw_value), "rawValue"))
+}
+
+impl ToCadence for Value {
+    fn to_cadence(&self) -> Value {
+        self.clone()
+    }
+
+    /// values are of any type
+    fn cadence_type() -> Type {
+        Type::simple("AnyStruct")
+    }
+}
+
+impl FromCadence for Value {
+    fn from_cadence(value: &Value) -> Result<Self, DecodeError> {
+        Ok(value.clone())
+    }
+}
+
+macro_rules! integer {
+    ($($rust:ty => $cadence:ident as $wide:ty),* $(,)?) => {$(
+        impl ToCadence for $rust {
+            fn to_cadence(&self) -> Value {
+                // the Cadence type holds every value of the Rust type
+                Value::Number(Number::new(NumberType::$cadence, BigInt::from(<$wide>::from(*self))).unwrap())
+            }
+
+            fn cadence_type() -> Type {
+                Type::simple(NumberType::$cadence.name())
+            }
+        }
+
+        impl FromCadence for $rust {
+            fn from_cadence(value: &Value) -> Result<Self, DecodeError> {
+                // and the Rust type every value of the Cadence type
+                Ok(number(value, NumberType::$cadence)?.to_string().parse().unwrap())
+            }
+        }
+    )*};
+}
+
+integer!(
+    u8 => UInt8 as u128, u16 => UInt16 as u128, u32 => UInt32 as u128, u64 => UInt64 as u128, u128 => UInt128 as u128,
+    i8 => Int8 as i128, i16 => Int16 as i128, i32 => Int32 as i128, i64 => Int64 as i128, i128 => Int128 as i128,
+);
+
+macro_rules! checked_number {
+    ($($name:ident),* $(,)?) => {$(
+        #[doc = concat!("a `", stringify!($name), "`, which no Rust integer holds the values of")]
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct $name(Number);
+
+        impl $name {
+            /// `number` if it is of this type
+            pub fn new(number: Number) -> Option<Self> {
+                (number.ty() == NumberType::$name).then_some(Self(number))
+            }
+
+            pub fn number(&self) -> &Number {
+                &self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = NumberError;
+
+            /// a literal as written in Cadence, e.g. `-1.5`, if it is in range
+            fn from_str(text: &str) -> Result<Self, NumberError> {
+                let (negative, magnitude) = text.strip_prefix('-').map_or((false, text), |magnitude| (true, magnitude));
+                Number::parse(NumberType::$name, magnitude, negative).map(Self)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl ToCadence for $name {
+            fn to_cadence(&self) -> Value {
+                Value::Number(self.0.clone())
+            }
+
+            fn cadence_type() -> Type {
+                Type::simple(NumberType::$name.name())
+            }
+        }
+
+        impl FromCadence for $name {
+            fn from_cadence(value: &Value) -> Result<Self, DecodeError> {
+                number(value, NumberType::$name).cloned().map(Self)
+            }
+        }
+    )*};
+}
+
+checked_number!(
+    Int, Int256, UInt, UInt256, Word8, Word16, Word32, Word64, Word128, Word256, Fix64, UFix64,
+);
+
+impl ToCadence for bool {
+    fn to_cadence(&self) -> Value {
+        Value::Bool(*self)
+    }
+
+    fn cadence_type() -> Type {
+        Type::simple("Bool")
+    }
+}
+
+impl FromCadence for bool {
+    fn from_cadence(value: &Value) -> Result<Self, DecodeError> {
+        match value {
+            Value::Bool(value) => Ok(*value),
+            value => Err(mismatch("Bool", value)),
+        }
+    }
+}
+
+impl ToCadence for String {
+    fn to_cadence(&self) -> Value {
+        Value::String(self.clone())
+    }
+
+    fn cadence_type() -> Type {
+        Type::simple("String")
+    }
+}
+
+impl FromCadence for String {
+    fn from_cadence(value: &Value) -> Result<Self, DecodeError> {
+        match value {
+            Value::String(value) => Ok(value.clone()),
+            value => Err(mismatch("String", value)),
+        }
+    }
+}
+
+/// an account address, `Address(0x01)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Address(pub u64);
+
+impl ToCadence for Address {
+    fn to_cadence(&self) -> Value {
+        Value::Address(self.0)
+    }
+
+    fn cadence_type() -> Type {
+        Type::simple("Address")
+    }
+}
+
+impl FromCadence for Address {
+    fn from_cadence(value: &Value) -> Result<Self, DecodeError> {
+        match value {
+            Value::Address(address) => Ok(Address(*address)),
+            value => Err(mismatch("Address", value)),
+        }
+    }
+}
+
+impl<T: ToCadence> ToCadence for Vec<T> {
+    fn to_cadence(&self) -> Value {
+        Value::Array(self.iter().map(T::to_cadence).collect())
+    }
+
+    fn cadence_type() -> Type {
+        Type::VariableSizedArray(Box::new(T::cadence_type()))
+    }
+}
+
+impl<T: FromCadence> FromCadence for Vec<T> {
+    fn from_cadence(value: &Value) -> Result<Self, DecodeError> {
+        let Value::Array(values) = value else {
+            return Err(mismatch("Array", value))
+        };
+        values.iter().enumerate()
+            .map(|(index, value)| T::from_cadence(value).map_err(|error| inside(error, &format!("[{}]", index))))
+            .collect()
+    }
+}
+
+impl<K: ToCadence, V: ToCadence> ToCadence for HashMap<K, V> {
+    /// entries ordered by their keys as JSON-Cadence, for the same arguments every time
+    fn to_cadence(&self) -> Value {
+        let mut entries: Vec<(Value, Value)> = self.iter().map(|(key, value)| (key.to_cadence(), value.to_cadence())).collect();
+        entries.sort_by_cached_key(|(key, _)| encode(key));
+        Value::Dictionary(entries)
+    }
+
+    fn cadence_type() -> Type {
+        Type::Dictionary(Box::new(K::cadence_type()), Box::new(V::cadence_type()))
+    }
+}
+
+impl<K: FromCadence + Eq + Hash, V: FromCadence> FromCadence for HashMap<K, V> {
+    fn from_cadence(value: &Value) -> Result<Self, DecodeError> {
+        let Value::Dictionary(entries) = value else {
+            return Err(mismatch("Dictionary", value))
+        };
+        let mut map = HashMap::new();
+        for (index, (key, value)) in entries.iter().enumerate() {
+            let path = format!("[{}]", index);
+            let key = K::from_cadence(key).map_err(|error| inside(error, &format!("{}.key", path)))?;
+            let value = V::from_cadence(value).map_err(|error| inside(error, &format!("{}.value", path)))?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+impl<T: ToCadence> ToCadence for Option<T> {
+    fn to_cadence(&self) -> Value {
+        Value::Optional(self.as_ref().map(|value| Box::new(value.to_cadence())))
+    }
+
+    fn cadence_type() -> Type {
+        Type::Optional(Box::new(T::cadence_type()))
+    }
+}
+
+impl<T: FromCadence> FromCadence for Option<T> {
+    fn from_cadence(value: &Value) -> Result<Self, DecodeError> {
+        match value {
+            Value::Optional(None) => Ok(None),
+            Value::Optional(Some(value)) => T::from_cadence(value).map(Some),
+            value => Err(mismatch("Optional", value)),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use crate::ast::CompositeKind;
+use crate::checker::types::{
+    self, MemberKind, NumberType, Types,
+};
+use crate::interpreter::number::Number;
+use crate::interpreter::storage::Ledger;
+use crate::interpreter::value::{
+    self as interpreter, CapabilityTarget,
+};
+
+use super::json_cadence::{
+    Authorization, Composite, CompositeType, FunctionType, Parameter, Type, Value,
+};
+
+/// the value of a program as it leaves it, with the types the program declares and the type ids of the contracts
+/// deployed to the ledger. `None` for what cannot leave a program: accounts and functions
+pub fn export(value: &interpreter::Value, types: &Types, ledger: &Ledger) -> Option<Value> {
+    let export = |value: &interpreter::Value| export(value, types, ledger);
+    Some(match value {
+        interpreter::Value::Void => Value::Void,
+        interpreter::Value::Nil => Value::Optional(None),
+        interpreter::Value::Some(value) => Value::Optional(Some(Box::new(export(value)?))),
+        interpreter::Value::Bool(value) => Value::Bool(*value),
+        interpreter::Value::Character(value) => Value::Character(value.clone()),
+        interpreter::Value::String(value) => Value::String(value.clone()),
+        interpreter::Value::Address(address) => Value::Address(*address),
+        interpreter::Value::Number(number) => Value::Number(number.clone()),
+        interpreter::Value::Path { domain, identifier } => Value::Path { domain: domain.clone(), identifier: identifier.clone() },
+        interpreter::Value::Array(array) => Value::Array(array.borrow().values.iter().map(export).collect::<Option<_>>()?),
+        interpreter::Value::Dictionary(dictionary) => {
+            let entries = dictionary.borrow().entries.iter()
+                .map(|(key, value)| Some((export(key)?, export(value)?)))
+                .collect::<Option<_>>()?;
+            Value::Dictionary(entries)
+        },
+        interpreter::Value::Composite(composite) => {
+            let composite = composite.borrow();
+            let mut fields = Vec::new();
+            if let Some(uuid) = composite.uuid {
+                fields.push(("uuid".to_string(), Value::Number(Number::integer(NumberType::UInt64, uuid as i128).ok()?)));
+            }
+            for (name, value) in composite.fields.iter() {
+                fields.push((name.clone(), export(value)?));
+            }
+            let exported = Composite { type_id: ledger.type_id(&composite.name), fields };
+            match composite.kind {
+                CompositeKind::Structure => Value::Struct(exported),
+                CompositeKind::Resource => Value::Resource(exported),
+                CompositeKind::Contract => Value::Contract(exported),
+                CompositeKind::Event => Value::Event(exported),
+                CompositeKind::Enum => Value::Enum(exported),
+            }
+        },
+        interpreter::Value::Reference(value) => export(value)?,
+        interpreter::Value::Type(ty) => Value::Type(export_type(ty, types, ledger)),
+        interpreter::Value::Capability { address, target, borrow_type } => Value::Capability {
+            id: match target {
+                CapabilityTarget::Controller(id) => *id,
+                CapabilityTarget::Link { .. } => 0,
+            },
+            address: *address,
+            borrow_type: export_type(borrow_type, types, ledger),
+        },
+        interpreter::Value::Function(_) | interpreter::Value::Account { .. } => return None,
+    })
+}
+
+/// a static type of the checker, composites with their fields and initializers
+pub fn export_type(ty: &types::Type, types: &Types, ledger: &Ledger) -> Type {
+    TypeExporter { types, ledger, exporting: Vec::new() }.export(ty)
+}
+
+struct TypeExporter<'t> {
+    types: &'t Types,
+    ledger: &'t Ledger,
+    /// composites whose fields are exported, which are only referred to inside them
+    exporting: Vec<String>,
+}
+
+impl TypeExporter<'_> {
+    fn export(&mut self, ty: &types::Type) -> Type {
+        match ty {
+            // neither is left in a checked program that runs
+            types::Type::Invalid | types::Type::TypeParameter(_) => Type::simple("Never"),
+            types::Type::MetaType => Type::simple("Type"),
+            types::Type::Optional(ty) => Type::Optional(Box::new(self.export(ty))),
+            types::Type::VariableSized(ty) => Type::VariableSizedArray(Box::new(self.export(ty))),
+            types::Type::ConstantSized(ty, size) => Type::ConstantSizedArray(Box::new(self.export(ty)), *size),
+            types::Type::Dictionary(key, value) => Type::Dictionary(Box::new(self.export(key)), Box::new(self.export(value))),
+            types::Type::Function(function) => Type::Function(self.function(function)),
+            types::Type::Reference { authorization, ty } => {
+                let type_ids = |entitlements: &[String]| entitlements.iter().map(|name| self.ledger.type_id(name)).collect();
+                let authorization = match authorization {
+                    // `auth` from before entitlements has no counterpart in the format
+                    types::Authorization::Unauthorized | types::Authorization::Auth => Authorization::Unauthorized,
+                    types::Authorization::Conjunction(entitlements) => Authorization::Conjunction(type_ids(entitlements)),
+                    types::Authorization::Disjunction(entitlements) => Authorization::Disjunction(type_ids(entitlements)),
+                    types::Authorization::Mapping(mapping) => Authorization::Mapping(self.ledger.type_id(mapping)),
+                };
+                Type::Reference { authorization, ty: Box::new(self.export(ty)) }
+            },
+            types::Type::Restricted { restrictions, .. } => {
+                let types: Vec<Type> = restrictions.iter().map(|name| self.composite(name, false)).collect();
+                let type_ids: Vec<String> = restrictions.iter().map(|name| self.ledger.type_id(name)).collect();
+                Type::Intersection { type_id: format!("{{{}}}", type_ids.join(",")), types }
+            },
+            types::Type::Composite(name) | types::Type::Interface(name) => self.composite(name, false),
+            types::Type::Imported { name, is_resource } => self.composite(name, *is_resource),
+            types::Type::Capability(ty) => Type::Capability(ty.as_ref().map(|ty| Box::new(self.export(ty)))),
+            ty => Type::Simple(ty.to_string()),
+        }
+    }
+
+    /// a declared composite or interface, types the program does not declare without fields
+    fn composite(&mut self, name: &str, is_resource: bool) -> Type {
+        let type_id = self.ledger.type_id(name);
+        let Some(declared) = self.types.composite(name) else {
+            let kind = if is_resource { CompositeKind::Resource } else { CompositeKind::Structure };
+            return Type::Composite(CompositeType { kind, is_interface: false, type_id, fields: Vec::new(), initializers: Vec::new(), raw_type: None })
+        };
+        let mut composite = CompositeType {
+            kind: declared.kind,
+            is_interface: declared.is_interface,
+            type_id,
+            fields: Vec::new(),
+            initializers: Vec::new(),
+            raw_type: None,
+        };
+        // encoded as the type id inside its own fields
+        if self.exporting.iter().any(|exporting| exporting == name) {
+            return Type::Composite(composite)
+        }
+        self.exporting.push(name.to_string());
+        for member in declared.members.iter() {
+            if let MemberKind::Field(_) = member.kind {
+                composite.fields.push((member.name.clone(), self.export(&member.ty)));
+            }
+        }
+        composite.raw_type = declared.raw_type.as_ref().map(|ty| Box::new(self.export(ty)));
+        // enums are created from their raw values, by their cases
+        if let Some(raw_type) = &composite.raw_type {
+            composite.fields.push(("rawValue".to_string(), (**raw_type).clone()));
+        } else if !declared.is_interface && declared.kind != CompositeKind::Contract {
+            let initializer = self.parameters(&declared.initializer);
+            composite.initializers.push(initializer);
+        }
+        self.exporting.pop();
+        Type::Composite(composite)
+    }
+
+    fn function(&mut self, function: &types::FunctionType) -> FunctionType {
+        FunctionType {
+            is_view: false,
+            type_parameters: function.type_parameters.iter().enumerate()
+                .map(|(index, bound)| (format!("T{}", index), Some(self.export(bound))))
+                .collect(),
+            parameters: self.parameters(&function.parameters),
+            return_type: Box::new(self.export(&function.return_type)),
+        }
+    }
+
+    fn parameters(&mut self, parameters: &[types::FunctionParameter]) -> Vec<Parameter> {
+        parameters.iter()
+            .map(|parameter| {
+                let label = parameter.label.clone().unwrap_or_else(|| "_".to_string());
+                // the checker keeps the labels arguments need, not the names of the parameters
+                let identifier = parameter.label.clone().unwrap_or_default();
+                Parameter { label, identifier, ty: self.export(&parameter.ty) }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,487 @@
+use std::fmt;
+
+use serde::de::{
+    self, MapAccess, SeqAccess, Visitor,
+};
+use serde::ser::{
+    self, SerializeMap, SerializeSeq,
+};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::ast::Span;
+
+/// objects and arrays nested deeper than this do not parse, instead of overflowing the stack
+const MAX_DEPTH: usize = 256;
+
+/// a JSON document
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    /// as written, numbers of JSON-Cadence are strings, these are only read
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    /// members in the order they are written
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object(members: Vec<(&str, Json)>) -> Self {
+        Json::Object(members.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    pub fn string(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+
+    /// the member of an object
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(member, _)| member == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// what kind of JSON value it is, for messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Json::Null => "null",
+            Json::Bool(_) => "a boolean",
+            Json::Number(_) => "a number",
+            Json::String(_) => "a string",
+            Json::Array(_) => "an array",
+            Json::Object(_) => "an object",
+        }
+    }
+
+    /// indented by two spaces, one member or element per line
+    pub fn pretty(&self) -> String {
+        let mut text = String::new();
+        self.write_pretty(&mut text, 0);
+        text
+    }
+
+    fn write_pretty(&self, text: &mut String, depth: usize) {
+        let indent = "  ".repeat(depth + 1);
+        match self {
+            Json::Array(values) if !values.is_empty() => {
+                text.push('[');
+                for (index, value) in values.iter().enumerate() {
+                    text.push_str(if index > 0 { ",\n" } else { "\n" });
+                    text.push_str(&indent);
+                    value.write_pretty(text, depth + 1);
+                }
+                text.push_str(&format!("\n{}]", "  ".repeat(depth)));
+            },
+            Json::Object(members) if !members.is_empty() => {
+                text.push('{');
+                for (index, (key, value)) in members.iter().enumerate() {
+                    text.push_str(if index > 0 { ",\n" } else { "\n" });
+                    text.push_str(&format!("{}{}: ", indent, quote(key)));
+                    value.write_pretty(text, depth + 1);
+                }
+                text.push_str(&format!("\n{}}}", "  ".repeat(depth)));
+            },
+            json => text.push_str(&json.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Json {
+    /// compact, without whitespace
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(number) => write!(f, "{}", number),
+            Json::String(value) => write!(f, "{}", quote(value)),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            },
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{}", quote(key), value)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+impl Serialize for Json {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Json::Null => serializer.serialize_unit(),
+            Json::Bool(value) => serializer.serialize_bool(*value),
+            Json::Number(number) => {
+                if let Ok(number) = number.parse::<u64>() {
+                    serializer.serialize_u64(number)
+                } else if let Ok(number) = number.parse::<i64>() {
+                    serializer.serialize_i64(number)
+                } else if let Ok(number) = number.parse::<f64>() {
+                    serializer.serialize_f64(number)
+                } else {
+                    Err(ser::Error::custom(format!("`{}` is not a number", number)))
+                }
+            },
+            Json::String(value) => serializer.serialize_str(value),
+            Json::Array(values) => {
+                let mut sequence = serializer.serialize_seq(Some(values.len()))?;
+                for value in values.iter() {
+                    sequence.serialize_element(value)?;
+                }
+                sequence.end()
+            },
+            Json::Object(members) => {
+                let mut map = serializer.serialize_map(Some(members.len()))?;
+                for (key, value) in members.iter() {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            },
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Json {
+    /// objects with the same key twice are rejected, as by `parse_json`
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonVisitor)
+    }
+}
+
+struct JsonVisitor;
+
+impl<'de> Visitor<'de> for JsonVisitor {
+    type Value = Json;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a JSON value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Json, D::Error> {
+        Json::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Json, E> {
+        Ok(Json::Bool(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Json, E> {
+        Ok(Json::Number(value.to_string()))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Json, E> {
+        Ok(Json::Number(value.to_string()))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Json, E> {
+        Ok(Json::Number(value.to_string()))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Json, E> {
+        Ok(Json::string(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut sequence: A) -> Result<Json, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = sequence.next_element()? {
+            values.push(value);
+        }
+        Ok(Json::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Json, A::Error> {
+        let mut members: Vec<(String, Json)> = Vec::new();
+        while let Some((key, value)) = map.next_entry::<String, Json>()? {
+            if members.iter().any(|(member, _)| *member == key) {
+                return Err(de::Error::custom(format!("duplicate key `{}`", key)))
+            }
+            members.push((key, value));
+        }
+        Ok(Json::Object(members))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl JsonError {
+    pub fn new(message: &str, span: Span) -> Self {
+        JsonError { message: message.to_string(), span }
+    }
+}
+
+/// parse a JSON document, objects with the same key twice are rejected
+pub fn parse_json(text: &str) -> Result<Json, JsonError> {
+    let mut reader = Reader { text, at: 0, depth: 0 };
+    let json = reader.value()?;
+    reader.whitespace();
+    if reader.at < text.len() {
+        return Err(reader.error("expected the end of the document"))
+    }
+    Ok(json)
+}
+
+struct Reader<'t> {
+    text: &'t str,
+    at: usize,
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn error(&self, message: &str) -> JsonError {
+        let size = self.text[self.at..].chars().next().map_or(0, char::len_utf8);
+        JsonError::new(message, Span::new(self.at, size))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.at).copied()
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.at += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), JsonError> {
+        self.whitespace();
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected `{}`", byte as char)))
+        }
+        self.at += 1;
+        Ok(())
+    }
+
+    /// `]` or `}` after the elements, or the `,` before the next
+    fn next_element(&mut self, close: u8) -> Result<bool, JsonError> {
+        self.whitespace();
+        match self.peek() {
+            Some(b',') => {
+                self.at += 1;
+                Ok(true)
+            },
+            Some(byte) if byte == close => {
+                self.at += 1;
+                Ok(false)
+            },
+            _ => Err(self.error(&format!("expected `,` or `{}`", close as char))),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.whitespace();
+        match self.peek() {
+            Some(b'{') => self.nested(|reader| {
+                let mut members: Vec<(String, Json)> = Vec::new();
+                reader.whitespace();
+                if reader.peek() == Some(b'}') {
+                    reader.at += 1;
+                    return Ok(Json::Object(members))
+                }
+                loop {
+                    reader.whitespace();
+                    let at = reader.at;
+                    let key = reader.string()?;
+                    if members.iter().any(|(member, _)| *member == key) {
+                        return Err(JsonError::new(&format!("duplicate key {}", quote(&key)), Span::new(at, reader.at - at)))
+                    }
+                    reader.expect(b':')?;
+                    members.push((key, reader.value()?));
+                    if !reader.next_element(b'}')? {
+                        return Ok(Json::Object(members))
+                    }
+                }
+            }),
+            Some(b'[') => self.nested(|reader| {
+                let mut values = Vec::new();
+                reader.whitespace();
+                if reader.peek() == Some(b']') {
+                    reader.at += 1;
+                    return Ok(Json::Array(values))
+                }
+                loop {
+                    values.push(reader.value()?);
+                    if !reader.next_element(b']')? {
+                        return Ok(Json::Array(values))
+                    }
+                }
+            }),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => {
+                for (keyword, json) in [("null", Json::Null), ("true", Json::Bool(true)), ("false", Json::Bool(false))] {
+                    if self.text[self.at..].starts_with(keyword) {
+                        self.at += keyword.len();
+                        return Ok(json)
+                    }
+                }
+                Err(self.error("expected a JSON value"))
+            },
+            None => Err(self.error("unexpected end of the document")),
+        }
+    }
+
+    /// read an object or array after its opening bracket
+    fn nested(&mut self, f: impl FnOnce(&mut Self) -> Result<Json, JsonError>) -> Result<Json, JsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("too deeply nested"))
+        }
+        self.depth += 1;
+        self.at += 1;
+        let json = f(self);
+        self.depth -= 1;
+        json
+    }
+
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.at;
+        let digits = |reader: &mut Self| {
+            let at = reader.at;
+            while reader.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+                reader.at += 1;
+            }
+            reader.at > at
+        };
+        if self.peek() == Some(b'-') {
+            self.at += 1;
+        }
+        let integer = self.at;
+        if !digits(self) || (self.text.as_bytes()[integer] == b'0' && self.at - integer > 1) {
+            return Err(self.error("invalid number"))
+        }
+        if self.peek() == Some(b'.') {
+            self.at += 1;
+            if !digits(self) {
+                return Err(self.error("invalid number"))
+            }
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.at += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.at += 1;
+            }
+            if !digits(self) {
+                return Err(self.error("invalid number"))
+            }
+        }
+        Ok(Json::Number(self.text[start..self.at].to_string()))
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        if self.peek() != Some(b'"') {
+            return Err(self.error("expected a string"))
+        }
+        self.at += 1;
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.text[self.at..].chars().next() else {
+                return Err(self.error("unterminated string"))
+            };
+            match c {
+                '"' => {
+                    self.at += 1;
+                    return Ok(value)
+                },
+                '\\' => {
+                    self.at += 1;
+                    let escaped = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            self.at += 1;
+                            value.push(self.unicode_escape()?);
+                            continue;
+                        },
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.at += 1;
+                    value.push(escaped);
+                },
+                c if (c as u32) < 0x20 => return Err(self.error("control character in string")),
+                c => {
+                    self.at += c.len_utf8();
+                    value.push(c);
+                },
+            }
+        }
+    }
+
+    /// the character of `\uXXXX` after the `u`, and of the low surrogate after it for characters above the BMP
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let hex = |reader: &mut Self| {
+            let code = reader.text.get(reader.at..reader.at + 4).and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .filter(|_| reader.text[reader.at..reader.at + 4].bytes().all(|byte| byte.is_ascii_hexdigit()));
+            let Some(code) = code else {
+                return Err(reader.error("invalid unicode escape"))
+            };
+            reader.at += 4;
+            Ok(code)
+        };
+        let high = hex(self)?;
+        let code = if (0xd800..0xdc00).contains(&high) {
+            if !self.text[self.at..].starts_with("\\u") {
+                return Err(self.error("unpaired surrogate"))
+            }
+            self.at += 2;
+            let low = hex(self)?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(self.error("unpaired surrogate"))
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("unpaired surrogate"))
+    }
+}
+
+/// a JSON string literal
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,725 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de;
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::ast::CompositeKind;
+use crate::checker::types::NumberType;
+use crate::interpreter::number::Number;
+
+use super::json::{
+    Json, parse_json,
+};
+
+/// types that take no type arguments, besides the number types
+const SIMPLE_TYPES: &[&str] = &[
+    "Any", "AnyStruct", "AnyResource", "AnyStructAttachment", "AnyResourceAttachment", "Type", "Void", "Never", "Bool",
+    "String", "Character", "Bytes", "Address", "Path", "CapabilityPath", "StoragePath", "PublicPath", "PrivatePath",
+    "Block", "DeployedContract", "AccountKey", "HashableStruct", "AuthAccount", "PublicAccount", "AuthAccount.Keys",
+    "PublicAccount.Keys", "AuthAccount.Contracts", "PublicAccount.Contracts", "AuthAccount.Inbox",
+    "AuthAccount.Capabilities", "PublicAccount.Capabilities", "AuthAccount.StorageCapabilities",
+    "AuthAccount.AccountCapabilities", "Account", "Account.Storage", "Account.Contracts", "Account.Keys",
+    "Account.Inbox", "Account.Capabilities", "Account.StorageCapabilities", "Account.AccountCapabilities",
+    "StorageCapabilityController", "AccountCapabilityController",
+];
+
+/// a value of the JSON-Cadence data interchange format, which transaction arguments and script results are sent in
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Void,
+    Optional(Option<Box<Value>>),
+    Bool(bool),
+    String(String),
+    Character(String),
+    Address(u64),
+    /// integers and fixed points of a concrete type
+    Number(Number),
+    Array(Vec<Value>),
+    /// in order
+    Dictionary(Vec<(Value, Value)>),
+    Struct(Composite),
+    Resource(Composite),
+    Event(Composite),
+    Contract(Composite),
+    Enum(Composite),
+    /// `/storage/vault`
+    Path {
+        domain: String,
+        identifier: String,
+    },
+    Type(Type),
+    /// a capability by id, `0` for capabilities of links before Cadence 1.0
+    Capability {
+        id: u64,
+        address: u64,
+        borrow_type: Type,
+    },
+    Function(FunctionType),
+}
+
+/// the value of a struct, resource, event, contract or enum
+#[derive(Clone, Debug, PartialEq)]
+pub struct Composite {
+    /// `A.<address>.<Contract>.<Name>`
+    pub type_id: String,
+    /// in the order they are encoded, the `uuid` of resources first
+    pub fields: Vec<(String, Value)>,
+}
+
+impl Composite {
+    pub fn new(type_id: &str, fields: Vec<(&str, Value)>) -> Self {
+        Composite { type_id: type_id.to_string(), fields: fields.into_iter().map(|(name, value)| (name.to_string(), value)).collect() }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        self.fields.iter().find(|(field, _)| field == name).map(|(_, value)| value)
+    }
+}
+
+/// a static type, as `Type` values, borrow types of capabilities and types of functions are encoded
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    /// by name, `Int`, `AnyStruct`, `Account.Storage`, ...
+    Simple(String),
+    Optional(Box<Type>),
+    VariableSizedArray(Box<Type>),
+    ConstantSizedArray(Box<Type>, u64),
+    Dictionary(Box<Type>, Box<Type>),
+    Composite(CompositeType),
+    Reference {
+        authorization: Authorization,
+        ty: Box<Type>,
+    },
+    /// `{I1, I2}`
+    Intersection {
+        type_id: String,
+        types: Vec<Type>,
+    },
+    /// `Capability` or `Capability<T>`
+    Capability(Option<Box<Type>>),
+    Function(FunctionType),
+}
+
+/// a composite or interface type with its fields and initializers
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompositeType {
+    pub kind: CompositeKind,
+    pub is_interface: bool,
+    pub type_id: String,
+    pub fields: Vec<(String, Type)>,
+    pub initializers: Vec<Vec<Parameter>>,
+    /// the raw value type of enums
+    pub raw_type: Option<Box<Type>>,
+}
+
+impl CompositeType {
+    /// `Struct`, `ResourceInterface`, ...
+    fn kind_name(&self) -> String {
+        let kind = match self.kind {
+            CompositeKind::Structure => "Struct",
+            CompositeKind::Resource => "Resource",
+            CompositeKind::Contract => "Contract",
+            CompositeKind::Event => "Event",
+            CompositeKind::Enum => "Enum",
+        };
+        if self.is_interface {
+            format!("{}Interface", kind)
+        } else {
+            kind.to_string()
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    /// `_` for none
+    pub label: String,
+    pub identifier: String,
+    pub ty: Type,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionType {
+    pub is_view: bool,
+    /// names with their bounds
+    pub type_parameters: Vec<(String, Option<Type>)>,
+    pub parameters: Vec<Parameter>,
+    pub return_type: Box<Type>,
+}
+
+/// what a reference is authorized for, entitlements by type id
+#[derive(Clone, Debug, PartialEq)]
+pub enum Authorization {
+    Unauthorized,
+    Conjunction(Vec<String>),
+    Disjunction(Vec<String>),
+    Mapping(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeError {
+    pub message: String,
+    /// where in the document, e.g. `value.fields[1].value`, empty for the whole document
+    pub path: String,
+}
+
+impl DecodeError {
+    pub fn new(message: &str, path: &str) -> Self {
+        DecodeError { message: message.to_string(), path: path.to_string() }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// a value as compact JSON-Cadence
+pub fn encode(value: &Value) -> String {
+    value.to_json().to_string()
+}
+
+/// a value from JSON-Cadence, which must be valid as a whole: no unknown types or keys, numbers in range,
+/// fixed points with 8 decimals
+pub fn decode(text: &str) -> Result<Value, DecodeError> {
+    let json = parse_json(text)
+        .map_err(|error| DecodeError::new(&format!("invalid JSON at byte {}: {}", error.span.at, error.message), ""))?;
+    Value::from_json(&json)
+}
+
+/// values serialize as JSON-Cadence, in any format of serde
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+/// with the validation of `decode`
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = Json::deserialize(deserializer)?;
+        Value::from_json(&json).map_err(de::Error::custom)
+    }
+}
+
+impl Value {
+    pub fn to_json(&self) -> Json {
+        let typed = |ty: &str, value: Json| Json::object(vec![("type", Json::string(ty)), ("value", value)]);
+        let composite = |ty: &str, composite: &Composite| {
+            let fields = composite.fields.iter()
+                .map(|(name, value)| Json::object(vec![("name", Json::string(name)), ("value", value.to_json())]))
+                .collect();
+            typed(ty, Json::object(vec![("id", Json::string(&composite.type_id)), ("fields", Json::Array(fields))]))
+        };
+        match self {
+            Value::Void => Json::object(vec![("type", Json::string("Void"))]),
+            Value::Optional(value) => typed("Optional", value.as_ref().map_or(Json::Null, |value| value.to_json())),
+            Value::Bool(value) => typed("Bool", Json::Bool(*value)),
+            Value::String(value) => typed("String", Json::string(value)),
+            Value::Character(value) => typed("Character", Json::string(value)),
+            Value::Address(address) => typed("Address", Json::String(format!("0x{:016x}", address))),
+            Value::Number(number) => typed(number.ty().name(), Json::String(number.to_string())),
+            Value::Array(values) => typed("Array", Json::Array(values.iter().map(Value::to_json).collect())),
+            Value::Dictionary(entries) => {
+                let entries = entries.iter()
+                    .map(|(key, value)| Json::object(vec![("key", key.to_json()), ("value", value.to_json())]))
+                    .collect();
+                typed("Dictionary", Json::Array(entries))
+            },
+            Value::Struct(value) => composite("Struct", value),
+            Value::Resource(value) => composite("Resource", value),
+            Value::Event(value) => composite("Event", value),
+            Value::Contract(value) => composite("Contract", value),
+            Value::Enum(value) => composite("Enum", value),
+            Value::Path { domain, identifier } => {
+                typed("Path", Json::object(vec![("domain", Json::string(domain)), ("identifier", Json::string(identifier))]))
+            },
+            Value::Type(ty) => typed("Type", Json::object(vec![("staticType", ty.to_json())])),
+            Value::Capability { id, address, borrow_type } => typed("Capability", Json::object(vec![
+                ("id", Json::String(id.to_string())),
+                ("address", Json::String(format!("0x{:016x}", address))),
+                ("borrowType", borrow_type.to_json()),
+            ])),
+            Value::Function(function) => {
+                let function_type = Type::Function(function.clone()).to_json();
+                typed("Function", Json::object(vec![("functionType", function_type)]))
+            },
+        }
+    }
+
+    pub fn from_json(json: &Json) -> Result<Value, DecodeError> {
+        value(json, "")
+    }
+
+    /// whether it can be a key of a dictionary
+    pub fn is_hashable(&self) -> bool {
+        matches!(self,
+            Value::Bool(_) | Value::String(_) | Value::Character(_) | Value::Address(_) | Value::Number(_) |
+            Value::Path { .. } | Value::Enum(_) | Value::Type(_)
+        )
+    }
+}
+
+impl Type {
+    pub fn simple(name: &str) -> Self {
+        Type::Simple(name.to_string())
+    }
+
+    /// composite types after the first of the same type id are encoded as the type id,
+    /// which is how recursive types are encoded
+    pub fn to_json(&self) -> Json {
+        self.json(&mut Vec::new())
+    }
+
+    fn json(&self, encoded: &mut Vec<String>) -> Json {
+        let kind = |kind: &str, members: Vec<(&str, Json)>| {
+            let mut object = vec![("kind", Json::string(kind))];
+            object.extend(members);
+            Json::object(object)
+        };
+        let parameters = |parameters: &[Parameter], encoded: &mut Vec<String>| {
+            let parameters = parameters.iter()
+                .map(|parameter| Json::object(vec![
+                    ("label", Json::string(&parameter.label)),
+                    ("id", Json::string(&parameter.identifier)),
+                    ("type", parameter.ty.json(encoded)),
+                ]))
+                .collect();
+            Json::Array(parameters)
+        };
+        match self {
+            Type::Simple(name) => kind(name, Vec::new()),
+            Type::Optional(ty) => kind("Optional", vec![("type", ty.json(encoded))]),
+            Type::VariableSizedArray(ty) => kind("VariableSizedArray", vec![("type", ty.json(encoded))]),
+            Type::ConstantSizedArray(ty, size) => {
+                kind("ConstantSizedArray", vec![("type", ty.json(encoded)), ("size", Json::Number(size.to_string()))])
+            },
+            Type::Dictionary(key, value) => kind("Dictionary", vec![("key", key.json(encoded)), ("value", value.json(encoded))]),
+            Type::Composite(composite) => {
+                if encoded.contains(&composite.type_id) {
+                    return Json::string(&composite.type_id)
+                }
+                encoded.push(composite.type_id.clone());
+                let raw_type = composite.raw_type.as_ref().map_or(Json::string(""), |ty| ty.json(encoded));
+                let initializers = composite.initializers.iter().map(|initializer| parameters(initializer, encoded)).collect();
+                let fields = composite.fields.iter()
+                    .map(|(name, ty)| Json::object(vec![("id", Json::string(name)), ("type", ty.json(encoded))]))
+                    .collect();
+                kind(&composite.kind_name(), vec![
+                    ("type", raw_type),
+                    ("typeID", Json::string(&composite.type_id)),
+                    ("initializers", Json::Array(initializers)),
+                    ("fields", Json::Array(fields)),
+                ])
+            },
+            Type::Reference { authorization, ty } => {
+                kind("Reference", vec![("authorization", authorization.to_json()), ("type", ty.json(encoded))])
+            },
+            Type::Intersection { type_id, types } => {
+                let types = types.iter().map(|ty| ty.json(encoded)).collect();
+                kind("Intersection", vec![("typeID", Json::string(type_id)), ("types", Json::Array(types))])
+            },
+            Type::Capability(ty) => kind("Capability", vec![("type", ty.as_ref().map_or(Json::string(""), |ty| ty.json(encoded)))]),
+            Type::Function(function) => {
+                let type_parameters = function.type_parameters.iter()
+                    .map(|(name, bound)| Json::object(vec![
+                        ("name", Json::string(name)),
+                        ("typeBound", bound.as_ref().map_or(Json::Null, |bound| bound.json(encoded))),
+                    ]))
+                    .collect();
+                kind("Function", vec![
+                    ("typeParameters", Json::Array(type_parameters)),
+                    ("parameters", parameters(&function.parameters, encoded)),
+                    ("purity", Json::string(if function.is_view { "view" } else { "impure" })),
+                    ("return", function.return_type.json(encoded)),
+                ])
+            },
+        }
+    }
+
+    pub fn from_json(json: &Json) -> Result<Type, DecodeError> {
+        TypeDecoder::default().decode(json, "")
+    }
+}
+
+impl Authorization {
+    pub fn to_json(&self) -> Json {
+        let entitlements = |kind: &str, type_ids: &[String]| {
+            let entitlements = type_ids.iter()
+                .map(|type_id| Json::object(vec![("kind", Json::string(kind)), ("typeID", Json::string(type_id))]))
+                .collect();
+            Json::Array(entitlements)
+        };
+        let (kind, entitlements) = match self {
+            Authorization::Unauthorized => ("Unauthorized", Json::Null),
+            Authorization::Conjunction(type_ids) => ("EntitlementConjunctionSet", entitlements("Entitlement", type_ids)),
+            Authorization::Disjunction(type_ids) => ("EntitlementDisjunctionSet", entitlements("Entitlement", type_ids)),
+            Authorization::Mapping(type_id) => ("EntitlementMapAuthorization", entitlements("EntitlementMap", std::slice::from_ref(type_id))),
+        };
+        Json::object(vec![("kind", Json::string(kind)), ("entitlements", entitlements)])
+    }
+}
+
+// decoding
+
+fn child(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// the members of an object with exactly the keys, and those of the optional keys it has
+fn object<'j, const N: usize>(json: &'j Json, path: &str, keys: [&str; N], optional: &[&str]) -> Result<[&'j Json; N], DecodeError> {
+    let Json::Object(members) = json else {
+        return Err(DecodeError::new(&format!("expected an object, got {}", json.kind()), path))
+    };
+    if let Some((key, _)) = members.iter().find(|(key, _)| !keys.contains(&key.as_str()) && !optional.contains(&key.as_str())) {
+        return Err(DecodeError::new(&format!("unexpected key `{}`", key), path))
+    }
+    let mut found = [&Json::Null; N];
+    for (index, key) in keys.iter().enumerate() {
+        found[index] = json.get(key).ok_or_else(|| DecodeError::new(&format!("missing key `{}`", key), path))?;
+    }
+    Ok(found)
+}
+
+fn string<'j>(json: &'j Json, path: &str) -> Result<&'j str, DecodeError> {
+    match json {
+        Json::String(value) => Ok(value),
+        json => Err(DecodeError::new(&format!("expected a string, got {}", json.kind()), path)),
+    }
+}
+
+fn array<'j>(json: &'j Json, path: &str) -> Result<&'j [Json], DecodeError> {
+    match json {
+        Json::Array(values) => Ok(values),
+        json => Err(DecodeError::new(&format!("expected an array, got {}", json.kind()), path)),
+    }
+}
+
+/// the elements of an array, each with its path
+fn elements<'j>(json: &'j Json, path: &str) -> Result<impl Iterator<Item = (&'j Json, String)>, DecodeError> {
+    let path = path.to_string();
+    Ok(array(json, &path)?.iter().enumerate().map(move |(index, element)| (element, format!("{}[{}]", path, index))))
+}
+
+/// whether `name` can be the identifier of a path
+pub fn is_identifier(name: &str) -> bool {
+    name.bytes().next().is_some_and(|c| c.is_ascii_alphabetic() || c == b'_')
+        && name.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'_')
+}
+
+/// `0x` followed by up to 8 bytes in hex
+fn address(json: &Json, path: &str) -> Result<u64, DecodeError> {
+    let text = string(json, path)?;
+    let digits = text.strip_prefix("0x").unwrap_or("");
+    let valid = !digits.is_empty() && digits.len() <= 16 && digits.len() % 2 == 0 && digits.bytes().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(DecodeError::new(&format!("invalid address `{}`", text), path))
+    }
+    u64::from_str_radix(digits, 16).map_err(|_| DecodeError::new(&format!("invalid address `{}`", text), path))
+}
+
+/// integers in decimal, fixed points with exactly 8 decimals
+fn number(json: &Json, ty: NumberType, path: &str) -> Result<Number, DecodeError> {
+    let text = string(json, path)?;
+    let (negative, magnitude) = match text.strip_prefix('-') {
+        Some(magnitude) => (true, magnitude),
+        None => (false, text),
+    };
+    let (integer, fraction) = match magnitude.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (magnitude, None),
+    };
+    let digits = |digits: &str| !digits.is_empty() && digits.bytes().all(|c| c.is_ascii_digit());
+    let valid = digits(integer) && (integer == "0" || !integer.starts_with('0')) && match fraction {
+        Some(fraction) => ty.is_fixed_point() && fraction.len() == 8 && digits(fraction),
+        None => !ty.is_fixed_point(),
+    };
+    if !valid {
+        let expected = if ty.is_fixed_point() { "with 8 decimals" } else { "in decimal" };
+        return Err(DecodeError::new(&format!("expected a `{}` {}, got `{}`", ty.name(), expected, text), path))
+    }
+    if negative && !ty.is_signed() {
+        return Err(DecodeError::new(&format!("`{}` cannot be negative, got `{}`", ty.name(), text), path))
+    }
+    Number::parse(ty, magnitude, negative)
+        .map_err(|_| DecodeError::new(&format!("`{}` is out of range for `{}`", text, ty.name()), path))
+}
+
+fn value(json: &Json, path: &str) -> Result<Value, DecodeError> {
+    let ty = string(json.get("type").ok_or_else(|| DecodeError::new("missing key `type`", path))?, &child(path, "type"))?;
+    if ty == "Void" {
+        object(json, path, ["type"], &[])?;
+        return Ok(Value::Void)
+    }
+    let [_, value_json] = object(json, path, ["type", "value"], &[])?;
+    let type_path = child(path, "type");
+    let path = &child(path, "value");
+    let composite = |json: &Json| {
+        let [id, fields] = object(json, path, ["id", "fields"], &[])?;
+        let type_id = string(id, &child(path, "id"))?;
+        if type_id.is_empty() {
+            return Err(DecodeError::new("empty type id", &child(path, "id")))
+        }
+        let mut composite = Composite { type_id: type_id.to_string(), fields: Vec::new() };
+        for (field, path) in elements(fields, &child(path, "fields"))? {
+            let [name, field_value] = object(field, &path, ["name", "value"], &[])?;
+            let name = string(name, &child(&path, "name"))?;
+            if !is_identifier(name) || composite.field(name).is_some() {
+                return Err(DecodeError::new(&format!("invalid or duplicate field name `{}`", name), &child(&path, "name")))
+            }
+            composite.fields.push((name.to_string(), value(field_value, &child(&path, "value"))?));
+        }
+        Ok(composite)
+    };
+    Ok(match ty {
+        "Optional" => match value_json {
+            Json::Null => Value::Optional(None),
+            json => Value::Optional(Some(Box::new(value(json, path)?))),
+        },
+        "Bool" => match value_json {
+            Json::Bool(value) => Value::Bool(*value),
+            json => return Err(DecodeError::new(&format!("expected a boolean, got {}", json.kind()), path)),
+        },
+        "String" => Value::String(string(value_json, path)?.to_string()),
+        "Character" => {
+            let character = string(value_json, path)?;
+            if character.chars().count() != 1 {
+                return Err(DecodeError::new(&format!("expected a single character, got {:?}", character), path))
+            }
+            Value::Character(character.to_string())
+        },
+        "Address" => Value::Address(address(value_json, path)?),
+        "Array" => Value::Array(elements(value_json, path)?.map(|(element, path)| value(element, &path)).collect::<Result<_, _>>()?),
+        "Dictionary" => {
+            let mut entries = Vec::new();
+            for (entry, path) in elements(value_json, path)? {
+                let [key, entry_value] = object(entry, &path, ["key", "value"], &[])?;
+                let key = value(key, &child(&path, "key"))?;
+                if !key.is_hashable() {
+                    return Err(DecodeError::new("dictionary keys are numbers, strings, characters, booleans, addresses, paths, enums or types", &child(&path, "key")))
+                }
+                entries.push((key, value(entry_value, &child(&path, "value"))?));
+            }
+            Value::Dictionary(entries)
+        },
+        "Struct" => Value::Struct(composite(value_json)?),
+        "Resource" => Value::Resource(composite(value_json)?),
+        "Event" => Value::Event(composite(value_json)?),
+        "Contract" => Value::Contract(composite(value_json)?),
+        "Enum" => Value::Enum(composite(value_json)?),
+        "Path" => {
+            let [domain, identifier] = object(value_json, path, ["domain", "identifier"], &[])?;
+            let domain = string(domain, &child(path, "domain"))?;
+            if !matches!(domain, "storage" | "public" | "private") {
+                return Err(DecodeError::new(&format!("invalid path domain `{}`", domain), &child(path, "domain")))
+            }
+            let identifier = string(identifier, &child(path, "identifier"))?;
+            if !is_identifier(identifier) {
+                return Err(DecodeError::new(&format!("invalid path identifier `{}`", identifier), &child(path, "identifier")))
+            }
+            Value::Path { domain: domain.to_string(), identifier: identifier.to_string() }
+        },
+        "Type" => {
+            let [static_type] = object(value_json, path, ["staticType"], &[])?;
+            Value::Type(TypeDecoder::default().decode(static_type, &child(path, "staticType"))?)
+        },
+        "Capability" => {
+            let [id, capability_address, borrow_type] = object(value_json, path, ["id", "address", "borrowType"], &[])?;
+            let id = number(id, NumberType::UInt64, &child(path, "id"))?.to_i128().unwrap_or_default() as u64;
+            let address = address(capability_address, &child(path, "address"))?;
+            let borrow_type = TypeDecoder::default().decode(borrow_type, &child(path, "borrowType"))?;
+            Value::Capability { id, address, borrow_type }
+        },
+        "Function" => {
+            let [function_type] = object(value_json, path, ["functionType"], &[])?;
+            match TypeDecoder::default().decode(function_type, &child(path, "functionType"))? {
+                Type::Function(function) => Value::Function(function),
+                _ => return Err(DecodeError::new("expected a function type", &child(path, "functionType"))),
+            }
+        },
+        ty => match NumberType::from_name(ty).filter(|number| !number.is_abstract()) {
+            Some(number_type) => Value::Number(number(value_json, number_type, path)?),
+            None => return Err(DecodeError::new(&format!("unknown type `{}`", ty), &type_path)),
+        },
+    })
+}
+
+/// decodes one type, composite types encoded as type ids refer to those decoded before in it
+#[derive(Default)]
+struct TypeDecoder {
+    composites: HashMap<String, CompositeType>,
+}
+
+impl TypeDecoder {
+    fn decode(&mut self, json: &Json, path: &str) -> Result<Type, DecodeError> {
+        if let Json::String(type_id) = json {
+            return match self.composites.get(type_id) {
+                Some(composite) => Ok(Type::Composite(composite.clone())),
+                None => Err(DecodeError::new(&format!("type id `{}` of no composite type before it", type_id), path)),
+            }
+        }
+        let kind = string(json.get("kind").ok_or_else(|| DecodeError::new("missing key `kind`", path))?, &child(path, "kind"))?;
+        let composite_kind = match kind.strip_suffix("Interface").unwrap_or(kind) {
+            "Struct" => Some(CompositeKind::Structure),
+            "Resource" => Some(CompositeKind::Resource),
+            "Contract" => Some(CompositeKind::Contract),
+            "Event" if !kind.ends_with("Interface") => Some(CompositeKind::Event),
+            "Enum" if !kind.ends_with("Interface") => Some(CompositeKind::Enum),
+            _ => None,
+        };
+        if let Some(composite_kind) = composite_kind {
+            return self.composite(json, path, composite_kind, kind.ends_with("Interface"))
+        }
+        Ok(match kind {
+            "Optional" | "VariableSizedArray" => {
+                let [_, ty] = object(json, path, ["kind", "type"], &[])?;
+                let ty = Box::new(self.decode(ty, &child(path, "type"))?);
+                if kind == "Optional" { Type::Optional(ty) } else { Type::VariableSizedArray(ty) }
+            },
+            "ConstantSizedArray" => {
+                let [_, ty, size] = object(json, path, ["kind", "type", "size"], &[])?;
+                let Some(size) = (match size {
+                    Json::Number(size) => size.parse::<u64>().ok(),
+                    _ => None,
+                }) else {
+                    return Err(DecodeError::new("expected a size", &child(path, "size")))
+                };
+                Type::ConstantSizedArray(Box::new(self.decode(ty, &child(path, "type"))?), size)
+            },
+            "Dictionary" => {
+                let [_, key, value] = object(json, path, ["kind", "key", "value"], &[])?;
+                Type::Dictionary(Box::new(self.decode(key, &child(path, "key"))?), Box::new(self.decode(value, &child(path, "value"))?))
+            },
+            "Reference" => {
+                let [_, authorization, ty] = object(json, path, ["kind", "authorization", "type"], &[])?;
+                let authorization = authorization_from_json(authorization, &child(path, "authorization"))?;
+                Type::Reference { authorization, ty: Box::new(self.decode(ty, &child(path, "type"))?) }
+            },
+            "Intersection" => {
+                let [_, type_id, types] = object(json, path, ["kind", "typeID", "types"], &[])?;
+                let type_id = string(type_id, &child(path, "typeID"))?.to_string();
+                let types = elements(types, &child(path, "types"))?.map(|(ty, path)| self.decode(ty, &path)).collect::<Result<_, _>>()?;
+                Type::Intersection { type_id, types }
+            },
+            "Capability" => {
+                let [_, ty] = object(json, path, ["kind", "type"], &[])?;
+                match ty {
+                    Json::String(empty) if empty.is_empty() => Type::Capability(None),
+                    ty => Type::Capability(Some(Box::new(self.decode(ty, &child(path, "type"))?))),
+                }
+            },
+            "Function" => {
+                let [_, type_parameters, parameters, return_type] = object(json, path, ["kind", "typeParameters", "parameters", "return"], &["purity"])?;
+                let is_view = match json.get("purity") {
+                    None => false,
+                    Some(Json::String(purity)) if purity == "view" => true,
+                    Some(Json::String(purity)) if purity == "impure" => false,
+                    Some(_) => return Err(DecodeError::new("expected `view` or `impure`", &child(path, "purity"))),
+                };
+                let mut type_parameter_list = Vec::new();
+                for (type_parameter, path) in elements(type_parameters, &child(path, "typeParameters"))? {
+                    let [name, bound] = object(type_parameter, &path, ["name", "typeBound"], &[])?;
+                    let bound = match bound {
+                        Json::Null => None,
+                        bound => Some(self.decode(bound, &child(&path, "typeBound"))?),
+                    };
+                    type_parameter_list.push((string(name, &child(&path, "name"))?.to_string(), bound));
+                }
+                Type::Function(FunctionType {
+                    is_view,
+                    type_parameters: type_parameter_list,
+                    parameters: self.parameters(parameters, &child(path, "parameters"))?,
+                    return_type: Box::new(self.decode(return_type, &child(path, "return"))?),
+                })
+            },
+            name if SIMPLE_TYPES.contains(&name) || NumberType::from_name(name).is_some() => {
+                object(json, path, ["kind"], &[])?;
+                Type::simple(name)
+            },
+            kind => return Err(DecodeError::new(&format!("unknown kind of type `{}`", kind), &child(path, "kind"))),
+        })
+    }
+
+    fn composite(&mut self, json: &Json, path: &str, kind: CompositeKind, is_interface: bool) -> Result<Type, DecodeError> {
+        let [_, raw_type, type_id, initializers, fields] = object(json, path, ["kind", "type", "typeID", "initializers", "fields"], &[])?;
+        let type_id = string(type_id, &child(path, "typeID"))?;
+        if type_id.is_empty() {
+            return Err(DecodeError::new("empty type id", &child(path, "typeID")))
+        }
+        // the type is known by its id in its own fields
+        let mut composite = CompositeType {
+            kind,
+            is_interface,
+            type_id: type_id.to_string(),
+            fields: Vec::new(),
+            initializers: Vec::new(),
+            raw_type: None,
+        };
+        self.composites.insert(composite.type_id.clone(), composite.clone());
+        composite.raw_type = match raw_type {
+            Json::String(empty) if empty.is_empty() => None,
+            raw_type => Some(Box::new(self.decode(raw_type, &child(path, "type"))?)),
+        };
+        for (initializer, path) in elements(initializers, &child(path, "initializers"))? {
+            composite.initializers.push(self.parameters(initializer, &path)?);
+        }
+        for (field, path) in elements(fields, &child(path, "fields"))? {
+            let [name, ty] = object(field, &path, ["id", "type"], &[])?;
+            let name = string(name, &child(&path, "id"))?.to_string();
+            composite.fields.push((name, self.decode(ty, &child(&path, "type"))?));
+        }
+        self.composites.insert(composite.type_id.clone(), composite.clone());
+        Ok(Type::Composite(composite))
+    }
+
+    fn parameters(&mut self, json: &Json, path: &str) -> Result<Vec<Parameter>, DecodeError> {
+        let mut parameters = Vec::new();
+        for (parameter, path) in elements(json, path)? {
+            let [label, identifier, ty] = object(parameter, &path, ["label", "id", "type"], &[])?;
+            parameters.push(Parameter {
+                label: string(label, &child(&path, "label"))?.to_string(),
+                identifier: string(identifier, &child(&path, "id"))?.to_string(),
+                ty: self.decode(ty, &child(&path, "type"))?,
+            });
+        }
+        Ok(parameters)
+    }
+}
+
+fn authorization_from_json(json: &Json, path: &str) -> Result<Authorization, DecodeError> {
+    let [kind, entitlements] = object(json, path, ["kind", "entitlements"], &[])?;
+    let kind = string(kind, &child(path, "kind"))?;
+    let type_ids = |entitlement_kind: &str| -> Result<Vec<String>, DecodeError> {
+        let mut type_ids = Vec::new();
+        for (entitlement, path) in elements(entitlements, &child(path, "entitlements"))? {
+            let [kind, type_id] = object(entitlement, &path, ["kind", "typeID"], &[])?;
+            if string(kind, &child(&path, "kind"))? != entitlement_kind {
+                return Err(DecodeError::new(&format!("expected the kind `{}`", entitlement_kind), &child(&path, "kind")))
+            }
+            type_ids.push(string(type_id, &child(&path, "typeID"))?.to_string());
+        }
+        Ok(type_ids)
+    };
+    match kind {
+        "Unauthorized" if *entitlements == Json::Null => Ok(Authorization::Unauthorized),
+        "EntitlementConjunctionSet" => Ok(Authorization::Conjunction(type_ids("Entitlement")?)),
+        "EntitlementDisjunctionSet" => Ok(Authorization::Disjunction(type_ids("Entitlement")?)),
+        "EntitlementMapAuthorization" => match type_ids("EntitlementMap")?.as_slice() {
+            [mapping] => Ok(Authorization::Mapping(mapping.clone())),
+            _ => Err(DecodeError::new("expected one entitlement mapping", &child(path, "entitlements"))),
+        },
+        kind => Err(DecodeError::new(&format!("invalid authorization `{}`", kind), &child(path, "kind"))),
+    }
+}
@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+pub mod json_cadence;
+pub mod convert;
+pub mod export;
+pub mod json;
@@ -26,9 +26,9 @@ impl<'a> Lexer<'a> {
 
     pub fn peekn(&self, n: usize) -> Option<u8> {
         if self.position + n >= self.input.len() {
-			return None
+			None
 		} else {
-			return Some(self.input[self.position + n])
+			Some(self.input[self.position + n])
 		}
     }
 
@@ -36,19 +36,32 @@ impl<'a> Lexer<'a> {
 	pub fn read(&mut self) -> Option<u8> {
 		if self.position >= self.input.len() {
             self.last_char = 0;
-			return None
+			None
 		} else {
 			let pos = self.position;
 			self.position += 1;
             self.last_char = self.input[pos];
-			return Some(self.input[pos])
+			Some(self.input[pos])
 		}
 	}
 
 	pub fn tokenize(&mut self) -> Token {
 		self.read_spaces();
-        self.read_comments();
-		self.read_spaces(); // read spaces after comments
+        loop {
+            let at = self.position;
+            match self.read_comments() {
+                Some(true) => {
+                    self.read_spaces(); // read spaces after comments
+                },
+                // not trivia, the rest of the input is lost in it
+                Some(false) => {
+                    self.commit();
+                    return Token::new(TokenKind::UnterminatedComment, at, self.position - at)
+                },
+                None => break,
+            }
+        }
+        self.commit(); // token starts after leading trivia
 
 		let kind = match self.read() {
 			None => TokenKind::EOF,
@@ -101,6 +114,19 @@ impl<'a> Lexer<'a> {
         Token::new(kind, at, size)
 	}
 
+    /// tokenize the whole input, the last token is always `EOF`
+    pub fn tokenize_all(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        loop {
+            let token = self.tokenize();
+            let eof = token.kind == TokenKind::EOF;
+            tokens.push(token);
+            if eof {
+                return tokens
+            }
+        }
+    }
+
     fn commit(&mut self) {
         self.commited_position = self.position;
     }
@@ -108,7 +134,7 @@ impl<'a> Lexer<'a> {
 	fn read_spaces(&mut self) -> usize {
         let mut count = 0;
         while let Some(c) = self.peek() {
-            if c == b' ' || c == b'\n' {
+            if c == b' ' || c == b'\n' || c == b'\t' || c == b'\r' {
                 self.read();
                 count += 1;
             }else{
@@ -118,7 +144,8 @@ impl<'a> Lexer<'a> {
         count
 	}
 
-    fn read_comments(&mut self) {
+    /// skip one comment if any, returning whether it was closed
+    fn read_comments(&mut self) -> Option<bool> {
         if let Some(c) = self.peek() {
             if c == b'/' {
                 if let Some(cc) = self.peekn(1) {
@@ -126,14 +153,16 @@ impl<'a> Lexer<'a> {
                         self.read();
                         self.read();
                         self.read_till_eol();
+                        return Some(true)
                     } else if cc == b'*' {
                         self.read();
                         self.read();
-                        self.read_till_blockcomment_close();
+                        return Some(self.read_till_blockcomment_close())
                     }
                 }
             }
         }
+        None
     }
 
     fn peek_spaces(&mut self, offset: usize) -> usize {
@@ -298,19 +327,14 @@ impl<'a> Lexer<'a> {
                 }
                 continue;
             }
-            chars.push(c);
 
             if c == b'"' {
-                break;
+                return Some(TokenKind::String(String::from_utf8_lossy(&chars).into_owned()))
             }
+            chars.push(c);
         }
 
-        if chars.len() == 0 {
-            return Some(TokenKind::DoubleQuote)
-        }
-
-        let str = String::from_utf8(chars[0..chars.len()-1].to_owned()).unwrap();
-        Some(TokenKind::String(str))
+        Some(TokenKind::UnterminatedString(String::from_utf8_lossy(&chars).into_owned()))
     }
 
     fn alphanumeric(&mut self) -> Option<TokenKind> {
@@ -400,16 +424,15 @@ impl<'a> Lexer<'a> {
             }
         }
     }
-    fn read_till_blockcomment_close(&mut self) {
+    /// whether the comment is closed before the end of the input
+    fn read_till_blockcomment_close(&mut self) -> bool {
         while let Some(c) = self.read() {
-            if c == b'*' {
-                if let Some(cc) = self.read() {
-                    if cc == b'/' {
-                        break;
-                    }   
-                }
+            if c == b'*' && self.peek() == Some(b'/') {
+                self.read();
+                return true
             }
         }
+        false
     }
 
 
@@ -481,11 +504,11 @@ impl<'a> Lexer<'a> {
     }
 
     fn is_decimal_digit(c: u8) -> bool {
-        c >= b'0' && c <= b'9'
+        c.is_ascii_digit()
     }
 
     fn is_nible(c: u8) -> bool {
-        (c >= b'0' && c <= b'9') || (c >= b'a' && c <= b'f') || (c >= b'A' && c <= b'F')
+        c.is_ascii_hexdigit()
     }
 
     
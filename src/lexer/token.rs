@@ -81,11 +81,15 @@ pub enum TokenKind {
     Keyword(Keyword),
 
     None,
+    /// a string literal without its closing `"`, up to the end of the input
+    UnterminatedString(String),
+    /// a block comment without its closing `*/`, up to the end of the input
+    UnterminatedComment,
 
     EOF, // \u{0}
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Keyword {
     Let,
     Var,
@@ -204,6 +208,75 @@ impl Keyword {
         };
         Some(keyword)
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Let => "let",
+            Self::Var => "var",
+
+            Self::True => "true",
+            Self::False => "false",
+
+            Self::Nil => "nil",
+
+            Self::If => "if",
+            Self::Else => "else",
+            Self::Switch => "switch",
+            Self::Case => "case",
+            Self::Break => "break",
+            Self::Default => "default",
+            Self::While => "while",
+            Self::For => "for",
+            Self::In => "in",
+            Self::Continue => "continue",
+
+            Self::Pub => "pub",
+            Self::Priv => "priv",
+            Self::Access => "access",
+            Self::All => "all",
+            Self::Contract => "contract",
+            Self::Account => "account",
+
+            Self::Struct => "struct",
+            Self::Resource => "resource",
+            Self::Interface => "interface",
+            Self::Enum => "enum",
+            Self::Init => "init",
+            Self::Get => "get",
+            Self::Set => "set",
+            Self::Pre => "pre",
+            Self::Post => "post",
+            Self::SSelf => "self",
+            Self::Create => "create",
+            Self::Destroy => "destroy",
+
+            Self::Import => "import",
+            Self::From => "from",
+
+            Self::Fun => "fun",
+            Self::Return => "return",
+            Self::Event => "event",
+            Self::Emit => "emit",
+
+            Self::Transaction => "transaction",
+            Self::Prepare => "prepare",
+            Self::Execute => "execute",
+
+            Self::As => "as",
+            Self::AsEx => "as!",
+            Self::AsQu => "as?",
+        }
+    }
+
+    /// soft keywords may also be used as identifiers, e.g. `self.account` or `from: Address`
+    pub fn is_soft(&self) -> bool {
+        matches!(self,
+            Self::Access | Self::All | Self::Contract | Self::Account |
+            Self::Struct | Self::Resource | Self::Interface | Self::Enum |
+            Self::Get | Self::Set | Self::Pre | Self::Post |
+            Self::From | Self::Event | Self::Transaction | Self::Prepare | Self::Execute
+        )
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
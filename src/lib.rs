@@ -1,10 +1,27 @@
+// the paths of derived conversions, inside of this crate too
+extern crate self as cadence_rs;
+
+pub mod abi;
+pub mod ast;
+pub mod bindgen;
+pub mod ccf;
+pub mod checker;
+pub mod cst;
+pub mod emulator;
+pub mod files;
+pub mod format;
+pub mod interpreter;
+pub mod json_cadence;
 pub mod lexer;
+pub mod migrate;
+pub mod parser;
+pub mod printer;
 
 #[cfg(test)]
 mod tests {    
     use crate::lexer::lexer::Lexer;
     use crate::lexer::token::{
-        TokenKind, Keyword,
+        Token, TokenKind, Keyword,
     };
 
     #[test]
@@ -139,6 +156,7 @@ mod tests {
         assert_eq!(lexer.tokenize().kind, TokenKind::BraceOpen);
         assert_eq!(lexer.tokenize().kind, TokenKind::Slash);
         assert_eq!(lexer.tokenize().kind, TokenKind::At);
+        assert_eq!(lexer.tokenize().kind, TokenKind::UnterminatedComment);
         assert_eq!(lexer.tokenize().kind, TokenKind::EOF);
     }
 
@@ -397,7 +415,28 @@ mod tests {
         assert_eq!(lexer.tokenize().kind, TokenKind::String("\n".to_string()));
         assert_eq!(lexer.tokenize().kind, TokenKind::String("\\".to_string()));
         assert_eq!(lexer.tokenize().kind, TokenKind::String("\"hello\nworld\"".to_string()));
-        assert_eq!(lexer.tokenize().kind, TokenKind::DoubleQuote);
+        assert_eq!(lexer.tokenize().kind, TokenKind::UnterminatedString("".to_string()));
+    }
+
+    #[test]
+    fn test_tokenize_unterminated() {
+        let mut lexer = Lexer::new("let x = \"abc");
+        lexer.tokenize();
+        lexer.tokenize();
+        lexer.tokenize();
+        assert_eq!(lexer.tokenize(), Token::new(TokenKind::UnterminatedString("abc".to_string()), 8, 4));
+        assert_eq!(lexer.tokenize().kind, TokenKind::EOF);
+
+        let mut lexer = Lexer::new("\"é");
+        assert_eq!(lexer.tokenize(), Token::new(TokenKind::UnterminatedString("é".to_string()), 0, 3));
+
+        let mut lexer = Lexer::new("a /* note");
+        lexer.tokenize();
+        assert_eq!(lexer.tokenize(), Token::new(TokenKind::UnterminatedComment, 2, 7));
+        assert_eq!(lexer.tokenize().kind, TokenKind::EOF);
+
+        let mut lexer = Lexer::new("/* note **/ a");
+        assert_eq!(lexer.tokenize().kind, TokenKind::Identifier("a".to_string()));
     }
 
     #[test]
@@ -467,3 +506,4411 @@ mod tests {
     }
 
 }
+
+#[cfg(test)]
+mod parser_tests {
+    use crate::ast::{
+        Access, BinaryOperation, CompositeKind, Declaration, ElseBranch, Expression, IfTest, ImportLocation, Purity,
+        SpecialFunctionKind, Span, Statement, Transfer, Type, VariableKind,
+    };
+    use crate::parser::parser::{
+        parse, parse_with_errors,
+    };
+
+    #[test]
+    fn test_parse_contract() {
+        let code = "
+pub contract FungibleToken: Token, Minter {
+    pub var totalSupply: UFix64
+    access(contract) let admin: Address
+
+    pub resource interface Provider {
+        pub fun withdraw(amount: UFix64): @Vault
+    }
+
+    pub resource Vault: Provider {
+        pub(set) var balance: UFix64
+
+        init(balance: UFix64) {
+            self.balance = balance
+        }
+
+        destroy() {}
+    }
+
+    pub event TokensDeposited(amount: UFix64, to: Address?)
+
+    init() {
+        self.totalSupply = 0.0
+        self.admin = 0x01
+    }
+}";
+        let program = parse(code).unwrap();
+        assert_eq!(program.declarations.len(), 1);
+
+        let contract = match &program.declarations[0] {
+            Declaration::Composite(contract) => contract,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(contract.access, Access::Pub);
+        assert_eq!(contract.kind, CompositeKind::Contract);
+        assert_eq!(contract.identifier.name, "FungibleToken");
+        assert_eq!(contract.conformances.iter().map(|c| c.name()).collect::<Vec<_>>(), vec!["Token", "Minter"]);
+        assert_eq!(contract.members.len(), 6);
+        assert_eq!(&code[contract.span.at..contract.span.end()], code.trim());
+
+        match &contract.members[0] {
+            Declaration::Field(field) => {
+                assert_eq!(field.access, Access::Pub);
+                assert_eq!(field.variable_kind, VariableKind::Variable);
+                assert_eq!(field.identifier.name, "totalSupply");
+                assert_eq!(&code[field.span.at..field.span.end()], "pub var totalSupply: UFix64");
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        match &contract.members[1] {
+            Declaration::Field(field) => {
+                assert_eq!(field.access, Access::Contract);
+                assert_eq!(field.variable_kind, VariableKind::Constant);
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        match &contract.members[2] {
+            Declaration::Interface(interface) => {
+                assert_eq!(interface.kind, CompositeKind::Resource);
+                assert_eq!(interface.identifier.name, "Provider");
+                match &interface.members[0] {
+                    Declaration::Function(function) => {
+                        assert!(function.body.is_none());
+                        assert!(function.return_type.as_ref().unwrap().is_resource);
+                    },
+                    other => panic!("unexpected {:?}", other),
+                }
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        match &contract.members[3] {
+            Declaration::Composite(vault) => {
+                assert_eq!(vault.kind, CompositeKind::Resource);
+                assert_eq!(vault.members.len(), 3);
+                match &vault.members[0] {
+                    Declaration::Field(field) => assert_eq!(field.access, Access::PubSet),
+                    other => panic!("unexpected {:?}", other),
+                }
+                match &vault.members[1] {
+                    Declaration::SpecialFunction(init) => {
+                        assert_eq!(init.kind, SpecialFunctionKind::Initializer);
+                        assert_eq!(init.function.parameters.len(), 1);
+                        assert_eq!(init.function.body.as_ref().unwrap().statements.len(), 1);
+                    },
+                    other => panic!("unexpected {:?}", other),
+                }
+                match &vault.members[2] {
+                    Declaration::SpecialFunction(destroy) => assert_eq!(destroy.kind, SpecialFunctionKind::Destructor),
+                    other => panic!("unexpected {:?}", other),
+                }
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        match &contract.members[4] {
+            Declaration::Composite(event) => {
+                assert_eq!(event.kind, CompositeKind::Event);
+                assert_eq!(event.parameters.len(), 2);
+                assert_eq!(event.parameters[1].identifier.name, "to");
+                assert!(matches!(event.parameters[1].type_annotation.ty, Type::Optional { .. }));
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        match &contract.members[5] {
+            Declaration::SpecialFunction(init) => {
+                let body = init.function.body.as_ref().unwrap();
+                match &body.statements[0] {
+                    Statement::Assignment { value, .. } => {
+                        assert!(matches!(value, Expression::FixedPoint { literal, .. } if literal == "0.0"));
+                    },
+                    other => panic!("unexpected {:?}", other),
+                }
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_enum() {
+        let code = "pub enum Color: UInt8 {\n    pub case red\n    pub case green\n    case blue\n}";
+        let program = parse(code).unwrap();
+        match &program.declarations[0] {
+            Declaration::Composite(color) => {
+                assert_eq!(color.kind, CompositeKind::Enum);
+                assert_eq!(color.raw_type().unwrap().name(), "UInt8");
+                let cases: Vec<_> = color.members.iter().map(|m| match m {
+                    Declaration::EnumCase(case) => (case.access.clone(), case.identifier.name.clone()),
+                    other => panic!("unexpected {:?}", other),
+                }).collect();
+                assert_eq!(cases, vec![
+                    (Access::Pub, "red".to_string()),
+                    (Access::Pub, "green".to_string()),
+                    (Access::NotSpecified, "blue".to_string()),
+                ]);
+                assert_eq!(color.members[2].span(), Span::new(64, 9));
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_nested_composites_and_types() {
+        let code = "
+access(all) contract Outer {
+    access(account) struct Inner {
+        access(self) let map: {String: [Int; 3]}
+        let cap: Capability<&Vault{Receiver}>?
+        let nested: Capability<Capability<&Outer.Inner>>
+        init() {}
+    }
+    pub struct interface Shape {}
+    pub contract interface Nested {}
+}";
+        let program = parse(code).unwrap();
+        let outer = match &program.declarations[0] {
+            Declaration::Composite(outer) => outer,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(outer.access, Access::All);
+        let inner = match &outer.members[0] {
+            Declaration::Composite(inner) => inner,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(inner.access, Access::Account);
+        assert_eq!(inner.kind, CompositeKind::Structure);
+
+        let field_types: Vec<_> = inner.members.iter().filter_map(|m| match m {
+            Declaration::Field(field) => Some((field.access.clone(), field.type_annotation.ty.clone())),
+            _ => None,
+        }).collect();
+        assert_eq!(field_types[0].0, Access::Self_);
+        assert!(matches!(&field_types[0].1, Type::Dictionary { value, .. } if matches!(**value, Type::ConstantSized { size: 3, .. })));
+        match &field_types[1].1 {
+            Type::Optional { ty, .. } => match &**ty {
+                Type::Instantiation { type_arguments, .. } => {
+                    assert!(matches!(&type_arguments[0].ty, Type::Reference { ty, .. } if matches!(**ty, Type::Restricted { .. })));
+                },
+                other => panic!("unexpected {:?}", other),
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(&field_types[2].1, Type::Instantiation { .. }));
+
+        assert!(matches!(&outer.members[1], Declaration::Interface(i) if i.kind == CompositeKind::Structure));
+        assert!(matches!(&outer.members[2], Declaration::Interface(i) if i.kind == CompositeKind::Contract));
+    }
+
+    #[test]
+    fn test_parse_return_type_before_body() {
+        let function = |code: &str| match parse(code).unwrap().declarations.remove(0) {
+            Declaration::Function(function) => function,
+            other => panic!("unexpected {:?}", other),
+        };
+
+        // braces right after the return type are the body, not restrictions
+        let empty = function("fun f(): Int{}");
+        assert!(matches!(empty.return_type.map(|annotation| annotation.ty), Some(Type::Nominal(nominal)) if nominal.name() == "Int"));
+        assert_eq!(empty.body.map(|body| body.statements.len()), Some(0));
+        let body = function("fun f(): Int{ return x }\nfun g() {}");
+        assert!(matches!(body.return_type.map(|annotation| annotation.ty), Some(Type::Nominal(_))));
+        assert_eq!(body.body.map(|body| body.statements.len()), Some(1));
+
+        // but restrictions followed by the body, or inside the return type, are
+        let restricted = function("fun f(): &R{I} {\n    return x\n}");
+        assert!(matches!(restricted.return_type.map(|annotation| annotation.ty), Some(Type::Reference { ty, .. }) if matches!(*ty, Type::Restricted { .. })));
+        assert_eq!(restricted.body.map(|body| body.statements.len()), Some(1));
+        let nested = function("fun f(): Capability<&R{I}>{ return x }");
+        assert!(matches!(nested.return_type.map(|annotation| annotation.ty), Some(Type::Instantiation { .. })));
+        assert_eq!(nested.body.map(|body| body.statements.len()), Some(1));
+
+        // braces holding only type names are restrictions, whatever follows them
+        let program = parse("pub resource interface P {\n    pub fun borrow(): &Vault{Balance}\n}").unwrap();
+        let borrow = match &program.declarations[0] {
+            Declaration::Interface(interface) => match &interface.members[0] {
+                Declaration::Function(function) => function.clone(),
+                other => panic!("unexpected {:?}", other),
+            },
+            other => panic!("unexpected {:?}", other),
+        };
+        assert!(matches!(borrow.return_type.map(|annotation| annotation.ty), Some(Type::Reference { ty, .. }) if matches!(*ty, Type::Restricted { .. })));
+        assert!(borrow.body.is_none());
+
+        let program = parse("let x: AnyStruct{} = 1").unwrap();
+        match &program.declarations[0] {
+            Declaration::Variable(variable) => {
+                let ty = variable.type_annotation.as_ref().map(|annotation| &annotation.ty);
+                assert!(matches!(ty, Some(Type::Restricted { restrictions, .. }) if restrictions.is_empty()));
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_second_transfer() {
+        let code = "fun deposit(token: @NFT) {\n    let old <- self.owned[token.id] <-! token\n    destroy old\n}";
+        let body = match parse(code).unwrap().declarations.remove(0) {
+            Declaration::Function(function) => function.body.unwrap(),
+            other => panic!("unexpected {:?}", other),
+        };
+        match &body.statements[0] {
+            Statement::Variable(variable) => {
+                assert_eq!(variable.transfer, Transfer::Move);
+                assert!(matches!(variable.value, Expression::Index { .. }));
+                assert!(matches!(&variable.second, Some((Transfer::MoveForce, token)) if matches!(&**token, Expression::Identifier(token) if token.name == "token")));
+                assert_eq!(&code[variable.span.at..variable.span.end()], "let old <- self.owned[token.id] <-! token");
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(&body.statements[1], Statement::Destroy { .. }));
+    }
+
+    #[test]
+    fn test_parse_type_arguments_lookahead() {
+        let value = |code: &str| match parse(code).unwrap().declarations.remove(0) {
+            Declaration::Variable(variable) => variable.value,
+            other => panic!("unexpected {:?}", other),
+        };
+        // looking for `<b>(` splits `>>`, which is restored when no invocation follows
+        match value("let x = a<b>>c") {
+            Expression::Binary { operation: BinaryOperation::Less, right, .. } => {
+                assert!(matches!(*right, Expression::Binary { operation: BinaryOperation::BitwiseShiftRight, .. }));
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(value("let x = f<Capability<&R>>(c)"), Expression::Invocation { .. }));
+    }
+
+    #[test]
+    fn test_parse_function_labels_and_conditions() {
+        let code = "
+pub resource Vault {
+    pub view fun getBalance(): UFix64 {
+        return self.balance
+    }
+
+    pub fun transfer(from: Address, to recipient: Address, _ amount: UFix64): Bool {
+        pre {
+            amount > 0.0: \"amount must be positive\"
+            recipient != from
+        }
+        post {
+            result
+            self.balance == before(self.balance) - amount: \"balance mismatch\"
+        }
+        return true
+    }
+}";
+        let program = parse(code).unwrap();
+        let vault = match &program.declarations[0] {
+            Declaration::Composite(vault) => vault,
+            other => panic!("unexpected {:?}", other),
+        };
+
+        match &vault.members[0] {
+            Declaration::Function(function) => {
+                assert_eq!(function.purity, Purity::View);
+                assert_eq!(function.identifier.name, "getBalance");
+                assert!(function.parameters.is_empty());
+                assert!(matches!(&function.return_type.as_ref().unwrap().ty, Type::Nominal(n) if n.name() == "UFix64"));
+                assert_eq!(function.body.as_ref().unwrap().statements.len(), 1);
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let transfer = match &vault.members[1] {
+            Declaration::Function(function) => function,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(transfer.purity, Purity::Impure);
+
+        let labels: Vec<_> = transfer.parameters.iter().map(|p| p.argument_label()).collect();
+        assert_eq!(labels, vec![Some("from"), Some("to"), None]);
+        let names: Vec<_> = transfer.parameters.iter().map(|p| p.identifier.name.as_str()).collect();
+        assert_eq!(names, vec!["from", "recipient", "amount"]);
+        assert!(transfer.parameters[0].label.is_none());
+
+        assert_eq!(transfer.pre_conditions.len(), 2);
+        assert!(matches!(&transfer.pre_conditions[0].message, Some(Expression::String { value, .. }) if value == "amount must be positive"));
+        assert!(matches!(&transfer.pre_conditions[0].test, Expression::Binary { operation: BinaryOperation::Greater, .. }));
+        assert!(transfer.pre_conditions[1].message.is_none());
+
+        assert_eq!(transfer.post_conditions.len(), 2);
+        match &transfer.post_conditions[1].test {
+            Expression::Binary { operation: BinaryOperation::Equal, right, .. } => match &**right {
+                Expression::Binary { operation: BinaryOperation::Minus, left, .. } => match &**left {
+                    Expression::Invocation { invoked, arguments, .. } => {
+                        assert!(matches!(&**invoked, Expression::Identifier(i) if i.name == "before"));
+                        assert!(matches!(&arguments[0].expression, Expression::Member { .. }));
+                    },
+                    other => panic!("unexpected {:?}", other),
+                },
+                other => panic!("unexpected {:?}", other),
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let body = transfer.body.as_ref().unwrap();
+        assert_eq!(body.statements.len(), 1);
+        assert!(matches!(&body.statements[0], Statement::Return { expression: Some(_), .. }));
+    }
+
+    #[test]
+    fn test_parse_transaction() {
+        let code = "
+import FungibleToken from 0xf233dcee88fe0abe
+
+transaction(amount: UFix64, to: Address) {
+    let vault: @FungibleToken.Vault
+
+    prepare(signer: AuthAccount) {
+        self.vault <- signer.borrow<&FungibleToken.Vault>(from: /storage/flowTokenVault)!.withdraw(amount: amount)
+    }
+
+    pre {
+        amount > 0.0
+    }
+
+    execute {
+        getAccount(to)
+    }
+
+    post {
+        true: \"unreachable\"
+    }
+}";
+        let program = parse(code).unwrap();
+        assert!(matches!(&program.declarations[0], Declaration::Import(i) if i.location == ImportLocation::Address("0xf233dcee88fe0abe".to_string())));
+        let transaction = match &program.declarations[1] {
+            Declaration::Transaction(transaction) => transaction,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(transaction.parameters.len(), 2);
+        assert_eq!(transaction.fields.len(), 1);
+        assert!(transaction.fields[0].type_annotation.is_resource);
+        let prepare = transaction.prepare.as_ref().unwrap();
+        assert_eq!(prepare.kind, SpecialFunctionKind::Prepare);
+        assert_eq!(prepare.function.parameters[0].identifier.name, "signer");
+        match &prepare.function.body.as_ref().unwrap().statements[0] {
+            Statement::Assignment { transfer: Transfer::Move, value, .. } => match value {
+                Expression::Invocation { invoked, arguments, .. } => {
+                    assert_eq!(arguments[0].label.as_ref().unwrap().name, "amount");
+                    match &**invoked {
+                        Expression::Member { expression, identifier, .. } => {
+                            assert_eq!(identifier.name, "withdraw");
+                            assert!(matches!(&**expression, Expression::Force { .. }));
+                        },
+                        other => panic!("unexpected {:?}", other),
+                    }
+                },
+                other => panic!("unexpected {:?}", other),
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(transaction.pre_conditions.len(), 1);
+        assert!(transaction.execute.is_some());
+        assert_eq!(transaction.post_conditions.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_statements() {
+        let code = "
+fun main(values: [Int], opt: @R?) {
+    if let r <- opt {
+        destroy r
+    } else if values.length > 0 {
+        return
+    } else {
+        emit Empty(count: 0)
+    }
+    while true { break }
+    for i, v in values { continue }
+    switch values[0] {
+        case 1, 2:
+            a = 1
+            b = 2
+        case 3:
+            a <-> b
+        default:
+            r <-! create R()
+    }
+    a <- b; c = d
+}";
+        let program = parse(code).unwrap();
+        let main = match &program.declarations[0] {
+            Declaration::Function(main) => main,
+            other => panic!("unexpected {:?}", other),
+        };
+        let statements = &main.body.as_ref().unwrap().statements;
+        assert_eq!(statements.len(), 6);
+
+        match &statements[0] {
+            Statement::If(if_statement) => {
+                match &if_statement.test {
+                    IfTest::Binding(binding) => {
+                        assert_eq!(binding.identifier.name, "r");
+                        assert_eq!(binding.transfer, Transfer::Move);
+                    },
+                    other => panic!("unexpected {:?}", other),
+                }
+                assert!(matches!(&if_statement.then.statements[0], Statement::Destroy { expression: Expression::Identifier(i), .. } if i.name == "r"));
+                match &if_statement.else_ {
+                    Some(ElseBranch::If(else_if)) => {
+                        assert!(matches!(&else_if.test, IfTest::Expression(_)));
+                        assert!(matches!(&else_if.then.statements[0], Statement::Return { expression: None, .. }));
+                        match &else_if.else_ {
+                            Some(ElseBranch::Block(block)) => {
+                                assert!(matches!(&block.statements[0], Statement::Emit { .. }));
+                            },
+                            other => panic!("unexpected {:?}", other),
+                        }
+                    },
+                    other => panic!("unexpected {:?}", other),
+                }
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+
+        match &statements[1] {
+            Statement::While { block, .. } => assert!(matches!(&block.statements[0], Statement::Break { .. })),
+            other => panic!("unexpected {:?}", other),
+        }
+        match &statements[2] {
+            Statement::For { index, identifier, block, span, .. } => {
+                assert_eq!(index.as_ref().unwrap().name, "i");
+                assert_eq!(identifier.name, "v");
+                assert!(matches!(&block.statements[0], Statement::Continue { .. }));
+                assert_eq!(&code[span.at..span.end()], "for i, v in values { continue }");
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        match &statements[3] {
+            Statement::Switch { expression, cases, .. } => {
+                assert!(matches!(expression, Expression::Index { .. }));
+                assert_eq!(cases.len(), 3);
+                assert_eq!(cases[0].expressions.len(), 2);
+                assert_eq!(cases[0].statements.len(), 2);
+                assert!(matches!(&cases[1].statements[0], Statement::Swap { .. }));
+                assert!(cases[2].is_default());
+                assert!(matches!(&cases[2].statements[0], Statement::Assignment { transfer: Transfer::MoveForce, value: Expression::Create { .. }, .. }));
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(&statements[4], Statement::Assignment { transfer: Transfer::Move, .. }));
+        assert!(matches!(&statements[5], Statement::Assignment { transfer: Transfer::Copy, .. }));
+
+        let error = parse("fun f() { a = 1 b = 2 }").unwrap_err();
+        assert_eq!(error.message, "statements on the same line must be separated with a semicolon");
+        assert_eq!(error.span, Span::new(16, 1));
+
+        let error = parse("fun f() { emit E }").unwrap_err();
+        assert_eq!(error.message, "expected event invocation after `emit`");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse("pub contract C {\n    pub let x\n}").unwrap_err();
+        assert_eq!(error.message, "expected `:`, got `}`");
+        assert_eq!(error.span, Span::new(31, 1));
+
+        let error = parse("pub resource R {").unwrap_err();
+        assert_eq!(error.message, "expected `}`, got end of file");
+    }
+
+    #[test]
+    fn test_parse_recovering() {
+        let code = "
+pub contract C {
+    pub let a: Int
+    pub let b
+    pub fun f(): Int {
+        let x = 1 +
+        let y = (2
+        return x
+    }
+    pub fun g() {}
+}
+}
+pub struct S {
+    pub let c: ;
+}";
+        let (program, errors) = parse_with_errors(code);
+        let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec![
+            "expected `:`, got `pub`",
+            "expected expression, got `let`",
+            "expected `)`, got `return`",
+            "expected declaration, got `}`",
+            "expected type, got `;`",
+        ]);
+        assert_eq!(&code[errors[3].span.at..errors[3].span.end()], "}");
+
+        assert_eq!(program.declarations.len(), 2);
+        let contract = match &program.declarations[0] {
+            Declaration::Composite(contract) => contract,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(contract.members.len(), 4);
+        assert!(matches!(&contract.members[0], Declaration::Field(_)));
+        match &contract.members[1] {
+            Declaration::Error(span) => assert_eq!(&code[span.at..span.end()], "pub let b"),
+            other => panic!("unexpected {:?}", other),
+        }
+        match &contract.members[2] {
+            Declaration::Function(f) => {
+                let statements = &f.body.as_ref().unwrap().statements;
+                assert_eq!(statements.len(), 3);
+                assert!(matches!(&statements[0], Statement::Error(_)));
+                assert!(matches!(&statements[1], Statement::Error(_)));
+                assert!(matches!(&statements[2], Statement::Return { .. }));
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(&contract.members[3], Declaration::Function(g) if g.identifier.name == "g"));
+
+        match &program.declarations[1] {
+            Declaration::Composite(s) => {
+                assert_eq!(s.identifier.name, "S");
+                assert!(matches!(&s.members[0], Declaration::Error(_)));
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let (program, errors) = parse_with_errors("fun f() {\n    if true {\n        x = 1\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "expected `}`, got end of file");
+        assert!(matches!(&program.declarations[0], Declaration::Function(_)));
+    }
+
+    #[test]
+    fn test_parse_unterminated() {
+        let (program, errors) = parse_with_errors("let x = \"abc");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "unterminated string literal");
+        assert_eq!(errors[0].span, Span::new(8, 4));
+        match &program.declarations[0] {
+            Declaration::Variable(variable) => {
+                assert!(matches!(&variable.value, Expression::String { value, .. } if value == "abc"));
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let error = parse("let x = \"é").unwrap_err();
+        assert_eq!(error.message, "unterminated string literal");
+
+        let (program, errors) = parse_with_errors("let x = 1\n/* note");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "unterminated block comment");
+        assert_eq!(errors[0].span, Span::new(10, 7));
+        assert_eq!(program.declarations.len(), 1);
+    }
+}
+
+#[cfg(test)]
+mod cst_tests {
+    use crate::ast::{
+        Declaration, Expression, Statement, Type,
+    };
+    use crate::cst::build::parse_cst;
+    use crate::cst::green::GreenToken;
+    use crate::cst::kind::SyntaxKind;
+    use crate::cst::nodes::{
+        CstNode, DeclarationNode, ExpressionNode, ProgramNode, TypeNode,
+    };
+    use crate::cst::syntax::SyntaxNode;
+    use crate::lexer::token::Keyword;
+
+    const CODE: &str = "// a counter\npub contract Counter {\n    /* the count */\n    pub var count: Int\n\n    pub fun add(by amount: Int, _ note: String?): Int {\n        self.count = self.count + amount // add\n        return self.count\n    }\n\n    let nested: {String: [Capability<&Vault{Receiver}>]}\n}\n";
+
+    #[test]
+    fn test_cst_round_trip() {
+        let (root, errors) = parse_cst(CODE);
+        assert!(errors.is_empty());
+        assert_eq!(root.kind(), SyntaxKind::Program);
+        assert_eq!(root.text(), CODE);
+
+        let tokens = root.descendant_tokens();
+        assert_eq!(tokens.iter().map(|token| token.text()).collect::<String>(), CODE);
+        assert_eq!(tokens[0].kind(), SyntaxKind::Comment);
+        assert_eq!(tokens[0].text(), "// a counter");
+        assert!(tokens.iter().any(|token| token.kind() == SyntaxKind::Comment && token.text() == "/* the count */"));
+        assert!(tokens.iter().any(|token| token.kind() == SyntaxKind::Comment && token.text() == "// add"));
+
+        // `>>` of nested generics is split so each type ends at its own `>`
+        let types: Vec<_> = root.descendants().into_iter()
+            .filter(|node| node.kind() == SyntaxKind::InstantiationType)
+            .collect();
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].text(), "Capability<&Vault{Receiver}>");
+
+        let source = "fun f() {\n    let x = [1, 2 +\n}\n";
+        let (root, errors) = parse_cst(source);
+        assert!(!errors.is_empty());
+        assert_eq!(root.text(), source);
+    }
+
+    #[test]
+    fn test_cst_nodes() {
+        let (root, _) = parse_cst(CODE);
+        let program = ProgramNode::cast(root.clone()).unwrap();
+        let declarations = program.declarations();
+        assert_eq!(declarations.len(), 1);
+
+        let contract = &declarations[0];
+        assert_eq!(contract.kind(), SyntaxKind::CompositeDeclaration);
+        assert_eq!(contract.identifier().unwrap().text(), "Counter");
+        // leading trivia belongs to the enclosing node
+        assert!(contract.text().starts_with("pub contract"));
+
+        let members = contract.members();
+        assert_eq!(members.len(), 3);
+        assert_eq!(members[0].kind(), SyntaxKind::FieldDeclaration);
+        assert_eq!(members[0].text(), "pub var count: Int");
+        assert_eq!(members[0].type_annotation().unwrap().text(), "Int");
+
+        let function = &members[1];
+        assert_eq!(function.identifier().unwrap().text(), "add");
+        let parameters = function.parameters();
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters[0].label().unwrap().text(), "by");
+        assert_eq!(parameters[0].name().unwrap().text(), "amount");
+        assert_eq!(parameters[1].label().unwrap().kind(), SyntaxKind::Identifier);
+        assert_eq!(parameters[1].name().unwrap().text(), "note");
+        let ty = parameters[1].type_annotation().unwrap().ty().unwrap();
+        assert_eq!(ty.kind(), SyntaxKind::OptionalType);
+        assert_eq!(function.type_annotation().unwrap().text(), "Int");
+
+        let statements = function.body().unwrap().statements();
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0].kind(), SyntaxKind::AssignmentStatement);
+        assert_eq!(statements[0].text(), "self.count = self.count + amount");
+        assert_eq!(statements[1].kind(), SyntaxKind::ReturnStatement);
+
+        let keyword = contract.syntax().tokens().into_iter()
+            .find(|token| token.kind() == SyntaxKind::Keyword(Keyword::Contract))
+            .unwrap();
+        assert_eq!(keyword.parent(), *contract.syntax());
+
+        let token = root.token_at_offset(CODE.find("amount //").unwrap()).unwrap();
+        assert_eq!(token.text(), "amount");
+        assert_eq!(token.parent().kind(), SyntaxKind::IdentifierExpression);
+        let node = root.covering_node(token.span());
+        assert_eq!(node.kind(), SyntaxKind::IdentifierExpression);
+        assert_eq!(node.ancestors().last().unwrap().kind(), SyntaxKind::Program);
+
+        // names are read from the tokens of the declaration, after its access modifier
+        let source = "access(contract) resource interface R: I {
+    access(E, F) var from: @{String: R}
+    access(all) enum Color: UInt8 { access(all) case red }
+    init() {}
+    pub(set) var x: Int
+    access(all) fun get(): Int {}
+}
+let r <- create R()";
+        let (root, _) = parse_cst(source);
+        let interface = DeclarationNode::cast(root.children()[0].clone()).unwrap();
+        assert_eq!(interface.identifier().unwrap().text(), "R");
+        let names: Vec<String> = interface.members().iter()
+            .map(|member| member.identifier().map_or(String::new(), |token| token.text().to_string()))
+            .collect();
+        assert_eq!(names, ["from", "Color", "init", "x", "get"]);
+        assert_eq!(interface.members()[1].members()[0].identifier().unwrap().text(), "red");
+        let variable = DeclarationNode::cast(root.children()[1].clone()).unwrap();
+        assert_eq!(variable.identifier().unwrap().text(), "r");
+    }
+
+    #[test]
+    fn test_cst_to_ast() {
+        let (root, _) = parse_cst(CODE);
+        let program = ProgramNode::cast(root.clone()).unwrap();
+        let ast = program.to_ast().unwrap();
+        assert_eq!(ast.declarations.len(), 1);
+
+        let function = &program.declarations()[0].members()[1];
+        match function.to_ast().unwrap() {
+            Declaration::Function(f) => {
+                assert_eq!(f.identifier.name, "add");
+                assert_eq!(f.identifier.span.at, CODE.find("add(").unwrap());
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let statement = &function.body().unwrap().statements()[0];
+        match statement.to_ast().unwrap() {
+            Statement::Assignment { value, .. } => assert!(matches!(value, Expression::Binary { .. })),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // a sub-expression converts on its own, without the rest of the binary expression
+        let left = root.descendants().into_iter()
+            .filter_map(ExpressionNode::cast)
+            .find(|node| node.text() == "self.count" && node.syntax().span().at > CODE.find(" = ").unwrap())
+            .unwrap();
+        assert!(matches!(left.to_ast().unwrap(), Expression::Member { .. }));
+
+        let ty = root.descendants().into_iter()
+            .filter_map(TypeNode::cast)
+            .find(|node| node.kind() == SyntaxKind::DictionaryType)
+            .unwrap();
+        assert!(matches!(ty.to_ast().unwrap(), Type::Dictionary { .. }));
+    }
+
+    #[test]
+    fn test_cst_edit() {
+        let (root, _) = parse_cst(CODE);
+        let token = root.descendant_tokens().into_iter().find(|token| token.text() == "Counter").unwrap();
+        let edited = SyntaxNode::new_root(token.replace_with(GreenToken::new(SyntaxKind::Identifier, "Tally")));
+        assert_eq!(edited.text(), CODE.replacen("Counter", "Tally", 1));
+
+        // untouched subtrees are shared between both trees
+        let before = &root.children()[0].children()[0];
+        let after = &edited.children()[0].children()[0];
+        assert!(std::ptr::eq(before.green(), after.green()));
+
+        let declaration = DeclarationNode::cast(edited.children()[0].clone()).unwrap();
+        assert_eq!(declaration.identifier().unwrap().text(), "Tally");
+        // the original tree is unchanged
+        assert_eq!(root.text(), CODE);
+    }
+}
+
+#[cfg(test)]
+mod visit_tests {
+    use crate::ast::{
+        BinaryOperation, Declaration, Expression, FunctionDeclaration, Identifier, Program, Statement,
+    };
+    use crate::ast::fold::{
+        self, Fold,
+    };
+    use crate::ast::visit::{
+        self, Visitor,
+    };
+    use crate::ast::visit_mut::VisitorMut;
+    use crate::parser::parser::parse;
+
+    const CODE: &str = "pub contract C {\n    pub resource R {}\n    pub fun make(amount: Int): @R {\n        let r <- create R()\n        if let x = self.lookup(key: amount) { log(x + 1 * 2) }\n        for i in [1, 2] { emit E(value: i as! Int) }\n        return <- r\n    }\n    pub fun lookup(key: Int): Int? {\n        switch key { case 1: return 10\n default: return nil }\n    }\n}\n";
+
+    #[derive(Default)]
+    struct Names {
+        functions: Vec<String>,
+        identifiers: Vec<String>,
+        integers: usize,
+    }
+
+    impl Visitor for Names {
+        fn visit_function_declaration(&mut self, declaration: &FunctionDeclaration) {
+            self.functions.push(declaration.identifier.name.clone());
+            visit::walk_function_declaration(self, declaration);
+        }
+
+        fn visit_expression(&mut self, expression: &Expression) {
+            if let Expression::Integer { .. } = expression {
+                self.integers += 1;
+            }
+            visit::walk_expression(self, expression);
+        }
+
+        fn visit_identifier(&mut self, identifier: &Identifier) {
+            self.identifiers.push(identifier.name.clone());
+        }
+    }
+
+    #[test]
+    fn test_visitor() {
+        let program = parse(CODE).unwrap();
+        let mut names = Names::default();
+        names.visit_program(&program);
+
+        assert_eq!(names.functions, vec!["make", "lookup"]);
+        assert_eq!(names.integers, 6);
+        for name in ["C", "R", "amount", "Int", "r", "x", "lookup", "key", "log", "i", "E", "value"] {
+            assert!(names.identifiers.iter().any(|identifier| identifier == name), "missing {}", name);
+        }
+    }
+
+    struct Rename;
+
+    impl VisitorMut for Rename {
+        fn visit_identifier_mut(&mut self, identifier: &mut Identifier) {
+            if identifier.name == "amount" {
+                identifier.name = "quantity".to_string();
+            }
+        }
+    }
+
+    #[test]
+    fn test_visitor_mut() {
+        let mut program = parse(CODE).unwrap();
+        Rename.visit_program_mut(&mut program);
+
+        let mut names = Names::default();
+        names.visit_program(&program);
+        assert!(!names.identifiers.iter().any(|identifier| identifier == "amount"));
+        assert_eq!(names.identifiers.iter().filter(|identifier| *identifier == "quantity").count(), 2);
+    }
+
+    /// folds `a * b` and `a + b` of integer literals
+    struct ConstantFolding;
+
+    impl Fold for ConstantFolding {
+        fn fold_expression(&mut self, expression: Expression) -> Expression {
+            match fold::walk_expression(self, expression) {
+                Expression::Binary { operation, left, right, span } => match (*left, *right) {
+                    (Expression::Integer { literal: a, .. }, Expression::Integer { literal: b, .. }) => {
+                        let (a, b): (i64, i64) = (a.parse().unwrap(), b.parse().unwrap());
+                        let value = match operation {
+                            BinaryOperation::Plus => a + b,
+                            BinaryOperation::Mul => a * b,
+                            _ => panic!("unexpected {:?}", operation),
+                        };
+                        Expression::Integer { literal: value.to_string(), span }
+                    },
+                    (left, right) => Expression::Binary { operation, left: Box::new(left), right: Box::new(right), span },
+                },
+                expression => expression,
+            }
+        }
+    }
+
+    fn function_body(program: &Program, index: usize) -> &[Statement] {
+        match &program.declarations[0] {
+            Declaration::Composite(c) => match &c.members[index] {
+                Declaration::Function(f) => &f.body.as_ref().unwrap().statements,
+                other => panic!("unexpected {:?}", other),
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_fold() {
+        let program = parse("pub contract C { pub fun f() { log(1 + 2 * 3); log(x + 1) } }").unwrap();
+        let folded = ConstantFolding.fold_program(program.clone());
+        assert_ne!(folded, program);
+
+        let statements = function_body(&folded, 0);
+        match &statements[0] {
+            Statement::Expression(Expression::Invocation { arguments, .. }) => {
+                assert!(matches!(&arguments[0].expression, Expression::Integer { literal, .. } if literal == "7"));
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(statements[1], function_body(&program, 0)[1]);
+
+        // the default fold rebuilds an identical tree
+        struct Identity;
+        impl Fold for Identity {}
+        let program = parse(CODE).unwrap();
+        assert_eq!(Identity.fold_program(program.clone()), program);
+    }
+}
+
+#[cfg(test)]
+mod printer_tests {
+    use crate::ast::{
+        Argument, BinaryOperation, CastingOperation, Expression, Identifier, NominalType, Program, Span, Statement,
+        Transfer, Type, TypeAnnotation, UnaryOperation, VariableDeclaration, VariableKind, Access,
+    };
+    use crate::ast::fold::Fold;
+    use crate::parser::parser::{
+        Parser, parse,
+    };
+    use crate::printer::printer::{
+        print_expression, print_program, print_statement, print_type,
+    };
+
+    /// positions differ between the original and the printed source
+    struct EraseSpans;
+
+    impl Fold for EraseSpans {
+        fn fold_span(&mut self, _span: Span) -> Span {
+            Span::default()
+        }
+    }
+
+    fn erase(program: Program) -> Program {
+        EraseSpans.fold_program(program)
+    }
+
+    fn parse_expression(source: &str) -> Expression {
+        let mut parser = Parser::new(source);
+        let expression = parser.parse_expression().unwrap_or_else(|e| panic!("{:?} in `{}`", e, source));
+        assert!(parser.errors().is_empty());
+        EraseSpans.fold_expression(expression)
+    }
+
+    fn identifier(name: &str) -> Expression {
+        Expression::Identifier(Identifier::new(name, Span::default()))
+    }
+
+    fn integer(literal: &str) -> Expression {
+        Expression::Integer { literal: literal.to_string(), span: Span::default() }
+    }
+
+    fn binary(operation: BinaryOperation, left: Expression, right: Expression) -> Expression {
+        Expression::Binary { operation, left: Box::new(left), right: Box::new(right), span: Span::default() }
+    }
+
+    fn nominal(name: &str) -> Type {
+        Type::Nominal(NominalType { identifier: Identifier::new(name, Span::default()), nested_identifiers: Vec::new() })
+    }
+
+    fn annotation(ty: Type) -> TypeAnnotation {
+        TypeAnnotation { is_resource: false, ty, span: Span::default() }
+    }
+
+    const CODE: &str = r#"import FungibleToken from 0x01
+import "Other"
+
+pub contract Token: FungibleToken {
+    pub var totalSupply: UFix64
+    pub event Deposit(amount: UFix64, to: Address?)
+    pub enum Kind: UInt8 { pub case a
+        pub case b }
+
+    pub resource interface Provider {
+        pub fun withdraw(amount: UFix64): @Vault {
+            post { result.balance == amount: "wrong \"amount\"" }
+        }
+    }
+
+    pub resource Vault: Provider, FungibleToken.Receiver {
+        pub(set) var balance: UFix64
+        init(balance: UFix64) { self.balance = balance }
+        pub fun withdraw(amount: UFix64): @Vault {
+            pre { amount <= self.balance }
+            self.balance = self.balance - amount
+            return <-create Vault(balance: amount)
+        }
+        pub view fun ids(_ of: [UInt64], by map: {String: &AnyResource{Provider}}): ((Int): Bool)?? {
+            let r <- self.vaults.remove(key: 0)!
+            let old <- self.owned[id] <- token
+            if let v = map["a"] { v.x() } else if !(a || b) && c { return nil } else { destroy r }
+            while i < 10 { i = i + 1; continue }
+            for i, x in [1, 2, 3] { switch x { case 1, 2: break
+                default: log(-(x + 1) * 2 ?? 3) } }
+            a <-> b
+            emit Deposit(amount: 1.5, to: nil)
+            let c = &self.vault as auth &Vault{Provider}
+            return (x as? Int ?? 0) > 1 ? f<Int, [String; 2]>(x)!.y?.z : cap.borrow<&Vault>()
+        }
+        destroy() {}
+    }
+}
+
+transaction(amount: UFix64) {
+    let vault: @Vault
+    prepare(signer: AuthAccount) { self.vault <- signer.load<@Vault>(from: /storage/vault)! }
+    pre { amount > 0.0 }
+    execute { log(self.vault) }
+    post { true }
+}
+"#;
+
+    #[test]
+    fn test_print_round_trip() {
+        let program = parse(CODE).unwrap();
+        let printed = print_program(&program);
+        let reparsed = parse(&printed).unwrap_or_else(|e| panic!("{:?}\n{}", e, printed));
+        assert_eq!(erase(reparsed.clone()), erase(program));
+        // printing is stable
+        assert_eq!(print_program(&reparsed), printed);
+        assert!(printed.contains("    pub resource Vault: Provider, FungibleToken.Receiver {\n        pub(set) var balance: UFix64\n\n        init(balance: UFix64) {\n            self.balance = balance\n        }\n"));
+        assert!(printed.contains("pre {\n                amount <= self.balance\n            }"));
+        assert!(printed.contains("\n            let old <- self.owned[id] <- token\n"));
+    }
+
+    #[test]
+    fn test_print_precedence() {
+        use BinaryOperation::*;
+        let a = || identifier("a");
+        let b = || identifier("b");
+        let c = || identifier("c");
+        let cases = vec![
+            (binary(Mul, binary(Plus, a(), b()), c()), "(a + b) * c"),
+            (binary(Plus, a(), binary(Mul, b(), c())), "a + b * c"),
+            (binary(Minus, a(), binary(Minus, b(), c())), "a - (b - c)"),
+            (binary(Minus, binary(Minus, a(), b()), c()), "a - b - c"),
+            (binary(NilCoalescing, a(), binary(NilCoalescing, b(), c())), "a ?? b ?? c"),
+            (binary(NilCoalescing, binary(NilCoalescing, a(), b()), c()), "(a ?? b) ?? c"),
+            (binary(Or, binary(And, a(), b()), c()), "a && b || c"),
+            (binary(And, binary(Or, a(), b()), c()), "(a || b) && c"),
+            (
+                Expression::Casting {
+                    expression: Box::new(binary(Plus, a(), b())),
+                    operation: CastingOperation::Failable,
+                    type_annotation: Box::new(annotation(Type::Optional { ty: Box::new(nominal("Int")), span: Span::default() })),
+                    span: Span::default(),
+                },
+                "(a + b) as? Int?",
+            ),
+            (
+                binary(NilCoalescing, Expression::Casting {
+                    expression: Box::new(a()),
+                    operation: CastingOperation::Static,
+                    type_annotation: Box::new(annotation(nominal("T"))),
+                    span: Span::default(),
+                }, b()),
+                "a as T ?? b",
+            ),
+            (
+                Expression::Unary {
+                    operation: UnaryOperation::Minus,
+                    expression: Box::new(Expression::Unary {
+                        operation: UnaryOperation::Minus,
+                        expression: Box::new(binary(Plus, a(), integer("1"))),
+                        span: Span::default(),
+                    }),
+                    span: Span::default(),
+                },
+                "- -(a + 1)",
+            ),
+            (
+                Expression::Member {
+                    expression: Box::new(integer("1")),
+                    optional: false,
+                    identifier: Identifier::new("x", Span::default()),
+                    span: Span::default(),
+                },
+                "(1).x",
+            ),
+            (
+                Expression::Conditional {
+                    test: Box::new(Expression::Conditional { test: Box::new(a()), then: Box::new(b()), else_: Box::new(c()), span: Span::default() }),
+                    then: Box::new(Expression::Conditional { test: Box::new(a()), then: Box::new(b()), else_: Box::new(c()), span: Span::default() }),
+                    else_: Box::new(c()),
+                    span: Span::default(),
+                },
+                "(a ? b : c) ? a ? b : c : c",
+            ),
+        ];
+        for (expression, expected) in cases {
+            let printed = print_expression(&expression);
+            assert_eq!(printed, expected);
+            assert_eq!(parse_expression(&printed), expression, "{}", printed);
+        }
+
+        let ty = Type::Reference {
+            authorized: true,
+            entitlements: None,
+            ty: Box::new(Type::Optional { ty: Box::new(nominal("T")), span: Span::default() }),
+            span: Span::default(),
+        };
+        assert_eq!(print_type(&ty), "auth &(T?)");
+
+        // a leading `-` would continue the previous line as a subtraction
+        let statement = Statement::Expression(Expression::Unary {
+            operation: UnaryOperation::Minus,
+            expression: Box::new(a()),
+            span: Span::default(),
+        });
+        assert_eq!(print_statement(&statement), "(-a)");
+        let statement = Statement::Variable(VariableDeclaration {
+            access: Access::NotSpecified,
+            kind: VariableKind::Constant,
+            identifier: Identifier::new("x", Span::default()),
+            type_annotation: None,
+            transfer: Transfer::Move,
+            value: Expression::Create {
+                invocation: Box::new(Expression::Invocation {
+                    invoked: Box::new(identifier("R")),
+                    type_arguments: Vec::new(),
+                    arguments: vec![Argument { label: Some(Identifier::new("x", Span::default())), expression: integer("1") }],
+                    span: Span::default(),
+                }),
+                span: Span::default(),
+            },
+            second: None,
+            span: Span::default(),
+        });
+        assert_eq!(print_statement(&statement), "let x <- create R(x: 1)");
+    }
+
+    /// xorshift, deterministic so failures reproduce
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    fn random_type(random: &mut Random, depth: usize) -> Type {
+        let span = Span::default();
+        let choice = if depth == 0 { 0 } else { random.next(8) };
+        match choice {
+            0 => nominal(["Int", "String", "R", "T"][random.next(4)]),
+            1 => Type::Optional { ty: Box::new(random_type(random, depth - 1)), span },
+            2 => Type::VariableSized { ty: Box::new(random_type(random, depth - 1)), span },
+            3 => Type::ConstantSized { ty: Box::new(random_type(random, depth - 1)), size: 3, span },
+            4 => Type::Dictionary { key: Box::new(random_type(random, depth - 1)), value: Box::new(random_type(random, depth - 1)), span },
+            5 => Type::Reference { authorized: random.next(2) == 0, entitlements: None, ty: Box::new(random_type(random, depth - 1)), span },
+            6 => Type::Instantiation {
+                ty: Box::new(nominal("Capability")),
+                type_arguments: vec![annotation(random_type(random, depth - 1))],
+                span,
+            },
+            _ => Type::Restricted {
+                ty: if random.next(2) == 0 { Some(Box::new(nominal("R"))) } else { None },
+                restrictions: vec![NominalType { identifier: Identifier::new("I", span), nested_identifiers: Vec::new() }],
+                span,
+            },
+        }
+    }
+
+    fn random_expression(random: &mut Random, depth: usize) -> Expression {
+        let span = Span::default();
+        let choice = if depth == 0 { random.next(6) } else { random.next(22) };
+        let sub = |random: &mut Random| Box::new(random_expression(random, depth.saturating_sub(1)));
+        match choice {
+            0 => identifier(["a", "b", "self", "account"][random.next(4)]),
+            1 => integer(["1", "42", "0x01"][random.next(3)]),
+            2 => Expression::FixedPoint { literal: "1.5".to_string(), span },
+            3 => Expression::String { value: "a \"b\"\n\\".to_string(), span },
+            4 => [Expression::Nil { span }, Expression::Bool { value: true, span }][random.next(2)].clone(),
+            5 => Expression::Path {
+                domain: Identifier::new("storage", span),
+                identifier: Identifier::new("vault", span),
+                span,
+            },
+            6 => Expression::Unary {
+                operation: [UnaryOperation::Minus, UnaryOperation::Not, UnaryOperation::Move][random.next(3)],
+                expression: sub(random),
+                span,
+            },
+            7..=10 => {
+                use BinaryOperation::*;
+                let operations = [
+                    Or, And, Equal, NotEqual, Less, LessEqual, Greater, GreaterEqual, NilCoalescing, BitwiseOr,
+                    BitwiseXor, BitwiseAnd, BitwiseShiftLeft, BitwiseShiftRight, Plus, Minus, Mul, Div, Mod,
+                ];
+                binary(operations[random.next(operations.len())], *sub(random), *sub(random))
+            },
+            11 => Expression::Conditional { test: sub(random), then: sub(random), else_: sub(random), span },
+            12 => Expression::Casting {
+                expression: sub(random),
+                operation: [CastingOperation::Static, CastingOperation::Failable, CastingOperation::Force][random.next(3)],
+                type_annotation: Box::new(TypeAnnotation { is_resource: random.next(2) == 0, ty: random_type(random, 2), span }),
+                span,
+            },
+            13 => Expression::Member {
+                expression: sub(random),
+                optional: random.next(2) == 0,
+                identifier: Identifier::new(["x", "balance", "from"][random.next(3)], span),
+                span,
+            },
+            14 => Expression::Index { expression: sub(random), index: sub(random), span },
+            15 | 16 => Expression::Invocation {
+                invoked: sub(random),
+                type_arguments: if random.next(3) == 0 { vec![annotation(random_type(random, 1))] } else { Vec::new() },
+                arguments: (0..random.next(3)).map(|_| Argument {
+                    label: if random.next(2) == 0 { Some(Identifier::new("to", span)) } else { None },
+                    expression: *sub(random),
+                }).collect(),
+                span,
+            },
+            17 => Expression::Create {
+                invocation: Box::new(Expression::Invocation {
+                    invoked: Box::new(identifier("R")),
+                    type_arguments: Vec::new(),
+                    arguments: vec![Argument { label: None, expression: *sub(random) }],
+                    span,
+                }),
+                span,
+            },
+            18 => Expression::Reference {
+                expression: sub(random),
+                ty: if random.next(2) == 0 { Some(Box::new(random_type(random, 2))) } else { None },
+                span,
+            },
+            19 => Expression::Force { expression: sub(random), span },
+            20 => Expression::Array { values: (0..random.next(3)).map(|_| *sub(random)).collect(), span },
+            _ => Expression::Dictionary { entries: (0..random.next(3)).map(|_| (*sub(random), *sub(random))).collect(), span },
+        }
+    }
+
+    fn random_statement(random: &mut Random, depth: usize) -> Statement {
+        let span = Span::default();
+        match random.next(5) {
+            0 => Statement::Expression(random_expression(random, depth)),
+            1 => Statement::Assignment {
+                target: random_expression(random, depth),
+                transfer: [Transfer::Copy, Transfer::Move, Transfer::MoveForce][random.next(3)],
+                value: random_expression(random, depth),
+                span,
+            },
+            2 => Statement::Swap { left: random_expression(random, depth), right: random_expression(random, depth), span },
+            3 => Statement::Variable(VariableDeclaration {
+                access: Access::NotSpecified,
+                kind: VariableKind::Variable,
+                identifier: Identifier::new("v", span),
+                type_annotation: if random.next(2) == 0 { Some(annotation(random_type(random, 2))) } else { None },
+                transfer: Transfer::Copy,
+                value: random_expression(random, depth),
+                second: if random.next(4) == 0 { Some((Transfer::Move, Box::new(random_expression(random, depth)))) } else { None },
+                span,
+            }),
+            _ => Statement::Return { expression: Some(random_expression(random, depth)), span },
+        }
+    }
+
+    #[test]
+    fn test_print_random_round_trip() {
+        let mut random = Random(0x2545f4914f6cdd1d);
+        for _ in 0..2000 {
+            let expression = random_expression(&mut random, 4);
+            let printed = print_expression(&expression);
+            assert_eq!(parse_expression(&printed), expression, "{}", printed);
+        }
+
+        for _ in 0..300 {
+            let statements: Vec<_> = (0..4).map(|_| random_statement(&mut random, 3)).collect();
+            let source = format!(
+                "fun f(): T {{\n{}\n}}\n",
+                statements.iter().map(print_statement).collect::<Vec<_>>().join("\n"),
+            );
+            let program = erase(parse(&source).unwrap_or_else(|e| panic!("{:?}\n{}", e, source)));
+            let body = match &program.declarations[0] {
+                crate::ast::Declaration::Function(f) => f.body.clone().unwrap().statements,
+                other => panic!("unexpected {:?}", other),
+            };
+            assert_eq!(body, statements, "{}", source);
+        }
+    }
+}
+
+#[cfg(test)]
+mod format_tests {
+    use crate::format::config::{
+        Config, ConfigError,
+    };
+    use crate::format::format::format_source;
+
+    fn format(source: &str) -> String {
+        format_source(source, &Config::default()).unwrap_or_else(|e| panic!("{:?}", e))
+    }
+
+    #[test]
+    fn test_format() {
+        let source = r#"
+// comment before the import
+import Token from 0x01
+pub contract C { // opening
+    pub var total: UFix64   // trailing
+    pub let name: String
+
+
+    /* doc
+       comment */
+    pub fun f(a: Int): Int {
+        let x = a+1 // one
+
+        // before return
+        return x*2
+        // at the end
+    }
+}
+"#;
+        let expected = r#"// comment before the import
+import Token from 0x01
+
+pub contract C { // opening
+    pub var total: UFix64 // trailing
+    pub let name: String
+
+    /* doc
+       comment */
+    pub fun f(a: Int): Int {
+        let x = a + 1 // one
+
+        // before return
+        return x * 2
+        // at the end
+    }
+}
+"#;
+        assert_eq!(format(source), expected);
+        assert_eq!(format(expected), expected);
+    }
+
+    #[test]
+    fn test_format_wrapping() {
+        let config = Config { max_width: 40, tab_spaces: 2, hard_tabs: false };
+        let source = "fun transfer(from: Address, to: Address, amount: UFix64) {\n\
+            if amount > 0.0 && from != to && balance(from) >= amount { send(from: from, to: to, amount: amount) }\n}";
+        let expected = "fun transfer(
+  from: Address,
+  to: Address,
+  amount: UFix64,
+) {
+  if amount > 0.0 &&
+    from != to &&
+    balance(from) >= amount {
+    send(
+      from: from,
+      to: to,
+      amount: amount,
+    )
+  }
+}
+";
+        let formatted = format_source(source, &config).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_source(&formatted, &config).unwrap(), expected);
+
+        let tabs = Config { hard_tabs: true, ..Config::default() };
+        assert_eq!(format_source("fun f() { g() }", &tabs).unwrap(), "fun f() {\n\tg()\n}\n");
+
+        // condition messages that do not fit go on their own line, and stay there
+        let source = "fun withdraw(amount: UFix64) {\n\
+            pre { amount <= self.balance: \"amount must not exceed the balance\" amount > 0.0: \"positive\" }\n}";
+        let expected = "fun withdraw(amount: UFix64) {
+  pre {
+    amount <= self.balance:
+      \"amount must not exceed the balance\"
+    amount > 0.0: \"positive\"
+  }
+}
+";
+        let formatted = format_source(source, &config).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_source(&formatted, &config).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_format_trailing_comments() {
+        let source = r#"
+fun f(a: Int, // first
+      b: Int): Int { // body
+    let x = 1 // note
+    let xs = [1, // one
+        2 // two
+    ]
+    return g(x: x, // x
+        y: xs[0])
+}
+transaction { // tx
+    prepare(signer: &Account) { // prepare
+        log(1) // log
+    } // after prepare
+}
+"#;
+        let expected = r#"fun f(
+    a: Int, // first
+    b: Int,
+): Int { // body
+    let x = 1 // note
+    let xs = [
+        1, // one
+        2, // two
+    ]
+    return g(
+        x: x, // x
+        y: xs[0],
+    )
+}
+
+transaction { // tx
+    prepare(signer: &Account) { // prepare
+        log(1) // log
+    } // after prepare
+}
+"#;
+        assert_eq!(format(source), expected);
+        assert_eq!(format(expected), expected);
+    }
+
+    #[test]
+    fn test_format_errors() {
+        assert!(format_source("fun f( {", &Config::default()).is_err());
+        assert!(format_source("let x = \"abc", &Config::default()).is_err());
+        assert!(format_source("let x = 1 /* note", &Config::default()).is_err());
+        assert_eq!(format(""), "");
+        assert_eq!(format("// only a comment\n"), "// only a comment\n");
+    }
+
+    #[test]
+    fn test_config() {
+        let config = Config::parse("# formatter settings\nmax_width = 80\nhard_tabs = true # tabs\n").unwrap();
+        assert_eq!(config, Config { max_width: 80, tab_spaces: 4, hard_tabs: true });
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+
+        assert_eq!(Config::parse("\nindent = 2").unwrap_err(), ConfigError::new("unknown option `indent`", 2));
+        assert_eq!(
+            Config::parse("max_width = 0").unwrap_err(),
+            ConfigError::new("expected a positive integer for `max_width`, got `0`", 1),
+        );
+        assert!(Config::parse("[format]").is_err());
+    }
+}
+
+#[cfg(test)]
+mod resolver_tests {
+    use crate::checker::diagnostic::Severity;
+    use crate::checker::resolver::{
+        Resolution, resolve,
+    };
+    use crate::checker::scope::{
+        Namespace, ScopeKind, SymbolKind,
+    };
+    use crate::parser::parser::parse;
+
+    fn resolve_source(source: &str) -> Resolution {
+        resolve(&parse(source).unwrap_or_else(|e| panic!("{:?}", e)))
+    }
+
+    /// offset of the `nth` occurrence of `needle`
+    fn offset(source: &str, needle: &str, nth: usize) -> usize {
+        source.match_indices(needle).nth(nth).unwrap_or_else(|| panic!("no {} `{}`", nth, needle)).0
+    }
+
+    /// the declaration the identifier at the `nth` occurrence of `needle` refers to
+    fn definition(resolution: &Resolution, source: &str, needle: &str, nth: usize) -> (SymbolKind, Option<usize>) {
+        let symbol = resolution.symbol_at(offset(source, needle, nth)).unwrap_or_else(|| panic!("`{}` is not bound", needle));
+        let symbol = resolution.symbol(symbol);
+        (symbol.kind, symbol.span.map(|span| span.at))
+    }
+
+    const CODE: &str = r#"
+import FungibleToken from 0x01
+
+pub contract Token: FungibleToken {
+    pub var totalSupply: UFix64
+
+    pub resource Vault: FungibleToken.Receiver {
+        pub var balance: UFix64
+
+        init(balance: UFix64) {
+            self.balance = balance
+        }
+
+        pub fun withdraw(amount: UFix64): @Vault {
+            post {
+                result.balance == amount: "wrong amount"
+                self.balance == before(self.balance) - amount
+            }
+            self.balance = self.balance - amount
+            return <-create Vault(balance: amount)
+        }
+    }
+
+    pub fun createEmptyVault(): @Vault {
+        return <-create Vault(balance: 0.0)
+    }
+
+    init() {
+        self.totalSupply = total()
+    }
+}
+
+pub fun total(): UFix64 {
+    let values = [1.0, 2.0]
+    var sum = 0.0
+    for index, value in values {
+        if let half = value / 2.0 {
+            sum = sum + half
+        }
+    }
+    return sum
+}
+
+transaction(amount: UFix64) {
+    let vault: @Token.Vault
+
+    prepare(signer: AuthAccount) {
+        self.vault <- signer.borrow<&Token.Vault>(from: /storage/vault)!.withdraw(amount: amount)
+    }
+
+    execute {
+        log(amount)
+        destroy self.vault
+    }
+}
+"#;
+
+    #[test]
+    fn test_resolve() {
+        let resolution = resolve_source(CODE);
+        assert_eq!(resolution.diagnostics, vec![]);
+
+        let import = offset(CODE, "FungibleToken", 0);
+        assert_eq!(definition(&resolution, CODE, "FungibleToken", 1), (SymbolKind::Import, Some(import)));
+        assert_eq!(definition(&resolution, CODE, "FungibleToken", 2), (SymbolKind::Import, Some(import)));
+
+        // the parameter, not the field
+        let parameter = offset(CODE, "balance: UFix64", 1);
+        assert_eq!(definition(&resolution, CODE, "balance\n", 0), (SymbolKind::Parameter, Some(parameter)));
+        assert_eq!(definition(&resolution, CODE, "self", 0), (SymbolKind::SelfValue, None));
+        assert_eq!(definition(&resolution, CODE, "result", 0), (SymbolKind::PostCondition, None));
+        assert_eq!(definition(&resolution, CODE, "before", 0), (SymbolKind::PostCondition, None));
+
+        // nested composites are visible in the whole contract, also before their declaration
+        let vault = offset(CODE, "Vault", 0);
+        let resource = SymbolKind::Composite(crate::ast::CompositeKind::Resource);
+        assert_eq!(definition(&resolution, CODE, "Vault", 1), (resource, Some(vault)));
+        assert_eq!(definition(&resolution, CODE, "Vault(balance: 0.0", 0), (resource, Some(vault)));
+        assert_eq!(definition(&resolution, CODE, "total()", 0), (SymbolKind::Function, Some(offset(CODE, "total()", 1))));
+
+        assert_eq!(definition(&resolution, CODE, "half\n", 0), (SymbolKind::Constant, Some(offset(CODE, "half", 0))));
+        assert_eq!(definition(&resolution, CODE, "values {", 0), (SymbolKind::Constant, Some(offset(CODE, "values", 0))));
+        assert_eq!(definition(&resolution, CODE, "signer.", 0), (SymbolKind::Signer, Some(offset(CODE, "signer", 0))));
+        assert_eq!(definition(&resolution, CODE, "amount)\n", 1), (SymbolKind::Parameter, Some(offset(CODE, "amount: UFix64) {", 0))));
+        assert_eq!(definition(&resolution, CODE, "log", 0), (SymbolKind::BuiltInFunction, None));
+        assert_eq!(definition(&resolution, CODE, "AuthAccount", 0), (SymbolKind::BuiltInType, None));
+
+        let sum = resolution.symbol_at(offset(CODE, "sum", 0)).unwrap();
+        let uses: Vec<_> = resolution.references_to(sum).iter().map(|span| span.at).collect();
+        assert_eq!(uses, vec![offset(CODE, "sum", 1), offset(CODE, "sum", 2), offset(CODE, "sum", 3)]);
+    }
+
+    #[test]
+    fn test_resolve_scopes() {
+        let resolution = resolve_source(CODE);
+        let scope = resolution.scope_at(offset(CODE, "sum + half", 0));
+        assert_eq!(resolution.scope(scope).kind, ScopeKind::Block);
+        assert!(resolution.lookup(scope, "half", Namespace::Value).is_some());
+        assert!(resolution.lookup(scope, "index", Namespace::Value).is_some());
+        assert!(resolution.lookup(scope, "Vault", Namespace::Type).is_none());
+
+        // members are only reachable through `self`
+        let scope = resolution.scope_at(offset(CODE, "self.balance = self", 0));
+        assert_eq!(resolution.scope(scope).kind, ScopeKind::Function);
+        assert!(resolution.lookup(scope, "totalSupply", Namespace::Value).is_none());
+        assert!(resolution.lookup(scope, "withdraw", Namespace::Value).is_none());
+        assert!(resolution.lookup(scope, "Vault", Namespace::Value).is_some());
+        assert!(resolution.lookup(scope, "FungibleToken", Namespace::Type).is_some());
+    }
+
+    #[test]
+    fn test_resolve_diagnostics() {
+        let source = r#"
+pub struct interface I {}
+
+pub struct S: I, J {
+    pub let a: Int
+    pub let a: String
+    pub fun f(a: Int, a: Int) {}
+}
+
+pub fun g(x: Int): Unknown {
+    let y = z
+    let y = x
+    if let x = maybe(x) {
+        let I = 1
+    }
+    return I
+}
+
+pub fun g() {}
+"#;
+        let resolution = resolve_source(source);
+        let diagnostics: Vec<_> = resolution.diagnostics.iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.message.as_str(), &source[diagnostic.span.at..diagnostic.span.end()]))
+            .collect();
+        assert_eq!(diagnostics, vec![
+            (Severity::Error, "cannot redeclare `g`: it is already declared as function in this scope", "g"),
+            (Severity::Error, "cannot find type in this scope: `J`", "J"),
+            (Severity::Error, "cannot redeclare `a`: it is already declared as field in this scope", "a"),
+            (Severity::Error, "cannot redeclare `a`: it is already declared as parameter in this scope", "a"),
+            (Severity::Error, "cannot find type in this scope: `Unknown`", "Unknown"),
+            (Severity::Error, "cannot find variable in this scope: `z`", "z"),
+            (Severity::Error, "cannot redeclare `y`: it is already declared as constant in this scope", "y"),
+            (Severity::Error, "cannot find variable in this scope: `maybe`", "maybe"),
+            (Severity::Warning, "`x` shadows the parameter of the same name", "x"),
+            (Severity::Error, "cannot find variable in this scope: `I`", "I"),
+        ]);
+        assert_eq!(resolution.errors().count(), 9);
+    }
+}
+
+#[cfg(test)]
+mod checker_tests {
+    use crate::checker::checker::{
+        TypeCheck, check,
+    };
+    use crate::checker::diagnostic::Severity;
+    use crate::checker::types::{
+        NumberType, Type,
+    };
+    use crate::parser::parser::parse;
+
+    fn check_source(source: &str) -> TypeCheck {
+        check(&parse(source).unwrap_or_else(|e| panic!("{:?}", e)))
+    }
+
+    /// severity, message and the source text each diagnostic points at
+    fn diagnostics<'s>(source: &'s str, checked: &TypeCheck) -> Vec<(Severity, String, &'s str)> {
+        checked.diagnostics.iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.message.clone(), &source[diagnostic.span.at..diagnostic.span.end()]))
+            .collect()
+    }
+
+    fn errors(source: &str) -> Vec<(String, &str)> {
+        let checked = check_source(source);
+        diagnostics(source, &checked).into_iter()
+            .filter(|(severity, ..)| *severity == Severity::Error)
+            .map(|(_, message, text)| (message, text))
+            .collect()
+    }
+
+    /// the type of the value of the variable declared by `let <name> =`
+    fn type_of(checked: &TypeCheck, source: &str, name: &str) -> String {
+        let at = source.find(&format!("let {} ", name)).unwrap_or_else(|| panic!("no `{}`", name)) + 4;
+        let symbol = checked.resolution.symbol_at(at).unwrap();
+        checked.symbol_type(symbol).unwrap_or_else(|| panic!("`{}` has no type", name)).to_string()
+    }
+
+    const CODE: &str = r#"
+pub contract Token {
+    pub var totalSupply: UFix64
+
+    pub event Deposit(amount: UFix64, to: Address?)
+
+    pub resource interface Receiver {
+        pub fun deposit(from: @Vault)
+    }
+
+    pub resource Vault: Receiver {
+        pub var balance: UFix64
+
+        init(balance: UFix64) {
+            self.balance = balance
+        }
+
+        pub fun withdraw(amount: UFix64): @Vault {
+            pre {
+                amount <= self.balance: "insufficient balance"
+            }
+            post {
+                result.balance == amount
+                self.balance == before(self.balance) - amount
+            }
+            self.balance = self.balance - amount
+            return <-create Vault(balance: amount)
+        }
+
+        pub fun deposit(from: @Vault) {
+            self.balance = self.balance + from.balance
+            emit Deposit(amount: from.balance, to: self.owner?.address)
+            destroy from
+        }
+    }
+
+    pub enum Color: UInt8 {
+        pub case red
+        pub case green
+    }
+
+    pub fun createEmptyVault(): @Vault {
+        return <-create Vault(balance: 0.0)
+    }
+
+    pub fun color(raw: UInt8): Color {
+        if let color = Color(rawValue: raw) {
+            return color
+        }
+        return Color.red
+    }
+
+    init() {
+        self.totalSupply = 1000.0
+        let vault <- create Vault(balance: self.totalSupply)
+        self.account.save(<-vault, to: /storage/vault)
+        self.account.link<&{Receiver}>(/public/receiver, target: /storage/vault)
+    }
+}
+
+pub fun sum(_ values: [Int8]): Int {
+    var total = 0
+    for index, value in values {
+        total = total + Int(value) * index
+    }
+    return total
+}
+
+transaction(amount: UFix64) {
+    let vault: @Token.Vault
+
+    prepare(signer: AuthAccount) {
+        self.vault <- signer.borrow<&Token.Vault>(from: /storage/vault)!.withdraw(amount: amount)
+    }
+
+    execute {
+        let receiver = getAccount(0x01).getCapability(/public/receiver).borrow<&{Token.Receiver}>()
+            ?? panic("no receiver")
+        receiver.deposit(from: <-self.vault)
+        log(sum([1, -2, 3]))
+    }
+}
+"#;
+
+    #[test]
+    fn test_check() {
+        let checked = check_source(CODE);
+        assert_eq!(diagnostics(CODE, &checked), vec![]);
+
+        assert_eq!(type_of(&checked, CODE, "vault"), "Token.Vault");
+        assert_eq!(type_of(&checked, CODE, "receiver"), "&{Token.Receiver}");
+        assert_eq!(type_of(&checked, CODE, "color"), "Token.Color");
+
+        let vault = checked.types.composite("Token.Vault").unwrap();
+        assert!(vault.is_resource());
+        assert_eq!(vault.conformances, vec!["Token.Receiver".to_string()]);
+        assert!(checked.types.is_subtype(&Type::Composite("Token.Vault".into()), &Type::Interface("Token.Receiver".into())));
+        assert_eq!(checked.types.composite("Token").unwrap().nested.len(), 4);
+
+        let total = CODE.find("total + Int(value)").unwrap();
+        let span = crate::ast::Span::new(total, "total + Int(value) * index".len());
+        assert_eq!(checked.expression_types.get(&span), Some(&Type::Number(NumberType::Int)));
+    }
+
+    #[test]
+    fn test_check_errors() {
+        let source = r#"
+pub struct S {
+    pub let name: String
+    pub let r: @R
+
+    init() {
+        self.name = 1
+    }
+
+    pub fun f(x: Int): Int {
+        if x > 0 {
+            return x
+        }
+    }
+}
+
+pub resource R {}
+
+pub fun g(a: Int, label b: String) {}
+
+pub fun h(): String {
+    let r = R()
+    let s <- create S()
+    g(1, b: "2")
+    g(a: 1, label: "2", 3)
+    let x: Int = "x"
+    let y = 1 + "y"
+    let z = nil
+    let n: String? = nil
+    let l = n.length
+    x = 2
+    destroy s
+    return n ?? 1
+}
+"#;
+        assert_eq!(errors(source), vec![
+            ("struct `S` cannot have resource field `r`".to_string(), "r"),
+            ("mismatched types: expected `String`, got `Int`".to_string(), "1"),
+            ("missing return statement".to_string(), "f"),
+            ("missing initialization of field `r` in type `S`".to_string(), "r"),
+            ("cannot create resource `R` without `create`".to_string(), "R()"),
+            ("cannot `create` non-resource type `S`".to_string(), "S()"),
+            ("missing argument label `a`".to_string(), "1"),
+            ("incorrect argument label: expected `label`, got `b`".to_string(), "b"),
+            ("incorrect number of arguments: expected 2, got 3".to_string(), "g(a: 1, label: \"2\", 3)"),
+            ("mismatched types: expected `Int`, got `String`".to_string(), "\"x\""),
+            ("cannot apply `+` to `Int` and `String`".to_string(), "1 + \"y\""),
+            ("cannot infer the type of `nil`, add a type annotation".to_string(), "nil"),
+            ("cannot access member `length` of optional type `String?`, use `?.`".to_string(), "length"),
+            ("cannot assign to constant `x`".to_string(), "x"),
+            ("cannot destroy non-resource type `S`".to_string(), "s"),
+            ("mismatched types: expected `String`, got `Int`".to_string(), "1"),
+        ]);
+    }
+
+    #[test]
+    fn test_check_type_requirements() {
+        let source = r#"
+pub contract interface FungibleToken {
+    pub resource Vault {
+        pub var balance: UFix64
+
+        init(balance: UFix64)
+
+        pub fun withdraw(amount: UFix64): @Vault {
+            post {
+                result.balance == amount: "incorrect amount"
+            }
+        }
+    }
+}
+
+pub contract C {
+    pub resource Vault {
+        pub var balance: UFix64
+
+        init(balance: UFix64)
+
+        pub fun withdraw(amount: UFix64): @Vault {
+            post {
+                result.balance == amount: "incorrect amount"
+            }
+        }
+    }
+}
+"#;
+        // only the concrete composite must initialize its fields and return
+        assert_eq!(errors(source), vec![
+            ("missing return statement".to_string(), "withdraw"),
+            ("missing initialization of field `balance` in type `C.Vault`".to_string(), "balance"),
+        ]);
+    }
+
+    #[test]
+    fn test_check_numbers() {
+        let source = r#"
+pub fun f() {
+    let a: UInt8 = 255
+    let b: UInt8 = 256
+    let c: Int8 = -128
+    let d: Int8 = -129
+    let e = 1.5
+    let f = -1.5
+    let g: UFix64 = 1.123456789
+    let h: UInt8 = -1
+    let i = a + 1
+    let j = 1 + a
+    let k = a + c
+    let l = 0x01 as Address
+    let m = 1.0 & 2.0
+    let n = 3 % 2 == 1
+    let o: Fix64 = 1.0 + f
+}
+"#;
+        let checked = check_source(source);
+        assert_eq!(type_of(&checked, source, "e"), "UFix64");
+        assert_eq!(type_of(&checked, source, "f"), "Fix64");
+        assert_eq!(type_of(&checked, source, "i"), "UInt8");
+        assert_eq!(type_of(&checked, source, "j"), "UInt8");
+        assert_eq!(type_of(&checked, source, "l"), "Address");
+        assert_eq!(type_of(&checked, source, "n"), "Bool");
+        assert_eq!(errors(source), vec![
+            ("integer literal `256` is out of range for `UInt8`".to_string(), "256"),
+            ("integer literal `-129` is out of range for `Int8`".to_string(), "129"),
+            ("fixed-point literal `1.123456789` has more than 8 fractional digits".to_string(), "1.123456789"),
+            ("cannot negate unsigned type `UInt8`".to_string(), "-1"),
+            ("cannot apply `+` to `UInt8` and `Int8`".to_string(), "a + c"),
+            ("cannot apply `&` to `UFix64`".to_string(), "1.0 & 2.0"),
+        ]);
+    }
+
+    #[test]
+    fn test_check_imported_restrictions() {
+        let source = r#"
+import FungibleToken from 0x01
+
+pub resource interface Local {
+    pub fun local()
+}
+
+pub fun f(vault: @AnyResource, local: &{Local}) {
+    let receiver: &{FungibleToken.Receiver} = getAccount(0x02).capabilities.borrow<&{FungibleToken.Receiver}>(/public/receiver)!
+    receiver.deposit(from: <- vault)
+    local.deposit()
+}
+"#;
+        assert_eq!(errors(source), vec![
+            ("value of type `&{Local}` has no member `deposit`".to_string(), "deposit"),
+        ]);
+    }
+
+    #[test]
+    fn test_check_subtyping() {
+        let source = r#"
+pub struct interface I {}
+pub struct interface J {}
+pub struct S: I {}
+pub resource R {}
+
+pub fun cast(s: S, i: {I}, any: AnyStruct, r: @R, ref: &S, authRef: auth &S) {
+    let a: AnyStruct = s
+    let b: {I} = s
+    let c: {J} = s
+    let d: S? = s
+    let e: [AnyStruct] = [s, 1]
+    let f: &{I} = ref
+    let g: auth &S = ref
+    let h: &S = authRef
+    let i2 = any as? S
+    let j = s as? S
+    let k = r as? AnyStruct
+    let l = any as! [Int]
+    let m: @AnyResource <- r
+    let n: AnyStruct = &s as &S
+}
+"#;
+        let checked = check_source(source);
+        assert_eq!(type_of(&checked, source, "i2"), "S?");
+        assert_eq!(type_of(&checked, source, "l"), "[Int]");
+        assert_eq!(diagnostics(source, &checked), vec![
+            (Severity::Error, "mismatched types: expected `{J}`, got `S`".to_string(), "s"),
+            (Severity::Error, "mismatched types: expected `auth &S`, got `&S`".to_string(), "ref"),
+            (Severity::Warning, "cast of `S` to `S` always succeeds, use `as`".to_string(), "s as? S"),
+            (Severity::Error, "cannot cast between resource and non-resource types: `R` and `AnyStruct`".to_string(), "r as? AnyStruct"),
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod linearity_tests {
+    use crate::checker::checker::check;
+    use crate::parser::parser::parse;
+
+    /// error messages and the source text they point at
+    fn errors(source: &str) -> Vec<(String, &str)> {
+        let checked = check(&parse(source).unwrap_or_else(|e| panic!("{:?}", e)));
+        checked.errors()
+            .map(|diagnostic| (diagnostic.message.clone(), &source[diagnostic.span.at..diagnostic.span.end()]))
+            .collect()
+    }
+
+    const DECLARATIONS: &str = r#"
+pub resource R {
+    pub let id: Int
+    init(id: Int) {
+        self.id = id
+    }
+}
+
+pub fun consume(_ r: @R) {
+    destroy r
+}
+
+pub fun maybe(): @R? {
+    return nil
+}
+"#;
+
+    #[test]
+    fn test_linearity() {
+        let source = DECLARATIONS.to_string() + r#"
+pub fun moves(flag: Bool): @R {
+    var a <- create R(id: 1)
+    var b <- create R(id: 2)
+    a <-> b
+    consume(<-a)
+
+    let c <- create R(id: b.id)
+    if flag {
+        destroy c
+    } else {
+        consume(<-c)
+    }
+
+    if let d <- maybe() {
+        destroy d
+    }
+
+    var rs: @[R] <- []
+    var i = 0
+    while i < 3 {
+        let r <- create R(id: i)
+        rs.append(<-r)
+        i = i + 1
+    }
+    destroy rs
+
+    let e <- create R(id: 3)
+    if flag {
+        destroy b
+        return <-e
+    }
+    destroy e
+    return <-b
+}
+
+pub fun aborts(): @R {
+    let r <- create R(id: 0)
+    if r.id == 0 {
+        destroy r
+        panic("no")
+    }
+    return <-r
+}
+"#;
+        assert_eq!(errors(&source), vec![]);
+    }
+
+    #[test]
+    fn test_linearity_nil() {
+        let source = DECLARATIONS.to_string() + r#"
+pub resource Holder {
+    pub var inner: @R?
+
+    init() {
+        self.inner <- nil
+    }
+
+    pub fun take(): @R? {
+        let inner <- self.inner <- nil
+        return <-inner
+    }
+}
+
+pub fun empty() {
+    let r: @R? <- nil
+    destroy r
+}
+"#;
+        assert_eq!(errors(&source), vec![]);
+    }
+
+    #[test]
+    fn test_linearity_errors() {
+        let source = DECLARATIONS.to_string() + r#"
+pub fun lost() {
+    let a <- create R(id: 1)
+}
+
+pub fun used(flag: Bool) {
+    let b <- create R(id: 2)
+    consume(<-b)
+    log(b.id)
+    destroy b
+
+    let c <- create R(id: 3)
+    if flag {
+        destroy c
+    }
+
+    var d <- create R(id: 4)
+    d <- create R(id: 5)
+    destroy d
+
+    let e = create R(id: 6)
+    destroy e
+    consume(create R(id: 7))
+    create R(id: 8)
+
+    let f <- create R(id: 9)
+    var i = 0
+    while i < 2 {
+        consume(<-f)
+        i = i + 1
+    }
+
+    let g <- 1
+}
+"#;
+        assert_eq!(errors(&source), vec![
+            ("loss of resource `a`: it is neither moved nor destroyed".to_string(), "a"),
+            ("use of moved resource `b`".to_string(), "b"),
+            ("resource `b` has already been moved".to_string(), "b"),
+            ("loss of resource `d`: it is overwritten by this assignment".to_string(), "d"),
+            ("missing move operator: resources are transferred with `<-`, not `=`".to_string(), "create R(id: 6)"),
+            ("missing move operator `<-` for resource value".to_string(), "create R(id: 7)"),
+            ("loss of resource: the value of this expression is neither moved nor destroyed".to_string(), "create R(id: 8)"),
+            ("resource `f` is moved in a loop, it would be moved again in the next iteration".to_string(), "f"),
+            ("invalid move operator `<-` for non-resource value, use `=`".to_string(), "1"),
+            // reported when their scope ends
+            ("potential loss of resource `c`: it is only moved on some paths".to_string(), "c"),
+            ("potential loss of resource `f`: it is only moved on some paths".to_string(), "f"),
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod conformance_tests {
+    use crate::checker::checker::{
+        TypeCheck, check,
+    };
+    use crate::parser::parser::parse;
+
+    fn check_source(source: &str) -> TypeCheck {
+        check(&parse(source).unwrap_or_else(|e| panic!("{:?}", e)))
+    }
+
+    /// each error message with the text it points at, and the texts its notes point at
+    fn errors<'s>(source: &'s str, checked: &TypeCheck) -> Vec<(String, &'s str, Vec<&'s str>)> {
+        let text = |span: crate::ast::Span| &source[span.at..span.end()];
+        checked.errors()
+            .map(|diagnostic| (diagnostic.message.clone(), text(diagnostic.span), diagnostic.notes.iter().map(|note| text(note.span)).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_conformance() {
+        let source = r#"
+pub contract Token {
+    pub resource interface Provider {
+        pub fun withdraw(amount: UFix64): @Vault {
+            pre {
+                amount > 0.0: "amount must be positive"
+            }
+            post {
+                result.balance == amount
+            }
+        }
+    }
+
+    pub resource interface Receiver {
+        pub fun deposit(from: @Vault)
+
+        pub fun describe(): String {
+            return "receiver"
+        }
+    }
+
+    pub resource interface Balance {
+        pub var balance: UFix64
+        init(balance: UFix64)
+    }
+
+    pub resource interface Vaulted: Provider, Receiver {}
+
+    pub resource Vault: Vaulted, Balance {
+        pub var balance: UFix64
+
+        init(balance: UFix64) {
+            self.balance = balance
+        }
+
+        pub fun withdraw(amount: UFix64): @Vault {
+            self.balance = self.balance - amount
+            return <-create Vault(balance: amount)
+        }
+
+        pub fun deposit(from: @Vault) {
+            self.balance = self.balance + from.balance
+            destroy from
+        }
+    }
+
+    pub fun describe(vault: &Vault): String {
+        return vault.describe()
+    }
+}
+"#;
+        let checked = check_source(source);
+        assert_eq!(errors(source, &checked), vec![]);
+
+        assert_eq!(checked.inherited_conditions.len(), 1);
+        let inherited = &checked.inherited_conditions[0];
+        assert_eq!((inherited.composite.as_str(), inherited.function.as_str()), ("Token.Vault", "withdraw"));
+        assert_eq!(inherited.interface, "Token.Provider");
+        assert_eq!((inherited.pre_conditions.len(), inherited.post_conditions.len()), (1, 1));
+    }
+
+    #[test]
+    fn test_conformance_errors() {
+        let source = r#"
+pub struct interface Named {
+    pub let name: String
+    pub var count: Int
+    pub(set) var label: String
+    pub fun rename(to name: String): Bool
+    pub fun greet(_ other: String, times: Int)
+    pub fun kind(): String
+}
+
+pub struct interface First {
+    pub fun hello(): String {
+        return "first"
+    }
+}
+
+pub struct interface Second {
+    pub fun hello(): String {
+        return "second"
+    }
+}
+
+pub struct Thing: Named, First, Second {
+    pub let name: Int
+    pub let count: Int
+    pub var label: String
+    access(contract) fun rename(name: String): Bool {
+        return true
+    }
+    pub fun greet(_ other: String) {}
+
+    init() {
+        self.name = 1
+        self.count = 0
+        self.label = ""
+    }
+}
+"#;
+        let checked = check_source(source);
+        let requirement = |needle: &str| &source[source.find(needle).unwrap()..][..needle.len()];
+        assert_eq!(errors(source, &checked), vec![
+            ("field `name` has type `Int`, but `Named` requires `String`".to_string(), "name", vec![requirement("name")]),
+            ("field `count` is declared with `let`, but `Named` requires `var`".to_string(), "count", vec![requirement("count")]),
+            ("`label` is less accessible than required by `Named`: `pub(set)`".to_string(), "label", vec![requirement("label")]),
+            ("parameter 1 of `rename` has argument label `name`, but `Named` requires `to`".to_string(), "rename", vec![requirement("rename")]),
+            ("`rename` is less accessible than required by `Named`: `pub`".to_string(), "rename", vec![requirement("rename")]),
+            ("`greet` has 1 parameters, but `Named` requires 2".to_string(), "greet", vec![requirement("greet")]),
+            ("`Thing` does not conform to `Named`: missing function `kind`".to_string(), "Thing", vec![requirement("kind")]),
+            ("`Thing` inherits conflicting default implementations of `hello` from `First` and `Second`".to_string(), "Thing", vec![
+                requirement("hello"),
+                &source[source.rfind("hello").unwrap()..][..5],
+            ]),
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod access_tests {
+    use crate::checker::access::{
+        Visibility, surface,
+    };
+    use crate::checker::checker::{
+        TypeCheck, check,
+    };
+    use crate::parser::parser::parse;
+
+    fn check_source(source: &str) -> TypeCheck {
+        check(&parse(source).unwrap_or_else(|e| panic!("{:?}", e)))
+    }
+
+    /// each error message with the text it points at
+    fn errors<'s>(source: &'s str, checked: &TypeCheck) -> Vec<(String, &'s str)> {
+        checked.errors().map(|diagnostic| (diagnostic.message.clone(), &source[diagnostic.span.at..diagnostic.span.end()])).collect()
+    }
+
+    const TOKEN: &str = r#"
+pub contract Token {
+    pub var totalSupply: UFix64
+    access(contract) var minted: Int
+    access(account) fun mint(amount: UFix64): @Vault {
+        self.minted = self.minted + 1
+        return <-create Vault(balance: amount)
+    }
+
+    pub resource interface Receiver {
+        pub fun deposit(from: @Vault)
+    }
+
+    pub resource Vault: Receiver {
+        pub var balance: UFix64
+        pub(set) var label: String
+        access(self) var secret: Int
+        priv let key: String
+
+        init(balance: UFix64) {
+            self.balance = balance
+            self.label = ""
+            self.secret = 0
+            self.key = ""
+        }
+
+        pub fun deposit(from: @Vault) {
+            self.balance = self.balance + from.balance
+            self.secret = from.secret
+            destroy from
+        }
+
+        access(contract) fun burn() {}
+    }
+
+    resource Hidden {
+        pub fun peek() {}
+    }
+
+    pub fun drain(vault: &Vault) {
+        vault.burn()
+    }
+
+    init() {
+        self.totalSupply = 0.0
+        self.minted = 0
+    }
+}
+"#;
+
+    #[test]
+    fn test_access() {
+        let checked = check_source(TOKEN);
+        assert_eq!(errors(TOKEN, &checked), vec![]);
+
+        let surface = surface(&checked);
+        assert_eq!(surface.contracts.len(), 1);
+        let members: Vec<(&str, Visibility, bool)> = surface.contracts[0].members.iter()
+            .map(|member| (member.name.as_str(), member.visibility, member.is_settable()))
+            .collect();
+        assert_eq!(members, vec![
+            ("Token.totalSupply", Visibility::Public, false),
+            ("Token.mint", Visibility::Account, false),
+            ("Token.Vault.balance", Visibility::Public, false),
+            ("Token.Vault.label", Visibility::Public, true),
+            ("Token.Vault.deposit", Visibility::Public, false),
+            ("Token.drain", Visibility::Public, false),
+        ]);
+
+        let json = surface.to_json();
+        assert!(json.starts_with("{\n  \"contracts\": [\n    {\n      \"name\": \"Token\",\n      \"members\": [\n"));
+        assert!(json.contains(
+            r#"{"name": "Token.Vault.label", "kind": "var", "access": "pub(set)", "visibility": "public", "type": "String", "settable": true}"#
+        ));
+        assert!(json.contains(r#""name": "Token.mint", "kind": "fun", "access": "access(account)", "visibility": "account""#));
+        assert!(!json.contains("secret") && !json.contains("burn") && !json.contains("peek"));
+    }
+
+    #[test]
+    fn test_access_errors() {
+        let source = format!("{}{}", TOKEN, r#"
+pub contract Bank {
+    pub fun take(vault: &Token.Vault): @Token.Vault {
+        vault.burn()
+        return <-Token.mint(amount: vault.balance)
+    }
+}
+
+pub fun main(vault: &Token.Vault, receiver: &{Token.Receiver}): Int {
+    vault.balance = 1.0
+    vault.label = "mine"
+    vault.key = "x"
+    Token.totalSupply = 0.0
+    let secret = vault.secret
+    receiver.deposit(from: <-Token.mint(amount: 1.0))
+    return Token.minted
+}
+"#);
+        let checked = check_source(&source);
+        assert_eq!(errors(&source, &checked), vec![
+            ("cannot assign to constant field `key`".to_string(), "key"),
+            ("cannot access `burn`: it is `access(contract)` in `Token.Vault`".to_string(), "burn"),
+            ("cannot write to field `balance` of `Token.Vault` from outside, it is `pub` and not `pub(set)`".to_string(), "balance"),
+            ("cannot access `key`: it is `priv` in `Token.Vault`".to_string(), "key"),
+            ("cannot write to field `totalSupply` of `Token` from outside, it is `pub` and not `pub(set)`".to_string(), "totalSupply"),
+            ("cannot access `secret`: it is `access(self)` in `Token.Vault`".to_string(), "secret"),
+            ("cannot access `mint`: it is `access(account)` in `Token`".to_string(), "mint"),
+            ("cannot access `minted`: it is `access(contract)` in `Token`".to_string(), "minted"),
+        ]);
+        let burn = checked.errors().nth(1).unwrap();
+        assert_eq!(burn.notes.len(), 1);
+        assert_eq!(&source[burn.notes[0].span.at..burn.notes[0].span.end()], "burn");
+    }
+}
+
+#[cfg(test)]
+mod entitlement_tests {
+    use crate::ast::{
+        Access, Authorization, Declaration,
+    };
+    use crate::checker::checker::{
+        TypeCheck, check,
+    };
+    use crate::checker::types::{
+        Authorization as Entitlements, Type,
+    };
+    use crate::parser::parser::{
+        parse, parse_with_errors,
+    };
+    use crate::printer::printer::print_program;
+
+    fn check_source(source: &str) -> TypeCheck {
+        check(&parse(source).unwrap_or_else(|e| panic!("{:?}", e)))
+    }
+
+    /// each error message with the text it points at
+    fn errors<'s>(source: &'s str, checked: &TypeCheck) -> Vec<(String, &'s str)> {
+        checked.errors().map(|diagnostic| (diagnostic.message.clone(), &source[diagnostic.span.at..diagnostic.span.end()])).collect()
+    }
+
+    const BANK: &str = r#"
+access(all) contract Bank {
+    access(all) entitlement Withdraw
+    access(all) entitlement Deposit
+    access(all) entitlement Open
+    access(all) entitlement mapping Unlock {
+        Withdraw -> Open
+    }
+    access(all) entitlement mapping All {
+        include Unlock
+        Deposit -> Open
+    }
+
+    access(all) struct Box {
+        access(Open) fun open() {}
+        init() {}
+    }
+
+    access(all) resource Vault {
+        access(all) var balance: UFix64
+        access(self) let box: Box
+
+        access(Withdraw) fun withdraw(amount: UFix64): @Vault {
+            self.balance = self.balance - amount
+            return <-create Vault(balance: amount)
+        }
+
+        access(Withdraw | Deposit) fun touch() {}
+
+        access(mapping Unlock) fun boxed(): auth(mapping Unlock) &Box {
+            return &self.box as auth(mapping Unlock) &Box
+        }
+
+        init(balance: UFix64) {
+            self.balance = balance
+            self.box = Box()
+        }
+    }
+
+    access(all) fun use(owned: @Vault, full: auth(Withdraw, Deposit) &Vault, either: auth(Withdraw | Deposit) &Vault) {
+        let a <- owned.withdraw(amount: 1.0)
+        let b <- full.withdraw(amount: 1.0)
+        either.touch()
+        full.boxed().open()
+        destroy a
+        destroy b
+        destroy owned
+    }
+
+    init() {}
+}
+"#;
+
+    #[test]
+    fn test_entitlement_parsing() {
+        let program = parse(BANK).unwrap();
+        let Declaration::Composite(bank) = &program.declarations[0] else {
+            panic!("expected a contract")
+        };
+        assert_eq!(bank.access, Access::All);
+        assert!(matches!(&bank.members[0], Declaration::Entitlement(e) if e.identifier.name == "Withdraw"));
+        let Declaration::EntitlementMapping(all) = &bank.members[4] else {
+            panic!("expected an entitlement mapping")
+        };
+        assert_eq!(all.includes.len(), 1);
+        assert_eq!(all.relations.len(), 1);
+        assert_eq!((all.relations[0].input.identifier.name.as_str(), all.relations[0].output.identifier.name.as_str()), ("Deposit", "Open"));
+
+        let Declaration::Composite(vault) = &bank.members[6] else {
+            panic!("expected a resource")
+        };
+        let accesses: Vec<String> = vault.members.iter().filter_map(|member| match member {
+            Declaration::Function(function) => Some(function.access.to_string()),
+            _ => None,
+        }).collect();
+        assert_eq!(accesses, vec!["access(Withdraw)", "access(Withdraw | Deposit)", "access(mapping Unlock)"]);
+        assert!(matches!(&vault.members[4], Declaration::Function(f) if matches!(f.access, Access::Entitlements(Authorization::Mapping(_)))));
+
+        let printed = print_program(&program);
+        assert!(printed.contains("    access(all) entitlement mapping All {\n        include Unlock\n        Deposit -> Open\n    }\n"));
+        assert!(printed.contains("        full: auth(Withdraw, Deposit) &Vault,\n        either: auth(Withdraw | Deposit) &Vault,\n"));
+        assert_eq!(print_program(&parse(&printed).unwrap()), printed);
+
+        let (_, errors) = parse_with_errors("access(all) fun f(r: auth(A, B | C) &R) {}");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "cannot mix `,` and `|` in an entitlement set");
+    }
+
+    #[test]
+    fn test_entitlements() {
+        let checked = check_source(BANK);
+        assert_eq!(errors(BANK, &checked), vec![]);
+
+        let types = &checked.types;
+        let open = Entitlements::Conjunction(vec!["Bank.Open".to_string()]);
+        let withdraw = Entitlements::Conjunction(vec!["Bank.Withdraw".to_string()]);
+        let deposit = Entitlements::Conjunction(vec!["Bank.Deposit".to_string()]);
+        assert_eq!(types.map_entitlements("Bank.Unlock", Some(&withdraw)), open);
+        assert_eq!(types.map_entitlements("Bank.Unlock", Some(&deposit)), Entitlements::Unauthorized);
+        assert_eq!(types.map_entitlements("Bank.All", Some(&deposit)), open);
+        assert_eq!(types.map_entitlements("Bank.All", None), open);
+
+        let full = Type::Reference { authorization: withdraw.clone(), ty: Box::new(Type::Composite("Bank.Vault".to_string())) };
+        let plain = Type::reference(Type::Composite("Bank.Vault".to_string()));
+        assert!(types.is_subtype(&full, &plain));
+        assert!(!types.is_subtype(&plain, &full));
+        assert!(Entitlements::Conjunction(vec!["A".to_string(), "B".to_string()]).grants(&Entitlements::Disjunction(vec!["B".to_string()])));
+        assert!(!Entitlements::Disjunction(vec!["A".to_string(), "B".to_string()]).grants(&Entitlements::Conjunction(vec!["A".to_string()])));
+    }
+
+    #[test]
+    fn test_entitlement_errors() {
+        let source = format!("{}{}", BANK, r#"
+access(all) fun main(either: auth(Bank.Withdraw | Bank.Deposit) &Bank.Vault, plain: &Bank.Vault) {
+    let a <- either.withdraw(amount: 1.0)
+    plain.touch()
+    plain.boxed().open()
+    destroy a
+}
+
+access(all) contract Other {
+    access(Bank.Withdraw) fun take() {}
+
+    access(all) resource R {
+        access(Bank.Vault) fun f() {}
+        access(mapping Bank.Withdraw) fun g() {}
+    }
+}
+"#);
+        let checked = check_source(&source);
+        assert_eq!(errors(&source, &checked), vec![
+            ("entitlement access is only allowed on fields and functions of structs, resources and their interfaces".to_string(), "Bank.Withdraw"),
+            ("`Bank.Vault` is not an entitlement".to_string(), "Bank.Vault"),
+            ("`Bank.Withdraw` is not an entitlement mapping".to_string(), "Bank.Withdraw"),
+            (
+                "cannot access `withdraw` through `auth(Bank.Withdraw | Bank.Deposit) &Bank.Vault`: it requires a reference authorized for `Bank.Withdraw`".to_string(),
+                "withdraw",
+            ),
+            ("cannot access `touch` through `&Bank.Vault`: it requires a reference authorized for `Bank.Withdraw | Bank.Deposit`".to_string(), "touch"),
+            ("cannot access `open` through `&Bank.Box`: it requires a reference authorized for `Bank.Open`".to_string(), "open"),
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod migrate_tests {
+    use crate::checker::checker::check;
+    use crate::format::config::Config;
+    use crate::migrate::migrate::migrate_source;
+    use crate::parser::parser::parse;
+
+    const TOKEN: &str = r#"// the token contract
+pub contract Token {
+    pub var totalSupply: UFix64
+
+    pub event TokensBurned(amount: UFix64)
+
+    pub resource interface Receiver {
+        pub fun deposit(from: @Vault)
+    }
+
+    pub resource Vault: Receiver {
+        pub(set) var balance: UFix64
+        priv var history: @[Vault]
+        var note: String
+
+        init(balance: UFix64) {
+            self.balance = balance
+            self.history <- []
+            self.note = ""
+        }
+
+        pub fun deposit(from: @Vault) {
+            self.balance = self.balance + from.balance
+            destroy from
+        }
+
+        destroy() {
+            emit TokensBurned(amount: self.balance)
+            destroy self.history
+        }
+    }
+
+    pub resource Minter {
+        destroy() {
+            Token.totalSupply = 0.0
+        }
+    }
+
+    pub fun setup(signer: AuthAccount, other: PublicAccount) {
+        signer.save(<-create Vault(balance: 0.0), to: /storage/vault)
+        let used = signer.storageUsed
+        signer.link<&Vault{Receiver}>(/public/receiver, target: /storage/vault)
+        let any: @AnyResource{Receiver}? <- nil
+        destroy any
+        self.account.save(1, to: /storage/x)
+    }
+
+    init() {
+        self.totalSupply = 0.0
+    }
+}
+"#;
+
+    #[test]
+    fn test_migrate() {
+        let migration = migrate_source(TOKEN, &Config::default()).unwrap();
+        assert_eq!(migration.source, r#"// the token contract
+access(all) contract Token {
+    access(all) var totalSupply: UFix64
+
+    access(all) event TokensBurned(amount: UFix64)
+
+    access(all) resource interface Receiver {
+        access(all) fun deposit(from: @Vault)
+    }
+
+    access(all) resource Vault: Receiver {
+        access(all) var balance: UFix64
+        access(self) var history: @[Vault]
+        access(self) var note: String
+
+        init(balance: UFix64) {
+            self.balance = balance
+            self.history <- []
+            self.note = ""
+        }
+
+        access(all) fun deposit(from: @Vault) {
+            self.balance = self.balance + from.balance
+            destroy from
+        }
+
+        access(all) event ResourceDestroyed(amount: UFix64 = self.balance)
+    }
+
+    access(all) resource Minter {
+        destroy() {
+            Token.totalSupply = 0.0
+        }
+    }
+
+    access(all) fun setup(signer: auth(Storage) &Account, other: &Account) {
+        signer.storage.save(<-create Vault(balance: 0.0), to: /storage/vault)
+        let used = signer.storage.used
+        signer.link<&Vault>(/public/receiver, target: /storage/vault)
+        let any: @{Receiver}? <- nil
+        destroy any
+        self.account.storage.save(1, to: /storage/x)
+    }
+
+    init() {
+        self.totalSupply = 0.0
+    }
+}
+"#);
+        // migrating again changes nothing
+        let again = migrate_source(&migration.source, &Config::default()).unwrap();
+        assert_eq!(again.source, migration.source);
+    }
+
+    #[test]
+    fn test_migrate_follow_ups() {
+        let migration = migrate_source(TOKEN, &Config::default()).unwrap();
+        let follow_ups: Vec<(usize, &str)> = migration.follow_ups.iter()
+            .map(|follow_up| (TOKEN[..follow_up.span.at].matches('\n').count() + 1, follow_up.message.as_str()))
+            .collect();
+        assert_eq!(follow_ups, vec![
+            (12, "`pub(set)` was removed, writes of `balance` from outside need a setter function"),
+            (28, "`TokensBurned` is no longer emitted when a `Vault` is destroyed, `Vault.ResourceDestroyed` is emitted instead"),
+            (
+                34,
+                "custom destructors were removed, what this one does must move elsewhere: only destroying fields and emitting one event are migrated",
+            ),
+            (42, "capability links were replaced by capability controllers, `link` must be migrated by hand"),
+            (42, "`Vault{Receiver}` is now `Vault`: restricted types were removed, limit access with entitlements instead"),
+        ]);
+
+        let passed = migrate_source("pub fun pass(account: AuthAccount) {\n    use(account)\n}\n", &Config::default()).unwrap();
+        assert_eq!(passed.source, "access(all) fun pass(account: &Account) {\n    use(account)\n}\n");
+        assert_eq!(passed.follow_ups[0].message, "`account` is now `auth(...) &Account`, where it is passed to may need other entitlements");
+
+        assert!(migrate_source("pub fun broken(", &Config::default()).is_err());
+
+        let checked = check(&parse("pub event Moved(amount: Int = 1)\npub resource R {\n    pub event ResourceDestroyed(id: Int = 1)\n}").unwrap());
+        let errors: Vec<&str> = checked.errors().map(|diagnostic| diagnostic.message.as_str()).collect();
+        assert_eq!(errors, vec!["only parameters of `ResourceDestroyed` events can have default arguments"]);
+    }
+}
+
+#[cfg(test)]
+mod interpreter_tests {
+    use crate::checker::checker::check;
+    use crate::interpreter::interpreter::{
+        ErrorKind, Interpreter, run_script,
+    };
+    use crate::interpreter::meter::{
+        CostTable, FunctionUsage, Meter,
+    };
+    use crate::interpreter::number::NumberError;
+    use crate::interpreter::storage::Ledger;
+    use crate::interpreter::value::Value;
+    use crate::parser::parser::parse;
+
+    fn run(source: &str) -> String {
+        match run_script(source, Vec::new()) {
+            Ok(value) => value.to_string(),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_interpreter_values() {
+        assert_eq!(run("access(all) fun main(): Int { return 1 + 2 * 3 }"), "7");
+        assert_eq!(run("access(all) fun main(): UFix64 { return 1.5 * 2.0 + 0.25 }"), "3.25000000");
+        assert_eq!(run("access(all) fun main(): Fix64 { return -1.5 / 2.0 }"), "-0.75000000");
+        assert_eq!(run("access(all) fun main(): Int8 { return -128 }"), "-128");
+        assert_eq!(run("access(all) fun main(): UInt8 { let x: UInt8 = 200\n return x + 56 }"), "overflow in UInt8 addition");
+        assert_eq!(run("access(all) fun main(): UInt8 { let x: UInt8 = 0\n return x - 1 }"), "underflow in UInt8 subtraction");
+        assert_eq!(run("access(all) fun main(): Word8 { let x: Word8 = 200\n return x + 56 }"), "0");
+        assert_eq!(run("access(all) fun main(): Int { return 1 / (2 - 2) }"), "division by zero in Int division");
+        assert_eq!(run("access(all) fun main(): UInt8 { let x = 256\n return UInt8(x) }"), "overflow in conversion to UInt8");
+        assert_eq!(run("access(all) fun main(): Int8 { let x: Int8 = -128\n return -x }"), "overflow in Int8 negation");
+        assert_eq!(run("access(all) fun main(): UInt16 { return UInt16(300) + UInt16(1.9) }"), "301");
+        assert_eq!(run("access(all) fun main(): String { return \"abc\".concat(\"def\").slice(from: 1, upTo: 4) }"), "\"bcd\"");
+        assert_eq!(
+            run("access(all) fun main(): [Int] { let a = [3, 1]\n let b = a\n b.append(2)\n a[0] = 0\n return a.concat(b) }"),
+            "[0, 1, 3, 1, 2]",
+        );
+        assert_eq!(run("access(all) fun main(): Int { let a = [1]\n return a[1] }"), "index out of bounds: index 1 is out of bounds for length 1");
+        assert_eq!(
+            run("access(all) fun main(): {String: Int} { let d = {\"a\": 1, \"b\": 2}\n d[\"a\"] = nil\n d.insert(key: \"c\", 3)\n return d }"),
+            "{\"b\": 2, \"c\": 3}",
+        );
+        assert_eq!(run("access(all) fun main(): Int { let d: {String: Int} = {}\n return (d[\"x\"] ?? 4) + d.length }"), "4");
+        assert_eq!(run("access(all) fun main(): Int { let x: Int? = nil\n return x! }"), "unexpectedly found nil while forcing an optional");
+        assert_eq!(run("access(all) fun main(): Int? { let x: AnyStruct = 1\n return x as? Int }"), "1");
+        assert_eq!(run("access(all) fun main(): String { let x: AnyStruct = 1\n return x as! String }"), "failed to force-cast value: expected `String`, got `Int`");
+
+        let error = run_script("access(all) fun main(): Int64 { return Int64(9223372036854775807) + 1 }", Vec::new()).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Arithmetic(NumberError::Overflow));
+        assert_eq!(error.message, "overflow in Int64 addition");
+        assert_eq!(run_script("access(all) fun main(a: Int, b: Int): Int { return a - b }", vec![Value::int(5), Value::int(7)]), Ok(Value::int(-2)));
+        assert_eq!(run_script("access(all) fun main(): Int { return \"x\" }", Vec::new()).unwrap_err().kind, ErrorKind::InvalidProgram);
+    }
+
+    #[test]
+    fn test_interpreter_call_depth() {
+        let error = run_script("access(all) fun f(_ n: Int): Int { return f(n + 1) }\naccess(all) fun main(): Int { return f(0) }", Vec::new())
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::CallDepthLimit);
+        assert!(error.to_string().starts_with("call stack depth limit exceeded: `f` is called "), "{}", error);
+        assert_eq!(run("access(all) fun f(_ n: Int): Int { return n == 0 ? 0 : f(n - 1) + 1 }\naccess(all) fun main(): Int { return f(20) }"), "20");
+
+        let mut emulator = crate::emulator::emulator::Emulator::new();
+        let error = emulator.execute_script("access(all) fun main(): Int { return main() }", Vec::new()).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::CallDepthLimit);
+    }
+
+    #[test]
+    fn test_interpreter_control_flow() {
+        assert_eq!(run(r#"
+access(all) fun fib(_ n: Int): Int {
+    if n < 2 {
+        return n
+    }
+    return fib(n - 1) + fib(n - 2)
+}
+
+access(all) fun main(): Int {
+    return fib(15)
+}
+"#), "610");
+
+        assert_eq!(run(r#"
+access(all) fun main(): [Int] {
+    var i = 0
+    let found: [Int] = []
+    while true {
+        i = i + 1
+        if i % 2 == 0 {
+            continue
+        }
+        if i > 9 {
+            break
+        }
+        found.append(i)
+    }
+    return found
+}
+"#), "[1, 3, 5, 7, 9]");
+
+        assert_eq!(run(r#"
+access(all) fun name(_ n: Int): String {
+    switch n {
+        case 1, 2:
+            return "small"
+        case 3:
+            break
+        default:
+            return "large"
+    }
+    return "three"
+}
+
+access(all) fun main(): [String] {
+    let names: [String] = []
+    for i, n in [1, 3, 8] {
+        names.append(name(n).concat(i.toString()))
+    }
+    if let first = names.firstIndex(of: "three1") {
+        names.append("found")
+    } else {
+        names.append("missing")
+    }
+    return names
+}
+"#), r#"["small0", "three1", "large2", "found"]"#);
+
+        assert_eq!(run("access(all) fun main(): Int { panic(\"no way\") }"), "panic: no way");
+        assert_eq!(run("access(all) fun main() { assert(1 > 2, message: \"wrong order\") }"), "assertion failed: wrong order");
+
+        // the second transfer moves a value into the place the first value is moved out of
+        assert_eq!(run(r#"
+access(all) resource R {
+    access(all) let id: Int
+    init(id: Int) { self.id = id }
+}
+
+access(all) fun main(): Int {
+    let rs: @{Int: R} <- {}
+    let none <- rs[1] <- create R(id: 1)
+    destroy none
+    let old <- rs[1] <- create R(id: 2)
+    let id = old?.id ?? 0
+    destroy old
+    let r <- rs.remove(key: 1)!
+    let result = r.id
+    destroy r
+    destroy rs
+    return id * 10 + result
+}
+"#), "12");
+    }
+
+    #[test]
+    fn test_interpreter_composites() {
+        let source = r#"
+access(all) contract Token {
+    access(all) var totalSupply: UFix64
+
+    access(all) event Deposited(amount: UFix64)
+
+    access(all) resource interface Receiver {
+        access(all) fun deposit(from: @Vault) {
+            pre {
+                from.balance > 0.0: "empty deposit"
+            }
+        }
+    }
+
+    access(all) resource Vault: Receiver {
+        access(all) var balance: UFix64
+
+        init(balance: UFix64) {
+            self.balance = balance
+            Token.totalSupply = Token.totalSupply + balance
+        }
+
+        access(all) fun deposit(from: @Vault) {
+            post {
+                self.balance == before(self.balance) + before(from.balance): "deposit lost tokens"
+            }
+            emit Deposited(amount: from.balance)
+            self.balance = self.balance + from.balance
+            destroy from
+        }
+
+        access(all) fun withdraw(amount: UFix64): @Vault {
+            pre {
+                amount <= self.balance: "insufficient balance"
+            }
+            post {
+                result.balance == amount
+            }
+            self.balance = self.balance - amount
+            return <-create Vault(balance: amount)
+        }
+
+        access(all) event ResourceDestroyed(balance: UFix64 = self.balance)
+    }
+
+    access(all) struct Point {
+        access(all) var x: Int
+
+        init(x: Int) {
+            self.x = x
+        }
+
+        access(all) fun setX(_ x: Int) {
+            self.x = x
+        }
+    }
+
+    access(all) enum Color: UInt8 {
+        access(all) case red
+        access(all) case green
+    }
+
+    init() {
+        self.totalSupply = 0.0
+    }
+}
+
+access(all) fun transfer(amount: UFix64): UFix64 {
+    let vault <- create Token.Vault(balance: 10.0)
+    let part <- vault.withdraw(amount: amount)
+    vault.deposit(from: <-part)
+    let balance = vault.balance
+    destroy vault
+    return balance
+}
+
+access(all) fun copies(): [Int] {
+    let a = Token.Point(x: 1)
+    let b = a
+    b.setX(2)
+    return [a.x, b.x, Token.Color.green.rawValue == 1 ? 1 : 0, Token.Color(rawValue: 5) == nil ? 1 : 0]
+}
+"#;
+        let program = parse(source).unwrap();
+        let checked = check(&program);
+        assert_eq!(checked.errors().next(), None);
+        let mut interpreter = Interpreter::new(&program, &checked).unwrap();
+        let amount = |literal: &str| run_script(&format!("access(all) fun main(): UFix64 {{ return {} }}", literal), Vec::new()).unwrap();
+
+        assert_eq!(interpreter.invoke("transfer", vec![amount("4.0")]).unwrap().to_string(), "10.00000000");
+        let events: Vec<String> = interpreter.events.iter().map(Value::to_string).collect();
+        assert_eq!(events, vec![
+            "Token.Deposited(amount: 4.00000000)",
+            "Token.Vault.ResourceDestroyed(balance: 4.00000000)",
+            "Token.Vault.ResourceDestroyed(balance: 10.00000000)",
+        ]);
+
+        let error = interpreter.invoke("transfer", vec![amount("11.0")]).unwrap_err();
+        assert_eq!((error.kind, error.message.as_str()), (ErrorKind::PreCondition, "insufficient balance"));
+        let error = interpreter.invoke("transfer", vec![amount("0.0")]).unwrap_err();
+        assert_eq!((error.kind, error.message.as_str()), (ErrorKind::PreCondition, "empty deposit"));
+
+        assert_eq!(interpreter.invoke("copies", Vec::new()).unwrap().to_string(), "[1, 2, 1, 1]");
+        assert_eq!(interpreter.invoke("main", Vec::new()).unwrap_err().message, "cannot find function `main`");
+    }
+
+    #[test]
+    fn test_interpreter_metering() {
+        let source = "
+            access(all) fun square(_ n: Int): Int {
+                return n * n
+            }
+
+            access(all) fun main(): Int {
+                var total = 0
+                var i = 0
+                while i < 3 {
+                    total = total + square(i)
+                    i = i + 1
+                }
+                return total
+            }
+        ";
+        let program = parse(source).unwrap();
+        let checked = check(&program);
+        let mut interpreter = Interpreter::new(&program, &checked).unwrap();
+        assert_eq!(interpreter.invoke("main", Vec::new()), Ok(Value::int(5)));
+        // each iteration is the loop, two statements, and an invocation of `square` with its statement
+        assert_eq!(interpreter.meter.used(), 20);
+        assert_eq!(interpreter.meter.usage().get("main"), Some(&FunctionUsage { invocations: 1, computation: 20, own: 14 }));
+        assert_eq!(interpreter.meter.usage().get("square"), Some(&FunctionUsage { invocations: 3, computation: 6, own: 6 }));
+        assert_eq!(interpreter.meter.report().lines().collect::<Vec<_>>(), vec![
+            "function  invocations  computation          own",
+            "main                1           20           14",
+            "square              3            6            6",
+            "total computation: 20",
+        ]);
+
+        let costs = CostTable { loop_iteration: 10, ..CostTable::default() };
+        let mut interpreter = Interpreter::with_ledger(&program, &checked, Ledger::default(), Meter::new(costs, 40)).unwrap();
+        let error = interpreter.invoke("main", Vec::new()).unwrap_err();
+        assert_eq!(error.to_string(), "computation limit exceeded: used more than the limit of 40");
+        assert_eq!(&source[error.span.at..error.span.at + 5], "while");
+        assert_eq!(interpreter.meter.usage().get("square").map(|usage| usage.invocations), Some(2));
+
+        // only account storage costs more than an invocation, not functions of arrays and strings
+        let source = "
+            access(all) fun main(): Int {
+                let values = [1]
+                values.append(2)
+                values.insert(at: 0, 3)
+                return values.length + \"a\".concat(\"b\").length
+            }
+        ";
+        let program = parse(source).unwrap();
+        let checked = check(&program);
+        let mut interpreter = Interpreter::new(&program, &checked).unwrap();
+        assert_eq!(interpreter.invoke("main", Vec::new()), Ok(Value::int(5)));
+        // four statements, and invocations of `main`, `append`, `insert` and `concat`
+        assert_eq!(interpreter.meter.used(), 8);
+    }
+}
+
+#[cfg(test)]
+mod number_tests {
+    use crate::checker::types::NumberType;
+    use crate::interpreter::interpreter::run_script;
+    use crate::interpreter::number::{
+        Number, NumberError,
+    };
+
+    fn number(ty: NumberType, literal: &str) -> Number {
+        let (negative, digits) = literal.strip_prefix('-').map_or((false, literal), |digits| (true, digits));
+        Number::parse(ty, digits, negative).unwrap_or_else(|error| panic!("{} is not a {}: {}", literal, ty.name(), error.as_str()))
+    }
+
+    fn show(result: Result<Number, NumberError>) -> String {
+        match result {
+            Ok(number) => number.to_string(),
+            Err(error) => error.as_str().to_string(),
+        }
+    }
+
+    fn apply(ty: NumberType, a: &str, operation: fn(&Number, &Number) -> Result<Number, NumberError>, b: &str) -> String {
+        show(operation(&number(ty, a), &number(ty, b)))
+    }
+
+    fn run(source: &str) -> String {
+        match run_script(source, Vec::new()) {
+            Ok(value) => value.to_string(),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_number_ranges() {
+        let ranges = [
+            (NumberType::Int8, "-128", "127"),
+            (NumberType::Int16, "-32768", "32767"),
+            (NumberType::Int32, "-2147483648", "2147483647"),
+            (NumberType::Int64, "-9223372036854775808", "9223372036854775807"),
+            (NumberType::Int128, "-170141183460469231731687303715884105728", "170141183460469231731687303715884105727"),
+            (
+                NumberType::Int256,
+                "-57896044618658097711785492504343953926634992332820282019728792003956564819968",
+                "57896044618658097711785492504343953926634992332820282019728792003956564819967",
+            ),
+            (NumberType::UInt8, "0", "255"),
+            (NumberType::UInt16, "0", "65535"),
+            (NumberType::UInt32, "0", "4294967295"),
+            (NumberType::UInt64, "0", "18446744073709551615"),
+            (NumberType::UInt128, "0", "340282366920938463463374607431768211455"),
+            (NumberType::UInt256, "0", "115792089237316195423570985008687907853269984665640564039457584007913129639935"),
+            (NumberType::Word8, "0", "255"),
+            (NumberType::Word16, "0", "65535"),
+            (NumberType::Word32, "0", "4294967295"),
+            (NumberType::Word64, "0", "18446744073709551615"),
+            (NumberType::Word128, "0", "340282366920938463463374607431768211455"),
+            (NumberType::Word256, "0", "115792089237316195423570985008687907853269984665640564039457584007913129639935"),
+            (NumberType::Fix64, "-92233720368.54775808", "92233720368.54775807"),
+            (NumberType::UFix64, "0.00000000", "184467440737.09551615"),
+        ];
+        for (ty, min, max) in ranges {
+            let name = ty.name();
+            assert_eq!((Number::min(ty).unwrap(), Number::max(ty).unwrap()), (number(ty, min), number(ty, max)), "{}", name);
+            assert_eq!((number(ty, min).to_string().as_str(), number(ty, max).to_string().as_str()), (min, max), "{}", name);
+            let unit = if ty.is_fixed_point() { "0.00000001" } else { "1" };
+            let (overflow, underflow) = if name.starts_with("Word") { (min, max) } else { ("overflow", "underflow") };
+            assert_eq!(apply(ty, max, Number::checked_add, unit), overflow, "{}", name);
+            assert_eq!(apply(ty, min, Number::checked_sub, unit), underflow, "{}", name);
+            assert_ne!(apply(ty, max, Number::checked_sub, unit), max, "{}", name);
+            if let Some(min) = min.strip_prefix('-') {
+                assert_eq!(show(Number::parse(ty, &format!("9{}", min), true)), "underflow", "{}", name);
+            }
+            assert_eq!(show(Number::parse(ty, &format!("9{}", max), false)), "overflow", "{}", name);
+            assert_eq!(apply(ty, max, Number::checked_div, "0"), "division by zero", "{}", name);
+            assert_eq!(apply(ty, max, Number::checked_rem, "0"), "division by zero", "{}", name);
+            assert_eq!(number(ty, max).saturating_add(&number(ty, unit)).to_string(), max, "{}", name);
+            assert_eq!(number(ty, min).saturating_sub(&number(ty, unit)).to_string(), min, "{}", name);
+        }
+        assert_eq!(apply(NumberType::UInt, "0", Number::checked_sub, "1"), "underflow");
+        assert_eq!(
+            apply(NumberType::Int, "-340282366920938463463374607431768211455", Number::checked_mul, "340282366920938463463374607431768211455"),
+            "-115792089237316195423570985008687907852589419931798687112530834793049593217025",
+        );
+        assert_eq!(show(Number::parse(NumberType::Int8, "0x80", false)), "overflow");
+        assert_eq!(show(Number::parse(NumberType::UInt8, "0b1111_1111", false)), "255");
+        assert_eq!(show(Number::parse(NumberType::UFix64, "1.123456789", false)), "invalid number");
+    }
+
+    #[test]
+    fn test_integer_arithmetic() {
+        assert_eq!(apply(NumberType::Int8, "-128", Number::checked_mul, "-1"), "overflow");
+        assert_eq!(apply(NumberType::Int8, "-128", Number::checked_div, "-1"), "overflow");
+        assert_eq!(apply(NumberType::Int8, "64", Number::checked_mul, "-2"), "-128");
+        assert_eq!(apply(NumberType::Int8, "-64", Number::checked_mul, "3"), "underflow");
+        assert_eq!(apply(NumberType::Int8, "-128", Number::checked_rem, "-1"), "0");
+        assert_eq!(apply(NumberType::Int64, "-7", Number::checked_div, "2"), "-3");
+        assert_eq!(apply(NumberType::Int64, "-7", Number::checked_rem, "2"), "-1");
+        assert_eq!(apply(NumberType::Int64, "7", Number::checked_rem, "-2"), "1");
+        assert_eq!(apply(NumberType::UInt8, "16", Number::checked_mul, "16"), "overflow");
+        assert_eq!(apply(NumberType::Word8, "16", Number::checked_mul, "16"), "0");
+        assert_eq!(apply(NumberType::Word64, "0", Number::checked_sub, "2"), "18446744073709551614");
+        assert_eq!(apply(NumberType::Word256, "2", Number::checked_sub, "3"), Number::max(NumberType::Word256).unwrap().to_string());
+        assert_eq!(show(number(NumberType::Int8, "-128").checked_neg()), "overflow");
+        assert_eq!(show(number(NumberType::UInt8, "1").checked_neg()), "underflow");
+        assert_eq!(show(number(NumberType::Word8, "1").checked_neg()), "255");
+        assert_eq!(show(number(NumberType::Int, "-5").checked_neg()), "5");
+
+        assert_eq!(number(NumberType::Int8, "100").saturating_add(&number(NumberType::Int8, "100")).to_string(), "127");
+        assert_eq!(number(NumberType::Int8, "-100").saturating_sub(&number(NumberType::Int8, "100")).to_string(), "-128");
+        assert_eq!(number(NumberType::Int8, "-100").saturating_mul(&number(NumberType::Int8, "2")).to_string(), "-128");
+        assert_eq!(number(NumberType::UInt8, "0").saturating_sub(&number(NumberType::UInt8, "1")).to_string(), "0");
+        assert_eq!(show(number(NumberType::Int8, "-128").saturating_div(&number(NumberType::Int8, "-1"))), "127");
+        assert_eq!(show(number(NumberType::Int8, "1").saturating_div(&number(NumberType::Int8, "0"))), "division by zero");
+        assert_eq!(number(NumberType::UInt, "0").saturating_sub(&number(NumberType::UInt, "1")).to_string(), "0");
+    }
+
+    #[test]
+    fn test_fixed_point_arithmetic() {
+        assert_eq!(apply(NumberType::UFix64, "0.1", Number::checked_mul, "0.1"), "0.01000000");
+        assert_eq!(apply(NumberType::UFix64, "0.00000001", Number::checked_mul, "0.5"), "0.00000000");
+        assert_eq!(apply(NumberType::Fix64, "-0.00000001", Number::checked_mul, "0.5"), "0.00000000");
+        assert_eq!(apply(NumberType::Fix64, "-0.00000003", Number::checked_mul, "0.5"), "-0.00000001");
+        assert_eq!(apply(NumberType::UFix64, "1.0", Number::checked_div, "3.0"), "0.33333333");
+        assert_eq!(apply(NumberType::UFix64, "2.0", Number::checked_div, "3.0"), "0.66666666");
+        assert_eq!(apply(NumberType::Fix64, "-2.0", Number::checked_div, "3.0"), "-0.66666666");
+        assert_eq!(apply(NumberType::UFix64, "7.5", Number::checked_rem, "2.0"), "1.50000000");
+        assert_eq!(apply(NumberType::Fix64, "-7.5", Number::checked_rem, "2.0"), "-1.50000000");
+        assert_eq!(apply(NumberType::UFix64, "184467440737.09551615", Number::checked_mul, "2.0"), "overflow");
+        assert_eq!(apply(NumberType::UFix64, "184467440737.09551615", Number::checked_mul, "1.0"), "184467440737.09551615");
+        assert_eq!(apply(NumberType::UFix64, "100000000000.0", Number::checked_div, "0.1"), "overflow");
+        assert_eq!(apply(NumberType::Fix64, "-92233720368.54775808", Number::checked_div, "-1.0"), "overflow");
+        assert_eq!(apply(NumberType::Fix64, "-92233720368.54775808", Number::checked_mul, "1.5"), "underflow");
+        assert_eq!(apply(NumberType::UFix64, "1.0", Number::checked_sub, "1.00000001"), "underflow");
+        assert_eq!(number(NumberType::UFix64, "184467440737.0").saturating_mul(&number(NumberType::UFix64, "2.0")).to_string(), "184467440737.09551615");
+        assert_eq!(number(NumberType::Fix64, "-1.0").saturating_mul(&number(NumberType::Fix64, "92233720368.0")).to_string(), "-92233720368.00000000");
+        assert_eq!(show(number(NumberType::Fix64, "-92233720368.54775808").saturating_div(&number(NumberType::Fix64, "-1.0"))), "92233720368.54775807");
+    }
+
+    #[test]
+    fn test_number_bitwise_and_shifts() {
+        assert_eq!(apply(NumberType::Int8, "-1", Number::bitwise_and, "15"), "15");
+        assert_eq!(apply(NumberType::Int8, "-16", Number::bitwise_or, "15"), "-1");
+        assert_eq!(apply(NumberType::Int8, "-1", Number::bitwise_xor, "127"), "-128");
+        assert_eq!(apply(NumberType::UInt64, "12", Number::bitwise_xor, "10"), "6");
+        assert_eq!(apply(NumberType::Int, "-6", Number::bitwise_and, "-3"), "-8");
+        assert_eq!(apply(NumberType::Int8, "1", Number::shift_left, "7"), "-128");
+        assert_eq!(apply(NumberType::Int8, "1", Number::shift_left, "8"), "0");
+        assert_eq!(apply(NumberType::Int8, "-1", Number::shift_left, "7"), "-128");
+        assert_eq!(apply(NumberType::UInt8, "255", Number::shift_left, "1"), "254");
+        assert_eq!(apply(NumberType::Word16, "3", Number::shift_left, "15"), "32768");
+        assert_eq!(apply(NumberType::UInt256, "1", Number::shift_left, "1000"), "0");
+        assert_eq!(apply(NumberType::Int, "1", Number::shift_left, "100"), "1267650600228229401496703205376");
+        assert_eq!(apply(NumberType::Int8, "-128", Number::shift_right, "1"), "-64");
+        assert_eq!(apply(NumberType::Int8, "-1", Number::shift_right, "10"), "-1");
+        assert_eq!(apply(NumberType::Int8, "-7", Number::shift_right, "1"), "-4");
+        assert_eq!(apply(NumberType::UInt8, "255", Number::shift_right, "7"), "1");
+        assert_eq!(apply(NumberType::Int, "1267650600228229401496703205376", Number::shift_right, "99"), "2");
+        assert_eq!(apply(NumberType::Int8, "1", Number::shift_left, "-1"), "invalid number");
+        assert_eq!(apply(NumberType::Int8, "1", Number::shift_right, "-1"), "invalid number");
+    }
+
+    #[test]
+    fn test_number_conversions() {
+        let convert = |from: NumberType, literal: &str, to: NumberType| show(number(from, literal).convert(to));
+        assert_eq!(convert(NumberType::Int8, "-1", NumberType::UInt8), "underflow");
+        assert_eq!(convert(NumberType::Int, "256", NumberType::UInt8), "overflow");
+        assert_eq!(convert(NumberType::Int, "256", NumberType::Word8), "0");
+        assert_eq!(convert(NumberType::Int, "-1", NumberType::Word8), "255");
+        assert_eq!(convert(NumberType::Int, "-1", NumberType::Word256), Number::max(NumberType::Word256).unwrap().to_string());
+        assert_eq!(convert(NumberType::UInt64, "18446744073709551615", NumberType::Int64), "overflow");
+        assert_eq!(convert(NumberType::UInt64, "18446744073709551615", NumberType::UInt128), "18446744073709551615");
+        assert_eq!(convert(NumberType::UFix64, "1.99", NumberType::Int), "1");
+        assert_eq!(convert(NumberType::Fix64, "-1.99", NumberType::Int8), "-1");
+        assert_eq!(convert(NumberType::Fix64, "-1.99", NumberType::UInt8), "underflow");
+        assert_eq!(convert(NumberType::Fix64, "-0.5", NumberType::UInt8), "0");
+        assert_eq!(convert(NumberType::Int, "92233720368", NumberType::Fix64), "92233720368.00000000");
+        assert_eq!(convert(NumberType::Int, "92233720369", NumberType::Fix64), "overflow");
+        assert_eq!(convert(NumberType::Int, "-92233720369", NumberType::Fix64), "underflow");
+        assert_eq!(convert(NumberType::UInt64, "18446744073709551615", NumberType::UFix64), "overflow");
+        assert_eq!(convert(NumberType::Fix64, "-1.0", NumberType::UFix64), "underflow");
+        assert_eq!(convert(NumberType::UFix64, "184467440737.09551615", NumberType::Fix64), "overflow");
+        assert_eq!(convert(NumberType::UFix64, "2.5", NumberType::Fix64), "2.50000000");
+        assert_eq!(number(NumberType::UFix64, "184467440737.09").to_i128(), Some(184467440737));
+        assert_eq!(number(NumberType::UInt256, "115792089237316195423570985008687907853269984665640564039457584007913129639935").to_i128(), None);
+    }
+
+    #[test]
+    fn test_number_big_endian_bytes() {
+        let bytes = |ty: NumberType, literal: &str| number(ty, literal).to_big_endian_bytes();
+        assert_eq!(bytes(NumberType::UInt16, "258"), vec![1, 2]);
+        assert_eq!(bytes(NumberType::Int8, "-1"), vec![255]);
+        assert_eq!(bytes(NumberType::Int32, "-2"), vec![255, 255, 255, 254]);
+        assert_eq!(bytes(NumberType::Word32, "1"), vec![0, 0, 0, 1]);
+        assert_eq!(bytes(NumberType::UFix64, "1.0"), vec![0, 0, 0, 0, 5, 245, 225, 0]);
+        assert_eq!(bytes(NumberType::Fix64, "-0.00000001"), vec![255; 8]);
+        assert_eq!(bytes(NumberType::UInt256, "1").len(), 32);
+        assert_eq!(bytes(NumberType::Int, "0"), vec![0]);
+        assert_eq!(bytes(NumberType::Int, "127"), vec![127]);
+        assert_eq!(bytes(NumberType::Int, "128"), vec![0, 128]);
+        assert_eq!(bytes(NumberType::Int, "-1"), vec![255]);
+        assert_eq!(bytes(NumberType::Int, "-128"), vec![128]);
+        assert_eq!(bytes(NumberType::Int, "-129"), vec![255, 127]);
+        assert_eq!(bytes(NumberType::UInt, "0"), vec![0]);
+        assert_eq!(bytes(NumberType::UInt, "255"), vec![255]);
+        assert_eq!(bytes(NumberType::UInt, "256"), vec![1, 0]);
+    }
+
+    #[test]
+    fn test_number_members() {
+        assert_eq!(run("access(all) fun main(): Int8 { let x: Int8 = 100\n return x.saturatingAdd(100) }"), "127");
+        assert_eq!(run("access(all) fun main(): UInt8 { let x: UInt8 = 1\n return x.saturatingSubtract(2) }"), "0");
+        assert_eq!(run("access(all) fun main(): UFix64 { let x: UFix64 = 184467440737.0\n return x.saturatingMultiply(2.0) }"), "184467440737.09551615");
+        assert_eq!(run("access(all) fun main(): Int8 { let x: Int8 = -128\n return x.saturatingDivide(-1) }"), "127");
+        assert_eq!(run("access(all) fun main(): Int8 { let x: Int8 = 1\n return x.saturatingDivide(0) }"), "division by zero in Int8 saturating division");
+        assert_eq!(run("access(all) fun main(): [UInt8] { let x: Int16 = -2\n return x.toBigEndianBytes() }"), "[255, 254]");
+        assert_eq!(run("access(all) fun main(): Int { let x: Int = 1\n return (x << 128) >> 127 }"), "2");
+        assert_eq!(run("access(all) fun main(): Int8 { let x: Int8 = 1\n return x << 7 }"), "-128");
+        assert_eq!(run("access(all) fun main(): Word64 { let x: Word64 = 0\n return x - 1 }"), "18446744073709551615");
+        assert_eq!(run("access(all) fun main(): UInt256 { return UInt256(1) << 255 }"), "57896044618658097711785492504343953926634992332820282019728792003956564819968");
+        assert_eq!(run("access(all) fun main(): UInt256 { return (UInt256(1) << 255) * 2 }"), "overflow in UInt256 multiplication");
+        assert_eq!(run("access(all) fun main(): Int { let a = [1]\n return a[340282366920938463463374607431768211456] }"), "index out of bounds: index 340282366920938463463374607431768211456 is out of bounds for length 1");
+    }
+}
+
+#[cfg(test)]
+mod emulator_tests {
+    use crate::emulator::emulator::Emulator;
+    use crate::interpreter::interpreter::ErrorKind;
+    use crate::interpreter::meter::CostTable;
+    use crate::interpreter::value::Value;
+
+    const TOKEN: &str = "
+        access(all) contract Token {
+            access(all) entitlement Withdraw
+
+            access(all) event Deposited(amount: UFix64, to: Address?)
+
+            access(all) resource interface Receiver {
+                access(all) fun deposit(from: @Vault)
+            }
+
+            access(all) resource Vault: Receiver {
+                access(all) var balance: UFix64
+
+                init(balance: UFix64) {
+                    self.balance = balance
+                }
+
+                access(Withdraw) fun withdraw(amount: UFix64): @Vault {
+                    self.balance = self.balance - amount
+                    return <- create Vault(balance: amount)
+                }
+
+                access(all) fun deposit(from: @Vault) {
+                    emit Deposited(amount: from.balance, to: nil)
+                    self.balance = self.balance + from.balance
+                    destroy from
+                }
+            }
+
+            access(all) var supply: UFix64
+
+            access(all) fun createVault(): @Vault {
+                return <- create Vault(balance: 0.0)
+            }
+
+            init() {
+                self.supply = 100.0
+                self.account.storage.save(<- create Vault(balance: 100.0), to: /storage/vault)
+                let capability = self.account.capabilities.storage.issue<&Vault>(/storage/vault)
+                self.account.capabilities.publish(capability, at: /public/receiver)
+            }
+        }
+    ";
+
+    const SETUP: &str = "
+        import Token from 0x01
+
+        transaction {
+            prepare(signer: auth(Storage, Capabilities) &Account) {
+                signer.storage.save(<- Token.createVault(), to: /storage/vault)
+                let capability = signer.capabilities.storage.issue<&Token.Vault>(/storage/vault)
+                signer.capabilities.publish(capability, at: /public/receiver)
+            }
+        }
+    ";
+
+    const TRANSFER: &str = "
+        import Token from 0x01
+
+        transaction(amount: UFix64, limit: UFix64) {
+            let vault: auth(Token.Withdraw) &Token.Vault
+
+            prepare(sender: auth(BorrowValue) &Account, recipient: &Account) {
+                self.vault = sender.storage.borrow<auth(Token.Withdraw) &Token.Vault>(from: /storage/vault)
+                    ?? panic(\"no vault\")
+                let receiver = recipient.capabilities.borrow<&Token.Vault>(/public/receiver) ?? panic(\"no receiver\")
+                receiver.deposit(from: <- self.vault.withdraw(amount: amount))
+            }
+
+            pre {
+                amount > 0.0: \"nothing to transfer\"
+            }
+
+            post {
+                self.vault.balance >= limit: \"balance below limit\"
+            }
+        }
+    ";
+
+    const BALANCE: &str = "
+        import Token from 0x01
+
+        access(all) fun main(address: Address): UFix64 {
+            return getAccount(address).capabilities.borrow<&Token.Vault>(/public/receiver)?.balance ?? 0.0
+        }
+    ";
+
+    fn ufix64(literal: &str) -> Value {
+        Value::Number(crate::interpreter::number::Number::parse(crate::checker::types::NumberType::UFix64, literal, false).unwrap())
+    }
+
+    fn balance(emulator: &mut Emulator, address: u64) -> String {
+        match emulator.execute_script(BALANCE, vec![Value::Address(address)]) {
+            Ok(value) => value.to_string(),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_emulator_transactions() {
+        let mut emulator = Emulator::new();
+        let token = emulator.create_account();
+        let user = emulator.create_account();
+        assert_eq!((token, user), (1, 2));
+        emulator.deploy_contract(token, TOKEN).unwrap();
+        assert_eq!(balance(&mut emulator, token), "100.00000000");
+        assert_eq!(balance(&mut emulator, user), "0.00000000");
+
+        emulator.execute_transaction(SETUP, Vec::new(), &[user]).unwrap();
+        let result = emulator.execute_transaction(TRANSFER, vec![ufix64("30.0"), ufix64("0.0")], &[token, user]).unwrap();
+        let events: Vec<&str> = result.events.iter().map(|event| event.type_id.as_str()).collect();
+        assert_eq!(events, vec!["A.0000000000000001.Token.Deposited"]);
+        assert_eq!(balance(&mut emulator, token), "70.00000000");
+        assert_eq!(balance(&mut emulator, user), "30.00000000");
+
+        // the post-condition fails after the deposit, which is rolled back
+        let error = emulator.execute_transaction(TRANSFER, vec![ufix64("50.0"), ufix64("60.0")], &[token, user]).unwrap_err();
+        assert_eq!((error.kind, error.message.as_str()), (ErrorKind::PostCondition, "balance below limit"));
+        assert_eq!(balance(&mut emulator, token), "70.00000000");
+        assert_eq!(balance(&mut emulator, user), "30.00000000");
+
+        let error = emulator.execute_transaction(TRANSFER, vec![ufix64("0.0"), ufix64("0.0")], &[token, user]).unwrap_err();
+        assert_eq!(error.to_string(), "pre-condition failed: nothing to transfer");
+        let error = emulator.execute_transaction(TRANSFER, vec![ufix64("1.0"), ufix64("0.0")], &[token]).unwrap_err();
+        assert_eq!(error.to_string(), "invalid program: `prepare` takes 2 signers, got 1");
+        let error = emulator.execute_transaction(SETUP, Vec::new(), &[user]).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Storage);
+        let error = emulator.execute_transaction(SETUP, Vec::new(), &[7]).unwrap_err();
+        assert_eq!(error.to_string(), "storage error: account 0x0000000000000007 does not exist");
+        let error = emulator.execute_transaction(&SETUP.replace("0x01", "0x02"), Vec::new(), &[user]).unwrap_err();
+        assert_eq!(error.to_string(), "invalid program: cannot find contract `Token` at 0x0000000000000002");
+        let error = emulator.deploy_contract(user, TOKEN).unwrap_err();
+        assert_eq!(error.to_string(), "invalid program: contract `Token` is already deployed to 0x0000000000000001");
+
+        // transfers read storage twice, and fail past the limit without changing anything
+        let result = emulator.execute_transaction(TRANSFER, vec![ufix64("1.0"), ufix64("0.0")], &[token, user]).unwrap();
+        assert!(result.computation > 20);
+        assert_eq!(result.usage.get("Token.Vault.deposit").map(|usage| usage.invocations), Some(1));
+        emulator.set_computation(CostTable::default(), 20);
+        let error = emulator.execute_transaction(TRANSFER, vec![ufix64("1.0"), ufix64("0.0")], &[token, user]).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ComputationLimit);
+        emulator.set_computation(CostTable::default(), u64::MAX);
+        assert_eq!(balance(&mut emulator, user), "31.00000000");
+    }
+
+    #[test]
+    fn test_emulator_events() {
+        let mut emulator = Emulator::new();
+        let token = emulator.create_account();
+        let user = emulator.create_account();
+        emulator.deploy_contract(token, TOKEN).unwrap();
+        emulator.execute_transaction(SETUP, Vec::new(), &[user]).unwrap();
+        emulator.execute_transaction(TRANSFER, vec![ufix64("30.0"), ufix64("0.0")], &[token, user]).unwrap();
+        // failed transactions emit nothing, but are counted
+        emulator.execute_transaction(TRANSFER, vec![ufix64("50.0"), ufix64("60.0")], &[token, user]).unwrap_err();
+        let twice = "
+            import Token from 0x01
+            transaction {
+                prepare(sender: auth(BorrowValue) &Account) {
+                    let vault = sender.storage.borrow<auth(Token.Withdraw) &Token.Vault>(from: /storage/vault)!
+                    vault.deposit(from: <- vault.withdraw(amount: 1.5))
+                    vault.deposit(from: <- vault.withdraw(amount: 2.0))
+                }
+            }
+        ";
+        emulator.execute_transaction(twice, Vec::new(), &[user]).unwrap();
+        // scripts are no transactions
+        balance(&mut emulator, user);
+
+        let positions: Vec<(usize, usize)> = emulator.events().iter().map(|event| (event.transaction, event.index)).collect();
+        assert_eq!(positions, vec![(2, 0), (4, 0), (4, 1)]);
+        let deposited = emulator.events_of_type("A.0000000000000001.Token.Deposited").collect::<Vec<_>>();
+        assert_eq!(deposited.len(), 3);
+        assert_eq!(deposited[1].field("amount"), Some(&ufix64("1.5")));
+        assert_eq!(deposited[1].field("to"), Some(&Value::Nil));
+        assert_eq!(emulator.events_of_type("Token.Deposited").count(), 0);
+        assert_eq!(deposited[0].to_json_cadence(), concat!(
+            "{\"type\":\"Event\",\"value\":{\"id\":\"A.0000000000000001.Token.Deposited\",\"fields\":[",
+            "{\"name\":\"amount\",\"value\":{\"type\":\"UFix64\",\"value\":\"30.00000000\"}},",
+            "{\"name\":\"to\",\"value\":{\"type\":\"Optional\",\"value\":null}}]}}",
+        ));
+        let json = emulator.events_json();
+        assert_eq!(json.lines().count(), 5);
+        assert!(json.lines().nth(3).unwrap().starts_with(
+            "  {\"type\":\"A.0000000000000001.Token.Deposited\",\"transactionIndex\":4,\"eventIndex\":1,\"values\":"
+        ));
+        assert!(json.lines().nth(1).unwrap().ends_with(&format!("\"values\":{}}},", emulator.events()[0].to_json_cadence())));
+        assert_eq!(Emulator::new().events_json(), "[]");
+    }
+
+    #[test]
+    fn test_emulator_storage() {
+        let mut emulator = Emulator::new();
+        let address = emulator.create_account();
+        let run = |emulator: &mut Emulator, source: &str| match emulator.execute_transaction(source, Vec::new(), &[address]) {
+            Ok(result) => result.logs.join(" "),
+            Err(error) => error.to_string(),
+        };
+        emulator.deploy_contract(address, "access(all) contract Counter { access(all) struct Count { access(all) let value: Int\n init(value: Int) { self.value = value } } }").unwrap();
+
+        // before Cadence 1.0, public paths link to storage paths
+        let linked = "
+            import Counter from 0x01
+            transaction {
+                prepare(signer: AuthAccount) {
+                    signer.save(Counter.Count(value: 1), to: /storage/count)
+                    signer.link<&Counter.Count>(/public/count, target: /storage/count)
+                    let capability = signer.getCapability<&Counter.Count>(/public/count)
+                    log(capability.borrow()!.value)
+                    log(signer.link<&Counter.Count>(/public/count, target: /storage/other))
+                    log(signer.getCapability<&Int>(/public/count).check())
+                    log(signer.type(at: /storage/count))
+                    log(signer.copy<Counter.Count>(from: /storage/count)?.value)
+                    signer.unlink(/public/count)
+                    log(capability.check())
+                }
+            }
+        ";
+        assert_eq!(run(&mut emulator, linked), "1 nil false Type<Counter.Count>() 1 false");
+
+        let controllers = "
+            import Counter from 0x01
+            transaction {
+                prepare(signer: auth(Storage, Capabilities) &Account) {
+                    signer.storage.save(Counter.Count(value: 2), to: /storage/other)
+                    let capability = signer.capabilities.storage.issue<&Counter.Count>(/storage/other)
+                    log(capability.id)
+                    log(capability.borrow()!.value)
+                    let controller = signer.capabilities.storage.getController(byCapabilityID: capability.id)!
+                    controller.retarget(/storage/count)
+                    log(capability.borrow()!.value)
+                    log(signer.capabilities.storage.getControllers(forPath: /storage/count).length)
+                    controller.delete()
+                    log(capability.check())
+                    log(signer.capabilities.get<&Counter.Count>(/public/count).check())
+                    log(signer.contracts.names)
+                }
+            }
+        ";
+        assert_eq!(run(&mut emulator, controllers), "1 2 1 1 false false [\"Counter\"]");
+
+        let load = "
+            transaction {
+                prepare(signer: auth(Storage) &Account) {
+                    log(signer.storage.load<Int>(from: /storage/missing))
+                    signer.storage.load<Int>(from: /storage/count)
+                }
+            }
+        ";
+        assert_eq!(run(&mut emulator, load), "failed to force-cast value: expected `Int`, got `Counter.Count`");
+
+        // scripts keep nothing they change
+        let script = "
+            access(all) fun main(address: Address): Bool {
+                let account = getAuthAccount(address)
+                account.save(1, to: /storage/number)
+                return account.type(at: /storage/number) != nil
+            }
+        ";
+        assert_eq!(emulator.execute_script(script, vec![Value::Address(address)]).unwrap(), Value::Bool(true));
+        assert_eq!(emulator.execute_script(script, vec![Value::Address(address)]).unwrap(), Value::Bool(true));
+        let account = &emulator.ledger().accounts[&address];
+        assert_eq!(account.storage.keys().collect::<Vec<_>>(), vec!["count", "other"]);
+        assert_eq!(account.capability_id, 1);
+    }
+}
+
+#[cfg(test)]
+mod json_cadence_tests {
+    use crate::ast::CompositeKind;
+    use crate::checker::types::NumberType;
+    use crate::interpreter::interpreter::{
+        Interpreter, checked_program,
+    };
+    use crate::interpreter::number::Number;
+    use crate::interpreter::storage::{
+        Contract, Ledger,
+    };
+    use crate::json_cadence::export::export;
+    use crate::json_cadence::json::parse_json;
+    use crate::json_cadence::json_cadence::{
+        Authorization, Composite, CompositeType, FunctionType, Parameter, Type, Value, decode, encode,
+    };
+
+    fn number(ty: NumberType, literal: &str) -> Value {
+        let (negative, literal) = match literal.strip_prefix('-') {
+            Some(literal) => (true, literal),
+            None => (false, literal),
+        };
+        Value::Number(Number::parse(ty, literal, negative).unwrap())
+    }
+
+    fn error(text: &str) -> String {
+        decode(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_json_cadence_values() {
+        let values = [
+            (Value::Void, r#"{"type":"Void"}"#),
+            (Value::Optional(None), r#"{"type":"Optional","value":null}"#),
+            (Value::Optional(Some(Box::new(Value::Bool(true)))), r#"{"type":"Optional","value":{"type":"Bool","value":true}}"#),
+            (Value::String("a\"\n".to_string()), r#"{"type":"String","value":"a\"\n"}"#),
+            (Value::Character("é".to_string()), r#"{"type":"Character","value":"é"}"#),
+            (Value::Address(0x1cf0e2f2f715450), r#"{"type":"Address","value":"0x01cf0e2f2f715450"}"#),
+            (number(NumberType::Int, "-340282366920938463463374607431768211456"), r#"{"type":"Int","value":"-340282366920938463463374607431768211456"}"#),
+            (number(NumberType::UInt8, "255"), r#"{"type":"UInt8","value":"255"}"#),
+            (number(NumberType::Word256, "0"), r#"{"type":"Word256","value":"0"}"#),
+            (number(NumberType::UFix64, "12.3"), r#"{"type":"UFix64","value":"12.30000000"}"#),
+            (number(NumberType::Fix64, "-0.5"), r#"{"type":"Fix64","value":"-0.50000000"}"#),
+            (
+                Value::Array(vec![Value::Bool(false), Value::Void]),
+                r#"{"type":"Array","value":[{"type":"Bool","value":false},{"type":"Void"}]}"#,
+            ),
+            (
+                Value::Dictionary(vec![(Value::String("a".to_string()), number(NumberType::Int, "1"))]),
+                r#"{"type":"Dictionary","value":[{"key":{"type":"String","value":"a"},"value":{"type":"Int","value":"1"}}]}"#,
+            ),
+            (
+                Value::Resource(Composite::new("A.0000000000000001.Token.Vault", vec![("uuid", number(NumberType::UInt64, "7"))])),
+                r#"{"type":"Resource","value":{"id":"A.0000000000000001.Token.Vault","fields":[{"name":"uuid","value":{"type":"UInt64","value":"7"}}]}}"#,
+            ),
+            (
+                Value::Enum(Composite::new("S.Color", vec![("rawValue", number(NumberType::UInt8, "1"))])),
+                r#"{"type":"Enum","value":{"id":"S.Color","fields":[{"name":"rawValue","value":{"type":"UInt8","value":"1"}}]}}"#,
+            ),
+            (
+                Value::Path { domain: "storage".to_string(), identifier: "vault".to_string() },
+                r#"{"type":"Path","value":{"domain":"storage","identifier":"vault"}}"#,
+            ),
+            (Value::Type(Type::simple("Int")), r#"{"type":"Type","value":{"staticType":{"kind":"Int"}}}"#),
+            (
+                Value::Capability {
+                    id: 3,
+                    address: 1,
+                    borrow_type: Type::Reference {
+                        authorization: Authorization::Conjunction(vec!["A.0000000000000001.Token.Withdraw".to_string()]),
+                        ty: Box::new(Type::simple("AnyResource")),
+                    },
+                },
+                concat!(
+                    r#"{"type":"Capability","value":{"id":"3","address":"0x0000000000000001","borrowType":{"kind":"Reference","#,
+                    r#""authorization":{"kind":"EntitlementConjunctionSet","entitlements":[{"kind":"Entitlement","typeID":"A.0000000000000001.Token.Withdraw"}]},"#,
+                    r#""type":{"kind":"AnyResource"}}}}"#,
+                ),
+            ),
+            (
+                Value::Function(FunctionType {
+                    is_view: true,
+                    type_parameters: Vec::new(),
+                    parameters: vec![Parameter { label: "_".to_string(), identifier: "x".to_string(), ty: Type::simple("String") }],
+                    return_type: Box::new(Type::Optional(Box::new(Type::simple("Int")))),
+                }),
+                concat!(
+                    r#"{"type":"Function","value":{"functionType":{"kind":"Function","typeParameters":[],"#,
+                    r#""parameters":[{"label":"_","id":"x","type":{"kind":"String"}}],"purity":"view","#,
+                    r#""return":{"kind":"Optional","type":{"kind":"Int"}}}}}"#,
+                ),
+            ),
+        ];
+        for (value, json) in values.iter() {
+            assert_eq!(&encode(value), json);
+            assert_eq!(&decode(json).unwrap(), value);
+        }
+        // whitespace and key order do not matter
+        assert_eq!(decode(" { \"value\" : \"1\" , \"type\" : \"Int8\" } ").unwrap(), number(NumberType::Int8, "1"));
+        assert_eq!(decode(r#"{"type":"String","value":"😀é"}"#).unwrap(), Value::String("😀é".to_string()));
+
+        // recursive composite types are encoded once, then by type id
+        let node = CompositeType {
+            kind: CompositeKind::Structure,
+            is_interface: false,
+            type_id: "S.Node".to_string(),
+            fields: Vec::new(),
+            initializers: Vec::new(),
+            raw_type: None,
+        };
+        let recursive = Type::Composite(CompositeType {
+            fields: vec![("next".to_string(), Type::Optional(Box::new(Type::Composite(node.clone()))))],
+            initializers: vec![Vec::new()],
+            ..node.clone()
+        });
+        let json = r#"{"kind":"Struct","type":"","typeID":"S.Node","initializers":[[]],"fields":[{"id":"next","type":{"kind":"Optional","type":"S.Node"}}]}"#;
+        assert_eq!(recursive.to_json().to_string(), json);
+        assert_eq!(Type::from_json(&parse_json(json).unwrap()), Ok(recursive));
+    }
+
+    #[test]
+    fn test_json_cadence_validation() {
+        assert_eq!(error(r#"{"type":"UFix64","value":"1.5"}"#), "value: expected a `UFix64` with 8 decimals, got `1.5`");
+        assert_eq!(error(r#"{"type":"UFix64","value":"-1.00000000"}"#), "value: `UFix64` cannot be negative, got `-1.00000000`");
+        assert_eq!(error(r#"{"type":"UInt8","value":"256"}"#), "value: `256` is out of range for `UInt8`");
+        assert_eq!(error(r#"{"type":"Int","value":"0x10"}"#), "value: expected a `Int` in decimal, got `0x10`");
+        assert_eq!(error(r#"{"type":"Int","value":"01"}"#), "value: expected a `Int` in decimal, got `01`");
+        assert_eq!(error(r#"{"type":"Int","value":1}"#), "value: expected a string, got a number");
+        assert_eq!(error(r#"{"type":"Integer","value":"1"}"#), "type: unknown type `Integer`");
+        assert_eq!(error(r#"{"type":"Address","value":"0x1"}"#), "value: invalid address `0x1`");
+        assert_eq!(error(r#"{"type":"Address","value":"000000000000000001"}"#), "value: invalid address `000000000000000001`");
+        assert_eq!(error(r#"{"type":"Character","value":"ab"}"#), "value: expected a single character, got \"ab\"");
+        assert_eq!(error(r#"{"type":"Bool"}"#), "missing key `value`");
+        assert_eq!(error(r#"{"type":"Bool","value":true,"extra":1}"#), "unexpected key `extra`");
+        assert_eq!(error(r#"{"type":"Void","value":null}"#), "unexpected key `value`");
+        assert_eq!(error(r#"{"type":"Path","value":{"domain":"storage","identifier":"1x"}}"#), "value.identifier: invalid path identifier `1x`");
+        assert_eq!(error(r#"{"type":"Path","value":{"domain":"data","identifier":"x"}}"#), "value.domain: invalid path domain `data`");
+        assert_eq!(
+            error(r#"{"type":"Array","value":[{"type":"Bool","value":true},{"type":"Bool","value":"true"}]}"#),
+            "value[1].value: expected a boolean, got a string",
+        );
+        assert_eq!(
+            error(r#"{"type":"Dictionary","value":[{"key":{"type":"Array","value":[]},"value":{"type":"Void"}}]}"#),
+            "value[0].key: dictionary keys are numbers, strings, characters, booleans, addresses, paths, enums or types",
+        );
+        assert_eq!(
+            error(r#"{"type":"Struct","value":{"id":"S.P","fields":[{"name":"x","value":{"type":"Void"}},{"name":"x","value":{"type":"Void"}}]}}"#),
+            "value.fields[1].name: invalid or duplicate field name `x`",
+        );
+        assert_eq!(error(r#"{"type":"Type","value":{"staticType":{"kind":"Int","type":""}}}"#), "value.staticType: unexpected key `type`");
+        assert_eq!(error(r#"{"type":"Type","value":{"staticType":"S.Node"}}"#), "value.staticType: type id `S.Node` of no composite type before it");
+        assert_eq!(error(r#"{"type":"Type","value":{"staticType":{"kind":"Thing"}}}"#), "value.staticType.kind: unknown kind of type `Thing`");
+        assert_eq!(error(r#"{"type":"Bool","value":true"#), "invalid JSON at byte 27: expected `,` or `}`");
+        assert_eq!(error(r#"{"type":"Bool","type":"Bool"}"#), "invalid JSON at byte 15: duplicate key \"type\"");
+        assert_eq!(error("[] []"), "invalid JSON at byte 3: expected the end of the document");
+        assert_eq!(error(&"[".repeat(1000)), "invalid JSON at byte 256: too deeply nested");
+    }
+
+    #[test]
+    fn test_json_cadence_serde() {
+        let value = Value::Dictionary(vec![(
+            Value::String("a\"b".to_string()),
+            Value::Optional(Some(Box::new(number(NumberType::UFix64, "1.5")))),
+        )]);
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, encode(&value));
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), value);
+        // values nest in documents of other types, with the validation of `decode`
+        let arguments: Vec<Value> = serde_json::from_str(&format!("[{}, {}]", json, encode(&Value::Bool(true)))).unwrap();
+        assert_eq!(arguments, [value, Value::Bool(true)]);
+        assert_eq!(
+            serde_json::from_str::<Value>(r#"{"type":"UFix64","value":"1.5"}"#).unwrap_err().to_string(),
+            "value: expected a `UFix64` with 8 decimals, got `1.5`",
+        );
+        assert!(serde_json::from_str::<Value>(r#"{"type":"Bool","type":"Bool"}"#).unwrap_err().to_string().contains("duplicate key `type`"));
+    }
+
+    #[test]
+    fn test_json_cadence_export() {
+        let source = "
+            access(all) contract Shapes {
+                access(all) enum Kind: UInt8 {
+                    access(all) case square
+                }
+
+                access(all) struct Shape {
+                    access(all) let kind: Kind
+                    access(all) let sides: [UInt8]
+                    access(all) let next: Shape?
+
+                    init(kind: Kind, sides: [UInt8]) {
+                        self.kind = kind
+                        self.sides = sides
+                        self.next = nil
+                    }
+                }
+
+                access(all) fun square(): Shape {
+                    return Shape(kind: Kind.square, sides: [1, 1, 1, 1])
+                }
+
+                access(all) fun shapeType(): Type {
+                    return Type<Shape>()
+                }
+            }
+        ";
+        let (program, checked) = checked_program(source).unwrap();
+        let mut interpreter = Interpreter::new(&program, &checked).unwrap();
+        let mut ledger = Ledger::default();
+        ledger.accounts.entry(1).or_default().contracts.insert(
+            "Shapes".to_string(),
+            Contract { source: source.to_string(), value: None },
+        );
+
+        let square = interpreter.invoke("Shapes.square", Vec::new()).unwrap();
+        let Some(Value::Struct(shape)) = export(&square, &checked.types, &ledger) else {
+            panic!("not a struct")
+        };
+        assert_eq!(shape.type_id, "A.0000000000000001.Shapes.Shape");
+        assert_eq!(
+            encode(shape.field("kind").unwrap()),
+            r#"{"type":"Enum","value":{"id":"A.0000000000000001.Shapes.Kind","fields":[{"name":"rawValue","value":{"type":"UInt8","value":"0"}}]}}"#,
+        );
+        assert_eq!(shape.field("next"), Some(&Value::Optional(None)));
+
+        let shape_type = interpreter.invoke("Shapes.shapeType", Vec::new()).unwrap();
+        let exported = encode(&export(&shape_type, &checked.types, &ledger).unwrap());
+        assert_eq!(exported, concat!(
+            r#"{"type":"Type","value":{"staticType":{"kind":"Struct","type":"","typeID":"A.0000000000000001.Shapes.Shape","#,
+            r#""initializers":[[{"label":"kind","id":"kind","type":{"kind":"Enum","type":{"kind":"UInt8"},"typeID":"A.0000000000000001.Shapes.Kind","#,
+            r#""initializers":[],"fields":[{"id":"rawValue","type":{"kind":"UInt8"}}]}},"#,
+            r#"{"label":"sides","id":"sides","type":{"kind":"VariableSizedArray","type":{"kind":"UInt8"}}}]],"#,
+            r#""fields":[{"id":"kind","type":"A.0000000000000001.Shapes.Kind"},{"id":"sides","type":{"kind":"VariableSizedArray","type":{"kind":"UInt8"}}},"#,
+            r#"{"id":"next","type":{"kind":"Optional","type":"A.0000000000000001.Shapes.Shape"}}]}}}"#,
+        ));
+        assert_eq!(decode(&exported).map(|value| encode(&value)), Ok(exported));
+    }
+}
+
+#[cfg(test)]
+mod ccf_tests {
+    use crate::ast::CompositeKind;
+    use crate::ccf::ccf::{
+        decode, decode_typed, encode, infer_type,
+    };
+    use crate::checker::types::NumberType;
+    use crate::interpreter::number::Number;
+    use crate::json_cadence::json_cadence::{
+        Authorization, Composite, CompositeType, FunctionType, Parameter, Type, Value,
+    };
+
+    fn number(ty: NumberType, literal: &str) -> Value {
+        let (negative, literal) = match literal.strip_prefix('-') {
+            Some(literal) => (true, literal),
+            None => (false, literal),
+        };
+        Value::Number(Number::parse(ty, literal, negative).unwrap())
+    }
+
+    fn concat(parts: &[&[u8]]) -> Vec<u8> {
+        parts.concat()
+    }
+
+    fn encoded(value: &Value) -> Vec<u8> {
+        encode(value, &infer_type(value), false).unwrap()
+    }
+
+    fn error(bytes: &[u8]) -> String {
+        decode(bytes).unwrap_err().to_string()
+    }
+
+    fn composite_type(kind: CompositeKind, type_id: &str, fields: Vec<(&str, Type)>) -> Type {
+        Type::Composite(CompositeType {
+            kind,
+            is_interface: false,
+            type_id: type_id.to_string(),
+            fields: fields.into_iter().map(|(name, ty)| (name.to_string(), ty)).collect(),
+            initializers: Vec::new(),
+            raw_type: None,
+        })
+    }
+
+    // derived by hand from the encoding rules of the CCF specification
+    #[test]
+    fn test_ccf_vectors() {
+        let vectors: [(Value, &[u8]); 9] = [
+            (Value::Bool(true), &[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x00, 0xf5]),
+            (Value::String("hi".to_string()), &[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x01, 0x62, b'h', b'i']),
+            (number(NumberType::Int, "42"), &[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x04, 0xc2, 0x41, 0x2a]),
+            (number(NumberType::Int, "-1"), &[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x04, 0xc3, 0x40]),
+            (number(NumberType::Int8, "-2"), &[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x05, 0x21]),
+            (number(NumberType::UFix64, "1.5"), &[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x17, 0x1a, 0x08, 0xf0, 0xd1, 0x80]),
+            (
+                Value::Address(1),
+                &[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x03, 0x48, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01],
+            ),
+            (
+                Value::Array(vec![number(NumberType::Int16, "1"), number(NumberType::Int16, "2")]),
+                &[0xd8, 0x82, 0x82, 0xd8, 0x8b, 0xd8, 0x89, 0x06, 0x82, 0x01, 0x02],
+            ),
+            (
+                Value::Path { domain: "public".to_string(), identifier: "a".to_string() },
+                &[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x18, 0x1b, 0x82, 0x03, 0x61, b'a'],
+            ),
+        ];
+        for (value, bytes) in vectors {
+            assert_eq!(encoded(&value), bytes, "{:?}", value);
+            assert_eq!(decode(bytes), Ok(value));
+        }
+
+        // a string of type `String?` and values of `AnyStruct` with their own types
+        let none = Value::Optional(None);
+        assert_eq!(
+            encode(&none, &Type::Optional(Box::new(Type::simple("String"))), false).unwrap(),
+            [0xd8, 0x82, 0x82, 0xd8, 0x8a, 0xd8, 0x89, 0x01, 0xf6],
+        );
+        let mixed = Value::Array(vec![number(NumberType::Int, "1"), Value::String("a".to_string())]);
+        assert_eq!(encoded(&mixed), concat(&[
+            &[0xd8, 0x82, 0x82, 0xd8, 0x8b, 0xd8, 0x89, 0x18, 0x27, 0x82],
+            &[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x04, 0xc2, 0x41, 0x01],
+            &[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x01, 0x61, b'a'],
+        ]));
+        assert_eq!(decode(&encoded(&mixed)), Ok(mixed));
+    }
+
+    /// vectors of the CCF specification and of the tests of its reference implementation, with their JSON-Cadence
+    /// and EDN notations, encoded deterministically as the reference implementation does by default
+    #[test]
+    fn test_ccf_reference_vectors() {
+        let resource = Value::Resource(Composite::new("S.test.Foo", vec![("bar", number(NumberType::Int, "42"))]));
+        let event = Value::Event(Composite::new("S.test.FooEvent", vec![
+            ("a", number(NumberType::Int, "1")),
+            ("b", Value::String("foo".to_string())),
+        ]));
+        let dictionary = Value::Dictionary(vec![
+            (Value::String("c".to_string()), number(NumberType::Int, "3")),
+            (Value::String("a".to_string()), number(NumberType::Int, "1")),
+            (Value::String("b".to_string()), number(NumberType::Int, "2")),
+        ]);
+        let vectors: [(&Value, Vec<u8>); 3] = [
+            // {"type":"Resource","value":{"id":"S.test.Foo","fields":[{"name":"bar","value":{"type":"Int","value":"42"}}]}}
+            // 129([[161([h'', "S.test.Foo", [["bar", 137(4)]]])], [136(h''), [42]]])
+            (&resource, concat(&[
+                &[0xd8, 0x81, 0x82, 0x81],
+                &[0xd8, 0xa1, 0x83, 0x40, 0x6a], b"S.test.Foo", &[0x81, 0x82, 0x63], b"bar", &[0xd8, 0x89, 0x04],
+                &[0x82, 0xd8, 0x88, 0x40, 0x81, 0xc2, 0x41, 0x2a],
+            ])),
+            // {"type":"Event","value":{"id":"S.test.FooEvent","fields":[{"name":"a","value":{"type":"Int","value":"1"}},
+            //     {"name":"b","value":{"type":"String","value":"foo"}}]}}
+            // 129([[162([h'', "S.test.FooEvent", [["a", 137(4)], ["b", 137(1)]]])], [136(h''), [1, "foo"]]])
+            (&event, concat(&[
+                &[0xd8, 0x81, 0x82, 0x81],
+                &[0xd8, 0xa2, 0x83, 0x40, 0x6f], b"S.test.FooEvent",
+                &[0x82, 0x82, 0x61], b"a", &[0xd8, 0x89, 0x04], &[0x82, 0x61], b"b", &[0xd8, 0x89, 0x01],
+                &[0x82, 0xd8, 0x88, 0x40, 0x82, 0xc2, 0x41, 0x01, 0x63], b"foo",
+            ])),
+            // keys sorted by their encoding
+            // {"type":"Dictionary","value":[{"key":{"type":"String","value":"c"},"value":{"type":"Int","value":"3"}}, ...]}
+            // 130([141([137(1), 137(4)]), ["a", 1, "b", 2, "c", 3]])
+            (&dictionary, concat(&[
+                &[0xd8, 0x82, 0x82, 0xd8, 0x8d, 0x82, 0xd8, 0x89, 0x01, 0xd8, 0x89, 0x04, 0x86],
+                &[0x61, b'a', 0xc2, 0x41, 0x01, 0x61, b'b', 0xc2, 0x41, 0x02, 0x61, b'c', 0xc2, 0x41, 0x03],
+            ])),
+        ];
+        for (value, bytes) in vectors {
+            assert_eq!(encode(value, &infer_type(value), true).unwrap(), bytes, "{:?}", value);
+            let (ty, decoded) = decode_typed(&bytes).unwrap();
+            assert_eq!(ty, infer_type(value));
+            match value {
+                Value::Dictionary(entries) => {
+                    let mut sorted = entries.clone();
+                    sorted.sort_by_key(|(key, _)| format!("{:?}", key));
+                    assert_eq!(decoded, Value::Dictionary(sorted));
+                },
+                value => assert_eq!(&decoded, value),
+            }
+        }
+
+        // type definitions and fields sort by their encoding too, shorter type ids and names first:
+        // 129([[160([h'', "S.test.Foo", [["c", 137(0)], ["bb", 137(0)]]]),
+        //       160([h'01', "S.test.Aaaa", [["x", 136(h'')]]])], [136(h'01'), [[false, true]]]])
+        let foo = Value::Struct(Composite::new("S.test.Foo", vec![("bb", Value::Bool(true)), ("c", Value::Bool(false))]));
+        let outer = Value::Struct(Composite::new("S.test.Aaaa", vec![("x", foo)]));
+        assert_eq!(encode(&outer, &infer_type(&outer), true).unwrap(), concat(&[
+            &[0xd8, 0x81, 0x82, 0x82],
+            &[0xd8, 0xa0, 0x83, 0x40, 0x6a], b"S.test.Foo",
+            &[0x82, 0x82, 0x61], b"c", &[0xd8, 0x89, 0x00], &[0x82, 0x62], b"bb", &[0xd8, 0x89, 0x00],
+            &[0xd8, 0xa0, 0x83, 0x41, 0x01, 0x6b], b"S.test.Aaaa", &[0x81, 0x82, 0x61], b"x", &[0xd8, 0x88, 0x40],
+            &[0x82, 0xd8, 0x88, 0x41, 0x01, 0x81, 0x82, 0xf4, 0xf5],
+        ]));
+    }
+
+    #[test]
+    fn test_ccf_events() {
+        let event = Value::Event(Composite::new("A.0000000000000001.Token.Deposited", vec![
+            ("amount", number(NumberType::UFix64, "1.5")),
+            ("to", Value::Optional(Some(Box::new(Value::Address(1))))),
+        ]));
+        let bytes = concat(&[
+            &[0xd8, 0x81, 0x82, 0x81],
+            &[0xd8, 0xa2, 0x83, 0x40, 0x78, 0x22],
+            b"A.0000000000000001.Token.Deposited",
+            &[0x82, 0x82, 0x66],
+            b"amount",
+            &[0xd8, 0x89, 0x17, 0x82, 0x62],
+            b"to",
+            &[0xd8, 0x8a, 0xd8, 0x89, 0x03],
+            &[0x82, 0xd8, 0x88, 0x40, 0x82, 0x1a, 0x08, 0xf0, 0xd1, 0x80, 0x48, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01],
+        ]);
+        assert_eq!(encoded(&event), bytes);
+        let (ty, value) = decode_typed(&bytes).unwrap();
+        assert_eq!(ty, infer_type(&event));
+        assert_eq!(value, event);
+
+        // both orders of the fields of a type and of the type definitions
+        let value = Value::Struct(Composite::new("S.B", vec![
+            ("zz", number(NumberType::Int8, "1")),
+            ("a", Value::Struct(Composite::new("S.A", vec![("x", Value::Bool(true))]))),
+        ]));
+        let ty = infer_type(&value);
+        assert_eq!(encode(&value, &ty, false).unwrap(), concat(&[
+            &[0xd8, 0x81, 0x82, 0x82],
+            &[0xd8, 0xa0, 0x83, 0x40, 0x63], b"S.B", &[0x82, 0x82, 0x62], b"zz", &[0xd8, 0x89, 0x05],
+            &[0x82, 0x61], b"a", &[0xd8, 0x88, 0x41, 0x01],
+            &[0xd8, 0xa0, 0x83, 0x41, 0x01, 0x63], b"S.A", &[0x81, 0x82, 0x61], b"x", &[0xd8, 0x89, 0x00],
+            &[0x82, 0xd8, 0x88, 0x40, 0x82, 0x01, 0x81, 0xf5],
+        ]));
+        let deterministic = encode(&value, &ty, true).unwrap();
+        assert_eq!(deterministic, concat(&[
+            &[0xd8, 0x81, 0x82, 0x82],
+            &[0xd8, 0xa0, 0x83, 0x40, 0x63], b"S.A", &[0x81, 0x82, 0x61], b"x", &[0xd8, 0x89, 0x00],
+            &[0xd8, 0xa0, 0x83, 0x41, 0x01, 0x63], b"S.B", &[0x82, 0x82, 0x61], b"a", &[0xd8, 0x88, 0x40],
+            &[0x82, 0x62], b"zz", &[0xd8, 0x89, 0x05],
+            &[0x82, 0xd8, 0x88, 0x41, 0x01, 0x82, 0x81, 0xf5, 0x01],
+        ]));
+        let Ok(Value::Struct(decoded)) = decode(&deterministic) else {
+            panic!("not a struct")
+        };
+        assert_eq!(decoded.field("zz"), Some(&number(NumberType::Int8, "1")));
+        assert_eq!(decoded.fields[0].0, "a");
+
+        let dictionary = Value::Dictionary(vec![
+            (Value::String("b".to_string()), number(NumberType::Int8, "1")),
+            (Value::String("a".to_string()), number(NumberType::Int8, "2")),
+        ]);
+        let prefix: &[u8] = &[0xd8, 0x82, 0x82, 0xd8, 0x8d, 0x82, 0xd8, 0x89, 0x01, 0xd8, 0x89, 0x05, 0x84];
+        assert_eq!(encoded(&dictionary), concat(&[prefix, &[0x61, b'b', 0x01, 0x61, b'a', 0x02]]));
+        assert_eq!(encode(&dictionary, &infer_type(&dictionary), true).unwrap(), concat(&[prefix, &[0x61, b'a', 0x02, 0x61, b'b', 0x01]]));
+    }
+
+    #[test]
+    fn test_ccf_round_trip() {
+        let node = CompositeType {
+            kind: CompositeKind::Structure,
+            is_interface: false,
+            type_id: "S.Node".to_string(),
+            fields: vec![("next".to_string(), Type::Optional(Box::new(composite_type(CompositeKind::Structure, "S.Node", Vec::new()))))],
+            initializers: vec![vec![Parameter { label: "_".to_string(), identifier: "next".to_string(), ty: Type::simple("AnyStruct") }]],
+            raw_type: None,
+        };
+        let receiver = CompositeType {
+            kind: CompositeKind::Resource,
+            is_interface: true,
+            type_id: "A.0000000000000001.Token.Receiver".to_string(),
+            fields: Vec::new(),
+            initializers: Vec::new(),
+            raw_type: None,
+        };
+        let values = [
+            Value::Void,
+            Value::Character("é".to_string()),
+            number(NumberType::Int, "-340282366920938463463374607431768211457"),
+            number(NumberType::UInt256, "115792089237316195423570985008687907853269984665640564039457584007913129639935"),
+            number(NumberType::Word64, "18446744073709551615"),
+            number(NumberType::Int64, "-9223372036854775808"),
+            number(NumberType::Fix64, "-0.5"),
+            Value::Optional(Some(Box::new(Value::Optional(Some(Box::new(Value::Bool(false))))))),
+            Value::Resource(Composite::new("A.0000000000000001.Token.Vault", vec![
+                ("uuid", number(NumberType::UInt64, "7")),
+                ("balance", number(NumberType::UFix64, "10")),
+            ])),
+            Value::Enum(Composite::new("S.Color", vec![("rawValue", number(NumberType::UInt8, "1"))])),
+            Value::Dictionary(vec![(number(NumberType::UInt8, "1"), Value::Array(Vec::new()))]),
+            Value::Type(Type::Composite(node)),
+            Value::Type(Type::Dictionary(
+                Box::new(Type::simple("String")),
+                Box::new(Type::ConstantSizedArray(Box::new(Type::Capability(None)), 2)),
+            )),
+            Value::Capability {
+                id: 3,
+                address: 1,
+                borrow_type: Type::Reference {
+                    authorization: Authorization::Disjunction(vec!["S.E".to_string(), "S.F".to_string()]),
+                    ty: Box::new(Type::Intersection {
+                        type_id: "{A.0000000000000001.Token.Receiver}".to_string(),
+                        types: vec![Type::Composite(receiver)],
+                    }),
+                },
+            },
+            Value::Function(FunctionType {
+                is_view: false,
+                type_parameters: vec![("T".to_string(), None)],
+                parameters: vec![Parameter { label: "_".to_string(), identifier: "x".to_string(), ty: Type::simple("Int") }],
+                return_type: Box::new(Type::Reference { authorization: Authorization::Mapping("S.M".to_string()), ty: Box::new(Type::simple("Void")) }),
+            }),
+        ];
+        for value in values {
+            assert_eq!(decode(&encoded(&value)), Ok(value.clone()), "{:?}", value);
+            assert_eq!(decode(&encode(&value, &infer_type(&value), true).unwrap()), Ok(value.clone()), "{:?}", value);
+            assert_eq!(decode(&encode(&value, &Type::simple("AnyStruct"), false).unwrap()), Ok(value));
+        }
+    }
+
+    #[test]
+    fn test_ccf_validation() {
+        assert_eq!(error(&[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x00, 0xf5, 0x00]), "invalid CBOR at byte 7: trailing bytes after the data item");
+        assert_eq!(error(&[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x18, 0x00, 0xf5]), "invalid CBOR at byte 5: head is not as short as possible");
+        assert_eq!(error(&[0xd8, 0x82, 0x9f, 0xff]), "invalid CBOR at byte 2: indefinite lengths are not allowed");
+        assert_eq!(error(&[0xd8, 0x82, 0xa0]), "invalid CBOR at byte 2: maps are not allowed");
+        assert_eq!(error(&[0x81; 300]), "invalid CBOR at byte 256: too deeply nested");
+        assert_eq!(error(&[0x82, 0xf5, 0xf5]), "expected a message tagged 129 or 130, got an array");
+        assert_eq!(error(&[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x18, 0x64, 0xf5]), "type: unknown simple type id 100");
+        assert_eq!(error(&[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x05, 0x18, 0xc8]), "value: out of range for `Int8`");
+        assert_eq!(error(&[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x04, 0x01]), "value: expected a bignum for `Int`, got an unsigned integer");
+        assert_eq!(error(&[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x04, 0xc2, 0x42, 0x00, 0x01]), "value: bignum with leading zero bytes");
+        assert_eq!(error(&[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x02, 0x62, b'a', b'b']), "value: expected a single character, got `ab`");
+        assert_eq!(error(&[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x03, 0x41, 0x01]), "value: expected an address of 8 bytes, got 1");
+        assert_eq!(error(&[0xd8, 0x82, 0x82, 0xd8, 0x88, 0x40, 0x80]), "type: no type is defined with id 0x");
+        assert_eq!(
+            error(&[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x18, 0x27, 0x01]),
+            "value: expected a value of `AnyStruct` tagged with its type, got an unsigned integer",
+        );
+        assert_eq!(
+            error(&[0xd8, 0x82, 0x82, 0xd8, 0x8d, 0x82, 0xd8, 0x8a, 0xd8, 0x89, 0x00, 0xd8, 0x89, 0x00, 0x82, 0xf6, 0xf5]),
+            "value[0].key: an optional cannot be a key",
+        );
+        assert_eq!(
+            error(&[0xd8, 0x82, 0x82, 0xd8, 0x89, 0x18, 0x1a, 0x82, 0x02, 0x61, b'a']),
+            "value: expected the domain 1 of `StoragePath`",
+        );
+        let definitions = concat(&[
+            &[0xd8, 0x81, 0x82, 0x82],
+            &[0xd8, 0xa0, 0x83, 0x40, 0x61, b'S', 0x80],
+            &[0xd8, 0xa0, 0x83, 0x40, 0x61, b'T', 0x80],
+            &[0x82, 0xd8, 0x88, 0x40, 0x80],
+        ]);
+        assert_eq!(error(&definitions), "typedefs[1]: type id 0x is defined twice");
+        let fields = concat(&[
+            &[0xd8, 0x81, 0x82, 0x81],
+            &[0xd8, 0xa0, 0x83, 0x40, 0x61, b'S', 0x81, 0x82, 0x61, b'x', 0xd8, 0x89, 0x00],
+            &[0x82, 0xd8, 0x88, 0x40, 0x80],
+        ]);
+        assert_eq!(error(&fields), "value: expected 1 fields for `S`, got 0");
+
+        let struct_type = composite_type(CompositeKind::Structure, "S.A", vec![("x", Type::simple("Bool"))]);
+        assert_eq!(
+            encode(&Value::Struct(Composite::new("S.A", Vec::new())), &struct_type, false).unwrap_err().to_string(),
+            "value: the struct is missing the field `x`",
+        );
+        assert_eq!(
+            encode(&Value::Bool(true), &Type::simple("Int"), false).unwrap_err().to_string(),
+            "value: expected a value of type `Int`, got a boolean",
+        );
+        assert_eq!(
+            encode(&Value::Bool(true), &Type::simple("AuthAccount.Inbox"), false).unwrap_err().to_string(),
+            "type: `AuthAccount.Inbox` has no CCF type id",
+        );
+    }
+}
+
+#[cfg(test)]
+mod convert_tests {
+    use std::collections::HashMap;
+
+    use crate::ccf::ccf;
+    use crate::interpreter::number::NumberError;
+    use crate::json_cadence::convert::{
+        Fix64, FromCadence, Int, ToCadence, UFix64, Word8, from_json_cadence, to_json_cadence,
+    };
+    use crate::json_cadence::json_cadence::{
+        Type, Value,
+    };
+
+    #[derive(Debug, PartialEq, ToCadence, FromCadence)]
+    #[cadence(type_id = "A.0000000000000001.Shapes.Kind")]
+    enum Kind {
+        Triangle,
+        Square,
+    }
+
+    #[derive(Debug, PartialEq, ToCadence, FromCadence)]
+    #[cadence(type_id = "A.0000000000000001.Shapes.Shape")]
+    struct Shape {
+        kind: Kind,
+        #[cadence(rename = "sideLengths")]
+        side_lengths: Vec<u64>,
+        labels: HashMap<String, i8>,
+        parent: Option<String>,
+        r#type: bool,
+    }
+
+    #[derive(Debug, PartialEq, ToCadence, FromCadence)]
+    #[cadence(kind = "event", type_id = "A.0000000000000001.Shapes.Moved")]
+    struct Moved {
+        shape: Shape,
+        by: i128,
+        ids: Vec<Option<u128>>,
+    }
+
+    fn square() -> Shape {
+        Shape {
+            kind: Kind::Square,
+            side_lengths: vec![2, 2, 2, 2],
+            labels: HashMap::from([("b".to_string(), -1), ("a".to_string(), 1)]),
+            parent: None,
+            r#type: true,
+        }
+    }
+
+    #[test]
+    fn test_derived_conversions() {
+        assert_eq!(to_json_cadence(&square()), concat!(
+            r#"{"type":"Struct","value":{"id":"A.0000000000000001.Shapes.Shape","fields":["#,
+            r#"{"name":"kind","value":{"type":"Enum","value":{"id":"A.0000000000000001.Shapes.Kind","fields":[{"name":"rawValue","value":{"type":"UInt8","value":"1"}}]}}},"#,
+            r#"{"name":"sideLengths","value":{"type":"Array","value":[{"type":"UInt64","value":"2"},{"type":"UInt64","value":"2"},{"type":"UInt64","value":"2"},{"type":"UInt64","value":"2"}]}},"#,
+            r#"{"name":"labels","value":{"type":"Dictionary","value":["#,
+            r#"{"key":{"type":"String","value":"a"},"value":{"type":"Int8","value":"1"}},{"key":{"type":"String","value":"b"},"value":{"type":"Int8","value":"-1"}}]}},"#,
+            r#"{"name":"parent","value":{"type":"Optional","value":null}},"#,
+            r#"{"name":"type","value":{"type":"Bool","value":true}}]}}"#,
+        ));
+        assert_eq!(from_json_cadence::<Shape>(&to_json_cadence(&square())), Ok(square()));
+
+        let moved = Moved { shape: square(), by: i128::MIN, ids: vec![Some(u128::MAX), None] };
+        let Value::Event(event) = moved.to_cadence() else {
+            panic!("not an event")
+        };
+        assert_eq!(event.type_id, "A.0000000000000001.Shapes.Moved");
+        assert_eq!(Moved::from_cadence(&Value::Event(event)), Ok(moved));
+
+        // the types encode values as CCF
+        let Type::Composite(shape_type) = Shape::cadence_type() else {
+            panic!("not a composite type")
+        };
+        assert_eq!(shape_type.fields[1], ("sideLengths".to_string(), Type::VariableSizedArray(Box::new(Type::simple("UInt64")))));
+        assert_eq!(shape_type.initializers[0].len(), 5);
+        let moved = Moved { shape: square(), by: 7, ids: Vec::new() };
+        let bytes = ccf::encode(&moved.to_cadence(), &Moved::cadence_type(), true).unwrap();
+        assert_eq!(ccf::decode(&bytes).map(|value| Moved::from_cadence(&value)), Ok(Ok(moved)));
+    }
+
+    #[test]
+    fn test_conversion_errors() {
+        let shape = |field: &str, value: &str| {
+            let json = to_json_cadence(&square());
+            let start = json.find(&format!(r#"{{"name":"{}""#, field)).unwrap();
+            let end = start + json[start..].find(r#"}},{"name""#).unwrap() + 2;
+            let json = format!(r#"{}{{"name":"{}","value":{}}}{}"#, &json[..start], field, value, &json[end..]);
+            from_json_cadence::<Shape>(&json).unwrap_err().to_string()
+        };
+        assert_eq!(
+            shape("sideLengths", r#"{"type":"Array","value":[{"type":"UInt8","value":"2"}]}"#),
+            "sideLengths[0]: expected `UInt64`, got `UInt8`",
+        );
+        assert_eq!(
+            shape("kind", r#"{"type":"Enum","value":{"id":"A.0000000000000001.Shapes.Kind","fields":[{"name":"rawValue","value":{"type":"UInt8","value":"5"}}]}}"#),
+            "kind.rawValue: `A.0000000000000001.Shapes.Kind` has no case with the raw value 5",
+        );
+        assert_eq!(
+            shape("labels", r#"{"type":"Dictionary","value":[{"key":{"type":"Int8","value":"1"},"value":{"type":"Int8","value":"1"}}]}"#),
+            "labels[0].key: expected `String`, got `Int8`",
+        );
+        assert_eq!(
+            from_json_cadence::<Shape>(r#"{"type":"Struct","value":{"id":"S.Shape","fields":[]}}"#).unwrap_err().to_string(),
+            "expected `A.0000000000000001.Shapes.Shape`, got `S.Shape`",
+        );
+        assert_eq!(
+            from_json_cadence::<Shape>(r#"{"type":"Struct","value":{"id":"A.0000000000000001.Shapes.Shape","fields":[]}}"#).unwrap_err().to_string(),
+            "`A.0000000000000001.Shapes.Shape` has no field `kind`",
+        );
+        assert_eq!(from_json_cadence::<Option<u8>>(r#"{"type":"UInt8","value":"1"}"#).unwrap_err().to_string(), "expected `Optional`, got `UInt8`");
+        assert_eq!(from_json_cadence::<Option<u8>>(r#"{"type":"Optional","value":{"type":"UInt8","value":"1"}}"#), Ok(Some(1)));
+    }
+
+    #[test]
+    fn test_checked_numbers() {
+        let amount: UFix64 = "12.5".parse().unwrap();
+        assert_eq!(to_json_cadence(&amount), r#"{"type":"UFix64","value":"12.50000000"}"#);
+        assert_eq!(from_json_cadence::<UFix64>(r#"{"type":"UFix64","value":"12.50000000"}"#), Ok(amount.clone()));
+        assert_eq!(
+            from_json_cadence::<UFix64>(r#"{"type":"Fix64","value":"12.50000000"}"#).unwrap_err().to_string(),
+            "expected `UFix64`, got `Fix64`",
+        );
+        assert_eq!("-1.0".parse::<UFix64>(), Err(NumberError::Underflow));
+        assert_eq!("256".parse::<Word8>(), Err(NumberError::Overflow));
+        assert_eq!(UFix64::new(amount.number().clone()), Some(amount.clone()));
+        assert_eq!(Fix64::new(amount.number().clone()), None);
+
+        let balances = HashMap::from([("1".parse::<Int>().unwrap(), "-0.5".parse::<Fix64>().unwrap())]);
+        assert_eq!(from_json_cadence::<HashMap<Int, Fix64>>(&to_json_cadence(&balances)), Ok(balances));
+    }
+}
+
+#[cfg(test)]
+mod abi_tests {
+    use crate::abi::abi::{
+        Abi, EntryPoint, abi,
+    };
+    use crate::checker::checker::check;
+    use crate::checker::types::{
+        NumberType, Type,
+    };
+    use crate::json_cadence::json::parse_json;
+    use crate::parser::parser::parse;
+
+    fn abi_of(source: &str) -> Abi {
+        let program = parse(source).unwrap_or_else(|e| panic!("{:?}", e));
+        let checked = check(&program);
+        assert_eq!(checked.errors().count(), 0, "{:?}", checked.diagnostics);
+        abi(&program, &checked)
+    }
+
+    #[test]
+    fn test_contract_abi() {
+        let abi = abi_of(r#"
+access(all) contract interface Receiver {
+    access(all) resource interface Deposit {
+        access(all) fun deposit(from: @{FungibleVault})
+    }
+    access(all) resource interface FungibleVault {}
+}
+
+access(all) contract Token: Receiver {
+    access(all) var totalSupply: UFix64
+    access(self) let minted: Int
+    access(all) event Deposited(amount: UFix64, to: Address?)
+
+    access(all) enum Color: UInt8 {
+        access(all) case red
+        access(all) case green
+    }
+
+    access(all) resource Vault: Receiver.Deposit, Receiver.FungibleVault {
+        access(all) var balance: UFix64
+        init(balance: UFix64) { self.balance = balance }
+        access(all) fun deposit(from vault: @{Receiver.FungibleVault}) {
+            destroy vault
+        }
+        access(all) view fun split(_ parts: Int): [UFix64] { return [] }
+    }
+
+    access(contract) resource Minter {}
+
+    access(account) fun mint(): @Vault { return <-create Vault(balance: 1.0) }
+
+    init() {
+        self.totalSupply = 0.0
+        self.minted = 0
+    }
+}
+"#);
+        assert_eq!(abi.entry_point, None);
+        let [receiver, token] = abi.composites.as_slice() else {
+            panic!("{:?}", abi.composites)
+        };
+        assert!(receiver.is_interface);
+        assert_eq!(receiver.nested.iter().map(|nested| nested.name.as_str()).collect::<Vec<_>>(), ["Receiver.Deposit", "Receiver.FungibleVault"]);
+        assert_eq!(token.conformances, ["Receiver"]);
+        // private fields, account functions and contract-only types are not part of the ABI
+        assert_eq!(token.fields.iter().map(|field| field.name.as_str()).collect::<Vec<_>>(), ["totalSupply"]);
+        assert!(token.functions.is_empty());
+        assert_eq!(token.events[0].name, "Token.Deposited");
+        assert_eq!(token.events[0].parameters[1].ty, Type::Optional(Box::new(Type::Address)));
+        assert_eq!(token.nested.iter().map(|nested| nested.name.as_str()).collect::<Vec<_>>(), ["Token.Color", "Token.Vault"]);
+        assert_eq!(token.nested[0].cases, ["red", "green"]);
+
+        let vault = &token.nested[1];
+        assert_eq!(vault.conformances, ["Receiver.Deposit", "Receiver.FungibleVault"]);
+        let [deposit, split] = vault.functions.as_slice() else {
+            panic!("{:?}", vault.functions)
+        };
+        assert_eq!((deposit.parameters[0].label.as_deref(), deposit.parameters[0].name.as_str()), (Some("from"), "vault"));
+        assert_eq!(deposit.return_type, Type::Void);
+        assert!(split.is_view);
+        assert_eq!(split.parameters[0].label, None);
+        assert_eq!(split.return_type, Type::VariableSized(Box::new(Type::Number(NumberType::UFix64))));
+
+        let json = abi.to_json();
+        assert_eq!(parse_json(&json.pretty()), Ok(json.clone()));
+        assert_eq!(
+            json.get("composites").map(|composites| composites.to_string().contains(
+                r#"{"name":"split","view":true,"parameters":[{"label":null,"name":"parts","type":"Int"}],"returnType":"[UFix64]"}"#
+            )),
+            Some(true),
+        );
+    }
+
+    #[test]
+    fn test_entry_points() {
+        let transaction = abi_of(r#"
+transaction(amount: UFix64, to: Address) {
+    prepare(signer: auth(Storage) &Account, payer: &Account) {}
+}
+"#);
+        let Some(EntryPoint::Transaction { parameters, signers }) = &transaction.entry_point else {
+            panic!("{:?}", transaction.entry_point)
+        };
+        assert_eq!(parameters.iter().map(|parameter| parameter.ty.to_string()).collect::<Vec<_>>(), ["UFix64", "Address"]);
+        assert_eq!(signers.iter().map(Type::to_string).collect::<Vec<_>>(), ["auth(Storage) &Account", "&Account"]);
+
+        let script = abi_of("access(all) fun main(address: Address, _ limit: Int): {String: [Int]} { return {} }");
+        assert_eq!(
+            script.to_json().get("entryPoint").map(ToString::to_string),
+            Some(concat!(
+                r#"{"kind":"script","parameters":[{"label":"address","name":"address","type":"Address"},"#,
+                r#"{"label":null,"name":"limit","type":"Int"}],"returnType":"{String: [Int]}"}"#,
+            ).to_string()),
+        );
+        assert_eq!(abi_of("access(all) fun helper() {}").to_json().pretty(), "{\n  \"composites\": [],\n  \"entryPoint\": null\n}");
+    }
+
+    #[test]
+    fn test_imported_conformances() {
+        let abi = abi_of(r#"
+import FungibleToken from 0x01
+
+access(all) contract Token: FungibleToken {
+    access(all) resource Vault: FungibleToken.Vault, FungibleToken.Receiver {}
+}
+"#);
+        assert_eq!(abi.composites[0].conformances, ["FungibleToken"]);
+        assert_eq!(abi.composites[0].nested[0].conformances, ["FungibleToken.Vault", "FungibleToken.Receiver"]);
+    }
+}
+
+#[cfg(test)]
+mod bindgen_tests {
+    use crate::bindgen::bindgen::{
+        BindgenError, generate,
+    };
+
+    #[test]
+    fn test_transaction_bindings() {
+        let source = r#"
+transaction(amount: UFix64, to: Address, memo: String?, type: UInt8, sideLengths: {String: [Int64]}) {
+    prepare(signer: auth(Storage) &Account, to: &Account) {}
+}
+"#;
+        let code = generate("transfer.cdc", "transfer", source).unwrap().unwrap();
+        assert!(code.starts_with("/// `transfer.cdc`, a transaction\npub mod transfer {\n"));
+        assert!(code.contains(&format!("pub const CODE: &str = {:?};", source)));
+        assert!(code.contains(concat!(
+            "pub fn arguments(amount: ::cadence_rs::json_cadence::convert::UFix64, to: ::cadence_rs::json_cadence::convert::Address, ",
+            "memo: Option<String>, r#type: u8, side_lengths: ::std::collections::HashMap<String, Vec<i64>>, ",
+            "signer: ::cadence_rs::json_cadence::convert::Address, to_: ::cadence_rs::json_cadence::convert::Address)",
+        )), "{}", code);
+        assert!(code.contains("/// - `to_`: `&Account`"));
+        assert!(code.contains("authorizers: vec![signer, to_],"));
+        assert!(!code.contains("fn result"));
+    }
+
+    #[test]
+    fn test_script_bindings() {
+        let code = generate("getNFTIds.cdc", "get_nft_ids", "access(all) fun main(address: Address): [UInt64]? { return nil }").unwrap().unwrap();
+        assert!(code.contains("pub fn arguments(address: ::cadence_rs::json_cadence::convert::Address) -> Vec<String> {"));
+        assert!(code.contains("pub fn result(json: &str) -> Result<Option<Vec<u64>>, ::cadence_rs::json_cadence::json_cadence::DecodeError> {"));
+
+        // numbers no Rust integer holds are checked, also as dictionary keys, abstract number types are values
+        let code = generate("balances.cdc", "balances", "access(all) fun main(total: Int, limit: Number): {Fix64: Word8} { return {} }").unwrap().unwrap();
+        assert!(code.contains(concat!(
+            "pub fn arguments(total: ::cadence_rs::json_cadence::convert::Int, ",
+            "limit: ::cadence_rs::json_cadence::json_cadence::Value) -> Vec<String> {",
+        )), "{}", code);
+        assert!(code.contains(concat!(
+            "Result<::std::collections::HashMap<::cadence_rs::json_cadence::convert::Fix64, ",
+            "::cadence_rs::json_cadence::convert::Word8>, ",
+        )), "{}", code);
+
+        // scripts returning nothing have no result to decode, contracts no bindings
+        let code = generate("log.cdc", "log", "access(all) fun main() {}").unwrap().unwrap();
+        assert!(code.contains("pub fn arguments() -> Vec<String> {\n        vec![]\n    }"));
+        assert!(!code.contains("fn result"));
+        assert_eq!(generate("Token.cdc", "token", "access(all) contract Token {}"), Ok(None));
+
+        assert_eq!(
+            generate("broken.cdc", "broken", "access(all) fun main(): Int {\n    return true\n}").map(|_| ()),
+            Err(BindgenError { message: "mismatched types: expected `Int`, got `Bool`".to_string(), path: "broken.cdc:2:12".to_string() }),
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+
+use crate::ast::visit_mut::{
+    self, VisitorMut,
+};
+use crate::ast::{
+    Access, Authorization, CompositeDeclaration, CompositeKind, Declaration, Expression, FieldDeclaration,
+    FunctionDeclaration, Identifier, InterfaceDeclaration, NominalType, Parameter, Program, Span, SpecialFunctionKind,
+    Statement, Type,
+};
+use crate::checker::diagnostic::Diagnostic;
+use crate::cst::build::build_green;
+use crate::cst::syntax::SyntaxNode;
+use crate::format::config::Config;
+use crate::format::format::trivia;
+use crate::parser::parser::{
+    ParseError, parse_with_errors,
+};
+use crate::printer::printer::{
+    Printer, print_type,
+};
+
+/// the entitlements of `auth(...) &Account`, in the order they are written
+const ACCOUNT_ENTITLEMENTS: [&str; 5] = ["Storage", "Contracts", "Keys", "Inbox", "Capabilities"];
+
+/// account members that link capabilities, which capability controllers replace
+const LINKING: [&str; 4] = ["link", "unlink", "getLinkTarget", "getCapability"];
+
+/// a program migrated to Cadence 1.0
+#[derive(Clone, Debug, PartialEq)]
+pub struct Migration {
+    pub source: String,
+    /// what is left to do by hand, as warnings at spans of the source before migration
+    pub follow_ups: Vec<Diagnostic>,
+}
+
+/// migrate pre-1.0 source and format it with `config`, sources with parse errors are left alone
+pub fn migrate_source(source: &str, config: &Config) -> Result<Migration, Vec<ParseError>> {
+    let (mut program, errors) = parse_with_errors(source);
+    if !errors.is_empty() {
+        return Err(errors)
+    }
+    let tree = SyntaxNode::new_root(build_green(source, &program));
+    let follow_ups = migrate(&mut program);
+    let mut printer = Printer::with_options(config.print_options()).with_trivia(trivia(&tree));
+    printer.program(&program);
+    Ok(Migration { source: printer.finish(), follow_ups })
+}
+
+/// rewrite the program to Cadence 1.0 where it can be done mechanically:
+/// - `pub` and `pub(set)` become `access(all)`, `priv` and missing member modifiers `access(self)`
+/// - `R{I}` becomes `R`, and `AnyResource{I}` the intersection type `{I}`
+/// - `AuthAccount` parameters become `auth(...) &Account` with the entitlements their uses need,
+///   storage functions move to `account.storage`, `PublicAccount` becomes `&Account`
+/// - destructors that only destroy fields and emit an event become a `ResourceDestroyed` event
+///
+/// returns the follow-ups in source order
+pub fn migrate(program: &mut Program) -> Vec<Diagnostic> {
+    let mut events = HashMap::new();
+    collect_events(&program.declarations, &mut events);
+    let mut migrator = Migrator { events, accounts: Vec::new(), follow_ups: Vec::new() };
+    migrator.visit_program_mut(program);
+    migrator.follow_ups.sort_by_key(|follow_up| follow_up.span.at);
+    migrator.follow_ups
+}
+
+/// parameters of events by event name
+fn collect_events(declarations: &[Declaration], events: &mut HashMap<String, Vec<Parameter>>) {
+    for declaration in declarations.iter() {
+        match declaration {
+            Declaration::Composite(d) if d.kind == CompositeKind::Event => {
+                events.insert(d.identifier.name.clone(), d.parameters.clone());
+            },
+            Declaration::Composite(d) => collect_events(&d.members, events),
+            Declaration::Interface(d) => collect_events(&d.members, events),
+            _ => (),
+        }
+    }
+}
+
+/// the `AuthAccount` parameters of a function being migrated
+#[derive(Default)]
+struct Accounts {
+    names: Vec<String>,
+    /// what the uses of the parameters need
+    entitlements: Vec<&'static str>,
+}
+
+struct Migrator {
+    events: HashMap<String, Vec<Parameter>>,
+    /// innermost function last
+    accounts: Vec<Accounts>,
+    follow_ups: Vec<Diagnostic>,
+}
+
+impl Migrator {
+    fn follow_up(&mut self, message: &str, span: Span) {
+        self.follow_ups.push(Diagnostic::warning(message, span));
+    }
+
+    /// the function declaring an `AuthAccount` parameter, shadowing is not considered
+    fn accounts_of(&mut self, name: &str) -> Option<&mut Accounts> {
+        self.accounts.iter_mut().rev().find(|accounts| accounts.names.iter().any(|account| account == name))
+    }
+
+    /// `Some(name)` for an `AuthAccount` parameter, `Some(None)` for `self.account` of a contract
+    fn account(&mut self, expression: &Expression) -> Option<Option<String>> {
+        match expression {
+            Expression::Identifier(identifier) => self.accounts_of(&identifier.name).map(|_| Some(identifier.name.clone())),
+            Expression::Member { expression, identifier, optional: false, .. } if identifier.name == "account" => {
+                matches!(&**expression, Expression::Identifier(base) if base.name == "self").then_some(None)
+            },
+            _ => None,
+        }
+    }
+
+    /// replace the destructor of a resource with a `ResourceDestroyed` event,
+    /// if it only destroys fields and emits at most one event whose arguments are literals or fields
+    fn destructor(&mut self, resource: &str, members: &mut Vec<Declaration>) {
+        let Some(index) = members.iter().position(|member| {
+            matches!(member, Declaration::SpecialFunction(d) if d.kind == SpecialFunctionKind::Destructor)
+        }) else {
+            return
+        };
+        let Declaration::SpecialFunction(destructor) = &members[index] else {
+            return
+        };
+        let function = &destructor.function;
+        let mut mechanical = function.pre_conditions.is_empty() && function.post_conditions.is_empty();
+        let mut emitted = None;
+        for statement in function.body.iter().flat_map(|body| body.statements.iter()) {
+            match statement {
+                Statement::Destroy { expression, .. } if is_self_member(expression) => (),
+                Statement::Emit { invocation, span } if emitted.is_none() => match self.resource_destroyed(invocation) {
+                    Some((event, parameters)) => emitted = Some((event, parameters, *span)),
+                    None => mechanical = false,
+                },
+                _ => mechanical = false,
+            }
+        }
+        if !mechanical {
+            let message = "custom destructors were removed, what this one does must move elsewhere: \
+                only destroying fields and emitting one event are migrated";
+            self.follow_up(message, function.identifier.span);
+            return
+        }
+
+        let (identifier, span) = (function.identifier.span, function.span);
+        let Some((event, parameters, emit)) = emitted else {
+            // nested resources are destroyed with their owner
+            members.remove(index);
+            return
+        };
+        let message = format!(
+            "`{}` is no longer emitted when a `{}` is destroyed, `{}.ResourceDestroyed` is emitted instead",
+            event, resource, resource,
+        );
+        self.follow_up(&message, emit);
+        members[index] = Declaration::Composite(CompositeDeclaration {
+            access: Access::All,
+            kind: CompositeKind::Event,
+            identifier: Identifier::new("ResourceDestroyed", identifier),
+            conformances: Vec::new(),
+            parameters,
+            members: Vec::new(),
+            span,
+        });
+    }
+
+    /// the event an `emit` invocation emits, and its parameters defaulting to the arguments
+    fn resource_destroyed(&self, invocation: &Expression) -> Option<(String, Vec<Parameter>)> {
+        let Expression::Invocation { invoked, arguments, .. } = invocation else {
+            return None
+        };
+        let event = match &**invoked {
+            Expression::Identifier(identifier) | Expression::Member { identifier, .. } => &identifier.name,
+            _ => return None,
+        };
+        let declared = self.events.get(event)?;
+        if declared.len() != arguments.len() {
+            return None
+        }
+        let parameters = declared.iter().zip(arguments.iter()).map(|(parameter, argument)| {
+            is_default_value(&argument.expression).then(|| Parameter {
+                label: None,
+                identifier: Identifier::new(&parameter.identifier.name, argument.expression.span()),
+                type_annotation: parameter.type_annotation.clone(),
+                default: Some(argument.expression.clone()),
+                span: argument.expression.span(),
+            })
+        });
+        Some((event.clone(), parameters.collect::<Option<Vec<_>>>()?))
+    }
+}
+
+impl VisitorMut for Migrator {
+    fn visit_composite_declaration_mut(&mut self, declaration: &mut CompositeDeclaration) {
+        if declaration.kind != CompositeKind::Event {
+            default_access(&mut declaration.members);
+        }
+        if declaration.kind == CompositeKind::Resource {
+            self.destructor(&declaration.identifier.name, &mut declaration.members);
+        }
+        visit_mut::walk_composite_declaration(self, declaration)
+    }
+
+    fn visit_interface_declaration_mut(&mut self, declaration: &mut InterfaceDeclaration) {
+        default_access(&mut declaration.members);
+        if declaration.kind == CompositeKind::Resource {
+            self.destructor(&declaration.identifier.name, &mut declaration.members);
+        }
+        visit_mut::walk_interface_declaration(self, declaration)
+    }
+
+    fn visit_field_declaration_mut(&mut self, declaration: &mut FieldDeclaration) {
+        if declaration.access == Access::PubSet {
+            let message = format!("`pub(set)` was removed, writes of `{}` from outside need a setter function", declaration.identifier.name);
+            self.follow_up(&message, declaration.identifier.span);
+        }
+        visit_mut::walk_field_declaration(self, declaration)
+    }
+
+    fn visit_function_declaration_mut(&mut self, declaration: &mut FunctionDeclaration) {
+        // without a body there are no uses to find the entitlements from
+        let mut migrated = Vec::new();
+        if declaration.body.is_some() {
+            for (index, parameter) in declaration.parameters.iter_mut().enumerate() {
+                if is_nominal(&parameter.type_annotation.ty, "AuthAccount") {
+                    parameter.type_annotation.ty = account_reference(&[], parameter.type_annotation.ty.span());
+                    migrated.push(index);
+                }
+            }
+        }
+        let names = migrated.iter().map(|&index| declaration.parameters[index].identifier.name.clone()).collect();
+        self.accounts.push(Accounts { names, entitlements: Vec::new() });
+        visit_mut::walk_function_declaration(self, declaration);
+
+        let accounts = self.accounts.pop().unwrap_or_default();
+        let entitlements: Vec<&str> = ACCOUNT_ENTITLEMENTS.into_iter().filter(|e| accounts.entitlements.contains(e)).collect();
+        for index in migrated {
+            let ty = &mut declaration.parameters[index].type_annotation.ty;
+            *ty = account_reference(&entitlements, ty.span());
+        }
+    }
+
+    fn visit_access_mut(&mut self, access: &mut Access) {
+        match access {
+            Access::Pub | Access::PubSet => *access = Access::All,
+            Access::Priv => *access = Access::Self_,
+            _ => (),
+        }
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        match ty {
+            Type::Nominal(nominal) if is_named(nominal, "PublicAccount") => {
+                *ty = account_reference(&[], nominal.identifier.span);
+                return
+            },
+            Type::Nominal(nominal) if is_named(nominal, "AuthAccount") => {
+                let message = "`AuthAccount` is now `auth(...) &Account`, the entitlements needed here must be chosen by hand";
+                self.follow_up(message, nominal.identifier.span);
+            },
+            Type::Restricted { ty: base, .. } if base.as_deref().is_some_and(|base| is_nominal(base, "AnyStruct") || is_nominal(base, "AnyResource")) => {
+                *base = None;
+            },
+            Type::Restricted { ty: Some(base), span, .. } => {
+                let (base, span) = ((**base).clone(), *span);
+                let message = format!(
+                    "`{}` is now `{}`: restricted types were removed, limit access with entitlements instead",
+                    print_type(ty), print_type(&base),
+                );
+                self.follow_up(&message, span);
+                *ty = base;
+            },
+            Type::Reference { authorized: true, entitlements: None, span, .. } => {
+                self.follow_up("`auth` references need entitlements in Cadence 1.0", *span);
+            },
+            _ => (),
+        }
+        visit_mut::walk_type(self, ty)
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Member { expression: base, identifier, .. } => {
+                if LINKING.contains(&identifier.name.as_str()) {
+                    let message = format!("capability links were replaced by capability controllers, `{}` must be migrated by hand", identifier.name);
+                    self.follow_up(&message, identifier.span);
+                }
+                if let Some(parameter) = self.account(base) {
+                    let (storage, entitlement) = account_member(&identifier.name);
+                    if let (Some(parameter), Some(entitlement)) = (&parameter, entitlement) {
+                        let accounts = self.accounts_of(parameter).unwrap_or_else(|| unreachable!());
+                        if !accounts.entitlements.contains(&entitlement) {
+                            accounts.entitlements.push(entitlement);
+                        }
+                    }
+                    if let Some(name) = storage {
+                        let span = base.span();
+                        let account = std::mem::replace(&mut **base, Expression::Nil { span });
+                        **base = Expression::Member {
+                            expression: Box::new(account),
+                            optional: false,
+                            identifier: Identifier::new("storage", span),
+                            span,
+                        };
+                        identifier.name = name.to_string();
+                    }
+                    // the parameter is used through one of its members
+                    if parameter.is_some() {
+                        return
+                    }
+                }
+            },
+            Expression::Identifier(identifier) if self.accounts_of(&identifier.name).is_some() => {
+                let message = format!(
+                    "`{}` is now `auth(...) &Account`, where it is passed to may need other entitlements",
+                    identifier.name,
+                );
+                self.follow_up(&message, identifier.span);
+            },
+            _ => (),
+        }
+        visit_mut::walk_expression(self, expression)
+    }
+}
+
+/// composite members without access modifier were private, Cadence 1.0 requires the modifier
+fn default_access(members: &mut [Declaration]) {
+    for member in members.iter_mut() {
+        match member {
+            Declaration::Field(d) if d.access == Access::NotSpecified => d.access = Access::Self_,
+            Declaration::Function(d) if d.access == Access::NotSpecified => d.access = Access::Self_,
+            _ => (),
+        }
+    }
+}
+
+/// where a member of `AuthAccount` went in `Account`: its name in `account.storage` if it moved there,
+/// and the entitlement it needs
+fn account_member(name: &str) -> (Option<&'static str>, Option<&'static str>) {
+    match name {
+        "save" => (Some("save"), Some("Storage")),
+        "load" => (Some("load"), Some("Storage")),
+        "copy" => (Some("copy"), Some("Storage")),
+        "borrow" => (Some("borrow"), Some("Storage")),
+        "type" => (Some("type"), None),
+        "forEachStored" => (Some("forEachStored"), None),
+        "storageUsed" => (Some("used"), None),
+        "storageCapacity" => (Some("capacity"), None),
+        "contracts" => (None, Some("Contracts")),
+        "keys" => (None, Some("Keys")),
+        "inbox" => (None, Some("Inbox")),
+        "capabilities" => (None, Some("Capabilities")),
+        _ => (None, None),
+    }
+}
+
+/// `auth(E, F) &Account`, or `&Account` without entitlements
+fn account_reference(entitlements: &[&str], span: Span) -> Type {
+    let nominal = |name: &str| NominalType { identifier: Identifier::new(name, span), nested_identifiers: Vec::new() };
+    Type::Reference {
+        authorized: !entitlements.is_empty(),
+        entitlements: (!entitlements.is_empty())
+            .then(|| Authorization::Conjunction(entitlements.iter().map(|entitlement| nominal(entitlement)).collect())),
+        ty: Box::new(Type::Nominal(nominal("Account"))),
+        span,
+    }
+}
+
+fn is_nominal(ty: &Type, name: &str) -> bool {
+    matches!(ty, Type::Nominal(nominal) if is_named(nominal, name))
+}
+
+/// whether the type is the built-in type `name`
+fn is_named(nominal: &NominalType, name: &str) -> bool {
+    nominal.nested_identifiers.is_empty() && nominal.identifier.name == name
+}
+
+/// `self.a` or `self.a.b`
+fn is_self_member(expression: &Expression) -> bool {
+    let Expression::Member { expression, .. } = expression else {
+        return false
+    };
+    matches!(&**expression, Expression::Identifier(base) if base.name == "self") || is_self_member(expression)
+}
+
+/// what the parameters of a `ResourceDestroyed` event can default to: literals and fields of `self`
+fn is_default_value(expression: &Expression) -> bool {
+    match expression {
+        Expression::Nil { .. } | Expression::Bool { .. } | Expression::Integer { .. } | Expression::FixedPoint { .. } |
+        Expression::String { .. } | Expression::Path { .. } => true,
+        expression => is_self_member(expression),
+    }
+}
@@ -0,0 +1,485 @@
+use crate::ast::{
+    Access, Block, CompositeDeclaration, CompositeKind, Condition, Declaration, EntitlementDeclaration,
+    EntitlementMappingDeclaration, EntitlementRelation, EnumCaseDeclaration, FieldDeclaration, FunctionDeclaration,
+    Identifier, ImportDeclaration, ImportLocation, InterfaceDeclaration, Parameter, Purity, SpecialFunctionDeclaration,
+    SpecialFunctionKind, TransactionDeclaration, VariableKind,
+};
+use crate::lexer::token::{
+    TokenKind, Keyword,
+};
+
+use super::parser::{
+    ParseError, ParseResult, Parser, Recovery, span_of, token_text,
+};
+
+impl<'a> Parser<'a> {
+    /// a declaration, or an error node after skipping past the error
+    pub(super) fn parse_declaration_recovering(&mut self, is_member: bool) -> Declaration {
+        let start = self.position;
+        let at = self.current().at;
+        match self.parse_declaration(is_member) {
+            Ok(declaration) => declaration,
+            Err(error) => {
+                self.report(error);
+                self.synchronize(start, Recovery::Declaration);
+                Declaration::Error(self.span_from(at))
+            },
+        }
+    }
+
+    /// a top level declaration, or a member of a composite when `is_member`
+    pub fn parse_declaration(&mut self, is_member: bool) -> ParseResult<Declaration> {
+        let at = self.current().at;
+
+        match &self.current().kind {
+            TokenKind::Keyword(Keyword::Import) if !is_member => {
+                return Ok(Declaration::Import(self.parse_import()?))
+            },
+            TokenKind::Keyword(Keyword::Transaction) if !is_member => {
+                return Ok(Declaration::Transaction(self.parse_transaction()?))
+            },
+            _ => (),
+        }
+
+        let access = self.parse_access()?;
+
+        match self.current().kind.clone() {
+            TokenKind::Keyword(Keyword::Let) | TokenKind::Keyword(Keyword::Var) => {
+                if is_member {
+                    Ok(Declaration::Field(self.parse_field(access, at)?))
+                } else {
+                    Ok(Declaration::Variable(self.parse_variable_declaration(access, at)?))
+                }
+            },
+            TokenKind::Keyword(Keyword::Fun) => {
+                Ok(Declaration::Function(self.parse_function(access, Purity::Impure, at)?))
+            },
+            TokenKind::Identifier(name) if name == "view" && *self.peek_kind(1) == TokenKind::Keyword(Keyword::Fun) => {
+                self.advance();
+                Ok(Declaration::Function(self.parse_function(access, Purity::View, at)?))
+            },
+            TokenKind::Identifier(name) if name == "entitlement" && matches!(self.peek_kind(1), TokenKind::Identifier(_)) => {
+                let is_mapping = matches!(self.peek_kind(1), TokenKind::Identifier(name) if name == "mapping") &&
+                    matches!(self.peek_kind(2), TokenKind::Identifier(_));
+                if is_mapping {
+                    Ok(Declaration::EntitlementMapping(self.parse_entitlement_mapping(access, at)?))
+                } else {
+                    self.advance();
+                    let identifier = self.expect_identifier()?;
+                    Ok(Declaration::Entitlement(EntitlementDeclaration { access, identifier, span: self.span_from(at) }))
+                }
+            },
+            TokenKind::Keyword(Keyword::Struct) |
+            TokenKind::Keyword(Keyword::Resource) |
+            TokenKind::Keyword(Keyword::Contract) |
+            TokenKind::Keyword(Keyword::Enum) |
+            TokenKind::Keyword(Keyword::Event) => {
+                self.parse_composite_or_interface(access, at)
+            },
+            TokenKind::Keyword(Keyword::Init) if is_member => {
+                let function = self.parse_special_function(SpecialFunctionKind::Initializer, access, at)?;
+                Ok(Declaration::SpecialFunction(function))
+            },
+            TokenKind::Keyword(Keyword::Destroy) if is_member => {
+                let function = self.parse_special_function(SpecialFunctionKind::Destructor, access, at)?;
+                Ok(Declaration::SpecialFunction(function))
+            },
+            TokenKind::Keyword(Keyword::Case) if is_member => {
+                self.advance();
+                let identifier = self.expect_name()?;
+                Ok(Declaration::EnumCase(EnumCaseDeclaration { access, identifier, span: self.span_from(at) }))
+            },
+            _ => Err(self.unexpected("declaration")),
+        }
+    }
+
+    /// `pub`, `pub(set)`, `priv`, `access(...)` or `access(E)`, if any
+    pub(super) fn parse_access(&mut self) -> ParseResult<Access> {
+        if self.eat_keyword(Keyword::Priv) {
+            return Ok(Access::Priv)
+        }
+
+        if self.eat_keyword(Keyword::Pub) {
+            if self.at(&TokenKind::ParenOpen) && *self.peek_kind(1) == TokenKind::Keyword(Keyword::Set) {
+                self.advance();
+                self.advance();
+                self.expect(&TokenKind::ParenClose)?;
+                return Ok(Access::PubSet)
+            }
+            return Ok(Access::Pub)
+        }
+
+        if self.at_keyword(Keyword::Access) && *self.peek_kind(1) == TokenKind::ParenOpen {
+            self.advance();
+            self.advance();
+            let access = match &self.current().kind {
+                TokenKind::Keyword(Keyword::All) => Access::All,
+                TokenKind::Keyword(Keyword::SSelf) => Access::Self_,
+                TokenKind::Keyword(Keyword::Contract) => Access::Contract,
+                TokenKind::Keyword(Keyword::Account) => Access::Account,
+                TokenKind::Identifier(_) => {
+                    let access = Access::Entitlements(self.parse_authorization()?);
+                    self.expect(&TokenKind::ParenClose)?;
+                    return Ok(access)
+                },
+                _ => return Err(self.unexpected("`all`, `self`, `contract`, `account` or entitlements")),
+            };
+            self.advance();
+            self.expect(&TokenKind::ParenClose)?;
+            return Ok(access)
+        }
+
+        Ok(Access::NotSpecified)
+    }
+
+    /// `entitlement mapping M { E -> F include N }`
+    fn parse_entitlement_mapping(&mut self, access: Access, at: usize) -> ParseResult<EntitlementMappingDeclaration> {
+        self.advance();
+        self.advance();
+        let identifier = self.expect_identifier()?;
+        let mut relations = Vec::new();
+        let mut includes = Vec::new();
+        self.expect(&TokenKind::BraceOpen)?;
+        while !self.at(&TokenKind::BraceClose) && !self.at(&TokenKind::EOF) {
+            let relation_at = self.current().at;
+            if matches!(&self.current().kind, TokenKind::Identifier(name) if name == "include") &&
+                matches!(self.peek_kind(1), TokenKind::Identifier(_)) {
+                self.advance();
+                includes.push(self.parse_nominal_type()?);
+                continue;
+            }
+            let input = self.parse_nominal_type()?;
+            // `->` is lexed as `-` and `>`
+            self.expect(&TokenKind::Minus)?;
+            if !(self.at(&TokenKind::AngleClose) && self.is_adjacent()) {
+                return Err(self.unexpected("`->`"))
+            }
+            self.advance();
+            let output = self.parse_nominal_type()?;
+            relations.push(EntitlementRelation { input, output, span: self.span_from(relation_at) });
+        }
+        self.close_brace();
+        Ok(EntitlementMappingDeclaration { access, identifier, relations, includes, span: self.span_from(at) })
+    }
+
+    /// `import A, B from 0x01` or `import "A"`
+    fn parse_import(&mut self) -> ParseResult<ImportDeclaration> {
+        let at = self.current().at;
+        self.expect_keyword(Keyword::Import)?;
+
+        if let TokenKind::String(location) = &self.current().kind {
+            let location = location.clone();
+            self.advance();
+            return Ok(ImportDeclaration {
+                identifiers: Vec::new(),
+                location: ImportLocation::String(location),
+                span: self.span_from(at),
+            })
+        }
+
+        let mut identifiers = vec![self.expect_identifier()?];
+        while self.eat(&TokenKind::Comma) {
+            identifiers.push(self.expect_identifier()?);
+        }
+
+        if !self.eat_keyword(Keyword::From) {
+            // `import A` refers to a location named `A`
+            if identifiers.len() == 1 {
+                let identifier = identifiers.remove(0);
+                return Ok(ImportDeclaration {
+                    identifiers,
+                    location: ImportLocation::Identifier(identifier),
+                    span: self.span_from(at),
+                })
+            }
+            return Err(self.unexpected("`from`"))
+        }
+
+        let location = match self.current().kind.clone() {
+            TokenKind::String(location) => {
+                self.advance();
+                ImportLocation::String(location)
+            },
+            TokenKind::Identifier(name) if name.starts_with("0x") => {
+                self.advance();
+                ImportLocation::Address(name)
+            },
+            TokenKind::Identifier(_) => ImportLocation::Identifier(self.expect_identifier()?),
+            _ => return Err(self.unexpected("import location")),
+        };
+
+        Ok(ImportDeclaration { identifiers, location, span: self.span_from(at) })
+    }
+
+    fn parse_composite_or_interface(&mut self, access: Access, at: usize) -> ParseResult<Declaration> {
+        let kind = match self.advance().kind {
+            TokenKind::Keyword(Keyword::Struct) => CompositeKind::Structure,
+            TokenKind::Keyword(Keyword::Resource) => CompositeKind::Resource,
+            TokenKind::Keyword(Keyword::Contract) => CompositeKind::Contract,
+            TokenKind::Keyword(Keyword::Enum) => CompositeKind::Enum,
+            _ => CompositeKind::Event,
+        };
+
+        let is_interface = self.at_keyword(Keyword::Interface) &&
+            matches!(kind, CompositeKind::Structure | CompositeKind::Resource | CompositeKind::Contract);
+        if is_interface {
+            self.advance();
+        }
+
+        let identifier = self.expect_identifier()?;
+
+        if kind == CompositeKind::Event {
+            let parameters = self.parse_parameters()?;
+            return Ok(Declaration::Composite(CompositeDeclaration {
+                access,
+                kind,
+                identifier,
+                conformances: Vec::new(),
+                parameters,
+                members: Vec::new(),
+                span: self.span_from(at),
+            }))
+        }
+
+        let mut conformances = Vec::new();
+        if self.eat(&TokenKind::Colon) {
+            loop {
+                conformances.push(self.parse_nominal_type()?);
+                if !self.eat(&TokenKind::Comma) {
+                    break;
+                }
+            }
+        }
+
+        let members = self.parse_members()?;
+
+        if is_interface {
+            Ok(Declaration::Interface(InterfaceDeclaration {
+                access,
+                kind,
+                identifier,
+                conformances,
+                members,
+                span: self.span_from(at),
+            }))
+        } else {
+            Ok(Declaration::Composite(CompositeDeclaration {
+                access,
+                kind,
+                identifier,
+                conformances,
+                parameters: Vec::new(),
+                members,
+                span: self.span_from(at),
+            }))
+        }
+    }
+
+    /// `{ members }` of a composite or interface
+    fn parse_members(&mut self) -> ParseResult<Vec<Declaration>> {
+        self.expect(&TokenKind::BraceOpen)?;
+        let mut members = Vec::new();
+        while !self.at(&TokenKind::BraceClose) && !self.at(&TokenKind::EOF) {
+            members.push(self.parse_declaration_recovering(true));
+            self.eat(&TokenKind::SemiColon);
+        }
+        self.close_brace();
+        Ok(members)
+    }
+
+    /// `let x: T` without a value
+    fn parse_field(&mut self, access: Access, at: usize) -> ParseResult<FieldDeclaration> {
+        let variable_kind = if self.eat_keyword(Keyword::Let) {
+            VariableKind::Constant
+        } else {
+            self.expect_keyword(Keyword::Var)?;
+            VariableKind::Variable
+        };
+        let identifier = self.expect_name()?;
+        self.expect(&TokenKind::Colon)?;
+        let type_annotation = self.parse_type_annotation()?;
+        Ok(FieldDeclaration {
+            access,
+            variable_kind,
+            identifier,
+            type_annotation,
+            span: self.span_from(at),
+        })
+    }
+
+    pub(super) fn parse_function(&mut self, access: Access, purity: Purity, at: usize) -> ParseResult<FunctionDeclaration> {
+        self.expect_keyword(Keyword::Fun)?;
+        let identifier = self.expect_name()?;
+        self.parse_function_remainder(access, purity, identifier, at)
+    }
+
+    /// parameters, return type and body following the function name
+    fn parse_function_remainder(&mut self, access: Access, purity: Purity, identifier: Identifier, at: usize) -> ParseResult<FunctionDeclaration> {
+        let parameters = self.parse_parameters()?;
+        let return_type = if self.eat(&TokenKind::Colon) {
+            self.in_return_type = true;
+            let return_type = self.parse_type_annotation();
+            self.in_return_type = false;
+            Some(return_type?)
+        } else {
+            None
+        };
+
+        let mut pre_conditions = Vec::new();
+        let mut post_conditions = Vec::new();
+        let mut body = None;
+
+        if self.at(&TokenKind::BraceOpen) {
+            let block_at = self.current().at;
+            self.advance();
+            pre_conditions = self.parse_conditions(Keyword::Pre)?;
+            post_conditions = self.parse_conditions(Keyword::Post)?;
+            let mut statements = Vec::new();
+            while !self.at(&TokenKind::BraceClose) && !self.at(&TokenKind::EOF) {
+                statements.push(self.parse_statement_recovering());
+            }
+            self.close_brace();
+            body = Some(Block { statements, span: self.span_from(block_at) });
+        }
+
+        Ok(FunctionDeclaration {
+            access,
+            purity,
+            identifier,
+            parameters,
+            return_type,
+            pre_conditions,
+            post_conditions,
+            body,
+            span: self.span_from(at),
+        })
+    }
+
+    /// `pre { test: "message" }` or `post { ... }`, if present
+    fn parse_conditions(&mut self, keyword: Keyword) -> ParseResult<Vec<Condition>> {
+        let mut conditions = Vec::new();
+        if !(self.at_keyword(keyword) && *self.peek_kind(1) == TokenKind::BraceOpen) {
+            return Ok(conditions)
+        }
+        self.advance();
+        self.advance();
+        while !self.at(&TokenKind::BraceClose) && !self.at(&TokenKind::EOF) {
+            let at = self.current().at;
+            let test = self.parse_expression()?;
+            let message = if self.eat(&TokenKind::Colon) {
+                Some(self.parse_expression()?)
+            } else {
+                None
+            };
+            conditions.push(Condition { test, message, span: self.span_from(at) });
+            self.eat(&TokenKind::SemiColon);
+        }
+        self.expect(&TokenKind::BraceClose)?;
+        Ok(conditions)
+    }
+
+    /// `init(...)`, `destroy()` or `prepare(...)`, named after the keyword
+    fn parse_special_function(&mut self, kind: SpecialFunctionKind, access: Access, at: usize) -> ParseResult<SpecialFunctionDeclaration> {
+        let token = self.advance();
+        let name = token_text(&token.kind).to_string();
+        let identifier = Identifier::new(&name, span_of(&token));
+        let function = self.parse_function_remainder(access, Purity::Impure, identifier, at)?;
+        if function.return_type.is_some() {
+            return Err(ParseError::new(&format!("`{}` cannot have a return type", name), function.span))
+        }
+        Ok(SpecialFunctionDeclaration { kind, function })
+    }
+
+    /// `(a: A, b: B)`
+    pub(super) fn parse_parameters(&mut self) -> ParseResult<Vec<Parameter>> {
+        self.expect(&TokenKind::ParenOpen)?;
+        let mut parameters = Vec::new();
+        while !self.at(&TokenKind::ParenClose) {
+            let at = self.current().at;
+            let mut label = None;
+            let mut identifier = self.expect_name()?;
+            if !self.at(&TokenKind::Colon) {
+                label = Some(identifier);
+                identifier = self.expect_name()?;
+            }
+            self.expect(&TokenKind::Colon)?;
+            let type_annotation = self.parse_type_annotation()?;
+            let default = if self.eat(&TokenKind::Assign) { Some(self.parse_expression()?) } else { None };
+            parameters.push(Parameter { label, identifier, type_annotation, default, span: self.span_from(at) });
+            if !self.eat(&TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect(&TokenKind::ParenClose)?;
+        Ok(parameters)
+    }
+
+    /// `transaction(params) { fields prepare(...) {} execute {} }`
+    fn parse_transaction(&mut self) -> ParseResult<TransactionDeclaration> {
+        let at = self.current().at;
+        self.expect_keyword(Keyword::Transaction)?;
+        let parameters = if self.at(&TokenKind::ParenOpen) {
+            self.parse_parameters()?
+        } else {
+            Vec::new()
+        };
+
+        let mut fields = Vec::new();
+        let mut prepare = None;
+        let mut pre_conditions = Vec::new();
+        let mut execute = None;
+        let mut post_conditions = Vec::new();
+
+        self.expect(&TokenKind::BraceOpen)?;
+        while !self.at(&TokenKind::BraceClose) && !self.at(&TokenKind::EOF) {
+            let start = self.position;
+            if let Err(error) = self.parse_transaction_member(&mut fields, &mut prepare, &mut pre_conditions, &mut execute, &mut post_conditions) {
+                self.report(error);
+                self.synchronize(start, Recovery::Declaration);
+            }
+            self.eat(&TokenKind::SemiColon);
+        }
+        self.close_brace();
+
+        Ok(TransactionDeclaration {
+            parameters,
+            fields,
+            prepare,
+            pre_conditions,
+            execute,
+            post_conditions,
+            span: self.span_from(at),
+        })
+    }
+
+    fn parse_transaction_member(
+        &mut self,
+        fields: &mut Vec<FieldDeclaration>,
+        prepare: &mut Option<SpecialFunctionDeclaration>,
+        pre_conditions: &mut Vec<Condition>,
+        execute: &mut Option<Block>,
+        post_conditions: &mut Vec<Condition>,
+    ) -> ParseResult<()> {
+        let member_at = self.current().at;
+        match &self.current().kind {
+            TokenKind::Keyword(Keyword::Let) | TokenKind::Keyword(Keyword::Var) => {
+                fields.push(self.parse_field(Access::NotSpecified, member_at)?);
+            },
+            TokenKind::Keyword(Keyword::Prepare) if prepare.is_none() => {
+                *prepare = Some(self.parse_special_function(SpecialFunctionKind::Prepare, Access::NotSpecified, member_at)?);
+            },
+            TokenKind::Keyword(Keyword::Pre) if pre_conditions.is_empty() => {
+                *pre_conditions = self.parse_conditions(Keyword::Pre)?;
+            },
+            TokenKind::Keyword(Keyword::Execute) if execute.is_none() => {
+                self.advance();
+                *execute = Some(self.parse_block()?);
+            },
+            TokenKind::Keyword(Keyword::Post) if post_conditions.is_empty() => {
+                *post_conditions = self.parse_conditions(Keyword::Post)?;
+            },
+            _ => return Err(self.unexpected("transaction field, `prepare`, `pre`, `execute` or `post`")),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,327 @@
+use crate::ast::{
+    Argument, BinaryOperation, CastingOperation, Expression, Identifier, UnaryOperation,
+    PRECEDENCE_CASTING, PRECEDENCE_TERNARY,
+};
+use crate::lexer::token::{
+    TokenKind, Keyword,
+};
+
+use super::parser::{
+    ParseError, ParseResult, Parser, is_identifier,
+};
+
+impl<'a> Parser<'a> {
+    pub fn parse_expression(&mut self) -> ParseResult<Expression> {
+        self.parse_expression_with_precedence(0)
+    }
+
+    /// pratt parser, `min` is the lowest binding power accepted for infix operations
+    fn parse_expression_with_precedence(&mut self, min: u8) -> ParseResult<Expression> {
+        let at = self.current().at;
+        let mut left = self.parse_unary_expression()?;
+
+        loop {
+            if self.at(&TokenKind::Question) && PRECEDENCE_TERNARY >= min {
+                self.advance();
+                let then = self.parse_expression_with_precedence(PRECEDENCE_TERNARY)?;
+                self.expect(&TokenKind::Colon)?;
+                let else_ = self.parse_expression_with_precedence(PRECEDENCE_TERNARY)?;
+                left = Expression::Conditional {
+                    test: Box::new(left),
+                    then: Box::new(then),
+                    else_: Box::new(else_),
+                    span: self.span_from(at),
+                };
+                continue;
+            }
+
+            if let Some(operation) = casting_operation(&self.current().kind) {
+                if PRECEDENCE_CASTING < min {
+                    break;
+                }
+                self.advance();
+                let type_annotation = self.parse_type_annotation()?;
+                left = Expression::Casting {
+                    expression: Box::new(left),
+                    operation,
+                    type_annotation: Box::new(type_annotation),
+                    span: self.span_from(at),
+                };
+                continue;
+            }
+
+            let operation = match binary_operation(&self.current().kind) {
+                Some(operation) => operation,
+                None => break,
+            };
+            let precedence = operation.precedence();
+            if precedence < min {
+                break;
+            }
+            self.advance();
+            let next = if operation.is_right_associative() { precedence } else { precedence + 1 };
+            let right = self.parse_expression_with_precedence(next)?;
+            left = Expression::Binary {
+                operation,
+                left: Box::new(left),
+                right: Box::new(right),
+                span: self.span_from(at),
+            };
+        }
+
+        Ok(left)
+    }
+
+    fn parse_unary_expression(&mut self) -> ParseResult<Expression> {
+        let at = self.current().at;
+
+        let operation = match &self.current().kind {
+            TokenKind::Minus => Some(UnaryOperation::Minus),
+            TokenKind::Exclamation => Some(UnaryOperation::Not),
+            TokenKind::Move => Some(UnaryOperation::Move),
+            _ => None,
+        };
+        if let Some(operation) = operation {
+            self.advance();
+            let expression = self.parse_unary_expression()?;
+            return Ok(Expression::Unary {
+                operation,
+                expression: Box::new(expression),
+                span: self.span_from(at),
+            })
+        }
+
+        match &self.current().kind {
+            TokenKind::BitwiseAnd => {
+                self.advance();
+                let expression = self.parse_unary_expression()?;
+                let ty = if self.eat_keyword(Keyword::As) {
+                    Some(Box::new(self.parse_type()?))
+                } else {
+                    None
+                };
+                Ok(Expression::Reference {
+                    expression: Box::new(expression),
+                    ty,
+                    span: self.span_from(at),
+                })
+            },
+            TokenKind::Keyword(Keyword::Create) => {
+                self.advance();
+                let invocation = self.parse_unary_expression()?;
+                if !matches!(invocation, Expression::Invocation { .. }) {
+                    return Err(ParseError::new("expected invocation after `create`", invocation.span()))
+                }
+                Ok(Expression::Create {
+                    invocation: Box::new(invocation),
+                    span: self.span_from(at),
+                })
+            },
+            _ => {
+                let primary = self.parse_primary_expression()?;
+                self.parse_postfix_expression(primary, at)
+            },
+        }
+    }
+
+    fn parse_postfix_expression(&mut self, mut expression: Expression, at: usize) -> ParseResult<Expression> {
+        loop {
+            match self.current().kind.clone() {
+                TokenKind::Dot | TokenKind::QuestionDot => {
+                    let optional = self.at(&TokenKind::QuestionDot);
+                    self.advance();
+                    let identifier = self.expect_member_name()?;
+                    expression = Expression::Member {
+                        expression: Box::new(expression),
+                        optional,
+                        identifier,
+                        span: self.span_from(at),
+                    };
+                },
+                TokenKind::BracketOpen if !self.at_new_line() => {
+                    self.advance();
+                    let index = self.parse_expression()?;
+                    self.expect(&TokenKind::BracketClose)?;
+                    expression = Expression::Index {
+                        expression: Box::new(expression),
+                        index: Box::new(index),
+                        span: self.span_from(at),
+                    };
+                },
+                TokenKind::ParenOpen if !self.at_new_line() => {
+                    let arguments = self.parse_arguments()?;
+                    expression = Expression::Invocation {
+                        invoked: Box::new(expression),
+                        type_arguments: Vec::new(),
+                        arguments,
+                        span: self.span_from(at),
+                    };
+                },
+                TokenKind::AngleOpen if self.is_adjacent() && self.at_type_arguments_invocation() => {
+                    let type_arguments = self.parse_type_arguments()?;
+                    let arguments = self.parse_arguments()?;
+                    expression = Expression::Invocation {
+                        invoked: Box::new(expression),
+                        type_arguments,
+                        arguments,
+                        span: self.span_from(at),
+                    };
+                },
+                TokenKind::Exclamation if self.is_adjacent() => {
+                    self.advance();
+                    expression = Expression::Force {
+                        expression: Box::new(expression),
+                        span: self.span_from(at),
+                    };
+                },
+                _ => return Ok(expression),
+            }
+        }
+    }
+
+    /// `(label: value, value)`
+    fn parse_arguments(&mut self) -> ParseResult<Vec<Argument>> {
+        self.expect(&TokenKind::ParenOpen)?;
+        let mut arguments = Vec::new();
+        while !self.at(&TokenKind::ParenClose) {
+            let label = if self.at_name() && *self.peek_kind(1) == TokenKind::Colon {
+                let label = self.expect_name()?;
+                self.advance();
+                Some(label)
+            } else {
+                None
+            };
+            let expression = self.parse_expression()?;
+            arguments.push(Argument { label, expression });
+            if !self.eat(&TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect(&TokenKind::ParenClose)?;
+        Ok(arguments)
+    }
+
+    fn parse_primary_expression(&mut self) -> ParseResult<Expression> {
+        let at = self.current().at;
+        let span = self.current_span();
+
+        match self.current().kind.clone() {
+            TokenKind::Keyword(Keyword::Nil) => {
+                self.advance();
+                Ok(Expression::Nil { span })
+            },
+            TokenKind::Keyword(Keyword::True) => {
+                self.advance();
+                Ok(Expression::Bool { value: true, span })
+            },
+            TokenKind::Keyword(Keyword::False) => {
+                self.advance();
+                Ok(Expression::Bool { value: false, span })
+            },
+            TokenKind::Keyword(Keyword::SSelf) => {
+                self.advance();
+                Ok(Expression::Identifier(Identifier::new("self", span)))
+            },
+            TokenKind::Keyword(keyword) if keyword.is_soft() => {
+                let identifier = self.expect_name()?;
+                Ok(Expression::Identifier(identifier))
+            },
+            TokenKind::String(value) => {
+                self.advance();
+                Ok(Expression::String { value, span })
+            },
+            TokenKind::Identifier(name) if is_identifier(&name) => {
+                let identifier = self.expect_identifier()?;
+                Ok(Expression::Identifier(identifier))
+            },
+            TokenKind::Identifier(literal) => {
+                self.advance();
+                if literal.contains('.') {
+                    if literal.ends_with('.') {
+                        return Err(ParseError::new(&format!("invalid fixed-point literal `{}`", literal), span))
+                    }
+                    Ok(Expression::FixedPoint { literal, span })
+                } else {
+                    Ok(Expression::Integer { literal, span })
+                }
+            },
+            TokenKind::Slash => {
+                // path `/domain/identifier`
+                self.advance();
+                let domain = self.expect_name()?;
+                self.expect(&TokenKind::Slash)?;
+                let identifier = self.expect_member_name()?;
+                Ok(Expression::Path { domain, identifier, span: self.span_from(at) })
+            },
+            TokenKind::ParenOpen => {
+                self.advance();
+                let expression = self.parse_expression()?;
+                self.expect(&TokenKind::ParenClose)?;
+                Ok(expression)
+            },
+            TokenKind::BracketOpen => {
+                self.advance();
+                let mut values = Vec::new();
+                while !self.at(&TokenKind::BracketClose) {
+                    values.push(self.parse_expression()?);
+                    if !self.eat(&TokenKind::Comma) {
+                        break;
+                    }
+                }
+                self.expect(&TokenKind::BracketClose)?;
+                Ok(Expression::Array { values, span: self.span_from(at) })
+            },
+            TokenKind::BraceOpen => {
+                self.advance();
+                let mut entries = Vec::new();
+                while !self.at(&TokenKind::BraceClose) {
+                    let key = self.parse_expression()?;
+                    self.expect(&TokenKind::Colon)?;
+                    let value = self.parse_expression()?;
+                    entries.push((key, value));
+                    if !self.eat(&TokenKind::Comma) {
+                        break;
+                    }
+                }
+                self.expect(&TokenKind::BraceClose)?;
+                Ok(Expression::Dictionary { entries, span: self.span_from(at) })
+            },
+            _ => Err(self.unexpected("expression")),
+        }
+    }
+}
+
+fn casting_operation(kind: &TokenKind) -> Option<CastingOperation> {
+    match kind {
+        TokenKind::Keyword(Keyword::As) => Some(CastingOperation::Static),
+        TokenKind::Keyword(Keyword::AsQu) => Some(CastingOperation::Failable),
+        TokenKind::Keyword(Keyword::AsEx) => Some(CastingOperation::Force),
+        _ => None,
+    }
+}
+
+fn binary_operation(kind: &TokenKind) -> Option<BinaryOperation> {
+    let operation = match kind {
+        TokenKind::LogicalDisjunction => BinaryOperation::Or,
+        TokenKind::LogicalConjunction => BinaryOperation::And,
+        TokenKind::EqualTo => BinaryOperation::Equal,
+        TokenKind::NotEqual => BinaryOperation::NotEqual,
+        TokenKind::AngleOpen | TokenKind::LessThan => BinaryOperation::Less,
+        TokenKind::LessThanOrEqual => BinaryOperation::LessEqual,
+        TokenKind::AngleClose | TokenKind::GreaterThan => BinaryOperation::Greater,
+        TokenKind::GreaterThanOrEqual => BinaryOperation::GreaterEqual,
+        TokenKind::QuestionDouble => BinaryOperation::NilCoalescing,
+        TokenKind::BitwiseOr => BinaryOperation::BitwiseOr,
+        TokenKind::Xor => BinaryOperation::BitwiseXor,
+        TokenKind::BitwiseAnd => BinaryOperation::BitwiseAnd,
+        TokenKind::BitwiseShiftLeft => BinaryOperation::BitwiseShiftLeft,
+        TokenKind::BitwiseShiftRight => BinaryOperation::BitwiseShiftRight,
+        TokenKind::Plus => BinaryOperation::Plus,
+        TokenKind::Minus => BinaryOperation::Minus,
+        TokenKind::Asterisk => BinaryOperation::Mul,
+        TokenKind::Slash => BinaryOperation::Div,
+        TokenKind::Percent => BinaryOperation::Mod,
+        _ => return None,
+    };
+    Some(operation)
+}
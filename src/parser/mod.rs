@@ -0,0 +1,6 @@
+#[allow(clippy::module_inception)]
+pub mod parser;
+pub mod declaration;
+pub mod expression;
+pub mod statement;
+pub mod types;
@@ -0,0 +1,407 @@
+use crate::ast::{
+    Identifier, Program, Span,
+};
+use crate::lexer::lexer::Lexer;
+use crate::lexer::token::{
+    Token, TokenKind, Keyword,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl ParseError {
+    pub fn new(message: &str, span: Span) -> Self {
+        ParseError { message: message.to_string(), span }
+    }
+}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// parse a whole program (contract, transaction or script), failing on the first error
+pub fn parse(source: &str) -> ParseResult<Program> {
+    let (program, mut errors) = parse_with_errors(source);
+    if errors.is_empty() {
+        Ok(program)
+    } else {
+        Err(errors.remove(0))
+    }
+}
+
+/// parse a whole program, recovering from errors.
+/// declarations and statements that failed to parse are replaced with error nodes
+pub fn parse_with_errors(source: &str) -> (Program, Vec<ParseError>) {
+    let mut parser = Parser::new(source);
+    let program = parser.parse_program();
+    let mut errors = parser.errors;
+    errors.sort_by_key(|error| error.span.at);
+    (program, errors)
+}
+
+/// where to resume after an error
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum Recovery {
+    Declaration,
+    Statement,
+}
+
+pub struct Parser<'a> {
+    pub(super) source: &'a str,
+    pub(super) tokens: Vec<Token>,
+    pub(super) position: usize,
+    pub(super) errors: Vec<ParseError>,
+    /// tokens replaced while parsing, with their originals, so that lookahead can restore them
+    pub(super) replaced: Vec<(usize, Token)>,
+    /// whether a function's return type is being parsed, after which `{` may start the body
+    pub(super) in_return_type: bool,
+}
+
+/// where lookahead started, see `Parser::checkpoint`
+pub(super) struct Checkpoint {
+    position: usize,
+    replaced: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let mut tokens = Lexer::new(source).tokenize_all();
+        let mut errors = Vec::new();
+        // report unterminated literals once, then parse on as if they were closed
+        for token in &mut tokens {
+            match &token.kind {
+                TokenKind::UnterminatedString(value) => {
+                    errors.push(ParseError::new(&describe(&token.kind), span_of(token)));
+                    token.kind = TokenKind::String(value.clone());
+                },
+                TokenKind::UnterminatedComment => {
+                    errors.push(ParseError::new(&describe(&token.kind), span_of(token)));
+                },
+                _ => {},
+            }
+        }
+        tokens.retain(|token| token.kind != TokenKind::UnterminatedComment);
+        Self {
+            source,
+            tokens,
+            position: 0,
+            errors,
+            replaced: Vec::new(),
+            in_return_type: false,
+        }
+    }
+
+    /// a parser positioned at the first token starting at or after `offset`, used to re-parse part of a source
+    pub fn new_at(source: &'a str, offset: usize) -> Self {
+        let mut parser = Self::new(source);
+        parser.position = parser.tokens.iter()
+            .position(|token| token.at >= offset)
+            .unwrap_or(parser.tokens.len() - 1);
+        parser
+    }
+
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    pub fn parse_program(&mut self) -> Program {
+        let mut declarations = Vec::new();
+        while !self.at(&TokenKind::EOF) {
+            if self.at(&TokenKind::BraceClose) {
+                let error = self.unexpected("declaration");
+                self.report(error);
+                self.advance();
+                continue;
+            }
+            declarations.push(self.parse_declaration_recovering(false));
+            self.eat(&TokenKind::SemiColon);
+        }
+        Program {
+            declarations,
+            span: Span::new(0, self.source.len()),
+        }
+    }
+
+    pub(super) fn report(&mut self, error: ParseError) {
+        // unclosed blocks all end at the same place
+        if self.errors.last() != Some(&error) {
+            self.errors.push(error);
+        }
+    }
+
+    /// skip tokens after an error up to a point where parsing can resume:
+    /// after `;`, before a `}` closing the enclosing block, or before a keyword starting a declaration or statement
+    pub(super) fn synchronize(&mut self, start: usize, recovery: Recovery) {
+        if self.position == start {
+            self.advance();
+        }
+        let mut depth = 0;
+        loop {
+            match &self.current().kind {
+                TokenKind::EOF => return,
+                TokenKind::BraceOpen => depth += 1,
+                TokenKind::BraceClose => {
+                    if depth == 0 {
+                        return
+                    }
+                    depth -= 1;
+                },
+                TokenKind::SemiColon if depth == 0 => {
+                    self.advance();
+                    return
+                },
+                TokenKind::Keyword(keyword) if depth == 0 && is_synchronizing(keyword, recovery) => return,
+                _ => (),
+            }
+            self.advance();
+        }
+    }
+
+    /// `}` closing a block, reported as missing rather than failing
+    pub(super) fn close_brace(&mut self) {
+        if !self.eat(&TokenKind::BraceClose) {
+            let error = self.unexpected("`}`");
+            self.report(error);
+        }
+    }
+
+    pub(super) fn current(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    /// token `n` positions ahead of the current one, `EOF` past the end
+    pub(super) fn peek_kind(&self, n: usize) -> &TokenKind {
+        let index = (self.position + n).min(self.tokens.len() - 1);
+        &self.tokens[index].kind
+    }
+
+    pub(super) fn advance(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        if token.kind != TokenKind::EOF {
+            self.position += 1;
+        }
+        token
+    }
+
+    pub(super) fn at(&self, kind: &TokenKind) -> bool {
+        self.current().kind == *kind
+    }
+
+    pub(super) fn at_keyword(&self, keyword: Keyword) -> bool {
+        self.current().kind == TokenKind::Keyword(keyword)
+    }
+
+    pub(super) fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.at(kind) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(super) fn eat_keyword(&mut self, keyword: Keyword) -> bool {
+        self.eat(&TokenKind::Keyword(keyword))
+    }
+
+    pub(super) fn expect(&mut self, kind: &TokenKind) -> ParseResult<Token> {
+        if self.at(kind) {
+            Ok(self.advance())
+        } else {
+            Err(self.unexpected(&format!("`{}`", describe(kind))))
+        }
+    }
+
+    pub(super) fn expect_keyword(&mut self, keyword: Keyword) -> ParseResult<Token> {
+        self.expect(&TokenKind::Keyword(keyword))
+    }
+
+    /// plain identifier, numbers excluded
+    pub(super) fn expect_identifier(&mut self) -> ParseResult<Identifier> {
+        if let TokenKind::Identifier(name) = &self.current().kind {
+            if is_identifier(name) {
+                let token = self.advance();
+                return Ok(Identifier::new(token_text(&token.kind), span_of(&token)))
+            }
+        }
+        Err(self.unexpected("identifier"))
+    }
+
+    /// identifier or soft keyword, e.g. a parameter named `from`
+    pub(super) fn expect_name(&mut self) -> ParseResult<Identifier> {
+        if let TokenKind::Keyword(keyword) = &self.current().kind {
+            if keyword.is_soft() {
+                let token = self.advance();
+                return Ok(Identifier::new(token_text(&token.kind), span_of(&token)))
+            }
+        }
+        self.expect_identifier()
+    }
+
+    /// identifier or any keyword, used after `.`
+    pub(super) fn expect_member_name(&mut self) -> ParseResult<Identifier> {
+        if let TokenKind::Keyword(_) = &self.current().kind {
+            let token = self.advance();
+            return Ok(Identifier::new(token_text(&token.kind), span_of(&token)))
+        }
+        self.expect_identifier()
+    }
+
+    pub(super) fn at_name(&self) -> bool {
+        match &self.current().kind {
+            TokenKind::Identifier(name) => is_identifier(name),
+            TokenKind::Keyword(keyword) => keyword.is_soft(),
+            _ => false,
+        }
+    }
+
+    pub(super) fn unexpected(&self, expected: &str) -> ParseError {
+        let token = self.current();
+        let message = match &token.kind {
+            TokenKind::EOF => format!("expected {}, got end of file", expected),
+            kind => format!("expected {}, got `{}`", expected, describe(kind)),
+        };
+        ParseError::new(&message, span_of(token))
+    }
+
+    pub(super) fn current_span(&self) -> Span {
+        span_of(self.current())
+    }
+
+    /// span from `at` to the end of the last consumed token
+    pub(super) fn span_from(&self, at: usize) -> Span {
+        let end = if self.position == 0 {
+            at
+        } else {
+            let last = &self.tokens[self.position - 1];
+            last.at + last.size
+        };
+        Span::new(at, end.max(at) - at)
+    }
+
+    pub(super) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint { position: self.position, replaced: self.replaced.len() }
+    }
+
+    /// go back to the checkpoint, undoing the replacements of tokens since
+    pub(super) fn restore(&mut self, checkpoint: Checkpoint) {
+        while self.replaced.len() > checkpoint.replaced {
+            let (index, token) = self.replaced.pop().unwrap();
+            self.tokens[index] = token;
+        }
+        self.position = checkpoint.position;
+    }
+
+    /// whether the previous token ends right where the current one starts
+    pub(super) fn is_adjacent(&self) -> bool {
+        if self.position == 0 {
+            return false
+        }
+        let last = &self.tokens[self.position - 1];
+        last.at + last.size == self.current().at
+    }
+
+    /// whether a line break separates the previous token and the current one
+    pub(super) fn at_new_line(&self) -> bool {
+        if self.position == 0 {
+            return false
+        }
+        let last = &self.tokens[self.position - 1];
+        let gap = &self.source.as_bytes()[last.at + last.size..self.current().at];
+        gap.contains(&b'\n')
+    }
+}
+
+fn is_synchronizing(keyword: &Keyword, recovery: Recovery) -> bool {
+    match keyword {
+        Keyword::Fun | Keyword::Let | Keyword::Var | Keyword::Pub | Keyword::Priv => true,
+        Keyword::Access | Keyword::Struct | Keyword::Resource | Keyword::Contract |
+        Keyword::Enum | Keyword::Event | Keyword::Import | Keyword::Transaction |
+        Keyword::Init | Keyword::Prepare | Keyword::Execute | Keyword::Pre | Keyword::Post => {
+            recovery == Recovery::Declaration
+        },
+        Keyword::If | Keyword::While | Keyword::For | Keyword::Switch | Keyword::Default |
+        Keyword::Return | Keyword::Break | Keyword::Continue | Keyword::Emit => {
+            recovery == Recovery::Statement
+        },
+        Keyword::Destroy | Keyword::Case => true,
+        _ => false,
+    }
+}
+
+pub(super) fn span_of(token: &Token) -> Span {
+    Span::new(token.at, token.size)
+}
+
+pub(super) fn is_identifier(name: &str) -> bool {
+    match name.bytes().next() {
+        Some(c) => c.is_ascii_alphabetic() || c == b'_',
+        None => false,
+    }
+}
+
+/// source text of identifiers, strings and keywords
+pub(super) fn token_text(kind: &TokenKind) -> &str {
+    match kind {
+        TokenKind::Identifier(name) => name,
+        TokenKind::String(value) => value,
+        TokenKind::Keyword(keyword) => keyword.as_str(),
+        _ => "",
+    }
+}
+
+pub fn describe(kind: &TokenKind) -> String {
+    let text = match kind {
+        TokenKind::ParenOpen => "(",
+        TokenKind::ParenClose => ")",
+        TokenKind::BracketOpen => "[",
+        TokenKind::BracketClose => "]",
+        TokenKind::BraceOpen => "{",
+        TokenKind::BraceClose => "}",
+        TokenKind::AngleOpen => "<",
+        TokenKind::AngleClose => ">",
+        TokenKind::Comma => ",",
+        TokenKind::Dot => ".",
+        TokenKind::Plus => "+",
+        TokenKind::Minus => "-",
+        TokenKind::Asterisk => "*",
+        TokenKind::Slash => "/",
+        TokenKind::Backslash => "\\",
+        TokenKind::Percent => "%",
+        TokenKind::Question => "?",
+        TokenKind::QuestionDot => "?.",
+        TokenKind::QuestionDouble => "??",
+        TokenKind::Exclamation => "!",
+        TokenKind::At => "@",
+        TokenKind::LogicalConjunction => "&&",
+        TokenKind::LogicalDisjunction => "||",
+        TokenKind::Xor => "^",
+        TokenKind::DoubleQuote => "\"",
+        TokenKind::SingleQuote => "'",
+        TokenKind::SemiColon => ";",
+        TokenKind::Colon => ":",
+        TokenKind::NotEqual => "!=",
+        TokenKind::LessThan => "<",
+        TokenKind::LessThanOrEqual => "<=",
+        TokenKind::GreaterThan => ">",
+        TokenKind::GreaterThanOrEqual => ">=",
+        TokenKind::Assign => "=",
+        TokenKind::EqualTo => "==",
+        TokenKind::Swap => "<->",
+        TokenKind::Move => "<-",
+        TokenKind::MoveForce => "<-!",
+        TokenKind::BitwiseAnd => "&",
+        TokenKind::BitwiseOr => "|",
+        TokenKind::BitwiseShiftRight => ">>",
+        TokenKind::BitwiseShiftLeft => "<<",
+        TokenKind::String(value) => return format!("\"{}\"", value),
+        TokenKind::Identifier(name) => name,
+        TokenKind::Keyword(keyword) => keyword.as_str(),
+        TokenKind::None => "invalid token",
+        TokenKind::UnterminatedString(_) => "unterminated string literal",
+        TokenKind::UnterminatedComment => "unterminated block comment",
+        TokenKind::EOF => "end of file",
+    };
+    text.to_string()
+}
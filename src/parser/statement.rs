@@ -0,0 +1,235 @@
+use crate::ast::{
+    Access, Block, ElseBranch, Expression, IfStatement, IfTest, Statement, SwitchCase, Transfer,
+    VariableDeclaration, VariableKind,
+};
+use crate::lexer::token::{
+    TokenKind, Keyword,
+};
+
+use super::parser::{
+    ParseError, ParseResult, Parser, Recovery,
+};
+
+impl<'a> Parser<'a> {
+    /// `{ statements }`
+    pub fn parse_block(&mut self) -> ParseResult<Block> {
+        let at = self.current().at;
+        self.expect(&TokenKind::BraceOpen)?;
+        let mut statements = Vec::new();
+        while !self.at(&TokenKind::BraceClose) && !self.at(&TokenKind::EOF) {
+            statements.push(self.parse_statement_recovering());
+        }
+        self.close_brace();
+        Ok(Block { statements, span: self.span_from(at) })
+    }
+
+    /// a statement, or an error node after skipping past the error
+    pub(super) fn parse_statement_recovering(&mut self) -> Statement {
+        let start = self.position;
+        let at = self.current().at;
+        match self.parse_statement() {
+            Ok(statement) => statement,
+            Err(error) => {
+                self.report(error);
+                self.synchronize(start, Recovery::Statement);
+                Statement::Error(self.span_from(at))
+            },
+        }
+    }
+
+    pub fn parse_statement(&mut self) -> ParseResult<Statement> {
+        let at = self.current().at;
+
+        let statement = match &self.current().kind {
+            TokenKind::Keyword(Keyword::Let) | TokenKind::Keyword(Keyword::Var) => {
+                Statement::Variable(self.parse_variable_declaration(Access::NotSpecified, at)?)
+            },
+            TokenKind::Keyword(Keyword::Return) => {
+                self.advance();
+                let expression = if self.at(&TokenKind::BraceClose) || self.at(&TokenKind::SemiColon) ||
+                    self.at(&TokenKind::EOF) || self.at_new_line() {
+                    None
+                } else {
+                    Some(self.parse_expression()?)
+                };
+                Statement::Return { expression, span: self.span_from(at) }
+            },
+            TokenKind::Keyword(Keyword::Break) => {
+                self.advance();
+                Statement::Break { span: self.span_from(at) }
+            },
+            TokenKind::Keyword(Keyword::Continue) => {
+                self.advance();
+                Statement::Continue { span: self.span_from(at) }
+            },
+            TokenKind::Keyword(Keyword::If) => Statement::If(self.parse_if_statement()?),
+            TokenKind::Keyword(Keyword::While) => {
+                self.advance();
+                let test = self.parse_expression()?;
+                let block = self.parse_block()?;
+                Statement::While { test, block, span: self.span_from(at) }
+            },
+            TokenKind::Keyword(Keyword::For) => self.parse_for_statement()?,
+            TokenKind::Keyword(Keyword::Switch) => self.parse_switch_statement()?,
+            TokenKind::Keyword(Keyword::Emit) => {
+                self.advance();
+                let invocation = self.parse_expression()?;
+                if !matches!(invocation, Expression::Invocation { .. }) {
+                    return Err(ParseError::new("expected event invocation after `emit`", invocation.span()))
+                }
+                Statement::Emit { invocation, span: self.span_from(at) }
+            },
+            TokenKind::Keyword(Keyword::Destroy) => {
+                self.advance();
+                let expression = self.parse_expression()?;
+                Statement::Destroy { expression, span: self.span_from(at) }
+            },
+            _ => {
+                let expression = self.parse_expression()?;
+                match &self.current().kind {
+                    TokenKind::Assign | TokenKind::Move | TokenKind::MoveForce => {
+                        let transfer = self.parse_transfer()?;
+                        let value = self.parse_expression()?;
+                        Statement::Assignment {
+                            target: expression,
+                            transfer,
+                            value,
+                            span: self.span_from(at),
+                        }
+                    },
+                    TokenKind::Swap => {
+                        self.advance();
+                        let right = self.parse_expression()?;
+                        Statement::Swap { left: expression, right, span: self.span_from(at) }
+                    },
+                    _ => Statement::Expression(expression),
+                }
+            },
+        };
+
+        if !self.eat(&TokenKind::SemiColon) && !self.at(&TokenKind::BraceClose) &&
+            !self.at(&TokenKind::EOF) && !self.at_new_line() {
+            return Err(ParseError::new(
+                "statements on the same line must be separated with a semicolon",
+                self.current_span(),
+            ))
+        }
+        Ok(statement)
+    }
+
+    /// `let x: T = value`, the current token is `let` or `var`
+    pub(super) fn parse_variable_declaration(&mut self, access: Access, at: usize) -> ParseResult<VariableDeclaration> {
+        let kind = if self.eat_keyword(Keyword::Let) {
+            VariableKind::Constant
+        } else {
+            self.expect_keyword(Keyword::Var)?;
+            VariableKind::Variable
+        };
+        let identifier = self.expect_name()?;
+        let type_annotation = if self.eat(&TokenKind::Colon) {
+            Some(self.parse_type_annotation()?)
+        } else {
+            None
+        };
+        let transfer = self.parse_transfer()?;
+        let value = self.parse_expression()?;
+        let second = match self.current().kind {
+            TokenKind::Assign | TokenKind::Move | TokenKind::MoveForce => Some((self.parse_transfer()?, Box::new(self.parse_expression()?))),
+            _ => None,
+        };
+        Ok(VariableDeclaration {
+            access,
+            kind,
+            identifier,
+            type_annotation,
+            transfer,
+            value,
+            second,
+            span: self.span_from(at),
+        })
+    }
+
+    pub(super) fn parse_transfer(&mut self) -> ParseResult<Transfer> {
+        let transfer = match self.current().kind {
+            TokenKind::Assign => Transfer::Copy,
+            TokenKind::Move => Transfer::Move,
+            TokenKind::MoveForce => Transfer::MoveForce,
+            _ => return Err(self.unexpected("`=`, `<-` or `<-!`")),
+        };
+        self.advance();
+        Ok(transfer)
+    }
+
+    fn parse_if_statement(&mut self) -> ParseResult<IfStatement> {
+        let at = self.current().at;
+        self.expect_keyword(Keyword::If)?;
+        let test = if self.at_keyword(Keyword::Let) || self.at_keyword(Keyword::Var) {
+            let binding_at = self.current().at;
+            IfTest::Binding(Box::new(self.parse_variable_declaration(Access::NotSpecified, binding_at)?))
+        } else {
+            IfTest::Expression(self.parse_expression()?)
+        };
+        let then = self.parse_block()?;
+        let else_ = if self.eat_keyword(Keyword::Else) {
+            if self.at_keyword(Keyword::If) {
+                Some(ElseBranch::If(Box::new(self.parse_if_statement()?)))
+            } else {
+                Some(ElseBranch::Block(self.parse_block()?))
+            }
+        } else {
+            None
+        };
+        Ok(IfStatement { test, then, else_, span: self.span_from(at) })
+    }
+
+    /// `for x in values {}` or `for i, x in values {}`
+    fn parse_for_statement(&mut self) -> ParseResult<Statement> {
+        let at = self.current().at;
+        self.expect_keyword(Keyword::For)?;
+        let mut index = None;
+        let mut identifier = self.expect_name()?;
+        if self.eat(&TokenKind::Comma) {
+            index = Some(identifier);
+            identifier = self.expect_name()?;
+        }
+        self.expect_keyword(Keyword::In)?;
+        let value = self.parse_expression()?;
+        let block = self.parse_block()?;
+        Ok(Statement::For { index, identifier, value, block, span: self.span_from(at) })
+    }
+
+    /// `switch x { case a, b: ... default: ... }`
+    fn parse_switch_statement(&mut self) -> ParseResult<Statement> {
+        let at = self.current().at;
+        self.expect_keyword(Keyword::Switch)?;
+        let expression = self.parse_expression()?;
+        self.expect(&TokenKind::BraceOpen)?;
+
+        let mut cases = Vec::new();
+        while !self.at(&TokenKind::BraceClose) && !self.at(&TokenKind::EOF) {
+            let case_at = self.current().at;
+            let mut expressions = Vec::new();
+            if self.eat_keyword(Keyword::Case) {
+                loop {
+                    expressions.push(self.parse_expression()?);
+                    if !self.eat(&TokenKind::Comma) {
+                        break;
+                    }
+                }
+            } else if !self.eat_keyword(Keyword::Default) {
+                return Err(self.unexpected("`case` or `default`"))
+            }
+            self.expect(&TokenKind::Colon)?;
+
+            let mut statements = Vec::new();
+            while !self.at(&TokenKind::BraceClose) && !self.at(&TokenKind::EOF) &&
+                !self.at_keyword(Keyword::Case) && !self.at_keyword(Keyword::Default) {
+                statements.push(self.parse_statement_recovering());
+            }
+            cases.push(SwitchCase { expressions, statements, span: self.span_from(case_at) });
+        }
+        self.close_brace();
+
+        Ok(Statement::Switch { expression, cases, span: self.span_from(at) })
+    }
+}
@@ -0,0 +1,279 @@
+use crate::ast::{
+    Authorization, NominalType, Span, Type, TypeAnnotation,
+};
+use crate::lexer::token::{
+    Token, TokenKind,
+};
+
+use super::parser::{
+    ParseError, ParseResult, Parser, is_identifier,
+};
+
+impl<'a> Parser<'a> {
+    /// `@T` or `T`
+    pub fn parse_type_annotation(&mut self) -> ParseResult<TypeAnnotation> {
+        let at = self.current().at;
+        let is_resource = self.eat(&TokenKind::At);
+        let ty = self.parse_type()?;
+        Ok(TypeAnnotation {
+            is_resource,
+            ty,
+            span: self.span_from(at),
+        })
+    }
+
+    pub fn parse_type(&mut self) -> ParseResult<Type> {
+        let at = self.current().at;
+        let mut ty = self.parse_non_optional_type()?;
+
+        // `T?` must be written without space, `x as T ?? y` is a nil-coalescing
+        while self.is_adjacent() {
+            if self.eat(&TokenKind::Question) {
+                ty = Type::Optional { ty: Box::new(ty), span: self.span_from(at) };
+            } else if self.at(&TokenKind::QuestionDouble) {
+                let token = self.advance();
+                let inner = Span::new(at, token.at + 1 - at);
+                ty = Type::Optional { ty: Box::new(ty), span: inner };
+                ty = Type::Optional { ty: Box::new(ty), span: self.span_from(at) };
+            } else {
+                break;
+            }
+        }
+        Ok(ty)
+    }
+
+    /// a type without trailing `?`, the operand of `&`
+    fn parse_non_optional_type(&mut self) -> ParseResult<Type> {
+        let at = self.current().at;
+
+        match &self.current().kind {
+            TokenKind::BracketOpen => {
+                self.advance();
+                let element = self.parse_type()?;
+                let ty = if self.eat(&TokenKind::SemiColon) {
+                    let size = self.parse_array_size()?;
+                    self.expect(&TokenKind::BracketClose)?;
+                    Type::ConstantSized { ty: Box::new(element), size, span: self.span_from(at) }
+                } else {
+                    self.expect(&TokenKind::BracketClose)?;
+                    Type::VariableSized { ty: Box::new(element), span: self.span_from(at) }
+                };
+                Ok(ty)
+            },
+            TokenKind::BraceOpen => {
+                self.advance();
+                if self.eat(&TokenKind::BraceClose) {
+                    return Ok(Type::Restricted { ty: None, restrictions: Vec::new(), span: self.span_from(at) })
+                }
+                let first = self.parse_type()?;
+                if self.eat(&TokenKind::Colon) {
+                    let value = self.parse_type()?;
+                    self.expect(&TokenKind::BraceClose)?;
+                    return Ok(Type::Dictionary { key: Box::new(first), value: Box::new(value), span: self.span_from(at) })
+                }
+                let mut restrictions = vec![Self::as_nominal(first, at)?];
+                while self.eat(&TokenKind::Comma) {
+                    if self.at(&TokenKind::BraceClose) {
+                        break;
+                    }
+                    restrictions.push(self.parse_nominal_type()?);
+                }
+                self.expect(&TokenKind::BraceClose)?;
+                Ok(Type::Restricted { ty: None, restrictions, span: self.span_from(at) })
+            },
+            TokenKind::BitwiseAnd => {
+                self.advance();
+                self.parse_reference_type(at, false, None)
+            },
+            TokenKind::LogicalConjunction => Err(self.unexpected("type")),
+            TokenKind::ParenOpen => {
+                self.advance();
+                if self.at(&TokenKind::ParenOpen) {
+                    // function type `((P1, P2): R)`
+                    self.advance();
+                    let mut parameters = Vec::new();
+                    while !self.at(&TokenKind::ParenClose) {
+                        parameters.push(self.parse_type_annotation()?);
+                        if !self.eat(&TokenKind::Comma) {
+                            break;
+                        }
+                    }
+                    self.expect(&TokenKind::ParenClose)?;
+                    self.expect(&TokenKind::Colon)?;
+                    let return_type = self.parse_type_annotation()?;
+                    self.expect(&TokenKind::ParenClose)?;
+                    return Ok(Type::Function { parameters, return_type: Box::new(return_type), span: self.span_from(at) })
+                }
+                let ty = self.parse_type()?;
+                self.expect(&TokenKind::ParenClose)?;
+                Ok(ty)
+            },
+            TokenKind::Identifier(name) if name == "auth" && *self.peek_kind(1) == TokenKind::BitwiseAnd => {
+                self.advance();
+                self.advance();
+                self.parse_reference_type(at, true, None)
+            },
+            TokenKind::Identifier(name) if name == "auth" && *self.peek_kind(1) == TokenKind::ParenOpen => {
+                self.advance();
+                self.advance();
+                let entitlements = self.parse_authorization()?;
+                self.expect(&TokenKind::ParenClose)?;
+                self.expect(&TokenKind::BitwiseAnd)?;
+                self.parse_reference_type(at, true, Some(entitlements))
+            },
+            TokenKind::Identifier(_) => {
+                let nominal = self.parse_nominal_type()?;
+                let mut ty = Type::Nominal(nominal);
+
+                if self.at(&TokenKind::AngleOpen) && self.is_adjacent() {
+                    let type_arguments = self.parse_type_arguments()?;
+                    ty = Type::Instantiation { ty: Box::new(ty), type_arguments, span: self.span_from(at) };
+                }
+
+                // `T{I}` must be written without space, `T {` may start a function body
+                if self.at(&TokenKind::BraceOpen) && self.is_adjacent() {
+                    if let Some(restrictions) = self.try_parse_restrictions() {
+                        ty = Type::Restricted { ty: Some(Box::new(ty)), restrictions, span: self.span_from(at) };
+                    }
+                }
+                Ok(ty)
+            },
+            _ => Err(self.unexpected("type")),
+        }
+    }
+
+    fn parse_reference_type(&mut self, at: usize, authorized: bool, entitlements: Option<Authorization>) -> ParseResult<Type> {
+        let ty = self.parse_non_optional_type()?;
+        Ok(Type::Reference { authorized, entitlements, ty: Box::new(ty), span: self.span_from(at) })
+    }
+
+    fn parse_array_size(&mut self) -> ParseResult<u64> {
+        if let TokenKind::Identifier(literal) = &self.current().kind {
+            if let Ok(size) = literal.parse::<u64>() {
+                self.advance();
+                return Ok(size)
+            }
+        }
+        Err(self.unexpected("array size"))
+    }
+
+    /// `A` or `A.B.C`
+    pub fn parse_nominal_type(&mut self) -> ParseResult<NominalType> {
+        let identifier = self.expect_identifier()?;
+        let mut nested_identifiers = Vec::new();
+        while self.at(&TokenKind::Dot) {
+            self.advance();
+            nested_identifiers.push(self.expect_identifier()?);
+        }
+        Ok(NominalType { identifier, nested_identifiers })
+    }
+
+    /// `E`, `E, F`, `E | F` or `mapping M`, inside `access(...)` or `auth(...)`
+    pub(super) fn parse_authorization(&mut self) -> ParseResult<Authorization> {
+        if matches!(&self.current().kind, TokenKind::Identifier(name) if name == "mapping") &&
+            matches!(self.peek_kind(1), TokenKind::Identifier(_)) {
+            self.advance();
+            return Ok(Authorization::Mapping(self.parse_nominal_type()?))
+        }
+        let mut entitlements = vec![self.parse_nominal_type()?];
+        let separator = self.current().kind.clone();
+        if separator != TokenKind::Comma && separator != TokenKind::BitwiseOr {
+            return Ok(Authorization::Conjunction(entitlements))
+        }
+        while self.at(&TokenKind::Comma) || self.at(&TokenKind::BitwiseOr) {
+            if !self.at(&separator) {
+                return Err(ParseError::new("cannot mix `,` and `|` in an entitlement set", self.current_span()))
+            }
+            self.advance();
+            entitlements.push(self.parse_nominal_type()?);
+        }
+        if separator == TokenKind::Comma {
+            Ok(Authorization::Conjunction(entitlements))
+        } else {
+            Ok(Authorization::Disjunction(entitlements))
+        }
+    }
+
+    fn as_nominal(ty: Type, at: usize) -> ParseResult<NominalType> {
+        match ty {
+            Type::Nominal(nominal) => Ok(nominal),
+            other => Err(ParseError::new(
+                "expected nominal type in restriction",
+                Span::between(Span::new(at, 0), other.span()),
+            )),
+        }
+    }
+
+    /// `<T, U>` after a type or an invoked expression
+    pub(super) fn parse_type_arguments(&mut self) -> ParseResult<Vec<TypeAnnotation>> {
+        self.expect(&TokenKind::AngleOpen)?;
+        let mut type_arguments = Vec::new();
+        while !self.at_angle_close() {
+            type_arguments.push(self.parse_type_annotation()?);
+            if !self.eat(&TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect_angle_close()?;
+        Ok(type_arguments)
+    }
+
+    fn at_angle_close(&self) -> bool {
+        matches!(self.current().kind, TokenKind::AngleClose | TokenKind::BitwiseShiftRight)
+    }
+
+    /// `>`, splitting `>>` of nested type arguments
+    fn expect_angle_close(&mut self) -> ParseResult<()> {
+        if self.at(&TokenKind::BitwiseShiftRight) {
+            let at = self.current().at;
+            let split = Token::new_c(TokenKind::AngleClose, at + 1);
+            let original = std::mem::replace(&mut self.tokens[self.position], split);
+            self.replaced.push((self.position, original));
+            return Ok(())
+        }
+        self.expect(&TokenKind::AngleClose)?;
+        Ok(())
+    }
+
+    /// `{I1, I2}` after a type, backtracking when the braces are not a restriction list.
+    /// a restriction list holds only type names, so braces holding anything else are a block, e.g. `fun f(): Int{ return 1 }`.
+    /// after a return type, empty braces are the body unless another `{` follows, e.g. `fun f(): Int{}`
+    fn try_parse_restrictions(&mut self) -> Option<Vec<NominalType>> {
+        let checkpoint = self.checkpoint();
+        self.advance();
+        let mut restrictions = Vec::new();
+        while !self.at(&TokenKind::BraceClose) {
+            let is_name = matches!(&self.current().kind, TokenKind::Identifier(name) if is_identifier(name));
+            if !is_name {
+                self.restore(checkpoint);
+                return None
+            }
+            match self.parse_nominal_type() {
+                Ok(nominal) => restrictions.push(nominal),
+                Err(_) => {
+                    self.restore(checkpoint);
+                    return None
+                },
+            }
+            let separated = self.eat(&TokenKind::Comma);
+            if separated == self.at(&TokenKind::BraceClose) {
+                self.restore(checkpoint);
+                return None
+            }
+        }
+        self.advance();
+        if restrictions.is_empty() && self.in_return_type && !self.at(&TokenKind::BraceOpen) {
+            self.restore(checkpoint);
+            return None
+        }
+        Some(restrictions)
+    }
+
+    /// whether the tokens ahead look like type arguments followed by `(`, e.g. `<T>(`
+    pub(super) fn at_type_arguments_invocation(&mut self) -> bool {
+        let checkpoint = self.checkpoint();
+        let result = self.parse_type_arguments().is_ok() && self.at(&TokenKind::ParenOpen);
+        self.restore(checkpoint);
+        result
+    }
+}
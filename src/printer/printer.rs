@@ -0,0 +1,1079 @@
+use crate::ast::{
+    Access, Argument, BinaryOperation, Block, CompositeDeclaration, CompositeKind, Condition, Declaration, ElseBranch,
+    EntitlementMappingDeclaration, Expression, FieldDeclaration, FunctionDeclaration, IfStatement, IfTest,
+    ImportDeclaration, ImportLocation, InterfaceDeclaration, NominalType, Parameter, Program, Purity, Span, SpecialFunctionDeclaration,
+    SpecialFunctionKind, Statement, SwitchCase, TransactionDeclaration, Type, TypeAnnotation, VariableDeclaration,
+    VariableKind, PRECEDENCE_ACCESS, PRECEDENCE_CASTING, PRECEDENCE_TERNARY, PRECEDENCE_UNARY_POSTFIX,
+    PRECEDENCE_UNARY_PREFIX,
+};
+
+/// print a program as Cadence source
+pub fn print_program(program: &Program) -> String {
+    let mut printer = Printer::new();
+    printer.program(program);
+    printer.finish()
+}
+
+pub fn print_declaration(declaration: &Declaration) -> String {
+    let mut printer = Printer::new();
+    printer.declaration(declaration);
+    printer.finish()
+}
+
+pub fn print_statement(statement: &Statement) -> String {
+    let mut printer = Printer::new();
+    printer.statement(statement);
+    printer.finish()
+}
+
+pub fn print_expression(expression: &Expression) -> String {
+    let mut printer = Printer::new();
+    printer.expression(expression);
+    printer.finish()
+}
+
+pub fn print_type(ty: &Type) -> String {
+    let mut printer = Printer::new();
+    printer.ty(ty);
+    printer.finish()
+}
+
+/// binding power of an expression, parenthesized when lower than its position requires
+pub fn precedence(expression: &Expression) -> u8 {
+    match expression {
+        Expression::Conditional { .. } => PRECEDENCE_TERNARY,
+        Expression::Binary { operation, .. } => operation.precedence(),
+        Expression::Casting { .. } => PRECEDENCE_CASTING,
+        Expression::Unary { .. } | Expression::Create { .. } | Expression::Reference { .. } => PRECEDENCE_UNARY_PREFIX,
+        Expression::Force { .. } => PRECEDENCE_UNARY_POSTFIX,
+        _ => PRECEDENCE_ACCESS,
+    }
+}
+
+/// whether the printed expression starts with `-`, `!`, `<-`, `&` or `/`
+fn starts_with_operator(expression: &Expression) -> bool {
+    match expression {
+        Expression::Unary { .. } | Expression::Reference { .. } | Expression::Path { .. } => true,
+        Expression::Binary { operation, left, .. } => {
+            let min = if operation.is_right_associative() { operation.precedence() + 1 } else { operation.precedence() };
+            precedence(left) >= min && starts_with_operator(left)
+        },
+        Expression::Conditional { test, .. } => {
+            precedence(test) > PRECEDENCE_TERNARY && starts_with_operator(test)
+        },
+        Expression::Casting { expression, .. } => {
+            precedence(expression) >= PRECEDENCE_CASTING && !ends_with_reference(expression) && starts_with_operator(expression)
+        },
+        Expression::Member { expression, .. } |
+        Expression::Index { expression, .. } |
+        Expression::Force { expression, .. } |
+        Expression::Invocation { invoked: expression, .. } => {
+            precedence(expression) >= PRECEDENCE_UNARY_POSTFIX && starts_with_operator(expression)
+        },
+        _ => false,
+    }
+}
+
+/// whether the printed expression ends with a reference expression without type,
+/// which would take a following `as T`
+fn ends_with_reference(expression: &Expression) -> bool {
+    match expression {
+        Expression::Reference { ty: None, .. } => true,
+        Expression::Unary { expression, .. } => {
+            precedence(expression) >= PRECEDENCE_UNARY_PREFIX && ends_with_reference(expression)
+        },
+        _ => false,
+    }
+}
+
+/// layout of the printed source
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrintOptions {
+    /// parameter lists, argument lists, `&&`/`||` chains and condition messages longer than this are broken over lines
+    pub max_width: usize,
+    pub indent_width: usize,
+    /// indent with tabs, which count as `indent_width` columns
+    pub hard_tabs: bool,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions { max_width: 100, indent_width: 4, hard_tabs: false }
+    }
+}
+
+/// a comment of the source, printed before the first declaration, statement or list item after it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comment {
+    pub text: String,
+    pub span: Span,
+    /// whether only whitespace precedes the comment on its line,
+    /// otherwise it stays at the end of the line printed before it
+    pub own_line: bool,
+}
+
+/// what the AST does not keep of the source
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trivia {
+    pub comments: Vec<Comment>,
+    /// offsets of the tokens and comments that follow an empty line
+    pub blank_lines: Vec<usize>,
+}
+
+/// writes the AST back to source, one declaration or statement per line.
+/// error nodes left by a recovering parse have no source and are skipped
+pub struct Printer {
+    output: String,
+    indent: usize,
+    options: PrintOptions,
+    /// comments not printed yet, last one first
+    comments: Vec<Comment>,
+    blank_lines: Vec<usize>,
+    /// whether lists are kept on one line while measuring them
+    flat: bool,
+    /// whether the current line ends with a `//` comment
+    line_comment: bool,
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Self::with_options(PrintOptions::default())
+    }
+
+    pub fn with_options(options: PrintOptions) -> Self {
+        Printer {
+            output: String::new(),
+            indent: 0,
+            options,
+            comments: Vec::new(),
+            blank_lines: Vec::new(),
+            flat: false,
+            line_comment: false,
+        }
+    }
+
+    /// keep the comments and blank lines of the source the printed AST was parsed from
+    pub fn with_trivia(mut self, trivia: Trivia) -> Self {
+        self.comments = trivia.comments;
+        self.comments.sort_by_key(|comment| std::cmp::Reverse(comment.span.at));
+        self.blank_lines = trivia.blank_lines;
+        self.blank_lines.sort_unstable();
+        self
+    }
+
+    pub fn finish(self) -> String {
+        self.output
+    }
+
+    fn write(&mut self, text: &str) {
+        self.output.push_str(text);
+    }
+
+    /// start a new line at the current indentation, there is nothing to separate at the start of the output
+    fn new_line(&mut self) {
+        self.line_comment = false;
+        if self.output.is_empty() {
+            return
+        }
+        self.output.push('\n');
+        for _ in 0..self.indent {
+            if self.options.hard_tabs {
+                self.output.push('\t');
+            } else {
+                self.output.extend(std::iter::repeat_n(' ', self.options.indent_width));
+            }
+        }
+    }
+
+    /// an empty line, if the source had one before `offset` and this is not the start of a block
+    fn source_blank_line(&mut self, offset: usize) {
+        if self.blank_lines.binary_search(&offset).is_ok() {
+            self.blank_line();
+        }
+    }
+
+    fn blank_line(&mut self) {
+        let previous = self.output.trim_end_matches([' ', '\t']);
+        if !previous.is_empty() && !previous.ends_with(['{', '\n']) {
+            self.output.truncate(previous.len());
+            self.output.push('\n');
+        }
+    }
+
+    /// comments before `offset` that follow code on their line
+    fn trailing_comments(&mut self, offset: usize) {
+        while self.comments.last().is_some_and(|comment| !comment.own_line && comment.span.at < offset) {
+            let comment = self.comments.pop().unwrap();
+            self.comment(&comment);
+        }
+    }
+
+    /// all comments before `offset`
+    fn leading_comments(&mut self, offset: usize) {
+        while self.comments.last().is_some_and(|comment| comment.span.at < offset) {
+            let comment = self.comments.pop().unwrap();
+            if comment.own_line {
+                self.source_blank_line(comment.span.at);
+            }
+            self.comment(&comment);
+        }
+    }
+
+    /// comments at the end of a block, before its `}` at `end`
+    fn closing_comments(&mut self, end: usize) {
+        self.trailing_comments(end);
+        self.leading_comments(end);
+    }
+
+    fn comment(&mut self, comment: &Comment) {
+        if comment.own_line || self.line_comment {
+            self.new_line();
+        } else {
+            self.write(" ");
+        }
+        self.write(&comment.text);
+        self.line_comment = comment.text.starts_with("//");
+    }
+
+    /// print with `inline` if that fits on the line, otherwise with `broken`
+    fn fitting(&mut self, inline: impl FnOnce(&mut Self), broken: impl FnOnce(&mut Self)) {
+        let start = self.output.len();
+        let flat = self.flat;
+        let comments = self.comments.clone();
+        let line_comment = self.line_comment;
+        self.flat = true;
+        inline(self);
+        self.flat = flat;
+        if flat || (!self.output[start..].contains('\n') && self.line_width() <= self.options.max_width) {
+            return
+        }
+        self.output.truncate(start);
+        // comments printed by `inline` are printed again by `broken`
+        self.comments = comments;
+        self.line_comment = line_comment;
+        broken(self);
+    }
+
+    /// columns of the current line
+    fn line_width(&self) -> usize {
+        let line = &self.output[self.output.rfind('\n').map_or(0, |at| at + 1)..];
+        line.chars().map(|c| if c == '\t' { self.options.indent_width } else { 1 }).sum()
+    }
+
+    /// `open`, the items, then `close` at `end`, one item per line with a trailing comma
+    /// if they do not fit on the line or a comment follows one of them
+    fn list<T>(
+        &mut self,
+        open: &str,
+        items: &[T],
+        close: &str,
+        end: usize,
+        span: impl Fn(&T) -> Span,
+        print: impl Fn(&mut Self, &T),
+    ) {
+        self.write(open);
+        if items.is_empty() {
+            self.write(close);
+            return
+        }
+        if self.commented(span(&items[0]).at, end) {
+            self.broken_list(items, close, end, span, print);
+            return
+        }
+        self.fitting(
+            |p| {
+                p.separated(items, ", ", |p, item| print(p, item));
+                p.write(close);
+            },
+            |p| p.broken_list(items, close, end, span, &print),
+        );
+    }
+
+    /// the items of a list one per line, each followed by a comma and the comments on its line
+    fn broken_list<T>(&mut self, items: &[T], close: &str, end: usize, span: impl Fn(&T) -> Span, print: impl Fn(&mut Self, &T)) {
+        self.indent += 1;
+        for item in items.iter() {
+            let at = span(item).at;
+            self.trailing_comments(at);
+            self.leading_comments(at);
+            self.new_line();
+            print(self, item);
+            self.write(",");
+        }
+        self.closing_comments(end);
+        self.indent -= 1;
+        self.new_line();
+        self.write(close);
+    }
+
+    /// whether a comment between `start` and `end` is still to be printed
+    fn commented(&self, start: usize, end: usize) -> bool {
+        self.comments.iter().rev()
+            .take_while(|comment| comment.span.at < end)
+            .any(|comment| comment.span.at >= start)
+    }
+
+    /// `{`, the indented lines written by `body`, then `}`
+    fn braced(&mut self, body: impl FnOnce(&mut Self)) {
+        self.write("{");
+        let start = self.output.len();
+        self.indent += 1;
+        body(self);
+        self.indent -= 1;
+        if self.output.len() == start {
+            self.write("}");
+        } else {
+            self.new_line();
+            self.write("}");
+        }
+    }
+
+    fn separated<T>(&mut self, items: &[T], separator: &str, mut print: impl FnMut(&mut Self, &T)) {
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                self.write(separator);
+            }
+            print(self, item);
+        }
+    }
+
+    pub fn program(&mut self, program: &Program) {
+        let declarations: Vec<_> = program.declarations.iter()
+            .filter(|declaration| !matches!(declaration, Declaration::Error(_)))
+            .collect();
+        for (index, declaration) in declarations.iter().enumerate() {
+            let at = declaration.span().at;
+            self.trailing_comments(at);
+            if index > 0 {
+                self.blank_line();
+            }
+            self.leading_comments(at);
+            self.new_line();
+            self.declaration(declaration);
+        }
+        self.closing_comments(usize::MAX);
+        if !self.output.is_empty() {
+            self.write("\n");
+        }
+    }
+
+    pub fn declaration(&mut self, declaration: &Declaration) {
+        match declaration {
+            Declaration::Import(d) => self.import(d),
+            Declaration::Composite(d) => self.composite(d),
+            Declaration::Interface(d) => self.interface(d),
+            Declaration::Field(d) => self.field(d),
+            Declaration::Function(d) => self.function(d),
+            Declaration::SpecialFunction(d) => self.special_function(d),
+            Declaration::EnumCase(d) => {
+                self.access(&d.access);
+                self.write("case ");
+                self.write(&d.identifier.name);
+            },
+            Declaration::Variable(d) => self.variable(d),
+            Declaration::Transaction(d) => self.transaction(d),
+            Declaration::Entitlement(d) => {
+                self.access(&d.access);
+                self.write("entitlement ");
+                self.write(&d.identifier.name);
+            },
+            Declaration::EntitlementMapping(d) => self.entitlement_mapping(d),
+            Declaration::Error(_) => (),
+        }
+    }
+
+    fn access(&mut self, access: &Access) {
+        if *access != Access::NotSpecified {
+            self.write(&access.to_string());
+            self.write(" ");
+        }
+    }
+
+    /// includes first, then one relation per line
+    fn entitlement_mapping(&mut self, declaration: &EntitlementMappingDeclaration) {
+        self.access(&declaration.access);
+        self.write("entitlement mapping ");
+        self.write(&declaration.identifier.name);
+        self.write(" ");
+        self.braced(|p| {
+            for include in declaration.includes.iter() {
+                let at = include.span().at;
+                p.trailing_comments(at);
+                p.leading_comments(at);
+                p.new_line();
+                p.write("include ");
+                p.nominal_type(include);
+            }
+            for relation in declaration.relations.iter() {
+                p.trailing_comments(relation.span.at);
+                p.source_blank_line(relation.span.at);
+                p.leading_comments(relation.span.at);
+                p.new_line();
+                p.nominal_type(&relation.input);
+                p.write(" -> ");
+                p.nominal_type(&relation.output);
+            }
+            p.closing_comments(declaration.span.end());
+        });
+    }
+
+    fn import(&mut self, declaration: &ImportDeclaration) {
+        self.write("import ");
+        self.separated(&declaration.identifiers, ", ", |p, identifier| p.write(&identifier.name));
+        if !declaration.identifiers.is_empty() {
+            self.write(" from ");
+        }
+        match &declaration.location {
+            ImportLocation::Address(address) => self.write(address),
+            ImportLocation::String(location) => self.string(location),
+            ImportLocation::Identifier(identifier) => self.write(&identifier.name),
+        }
+    }
+
+    /// members one per line, with a blank line around functions and nested composites
+    fn members(&mut self, members: &[Declaration], end: usize) {
+        let members: Vec<_> = members.iter().filter(|member| !matches!(member, Declaration::Error(_))).collect();
+        self.braced(|p| {
+            let mut previous_is_block = false;
+            for (index, member) in members.iter().enumerate() {
+                let at = member.span().at;
+                p.trailing_comments(at);
+                let is_block = !matches!(
+                    member,
+                    Declaration::Field(_) | Declaration::EnumCase(_) | Declaration::Variable(_) | Declaration::Entitlement(_)
+                );
+                if index > 0 && (is_block || previous_is_block) {
+                    p.blank_line();
+                } else {
+                    p.source_blank_line(at);
+                }
+                p.leading_comments(at);
+                p.new_line();
+                p.declaration(member);
+                previous_is_block = is_block;
+            }
+            p.closing_comments(end);
+        });
+    }
+
+    fn conformances(&mut self, conformances: &[NominalType]) {
+        if !conformances.is_empty() {
+            self.write(": ");
+            self.separated(conformances, ", ", |p, conformance| p.nominal_type(conformance));
+        }
+    }
+
+    fn composite(&mut self, declaration: &CompositeDeclaration) {
+        self.access(&declaration.access);
+        self.write(declaration.kind.keyword());
+        self.write(" ");
+        self.write(&declaration.identifier.name);
+        if declaration.kind == CompositeKind::Event {
+            self.parameters(&declaration.parameters);
+            return
+        }
+        self.conformances(&declaration.conformances);
+        self.write(" ");
+        self.members(&declaration.members, declaration.span.end());
+    }
+
+    fn interface(&mut self, declaration: &InterfaceDeclaration) {
+        self.access(&declaration.access);
+        self.write(declaration.kind.keyword());
+        self.write(" interface ");
+        self.write(&declaration.identifier.name);
+        self.conformances(&declaration.conformances);
+        self.write(" ");
+        self.members(&declaration.members, declaration.span.end());
+    }
+
+    fn field(&mut self, declaration: &FieldDeclaration) {
+        self.access(&declaration.access);
+        self.variable_kind(declaration.variable_kind);
+        self.write(&declaration.identifier.name);
+        self.write(": ");
+        self.type_annotation(&declaration.type_annotation);
+    }
+
+    fn variable_kind(&mut self, kind: VariableKind) {
+        match kind {
+            VariableKind::Constant => self.write("let "),
+            VariableKind::Variable => self.write("var "),
+        }
+    }
+
+    fn function(&mut self, declaration: &FunctionDeclaration) {
+        self.access(&declaration.access);
+        if declaration.purity == Purity::View {
+            self.write("view ");
+        }
+        self.write("fun ");
+        self.write(&declaration.identifier.name);
+        self.function_remainder(declaration);
+    }
+
+    /// parameters, return type and body with conditions
+    fn function_remainder(&mut self, declaration: &FunctionDeclaration) {
+        self.parameters(&declaration.parameters);
+        if let Some(return_type) = &declaration.return_type {
+            self.write(": ");
+            self.type_annotation(return_type);
+        }
+
+        let has_conditions = !declaration.pre_conditions.is_empty() || !declaration.post_conditions.is_empty();
+        if declaration.body.is_none() && !has_conditions {
+            return
+        }
+        self.write(" ");
+        self.braced(|p| {
+            p.conditions("pre", &declaration.pre_conditions);
+            p.conditions("post", &declaration.post_conditions);
+            if let Some(body) = &declaration.body {
+                p.statements(&body.statements);
+            }
+            p.closing_comments(declaration.span.end());
+        });
+    }
+
+    fn conditions(&mut self, keyword: &str, conditions: &[Condition]) {
+        if conditions.is_empty() {
+            return
+        }
+        self.new_line();
+        self.write(keyword);
+        self.write(" ");
+        self.braced(|p| {
+            for condition in conditions.iter() {
+                let at = condition.span.at;
+                p.trailing_comments(at);
+                p.leading_comments(at);
+                p.new_line();
+                p.expression(&condition.test);
+                if let Some(message) = &condition.message {
+                    // a message that does not fit goes on its own line, indented below the test
+                    p.fitting(
+                        |p| {
+                            p.write(": ");
+                            p.expression(message);
+                        },
+                        |p| {
+                            p.write(":");
+                            p.indent += 1;
+                            p.new_line();
+                            p.expression(message);
+                            p.indent -= 1;
+                        },
+                    );
+                }
+            }
+        });
+    }
+
+    fn special_function(&mut self, declaration: &SpecialFunctionDeclaration) {
+        self.access(&declaration.function.access);
+        self.write(match declaration.kind {
+            SpecialFunctionKind::Initializer => "init",
+            SpecialFunctionKind::Destructor => "destroy",
+            SpecialFunctionKind::Prepare => "prepare",
+        });
+        self.function_remainder(&declaration.function);
+    }
+
+    fn parameters(&mut self, parameters: &[Parameter]) {
+        let end = parameters.last().map_or(0, |parameter| parameter.span.end());
+        self.list("(", parameters, ")", end, |parameter| parameter.span, |p, parameter| {
+            if let Some(label) = &parameter.label {
+                p.write(&label.name);
+                p.write(" ");
+            }
+            p.write(&parameter.identifier.name);
+            p.write(": ");
+            p.type_annotation(&parameter.type_annotation);
+            if let Some(default) = &parameter.default {
+                p.write(" = ");
+                p.expression(default);
+            }
+        });
+    }
+
+    fn variable(&mut self, declaration: &VariableDeclaration) {
+        self.access(&declaration.access);
+        self.variable_kind(declaration.kind);
+        self.write(&declaration.identifier.name);
+        if let Some(type_annotation) = &declaration.type_annotation {
+            self.write(": ");
+            self.type_annotation(type_annotation);
+        }
+        self.write(" ");
+        self.write(declaration.transfer.as_str());
+        self.write(" ");
+        self.expression(&declaration.value);
+        if let Some((transfer, value)) = &declaration.second {
+            self.write(" ");
+            self.write(transfer.as_str());
+            self.write(" ");
+            self.expression(value);
+        }
+    }
+
+    fn transaction(&mut self, declaration: &TransactionDeclaration) {
+        self.write("transaction");
+        if !declaration.parameters.is_empty() {
+            self.parameters(&declaration.parameters);
+        }
+        self.write(" ");
+        self.braced(|p| {
+            // comments before a member stay where they are, not in the prepare or execute block
+            for field in declaration.fields.iter() {
+                p.trailing_comments(field.span.at);
+                p.leading_comments(field.span.at);
+                p.new_line();
+                p.field(field);
+            }
+            let mut blank = !declaration.fields.is_empty();
+            let mut separate = |p: &mut Self| {
+                if blank {
+                    p.write("\n");
+                }
+                blank = true;
+            };
+            if let Some(prepare) = &declaration.prepare {
+                p.trailing_comments(prepare.function.span.at);
+                separate(p);
+                p.leading_comments(prepare.function.span.at);
+                p.new_line();
+                p.special_function(prepare);
+            }
+            if !declaration.pre_conditions.is_empty() {
+                separate(p);
+                p.conditions("pre", &declaration.pre_conditions);
+            }
+            if let Some(execute) = &declaration.execute {
+                p.trailing_comments(execute.span.at);
+                separate(p);
+                p.leading_comments(execute.span.at);
+                p.new_line();
+                p.write("execute ");
+                p.block(execute);
+            }
+            if !declaration.post_conditions.is_empty() {
+                separate(p);
+                p.conditions("post", &declaration.post_conditions);
+            }
+            p.closing_comments(declaration.span.end());
+        });
+    }
+
+    pub fn type_annotation(&mut self, type_annotation: &TypeAnnotation) {
+        if type_annotation.is_resource {
+            self.write("@");
+        }
+        self.ty(&type_annotation.ty);
+    }
+
+    fn nominal_type(&mut self, nominal: &NominalType) {
+        self.write(&nominal.name());
+    }
+
+    pub fn ty(&mut self, ty: &Type) {
+        match ty {
+            Type::Nominal(nominal) => self.nominal_type(nominal),
+            Type::Optional { ty, .. } => {
+                self.ty(ty);
+                self.write("?");
+            },
+            Type::VariableSized { ty, .. } => {
+                self.write("[");
+                self.ty(ty);
+                self.write("]");
+            },
+            Type::ConstantSized { ty, size, .. } => {
+                self.write("[");
+                self.ty(ty);
+                self.write(&format!("; {}]", size));
+            },
+            Type::Dictionary { key, value, .. } => {
+                self.write("{");
+                self.ty(key);
+                self.write(": ");
+                self.ty(value);
+                self.write("}");
+            },
+            Type::Function { parameters, return_type, .. } => {
+                self.write("((");
+                self.separated(parameters, ", ", |p, parameter| p.type_annotation(parameter));
+                self.write("): ");
+                self.type_annotation(return_type);
+                self.write(")");
+            },
+            Type::Reference { authorized, entitlements, ty, .. } => {
+                match entitlements {
+                    Some(entitlements) => self.write(&format!("auth({}) ", entitlements)),
+                    None if *authorized => self.write("auth "),
+                    None => (),
+                }
+                self.write("&");
+                match **ty {
+                    // `&T?` is an optional reference
+                    Type::Optional { .. } => {
+                        self.write("(");
+                        self.ty(ty);
+                        self.write(")");
+                    },
+                    // `&&` is the logical and
+                    Type::Reference { .. } => {
+                        self.write(" ");
+                        self.ty(ty);
+                    },
+                    _ => self.ty(ty),
+                }
+            },
+            Type::Restricted { ty, restrictions, .. } => {
+                if let Some(ty) = ty {
+                    self.ty(ty);
+                }
+                self.write("{");
+                self.separated(restrictions, ", ", |p, restriction| p.nominal_type(restriction));
+                self.write("}");
+            },
+            Type::Instantiation { ty, type_arguments, .. } => {
+                self.ty(ty);
+                self.write("<");
+                self.separated(type_arguments, ", ", |p, argument| p.type_annotation(argument));
+                self.write(">");
+            },
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        self.braced(|p| {
+            p.statements(&block.statements);
+            p.closing_comments(block.span.end());
+        });
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements.iter() {
+            if !matches!(statement, Statement::Error(_)) {
+                let at = statement.span().at;
+                self.trailing_comments(at);
+                self.source_blank_line(at);
+                self.leading_comments(at);
+                self.new_line();
+                self.statement(statement);
+            }
+        }
+    }
+
+    pub fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Expression(expression) => self.statement_expression(expression),
+            Statement::Return { expression, .. } => {
+                self.write("return");
+                if let Some(expression) = expression {
+                    self.write(" ");
+                    self.expression(expression);
+                }
+            },
+            Statement::Break { .. } => self.write("break"),
+            Statement::Continue { .. } => self.write("continue"),
+            Statement::Variable(declaration) => self.variable(declaration),
+            Statement::Assignment { target, transfer, value, .. } => {
+                self.statement_expression(target);
+                self.write(" ");
+                self.write(transfer.as_str());
+                self.write(" ");
+                self.expression(value);
+            },
+            Statement::Swap { left, right, .. } => {
+                self.statement_expression(left);
+                self.write(" <-> ");
+                self.expression(right);
+            },
+            Statement::If(statement) => self.if_statement(statement),
+            Statement::While { test, block, .. } => {
+                self.write("while ");
+                self.expression(test);
+                self.write(" ");
+                self.block(block);
+            },
+            Statement::For { index, identifier, value, block, .. } => {
+                self.write("for ");
+                if let Some(index) = index {
+                    self.write(&index.name);
+                    self.write(", ");
+                }
+                self.write(&identifier.name);
+                self.write(" in ");
+                self.expression(value);
+                self.write(" ");
+                self.block(block);
+            },
+            Statement::Switch { expression, cases, span } => {
+                self.write("switch ");
+                self.expression(expression);
+                self.write(" ");
+                self.braced(|p| {
+                    for case in cases.iter() {
+                        p.switch_case(case);
+                    }
+                    p.closing_comments(span.end());
+                });
+            },
+            Statement::Emit { invocation, .. } => {
+                self.write("emit ");
+                self.expression(invocation);
+            },
+            Statement::Destroy { expression, .. } => {
+                self.write("destroy ");
+                self.expression(expression);
+            },
+            Statement::Error(_) => (),
+        }
+    }
+
+    /// an expression at the start of a statement, where a leading operator would continue the previous line
+    fn statement_expression(&mut self, expression: &Expression) {
+        self.expression_with_parentheses(expression, starts_with_operator(expression));
+    }
+
+    fn if_statement(&mut self, statement: &IfStatement) {
+        self.write("if ");
+        match &statement.test {
+            IfTest::Expression(test) => self.expression(test),
+            IfTest::Binding(binding) => self.variable(binding),
+        }
+        self.write(" ");
+        self.block(&statement.then);
+        match &statement.else_ {
+            Some(ElseBranch::Block(block)) => {
+                self.write(" else ");
+                self.block(block);
+            },
+            Some(ElseBranch::If(else_if)) => {
+                self.write(" else ");
+                self.if_statement(else_if);
+            },
+            None => (),
+        }
+    }
+
+    fn switch_case(&mut self, case: &SwitchCase) {
+        self.trailing_comments(case.span.at);
+        self.leading_comments(case.span.at);
+        self.new_line();
+        if case.is_default() {
+            self.write("default:");
+        } else {
+            self.write("case ");
+            self.separated(&case.expressions, ", ", |p, expression| p.expression(expression));
+            self.write(":");
+        }
+        self.indent += 1;
+        self.statements(&case.statements);
+        self.indent -= 1;
+    }
+
+    pub fn expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Nil { .. } => self.write("nil"),
+            Expression::Bool { value, .. } => self.write(if *value { "true" } else { "false" }),
+            Expression::Integer { literal, .. } | Expression::FixedPoint { literal, .. } => self.write(literal),
+            Expression::String { value, .. } => self.string(value),
+            Expression::Array { values, span } => {
+                self.write("[");
+                if values.first().is_some_and(|value| self.commented(value.span().at, span.end())) {
+                    self.broken_list(values, "]", span.end(), Expression::span, |p, value| p.expression(value));
+                } else {
+                    self.separated(values, ", ", |p, value| p.expression(value));
+                    self.write("]");
+                }
+            },
+            Expression::Dictionary { entries, span } => {
+                let entry = |p: &mut Self, (key, value): &(Expression, Expression)| {
+                    p.expression(key);
+                    p.write(": ");
+                    p.expression(value);
+                };
+                self.write("{");
+                if entries.first().is_some_and(|(key, _)| self.commented(key.span().at, span.end())) {
+                    self.broken_list(entries, "}", span.end(), |(key, _)| key.span(), entry);
+                } else {
+                    self.separated(entries, ", ", entry);
+                    self.write("}");
+                }
+            },
+            Expression::Identifier(identifier) => self.write(&identifier.name),
+            Expression::Path { domain, identifier, .. } => {
+                self.write("/");
+                self.write(&domain.name);
+                self.write("/");
+                self.write(&identifier.name);
+            },
+            Expression::Member { expression, optional, identifier, .. } => {
+                self.postfix_operand(expression);
+                self.write(if *optional { "?." } else { "." });
+                self.write(&identifier.name);
+            },
+            Expression::Index { expression, index, .. } => {
+                self.postfix_operand(expression);
+                self.write("[");
+                self.expression(index);
+                self.write("]");
+            },
+            Expression::Invocation { invoked, type_arguments, arguments, span } => {
+                self.postfix_operand(invoked);
+                if !type_arguments.is_empty() {
+                    self.write("<");
+                    self.separated(type_arguments, ", ", |p, argument| p.type_annotation(argument));
+                    self.write(">");
+                }
+                let argument_span = |argument: &Argument| {
+                    argument.label.as_ref().map_or_else(|| argument.expression.span(), |label| label.span)
+                };
+                self.list("(", arguments, ")", span.end(), argument_span, |p, argument| p.argument(argument));
+            },
+            Expression::Unary { operation, expression, .. } => {
+                self.write(operation.as_str());
+                // `- -x` and `<- !x` must not be read as one token
+                if let Expression::Unary { .. } = **expression {
+                    self.write(" ");
+                }
+                self.operand(expression, PRECEDENCE_UNARY_PREFIX);
+            },
+            Expression::Binary { operation: operation @ (BinaryOperation::And | BinaryOperation::Or), left, right, .. } => {
+                self.fitting(
+                    |p| p.binary(*operation, left, right),
+                    |p| p.logical_chain(*operation, expression),
+                );
+            },
+            Expression::Binary { operation, left, right, .. } => self.binary(*operation, left, right),
+            Expression::Conditional { test, then, else_, .. } => {
+                self.operand(test, PRECEDENCE_TERNARY + 1);
+                self.write(" ? ");
+                self.expression(then);
+                self.write(" : ");
+                self.expression(else_);
+            },
+            Expression::Casting { expression, operation, type_annotation, .. } => {
+                // in `&x as T` the type belongs to the reference expression
+                self.expression_with_parentheses(
+                    expression,
+                    ends_with_reference(expression) || precedence(expression) < PRECEDENCE_CASTING,
+                );
+                self.write(" ");
+                self.write(operation.as_str());
+                self.write(" ");
+                self.type_annotation(type_annotation);
+            },
+            Expression::Create { invocation, .. } => {
+                self.write("create ");
+                self.expression(invocation);
+            },
+            Expression::Reference { expression, ty, .. } => {
+                self.write("&");
+                // `&&` is the logical and
+                if let Expression::Reference { .. } = **expression {
+                    self.write(" ");
+                }
+                self.expression_with_parentheses(
+                    expression,
+                    (ty.is_some() && ends_with_reference(expression)) || precedence(expression) < PRECEDENCE_UNARY_PREFIX,
+                );
+                if let Some(ty) = ty {
+                    self.write(" as ");
+                    self.ty(ty);
+                }
+            },
+            Expression::Force { expression, .. } => {
+                self.postfix_operand(expression);
+                self.write("!");
+            },
+        }
+    }
+
+    fn binary(&mut self, operation: BinaryOperation, left: &Expression, right: &Expression) {
+        let precedence = operation.precedence();
+        let (left_min, right_min) = if operation.is_right_associative() {
+            (precedence + 1, precedence)
+        } else {
+            (precedence, precedence + 1)
+        };
+        self.operand(left, left_min);
+        self.write(" ");
+        self.write(operation.as_str());
+        self.write(" ");
+        self.operand(right, right_min);
+    }
+
+    /// `a &&`, `b &&` and `c` on their own lines
+    fn logical_chain(&mut self, operation: BinaryOperation, expression: &Expression) {
+        let mut operands = Vec::new();
+        let mut first = expression;
+        while let Expression::Binary { operation: chained, left, right, .. } = first {
+            if *chained != operation {
+                break;
+            }
+            operands.push(&**right);
+            first = left;
+        }
+        self.operand(first, operation.precedence());
+        self.indent += 1;
+        for operand in operands.into_iter().rev() {
+            self.write(" ");
+            self.write(operation.as_str());
+            self.new_line();
+            self.operand(operand, operation.precedence() + 1);
+        }
+        self.indent -= 1;
+    }
+
+    fn argument(&mut self, argument: &Argument) {
+        if let Some(label) = &argument.label {
+            self.write(&label.name);
+            self.write(": ");
+        }
+        self.expression(&argument.expression);
+    }
+
+    /// `expression`, parenthesized if it binds weaker than `min`
+    fn operand(&mut self, expression: &Expression, min: u8) {
+        self.expression_with_parentheses(expression, precedence(expression) < min);
+    }
+
+    /// the expression before `.`, `[`, `(` or `!`
+    fn postfix_operand(&mut self, expression: &Expression) {
+        // `1.x` would read as a fixed point literal
+        let is_number = matches!(expression, Expression::Integer { .. } | Expression::FixedPoint { .. });
+        self.expression_with_parentheses(expression, is_number || precedence(expression) < PRECEDENCE_UNARY_POSTFIX);
+    }
+
+    fn expression_with_parentheses(&mut self, expression: &Expression, parenthesize: bool) {
+        if parenthesize {
+            self.write("(");
+            self.expression(expression);
+            self.write(")");
+        } else {
+            self.expression(expression);
+        }
+    }
+
+    fn string(&mut self, value: &str) {
+        self.write("\"");
+        for c in value.chars() {
+            match c {
+                '"' => self.write("\\\""),
+                '\\' => self.write("\\\\"),
+                '\n' => self.write("\\n"),
+                c => self.output.push(c),
+            }
+        }
+        self.write("\"");
+    }
+}